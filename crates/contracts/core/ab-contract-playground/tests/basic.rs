@@ -32,6 +32,7 @@ fn basic() {
                 MethodContext::Reset,
                 Address::SYSTEM_CODE,
                 &DummyWallet::code(),
+                &DummyWallet::metadata(),
             )
             .unwrap();
         let bob = env
@@ -39,6 +40,7 @@ fn basic() {
                 MethodContext::Reset,
                 Address::SYSTEM_CODE,
                 &DummyWallet::code(),
+                &DummyWallet::metadata(),
             )
             .unwrap();
 
@@ -52,6 +54,7 @@ fn basic() {
                 MethodContext::Keep,
                 Address::SYSTEM_CODE,
                 &Playground::code(),
+                &Playground::metadata(),
             )
             .unwrap();
         env.playground_new_result(
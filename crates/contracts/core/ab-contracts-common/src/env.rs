@@ -46,10 +46,76 @@ pub struct PreparedMethod<'a> {
     pub phantom: PhantomData<&'a ()>,
 }
 
+/// State of [`SlotScanCursor`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TrivialType)]
+#[repr(u8)]
+pub enum SlotScanState {
+    /// No slots were yielded yet
+    NotStarted,
+    /// Some slots were yielded, [`SlotScanCursor::last_owner`] is the owner of the last one
+    Started,
+    /// There are no more slots to yield
+    Finished,
+}
+
+/// Cursor for iterating over slots managed by the current contract, see [`Env::next_own_slot()`]
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+pub struct SlotScanCursor {
+    /// Prefix that owners of yielded slots must match
+    pub owner_prefix: Address,
+    /// Owner of the last yielded slot, only meaningful in [`SlotScanState::Started`] state
+    pub last_owner: Address,
+    /// The number of the most significant bits of `owner_prefix` that owners must match, `0`
+    /// matches all owners
+    pub owner_prefix_bits: u8,
+    /// State of the cursor
+    pub state: SlotScanState,
+    /// Explicit padding, contents must be all zeroes
+    pub padding_0: [u8; 6],
+}
+
+impl SlotScanCursor {
+    /// Create a new cursor for slots whose owners match `owner_prefix_bits` most significant bits
+    /// of `owner_prefix`
+    #[inline(always)]
+    #[cfg_attr(feature = "no-panic", no_panic::no_panic)]
+    pub fn new(owner_prefix: Address, owner_prefix_bits: u8) -> Self {
+        Self {
+            owner_prefix,
+            last_owner: Address::NULL,
+            owner_prefix_bits,
+            state: SlotScanState::NotStarted,
+            padding_0: [0; _],
+        }
+    }
+
+    /// Cursor for all slots managed by the current contract
+    #[inline(always)]
+    #[cfg_attr(feature = "no-panic", no_panic::no_panic)]
+    pub fn all() -> Self {
+        Self::new(Address::NULL, 0)
+    }
+}
+
 #[cfg(feature = "guest")]
 unsafe extern "C" {
     /// Host-level API
     fn __ab_host_call_import(method: &PreparedMethod<'_>) -> crate::ExitCode;
+
+    /// Host-level API for static (read-only) calls
+    fn __ab_host_static_call_import(
+        method: &PreparedMethod<'_>,
+        fuel_limit: u64,
+    ) -> crate::ExitCode;
+
+    /// Host-level API for iteration over slots managed by the current contract
+    fn __ab_host_next_own_slot_import(
+        cursor: &mut SlotScanCursor,
+        value: NonNull<u8>,
+        value_capacity: u32,
+        value_size: &mut u32,
+    ) -> crate::ExitCode;
 }
 
 /// Internal wrapper around [`__ab_host_call_import()`] that will always have a single copy (never
@@ -64,6 +130,36 @@ pub extern "C" fn __ab_host_call(method: &PreparedMethod<'_>) -> crate::ExitCode
     unsafe { __ab_host_call_import(method) }
 }
 
+/// Internal wrapper around [`__ab_host_static_call_import()`], see [`__ab_host_call()`] for
+/// details
+#[cfg(feature = "guest")]
+#[unsafe(no_mangle)]
+#[inline(never)]
+#[doc(hidden)]
+pub extern "C" fn __ab_host_static_call(
+    method: &PreparedMethod<'_>,
+    fuel_limit: u64,
+) -> crate::ExitCode {
+    // SAFETY: FFI call with correct argument, there are no other safety requirements
+    unsafe { __ab_host_static_call_import(method, fuel_limit) }
+}
+
+/// Internal wrapper around [`__ab_host_next_own_slot_import()`], see [`__ab_host_call()`] for
+/// details
+#[cfg(feature = "guest")]
+#[unsafe(no_mangle)]
+#[inline(never)]
+#[doc(hidden)]
+pub extern "C" fn __ab_host_next_own_slot(
+    cursor: &mut SlotScanCursor,
+    value: NonNull<u8>,
+    value_capacity: u32,
+    value_size: &mut u32,
+) -> crate::ExitCode {
+    // SAFETY: FFI call with correct arguments, there are no other safety requirements
+    unsafe { __ab_host_next_own_slot_import(cursor, value, value_capacity, value_size) }
+}
+
 /// Environment state
 #[derive(Debug, Copy, Clone, TrivialType)]
 #[repr(C)]
@@ -89,6 +185,27 @@ pub trait ExecutorContext: core::fmt::Debug {
         previous_env_state: &EnvState,
         prepared_method: &mut PreparedMethod<'_>,
     ) -> Result<(), ContractError>;
+
+    /// Call prepared method read-only with a separate fuel sub-budget.
+    ///
+    /// See [`Env::static_call_prepared()`] for details.
+    fn static_call(
+        &self,
+        previous_env_state: &EnvState,
+        prepared_method: &mut PreparedMethod<'_>,
+        fuel_limit: u64,
+    ) -> Result<(), ContractError>;
+
+    /// Copy the next slot managed by the current contract into `value` and advance the cursor,
+    /// returns the size of the slot contents.
+    ///
+    /// See [`Env::next_own_slot()`] for details.
+    fn next_own_slot(
+        &self,
+        env_state: &EnvState,
+        cursor: &mut SlotScanCursor,
+        value: &mut [u8],
+    ) -> Result<u32, ContractError>;
 }
 
 #[cfg(all(feature = "executor", feature = "guest", not(any(doc, unix, windows))))]
@@ -223,4 +340,108 @@ impl<'a> Env<'a> {
             }
         }
     }
+
+    /// Call a method at specified address and with specified arguments read-only.
+    ///
+    /// This is a shortcut for [`Self::prepare_method_call()`] + [`Self::static_call_prepared()`].
+    #[inline(always)]
+    pub fn static_call<Args>(
+        &self,
+        contract: Address,
+        args: &mut Args,
+        method_context: MethodContext,
+        fuel_limit: u64,
+    ) -> Result<(), ContractError>
+    where
+        Args: ExternalArgs,
+    {
+        let prepared_method = Self::prepare_method_call(contract, args, method_context);
+        self.static_call_prepared(prepared_method, fuel_limit)
+    }
+
+    /// Call prepared method read-only.
+    ///
+    /// In contrast to [`Self::call_prepared()`], only `#[view]` methods can be called this way
+    /// (also in nested calls), regardless of whether the current method is allowed to modify
+    /// the state. This makes it possible to make composable queries to other contracts with a
+    /// guarantee that no state modifications escape the call.
+    ///
+    /// The call is executed with a separate fuel sub-budget of `fuel_limit` (capped by the fuel
+    /// remaining in the current static call if there is one), the call fails with
+    /// [`ContractError::Forbidden`] once the sub-budget is exhausted.
+    #[inline]
+    pub fn static_call_prepared(
+        &self,
+        method: PreparedMethod<'_>,
+        fuel_limit: u64,
+    ) -> Result<(), ContractError> {
+        cfg_select! {
+            feature = "executor" => {
+                let mut method = method;
+                self.executor_context.static_call(&self.state, &mut method, fuel_limit)
+            }
+            feature = "guest" => {
+                __ab_host_static_call(&method, fuel_limit).into()
+            }
+            _ => {
+                let _: PreparedMethod<'_> = method;
+                let _: u64 = fuel_limit;
+                Err(ContractError::InternalError)
+            }
+        }
+    }
+
+    /// Get the next slot managed by the current contract.
+    ///
+    /// Slots are yielded in ascending order of owner addresses, only non-empty slots whose owners
+    /// match the prefix of the `cursor` are yielded. This makes it possible to implement map-like
+    /// data structures without emulating iteration with linked lists in slots.
+    ///
+    /// Slot contents are copied into `value`, which must be large enough to fit them, otherwise
+    /// [`ContractError::BadInput`] is returned. Only slots available to the current transaction
+    /// are considered, and [`ContractError::Forbidden`] is returned when the next slot is
+    /// currently being modified. Within static calls, each yielded slot consumes fuel from the
+    /// sub-budget.
+    ///
+    /// Returns `None` once there are no more slots.
+    #[inline]
+    pub fn next_own_slot<'b>(
+        &self,
+        cursor: &mut SlotScanCursor,
+        value: &'b mut [u8],
+    ) -> Result<Option<(Address, &'b [u8])>, ContractError> {
+        if cursor.state == SlotScanState::Finished {
+            return Ok(None);
+        }
+
+        let result: Result<u32, ContractError> = cfg_select! {
+            feature = "executor" => {
+                self.executor_context.next_own_slot(&self.state, cursor, value)
+            }
+            feature = "guest" => {{
+                let mut value_size = 0;
+                Result::<(), ContractError>::from(__ab_host_next_own_slot(
+                    cursor,
+                    NonNull::from_mut(value).cast::<u8>(),
+                    u32::try_from(value.len()).unwrap_or(u32::MAX),
+                    &mut value_size,
+                ))
+                .map(|()| value_size)
+            }}
+            _ => {
+                Err(ContractError::InternalError)
+            }
+        };
+        let value_size = result?;
+
+        if cursor.state == SlotScanState::Finished {
+            return Ok(None);
+        }
+
+        let value = value
+            .get(..value_size as usize)
+            .ok_or(ContractError::InternalError)?;
+
+        Ok(Some((cursor.last_owner, value)))
+    }
 }
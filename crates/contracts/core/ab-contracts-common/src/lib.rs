@@ -15,6 +15,7 @@ pub mod method;
 
 use crate::method::MethodFingerprint;
 use ab_io_type::IoType;
+use ab_io_type::metadata::MAX_METADATA_CAPACITY;
 use ab_io_type::variable_bytes::VariableBytes;
 use core::ffi::c_void;
 use core::ops::Deref;
@@ -25,10 +26,20 @@ pub use error::{ContractError, CustomContractErrorCode, ExitCode};
 pub const HOST_CALL_FN: &str = "__ab_host_call";
 /// Import function name used to make calls from guest to host
 pub const HOST_CALL_FN_IMPORT: &str = "__ab_host_call_import";
+/// Function name used to make static (read-only) calls from guest to host
+pub const HOST_STATIC_CALL_FN: &str = "__ab_host_static_call";
+/// Import function name used to make static (read-only) calls from guest to host
+pub const HOST_STATIC_CALL_FN_IMPORT: &str = "__ab_host_static_call_import";
+/// Function name used to iterate over slots managed by the current contract from guest
+pub const HOST_NEXT_OWN_SLOT_FN: &str = "__ab_host_next_own_slot";
+/// Import function name used to iterate over slots managed by the current contract from guest
+pub const HOST_NEXT_OWN_SLOT_FN_IMPORT: &str = "__ab_host_next_own_slot_import";
 /// The name of the static variable that contains contract metadata
 pub const METADATA_STATIC_NAME_PREFIX: &str = "__ab_metadata_";
 /// Max allowed size of the contract code
 pub const MAX_CODE_SIZE: u32 = 1024 * 1024;
+/// Max allowed size of the contract metadata
+pub const MAX_METADATA_SIZE: u32 = MAX_METADATA_CAPACITY as u32;
 /// Max number of arguments in a method.
 ///
 /// NOTE: Both `self` and return type that is not `()` or `Result<(), ContractError>` count towards
@@ -88,6 +99,10 @@ pub trait Contract: IoType {
     /// This is NOT the code compiled for guest architecture!
     // TODO: Make `const` when possible
     fn code() -> impl Deref<Target = VariableBytes<MAX_CODE_SIZE>>;
+    /// [`Self::MAIN_CONTRACT_METADATA`] as variable bytes, stored in the metadata registry during
+    /// deployment
+    // TODO: Make `const` when possible
+    fn metadata() -> impl Deref<Target = VariableBytes<MAX_METADATA_SIZE>>;
 }
 
 /// A trait that indicates the implementation of a contract trait by a contract.
@@ -518,6 +518,27 @@ fn process_struct_impl(mut item_impl: ItemImpl) -> Result<TokenStream, Error> {
                     &CODE_SIZE
                 )
             }
+
+            fn metadata() -> impl ::core::ops::Deref<
+                Target = ::ab_contracts_macros::__private::VariableBytes<
+                    { ::ab_contracts_macros::__private::MAX_METADATA_SIZE },
+                >,
+            > {
+                const fn metadata_bytes() -> &'static [::core::primitive::u8] {
+                    <#struct_name as ::ab_contracts_macros::__private::Contract>::MAIN_CONTRACT_METADATA
+                }
+
+                const fn metadata_size() -> ::core::primitive::u32 {
+                    metadata_bytes().len() as ::core::primitive::u32
+                }
+
+                static METADATA_SIZE: ::core::primitive::u32 = metadata_size();
+
+                ::ab_contracts_macros::__private::VariableBytes::from_buffer(
+                    metadata_bytes(),
+                    &METADATA_SIZE
+                )
+            }
         }
 
         #item_impl
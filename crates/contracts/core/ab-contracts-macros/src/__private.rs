@@ -3,7 +3,7 @@ pub use ab_contracts_common::metadata::ContractMetadataKind;
 pub use ab_contracts_common::method::{ExternalArgs, MethodFingerprint};
 pub use ab_contracts_common::{
     Contract, ContractError, ContractTrait, ContractTraitDefinition, ExitCode, MAX_CODE_SIZE,
-    MAX_TOTAL_METHOD_ARGS, NativeExecutorContactMethod,
+    MAX_METADATA_SIZE, MAX_TOTAL_METHOD_ARGS, NativeExecutorContactMethod,
 };
 pub use ab_core_primitives::address::Address;
 pub use ab_io_type::metadata::{MAX_METADATA_CAPACITY, concat_metadata_sources};
@@ -20,7 +20,12 @@ fn criterion_benchmark(c: &mut Criterion) {
     let flipper_address = executor.transaction_emulate(Address::NULL, slots, |env| {
         // Deploy
         let flipper_address = env
-            .code_deploy(MethodContext::Keep, Address::SYSTEM_CODE, &Flipper::code())
+            .code_deploy(
+                MethodContext::Keep,
+                Address::SYSTEM_CODE,
+                &Flipper::code(),
+                &Flipper::metadata(),
+            )
             .unwrap();
 
         // Initialize state
@@ -1,11 +1,14 @@
-use ab_contracts_common::Contract;
 use ab_contracts_common::env::MethodContext;
+use ab_contracts_common::{Contract, ContractError};
 use ab_core_primitives::address::Address;
 use ab_core_primitives::shard::ShardIndex;
+use ab_example_contract_flipper::ffi::flip::FlipperFlipArgs;
+use ab_example_contract_flipper::ffi::value::FlipperValueArgs;
 use ab_example_contract_flipper::{Flipper, FlipperExt};
 use ab_executor_native::NativeExecutor;
 use ab_io_type::bool::Bool;
 use ab_system_contract_code::CodeExt;
+use std::mem::MaybeUninit;
 
 #[test]
 fn basic() {
@@ -20,7 +23,12 @@ fn basic() {
     executor.transaction_emulate(Address::NULL, slots, |env| {
         // Deploy
         let flipper_address = env
-            .code_deploy(MethodContext::Keep, Address::SYSTEM_CODE, &Flipper::code())
+            .code_deploy(
+                MethodContext::Keep,
+                Address::SYSTEM_CODE,
+                &Flipper::code(),
+                &Flipper::metadata(),
+            )
             .unwrap();
 
         let init_value = Bool::new(true);
@@ -40,3 +48,65 @@ fn basic() {
         assert_eq!(env.flipper_value(flipper_address).unwrap(), !init_value);
     });
 }
+
+#[test]
+fn static_call() {
+    let shard_index = ShardIndex::new(1).unwrap();
+    let executor = NativeExecutor::builder(shard_index)
+        .with_contract::<Flipper>()
+        .build()
+        .unwrap();
+
+    let slots = &mut executor.new_storage_slots().unwrap();
+
+    executor.transaction_emulate(Address::NULL, slots, |env| {
+        let flipper_address = env
+            .code_deploy(
+                MethodContext::Keep,
+                Address::SYSTEM_CODE,
+                &Flipper::code(),
+                &Flipper::metadata(),
+            )
+            .unwrap();
+
+        let init_value = Bool::new(true);
+        env.flipper_new(MethodContext::Keep, flipper_address, &init_value)
+            .unwrap();
+
+        // View methods can be called
+        let mut value = MaybeUninit::uninit();
+        env.static_call(
+            flipper_address,
+            &mut FlipperValueArgs::new(&mut value),
+            MethodContext::Keep,
+            1,
+        )
+        .unwrap();
+        // SAFETY: Initialized by successful call
+        assert_eq!(unsafe { value.assume_init() }, init_value);
+
+        // State modifications are not allowed
+        assert_eq!(
+            env.static_call(
+                flipper_address,
+                &mut FlipperFlipArgs::new(),
+                MethodContext::Keep,
+                1,
+            ),
+            Err(ContractError::Forbidden)
+        );
+        assert_eq!(env.flipper_value(flipper_address).unwrap(), init_value);
+
+        // Not enough fuel
+        let mut value = MaybeUninit::uninit();
+        assert_eq!(
+            env.static_call(
+                flipper_address,
+                &mut FlipperValueArgs::new(&mut value),
+                MethodContext::Keep,
+                0,
+            ),
+            Err(ContractError::Forbidden)
+        );
+    });
+}
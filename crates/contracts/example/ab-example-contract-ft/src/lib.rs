@@ -1,7 +1,7 @@
 #![no_std]
 
 use ab_contracts_common::ContractError;
-use ab_contracts_common::env::{Env, MethodContext};
+use ab_contracts_common::env::{Env, MethodContext, SlotScanCursor};
 use ab_contracts_macros::contract;
 use ab_contracts_standards::fungible::Fungible;
 use ab_core_primitives::address::Address;
@@ -89,6 +89,20 @@ impl ExampleFt {
             .map_or_else(Balance::default, |slot| slot.balance)
     }
 
+    /// Number of addresses with non-zero balance among those available to the transaction
+    #[view]
+    pub fn num_holders(#[env] env: &Env<'_>) -> Result<u32, ContractError> {
+        let mut cursor = SlotScanCursor::all();
+        let mut value = [0; size_of::<Slot>()];
+        let mut num_holders = 0;
+
+        while env.next_own_slot(&mut cursor, &mut value)?.is_some() {
+            num_holders += 1;
+        }
+
+        Ok(num_holders)
+    }
+
     #[update]
     pub fn transfer(
         #[env] env: &mut Env<'_>,
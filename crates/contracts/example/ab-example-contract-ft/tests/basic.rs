@@ -5,9 +5,11 @@ use ab_contracts_test_utils::dummy_wallet::DummyWallet;
 use ab_core_primitives::address::Address;
 use ab_core_primitives::balance::Balance;
 use ab_core_primitives::shard::ShardIndex;
+use ab_example_contract_ft::ffi::num_holders::ExampleFtNumHoldersArgs;
 use ab_example_contract_ft::{ExampleFt, ExampleFtExt};
 use ab_executor_native::NativeExecutor;
 use ab_system_contract_code::CodeExt;
+use std::mem::MaybeUninit;
 
 #[test]
 fn basic() {
@@ -28,6 +30,7 @@ fn basic() {
                 MethodContext::Reset,
                 Address::SYSTEM_CODE,
                 &DummyWallet::code(),
+                &DummyWallet::metadata(),
             )
             .unwrap();
         let bob = env
@@ -35,6 +38,7 @@ fn basic() {
                 MethodContext::Reset,
                 Address::SYSTEM_CODE,
                 &DummyWallet::code(),
+                &DummyWallet::metadata(),
             )
             .unwrap();
 
@@ -48,6 +52,7 @@ fn basic() {
                 MethodContext::Keep,
                 Address::SYSTEM_CODE,
                 &ExampleFt::code(),
+                &ExampleFt::metadata(),
             )
             .unwrap();
         env.example_ft_new(MethodContext::Keep, token_address, &alice, &Balance::MAX)
@@ -124,3 +129,91 @@ fn basic() {
         ));
     });
 }
+
+#[test]
+fn num_holders() {
+    let shard_index = ShardIndex::new(1).unwrap();
+    let executor = NativeExecutor::builder(shard_index)
+        .with_contract::<DummyWallet>()
+        .with_contract::<ExampleFt>()
+        .build()
+        .unwrap();
+
+    let slots = &mut executor.new_storage_slots().unwrap();
+
+    // Create three wallets
+    let (alice, bob, carol) = executor.transaction_emulate(Address::NULL, slots, |env| {
+        let [alice, bob, carol] = [(); 3].map(|()| {
+            env.code_deploy(
+                MethodContext::Reset,
+                Address::SYSTEM_CODE,
+                &DummyWallet::code(),
+                &DummyWallet::metadata(),
+            )
+            .unwrap()
+        });
+
+        (alice, bob, carol)
+    });
+
+    // Deploy and initialize
+    let token_address = executor.transaction_emulate(alice, slots, |env| {
+        let token_address = env
+            .code_deploy(
+                MethodContext::Keep,
+                Address::SYSTEM_CODE,
+                &ExampleFt::code(),
+                &ExampleFt::metadata(),
+            )
+            .unwrap();
+        env.example_ft_new(MethodContext::Keep, token_address, &alice, &Balance::MAX)
+            .unwrap();
+
+        token_address
+    });
+
+    executor.transaction_emulate(alice, slots, |env| {
+        let amount = Balance::from(10);
+
+        assert_eq!(env.example_ft_num_holders(token_address).unwrap(), 1);
+
+        env.example_ft_transfer(MethodContext::Keep, token_address, &alice, &carol, &amount)
+            .unwrap();
+        env.example_ft_transfer(MethodContext::Keep, token_address, &alice, &bob, &amount)
+            .unwrap();
+        assert_eq!(env.example_ft_num_holders(token_address).unwrap(), 3);
+    });
+
+    executor.transaction_emulate(bob, slots, |env| {
+        let amount = Balance::from(10);
+
+        // Emptied slots are not yielded
+        env.example_ft_transfer(MethodContext::Keep, token_address, &bob, &alice, &amount)
+            .unwrap();
+        assert_eq!(env.example_ft_num_holders(token_address).unwrap(), 2);
+
+        // One unit of fuel for the call itself and one for each yielded slot
+        let mut num_holders = MaybeUninit::uninit();
+        env.static_call(
+            token_address,
+            &mut ExampleFtNumHoldersArgs::new(&mut num_holders),
+            MethodContext::Keep,
+            3,
+        )
+        .unwrap();
+        // SAFETY: Initialized by successful call
+        assert_eq!(unsafe { num_holders.assume_init() }, 2);
+
+        // Not enough fuel for all yielded slots
+        let mut num_holders = MaybeUninit::uninit();
+        assert_eq!(
+            env.static_call(
+                token_address,
+                &mut ExampleFtNumHoldersArgs::new(&mut num_holders),
+                MethodContext::Keep,
+                2,
+            ),
+            Err(ContractError::Forbidden)
+        );
+    });
+}
@@ -58,6 +58,7 @@ fn criterion_benchmark(c: &mut Criterion) {
                 MethodContext::Keep,
                 Address::SYSTEM_CODE,
                 &ExampleWallet::code(),
+                &ExampleWallet::metadata(),
             )
             .unwrap();
 
@@ -75,7 +76,12 @@ fn criterion_benchmark(c: &mut Criterion) {
     let flipper_address = executor.transaction_emulate(Address::NULL, slots, |env| {
         // Deploy
         let flipper_address = env
-            .code_deploy(MethodContext::Keep, Address::SYSTEM_CODE, &Flipper::code())
+            .code_deploy(
+                MethodContext::Keep,
+                Address::SYSTEM_CODE,
+                &Flipper::code(),
+                &Flipper::metadata(),
+            )
             .unwrap();
 
         // Initialize state
@@ -7,11 +7,12 @@ use ab_contracts_common::env::MethodContext;
 use ab_contracts_macros::contract;
 use ab_contracts_standards::tx_handler::TxHandler;
 use ab_core_primitives::address::Address;
+use ab_core_primitives::balance::Balance;
 use ab_core_primitives::block::BlockRoot;
 use ab_core_primitives::shard::ShardIndex;
 use ab_core_primitives::transaction::{Gas, Transaction, TransactionHeader, TransactionSlot};
 use ab_example_contract_wallet::{ExampleWallet, ExampleWalletExt};
-use ab_executor_native::NativeExecutor;
+use ab_executor_native::{NativeExecutor, TransactionPrevalidationError};
 use ab_io_type::bool::Bool;
 use ab_io_type::trivial_type::TrivialType;
 use ab_system_contract_code::CodeExt;
@@ -60,6 +61,7 @@ fn flip() {
                 MethodContext::Keep,
                 Address::SYSTEM_CODE,
                 &ExampleWallet::code(),
+                &ExampleWallet::metadata(),
             )
             .unwrap();
 
@@ -77,7 +79,12 @@ fn flip() {
     let flipper_address = executor.transaction_emulate(Address::NULL, slots, |env| {
         // Deploy
         let flipper_address = env
-            .code_deploy(MethodContext::Keep, Address::SYSTEM_CODE, &Flipper::code())
+            .code_deploy(
+                MethodContext::Keep,
+                Address::SYSTEM_CODE,
+                &Flipper::code(),
+                &Flipper::metadata(),
+            )
             .unwrap();
 
         // Initialize state
@@ -129,6 +136,73 @@ fn flip() {
             .unwrap();
     }
 
+    // Prevalidation
+    {
+        let seal = hash_and_sign(&keypair, &header, read_slots, write_slots, &payload, nonce);
+        let transaction = Transaction {
+            header: &header,
+            payload: &payload,
+            read_slots,
+            write_slots,
+            seal: seal.as_bytes(),
+        };
+        executor
+            .transaction_prevalidate(transaction, slots, Balance::MAX)
+            .unwrap();
+
+        // Unexpected nonce
+        let seal = hash_and_sign(
+            &keypair,
+            &header,
+            read_slots,
+            write_slots,
+            &payload,
+            nonce + 1,
+        );
+        assert!(matches!(
+            executor.transaction_prevalidate(
+                Transaction {
+                    seal: seal.as_bytes(),
+                    ..transaction
+                },
+                slots,
+                Balance::MIN,
+            ),
+            Err(TransactionPrevalidationError::AuthorizationFailed { .. })
+        ));
+
+        // Duplicate slot
+        let write_slots = &[write_slots[0], write_slots[0]];
+        assert!(matches!(
+            executor.transaction_prevalidate(
+                Transaction {
+                    write_slots,
+                    ..transaction
+                },
+                slots,
+                Balance::MIN,
+            ),
+            Err(TransactionPrevalidationError::DuplicateSlot { .. })
+        ));
+
+        // Wallet has no balance to pay for gas
+        let header = TransactionHeader {
+            gas_limit: Gas::from(1),
+            ..header
+        };
+        assert!(matches!(
+            executor.transaction_prevalidate(
+                Transaction {
+                    header: &header,
+                    ..transaction
+                },
+                slots,
+                Balance::from(1),
+            ),
+            Err(TransactionPrevalidationError::InsufficientBalance { .. })
+        ));
+    }
+
     for nonce in (nonce..).take(2) {
         let seal = hash_and_sign(&keypair, &header, read_slots, write_slots, &payload, nonce);
 
@@ -1,12 +1,13 @@
 #![no_std]
 
 use ab_contracts_common::env::{Env, MethodContext};
-use ab_contracts_common::{ContractError, MAX_CODE_SIZE};
+use ab_contracts_common::{ContractError, MAX_CODE_SIZE, MAX_METADATA_SIZE};
 use ab_contracts_macros::__private::Address;
 use ab_contracts_macros::contract;
 use ab_io_type::trivial_type::TrivialType;
 use ab_io_type::variable_bytes::VariableBytes;
 use ab_system_contract_address_allocator::AddressAllocatorExt;
+use ab_system_contract_metadata::MetadataExt;
 
 #[derive(Debug, Copy, Clone, TrivialType)]
 #[repr(C)]
@@ -14,11 +15,14 @@ pub struct Code;
 
 #[contract]
 impl Code {
-    /// Deploy a new contract with specified code
+    /// Deploy a new contract with specified code.
+    ///
+    /// Contract metadata is stored in the metadata registry alongside the code.
     #[update]
     pub fn deploy(
         #[env] env: &mut Env<'_>,
         #[input] code: &VariableBytes<MAX_CODE_SIZE>,
+        #[input] metadata: &VariableBytes<MAX_METADATA_SIZE>,
     ) -> Result<Address, ContractError> {
         let new_contract_address = env.address_allocator_allocate_address(
             MethodContext::Replace,
@@ -32,6 +36,13 @@ impl Code {
             code,
         )?;
 
+        env.metadata_store(
+            MethodContext::Replace,
+            Address::SYSTEM_METADATA,
+            &new_contract_address,
+            metadata,
+        )?;
+
         Ok(new_contract_address)
     }
 
@@ -0,0 +1,59 @@
+#![no_std]
+
+use ab_contracts_common::env::Env;
+use ab_contracts_common::{ContractError, MAX_METADATA_SIZE};
+use ab_contracts_macros::contract;
+use ab_core_primitives::address::Address;
+use ab_io_type::trivial_type::TrivialType;
+use ab_io_type::variable_bytes::VariableBytes;
+
+/// Registry of contract metadata.
+///
+/// Metadata (methods and their arguments, see [`ContractMetadataKind`] for encoding details) is
+/// stored during deployment and allows wallets and other tooling to construct method calls without
+/// out-of-band information about the contract.
+///
+/// [`ContractMetadataKind`]: ab_contracts_common::metadata::ContractMetadataKind
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+pub struct Metadata;
+
+#[contract]
+impl Metadata {
+    /// Store contact's metadata overriding previous metadata that might have been there.
+    ///
+    /// Updates can only be done by the system code contract during deployment or by the contract
+    /// itself with direct calls.
+    #[update]
+    pub fn store(
+        #[env] env: &mut Env<'_>,
+        #[slot] (address, contract_metadata): (&Address, &mut VariableBytes<MAX_METADATA_SIZE>),
+        #[input] new_metadata: &VariableBytes<MAX_METADATA_SIZE>,
+    ) -> Result<(), ContractError> {
+        if !(env.caller() == Address::NULL
+            || env.caller() == Address::SYSTEM_CODE
+            || env.caller() == address)
+        {
+            return Err(ContractError::Forbidden);
+        }
+
+        if !contract_metadata.copy_from(new_metadata) {
+            return Err(ContractError::BadInput);
+        }
+
+        Ok(())
+    }
+
+    /// Read contract's metadata
+    #[view]
+    pub fn read(
+        #[slot] contract_metadata: &VariableBytes<MAX_METADATA_SIZE>,
+        #[output] metadata: &mut VariableBytes<MAX_METADATA_SIZE>,
+    ) -> Result<(), ContractError> {
+        if metadata.copy_from(contract_metadata) {
+            Ok(())
+        } else {
+            Err(ContractError::BadInput)
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use ab_contracts_common::env::MethodContext;
+use ab_contracts_common::{Contract, MAX_METADATA_SIZE};
+use ab_contracts_test_utils::dummy_wallet::DummyWallet;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::shard::ShardIndex;
+use ab_executor_native::NativeExecutor;
+use ab_io_type::variable_bytes::VariableBytes;
+use ab_system_contract_code::CodeExt;
+use ab_system_contract_metadata::MetadataExt;
+use ab_system_contract_native_token::NativeToken;
+use std::mem::MaybeUninit;
+
+#[test]
+fn basic() {
+    let shard_index = ShardIndex::new(1).unwrap();
+    let executor = NativeExecutor::builder(shard_index)
+        .with_contract::<DummyWallet>()
+        .build()
+        .unwrap();
+
+    let slots = &mut executor.new_storage_slots().unwrap();
+
+    executor.transaction_emulate(Address::NULL, slots, |env| {
+        let mut metadata_bytes = [MaybeUninit::uninit(); MAX_METADATA_SIZE as usize];
+        let mut metadata_size = 0;
+        let mut metadata = VariableBytes::from_uninit(&mut metadata_bytes, &mut metadata_size);
+
+        // Metadata of system contracts is stored during initialization
+        env.metadata_read(
+            Address::SYSTEM_METADATA,
+            &Address::SYSTEM_NATIVE_TOKEN,
+            &mut metadata,
+        )
+        .unwrap();
+        assert_eq!(
+            metadata.get_initialized(),
+            NativeToken::MAIN_CONTRACT_METADATA
+        );
+
+        // Metadata of other contracts is stored during deployment
+        let wallet_address = env
+            .code_deploy(
+                MethodContext::Reset,
+                Address::SYSTEM_CODE,
+                &DummyWallet::code(),
+                &DummyWallet::metadata(),
+            )
+            .unwrap();
+        env.metadata_read(Address::SYSTEM_METADATA, &wallet_address, &mut metadata)
+            .unwrap();
+        assert_eq!(
+            metadata.get_initialized(),
+            DummyWallet::MAIN_CONTRACT_METADATA
+        );
+    });
+}
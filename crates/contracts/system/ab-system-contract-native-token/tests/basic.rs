@@ -26,6 +26,7 @@ fn basic() {
                 MethodContext::Reset,
                 Address::SYSTEM_CODE,
                 &DummyWallet::code(),
+                &DummyWallet::metadata(),
             )
             .unwrap();
         let bob = env
@@ -33,6 +34,7 @@ fn basic() {
                 MethodContext::Reset,
                 Address::SYSTEM_CODE,
                 &DummyWallet::code(),
+                &DummyWallet::metadata(),
             )
             .unwrap();
 
@@ -46,6 +48,7 @@ fn basic() {
                 MethodContext::Reset,
                 Address::SYSTEM_CODE,
                 &NativeToken::code(),
+                &NativeToken::metadata(),
             )
             .unwrap();
         env.native_token_initialize(
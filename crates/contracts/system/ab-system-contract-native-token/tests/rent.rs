@@ -0,0 +1,165 @@
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_contracts_common::env::MethodContext;
+use ab_contracts_common::{Contract, ContractError};
+use ab_contracts_test_utils::dummy_wallet::DummyWallet;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::balance::Balance;
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::shard::ShardIndex;
+use ab_executor_native::NativeExecutor;
+use ab_executor_native::rent::RentParameters;
+use ab_executor_slots::{SlotKey, Slots};
+use ab_system_contract_code::CodeExt;
+use ab_system_contract_native_token::NativeTokenExt;
+
+#[test]
+fn rent() {
+    let shard_index = ShardIndex::new(1).unwrap();
+    let executor = NativeExecutor::builder(shard_index)
+        .with_contract::<DummyWallet>()
+        .build()
+        .unwrap();
+    let parameters = RentParameters {
+        price_per_byte: Balance::from(1),
+    };
+
+    let slots = &mut executor.new_storage_slots().unwrap();
+
+    let alice = executor.transaction_emulate(Address::NULL, slots, |env| {
+        let alice = env
+            .code_deploy(
+                MethodContext::Reset,
+                Address::SYSTEM_CODE,
+                &DummyWallet::code(),
+                &DummyWallet::metadata(),
+            )
+            .unwrap();
+        env.native_token_transfer(
+            MethodContext::Reset,
+            Address::SYSTEM_NATIVE_TOKEN,
+            &Address::SYSTEM_NATIVE_TOKEN,
+            &alice,
+            &Balance::from(1_000_000),
+        )
+        .unwrap();
+
+        alice
+    });
+    let code_slot_key = SlotKey {
+        owner: alice,
+        contract: Address::SYSTEM_CODE,
+    };
+    let code = slots.get(&code_slot_key).unwrap().clone();
+    let footprint = slots.owner_footprints()[&alice];
+    let balance = |slots: &Slots| {
+        executor.with_env_ro(slots, |env| {
+            env.native_token_balance(Address::SYSTEM_NATIVE_TOKEN, &alice)
+                .unwrap()
+        })
+    };
+
+    // Rent is paid for every byte, system contracts do not pay rent
+    let rent_charge = executor
+        .charge_rent(slots, &parameters, BlockNumber::from(1), 1)
+        .unwrap();
+    assert_eq!(rent_charge.charged, Balance::from(u128::from(footprint)));
+    assert!(rent_charge.expired_slots.is_empty());
+    assert_eq!(
+        balance(slots),
+        Balance::from(1_000_000 - u128::from(footprint))
+    );
+
+    // Not enough balance to pay the rent, remaining balance is taken and slots expire
+    let rent_charge = executor
+        .charge_rent(slots, &parameters, BlockNumber::from(2), 1_000_000)
+        .unwrap();
+    assert_eq!(
+        rent_charge.charged,
+        Balance::from(1_000_000 - u128::from(footprint))
+    );
+    assert!(
+        rent_charge
+            .expired_slots
+            .iter()
+            .all(|expired_slot| expired_slot.key.owner == alice)
+    );
+    let expired_code_slot = *rent_charge
+        .expired_slots
+        .iter()
+        .find(|expired_slot| expired_slot.key == code_slot_key)
+        .unwrap();
+    assert_eq!(balance(slots), Balance::MIN);
+    assert!(slots.get(&code_slot_key).unwrap().is_empty());
+    assert_eq!(slots.owner_footprints()[&alice], 0);
+
+    // Expired slots do not pay rent
+    let rent_charge = executor
+        .charge_rent(slots, &parameters, BlockNumber::from(3), 1)
+        .unwrap();
+    assert_eq!(rent_charge.charged, Balance::MIN);
+    assert!(rent_charge.expired_slots.is_empty());
+
+    // Resurrection requires original contents and paying rent since expiry
+    assert_eq!(
+        executor.resurrect_slot(
+            slots,
+            &parameters,
+            BlockNumber::from(12),
+            &expired_code_slot,
+            code.clone(),
+        ),
+        Err(ContractError::BadInput)
+    );
+    executor.transaction_emulate(Address::NULL, slots, |env| {
+        env.native_token_transfer(
+            MethodContext::Reset,
+            Address::SYSTEM_NATIVE_TOKEN,
+            &Address::SYSTEM_NATIVE_TOKEN,
+            &alice,
+            &Balance::from(1_000_000),
+        )
+        .unwrap();
+    });
+    assert_eq!(
+        executor.resurrect_slot(
+            slots,
+            &parameters,
+            BlockNumber::from(12),
+            &expired_code_slot,
+            SharedAlignedBuffer::default(),
+        ),
+        Err(ContractError::BadInput)
+    );
+    assert_eq!(
+        executor
+            .resurrect_slot(
+                slots,
+                &parameters,
+                BlockNumber::from(12),
+                &expired_code_slot,
+                code.clone(),
+            )
+            .unwrap(),
+        Balance::from(u128::from(code.len()) * 10)
+    );
+    assert_eq!(
+        slots.get(&code_slot_key).unwrap().as_slice(),
+        code.as_slice()
+    );
+    assert_eq!(
+        balance(slots),
+        Balance::from(1_000_000 - u128::from(code.len()) * 10)
+    );
+
+    // Can't resurrect twice
+    assert_eq!(
+        executor.resurrect_slot(
+            slots,
+            &parameters,
+            BlockNumber::from(12),
+            &expired_code_slot,
+            code,
+        ),
+        Err(ContractError::Conflict)
+    );
+}
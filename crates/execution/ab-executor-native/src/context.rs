@@ -1,7 +1,10 @@
 mod ffi_call;
 
 use crate::context::ffi_call::make_ffi_call;
-use ab_contracts_common::env::{EnvState, ExecutorContext, MethodContext, PreparedMethod};
+use crate::diagnostics::{CallDiagnosticsCollector, CallFrame};
+use ab_contracts_common::env::{
+    EnvState, ExecutorContext, MethodContext, PreparedMethod, SlotScanCursor, SlotScanState,
+};
 use ab_contracts_common::method::{ExternalArgs, MethodFingerprint};
 use ab_contracts_common::{ContractError, ExitCode};
 use ab_core_primitives::address::Address;
@@ -9,10 +12,20 @@ use ab_core_primitives::shard::ShardIndex;
 use ab_executor_slots::NestedSlots;
 use ab_system_contract_address_allocator::ffi::allocate_address::AddressAllocatorAllocateAddressArgs;
 use halfbrown::HashMap;
-use std::cell::UnsafeCell;
+use std::cell::{Cell, RefCell, UnsafeCell};
 use std::ffi::c_void;
+use std::ops::RangeInclusive;
 use std::ptr::NonNull;
-use tracing::{error, info_span};
+use tracing::{debug, error, info_span};
+
+/// Fuel consumed by every method call within a static call.
+///
+/// Until fuel metering is implemented, fuel sub-budget of a static call effectively limits the
+/// number of method calls made within it.
+const CALL_FUEL_COST: u64 = 1;
+/// Fuel consumed by every slot yielded by [`ExecutorContext::next_own_slot()`] within a static
+/// call
+const SLOT_SCAN_ENTRY_FUEL_COST: u64 = 1;
 
 #[derive(Debug, Copy, Clone)]
 pub(super) struct MethodDetails {
@@ -31,6 +44,9 @@ pub(super) struct NativeExecutorContext<'a> {
     methods_by_code: &'a HashMap<(&'static [u8], &'static MethodFingerprint), MethodDetails>,
     slots: UnsafeCell<NestedSlots<'a>>,
     allow_env_mutation: bool,
+    /// Fuel remaining in the current static call, `None` outside of static calls
+    remaining_fuel: Option<&'a Cell<u64>>,
+    diagnostics: Option<&'a RefCell<CallDiagnosticsCollector>>,
 }
 
 impl<'a> ExecutorContext for NativeExecutorContext<'a> {
@@ -39,9 +55,187 @@ impl<'a> ExecutorContext for NativeExecutorContext<'a> {
         previous_env_state: &EnvState,
         prepared_method: &mut PreparedMethod<'_>,
     ) -> Result<(), ContractError> {
+        let Some(diagnostics) = self.diagnostics else {
+            return self.call_internal(previous_env_state, prepared_method);
+        };
+
+        diagnostics.borrow_mut().enter(CallFrame {
+            contract: prepared_method.contract,
+            method_fingerprint: prepared_method.fingerprint,
+        });
+        let result = self.call_internal(previous_env_state, prepared_method);
+        diagnostics.borrow_mut().exit(&result);
+
+        result
+    }
+
+    fn static_call(
+        &self,
+        previous_env_state: &EnvState,
+        prepared_method: &mut PreparedMethod<'_>,
+        fuel_limit: u64,
+    ) -> Result<(), ContractError> {
+        // Sub-budget can't exceed the fuel remaining in the parent static call
+        let fuel_limit = match self.remaining_fuel {
+            Some(remaining_fuel) => fuel_limit.min(remaining_fuel.get()),
+            None => fuel_limit,
+        };
+        let remaining_fuel = Cell::new(fuel_limit);
+
+        // SAFETY: `NativeExecutorContext` is not `Sync`, slots instance was provided as `&mut` in
+        // the constructor (meaning exclusive access) and nothing is modifying it while read-only
+        // nested slots are alive
+        let slots = unsafe { self.slots.get().as_ref_unchecked() };
+
+        // Read-only slots and disallowed environment mutation ensure that no state modifications
+        // can escape the call
+        let static_context = NativeExecutorContext {
+            shard_index: self.shard_index,
+            system_allocator_address: self.system_allocator_address,
+            methods_by_code: self.methods_by_code,
+            slots: UnsafeCell::new(slots.new_nested_ro()),
+            allow_env_mutation: false,
+            remaining_fuel: Some(&remaining_fuel),
+            diagnostics: self.diagnostics,
+        };
+        let result = static_context.call(previous_env_state, prepared_method);
+
+        if let Some(parent_remaining_fuel) = self.remaining_fuel {
+            let consumed_fuel = fuel_limit - remaining_fuel.get();
+            parent_remaining_fuel.set(parent_remaining_fuel.get() - consumed_fuel);
+        }
+
+        result
+    }
+
+    fn next_own_slot(
+        &self,
+        env_state: &EnvState,
+        cursor: &mut SlotScanCursor,
+        value: &mut [u8],
+    ) -> Result<u32, ContractError> {
+        let after = match cursor.state {
+            SlotScanState::NotStarted => None,
+            SlotScanState::Started => Some(cursor.last_owner),
+            SlotScanState::Finished => {
+                return Ok(0);
+            }
+        };
+        let owners = slot_scan_owners(cursor).ok_or_else(|| {
+            debug!(
+                owner_prefix_bits = %cursor.owner_prefix_bits,
+                "Invalid slot scan owner prefix length"
+            );
+            ContractError::BadInput
+        })?;
+
+        // SAFETY: `NativeExecutorContext` is not `Sync`, slots instance was provided as `&mut` in
+        // the constructor (meaning exclusive access) and nothing is modifying it during this call
+        let slots = unsafe { self.slots.get().as_ref_unchecked() };
+
+        let maybe_next_slot = slots
+            .next_slot(env_state.own_address, owners, after)
+            .ok_or(ContractError::Forbidden)?;
+        let Some((owner, buffer)) = maybe_next_slot else {
+            cursor.state = SlotScanState::Finished;
+            return Ok(0);
+        };
+
+        let Some(value) = value.get_mut(..buffer.len() as usize) else {
+            debug!(
+                value_size = %buffer.len(),
+                value_capacity = %value.len(),
+                "Slot doesn't fit into the value buffer"
+            );
+            return Err(ContractError::BadInput);
+        };
+
+        if let Some(remaining_fuel) = self.remaining_fuel {
+            let Some(new_remaining_fuel) =
+                remaining_fuel.get().checked_sub(SLOT_SCAN_ENTRY_FUEL_COST)
+            else {
+                debug!("Fuel sub-budget of static call exhausted");
+                return Err(ContractError::Forbidden);
+            };
+            remaining_fuel.set(new_remaining_fuel);
+        }
+
+        value.copy_from_slice(buffer.as_slice());
+        cursor.last_owner = owner;
+        cursor.state = SlotScanState::Started;
+
+        Ok(buffer.len())
+    }
+}
+
+/// Range of owners matching the prefix of the cursor, `None` if prefix length is invalid
+fn slot_scan_owners(cursor: &SlotScanCursor) -> Option<RangeInclusive<Address>> {
+    let mask = match cursor.owner_prefix_bits {
+        0 => 0,
+        owner_prefix_bits @ 1..=128 => u128::MAX << (128 - u32::from(owner_prefix_bits)),
+        _ => {
+            return None;
+        }
+    };
+    let first = u128::from(&cursor.owner_prefix) & mask;
+    let last = first | !mask;
+
+    Some(Address::from(first)..=Address::from(last))
+}
+
+impl<'a> NativeExecutorContext<'a> {
+    #[inline(always)]
+    pub(super) fn new(
+        shard_index: ShardIndex,
+        methods_by_code: &'a HashMap<(&'static [u8], &'static MethodFingerprint), MethodDetails>,
+        slots: NestedSlots<'a>,
+        allow_env_mutation: bool,
+        diagnostics: Option<&'a RefCell<CallDiagnosticsCollector>>,
+    ) -> Self {
+        Self {
+            shard_index,
+            system_allocator_address: Address::system_address_allocator(shard_index),
+            methods_by_code,
+            slots: UnsafeCell::new(slots),
+            allow_env_mutation,
+            remaining_fuel: None,
+            diagnostics,
+        }
+    }
+
+    #[inline(always)]
+    fn new_nested(
+        &self,
+        slots: NestedSlots<'a>,
+        allow_env_mutation: bool,
+    ) -> NativeExecutorContext<'a> {
+        Self {
+            shard_index: self.shard_index,
+            system_allocator_address: self.system_allocator_address,
+            methods_by_code: self.methods_by_code,
+            slots: UnsafeCell::new(slots),
+            allow_env_mutation,
+            remaining_fuel: self.remaining_fuel,
+            diagnostics: self.diagnostics,
+        }
+    }
+
+    fn call_internal(
+        &self,
+        previous_env_state: &EnvState,
+        prepared_method: &mut PreparedMethod<'_>,
+    ) -> Result<(), ContractError> {
+        if let Some(remaining_fuel) = self.remaining_fuel {
+            let Some(new_remaining_fuel) = remaining_fuel.get().checked_sub(CALL_FUEL_COST) else {
+                debug!("Fuel sub-budget of static call exhausted");
+                return Err(ContractError::Forbidden);
+            };
+            remaining_fuel.set(new_remaining_fuel);
+        }
+
         // SAFETY: `NativeExecutorContext` is not `Sync`, slots instance was provided as `&mut` in
         // the constructor (meaning exclusive access) and this function is the only place where it
-        // is accessed without recursive calls to itself
+        // is accessed mutably without recursive calls to itself
         // TODO: This ignores the lifetime by going through the pointer, find a way to make it work
         //  with the inherited lifetime
         let slots = unsafe { self.slots.get().as_mut_unchecked() };
@@ -98,40 +292,8 @@ impl<'a> ExecutorContext for NativeExecutorContext<'a> {
             method_details,
             external_args,
             env_state,
+            self.diagnostics,
             |slots, allow_env_mutation| self.new_nested(slots, allow_env_mutation),
         )
     }
 }
-
-impl<'a> NativeExecutorContext<'a> {
-    #[inline(always)]
-    pub(super) fn new(
-        shard_index: ShardIndex,
-        methods_by_code: &'a HashMap<(&'static [u8], &'static MethodFingerprint), MethodDetails>,
-        slots: NestedSlots<'a>,
-        allow_env_mutation: bool,
-    ) -> Self {
-        Self {
-            shard_index,
-            system_allocator_address: Address::system_address_allocator(shard_index),
-            methods_by_code,
-            slots: UnsafeCell::new(slots),
-            allow_env_mutation,
-        }
-    }
-
-    #[inline(always)]
-    fn new_nested(
-        &self,
-        slots: NestedSlots<'a>,
-        allow_env_mutation: bool,
-    ) -> NativeExecutorContext<'a> {
-        Self {
-            shard_index: self.shard_index,
-            system_allocator_address: self.system_allocator_address,
-            methods_by_code: self.methods_by_code,
-            slots: UnsafeCell::new(slots),
-            allow_env_mutation,
-        }
-    }
-}
@@ -1,4 +1,5 @@
 use crate::context::{MethodDetails, NativeExecutorContext};
+use crate::diagnostics::{CallDiagnosticsCollector, SlotAccessViolation};
 use ab_contracts_common::env::{Env, EnvState, ExecutorContext};
 use ab_contracts_common::metadata::decode::{
     ArgumentKind, MethodKind, MethodMetadataDecoder, MethodMetadataItem, MethodsContainerKind,
@@ -8,7 +9,7 @@ use ab_core_primitives::address::Address;
 use ab_executor_slots::{NestedSlots, SlotIndex, SlotKey};
 use ab_system_contract_address_allocator::AddressAllocator;
 use arrayvec::ArrayVec;
-use std::cell::UnsafeCell;
+use std::cell::{RefCell, UnsafeCell};
 use std::ffi::c_void;
 use std::mem::MaybeUninit;
 use std::ptr::NonNull;
@@ -231,6 +232,7 @@ pub(super) fn make_ffi_call<'slots, 'external_args, CreateNestedContext>(
     method_details: MethodDetails,
     external_args: &'external_args mut NonNull<c_void>,
     env_state: EnvState,
+    diagnostics: Option<&RefCell<CallDiagnosticsCollector>>,
     create_nested_context: CreateNestedContext,
 ) -> Result<(), ContractError>
 where
@@ -314,12 +316,13 @@ where
             // No state handling is needed
         }
         MethodKind::UpdateStatefulRo | MethodKind::ViewStateful => {
-            let state_bytes = slots
-                .use_ro(SlotKey {
-                    owner: contract,
-                    contract: Address::SYSTEM_STATE,
-                })
-                .ok_or(ContractError::Forbidden)?;
+            let slot_key = SlotKey {
+                owner: contract,
+                contract: Address::SYSTEM_STATE,
+            };
+            let state_bytes = slots.use_ro(slot_key).ok_or_else(|| {
+                slot_access_violation(diagnostics, SlotAccessViolation::ReadOnly { slot_key })
+            })?;
 
             if state_bytes.is_empty() {
                 warn!("Contract does not have state yet, can't call stateful method before init");
@@ -351,7 +354,9 @@ where
             };
             let (slot_index, state_bytes) = slots
                 .use_rw(slot_key, recommended_state_capacity)
-                .ok_or(ContractError::Forbidden)?;
+                .ok_or_else(|| {
+                    slot_access_violation(diagnostics, SlotAccessViolation::ReadWrite { slot_key })
+                })?;
 
             if state_bytes.is_empty() {
                 warn!("Contract does not have state yet, can't call stateful method before init");
@@ -449,7 +454,9 @@ where
                     owner: *owner,
                     contract,
                 };
-                let slot_bytes = slots.use_ro(slot_key).ok_or(ContractError::Forbidden)?;
+                let slot_bytes = slots.use_ro(slot_key).ok_or_else(|| {
+                    slot_access_violation(diagnostics, SlotAccessViolation::ReadOnly { slot_key })
+                })?;
 
                 // SAFETY: `internal_args_cursor`'s memory is allocated with a sufficient size
                 // above and aligned correctly
@@ -491,9 +498,13 @@ where
                     owner: *owner,
                     contract,
                 };
-                let (slot_index, slot_bytes) = slots
-                    .use_rw(slot_key, capacity)
-                    .ok_or(ContractError::Forbidden)?;
+                let (slot_index, slot_bytes) =
+                    slots.use_rw(slot_key, capacity).ok_or_else(|| {
+                        slot_access_violation(
+                            diagnostics,
+                            SlotAccessViolation::ReadWrite { slot_key },
+                        )
+                    })?;
 
                 if !tmp {
                     // SAFETY: `internal_args_cursor`'s memory is allocated with a sufficient size
@@ -547,7 +558,12 @@ where
                     };
                     let (slot_index, state_bytes) = slots
                         .use_rw(slot_key, recommended_state_capacity)
-                        .ok_or(ContractError::Forbidden)?;
+                        .ok_or_else(|| {
+                            slot_access_violation(
+                                diagnostics,
+                                SlotAccessViolation::ReadWrite { slot_key },
+                            )
+                        })?;
 
                     if !state_bytes.is_empty() {
                         debug!("Can't initialize already initialized contract");
@@ -756,3 +772,18 @@ where
 
     Ok(())
 }
+
+/// Records slot access violation in diagnostics (if enabled) and returns corresponding error
+#[cold]
+fn slot_access_violation(
+    diagnostics: Option<&RefCell<CallDiagnosticsCollector>>,
+    slot_access_violation: SlotAccessViolation,
+) -> ContractError {
+    if let Some(diagnostics) = diagnostics {
+        diagnostics
+            .borrow_mut()
+            .add_slot_access_violation(slot_access_violation);
+    }
+
+    ContractError::Forbidden
+}
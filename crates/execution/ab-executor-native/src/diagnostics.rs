@@ -0,0 +1,108 @@
+//! Diagnostics of failed contract calls.
+//!
+//! Collection of diagnostics is disabled by default and is meant for development purposes, see
+//! [`NativeExecutorBuilder::with_call_diagnostics()`].
+//!
+//! [`NativeExecutorBuilder::with_call_diagnostics()`]: crate::NativeExecutorBuilder::with_call_diagnostics()
+
+use ab_contracts_common::ContractError;
+use ab_contracts_common::method::MethodFingerprint;
+use ab_core_primitives::address::Address;
+use ab_executor_slots::SlotKey;
+
+/// Frame of the contract call stack
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CallFrame {
+    /// Contract that was called
+    pub contract: Address,
+    /// Fingerprint of the method that was called
+    pub method_fingerprint: MethodFingerprint,
+}
+
+/// Slot access violation encountered during contract call
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SlotAccessViolation {
+    /// Read-only access to a slot was denied
+    ReadOnly {
+        /// Slot key
+        slot_key: SlotKey,
+    },
+    /// Read-write access to a slot was denied
+    ReadWrite {
+        /// Slot key
+        slot_key: SlotKey,
+    },
+}
+
+/// Diagnostics of a failed contract call
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FailedCallDiagnostics {
+    /// Call stack at the time of failure, from the outermost call to the call that failed
+    pub call_stack: Vec<CallFrame>,
+    /// Error returned by the call that failed
+    pub error: ContractError,
+    /// Slot access violations encountered during transaction execution up to the failure
+    pub slot_access_violations: Vec<SlotAccessViolation>,
+    // TODO: Fuel at failure once fuel metering is implemented
+}
+
+/// Collects diagnostics during transaction execution
+#[derive(Debug, Default)]
+pub(crate) struct CallDiagnosticsCollector {
+    /// Call stack with a unique index of each call
+    call_stack: Vec<(u64, CallFrame)>,
+    next_call_index: u64,
+    slot_access_violations: Vec<SlotAccessViolation>,
+    /// Failure with indices of calls in its call stack
+    failure: Option<(Vec<u64>, FailedCallDiagnostics)>,
+}
+
+impl CallDiagnosticsCollector {
+    pub(crate) fn enter(&mut self, call_frame: CallFrame) {
+        self.call_stack.push((self.next_call_index, call_frame));
+        self.next_call_index += 1;
+    }
+
+    pub(crate) fn exit(&mut self, result: &Result<(), ContractError>) {
+        let Some(&(call_index, _call_frame)) = self.call_stack.last() else {
+            return;
+        };
+
+        if let Err(error) = result {
+            // The innermost failure is the most useful one, so the failure is only replaced if it
+            // didn't happen during this call (it was handled by one of the callers otherwise)
+            let failed_during_this_call = self
+                .failure
+                .as_ref()
+                .is_some_and(|(call_indices, _failure)| call_indices.contains(&call_index));
+
+            if !failed_during_this_call {
+                self.failure.replace((
+                    self.call_stack
+                        .iter()
+                        .map(|(call_index, _call_frame)| *call_index)
+                        .collect(),
+                    FailedCallDiagnostics {
+                        call_stack: self
+                            .call_stack
+                            .iter()
+                            .map(|(_call_index, call_frame)| *call_frame)
+                            .collect(),
+                        error: *error,
+                        slot_access_violations: self.slot_access_violations.clone(),
+                    },
+                ));
+            }
+        }
+
+        self.call_stack.pop();
+    }
+
+    pub(crate) fn add_slot_access_violation(&mut self, slot_access_violation: SlotAccessViolation) {
+        self.slot_access_violations.push(slot_access_violation);
+    }
+
+    pub(crate) fn into_failure(self) -> Option<FailedCallDiagnostics> {
+        self.failure.map(|(_call_indices, failure)| failure)
+    }
+}
@@ -1,20 +1,25 @@
 #![feature(const_convert, const_trait_impl, slice_ptr_get)]
 
 mod context;
+pub mod diagnostics;
+pub mod rent;
 
 use crate::context::{MethodDetails, NativeExecutorContext};
+use crate::diagnostics::{CallDiagnosticsCollector, FailedCallDiagnostics};
+use crate::rent::{ExpiredSlot, RentCharge, RentParameters, is_rent_exempt};
 use ab_aligned_buffer::SharedAlignedBuffer;
 use ab_contracts_common::env::{Env, EnvState, MethodContext};
 use ab_contracts_common::metadata::decode::{MetadataDecoder, MetadataDecodingError, MetadataItem};
 use ab_contracts_common::method::MethodFingerprint;
 use ab_contracts_common::{
     Contract, ContractError, ContractTrait, ContractTraitDefinition, MAX_CODE_SIZE,
-    NativeExecutorContactMethod,
+    MAX_METADATA_SIZE, NativeExecutorContactMethod,
 };
 use ab_contracts_standards::fungible::Fungible;
 use ab_contracts_standards::tx_handler::TxHandlerExt;
 use ab_core_primitives::address::Address;
 use ab_core_primitives::balance::Balance;
+use ab_core_primitives::block::BlockNumber;
 use ab_core_primitives::shard::ShardIndex;
 use ab_core_primitives::transaction::{Transaction, TransactionHeader, TransactionSlot};
 use ab_executor_slots::{Slot, SlotKey, Slots};
@@ -23,10 +28,13 @@ use ab_io_type::variable_elements::VariableElements;
 use ab_system_contract_address_allocator::{AddressAllocator, AddressAllocatorExt};
 use ab_system_contract_block::{Block, BlockExt};
 use ab_system_contract_code::{Code, CodeExt};
+use ab_system_contract_metadata::{Metadata, MetadataExt};
 use ab_system_contract_native_token::{NativeToken, NativeTokenExt};
 use ab_system_contract_simple_wallet_base::SimpleWalletBase;
 use ab_system_contract_state::State;
 use halfbrown::HashMap;
+use std::cell::RefCell;
+use tracing::debug;
 
 /// Native executor errors
 #[derive(Debug, thiserror::Error)]
@@ -52,6 +60,57 @@ pub enum NativeExecutorError {
     },
 }
 
+/// Error for [`NativeExecutor::transaction_prevalidate()`]
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionPrevalidationError {
+    /// Unsupported transaction version
+    #[error("Unsupported transaction version {version}")]
+    UnsupportedVersion {
+        /// Transaction version
+        version: u64,
+    },
+    /// The same slot is declared more than once in read or write slots
+    #[error("Slot (owner {owner:?}, contract {contract:?}) is declared more than once")]
+    DuplicateSlot {
+        /// Slot owner
+        owner: Address,
+        /// Contract that manages the slot
+        contract: Address,
+    },
+    /// Failed to read the balance of the transaction handler contract
+    #[error("Failed to read the balance of the transaction handler contract: {error}")]
+    BalanceReadFailed {
+        /// Low-level error
+        error: ContractError,
+    },
+    /// Transaction handler contract doesn't have enough balance to pay for the gas limit
+    #[error("Insufficient balance: required {required}, available {available}")]
+    InsufficientBalance {
+        /// Balance required to pay for the gas limit
+        required: Balance,
+        /// Available balance
+        available: Balance,
+    },
+    /// Transaction authorization failed (invalid seal, unexpected nonce, etc.)
+    #[error("Transaction authorization failed: {error}")]
+    AuthorizationFailed {
+        /// Low-level error
+        error: ContractError,
+    },
+}
+
+/// Receipt of transaction execution
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TransactionReceipt {
+    /// Result of transaction execution
+    pub result: Result<(), ContractError>,
+    /// Diagnostics of the failed call.
+    ///
+    /// Only present if transaction execution failed and call diagnostics were enabled with
+    /// [`NativeExecutorBuilder::with_call_diagnostics()`].
+    pub diagnostics: Option<FailedCallDiagnostics>,
+}
+
 #[derive(Debug, Clone)]
 struct MethodsEntry {
     contact_code: &'static str,
@@ -64,6 +123,7 @@ struct MethodsEntry {
 pub struct NativeExecutorBuilder {
     shard_index: ShardIndex,
     methods: Vec<MethodsEntry>,
+    call_diagnostics: bool,
 }
 
 impl NativeExecutorBuilder {
@@ -71,6 +131,7 @@ impl NativeExecutorBuilder {
         let instance = Self {
             shard_index,
             methods: Vec::new(),
+            call_diagnostics: false,
         };
 
         // Start with system contracts
@@ -78,6 +139,7 @@ impl NativeExecutorBuilder {
             .with_contract::<AddressAllocator>()
             .with_contract::<Block>()
             .with_contract::<Code>()
+            .with_contract::<Metadata>()
             .with_contract::<NativeToken>()
             .with_contract_trait::<NativeToken, dyn Fungible>()
             .with_contract::<SimpleWalletBase>()
@@ -134,6 +196,16 @@ impl NativeExecutorBuilder {
         self
     }
 
+    /// Collect diagnostics of failed contract calls (call stack, slot access violations, etc.),
+    /// which will be attached to [`TransactionReceipt`].
+    ///
+    /// This has a performance cost and is meant for development purposes, disabled by default.
+    #[must_use]
+    pub fn with_call_diagnostics(mut self, enabled: bool) -> Self {
+        self.call_diagnostics = enabled;
+        self
+    }
+
     /// Build native execution configuration
     pub fn build(self) -> Result<NativeExecutor, NativeExecutorError> {
         // 10 is a decent capacity for many typical cases without reallocation
@@ -199,6 +271,7 @@ impl NativeExecutorBuilder {
         Ok(NativeExecutor {
             shard_index: self.shard_index,
             methods_by_code,
+            call_diagnostics: self.call_diagnostics,
         })
     }
 }
@@ -208,6 +281,7 @@ pub struct NativeExecutor {
     shard_index: ShardIndex,
     /// Indexed by contract's code and method fingerprint
     methods_by_code: HashMap<(&'static [u8], &'static MethodFingerprint), MethodDetails>,
+    call_diagnostics: bool,
 }
 
 impl NativeExecutor {
@@ -225,31 +299,63 @@ impl NativeExecutor {
         let address_allocator_address = Address::system_address_allocator(self.shard_index);
         let mut slots = Slots::new(slots);
 
-        let system_contracts: [(Address, &VariableBytes<{ MAX_CODE_SIZE }>); _] = [
-            (Address::SYSTEM_STATE, &State::code()),
-            (address_allocator_address, &AddressAllocator::code()),
-            (Address::SYSTEM_BLOCK, &Block::code()),
-            (Address::SYSTEM_NATIVE_TOKEN, &NativeToken::code()),
+        let system_contracts: [(
+            Address,
+            &VariableBytes<{ MAX_CODE_SIZE }>,
+            &VariableBytes<{ MAX_METADATA_SIZE }>,
+        ); _] = [
+            (Address::SYSTEM_STATE, &State::code(), &State::metadata()),
+            (
+                address_allocator_address,
+                &AddressAllocator::code(),
+                &AddressAllocator::metadata(),
+            ),
+            (Address::SYSTEM_BLOCK, &Block::code(), &Block::metadata()),
+            (
+                Address::SYSTEM_METADATA,
+                &Metadata::code(),
+                &Metadata::metadata(),
+            ),
+            (
+                Address::SYSTEM_NATIVE_TOKEN,
+                &NativeToken::code(),
+                &NativeToken::metadata(),
+            ),
             (
                 Address::SYSTEM_SIMPLE_WALLET_BASE,
                 &SimpleWalletBase::code(),
+                &SimpleWalletBase::metadata(),
             ),
         ];
 
         {
             let mut nested_slots = slots.new_nested_rw();
             // Allow deployment of system contracts
-            for (address, _code) in system_contracts {
+            for (address, _code, _metadata) in system_contracts {
                 assert!(nested_slots.add_new_contract(address));
             }
         }
 
         // Deploy and initialize other system contacts
         self.transaction_emulate(Address::NULL, &mut slots, |env| {
-            for (address, code) in system_contracts {
+            for (address, code, _metadata) in system_contracts {
                 env.code_store(MethodContext::Reset, Address::SYSTEM_CODE, &address, code)?;
             }
 
+            // Metadata of all system contracts, including manually deployed system code contract
+            for (address, metadata) in system_contracts
+                .into_iter()
+                .map(|(address, _code, metadata)| (address, metadata))
+                .chain([(Address::SYSTEM_CODE, &*Code::metadata())])
+            {
+                env.metadata_store(
+                    MethodContext::Reset,
+                    Address::SYSTEM_METADATA,
+                    &address,
+                    metadata,
+                )?;
+            }
+
             env.address_allocator_new(MethodContext::Reset, address_allocator_address)?;
             env.block_genesis(MethodContext::Reset, Address::SYSTEM_BLOCK)?;
             env.native_token_initialize(
@@ -315,6 +421,7 @@ impl NativeExecutor {
             &self.methods_by_code,
             slots.new_nested_ro(),
             false,
+            None,
         );
         let env = Env::with_executor_context(env_state, &mut executor_context);
         env.tx_handler_authorize(
@@ -327,6 +434,62 @@ impl NativeExecutor {
         )
     }
 
+    /// Cheap prevalidation of the provided transaction against the state in `slots`, without
+    /// executing it.
+    ///
+    /// Checks transaction version, that read and write slots are not declared more than once,
+    /// that the transaction handler contract has enough native token balance to pay for the gas
+    /// limit at `gas_price` and finally authorizes the transaction like
+    /// [`Self::transaction_verify()`] does (seal and nonce checks). Cheaper checks go first.
+    ///
+    /// This is meant for the transaction pool, which prevalidates transactions on submission and
+    /// again against the state of every new best block, such that it only retains transactions
+    /// that can be included in a block.
+    pub fn transaction_prevalidate(
+        &self,
+        transaction: Transaction<'_>,
+        slots: &Slots,
+        gas_price: Balance,
+    ) -> Result<(), TransactionPrevalidationError> {
+        if transaction.header.version != TransactionHeader::TRANSACTION_VERSION {
+            return Err(TransactionPrevalidationError::UnsupportedVersion {
+                version: transaction.header.version,
+            });
+        }
+
+        for declared_slots in [transaction.read_slots, transaction.write_slots] {
+            // Number of slots is small, so quadratic complexity is fine and avoids allocation
+            for (index, slot) in declared_slots.iter().enumerate() {
+                if declared_slots[..index].contains(slot) {
+                    return Err(TransactionPrevalidationError::DuplicateSlot {
+                        owner: slot.owner,
+                        contract: slot.contract,
+                    });
+                }
+            }
+        }
+
+        let payer = transaction.header.contract;
+        let available = self
+            .with_env_ro(slots, |env| {
+                env.native_token_balance(Address::SYSTEM_NATIVE_TOKEN, &payer)
+            })
+            .map_err(|error| TransactionPrevalidationError::BalanceReadFailed { error })?;
+        // Overflow means the balance is definitely not sufficient
+        let required = u128::from(u64::from(transaction.header.gas_limit))
+            .checked_mul(u128::from(gas_price))
+            .map(Balance::from);
+        if required.is_none_or(|required| available < required) {
+            return Err(TransactionPrevalidationError::InsufficientBalance {
+                required: required.unwrap_or(Balance::MAX),
+                available,
+            });
+        }
+
+        self.transaction_verify(transaction, slots)
+            .map_err(|error| TransactionPrevalidationError::AuthorizationFailed { error })
+    }
+
     /// Execute the previously verified transaction.
     ///
     /// [`Self::transaction_verify()`] must be used for verification.
@@ -336,6 +499,45 @@ impl NativeExecutor {
         &self,
         transaction: Transaction<'_>,
         slots: &mut Slots,
+    ) -> Result<(), ContractError> {
+        self.transaction_execute_internal(transaction, slots, None)
+    }
+
+    /// Execute the previously verified transaction and produce a receipt.
+    ///
+    /// Same as [`Self::transaction_execute()`], but the receipt will contain diagnostics of the
+    /// failed call if enabled with [`NativeExecutorBuilder::with_call_diagnostics()`].
+    pub fn transaction_execute_with_receipt(
+        &self,
+        transaction: Transaction<'_>,
+        slots: &mut Slots,
+    ) -> TransactionReceipt {
+        if !self.call_diagnostics {
+            return TransactionReceipt {
+                result: self.transaction_execute_internal(transaction, slots, None),
+                diagnostics: None,
+            };
+        }
+
+        let collector = RefCell::new(CallDiagnosticsCollector::default());
+        let result = self.transaction_execute_internal(transaction, slots, Some(&collector));
+        let diagnostics = if result.is_err() {
+            collector.into_inner().into_failure()
+        } else {
+            None
+        };
+
+        TransactionReceipt {
+            result,
+            diagnostics,
+        }
+    }
+
+    fn transaction_execute_internal(
+        &self,
+        transaction: Transaction<'_>,
+        slots: &mut Slots,
+        diagnostics: Option<&RefCell<CallDiagnosticsCollector>>,
     ) -> Result<(), ContractError> {
         if transaction.header.version != TransactionHeader::TRANSACTION_VERSION {
             return Err(ContractError::BadInput);
@@ -373,6 +575,7 @@ impl NativeExecutor {
             &self.methods_by_code,
             slots.new_nested_rw(),
             true,
+            diagnostics,
         );
 
         let mut env = Env::with_executor_context(env_state, &mut executor_context);
@@ -434,6 +637,7 @@ impl NativeExecutor {
                 &self.methods_by_code,
                 slots.new_nested_ro(),
                 false,
+                None,
             );
             let env = Env::with_executor_context(env_state, &mut executor_context);
             env.tx_handler_authorize(
@@ -452,6 +656,7 @@ impl NativeExecutor {
                 &self.methods_by_code,
                 slots.new_nested_rw(),
                 true,
+                None,
             );
             let mut env = Env::with_executor_context(env_state, &mut executor_context);
             env.tx_handler_execute(
@@ -468,6 +673,122 @@ impl NativeExecutor {
         Ok(())
     }
 
+    /// Charge storage rent for `blocks` blocks from all owners of slots in `slots`, see [`rent`]
+    /// module for details.
+    ///
+    /// Owners are processed in the order of their addresses. Rent is transferred from owner's
+    /// native token balance to the native token contract. Owners that can't pay the rent in full
+    /// lose their remaining balance, and their slots (except the balance itself) expire at
+    /// `block_number`.
+    ///
+    /// Native token balance slots of all non-exempt owners with non-empty slots must be present in
+    /// `slots`, otherwise an error is returned.
+    pub fn charge_rent(
+        &self,
+        slots: &mut Slots,
+        parameters: &RentParameters,
+        block_number: BlockNumber,
+        blocks: u64,
+    ) -> Result<RentCharge, ContractError> {
+        let mut rent_charge = RentCharge::default();
+
+        for (owner, footprint) in slots.owner_footprints() {
+            if is_rent_exempt(owner, self.shard_index) {
+                continue;
+            }
+
+            let rent = parameters.rent(footprint, blocks);
+            if rent == Balance::MIN {
+                continue;
+            }
+
+            let paid = self.transaction_emulate(Address::NULL, slots, |env| {
+                let balance = env.native_token_balance(Address::SYSTEM_NATIVE_TOKEN, &owner)?;
+                let paid = balance.min(rent);
+
+                if paid > Balance::MIN {
+                    env.native_token_transfer(
+                        MethodContext::Reset,
+                        Address::SYSTEM_NATIVE_TOKEN,
+                        &owner,
+                        &Address::SYSTEM_NATIVE_TOKEN,
+                        &paid,
+                    )?;
+                }
+
+                Ok::<_, ContractError>(paid)
+            })?;
+            rent_charge.charged += paid;
+
+            if paid < rent {
+                debug!(?owner, %rent, %paid, "Owner couldn't pay rent, expiring its slots");
+
+                rent_charge.expired_slots.extend(
+                    slots
+                        .expire_owner(owner, &[Address::SYSTEM_NATIVE_TOKEN])
+                        .into_iter()
+                        .map(|(slot_key, contents)| {
+                            ExpiredSlot::new(slot_key, &contents, block_number)
+                        }),
+                );
+            }
+        }
+
+        Ok(rent_charge)
+    }
+
+    /// Resurrect a slot that expired due to unpaid rent, see [`rent`] module for details.
+    ///
+    /// `contents` must be the contents of the slot at the time of expiry. Rent for the whole
+    /// period since expiry until `block_number` is charged from the native token balance of the
+    /// slot owner, such that letting slots expire is never cheaper than paying the rent.
+    ///
+    /// Returns charged rent.
+    pub fn resurrect_slot(
+        &self,
+        slots: &mut Slots,
+        parameters: &RentParameters,
+        block_number: BlockNumber,
+        expired_slot: &ExpiredSlot,
+        contents: SharedAlignedBuffer,
+    ) -> Result<Balance, ContractError> {
+        if !expired_slot.is_expired_contents(&contents) {
+            return Err(ContractError::BadInput);
+        }
+
+        let blocks = block_number
+            .checked_sub(expired_slot.expired_at)
+            .ok_or(ContractError::BadInput)?;
+
+        // Slot might have been written to since expiry
+        if slots
+            .get(&expired_slot.key)
+            .is_some_and(|buffer| !buffer.is_empty())
+        {
+            return Err(ContractError::Conflict);
+        }
+
+        let owner = expired_slot.key.owner;
+        let rent = parameters.rent(u64::from(expired_slot.size), u64::from(blocks));
+        if rent > Balance::MIN {
+            self.transaction_emulate(Address::NULL, slots, |env| {
+                env.native_token_transfer(
+                    MethodContext::Reset,
+                    Address::SYSTEM_NATIVE_TOKEN,
+                    &owner,
+                    &Address::SYSTEM_NATIVE_TOKEN,
+                    &rent,
+                )
+            })?;
+        }
+
+        if !slots.restore_expired(expired_slot.key, contents) {
+            return Err(ContractError::Conflict);
+        }
+
+        Ok(rent)
+    }
+
     /// Emulate a transaction submitted by `contract` with method calls happening inside `calls`
     /// without going through `TxHandler`.
     ///
@@ -497,6 +818,7 @@ impl NativeExecutor {
             &self.methods_by_code,
             slots.new_nested_rw(),
             true,
+            None,
         );
         let mut env = Env::with_executor_context(env_state, &mut executor_context);
         calls(&mut env)
@@ -524,6 +846,7 @@ impl NativeExecutor {
             &self.methods_by_code,
             slots.new_nested_ro(),
             false,
+            None,
         );
         let env = Env::with_executor_context(env_state, &mut executor_context);
         callback(&env)
@@ -0,0 +1,99 @@
+//! Storage rent accounting.
+//!
+//! Every owner pays rent for the total size of contents of its slots (see
+//! [`Slots::owner_footprints()`]) in native token, which prevents unbounded free state growth.
+//! Rent is charged with [`NativeExecutor::charge_rent()`] during block execution. Slots of owners
+//! that can't pay the rent expire and can later be resurrected with
+//! [`NativeExecutor::resurrect_slot()`].
+//!
+//! [`Slots::owner_footprints()`]: ab_executor_slots::Slots::owner_footprints()
+//! [`NativeExecutor::charge_rent()`]: crate::NativeExecutor::charge_rent()
+//! [`NativeExecutor::resurrect_slot()`]: crate::NativeExecutor::resurrect_slot()
+
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::balance::Balance;
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::shard::ShardIndex;
+use ab_executor_slots::SlotKey;
+
+/// Parameters of storage rent
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RentParameters {
+    /// Rent charged for every byte of slot contents per block
+    pub price_per_byte: Balance,
+}
+
+impl RentParameters {
+    /// Rent for `size` bytes stored for `blocks` blocks, saturates at [`Balance::MAX`]
+    pub fn rent(&self, size: u64, blocks: u64) -> Balance {
+        u128::from(self.price_per_byte)
+            .checked_mul(u128::from(size))
+            .and_then(|rent| rent.checked_mul(u128::from(blocks)))
+            .map_or(Balance::MAX, Balance::from)
+    }
+}
+
+/// Slot that expired because its owner didn't pay the rent.
+///
+/// Contents of the slot are removed from the state, only their commitment is retained, such that
+/// the slot can be resurrected deterministically by anyone who still has the original contents.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ExpiredSlot {
+    /// Slot key
+    pub key: SlotKey,
+    /// Hash of slot contents at the time of expiry
+    pub contents_hash: Blake3Hash,
+    /// Size of slot contents at the time of expiry
+    pub size: u32,
+    /// Block at which the slot expired
+    pub expired_at: BlockNumber,
+}
+
+impl ExpiredSlot {
+    /// Create a new instance for slot contents that expired at `expired_at`
+    pub fn new(key: SlotKey, contents: &SharedAlignedBuffer, expired_at: BlockNumber) -> Self {
+        Self {
+            key,
+            contents_hash: contents_hash(contents),
+            size: contents.len(),
+            expired_at,
+        }
+    }
+
+    /// Check whether provided contents are the contents of the slot at the time of expiry
+    pub fn is_expired_contents(&self, contents: &SharedAlignedBuffer) -> bool {
+        contents.len() == self.size && contents_hash(contents) == self.contents_hash
+    }
+}
+
+/// Result of charging storage rent
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct RentCharge {
+    /// Total rent charged from all owners
+    pub charged: Balance,
+    /// Slots that expired because their owners could not pay the rent, ordered by owner and
+    /// contract address
+    pub expired_slots: Vec<ExpiredSlot>,
+}
+
+fn contents_hash(contents: &SharedAlignedBuffer) -> Blake3Hash {
+    // TODO: Keyed hash
+    Blake3Hash::from(blake3::hash(contents.as_slice()))
+}
+
+/// System contracts do not pay rent
+pub(crate) fn is_rent_exempt(owner: Address, shard_index: ShardIndex) -> bool {
+    [
+        Address::NULL,
+        Address::SYSTEM_CODE,
+        Address::SYSTEM_BLOCK,
+        Address::SYSTEM_STATE,
+        Address::SYSTEM_NATIVE_TOKEN,
+        Address::SYSTEM_METADATA,
+        Address::SYSTEM_SIMPLE_WALLET_BASE,
+        Address::system_address_allocator(shard_index),
+    ]
+    .contains(&owner)
+}
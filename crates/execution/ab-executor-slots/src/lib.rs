@@ -5,6 +5,9 @@ extern crate alloc;
 use ab_aligned_buffer::{OwnedAlignedBuffer, SharedAlignedBuffer};
 use ab_core_primitives::address::Address;
 use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::ops::{Bound, RangeInclusive};
 use replace_with::replace_with_or_abort;
 use smallvec::SmallVec;
 use tracing::debug;
@@ -101,6 +104,25 @@ struct Inner {
     /// Addresses in this list are allowed to create slots for any owner, and other contacts are
     /// allowed to create slots owned by these addresses.
     new_contracts: SmallVec<[Address; NEW_CONTRACTS_INLINE]>,
+    /// Index of slots ordered by contract and owner, used for deterministic iteration over slots
+    /// managed by a contract.
+    ///
+    /// Ephemeral slots of `Address::NULL` contract are not indexed.
+    ordered_index: BTreeMap<(Address, Address), SlotIndex>,
+}
+
+impl Inner {
+    fn rebuild_ordered_index(&mut self) {
+        self.ordered_index = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(_slot_index, (slot_key, _slot))| slot_key.contract != Address::NULL)
+            .map(|(slot_index, (slot_key, _slot))| {
+                ((slot_key.contract, slot_key.owner), SlotIndex(slot_index))
+            })
+            .collect();
+    }
 }
 
 /// Collection of slots, primarily for the execution environment
@@ -139,11 +161,13 @@ impl Slots {
             })
             .collect();
 
-        let inner = Inner {
+        let mut inner = Inner {
             slots,
             slot_access: SmallVec::new(),
             new_contracts: SmallVec::new(),
+            ordered_index: BTreeMap::new(),
         };
+        inner.rebuild_ordered_index();
 
         Self(Box::new(inner))
     }
@@ -188,6 +212,153 @@ impl Slots {
         true
     }
 
+    /// Get contents of a slot with a specified owner and contract without marking it as used.
+    ///
+    /// Returns `None` if the slot is not present in the collection.
+    #[inline]
+    pub fn get(&self, slot_key: &SlotKey) -> Option<&SharedAlignedBuffer> {
+        let inner = &*self.0;
+        let &slot_index = inner
+            .ordered_index
+            .get(&(slot_key.contract, slot_key.owner))?;
+
+        match &inner
+            .slots
+            .get(usize::from(slot_index))
+            .expect("Indexed slot exists; qed")
+            .1
+        {
+            SlotState::Original(buffer)
+            | SlotState::OriginalReadOnly(buffer)
+            | SlotState::Modified(buffer) => Some(buffer),
+            SlotState::ModifiedReadOnly(_)
+            | SlotState::OriginalReadWrite { .. }
+            | SlotState::ModifiedReadWrite { .. } => {
+                unreachable!("Only original and modified slots can exist at the `Slots` level; qed")
+            }
+        }
+    }
+
+    /// Total size of contents of slots in the collection in bytes for each owner, ordered by owner
+    /// address.
+    ///
+    /// This is the storage footprint used for rent accounting, empty slots do not contribute to it.
+    pub fn owner_footprints(&self) -> BTreeMap<Address, u64> {
+        let mut footprints = BTreeMap::<Address, u64>::new();
+
+        for (slot_key, slot) in &self.0.slots {
+            let buffer = match slot {
+                SlotState::Original(buffer)
+                | SlotState::OriginalReadOnly(buffer)
+                | SlotState::Modified(buffer) => buffer,
+                SlotState::ModifiedReadOnly(_)
+                | SlotState::OriginalReadWrite { .. }
+                | SlotState::ModifiedReadWrite { .. } => unreachable!(
+                    "Only original and modified slots can exist at the `Slots` level; qed"
+                ),
+            };
+
+            *footprints.entry(slot_key.owner).or_default() += u64::from(buffer.len());
+        }
+
+        footprints
+    }
+
+    /// Expire non-empty slots owned by `owner`, replacing their contents with empty buffers.
+    ///
+    /// Slots managed by contracts in `retained_contracts` and read-only slots are left untouched.
+    /// Returns keys and previous contents of expired slots, ordered by contract address.
+    pub fn expire_owner(
+        &mut self,
+        owner: Address,
+        retained_contracts: &[Address],
+    ) -> Vec<(SlotKey, SharedAlignedBuffer)> {
+        let inner = &mut *self.0;
+        let mut expired = Vec::new();
+
+        for (&(contract, slot_owner), &slot_index) in &inner.ordered_index {
+            if slot_owner != owner || retained_contracts.contains(&contract) {
+                continue;
+            }
+
+            let (slot_key, slot) = inner
+                .slots
+                .get_mut(usize::from(slot_index))
+                .expect("Indexed slot exists; qed");
+            let buffer = match slot {
+                SlotState::Original(buffer) | SlotState::Modified(buffer) => buffer,
+                SlotState::OriginalReadOnly(_) => {
+                    continue;
+                }
+                SlotState::ModifiedReadOnly(_)
+                | SlotState::OriginalReadWrite { .. }
+                | SlotState::ModifiedReadWrite { .. } => unreachable!(
+                    "Only original and modified slots can exist at the `Slots` level; qed"
+                ),
+            };
+
+            if buffer.is_empty() {
+                continue;
+            }
+
+            expired.push((*slot_key, buffer.clone()));
+            *slot = SlotState::Modified(SharedAlignedBuffer::default());
+        }
+
+        expired
+    }
+
+    /// Restore contents of a slot that was previously expired with [`Self::expire_owner()`].
+    ///
+    /// Returns `false` if the slot is ephemeral, read-only or not empty.
+    #[must_use]
+    pub fn restore_expired(&mut self, slot_key: SlotKey, contents: SharedAlignedBuffer) -> bool {
+        // Ephemeral slots never expire
+        if slot_key.contract == Address::NULL {
+            return false;
+        }
+
+        let inner = &mut *self.0;
+
+        let Some(&slot_index) = inner
+            .ordered_index
+            .get(&(slot_key.contract, slot_key.owner))
+        else {
+            let slot_index = SlotIndex(inner.slots.len());
+            inner
+                .ordered_index
+                .insert((slot_key.contract, slot_key.owner), slot_index);
+            inner.slots.push((slot_key, SlotState::Modified(contents)));
+            return true;
+        };
+
+        let slot = &mut inner
+            .slots
+            .get_mut(usize::from(slot_index))
+            .expect("Indexed slot exists; qed")
+            .1;
+        let buffer = match slot {
+            SlotState::Original(buffer) | SlotState::Modified(buffer) => buffer,
+            SlotState::OriginalReadOnly(_) => {
+                debug!(?slot_key, "Not restoring expired slot that is read-only");
+                return false;
+            }
+            SlotState::ModifiedReadOnly(_)
+            | SlotState::OriginalReadWrite { .. }
+            | SlotState::ModifiedReadWrite { .. } => {
+                unreachable!("Only original and modified slots can exist at the `Slots` level; qed")
+            }
+        };
+
+        if !buffer.is_empty() {
+            debug!(?slot_key, "Not restoring expired slot that is not empty");
+            return false;
+        }
+
+        *slot = SlotState::Modified(contents);
+        true
+    }
+
     /// Iterate over all slots in the collection
     #[inline]
     pub fn iter(&self) -> impl ExactSizeIterator<Item = (&SlotKey, &SharedAlignedBuffer)> + '_ {
@@ -314,9 +485,15 @@ impl Drop for NestedSlots<'_> {
             // Remove temporary values for `Address::NULL` contract, these are used as `#[tmp]`
             // "slots" by convention in the execution environment since there is no code behind
             // `Address::NULL` to possibly use it for anything
+            let slots_before = inner.slots.len();
             inner
                 .slots
                 .retain(|(slot_key, _slot)| slot_key.contract != Address::NULL);
+
+            // Removal shifts indices of the remaining slots
+            if inner.slots.len() != slots_before {
+                inner.rebuild_ordered_index();
+            }
         }
     }
 }
@@ -460,6 +637,77 @@ impl<'a> NestedSlots<'a> {
         Some(buffer.clone())
     }
 
+    /// Get the next non-empty slot managed by `contract` with owner in `owners` range, ordered by
+    /// owner address.
+    ///
+    /// Iteration starts after `after` if specified and within the range. Similarly to
+    /// [`Self::get_code()`], the slot is not marked as used, instead its current contents is
+    /// cloned and returned along with the owner.
+    ///
+    /// Returns `None` in case of access violation (the next slot is currently being modified) and
+    /// `Some(None)` if there are no more slots.
+    #[inline]
+    pub fn next_slot(
+        &self,
+        contract: Address,
+        owners: RangeInclusive<Address>,
+        after: Option<Address>,
+    ) -> Option<Option<(Address, SharedAlignedBuffer)>> {
+        let inner = self.inner_ro();
+
+        let (&first, &last) = (owners.start(), owners.end());
+        if owners.is_empty() {
+            return Some(None);
+        }
+        let lower_bound = match after {
+            Some(after) if after >= last => {
+                return Some(None);
+            }
+            Some(after) if after >= first => Bound::Excluded((contract, after)),
+            _ => Bound::Included((contract, first)),
+        };
+
+        for (&(_contract, owner), &slot_index) in inner
+            .ordered_index
+            .range((lower_bound, Bound::Included((contract, last))))
+        {
+            // Ensure slot is not currently being written to
+            if inner
+                .slot_access
+                .iter()
+                .any(|slot_access| slot_access.slot_index == slot_index && slot_access.read_write)
+            {
+                debug!(?contract, ?owner, "`next_slot` access violation");
+                return None;
+            }
+
+            let buffer = match &inner
+                .slots
+                .get(usize::from(slot_index))
+                .expect("Indexed slot exists; qed")
+                .1
+            {
+                SlotState::Original(buffer)
+                | SlotState::OriginalReadOnly(buffer)
+                | SlotState::Modified(buffer)
+                | SlotState::ModifiedReadOnly(buffer) => buffer,
+                SlotState::OriginalReadWrite { .. } | SlotState::ModifiedReadWrite { .. } => {
+                    debug!(?contract, ?owner, "`next_slot` access violation");
+                    return None;
+                }
+            };
+
+            // Empty slots are indistinguishable from non-existent ones
+            if buffer.is_empty() {
+                continue;
+            }
+
+            return Some(Some((owner, buffer.clone())));
+        }
+
+        Some(None)
+    }
+
     /// Read-only access to a slot with a specified owner and contract, marks it as used.
     ///
     /// Returns `None` in case of access violation.
@@ -489,6 +737,7 @@ impl<'a> NestedSlots<'a> {
             &mut inner_rw.slots,
             &mut inner_rw.slot_access,
             &inner_rw.new_contracts,
+            &mut inner_rw.ordered_index,
         );
 
         if result.is_none() {
@@ -504,6 +753,7 @@ impl<'a> NestedSlots<'a> {
         slots: &'b mut SmallVec<[(SlotKey, SlotState); INLINE_SIZE]>,
         slot_access: &mut SmallVec<[SlotAccess; INLINE_SIZE]>,
         new_contracts: &[Address],
+        ordered_index: &mut BTreeMap<(Address, Address), SlotIndex>,
     ) -> Option<&'b SharedAlignedBuffer> {
         let maybe_slot_index = slots
             .iter()
@@ -565,11 +815,16 @@ impl<'a> NestedSlots<'a> {
                 return None;
             }
 
+            let slot_index = SlotIndex(slots.len());
             slot_access.push(SlotAccess {
-                slot_index: SlotIndex(slots.len()),
+                slot_index,
                 read_write: false,
             });
 
+            if slot_key.contract != Address::NULL {
+                ordered_index.insert((slot_key.contract, slot_key.owner), slot_index);
+            }
+
             let slot = SlotState::OriginalReadOnly(SharedAlignedBuffer::default());
             slots.push((slot_key, slot));
             let slot = &slots.last().expect("Just inserted; qed").1;
@@ -650,8 +905,16 @@ impl<'a> NestedSlots<'a> {
         let slots = &mut inner.slots;
         let slot_access = &mut inner.slot_access;
         let new_contracts = &inner.new_contracts;
+        let ordered_index = &mut inner.ordered_index;
 
-        let result = Self::use_rw_internal(slot_key, capacity, slots, slot_access, new_contracts);
+        let result = Self::use_rw_internal(
+            slot_key,
+            capacity,
+            slots,
+            slot_access,
+            new_contracts,
+            ordered_index,
+        );
 
         if result.is_none() {
             debug!(?slot_key, "`use_rw` access violation");
@@ -667,6 +930,7 @@ impl<'a> NestedSlots<'a> {
         slots: &'b mut SmallVec<[(SlotKey, SlotState); INLINE_SIZE]>,
         slot_access: &mut SmallVec<[SlotAccess; INLINE_SIZE]>,
         new_contracts: &[Address],
+        ordered_index: &mut BTreeMap<(Address, Address), SlotIndex>,
     ) -> Option<(SlotIndex, &'b mut OwnedAlignedBuffer)> {
         let maybe_slot_index = slots
             .iter()
@@ -751,6 +1015,10 @@ impl<'a> NestedSlots<'a> {
                 read_write: true,
             });
 
+            if slot_key.contract != Address::NULL {
+                ordered_index.insert((slot_key.contract, slot_key.owner), slot_index);
+            }
+
             let slot = SlotState::OriginalReadWrite {
                 buffer: OwnedAlignedBuffer::with_capacity(capacity),
                 previous: SharedAlignedBuffer::default(),
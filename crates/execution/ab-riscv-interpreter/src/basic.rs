@@ -488,6 +488,13 @@ where
             _phantom: PhantomData,
         }
     }
+
+    /// Advance the program counter past an instruction that was fetched without going through
+    /// [`InstructionFetcher::fetch_instruction()`]
+    #[inline(always)]
+    pub(crate) fn advance_pc(&mut self, instruction_size: u8) {
+        self.pc += instruction_size.into();
+    }
 }
 
 /// System instruction handler that results in illegal instruction for all system calls and does
@@ -107,10 +107,12 @@
 #![no_std]
 
 pub mod basic;
+pub mod mapped_memory;
 pub mod prelude;
 mod private;
 pub mod rv32;
 pub mod rv64;
+pub mod tier;
 pub mod v;
 pub mod zicond;
 pub mod zicsr;
@@ -0,0 +1,316 @@
+//! Virtual memory with host buffers mapped into guest address space.
+//!
+//! [`MappedMemory`] wraps another [`VirtualMemory`] implementation and allows mapping host buffers
+//! (like contents of storage slots) into guest address space without copying them into guest
+//! memory first. Read-only mappings are backed by shared slices, while read-write mappings (like
+//! output regions) are backed by exclusive slices.
+//!
+//! ## Safety analysis
+//!
+//! Mappings borrow host buffers for the lifetime `'a` of [`MappedMemory`], so the borrow checker
+//! guarantees that host buffers are neither modified nor deallocated while mapped, and that the
+//! host can't observe partially written output until [`MappedMemory`] is dropped or
+//! [`MappedMemory::into_inner()`] is called. The only `unsafe` code reads and writes basic integers
+//! from and to bounds-checked subslices of mappings.
+//!
+//! Mappings take precedence over the underlying memory and never overlap with each other. Each
+//! access must be fully contained within a single mapping, accesses that cross the boundary of a
+//! mapping are treated as out-of-bounds even if the remaining bytes are backed by another mapping
+//! or the underlying memory. Writes to read-only mappings are treated as out-of-bounds writes.
+//!
+//! Mappings are not executable from the point of view of the memory itself, but instruction
+//! fetchers read instructions through [`VirtualMemory`] as well, so it is up to the instruction
+//! fetcher to restrict execution to a specific region of memory.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{BasicInt, VirtualMemory, VirtualMemoryError};
+use core::hint::cold_path;
+
+/// Errors for [`MappedMemory::map_ro()`] and [`MappedMemory::map_rw()`]
+#[derive(Debug, thiserror::Error)]
+pub enum MapRegionError {
+    /// Too many mappings
+    #[error("Too many mappings, max is {max}")]
+    TooManyMappings {
+        /// Max number of mappings
+        max: usize,
+    },
+    /// Mapping of an empty buffer
+    #[error("Mapping of an empty buffer at address {address}")]
+    Empty {
+        /// Address of the mapping
+        address: u64,
+    },
+    /// Mapping doesn't fit into the address space
+    #[error("Mapping at address {address} with size {size} doesn't fit into the address space")]
+    AddressSpaceOverflow {
+        /// Address of the mapping
+        address: u64,
+        /// Size of the mapping
+        size: u64,
+    },
+    /// Mapping overlaps with an existing mapping
+    #[error("Mapping at address {address} overlaps with an existing mapping at {existing}")]
+    Overlap {
+        /// Address of the mapping
+        address: u64,
+        /// Address of the existing mapping
+        existing: u64,
+    },
+}
+
+#[derive(Debug)]
+enum MappingData<'a> {
+    ReadOnly(&'a [u8]),
+    ReadWrite(&'a mut [u8]),
+}
+
+impl MappingData<'_> {
+    #[inline(always)]
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::ReadOnly(data) => data,
+            Self::ReadWrite(data) => data,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Mapping<'a> {
+    address: u64,
+    data: MappingData<'a>,
+}
+
+impl Mapping<'_> {
+    /// Returns offset within the mapping if the `address` is within it
+    #[inline(always)]
+    fn offset(&self, address: u64) -> Option<usize> {
+        let offset = address.checked_sub(self.address)?;
+        (offset < self.data.as_slice().len() as u64).then_some(offset as usize)
+    }
+}
+
+/// Virtual memory with up to `MAX_MAPPINGS` host buffers mapped into guest address space.
+///
+/// See [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct MappedMemory<'a, Memory, const MAX_MAPPINGS: usize> {
+    memory: Memory,
+    mappings: [Option<Mapping<'a>>; MAX_MAPPINGS],
+    num_mappings: usize,
+}
+
+impl<Memory, const MAX_MAPPINGS: usize> VirtualMemory for MappedMemory<'_, Memory, MAX_MAPPINGS>
+where
+    Memory: VirtualMemory,
+{
+    #[inline(always)]
+    fn read<T>(&self, address: u64) -> Result<T, VirtualMemoryError>
+    where
+        T: BasicInt,
+    {
+        let Some((mapping, offset)) = self.find(address) else {
+            return self.memory.read(address);
+        };
+
+        let Some(bytes) = mapping
+            .data
+            .as_slice()
+            .get(offset..)
+            .and_then(|data| data.get(..size_of::<T>()))
+        else {
+            cold_path();
+            return Err(VirtualMemoryError::OutOfBoundsRead { address });
+        };
+
+        // SAFETY: Only reading basic integers from initialized memory of sufficient size
+        Ok(unsafe { bytes.as_ptr().cast::<T>().read_unaligned() })
+    }
+
+    #[inline(always)]
+    unsafe fn read_unchecked<T>(&self, address: u64) -> T
+    where
+        T: BasicInt,
+    {
+        let Some((mapping, offset)) = self.find(address) else {
+            // SAFETY: Guaranteed by function contract
+            return unsafe { self.memory.read_unchecked(address) };
+        };
+
+        // SAFETY: Guaranteed by function contract
+        unsafe {
+            mapping
+                .data
+                .as_slice()
+                .as_ptr()
+                .byte_add(offset)
+                .cast::<T>()
+                .read_unaligned()
+        }
+    }
+
+    fn read_slice(&self, address: u64, len: u32) -> Result<&[u8], VirtualMemoryError> {
+        let Some((mapping, offset)) = self.find(address) else {
+            return self.memory.read_slice(address, len);
+        };
+
+        mapping
+            .data
+            .as_slice()
+            .get(offset..)
+            .and_then(|data| data.get(..len as usize))
+            .ok_or(VirtualMemoryError::OutOfBoundsRead { address })
+    }
+
+    fn read_slice_up_to(&self, address: u64, len: u32) -> &[u8] {
+        let Some((mapping, offset)) = self.find(address) else {
+            return self.memory.read_slice_up_to(address, len);
+        };
+
+        let remaining = mapping.data.as_slice().get(offset..).unwrap_or_default();
+        remaining.get(..len as usize).unwrap_or(remaining)
+    }
+
+    #[inline(always)]
+    fn write<T>(&mut self, address: u64, value: T) -> Result<(), VirtualMemoryError>
+    where
+        T: BasicInt,
+    {
+        let Some((data, offset)) = self.find_mut(address) else {
+            return self.memory.write(address, value);
+        };
+
+        let Some(bytes) = data
+            .and_then(|data| data.get_mut(offset..))
+            .and_then(|data| data.get_mut(..size_of::<T>()))
+        else {
+            cold_path();
+            return Err(VirtualMemoryError::OutOfBoundsWrite { address });
+        };
+
+        // SAFETY: Only writing basic integers to initialized memory of sufficient size
+        unsafe {
+            bytes.as_mut_ptr().cast::<T>().write_unaligned(value);
+        }
+
+        Ok(())
+    }
+
+    fn write_slice(&mut self, address: u64, data: &[u8]) -> Result<(), VirtualMemoryError> {
+        let Some((target_data, offset)) = self.find_mut(address) else {
+            return self.memory.write_slice(address, data);
+        };
+
+        let Some(target_data) = target_data
+            .and_then(|target_data| target_data.get_mut(offset..))
+            .and_then(|target_data| target_data.get_mut(..data.len()))
+        else {
+            cold_path();
+            return Err(VirtualMemoryError::OutOfBoundsWrite { address });
+        };
+
+        target_data.copy_from_slice(data);
+
+        Ok(())
+    }
+}
+
+impl<'a, Memory, const MAX_MAPPINGS: usize> MappedMemory<'a, Memory, MAX_MAPPINGS> {
+    /// Create a new instance without any mappings on top of the provided memory
+    #[inline(always)]
+    pub fn new(memory: Memory) -> Self {
+        Self {
+            memory,
+            mappings: [const { None }; _],
+            num_mappings: 0,
+        }
+    }
+
+    /// Map `data` read-only at `address`
+    pub fn map_ro(&mut self, address: u64, data: &'a [u8]) -> Result<(), MapRegionError> {
+        self.map(address, MappingData::ReadOnly(data))
+    }
+
+    /// Map `data` read-write at `address`, typically used as an output region.
+    ///
+    /// Data written by the guest is visible in `data` once [`MappedMemory`] is dropped or
+    /// [`MappedMemory::into_inner()`] is called.
+    pub fn map_rw(&mut self, address: u64, data: &'a mut [u8]) -> Result<(), MapRegionError> {
+        self.map(address, MappingData::ReadWrite(data))
+    }
+
+    /// Underlying memory
+    #[inline(always)]
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    /// Mutable underlying memory.
+    ///
+    /// This is primarily useful for setting up the program and should not be used beyond that.
+    #[inline(always)]
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+
+    /// Remove all mappings and return the underlying memory
+    #[inline(always)]
+    pub fn into_inner(self) -> Memory {
+        self.memory
+    }
+
+    fn map(&mut self, address: u64, data: MappingData<'a>) -> Result<(), MapRegionError> {
+        let size = data.as_slice().len() as u64;
+        if size == 0 {
+            return Err(MapRegionError::Empty { address });
+        }
+        let Some(last_address) = address.checked_add(size - 1) else {
+            return Err(MapRegionError::AddressSpaceOverflow { address, size });
+        };
+
+        for mapping in self.mappings[..self.num_mappings].iter().flatten() {
+            let existing_last_address =
+                mapping.address + (mapping.data.as_slice().len() as u64 - 1);
+            if address <= existing_last_address && mapping.address <= last_address {
+                return Err(MapRegionError::Overlap {
+                    address,
+                    existing: mapping.address,
+                });
+            }
+        }
+
+        let Some(slot) = self.mappings.get_mut(self.num_mappings) else {
+            return Err(MapRegionError::TooManyMappings { max: MAX_MAPPINGS });
+        };
+        *slot = Some(Mapping { address, data });
+        self.num_mappings += 1;
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn find(&self, address: u64) -> Option<(&Mapping<'a>, usize)> {
+        self.mappings[..self.num_mappings]
+            .iter()
+            .flatten()
+            .find_map(|mapping| Some((mapping, mapping.offset(address)?)))
+    }
+
+    /// Returns `None` if the address is not mapped, `Some((None, offset))` for read-only mappings
+    #[inline(always)]
+    fn find_mut(&mut self, address: u64) -> Option<(Option<&mut [u8]>, usize)> {
+        self.mappings[..self.num_mappings]
+            .iter_mut()
+            .flatten()
+            .find_map(|mapping| {
+                let offset = mapping.offset(address)?;
+                let data = match &mut mapping.data {
+                    MappingData::ReadOnly(_) => None,
+                    MappingData::ReadWrite(data) => Some(&mut **data),
+                };
+                Some((data, offset))
+            })
+    }
+}
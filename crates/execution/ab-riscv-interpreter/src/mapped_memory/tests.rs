@@ -0,0 +1,90 @@
+use crate::VirtualMemory;
+use crate::basic::BasicMemory;
+use crate::mapped_memory::{MapRegionError, MappedMemory};
+
+const BASE_ADDR: u64 = 0x1000;
+const SIZE: usize = 0x100;
+const RO_ADDR: u64 = 0x2000;
+const RW_ADDR: u64 = 0x3000;
+
+#[test]
+fn test_mapped_memory_read_write() {
+    let ro_data = [1u8, 2, 3, 4, 5, 6, 7, 8];
+    let mut rw_data = [0u8; 8];
+
+    {
+        let mut memory = MappedMemory::<'_, _, 2>::new(BasicMemory::<BASE_ADDR, SIZE>::default());
+        memory.map_ro(RO_ADDR, &ro_data).unwrap();
+        memory.map_rw(RW_ADDR, &mut rw_data).unwrap();
+
+        // Underlying memory is still accessible
+        memory.write(BASE_ADDR, 0xdead_beef_u32).unwrap();
+        assert_eq!(memory.read::<u32>(BASE_ADDR).unwrap(), 0xdead_beef);
+
+        // Read-only mapping
+        assert_eq!(
+            memory.read::<u32>(RO_ADDR + 1).unwrap(),
+            u32::from_le_bytes([2, 3, 4, 5])
+        );
+        // SAFETY: In-bounds read
+        assert_eq!(unsafe { memory.read_unchecked::<u8>(RO_ADDR + 7) }, 8);
+        assert_eq!(memory.read_slice(RO_ADDR + 2, 3).unwrap(), &[3, 4, 5]);
+        assert_eq!(memory.read_slice_up_to(RO_ADDR + 6, 10), &[7, 8]);
+        assert!(memory.write(RO_ADDR, 0_u8).is_err());
+        assert!(memory.write_slice(RO_ADDR, &[0]).is_err());
+
+        // Read-write mapping
+        memory.write(RW_ADDR, 0x0102_0304_u32).unwrap();
+        memory.write_slice(RW_ADDR + 6, &[9, 10]).unwrap();
+        assert_eq!(
+            memory.read_slice(RW_ADDR, 8).unwrap(),
+            &[4, 3, 2, 1, 0, 0, 9, 10]
+        );
+
+        // Accesses crossing the boundary of a mapping
+        memory.read::<u32>(RO_ADDR + 6).unwrap_err();
+        memory.read_slice(RO_ADDR, 9).unwrap_err();
+        assert!(memory.write::<u32>(RW_ADDR + 6, 0).is_err());
+        assert!(memory.write_slice(RW_ADDR + 7, &[0, 0]).is_err());
+
+        // Not mapped and not backed by the underlying memory
+        memory.read::<u8>(RO_ADDR + 8).unwrap_err();
+        memory.read::<u8>(RW_ADDR - 1).unwrap_err();
+    }
+
+    assert_eq!(rw_data, [4, 3, 2, 1, 0, 0, 9, 10]);
+}
+
+#[test]
+fn test_mapped_memory_map_errors() {
+    let data = [0u8; 8];
+    let mut memory = MappedMemory::<'_, _, 2>::new(BasicMemory::<BASE_ADDR, SIZE>::default());
+
+    assert!(matches!(
+        memory.map_ro(RO_ADDR, &[]),
+        Err(MapRegionError::Empty { address: RO_ADDR })
+    ));
+    assert!(matches!(
+        memory.map_ro(u64::MAX - 6, &data),
+        Err(MapRegionError::AddressSpaceOverflow { .. })
+    ));
+    // The very end of the address space
+    memory.map_ro(u64::MAX - 7, &data).unwrap();
+    assert_eq!(memory.read::<u8>(u64::MAX).unwrap(), 0);
+
+    memory.map_ro(RO_ADDR, &data).unwrap();
+    for address in [RO_ADDR - 7, RO_ADDR, RO_ADDR + 7] {
+        assert!(matches!(
+            memory.map_ro(address, &data),
+            Err(MapRegionError::Overlap {
+                existing: RO_ADDR,
+                ..
+            })
+        ));
+    }
+
+    assert!(matches!(
+        memory.map_ro(RO_ADDR + 8, &data),
+        Err(MapRegionError::TooManyMappings { max: 2 })
+    ));
+}
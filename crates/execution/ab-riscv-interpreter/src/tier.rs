@@ -0,0 +1,322 @@
+//! Execution tiers.
+//!
+//! The interpreter tier ([`ExecutionTier::Interpreter`]) is the baseline: every instruction is read
+//! from memory and decoded right before execution using [`BasicInstructionFetcher`].
+//!
+//! The recompiled tier ([`ExecutionTier::Recompiled`]) translates a region of code ahead of time
+//! into a table of decoded instructions ([`RecompiledCode`]), which can be cached and reused across
+//! many executions of the same code. [`RecompiledInstructionFetcher`] then fetches instructions
+//! from that table without reading memory or decoding anything. Whenever the table doesn't have a
+//! decoded instruction for the program counter (outside the recompiled region, undecodable
+//! instruction, etc.), it falls back to the interpreter tier, so both tiers produce identical
+//! results, including errors. This is checked by differential tests that run the same programs on
+//! both tiers.
+//!
+//! Recompilation costs time and memory proportional to the size of the code, so it is up to the
+//! node to decide whether it can afford it. [`TieredInstructionFetcher`] allows selecting the tier
+//! at runtime without making the rest of the interpreter state generic over it.
+//!
+//! The recompiled tier assumes that the recompiled region of memory is not modified after
+//! recompilation. It is up to the memory implementation to enforce this, for example, by making the
+//! region read-only.
+
+#[cfg(test)]
+mod tests;
+
+use crate::basic::BasicInstructionFetcher;
+use crate::{
+    Address, CustomErrorPlaceholder, ExecutionError, FetchInstructionResult, InstructionFetcher,
+    ProgramCounter, ProgramCounterError, VirtualMemory,
+};
+use ab_riscv_primitives::prelude::*;
+use core::hint::cold_path;
+use core::ops::ControlFlow;
+
+/// Execution tier
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum ExecutionTier {
+    /// Instructions are decoded right before execution
+    #[default]
+    Interpreter,
+    /// Instructions are decoded ahead of time
+    Recompiled,
+}
+
+/// Errors for [`RecompiledCode::new()`]
+#[derive(Debug, thiserror::Error)]
+pub enum RecompilationError {
+    /// Base address is not aligned to instruction alignment
+    #[error("Base address {base_addr:#x} is not aligned to {alignment} bytes")]
+    UnalignedBaseAddress {
+        /// Base address
+        base_addr: u64,
+        /// Instruction alignment
+        alignment: u8,
+    },
+    /// Code is too large
+    #[error("Code size {size} bytes is too large, max is {max_size} bytes")]
+    CodeTooLarge {
+        /// Code size
+        size: u64,
+        /// Max code size
+        max_size: u64,
+    },
+}
+
+/// Code recompiled ahead of time into a table of decoded instructions.
+///
+/// There is one entry in the table for every `I::alignment()` bytes of code, `SLOTS` is the max
+/// number of entries. Similarly to [`BasicMemory`], it is stored inline, so for larger code it'll
+/// need to be boxed.
+///
+/// [`BasicMemory`]: crate::basic::BasicMemory
+#[derive(Debug, Clone)]
+pub struct RecompiledCode<I, const SLOTS: usize>
+where
+    I: Instruction,
+{
+    base_addr: u64,
+    instructions: [Option<I>; SLOTS],
+}
+
+impl<I, const SLOTS: usize> RecompiledCode<I, SLOTS>
+where
+    I: Instruction,
+{
+    /// Recompile `size` bytes of code located in `memory` at `base_addr`.
+    ///
+    /// Instructions are read the same way as [`BasicInstructionFetcher`] does it, so the contents
+    /// of `memory` in this region must not change afterward.
+    pub fn new<Memory>(
+        memory: &Memory,
+        base_addr: u64,
+        size: u64,
+    ) -> Result<Self, RecompilationError>
+    where
+        Memory: VirtualMemory,
+    {
+        let alignment = I::alignment();
+
+        if !base_addr.is_multiple_of(u64::from(alignment)) {
+            return Err(RecompilationError::UnalignedBaseAddress {
+                base_addr,
+                alignment,
+            });
+        }
+
+        let max_size = SLOTS as u64 * u64::from(alignment);
+        if size > max_size {
+            return Err(RecompilationError::CodeTooLarge { size, max_size });
+        }
+
+        let mut instructions = [None; SLOTS];
+        for (offset, instruction) in (0..size)
+            .step_by(usize::from(alignment))
+            .zip(&mut instructions)
+        {
+            // Instructions that can't be read as a full 32-bit word or can't be decoded are left
+            // for the interpreter tier to handle
+            *instruction = memory
+                .read::<u32>(base_addr + offset)
+                .ok()
+                .and_then(I::try_decode);
+        }
+
+        Ok(Self {
+            base_addr,
+            instructions,
+        })
+    }
+
+    /// Get a decoded instruction at a specified address, if available
+    #[inline(always)]
+    fn get(&self, address: u64) -> Option<I> {
+        let offset = address.checked_sub(self.base_addr)?;
+        let alignment = u64::from(I::alignment());
+
+        if !offset.is_multiple_of(alignment) {
+            cold_path();
+            return None;
+        }
+
+        *self
+            .instructions
+            .get(usize::try_from(offset / alignment).ok()?)?
+    }
+}
+
+/// Instruction fetcher of the recompiled tier.
+///
+/// Fetches instructions from [`RecompiledCode`] and falls back to [`BasicInstructionFetcher`] for
+/// addresses that don't have a decoded instruction.
+#[derive(Debug, Copy, Clone)]
+pub struct RecompiledInstructionFetcher<
+    'a,
+    I,
+    const SLOTS: usize,
+    CustomError = CustomErrorPlaceholder,
+> where
+    I: Instruction,
+{
+    code: &'a RecompiledCode<I, SLOTS>,
+    fallback: BasicInstructionFetcher<I, CustomError>,
+}
+
+impl<I, Memory, const SLOTS: usize, CustomError> ProgramCounter<Address<I>, Memory, CustomError>
+    for RecompiledInstructionFetcher<'_, I, SLOTS, CustomError>
+where
+    I: Instruction,
+    Memory: VirtualMemory,
+{
+    #[inline(always)]
+    fn get_pc(&self) -> Address<I> {
+        ProgramCounter::<_, Memory, CustomError>::get_pc(&self.fallback)
+    }
+
+    #[inline]
+    fn set_pc(
+        &mut self,
+        memory: &Memory,
+        pc: Address<I>,
+    ) -> Result<ControlFlow<()>, ProgramCounterError<Address<I>, CustomError>> {
+        self.fallback.set_pc(memory, pc)
+    }
+}
+
+impl<I, Memory, const SLOTS: usize, CustomError> InstructionFetcher<I, Memory, CustomError>
+    for RecompiledInstructionFetcher<'_, I, SLOTS, CustomError>
+where
+    I: Instruction,
+    Memory: VirtualMemory,
+{
+    #[inline]
+    fn fetch_instruction(
+        &mut self,
+        memory: &Memory,
+    ) -> Result<FetchInstructionResult<I>, ExecutionError<Address<I>, CustomError>> {
+        let pc = ProgramCounter::<_, Memory, CustomError>::get_pc(&self.fallback);
+
+        if let Some(instruction) = self.code.get(pc.as_u64()) {
+            self.fallback.advance_pc(instruction.size());
+            return Ok(FetchInstructionResult::Instruction(instruction));
+        }
+
+        cold_path();
+        self.fallback.fetch_instruction(memory)
+    }
+}
+
+impl<'a, I, const SLOTS: usize, CustomError> RecompiledInstructionFetcher<'a, I, SLOTS, CustomError>
+where
+    I: Instruction,
+{
+    /// Create a new instance.
+    ///
+    /// `return_trap_address` is the address at which the interpreter will stop execution
+    /// (gracefully).
+    #[inline(always)]
+    pub fn new(
+        code: &'a RecompiledCode<I, SLOTS>,
+        return_trap_address: Address<I>,
+        pc: Address<I>,
+    ) -> Self {
+        Self {
+            code,
+            fallback: BasicInstructionFetcher::new(return_trap_address, pc),
+        }
+    }
+}
+
+/// Instruction fetcher with execution tier selected at runtime
+#[derive(Debug, Copy, Clone)]
+pub enum TieredInstructionFetcher<'a, I, const SLOTS: usize, CustomError = CustomErrorPlaceholder>
+where
+    I: Instruction,
+{
+    /// Interpreter tier
+    Interpreter(BasicInstructionFetcher<I, CustomError>),
+    /// Recompiled tier
+    Recompiled(RecompiledInstructionFetcher<'a, I, SLOTS, CustomError>),
+}
+
+impl<I, Memory, const SLOTS: usize, CustomError> ProgramCounter<Address<I>, Memory, CustomError>
+    for TieredInstructionFetcher<'_, I, SLOTS, CustomError>
+where
+    I: Instruction,
+    Memory: VirtualMemory,
+{
+    #[inline(always)]
+    fn get_pc(&self) -> Address<I> {
+        match self {
+            Self::Interpreter(fetcher) => ProgramCounter::<_, Memory, CustomError>::get_pc(fetcher),
+            Self::Recompiled(fetcher) => ProgramCounter::<_, Memory, CustomError>::get_pc(fetcher),
+        }
+    }
+
+    #[inline]
+    fn set_pc(
+        &mut self,
+        memory: &Memory,
+        pc: Address<I>,
+    ) -> Result<ControlFlow<()>, ProgramCounterError<Address<I>, CustomError>> {
+        match self {
+            Self::Interpreter(fetcher) => fetcher.set_pc(memory, pc),
+            Self::Recompiled(fetcher) => fetcher.set_pc(memory, pc),
+        }
+    }
+}
+
+impl<I, Memory, const SLOTS: usize, CustomError> InstructionFetcher<I, Memory, CustomError>
+    for TieredInstructionFetcher<'_, I, SLOTS, CustomError>
+where
+    I: Instruction,
+    Memory: VirtualMemory,
+{
+    #[inline]
+    fn fetch_instruction(
+        &mut self,
+        memory: &Memory,
+    ) -> Result<FetchInstructionResult<I>, ExecutionError<Address<I>, CustomError>> {
+        match self {
+            Self::Interpreter(fetcher) => fetcher.fetch_instruction(memory),
+            Self::Recompiled(fetcher) => fetcher.fetch_instruction(memory),
+        }
+    }
+}
+
+impl<'a, I, const SLOTS: usize, CustomError> TieredInstructionFetcher<'a, I, SLOTS, CustomError>
+where
+    I: Instruction,
+{
+    /// Create a new instance.
+    ///
+    /// Recompiled tier is used when `recompiled_code` is provided, otherwise the interpreter tier
+    /// is used.
+    ///
+    /// `return_trap_address` is the address at which the interpreter will stop execution
+    /// (gracefully).
+    #[inline(always)]
+    pub fn new(
+        recompiled_code: Option<&'a RecompiledCode<I, SLOTS>>,
+        return_trap_address: Address<I>,
+        pc: Address<I>,
+    ) -> Self {
+        match recompiled_code {
+            Some(code) => Self::Recompiled(RecompiledInstructionFetcher::new(
+                code,
+                return_trap_address,
+                pc,
+            )),
+            None => Self::Interpreter(BasicInstructionFetcher::new(return_trap_address, pc)),
+        }
+    }
+
+    /// Execution tier used by this instruction fetcher
+    #[inline(always)]
+    pub fn tier(&self) -> ExecutionTier {
+        match self {
+            Self::Interpreter(_) => ExecutionTier::Interpreter,
+            Self::Recompiled(_) => ExecutionTier::Recompiled,
+        }
+    }
+}
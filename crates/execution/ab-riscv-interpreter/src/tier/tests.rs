@@ -0,0 +1,272 @@
+use crate::basic::{
+    BasicInterpreterState, BasicMemory, BasicRegisters, IllegalEcallSystemInstructionHandler,
+};
+use crate::tier::{ExecutionTier, RecompilationError, RecompiledCode, TieredInstructionFetcher};
+use crate::{ExecutionError, ProgramCounterError, RegisterFile, VirtualMemory};
+use ab_riscv_primitives::prelude::*;
+
+const BASE_ADDR: u64 = 0x1000;
+const MEMORY_SIZE: usize = 4096;
+const TRAP_ADDRESS: u64 = 0;
+const SLOTS: usize = 256;
+/// `jalr zero, 0(ra)`
+const RET: u32 = 0x0000_8067;
+
+type TestInstruction = Rv64Instruction<Reg<u64>>;
+type TestMemory = BasicMemory<BASE_ADDR, MEMORY_SIZE>;
+type TestRegisters = BasicRegisters<Reg<u64>>;
+
+fn memory(program: &[u32]) -> TestMemory {
+    let mut memory = TestMemory::default();
+    for (address, &instruction) in (BASE_ADDR..).step_by(size_of::<u32>()).zip(program) {
+        memory.write(address, instruction).unwrap();
+    }
+    memory
+}
+
+/// Run the program on a specified tier, returning the final registers, memory, the tier that was
+/// used and the result
+fn run(
+    memory: &TestMemory,
+    regs: TestRegisters,
+    recompiled_code: Option<&RecompiledCode<TestInstruction, SLOTS>>,
+) -> (
+    TestRegisters,
+    TestMemory,
+    ExecutionTier,
+    Result<(), ExecutionError<u64>>,
+) {
+    let mut state = BasicInterpreterState {
+        regs,
+        ext_state: (),
+        memory: *memory,
+        instruction_fetcher: TieredInstructionFetcher::new(
+            recompiled_code,
+            TRAP_ADDRESS,
+            BASE_ADDR,
+        ),
+        system_instruction_handler: IllegalEcallSystemInstructionHandler,
+    };
+    let result = state.execute::<TestInstruction>();
+    (
+        state.regs,
+        state.memory,
+        state.instruction_fetcher.tier(),
+        result,
+    )
+}
+
+/// Run the program on both tiers and check that the final state is identical, returning results
+/// of both tiers.
+///
+/// `recompiled_size` is the size of code to recompile, the rest of the memory is left for the
+/// interpreter fallback.
+fn run_both(
+    program: &[u32],
+    regs: TestRegisters,
+    recompiled_size: u64,
+) -> [Result<(), ExecutionError<u64>>; 2] {
+    let memory = memory(program);
+    let recompiled_code = RecompiledCode::new(&memory, BASE_ADDR, recompiled_size).unwrap();
+
+    let (interpreter_regs, interpreter_memory, interpreter_tier, interpreter_result) =
+        run(&memory, regs, None);
+    let (recompiled_regs, recompiled_memory, recompiled_tier, recompiled_result) =
+        run(&memory, regs, Some(&recompiled_code));
+
+    assert_eq!(interpreter_tier, ExecutionTier::Interpreter);
+    assert_eq!(recompiled_tier, ExecutionTier::Recompiled);
+
+    for reg in (0..32).map(|bits| Reg::from_bits(bits).unwrap()) {
+        assert_eq!(
+            interpreter_regs.read(reg),
+            recompiled_regs.read(reg),
+            "Register {reg} differs"
+        );
+    }
+    assert_eq!(
+        interpreter_memory
+            .read_slice(BASE_ADDR, MEMORY_SIZE as u32)
+            .unwrap(),
+        recompiled_memory
+            .read_slice(BASE_ADDR, MEMORY_SIZE as u32)
+            .unwrap()
+    );
+
+    [interpreter_result, recompiled_result]
+}
+
+fn regs() -> TestRegisters {
+    let mut regs = BasicRegisters::default();
+    regs.write(Reg::Ra, TRAP_ADDRESS);
+    regs.write(Reg::Sp, BASE_ADDR + MEMORY_SIZE as u64 - 16);
+    regs
+}
+
+#[test]
+fn loop_and_memory_access() {
+    let program = [
+        // addi a0, zero, 0
+        0x0000_0513,
+        // addi a1, zero, 10
+        0x00a0_0593,
+        // add a0, a0, a1
+        0x00b5_0533,
+        // addi a1, a1, -1
+        0xfff5_8593,
+        // bne a1, zero, -8
+        0xfe05_9ce3,
+        // sd a0, 0(sp)
+        0x00a1_3023,
+        // ld a2, 0(sp)
+        0x0001_3603,
+        RET,
+    ];
+    let code_size = size_of_val(&program) as u64;
+
+    for result in run_both(&program, regs(), code_size) {
+        result.unwrap();
+    }
+
+    let (regs, _memory, _tier, result) = run(&memory(&program), regs(), None);
+    result.unwrap();
+    assert_eq!(regs.read(Reg::A0), 55);
+    assert_eq!(regs.read(Reg::A2), 55);
+}
+
+#[test]
+fn fallback_outside_of_recompiled_code() {
+    let program = [
+        // jal zero, +16
+        0x0100_006f,
+        // Unreachable, but recompiled
+        0,
+        0,
+        0,
+        // addi a2, a2, 1, outside the recompiled region
+        0x0016_0613,
+        RET,
+    ];
+
+    for result in run_both(&program, regs(), 4 * size_of::<u32>() as u64) {
+        result.unwrap();
+    }
+
+    let (regs, _memory, _tier, result) = run(&memory(&program), regs(), None);
+    result.unwrap();
+    assert_eq!(regs.read(Reg::A2), 1);
+}
+
+#[test]
+fn illegal_instruction() {
+    // addi a2, a2, 1 followed by an illegal instruction
+    let program = [0x0016_0613, 0];
+    let illegal_address = BASE_ADDR + size_of::<u32>() as u64;
+
+    for result in run_both(&program, regs(), size_of_val(&program) as u64) {
+        assert!(matches!(
+            result,
+            Err(ExecutionError::IllegalInstruction { address }) if address == illegal_address
+        ));
+    }
+}
+
+#[test]
+fn unaligned_jump() {
+    // jalr zero, 2(ra)
+    let program = [0x0020_8067];
+    let mut regs = regs();
+    regs.write(Reg::Ra, BASE_ADDR);
+
+    for result in run_both(&program, regs, size_of_val(&program) as u64) {
+        assert!(matches!(
+            result,
+            Err(ExecutionError::ProgramCounter(
+                ProgramCounterError::UnalignedInstruction { address }
+            )) if address == BASE_ADDR + 2
+        ));
+    }
+}
+
+#[test]
+fn random_arithmetic() {
+    /// `(funct7, funct3, opcode)` of R-type instructions
+    const R_TYPE: [(u32, u32, u32); 15] = [
+        // add, sub, sll, slt, sltu, xor, srl, sra, or, and
+        (0x00, 0b000, 0x33),
+        (0x20, 0b000, 0x33),
+        (0x00, 0b001, 0x33),
+        (0x00, 0b010, 0x33),
+        (0x00, 0b011, 0x33),
+        (0x00, 0b100, 0x33),
+        (0x00, 0b101, 0x33),
+        (0x20, 0b101, 0x33),
+        (0x00, 0b110, 0x33),
+        (0x00, 0b111, 0x33),
+        // addw, subw, sllw, srlw, sraw
+        (0x00, 0b000, 0x3b),
+        (0x20, 0b000, 0x3b),
+        (0x00, 0b001, 0x3b),
+        (0x00, 0b101, 0x3b),
+        (0x20, 0b101, 0x3b),
+    ];
+
+    // Xorshift with a fixed seed for reproducibility
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..100 {
+        let mut regs = regs();
+        // Don't touch `zero`, `ra` and `sp`
+        for bits in 3..32 {
+            regs.write(Reg::from_bits(bits).unwrap(), next());
+        }
+
+        let mut program = [0; SLOTS];
+        let (last, instructions) = program.split_last_mut().unwrap();
+        *last = RET;
+        for instruction in instructions {
+            let random = next();
+            let rd = (random % 29 + 3) as u32;
+            let rs1 = (random >> 8) as u32 % 32;
+            let rs2 = (random >> 16) as u32 % 32;
+            *instruction = if (random >> 24).is_multiple_of(4) {
+                // addi rd, rs1, imm
+                let imm = (random >> 32) as u32 & 0xfff;
+                (imm << 20) | (rs1 << 15) | (rd << 7) | 0x13
+            } else {
+                let (funct7, funct3, opcode) = R_TYPE[(random >> 32) as usize % R_TYPE.len()];
+                (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+            };
+        }
+
+        for result in run_both(&program, regs, size_of_val(&program) as u64) {
+            result.unwrap();
+        }
+    }
+}
+
+#[test]
+fn recompilation_errors() {
+    let memory = memory(&[RET]);
+
+    assert!(matches!(
+        RecompiledCode::<TestInstruction, SLOTS>::new(&memory, BASE_ADDR + 2, 4),
+        Err(RecompilationError::UnalignedBaseAddress {
+            base_addr,
+            alignment: 4
+        }) if base_addr == BASE_ADDR + 2
+    ));
+    assert!(matches!(
+        RecompiledCode::<TestInstruction, SLOTS>::new(&memory, BASE_ADDR, SLOTS as u64 * 4 + 1),
+        Err(RecompilationError::CodeTooLarge {
+            size,
+            max_size,
+        }) if size == SLOTS as u64 * 4 + 1 && max_size == SLOTS as u64 * 4
+    ));
+}
@@ -1,7 +1,8 @@
 use ab_core_primitives::block::{BlockNumber, BlockRoot};
-use ab_core_primitives::transaction::TransactionHash;
 use ab_core_primitives::transaction::owned::OwnedTransaction;
+use ab_core_primitives::transaction::{Transaction, TransactionHash};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
 use std::num::{NonZeroU8, NonZeroU64, NonZeroUsize};
 
 /// Transaction pool limits
@@ -13,6 +14,21 @@ pub struct TransactionPoolLimits {
     pub size: NonZeroUsize,
 }
 
+/// Transaction pool status
+#[derive(Debug, Copy, Clone)]
+pub struct TransactionPoolStatus {
+    /// Number of transactions
+    pub count: usize,
+    /// Total size of all transactions
+    pub size: usize,
+    /// Number of transactions that were not authorized yet
+    pub new: usize,
+    /// Number of authorized transactions
+    pub authorized: usize,
+    /// Transaction pool limits
+    pub limits: TransactionPoolLimits,
+}
+
 #[derive(Debug)]
 pub struct TransactionAuthorizedDetails {
     /// Block number at which transaction was authorized
@@ -52,6 +68,13 @@ pub enum TransactionAddError {
     /// Total size too large
     #[error("Total size too large")]
     TotalSizeTooLarge,
+    /// Transaction failed prevalidation against the state of the best block
+    #[error("Transaction failed prevalidation: {error}")]
+    PrevalidationFailed {
+        /// Prevalidation error
+        #[source]
+        error: Box<dyn Error + Send + Sync + 'static>,
+    },
 }
 
 #[derive(Debug)]
@@ -73,6 +96,8 @@ pub struct TransactionPool {
     by_block_root: HashMap<BlockRoot, BlockRootDetails>,
     // TODO: Optimize with an oldest block + `Vec<BlockHash>` instead
     by_block_number: HashMap<BlockNumber, BlockRoot>,
+    /// The best block added with [`Self::add_best_block()`]
+    best_block: Option<(BlockNumber, BlockRoot)>,
     pruning_depth: NonZeroU64,
     authorization_history_depth: NonZeroU8,
     limits: TransactionPoolLimits,
@@ -98,18 +123,29 @@ impl TransactionPool {
             total_size: 0,
             by_block_root: HashMap::default(),
             by_block_number: HashMap::default(),
+            best_block: None,
             pruning_depth,
             authorization_history_depth,
             limits,
         }
     }
 
-    /// Add new transaction to the pool
-    pub fn add(
+    /// Add new transaction to the pool.
+    ///
+    /// `prevalidate` is expected to do cheap prevalidation of the transaction against the state of
+    /// the best block without executing it (seal, nonce, fee balance, etc.), transaction is marked
+    /// as authorized at the best block if it succeeds. See [`Self::revalidate()`] for transactions
+    /// that are already in the pool.
+    pub fn add<F, E>(
         &mut self,
         tx_hash: TransactionHash,
         tx: OwnedTransaction,
-    ) -> Result<(), TransactionAddError> {
+        prevalidate: F,
+    ) -> Result<(), TransactionAddError>
+    where
+        F: FnOnce(Transaction<'_>) -> Result<(), E>,
+        E: Error + Send + Sync + 'static,
+    {
         if self.contains(&tx_hash) {
             return Err(TransactionAddError::AlreadyExists);
         }
@@ -128,6 +164,12 @@ impl TransactionPool {
             return Err(TransactionAddError::TotalSizeTooLarge);
         }
 
+        prevalidate(tx.transaction()).map_err(|error| {
+            TransactionAddError::PrevalidationFailed {
+                error: Box::new(error),
+            }
+        })?;
+
         self.total_size += tx_size;
         self.transactions.insert(
             tx_hash,
@@ -138,9 +180,52 @@ impl TransactionPool {
         );
         block_txs.txs.insert(tx_hash);
 
+        if let Some((block_number, block_root)) = self.best_block {
+            self.mark_authorized(&tx_hash, block_number, block_root);
+        }
+
         Ok(())
     }
 
+    /// Prevalidate all transactions in the pool against the state of the best block again.
+    ///
+    /// Expected to be called after every [`Self::add_best_block()`] with `prevalidate` that does
+    /// the same checks as in [`Self::add()`], but against the state of the new best block. This
+    /// keeps the pool free of transactions that can no longer be included in a block, like those
+    /// with an outdated nonce or whose fee can no longer be paid.
+    ///
+    /// Transactions that pass prevalidation are marked as authorized at the best block, the rest
+    /// are removed from the pool and returned together with corresponding errors.
+    pub fn revalidate<F, E>(&mut self, mut prevalidate: F) -> Vec<(TransactionHash, E)>
+    where
+        F: FnMut(Transaction<'_>) -> Result<(), E>,
+    {
+        let mut valid = Vec::with_capacity(self.transactions.len());
+        let mut invalid = Vec::new();
+        for (tx_hash, tx) in &self.transactions {
+            match prevalidate(tx.tx.transaction()) {
+                Ok(()) => {
+                    valid.push(*tx_hash);
+                }
+                Err(error) => {
+                    invalid.push((*tx_hash, error));
+                }
+            }
+        }
+
+        if let Some((block_number, block_root)) = self.best_block {
+            for tx_hash in &valid {
+                self.mark_authorized(tx_hash, block_number, block_root);
+            }
+        }
+
+        for (tx_hash, _error) in &invalid {
+            self.remove_single(tx_hash);
+        }
+
+        invalid
+    }
+
     /// Mark transaction as authorized as of a specific block.
     ///
     /// Returns `false` if transaction is unknown.
@@ -181,6 +266,28 @@ impl TransactionPool {
         self.transactions.contains_key(tx_hash)
     }
 
+    /// Get transaction by its hash
+    pub fn get(&self, tx_hash: &TransactionHash) -> Option<&PoolTransaction> {
+        self.transactions.get(tx_hash)
+    }
+
+    /// Current status of the transaction pool
+    pub fn status(&self) -> TransactionPoolStatus {
+        let new = self
+            .transactions
+            .values()
+            .filter(|tx| matches!(tx.state, TransactionState::New))
+            .count();
+
+        TransactionPoolStatus {
+            count: self.transactions.len(),
+            size: self.total_size,
+            new,
+            authorized: self.transactions.len() - new,
+            limits: self.limits,
+        }
+    }
+
     /// Get iterator over all transactions
     pub fn iter(
         &self,
@@ -194,22 +301,29 @@ impl TransactionPool {
         Txs: Iterator<Item = &'a TransactionHash>,
     {
         for tx_hash in tx_hashes {
-            self.remove_single_tx(tx_hash);
+            self.remove_single(tx_hash);
         }
     }
 
-    fn remove_single_tx(&mut self, tx_hash: &TransactionHash) {
-        if let Some(tx) = self.transactions.remove(tx_hash) {
-            self.total_size -= tx.tx.buffer().len() as usize;
+    /// Remove a single transaction from the pool.
+    ///
+    /// Returns `false` if transaction is unknown.
+    pub fn remove_single(&mut self, tx_hash: &TransactionHash) -> bool {
+        let Some(tx) = self.transactions.remove(tx_hash) else {
+            return false;
+        };
+
+        self.total_size -= tx.tx.buffer().len() as usize;
 
-            let block_root = &tx.tx.transaction().header.block_root;
-            if let Some(set) = self.by_block_root.get_mut(block_root) {
-                set.txs.remove(tx_hash);
-                if set.txs.is_empty() {
-                    self.by_block_root.remove(block_root);
-                }
+        let block_root = &tx.tx.transaction().header.block_root;
+        if let Some(set) = self.by_block_root.get_mut(block_root) {
+            set.txs.remove(tx_hash);
+            if set.txs.is_empty() {
+                self.by_block_root.remove(block_root);
             }
         }
+
+        true
     }
 
     /// Add the new best block.
@@ -218,7 +332,9 @@ impl TransactionPool {
     /// removed alongside all transactions. Blocks older than configured pruning depth will be
     /// removed automatically as well.
     ///
-    /// This allows accepting transactions created at specified block root.
+    /// This allows accepting transactions created at specified block root. Transactions already in
+    /// the pool are expected to be prevalidated against the new best block with
+    /// [`Self::revalidate()`] afterward.
     pub fn add_best_block(&mut self, block_number: BlockNumber, block_root: BlockRoot) {
         // Clean up old blocks or blocks that are at the same or higher block number
         let allowed_blocks =
@@ -249,6 +365,7 @@ impl TransactionPool {
             }
         }
 
+        self.best_block = Some((block_number, block_root));
         self.by_block_number.insert(block_number, block_root);
         self.by_block_root.insert(
             block_root,
@@ -0,0 +1,89 @@
+//! Error codes of the farmer RPC.
+//!
+//! Every error returned by the node has a stable numeric code, such that farmers can branch on
+//! codes instead of parsing error messages, which are meant for humans and may change at any
+//! time. Codes are never reused: once an error is removed, its code stays reserved.
+
+#[cfg(test)]
+mod tests;
+
+/// Error code of the farmer RPC, see module-level documentation for details
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum RpcErrorCode {
+    /// Solution was ignored
+    SolutionWasIgnored = 0,
+    /// Super segment headers length exceeded the limit
+    SuperSegmentHeadersLengthExceeded = 1,
+    /// Failed to recreate segment
+    FailedToRecreateSegment = 2,
+    /// Blocking task join error
+    BlockingTaskJoinError = 3,
+    /// Shard assignments length exceeded the limit
+    ShardAssignmentsLengthExceeded = 4,
+    /// Sector expirations length exceeded the limit
+    SectorExpirationsLengthExceeded = 5,
+    // Code 6 is reserved
+    /// Solution was submitted too late
+    SolutionTooLate = 7,
+    /// Too many subscriptions on a single connection
+    TooManySubscriptions = 8,
+    // Code 9 is reserved
+    /// Slot is unknown or too old
+    UnknownSlot = 10,
+    /// Archiver re-initialization failed
+    ArchiverReinitializationFailed = 11,
+    /// Requested number of items in a page exceeded the limit
+    PageItemsLimitExceeded = 12,
+    /// Invalid contract address
+    InvalidAddress = 13,
+    /// Invalid slot info filter
+    InvalidSlotInfoFilter = 14,
+}
+
+impl From<RpcErrorCode> for i32 {
+    #[inline(always)]
+    fn from(code: RpcErrorCode) -> Self {
+        code.code()
+    }
+}
+
+impl TryFrom<i32> for RpcErrorCode {
+    type Error = i32;
+
+    /// Returns the original code back if it is not known
+    #[inline]
+    fn try_from(code: i32) -> Result<Self, Self::Error> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|known| known.code() == code)
+            .ok_or(code)
+    }
+}
+
+impl RpcErrorCode {
+    /// All known error codes in increasing order
+    pub const ALL: &[Self] = &[
+        Self::SolutionWasIgnored,
+        Self::SuperSegmentHeadersLengthExceeded,
+        Self::FailedToRecreateSegment,
+        Self::BlockingTaskJoinError,
+        Self::ShardAssignmentsLengthExceeded,
+        Self::SectorExpirationsLengthExceeded,
+        Self::SolutionTooLate,
+        Self::TooManySubscriptions,
+        Self::UnknownSlot,
+        Self::ArchiverReinitializationFailed,
+        Self::PageItemsLimitExceeded,
+        Self::InvalidAddress,
+        Self::InvalidSlotInfoFilter,
+    ];
+
+    /// Numeric error code as used in JSON-RPC error objects
+    #[inline(always)]
+    pub const fn code(self) -> i32 {
+        self as i32
+    }
+}
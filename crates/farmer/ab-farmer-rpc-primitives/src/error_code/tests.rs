@@ -0,0 +1,43 @@
+use crate::error_code::RpcErrorCode;
+
+/// Codes are part of the public API and must never change
+#[test]
+fn error_codes_are_stable() {
+    let expected = [
+        (RpcErrorCode::SolutionWasIgnored, 0),
+        (RpcErrorCode::SuperSegmentHeadersLengthExceeded, 1),
+        (RpcErrorCode::FailedToRecreateSegment, 2),
+        (RpcErrorCode::BlockingTaskJoinError, 3),
+        (RpcErrorCode::ShardAssignmentsLengthExceeded, 4),
+        (RpcErrorCode::SectorExpirationsLengthExceeded, 5),
+        (RpcErrorCode::SolutionTooLate, 7),
+        (RpcErrorCode::TooManySubscriptions, 8),
+        (RpcErrorCode::UnknownSlot, 10),
+        (RpcErrorCode::ArchiverReinitializationFailed, 11),
+        (RpcErrorCode::PageItemsLimitExceeded, 12),
+        (RpcErrorCode::InvalidAddress, 13),
+        (RpcErrorCode::InvalidSlotInfoFilter, 14),
+    ];
+
+    assert_eq!(RpcErrorCode::ALL.len(), expected.len());
+    for (error_code, code) in expected {
+        assert_eq!(error_code.code(), code);
+        assert_eq!(i32::from(error_code), code);
+        assert_eq!(RpcErrorCode::try_from(code), Ok(error_code));
+    }
+}
+
+#[test]
+fn error_codes_catalog() {
+    // Listed in increasing order without duplicates
+    assert!(
+        RpcErrorCode::ALL
+            .windows(2)
+            .all(|window| window[0].code() < window[1].code())
+    );
+
+    // Reserved and unknown codes are returned back
+    for code in [-1, 6, 9, 1000, i32::MAX] {
+        assert_eq!(RpcErrorCode::try_from(code), Err(code));
+    }
+}
@@ -1,20 +1,41 @@
 //! Primitives for the farmer
 
-use ab_core_primitives::block::BlockRoot;
+mod error_code;
+mod pagination;
+
+pub use crate::error_code::RpcErrorCode;
+pub use crate::pagination::{
+    ContinuationToken, MAX_PAGE_ITEMS, MAX_PAGE_SIZE, Page, PageBuilder, collect_pages,
+};
 use ab_core_primitives::block::header::OwnedBlockHeaderSeal;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
 use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::pieces::SegmentProof;
 use ab_core_primitives::pot::SlotNumber;
-use ab_core_primitives::segments::HistorySize;
-use ab_core_primitives::shard::NumShards;
-use ab_core_primitives::solutions::{ShardMembershipEntropy, Solution, SolutionRange};
+use ab_core_primitives::sectors::{SectorExpiration, SectorId};
+use ab_core_primitives::segments::{
+    HistorySize, LocalSegmentIndex, SegmentHeader, SegmentPosition, SuperSegmentHeader,
+    SuperSegmentIndex,
+};
+use ab_core_primitives::shard::{NumShards, ShardIndex};
+use ab_core_primitives::solutions::{
+    FarmerSolutionRange, ShardMembershipEntropy, Solution, SolutionDistance, SolutionRange,
+};
+use ab_core_primitives::transaction::TransactionHash;
 use ab_farmer_components::FarmerProtocolInfo;
 use ab_networking::libp2p::Multiaddr;
 use parity_scale_codec::{Decode, Encode, EncodeLike, Input, Output};
 use serde::{Deserialize, Serialize};
+use std::num::NonZeroU64;
 use std::time::Duration;
 
 /// Defines a limit for the number of super segments that can be requested over RPC
 pub const MAX_SUPER_SEGMENT_HEADERS_PER_REQUEST: usize = 1000;
+/// Defines a limit for the number of farmers whose shard assignments can be requested over RPC
+pub const MAX_SHARD_ASSIGNMENTS_PER_REQUEST: usize = 1000;
+/// Defines a limit for the number of sectors whose expiration can be requested over RPC (also
+/// applies to a single expiration subscription)
+pub const MAX_SECTOR_EXPIRATIONS_PER_REQUEST: usize = 1000;
 // TODO: This is a workaround for https://github.com/paritytech/jsonrpsee/issues/1617 and should be
 //  removed once that issue is resolved
 /// Shard membership expiration
@@ -34,6 +55,8 @@ pub struct FarmerAppInfo {
     pub farming_timeout: Duration,
     /// Protocol info for farmer
     pub protocol_info: FarmerProtocolInfo,
+    /// Shard topology of the network and shards served by the node
+    pub shard_topology: ShardTopology,
 }
 
 impl Encode for FarmerAppInfo {
@@ -50,6 +73,7 @@ impl Encode for FarmerAppInfo {
             .saturating_add(Encode::size_hint(&self.syncing))
             .saturating_add(Encode::size_hint(&self.farming_timeout))
             .saturating_add(Encode::size_hint(&self.protocol_info))
+            .saturating_add(Encode::size_hint(&self.shard_topology))
     }
 
     fn encode_to<O>(&self, dest: &mut O)
@@ -68,6 +92,7 @@ impl Encode for FarmerAppInfo {
         Encode::encode_to(&self.syncing, dest);
         Encode::encode_to(&self.farming_timeout, dest);
         Encode::encode_to(&self.protocol_info, dest);
+        Encode::encode_to(&self.shard_topology, dest);
     }
 }
 
@@ -100,10 +125,32 @@ impl Decode for FarmerAppInfo {
             })?,
             protocol_info: FarmerProtocolInfo::decode(input)
                 .map_err(|error| error.chain("Could not decode `FarmerAppInfo::protocol_info`"))?,
+            shard_topology: ShardTopology::decode(input)
+                .map_err(|error| error.chain("Could not decode `FarmerAppInfo::shard_topology`"))?,
         })
     }
 }
 
+/// Shard topology of the network and shards served by the node
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardTopology {
+    /// The number of shards in the network
+    pub num_shards: NumShards,
+    /// Shards served by the node
+    pub shards: Vec<NodeShardInfo>,
+}
+
+/// Shard served by the node
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeShardInfo {
+    /// Shard index
+    pub shard_index: ShardIndex,
+    /// Genesis root of the shard, `None` if the shard doesn't have a genesis block of its own
+    pub genesis_root: Option<BlockRoot>,
+}
+
 /// Information about new slot that just arrived
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Encode, Decode, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -113,13 +160,40 @@ pub struct SlotInfo {
     /// Global slot challenge
     pub global_challenge: Blake3Hash,
     /// Acceptable solution range for farmer audits
-    pub solution_range: SolutionRange,
+    pub solution_range: FarmerSolutionRange,
     /// Shard membership entropy
     pub shard_membership_entropy: ShardMembershipEntropy,
     /// The number of shards in the network
     pub num_shards: NumShards,
 }
 
+/// Filter of slots delivered by slot info subscription.
+///
+/// Only slots for which `slot % stride == offset` are delivered. This allows large farms that are
+/// split across multiple farmer processes to audit disjoint subsets of slots in each process.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotInfoFilter {
+    /// Only every `stride`-th slot is delivered
+    pub stride: NonZeroU64,
+    /// Offset of delivered slots, must be smaller than `stride`
+    pub offset: u64,
+}
+
+impl SlotInfoFilter {
+    /// Whether the filter is valid
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.offset < self.stride.get()
+    }
+
+    /// Whether the slot passes the filter
+    #[inline]
+    pub fn matches(&self, slot: SlotNumber) -> bool {
+        u64::from(slot) % self.stride.get() == self.offset
+    }
+}
+
 /// Response of a slot challenge consisting of an optional solution and
 /// the submitter(farmer)'s secret key for block signing.
 #[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
@@ -164,3 +238,246 @@ pub struct FarmerShardMembershipInfo {
     /// History sizes
     pub history_sizes: Vec<HistorySize>,
 }
+
+/// Shard assignment of a farmer for a specific history size
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FarmerShardAssignment {
+    /// Public key hash of the plot identity
+    pub public_key_hash: Blake3Hash,
+    /// History size for which the assignment was derived
+    pub history_size: HistorySize,
+    /// Shard membership entropy for which the assignment was derived
+    pub shard_membership_entropy: ShardMembershipEntropy,
+    /// Assigned shard
+    pub shard_index: ShardIndex,
+}
+
+/// Sector for which expiration is requested
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectorExpirationRequest {
+    /// Sector ID
+    pub sector_id: SectorId,
+    /// History size at which sector was created (from sector metadata)
+    pub history_size: HistorySize,
+}
+
+/// Expiration of a sector
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectorExpirationInfo {
+    /// Sector ID
+    pub sector_id: SectorId,
+    /// Sector expiration as of the current history size
+    pub expiration: SectorExpiration,
+}
+
+/// Transaction in the transaction pool
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransactionInfo {
+    /// Transaction hash
+    pub hash: TransactionHash,
+    /// Block root at which transaction was created
+    pub block_root: BlockRoot,
+    /// Transaction size in bytes
+    pub size: u32,
+    /// The most recent block number at which transaction was authorized, `None` if it was not
+    /// authorized yet
+    pub authorized_at: Option<BlockNumber>,
+}
+
+/// Transaction pool status
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionPoolStatusInfo {
+    /// Number of transactions in the pool
+    pub count: usize,
+    /// Total size of all transactions in the pool in bytes
+    pub size: usize,
+    /// Number of transactions that were not authorized yet
+    pub new: usize,
+    /// Number of authorized transactions
+    pub authorized: usize,
+    /// Max number of transactions in the pool
+    pub count_limit: usize,
+    /// Max total size of all transactions in the pool in bytes
+    pub size_limit: usize,
+}
+
+/// Progress of archiving of blocks that were produced before archiver started
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiverProgressInfo {
+    /// Whether archiver caught up with already produced blocks
+    pub caught_up: bool,
+    /// The last block that was archived
+    pub current_block: BlockNumber,
+    /// The block archiver is catching up to
+    pub target_block: BlockNumber,
+    /// Number of segments produced during catch-up
+    pub segments_produced: u64,
+    /// Estimated time remaining until catch-up is finished, `None` if unknown
+    pub eta: Option<Duration>,
+}
+
+/// Statistics of archiving a single segment
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentStatsInfo {
+    /// Index of the segment
+    pub segment_index: LocalSegmentIndex,
+    /// Number of blocks added to the archiver since the previous segment, the last of which
+    /// completed this segment
+    pub blocks: u32,
+    /// Size of encoded blocks added to the archiver since the previous segment
+    pub bytes: u64,
+    /// Time spent encoding blocks added to the archiver since the previous segment
+    pub encoding_time: Duration,
+    /// Time spent erasure coding and committing to the segment
+    pub erasure_coding_time: Duration,
+    /// Time spent waiting for acknowledgement of the archived segment, `None` for segments
+    /// produced while catching up with already produced blocks
+    pub acknowledgement_wait_time: Option<Duration>,
+}
+
+/// Contract metadata information
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractMetadataInfo {
+    /// Block at which metadata was read
+    pub block: BlockInfo,
+    /// Encoded contract metadata (methods and their arguments), see `ContractMetadataKind` in
+    /// `ab-contracts-common` crate for encoding details
+    #[serde(with = "hex")]
+    pub metadata: Vec<u8>,
+}
+
+/// Block information
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockInfo {
+    /// Block root
+    pub root: BlockRoot,
+    /// Block number
+    pub number: BlockNumber,
+}
+
+/// Node status
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatusInfo {
+    /// Best block number
+    pub best_block_number: BlockNumber,
+    /// Whether node is syncing right now
+    pub syncing: bool,
+    /// Archiver progress, `None` if not available
+    pub archiver: Option<ArchiverProgressInfo>,
+}
+
+/// Super segment header delivered by the super segment header feed.
+///
+/// Each item refers to the super segment header delivered before it, such that the receiver can
+/// detect gaps by comparing [`Self::previous_super_segment_index`] with the index of the last
+/// header it has seen.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuperSegmentHeaderFeedItem {
+    /// Index of the previously delivered super segment header (or last seen index supplied on
+    /// subscription), `None` for the first header of a subscription without a last seen index
+    pub previous_super_segment_index: Option<SuperSegmentIndex>,
+    /// Super segment header
+    pub super_segment_header: SuperSegmentHeader,
+}
+
+impl SuperSegmentHeaderFeedItem {
+    /// Whether this item directly follows the super segment header with `last_seen` index, i.e.
+    /// there is no gap between them
+    pub fn follows(&self, last_seen: Option<SuperSegmentIndex>) -> bool {
+        self.previous_super_segment_index == last_seen
+    }
+}
+
+/// Proof of inclusion of a segment into a super segment
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentInclusionProof {
+    /// Super segment the segment is included in
+    pub super_segment_index: SuperSegmentIndex,
+    /// Position of the segment in the super segment
+    pub segment_position: SegmentPosition,
+    /// Proof of the segment root against the root of the super segment
+    pub segment_proof: SegmentProof,
+}
+
+/// Contiguous range of beacon chain segment headers with proofs binding them to the chain.
+///
+/// Each segment header's root is verified against the root of the super segment it is included
+/// in using the corresponding inclusion proof. Super segment headers are linked with each other by
+/// hashes of previous headers, the last one is the latest super segment header known to the node,
+/// which is committed to in the beacon chain block headers.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentHeadersRange {
+    /// Segment headers, starting with the requested segment index
+    pub segment_headers: Vec<SegmentHeader>,
+    /// Inclusion proofs for segment headers in the same order.
+    ///
+    /// `None` if the segment is not included in a super segment yet or the super segment is too
+    /// old for the node to produce a proof.
+    pub inclusion_proofs: Vec<Option<SegmentInclusionProof>>,
+    /// Super segment headers starting with the oldest super segment referenced by inclusion proofs
+    /// up to the latest super segment, empty if there are no inclusion proofs
+    pub super_segment_headers: Vec<SuperSegmentHeader>,
+    /// Token for requesting the rest of the range, `None` if there are no more segment headers.
+    ///
+    /// Position is the index of the next segment header.
+    pub continuation_token: Option<ContinuationToken>,
+}
+
+/// Outcome of a single check performed during solution verification
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum SolutionCheck {
+    /// Check passed
+    Passed,
+    /// Check failed
+    Failed {
+        /// Reason of the failure
+        reason: String,
+    },
+    /// Check was not performed because the information necessary for it is not available
+    Skipped {
+        /// Reason why the check was skipped
+        reason: String,
+    },
+}
+
+/// Detailed outcome of a solution verification dry-run.
+///
+/// Individual checks are performed independently of each other, such that all problems with a
+/// solution are reported at once, while [`Self::full_verification`] is the outcome of the same
+/// verification that is performed on the solution in a block.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolutionVerificationInfo {
+    /// Slot for which the solution was verified
+    pub slot: SlotNumber,
+    /// Beacon chain solution range for the slot
+    pub solution_range: SolutionRange,
+    /// Distance of the solution from the slot challenge
+    pub solution_distance: SolutionDistance,
+    /// Whether solution distance is within the solution range
+    pub range_check: SolutionCheck,
+    /// Whether proof of space is valid
+    pub proof_of_space_check: SolutionCheck,
+    /// Sector expiration as of the current history size, `None` if the solution has invalid
+    /// history size
+    pub sector_expiration: Option<SectorExpiration>,
+    /// Whether the sector has not expired yet
+    pub sector_expiration_check: SolutionCheck,
+    /// Full solution verification, including checks not covered above (shard commitment, chunk,
+    /// record and segment proofs, etc.)
+    pub full_verification: SolutionCheck,
+}
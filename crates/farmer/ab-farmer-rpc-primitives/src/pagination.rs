@@ -0,0 +1,182 @@
+//! Pagination of RPC responses that can be large.
+//!
+//! Paginated methods accept a limit on the number of items along with an optional
+//! [`ContinuationToken`] and return a [`Page`] of items. Server builds pages with [`PageBuilder`],
+//! which stops adding items once either the limit on the number of items or [`MAX_PAGE_SIZE`] is
+//! reached, such that responses never exceed frame limits of the RPC server. In case there are
+//! more items available, the page contains a continuation token for requesting the next page, see
+//! [`collect_pages()`] for a client-side helper that fetches all pages.
+
+#[cfg(test)]
+mod tests;
+
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// Defines a limit for the number of items in a single page of a paginated response
+pub const MAX_PAGE_ITEMS: usize = 1000;
+/// Defines a limit for the size of items in a single page of a paginated response (in JSON
+/// encoding), comfortably below the max response size of the RPC server
+pub const MAX_PAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Token for requesting the next page of a paginated response.
+///
+/// Contains the position of the next item, whose meaning is method-specific (for example, an
+/// index of the next segment header or an offset in the list of transactions).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Encode, Decode, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ContinuationToken(u64);
+
+impl ContinuationToken {
+    /// Create a new instance from the position of the next item
+    #[inline(always)]
+    pub const fn new(position: u64) -> Self {
+        Self(position)
+    }
+
+    /// Position of the next item
+    #[inline(always)]
+    pub const fn position(self) -> u64 {
+        self.0
+    }
+}
+
+/// A page of a paginated response
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    /// Items in this page
+    pub items: Vec<T>,
+    /// Token for requesting the next page, `None` if this is the last page
+    pub continuation_token: Option<ContinuationToken>,
+}
+
+/// Counts bytes written to it
+#[derive(Debug, Default)]
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Server-side builder of a [`Page`] that enforces limits on the number of items and their size
+#[derive(Debug)]
+pub struct PageBuilder<T> {
+    first_position: u64,
+    items: Vec<T>,
+    max_items: usize,
+    remaining_size: usize,
+    full: bool,
+}
+
+impl<T> PageBuilder<T>
+where
+    T: Serialize,
+{
+    /// Create a new builder for a page with the first item at `first_position`, containing up to
+    /// `max_items` items and up to `max_size` bytes of items in JSON encoding
+    pub fn new(first_position: u64, max_items: usize, max_size: usize) -> Self {
+        Self {
+            first_position,
+            items: Vec::new(),
+            max_items,
+            remaining_size: max_size,
+            full: false,
+        }
+    }
+
+    /// Number of items in the page so far
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the page is empty so far
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Whether the page is full and no more items can be added
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    /// Add an item to the page.
+    ///
+    /// Returns item back if the page is full. The first item is always added even if it exceeds
+    /// the max size on its own, otherwise it would never be possible to retrieve it.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.full || self.items.len() >= self.max_items {
+            self.full = true;
+            return Err(item);
+        }
+
+        let mut byte_counter = ByteCounter::default();
+        serde_json::to_writer(&mut byte_counter, &item)
+            .expect("Items are serializable since they are sent in RPC responses; qed");
+        let item_size = byte_counter.0;
+
+        if item_size > self.remaining_size && !self.items.is_empty() {
+            self.full = true;
+            return Err(item);
+        }
+
+        self.remaining_size = self.remaining_size.saturating_sub(item_size);
+        self.items.push(item);
+
+        Ok(())
+    }
+
+    /// Finish building the page.
+    ///
+    /// `has_more` indicates whether there are more items after those added to the page, in which
+    /// case continuation token will point to the item right after the last one in the page.
+    pub fn finish(self, has_more: bool) -> Page<T> {
+        let continuation_token =
+            has_more.then(|| ContinuationToken::new(self.first_position + self.items.len() as u64));
+
+        Page {
+            items: self.items,
+            continuation_token,
+        }
+    }
+}
+
+/// Client-side helper that collects items from all pages of a paginated response.
+///
+/// `fetch_page` is called with `None` for the first page and with continuation token from the
+/// previous page for subsequent pages until the last page is received.
+pub async fn collect_pages<T, E, F, Fut>(mut fetch_page: F) -> Result<Vec<T>, E>
+where
+    F: FnMut(Option<ContinuationToken>) -> Fut,
+    Fut: Future<Output = Result<Page<T>, E>>,
+{
+    let mut items = Vec::new();
+    let mut continuation_token = None;
+
+    loop {
+        let page = fetch_page(continuation_token).await?;
+        items.extend(page.items);
+
+        match page.continuation_token {
+            Some(next_continuation_token) => {
+                continuation_token.replace(next_continuation_token);
+            }
+            None => {
+                return Ok(items);
+            }
+        }
+    }
+}
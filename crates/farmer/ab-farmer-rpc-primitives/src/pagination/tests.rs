@@ -0,0 +1,58 @@
+use crate::pagination::{ContinuationToken, Page, PageBuilder, collect_pages};
+use futures::executor::block_on;
+
+#[test]
+fn page_builder_limits() {
+    // Limited by the number of items
+    let mut page = PageBuilder::new(10, 2, usize::MAX);
+    assert_eq!(page.push(0u32), Ok(()));
+    assert_eq!(page.push(1u32), Ok(()));
+    assert_eq!(page.push(2u32), Err(2));
+    assert!(page.is_full());
+    assert_eq!(
+        page.finish(true),
+        Page {
+            items: vec![0, 1],
+            continuation_token: Some(ContinuationToken::new(12)),
+        }
+    );
+
+    // Limited by size, each string is 5 bytes in JSON encoding
+    let mut page = PageBuilder::new(0, usize::MAX, 12);
+    assert_eq!(page.push("abc"), Ok(()));
+    assert_eq!(page.push("def"), Ok(()));
+    assert_eq!(page.push("ghi"), Err("ghi"));
+    // Page remains full even if a smaller item would fit
+    assert_eq!(page.push(""), Err(""));
+    assert_eq!(page.len(), 2);
+
+    // The first item is always added
+    let mut page = PageBuilder::new(0, usize::MAX, 1);
+    assert_eq!(page.push("abc"), Ok(()));
+    assert_eq!(page.push("def"), Err("def"));
+
+    // No continuation token for the last page
+    let mut page = PageBuilder::new(0, 2, usize::MAX);
+    assert_eq!(page.push(0u32), Ok(()));
+    assert_eq!(page.finish(false).continuation_token, None);
+}
+
+#[test]
+fn collect_all_pages() {
+    let items = (0..10).collect::<Vec<u32>>();
+
+    let collected_items = block_on(collect_pages(|continuation_token| {
+        let offset = continuation_token.map_or(0, ContinuationToken::position);
+
+        let mut page = PageBuilder::new(offset, 3, usize::MAX);
+        let has_more = items
+            .iter()
+            .skip(usize::try_from(offset).unwrap())
+            .any(|&item| page.push(item).is_err());
+
+        async move { Ok::<_, ()>(page.finish(has_more)) }
+    }))
+    .unwrap();
+
+    assert_eq!(collected_items, items);
+}
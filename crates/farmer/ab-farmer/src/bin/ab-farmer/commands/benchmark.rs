@@ -2,7 +2,7 @@ use crate::PosTable;
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pot::SlotNumber;
 use ab_core_primitives::shard::NumShards;
-use ab_core_primitives::solutions::{ShardMembershipEntropy, SolutionRange};
+use ab_core_primitives::solutions::{FarmerSolutionRange, ShardMembershipEntropy};
 use ab_erasure_coding::ErasureCoding;
 use ab_farmer::single_disk_farm::direct_io_file_wrapper::DirectIoFileWrapper;
 use ab_farmer::single_disk_farm::farming::rayon_files::RayonFiles;
@@ -190,7 +190,7 @@ where
                                 slot: SlotNumber::ZERO,
                                 global_challenge: Blake3Hash::from(global_challenge),
                                 // No solution will be found, pure audit
-                                solution_range: SolutionRange::MIN,
+                                solution_range: FarmerSolutionRange::MIN,
                                 shard_membership_entropy: ShardMembershipEntropy::default(),
                                 num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN)
                                     .expect("Values are statically known to be valid; qed"),
@@ -225,7 +225,7 @@ where
                                 slot: SlotNumber::ZERO,
                                 global_challenge: Blake3Hash::from(global_challenge),
                                 // No solution will be found, pure audit
-                                solution_range: SolutionRange::MIN,
+                                solution_range: FarmerSolutionRange::MIN,
                                 shard_membership_entropy: ShardMembershipEntropy::default(),
                                 num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN)
                                     .expect("Values are statically known to be valid; qed"),
@@ -258,7 +258,7 @@ where
                                 slot: SlotNumber::ZERO,
                                 global_challenge: Blake3Hash::from(global_challenge),
                                 // No solution will be found, pure audit
-                                solution_range: SolutionRange::MIN,
+                                solution_range: FarmerSolutionRange::MIN,
                                 shard_membership_entropy: ShardMembershipEntropy::default(),
                                 num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN)
                                     .expect("Values are statically known to be valid; qed"),
@@ -357,7 +357,7 @@ where
                     slot: SlotNumber::ZERO,
                     global_challenge: Blake3Hash::from(rand::random::<[u8; 32]>()),
                     // Solution is guaranteed to be found
-                    solution_range: SolutionRange::MAX,
+                    solution_range: FarmerSolutionRange::MAX,
                     shard_membership_entropy: ShardMembershipEntropy::default(),
                     num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN)
                         .expect("Values are statically known to be valid; qed"),
@@ -405,7 +405,7 @@ where
                     slot: SlotNumber::ZERO,
                     global_challenge: Blake3Hash::from(rand::random::<[u8; 32]>()),
                     // Solution is guaranteed to be found
-                    solution_range: SolutionRange::MAX,
+                    solution_range: FarmerSolutionRange::MAX,
                     shard_membership_entropy: ShardMembershipEntropy::default(),
                     num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN)
                         .expect("Values are statically known to be valid; qed"),
@@ -451,7 +451,7 @@ where
                     slot: SlotNumber::ZERO,
                     global_challenge: Blake3Hash::from(rand::random::<[u8; 32]>()),
                     // Solution is guaranteed to be found
-                    solution_range: SolutionRange::MAX,
+                    solution_range: FarmerSolutionRange::MAX,
                     shard_membership_entropy: ShardMembershipEntropy::default(),
                     num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN)
                         .expect("Values are statically known to be valid; qed"),
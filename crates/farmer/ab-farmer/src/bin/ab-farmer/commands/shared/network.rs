@@ -13,6 +13,9 @@ use ab_networking::protocols::request_response::handlers::cached_piece_by_index:
 use ab_networking::protocols::request_response::handlers::piece_by_index::{
     PieceByIndexRequest, PieceByIndexRequestHandler, PieceByIndexResponse,
 };
+use ab_networking::protocols::request_response::handlers::request_limits::{
+    RequestLimits, RequestRateLimiter,
+};
 use ab_networking::protocols::request_response::handlers::segment_header::{
     SuperSegmentHeaderBySegmentIndexesRequestHandler, SuperSegmentHeaderRequest,
     SuperSegmentHeaderResponse,
@@ -31,6 +34,7 @@ use std::collections::HashSet;
 use std::fmt;
 use std::hash::Hash;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::path::Path;
 use std::sync::{Arc, Weak};
 use tracing::{Instrument, debug, error, info, warn};
@@ -39,6 +43,13 @@ use tracing::{Instrument, debug, error, info, warn};
 ///
 /// Must be the same as RPC limit since all requests go to the node anyway.
 const SUPER_SEGMENT_HEADERS_LIMIT: u32 = MAX_SUPER_SEGMENT_HEADERS_PER_REQUEST as u32;
+/// Max number of piece requests from a single peer waiting to be handled (per protocol)
+const MAX_QUEUED_PIECE_REQUESTS_PER_PEER: NonZeroUsize =
+    NonZeroUsize::new(10).expect("Not zero; qed");
+/// Average number of piece requests served per second across all piece protocols
+const PIECE_REQUESTS_PER_SECOND: NonZeroU32 = NonZeroU32::new(100).expect("Not zero; qed");
+/// Number of piece requests that can be served in a burst above [`PIECE_REQUESTS_PER_SECOND`]
+const PIECE_REQUESTS_BURST: NonZeroU32 = NonZeroU32::new(100).expect("Not zero; qed");
 
 /// Configuration for network stack
 #[derive(Debug, Parser)]
@@ -117,6 +128,14 @@ where
     .map(Box::new)?;
 
     let maybe_weak_node = Arc::new(Mutex::new(None::<WeakNode>));
+    // Shared between piece protocols, super segment headers are consensus-critical and not limited
+    let piece_request_limits = RequestLimits {
+        max_queued_requests_per_peer: MAX_QUEUED_PIECE_REQUESTS_PER_PEER,
+        rate_limiter: Some(RequestRateLimiter::new(
+            PIECE_REQUESTS_PER_SECOND,
+            PIECE_REQUESTS_BURST,
+        )),
+    };
     let default_config = Config::new(protocol_prefix, keypair, prometheus_metrics_registry);
     let config = Config {
         reserved_peers,
@@ -128,104 +147,110 @@ where
                 let maybe_weak_node = Arc::clone(&maybe_weak_node);
                 let farmer_caches = farmer_caches.clone();
 
-                CachedPieceByIndexRequestHandler::create(move |peer_id, request| {
-                    let CachedPieceByIndexRequest {
+                CachedPieceByIndexRequestHandler::create_with_limits(
+                    move |peer_id, request| {
+                        let CachedPieceByIndexRequest {
+                            piece_index,
+                            cached_pieces,
+                        } = request;
+                        debug!(?piece_index, "Cached piece request received");
+
+                        let maybe_weak_node = Arc::clone(&maybe_weak_node);
+                        let farmer_caches = farmer_caches.clone();
+                        let mut cached_pieces = Arc::unwrap_or_clone(cached_pieces);
+
+                        async move {
+                            let piece_from_cache =
+                                farmer_caches.get_piece(piece_index.to_multihash()).await;
+                            cached_pieces.truncate(CachedPieceByIndexRequest::RECOMMENDED_LIMIT);
+                            let cached_pieces = farmer_caches.has_pieces(cached_pieces).await;
+
+                            Some(CachedPieceByIndexResponse {
+                                result: if let Some(piece) = piece_from_cache {
+                                    PieceResult::Piece(piece)
+                                } else {
+                                    let maybe_node = maybe_weak_node
+                                        .lock()
+                                        .as_ref()
+                                        .expect("Always called after network instantiation; qed")
+                                        .upgrade();
+
+                                    let closest_peers = if let Some(node) = maybe_node {
+                                        node.get_closest_local_peers(
+                                            piece_index.to_multihash(),
+                                            Some(peer_id),
+                                        )
+                                        .await
+                                        .inspect_err(|error| {
+                                            warn!(%error, "Failed to get closest local peers");
+                                        })
+                                        .unwrap_or_default()
+                                    } else {
+                                        Vec::new()
+                                    };
+
+                                    PieceResult::ClosestPeers(closest_peers.into())
+                                },
+                                cached_pieces,
+                            })
+                        }
+                        .in_current_span()
+                    },
+                    piece_request_limits.clone(),
+                )
+            },
+            PieceByIndexRequestHandler::create_with_limits(
+                move |_, request| {
+                    let PieceByIndexRequest {
                         piece_index,
                         cached_pieces,
                     } = request;
-                    debug!(?piece_index, "Cached piece request received");
+                    debug!(?piece_index, "Piece request received. Trying cache...");
 
-                    let maybe_weak_node = Arc::clone(&maybe_weak_node);
+                    let weak_plotted_pieces = Weak::clone(&weak_plotted_pieces);
                     let farmer_caches = farmer_caches.clone();
                     let mut cached_pieces = Arc::unwrap_or_clone(cached_pieces);
 
                     async move {
                         let piece_from_cache =
                             farmer_caches.get_piece(piece_index.to_multihash()).await;
-                        cached_pieces.truncate(CachedPieceByIndexRequest::RECOMMENDED_LIMIT);
+                        cached_pieces.truncate(PieceByIndexRequest::RECOMMENDED_LIMIT);
                         let cached_pieces = farmer_caches.has_pieces(cached_pieces).await;
 
-                        Some(CachedPieceByIndexResponse {
-                            result: if let Some(piece) = piece_from_cache {
-                                PieceResult::Piece(piece)
-                            } else {
-                                let maybe_node = maybe_weak_node
-                                    .lock()
-                                    .as_ref()
-                                    .expect("Always called after network instantiation; qed")
-                                    .upgrade();
+                        if let Some(piece) = piece_from_cache {
+                            Some(PieceByIndexResponse {
+                                piece: Some(piece),
+                                cached_pieces,
+                            })
+                        } else {
+                            debug!(
+                                ?piece_index,
+                                "No piece in the cache. Trying archival storage..."
+                            );
 
-                                let closest_peers = if let Some(node) = maybe_node {
-                                    node.get_closest_local_peers(
-                                        piece_index.to_multihash(),
-                                        Some(peer_id),
-                                    )
-                                    .await
-                                    .inspect_err(|error| {
-                                        warn!(%error, "Failed to get closest local peers");
-                                    })
-                                    .unwrap_or_default()
+                            let read_piece_fut =
+                                if let Some(plotted_pieces) = weak_plotted_pieces.upgrade() {
+                                    plotted_pieces
+                                        .try_read()?
+                                        .read_piece(piece_index)?
+                                        .in_current_span()
                                 } else {
-                                    Vec::new()
+                                    debug!("A readers and pieces are already dropped");
+                                    return None;
                                 };
 
-                                PieceResult::ClosestPeers(closest_peers.into())
-                            },
-                            cached_pieces,
-                        })
-                    }
-                    .in_current_span()
-                })
-            },
-            PieceByIndexRequestHandler::create(move |_, request| {
-                let PieceByIndexRequest {
-                    piece_index,
-                    cached_pieces,
-                } = request;
-                debug!(?piece_index, "Piece request received. Trying cache...");
-
-                let weak_plotted_pieces = Weak::clone(&weak_plotted_pieces);
-                let farmer_caches = farmer_caches.clone();
-                let mut cached_pieces = Arc::unwrap_or_clone(cached_pieces);
-
-                async move {
-                    let piece_from_cache =
-                        farmer_caches.get_piece(piece_index.to_multihash()).await;
-                    cached_pieces.truncate(PieceByIndexRequest::RECOMMENDED_LIMIT);
-                    let cached_pieces = farmer_caches.has_pieces(cached_pieces).await;
-
-                    if let Some(piece) = piece_from_cache {
-                        Some(PieceByIndexResponse {
-                            piece: Some(piece),
-                            cached_pieces,
-                        })
-                    } else {
-                        debug!(
-                            ?piece_index,
-                            "No piece in the cache. Trying archival storage..."
-                        );
-
-                        let read_piece_fut =
-                            if let Some(plotted_pieces) = weak_plotted_pieces.upgrade() {
-                                plotted_pieces
-                                    .try_read()?
-                                    .read_piece(piece_index)?
-                                    .in_current_span()
-                            } else {
-                                debug!("A readers and pieces are already dropped");
-                                return None;
-                            };
+                            let piece = read_piece_fut.await;
 
-                        let piece = read_piece_fut.await;
-
-                        Some(PieceByIndexResponse {
-                            piece,
-                            cached_pieces,
-                        })
+                            Some(PieceByIndexResponse {
+                                piece,
+                                cached_pieces,
+                            })
+                        }
                     }
-                }
-                .in_current_span()
-            }),
+                    .in_current_span()
+                },
+                piece_request_limits,
+            ),
             SuperSegmentHeaderBySegmentIndexesRequestHandler::create(move |peer_id, req| {
                 debug!(%peer_id, ?req, "Segment headers request received.");
 
@@ -7,11 +7,12 @@ use ab_core_primitives::pieces::{Piece, PieceIndex};
 use ab_core_primitives::segments::{
     HistorySize, SegmentIndex, SuperSegmentHeader, SuperSegmentIndex, SuperSegmentRoot,
 };
+use ab_core_primitives::shard::NumShards;
 use ab_data_retrieval::piece_getter::PieceGetter;
 use ab_farmer_components::FarmerProtocolInfo;
 use ab_farmer_rpc_primitives::{
-    BlockSealInfo, BlockSealResponse, FarmerAppInfo, FarmerShardMembershipInfo, SlotInfo,
-    SolutionResponse,
+    BlockSealInfo, BlockSealResponse, FarmerAppInfo, FarmerShardMembershipInfo, ShardTopology,
+    SlotInfo, SolutionResponse,
 };
 use ab_networking::libp2p::identity;
 use ab_networking::libp2p::kad::RecordKey;
@@ -23,7 +24,7 @@ use futures::{SinkExt, Stream, StreamExt};
 use parking_lot::Mutex;
 use rand::prelude::*;
 use std::collections::HashMap;
-use std::num::{NonZeroU32, NonZeroU64};
+use std::num::{NonZeroU16, NonZeroU32, NonZeroU64};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -59,6 +60,10 @@ impl NodeClient for MockNodeClient {
                 ),
                 min_sector_lifetime: HistorySize::new(NonZeroU64::new(4).unwrap()),
             },
+            shard_topology: ShardTopology {
+                num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN).unwrap(),
+                shards: Vec::new(),
+            },
         })
     }
 
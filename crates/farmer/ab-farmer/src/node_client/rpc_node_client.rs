@@ -7,7 +7,7 @@ use ab_core_primitives::segments::{
 };
 use ab_farmer_rpc_primitives::{
     BlockSealInfo, BlockSealResponse, FarmerAppInfo, FarmerShardMembershipInfo, SlotInfo,
-    SolutionResponse,
+    SlotInfoFilter, SolutionResponse,
 };
 use async_lock::Semaphore;
 use async_trait::async_trait;
@@ -33,6 +33,7 @@ const MAX_CONCURRENT_PIECE_REQUESTS: usize = 10;
 pub struct RpcNodeClient {
     client: Arc<WsClient>,
     piece_request_semaphore: Arc<Semaphore>,
+    slot_info_filter: Option<SlotInfoFilter>,
 }
 
 impl RpcNodeClient {
@@ -48,8 +49,17 @@ impl RpcNodeClient {
         Ok(Self {
             client,
             piece_request_semaphore,
+            slot_info_filter: None,
         })
     }
+
+    /// Only receive slots that pass the filter in slot info subscription.
+    ///
+    /// Useful for large farms split across multiple farmer processes.
+    pub fn with_slot_info_filter(mut self, slot_info_filter: SlotInfoFilter) -> Self {
+        self.slot_info_filter = Some(slot_info_filter);
+        self
+    }
 }
 
 #[async_trait]
@@ -66,7 +76,11 @@ impl NodeClient for RpcNodeClient {
     ) -> anyhow::Result<Pin<Box<dyn Stream<Item = SlotInfo> + Send + 'static>>> {
         let subscription = self
             .client
-            .subscribe("subscribeSlotInfo", rpc_params![], "unsubscribeSlotInfo")
+            .subscribe(
+                "subscribeSlotInfo",
+                rpc_params![self.slot_info_filter],
+                "unsubscribeSlotInfo",
+            )
             .await?;
 
         Ok(Box::pin(subscription.filter_map(
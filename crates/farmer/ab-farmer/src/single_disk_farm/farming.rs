@@ -17,7 +17,7 @@ use ab_core_primitives::pieces::Record;
 use ab_core_primitives::pos::PosSeed;
 use ab_core_primitives::sectors::SectorIndex;
 use ab_core_primitives::segments::{HistorySize, SegmentIndex};
-use ab_core_primitives::solutions::{Solution, SolutionDistance};
+use ab_core_primitives::solutions::{Solution, SolutionDistance, SolutionRange};
 use ab_erasure_coding::ErasureCoding;
 use ab_farmer_components::ReadAtSync;
 use ab_farmer_components::auditing::{AuditingError, audit_plot_sync};
@@ -26,6 +26,9 @@ use ab_farmer_components::sector::{SectorMetadata, SectorMetadataChecksummed};
 use ab_farmer_components::shard_commitment::ShardCommitmentsRootsCache;
 use ab_farmer_rpc_primitives::{SlotInfo, SolutionResponse};
 use ab_proof_of_space::{Table, TableGenerator};
+use ab_solution_verification::{
+    SolutionVerifyStatelessParams, solution_shard_index, verify_stateless,
+};
 use async_lock::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
 use futures::StreamExt;
 use futures::channel::mpsc;
@@ -155,7 +158,7 @@ where
             slot_info.shard_membership_entropy,
             slot_info.num_shards,
             &slot_info.global_challenge,
-            slot_info.solution_range,
+            SolutionRange::from(slot_info.solution_range),
             &self.0,
             sectors_metadata,
             sectors_being_modified,
@@ -376,6 +379,49 @@ where
                         }
                     }
 
+                    // Farmer audits with the solution range of leaf shards, so the solution is
+                    // checked for the leaf shard it belongs to, the same way node will check it
+                    if let Some(solution_range) = slot_info
+                        .solution_range
+                        .to_consensus_solution_range(slot_info.num_shards)
+                    {
+                        let shard_index = solution_shard_index(
+                            &solution,
+                            &slot_info.shard_membership_entropy,
+                            slot_info.num_shards,
+                        );
+                        if let Err(error) = verify_stateless::<PosTable>(
+                            &solution,
+                            &SolutionVerifyStatelessParams {
+                                shard_index,
+                                global_challenge: slot_info.global_challenge,
+                                solution_range,
+                                shard_membership_entropy: slot_info.shard_membership_entropy,
+                                num_shards: slot_info.num_shards,
+                            },
+                        ) {
+                            if let Some(metrics) = &metrics {
+                                metrics
+                                    .observe_proving_time(&start.elapsed(), ProvingResult::Failed);
+                            }
+                            error!(
+                                %slot,
+                                %sector_index,
+                                %error,
+                                "Invalid solution produced, scheduling sector for replotting"
+                            );
+                            problematic_sectors.push(sector_index);
+                            start = Instant::now();
+                            continue;
+                        }
+                    } else {
+                        debug!(
+                            %slot,
+                            solution_range = %slot_info.solution_range,
+                            "Unable to derive consensus solution range, skipping solution check"
+                        );
+                    }
+
                     let response = SolutionResponse {
                         slot_number: slot,
                         solution,
@@ -8,6 +8,9 @@
 
 use ab_aligned_buffer::SharedAlignedBuffer;
 use ab_core_primitives::address::Address;
+use ab_core_primitives::block::execution::ExecutionReceipt;
+use ab_core_primitives::block::header::GenericBlockHeader;
+use ab_core_primitives::block::header::owned::GenericOwnedBlockHeader;
 use ab_core_primitives::block::owned::{GenericOwnedBlock, OwnedBeaconChainBlock};
 use ab_core_primitives::block::{BlockNumber, BlockRoot};
 use ab_core_primitives::segments::{
@@ -15,10 +18,13 @@ use ab_core_primitives::segments::{
     SuperSegmentIndex,
 };
 use ab_core_primitives::shard::ShardIndex;
-use ab_merkle_tree::mmr::MerkleMountainRange;
+use ab_core_primitives::transaction::{Gas, TransactionHash};
+use ab_merkle_tree::mmr::{MerkleMountainRange, MmrPeaks};
 use rclite::Arc;
-use std::io;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::sync::Arc as StdArc;
+use std::{io, iter};
 
 // TODO: This is a workaround for https://github.com/rust-lang/rust/issues/139866 that allows the
 //  code to compile. Constant 4_294_967_295 is hardcoded here and below for compilation to succeed.
@@ -36,6 +42,39 @@ const {
 /// time and substantially decrease the size of the data structure.
 pub type BlockMerkleMountainRange = MerkleMountainRange<4_294_967_295>;
 
+/// Type alias for peaks of [`BlockMerkleMountainRange`]
+pub type BlockMmrPeaks = MmrPeaks<4_294_967_295>;
+
+/// Inclusion proof of a block root in the Merkle Mountain Range of the canonical chain, see
+/// [`ChainInfo::mmr_proof()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockMmrProof {
+    /// Number of the proven block, which is also the index of the leaf
+    pub block_number: BlockNumber,
+    /// Root of the proven block
+    pub block_root: BlockRoot,
+    /// Number of leaves in the Merkle Mountain Range
+    pub num_leaves: u64,
+    /// Root of the Merkle Mountain Range
+    pub mmr_root: [u8; 32],
+    /// Merkle proof of the block root
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl BlockMmrProof {
+    /// Verify the proof of the block root against the Merkle Mountain Range root
+    #[inline]
+    pub fn verify(&self) -> bool {
+        BlockMerkleMountainRange::verify(
+            &self.mmr_root,
+            &self.proof,
+            u64::from(self.block_number),
+            **self.block_root,
+            self.num_leaves,
+        )
+    }
+}
+
 /// State of a contract slot
 #[derive(Debug, Clone)]
 pub struct ContractSlotState {
@@ -47,6 +86,63 @@ pub struct ContractSlotState {
     pub contents: SharedAlignedBuffer,
 }
 
+/// Status of a transaction execution
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u8)]
+pub enum TransactionStatus {
+    /// Transaction was executed successfully
+    Success = 0,
+    /// Transaction execution failed, its state changes were reverted (gas is still charged)
+    Failed = 1,
+}
+
+/// Outcome of a single transaction execution
+#[derive(Debug, Clone)]
+pub struct TransactionOutcome {
+    /// Execution receipt of the transaction
+    pub receipt: ExecutionReceipt,
+    /// Transaction status
+    pub status: TransactionStatus,
+    /// Events emitted by the transaction, encoded by the execution environment
+    pub events: SharedAlignedBuffer,
+}
+
+/// Outcome of a block execution, see [`ChainInfoWrite::persist_block_outcome()`]
+#[derive(Debug, Clone, Default)]
+pub struct BlockOutcome {
+    /// Outcomes of transactions in the same order as transactions in the block body
+    pub transactions: StdArc<[TransactionOutcome]>,
+}
+
+impl BlockOutcome {
+    /// Total gas used by all transactions of the block
+    pub fn gas_used(&self) -> Gas {
+        Gas::from(
+            self.transactions
+                .iter()
+                .map(|transaction| u64::from(transaction.receipt.gas_used))
+                .fold(0u64, u64::saturating_add),
+        )
+    }
+
+    /// Execution receipts of all transactions of the block, can be used to compute and prove
+    /// against the execution receipts root of the block
+    pub fn execution_receipts(&self) -> impl ExactSizeIterator<Item = &ExecutionReceipt> {
+        self.transactions
+            .iter()
+            .map(|transaction| &transaction.receipt)
+    }
+}
+
+/// Location of a transaction in a block body, see [`ChainInfo::find_transaction()`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TransactionLocation {
+    /// Root of the block that includes the transaction
+    pub block_root: BlockRoot,
+    /// Offset of the transaction among transactions in the block body
+    pub offset: u32,
+}
+
 /// Additional details about a block
 #[derive(Debug, Clone)]
 pub struct BlockDetails {
@@ -72,6 +168,117 @@ pub enum BlockOrigin {
     Broadcast,
 }
 
+/// Max size of auxiliary data attached to a block, see [`ChainInfoWrite::persist_block_aux_data()`]
+pub const MAX_BLOCK_AUX_DATA_SIZE: u32 = 64 * 1024;
+
+/// Namespace of auxiliary data attached to a block.
+///
+/// Allows different components to attach auxiliary data to the same block independently of each
+/// other.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct BlockAuxDataNamespace([u8; 8]);
+
+impl BlockAuxDataNamespace {
+    /// Create a new instance from bytes
+    #[inline(always)]
+    pub const fn new(bytes: [u8; 8]) -> Self {
+        Self(bytes)
+    }
+
+    /// Get internal representation
+    #[inline(always)]
+    pub const fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+/// Symbolic tag that refers to a block whose root changes as the chain progresses
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum BlockTag {
+    /// Best block
+    Best,
+    /// The latest block that is deep enough to be considered confirmed
+    Confirmed,
+    /// The last block that was (fully or partially) archived, according to the last segment header
+    LastArchived,
+}
+
+/// Block identifier that can be resolved into a concrete block with [`resolve_block_id()`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum BlockId {
+    /// Symbolic tag
+    Tag(BlockTag),
+    /// Block number on the best chain
+    Number(BlockNumber),
+    /// Block root
+    Root(BlockRoot),
+}
+
+impl From<BlockTag> for BlockId {
+    #[inline(always)]
+    fn from(tag: BlockTag) -> Self {
+        Self::Tag(tag)
+    }
+}
+
+impl From<BlockNumber> for BlockId {
+    #[inline(always)]
+    fn from(number: BlockNumber) -> Self {
+        Self::Number(number)
+    }
+}
+
+impl From<BlockRoot> for BlockId {
+    #[inline(always)]
+    fn from(root: BlockRoot) -> Self {
+        Self::Root(root)
+    }
+}
+
+/// Resolve block identifier into a header of the corresponding block.
+///
+/// Block numbers and tags are resolved against the best chain, `block_confirmation_depth` is used
+/// for [`BlockTag::Confirmed`]. Returns `None` if the block is not known (or was already pruned).
+pub fn resolve_block_id<Block, CI>(
+    chain_info: &CI,
+    block_id: BlockId,
+    block_confirmation_depth: BlockNumber,
+) -> Option<Block::Header>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfo<Block>,
+{
+    let block_number = match block_id {
+        BlockId::Tag(BlockTag::Best) => {
+            return Some(chain_info.best_header());
+        }
+        BlockId::Tag(BlockTag::Confirmed) => {
+            let best_header = chain_info.best_header();
+            let best_header = best_header.header();
+            let confirmed_block_number = best_header
+                .prefix
+                .number
+                .saturating_sub(block_confirmation_depth);
+
+            return chain_info.ancestor_header(confirmed_block_number, &best_header.root());
+        }
+        BlockId::Tag(BlockTag::LastArchived) => chain_info
+            .last_segment_header()?
+            .last_archived_block
+            .number(),
+        BlockId::Number(block_number) => block_number,
+        BlockId::Root(block_root) => {
+            return chain_info.header(&block_root);
+        }
+    };
+
+    chain_info.canonical_header(block_number)
+}
+
 /// Intermediate or leaf shard segment root information
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ShardSegmentRoot {
@@ -83,6 +290,52 @@ pub struct ShardSegmentRoot {
     pub segment_root: SegmentRoot,
 }
 
+/// Storage item read from a known location doesn't match what is expected there, which indicates
+/// database corruption or an implementation bug
+#[derive(Debug, thiserror::Error)]
+pub enum StorageItemCorruptionError {
+    /// Storage item failed verification (checksums, size, encoding)
+    #[error("Storage item at page offset {page_offset} failed verification: {reason}")]
+    InvalidStorageItem {
+        /// Page offset of the storage item
+        page_offset: u32,
+        /// Reason of verification failure
+        reason: String,
+    },
+    /// Unexpected sequence number
+    #[error(
+        "Unexpected sequence number of storage item at page offset {page_offset}: expected \
+        {expected}, actual {actual}"
+    )]
+    UnexpectedSequenceNumber {
+        /// Page offset of the storage item
+        page_offset: u32,
+        /// Expected sequence number
+        expected: u64,
+        /// Actual sequence number
+        actual: u64,
+    },
+    /// Unexpected storage item type
+    #[error(
+        "Unexpected storage item type at page offset {page_offset}: expected {expected}, actual \
+        {actual}"
+    )]
+    UnexpectedStorageItemType {
+        /// Page offset of the storage item
+        page_offset: u32,
+        /// Expected storage item type
+        expected: &'static str,
+        /// Actual storage item type
+        actual: &'static str,
+    },
+    /// Storage item contents don't match the expected contents
+    #[error("Unexpected storage item contents at page offset {page_offset}")]
+    UnexpectedContents {
+        /// Page offset of the storage item
+        page_offset: u32,
+    },
+}
+
 /// Error for [`ChainInfo::block()`]
 #[derive(Debug, thiserror::Error)]
 pub enum ReadBlockError {
@@ -99,9 +352,16 @@ pub enum ReadBlockError {
         #[from]
         error: io::Error,
     },
+    /// Corrupted storage item
+    #[error("Corrupted storage item")]
+    CorruptedStorageItem {
+        /// Low-level error
+        #[from]
+        error: StorageItemCorruptionError,
+    },
 }
 
-/// Error for [`ChainInfoWrite::persist_block()`]
+/// Error for [`ChainInfoWrite::persist_block()`] and [`ChainInfoWrite::persist_blocks()`]
 #[derive(Debug, thiserror::Error)]
 pub enum PersistBlockError {
     /// Missing parent
@@ -110,6 +370,85 @@ pub enum PersistBlockError {
     /// Block is outside the acceptable range
     #[error("Block is outside the acceptable range")]
     OutsideAcceptableRange,
+    /// Fork choice outcome is not supported by the database
+    #[error("Fork choice outcome is not supported by the database")]
+    UnsupportedForkChoice,
+    /// Storage item write error
+    #[error("Storage item write error")]
+    StorageItemWriteError {
+        /// Low-level error
+        #[from]
+        error: io::Error,
+    },
+}
+
+/// Error for [`ChainInfoWrite::persist_block_aux_data()`]
+#[derive(Debug, thiserror::Error)]
+pub enum PersistBlockAuxDataError {
+    /// Unknown block root
+    #[error("Unknown block root")]
+    UnknownBlockRoot,
+    /// Auxiliary data is too large
+    #[error("Auxiliary data is too large: {size} bytes, max is {MAX_BLOCK_AUX_DATA_SIZE} bytes")]
+    TooLarge {
+        /// Size of auxiliary data in bytes
+        size: u32,
+    },
+    /// Storage item write error
+    #[error("Storage item write error")]
+    StorageItemWriteError {
+        /// Low-level error
+        #[from]
+        error: io::Error,
+    },
+}
+
+/// Error for [`ChainInfoWrite::persist_block_outcome()`]
+#[derive(Debug, thiserror::Error)]
+pub enum PersistBlockOutcomeError {
+    /// Unknown block root
+    #[error("Unknown block root")]
+    UnknownBlockRoot,
+    /// Storage item write error
+    #[error("Storage item write error")]
+    StorageItemWriteError {
+        /// Low-level error
+        #[from]
+        error: io::Error,
+    },
+}
+
+/// Error for [`ChainInfo::contract_slot()`]
+#[derive(Debug, thiserror::Error)]
+pub enum ReadContractSlotError {
+    /// Unknown block root
+    #[error("Unknown block root")]
+    UnknownBlockRoot,
+}
+
+/// Error for [`ChainInfo::mmr_proof()`]
+#[derive(Debug, thiserror::Error)]
+pub enum ReadMmrProofError {
+    /// Block number is above the best block
+    #[error("Block number {block_number} is above the best block number {best_block_number}")]
+    UnknownBlockNumber {
+        /// Requested block number
+        block_number: BlockNumber,
+        /// Best block number
+        best_block_number: BlockNumber,
+    },
+    /// Roots of some blocks are not available, which happens when blocks were pruned before their
+    /// roots were persisted
+    #[error("Roots of some blocks are not available")]
+    BlockRootsUnavailable,
+}
+
+/// Error for [`ChainInfoWrite::persist_contract_slots()`]
+#[derive(Debug, thiserror::Error)]
+pub enum PersistContractSlotsError {
+    /// Unknown block root
+    #[error("Unknown block root")]
+    UnknownBlockRoot,
     /// Storage item write error
     #[error("Storage item write error")]
     StorageItemWriteError {
@@ -139,6 +478,16 @@ pub enum PersistSegmentHeadersError {
         /// Segment index that was attempted to be inserted
         local_segment_index: LocalSegmentIndex,
     },
+    /// Segment header with the same segment index is already stored and has different contents,
+    /// stored segment headers can't be overwritten
+    #[error(
+        "Segment header with segment index {local_segment_index} is already stored and has \
+        different contents, can't overwrite it"
+    )]
+    SegmentHeaderMismatch {
+        /// Segment index that was attempted to be inserted
+        local_segment_index: LocalSegmentIndex,
+    },
     /// Storage item write error
     #[error("Storage item write error")]
     StorageItemWriteError {
@@ -218,6 +567,13 @@ where
     /// Best block header
     fn best_header(&self) -> Block::Header;
 
+    /// Blocks at this depth below the best block are confirmed and irreversible.
+    ///
+    /// Only confirmed blocks can be archived, so archiver must use this value rather than a
+    /// separately configured one (see [`ChainInfoWrite::retain_blocks_for_archiving()`] for the
+    /// other side of this contract).
+    fn block_confirmation_depth(&self) -> BlockNumber;
+
     /// Returns the best block header like [`Self::best_header()`] with additional block details
     fn best_header_with_details(&self) -> (Block::Header, BlockDetails);
 
@@ -228,6 +584,40 @@ where
         descendant_block_root: &BlockRoot,
     ) -> Option<Block::Header>;
 
+    /// Iterate over headers of ancestors of the descendant block, starting with its parent and
+    /// going back towards genesis.
+    ///
+    /// Follows parent roots, so it works for blocks on forks as well as on the canonical chain.
+    /// Iteration stops once the ancestor is no longer known (for example, when it was pruned).
+    /// Prefer this over repeated [`Self::ancestor_header()`] calls when walking through many
+    /// ancestors of the same block.
+    fn ancestors(
+        &self,
+        descendant_block_root: &BlockRoot,
+    ) -> impl Iterator<Item = Block::Header> + Send {
+        let mut maybe_block_root = self
+            .header(descendant_block_root)
+            .map(|header| header.header().prefix.parent_root);
+
+        iter::from_fn(move || {
+            let header = self.header(&maybe_block_root.take()?)?;
+            maybe_block_root.replace(header.header().prefix.parent_root);
+
+            Some(header)
+        })
+    }
+
+    /// Root of the block at `block_number` on the canonical chain (the chain of the best block)
+    fn canonical_root(&self, block_number: BlockNumber) -> Option<BlockRoot> {
+        self.canonical_header(block_number)
+            .map(|header| *header.header().root())
+    }
+
+    /// Header of the block at `block_number` on the canonical chain (the chain of the best block)
+    fn canonical_header(&self, block_number: BlockNumber) -> Option<Block::Header> {
+        self.ancestor_header(block_number, &self.best_root())
+    }
+
     /// Block header
     fn header(&self, block_root: &BlockRoot) -> Option<Block::Header>;
 
@@ -247,6 +637,44 @@ where
 
     /// Get segment headers that are expected to be included at specified block number
     fn segment_headers_for_block(&self, block_number: BlockNumber) -> Vec<SegmentHeader>;
+
+    /// Auxiliary data attached to a block in a specified namespace, see
+    /// [`ChainInfoWrite::persist_block_aux_data()`]
+    fn block_aux_data(
+        &self,
+        block_root: &BlockRoot,
+        namespace: BlockAuxDataNamespace,
+    ) -> Option<SharedAlignedBuffer>;
+
+    /// Execution outcome of a block, see [`ChainInfoWrite::persist_block_outcome()`]
+    fn block_outcome(&self, block_root: &BlockRoot) -> Option<BlockOutcome>;
+
+    /// Find a transaction by its hash in bodies of known blocks.
+    ///
+    /// Only works when the transaction index is enabled, returns `None` otherwise. The location in
+    /// the block of the canonical chain is returned if the transaction is included in multiple
+    /// blocks (on different forks). Transactions are no longer found once corresponding blocks are
+    /// pruned.
+    fn find_transaction(&self, tx_hash: &TransactionHash) -> Option<TransactionLocation>;
+
+    /// Contents of a contract slot as of the specified block, see
+    /// [`ChainInfoWrite::persist_contract_slots()`].
+    ///
+    /// Works for the best block, its ancestors and blocks on forks, as long as they were not
+    /// pruned yet. Returns `None` if the slot was not modified by the block or any of its
+    /// ancestors.
+    fn contract_slot(
+        &self,
+        block_root: &BlockRoot,
+        owner: &Address,
+        contract: &Address,
+    ) -> Result<Option<SharedAlignedBuffer>, ReadContractSlotError>;
+
+    /// Inclusion proof of the block at `block_number` on the canonical chain against the Merkle
+    /// Mountain Range of the best block (which includes the best block itself).
+    ///
+    /// Works for any block of the canonical chain, including blocks that were already pruned.
+    fn mmr_proof(&self, block_number: BlockNumber) -> Result<BlockMmrProof, ReadMmrProofError>;
 }
 
 /// [`ChainInfo`] extension for writing information
@@ -261,6 +689,18 @@ where
         block_details: BlockDetails,
     ) -> impl Future<Output = Result<(), PersistBlockError>> + Send;
 
+    /// Persist a batch of newly imported blocks.
+    ///
+    /// Blocks must be ordered by block number, each block must be a child of the previous one and
+    /// each of them must become the new best block, unlike [`Self::persist_block()`] forks are not
+    /// supported. The whole batch is validated before any block is inserted and is then inserted
+    /// at once, which is more efficient than persisting blocks one by one (during sync, for
+    /// example).
+    fn persist_blocks(
+        &self,
+        blocks: Vec<(Block, BlockDetails)>,
+    ) -> impl Future<Output = Result<(), PersistBlockError>> + Send;
+
     /// Persist segment headers.
     ///
     /// Multiple can be inserted for efficiency purposes.
@@ -268,6 +708,52 @@ where
         &self,
         segment_headers: Vec<SegmentHeader>,
     ) -> impl Future<Output = Result<(), PersistSegmentHeadersError>> + Send;
+
+    /// Persist small auxiliary data attached to a known block in a specified namespace.
+    ///
+    /// This allows components like consensus caches or indexes to persist per-block data without
+    /// managing their own files. Previously stored data in the same namespace is replaced. Data is
+    /// pruned together with the block it is attached to.
+    ///
+    /// Data can't be larger than [`MAX_BLOCK_AUX_DATA_SIZE`].
+    fn persist_block_aux_data(
+        &self,
+        block_root: &BlockRoot,
+        namespace: BlockAuxDataNamespace,
+        data: SharedAlignedBuffer,
+    ) -> impl Future<Output = Result<(), PersistBlockAuxDataError>> + Send;
+
+    /// Persist execution outcome of a known block.
+    ///
+    /// Outcome is stored alongside the block, such that execution results remain available for
+    /// RPC and light client proofs after the block was persisted. Previously stored outcome of the
+    /// same block is replaced. Outcome is pruned together with the block it is attached to.
+    fn persist_block_outcome(
+        &self,
+        block_root: &BlockRoot,
+        outcome: BlockOutcome,
+    ) -> impl Future<Output = Result<(), PersistBlockOutcomeError>> + Send;
+
+    /// Persist contract slots modified by a known block.
+    ///
+    /// Only modified slots need to be provided, slots that were not modified are inherited from
+    /// ancestor blocks. Slots persisted for the same block again replace previously persisted
+    /// contents. Contents of slots are retained after the block itself is pruned for as long as
+    /// they are not superseded by a newer block on the canonical chain, such that execution doesn't
+    /// need to carry the full state around.
+    fn persist_contract_slots(
+        &self,
+        block_root: &BlockRoot,
+        slots: StdArc<[ContractSlotState]>,
+    ) -> impl Future<Output = Result<(), PersistContractSlotsError>> + Send;
+
+    /// Retain blocks starting with `first_block_number` for archiving.
+    ///
+    /// Archiver calls this as it makes progress with the number of the last block of the last
+    /// archived segment, which it reads again after restart, such that blocks it still needs are
+    /// not pruned regardless of how many blocks are retained otherwise. Replaces the block number
+    /// from the previous call.
+    fn retain_blocks_for_archiving(&self, first_block_number: BlockNumber);
 }
 
 /// Beacon chain info
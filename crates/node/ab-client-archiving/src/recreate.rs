@@ -2,7 +2,7 @@
 
 use crate::task::encode_block;
 use ab_archiving::archiver::{Archiver, ArchiverInstantiationError, NewArchivedSegment};
-use ab_archiving::objects::BlockObject;
+use ab_archiving::objects::{BlockObjectCandidate, ObjectMappingPolicy};
 use ab_client_api::{ChainInfo, ReadBlockError};
 use ab_core_primitives::block::BlockNumber;
 use ab_core_primitives::block::header::GenericBlockHeader;
@@ -12,6 +12,7 @@ use ab_core_primitives::pieces::SegmentProof;
 use ab_core_primitives::segments::{SegmentHeader, SegmentPosition, SuperSegmentIndex};
 use ab_core_primitives::shard::ShardIndex;
 use ab_erasure_coding::ErasureCoding;
+use std::iter;
 use tokio::task::{JoinError, spawn_blocking};
 
 /// Re-create the genesis segment on demand.
@@ -79,7 +80,7 @@ pub struct RecreateSegmentSuperSegmentDetails {
 /// indicates where segment archiving should start.
 ///
 /// `extract_block_objects` allows extracting objects stored in blocks to translate them into global
-/// objects.
+/// objects, `object_mapping_policy` is applied to extracted objects.
 ///
 /// Returns `Ok(None)` if one of the segment blocks is already pruned.
 pub async fn recreate_segment<Block, CI, EBO>(
@@ -87,14 +88,16 @@ pub async fn recreate_segment<Block, CI, EBO>(
     chain_info: &CI,
     erasure_coding: ErasureCoding,
     super_segment_details: &RecreateSegmentSuperSegmentDetails,
+    object_mapping_policy: &ObjectMappingPolicy,
     mut extract_block_objects: EBO,
 ) -> Result<Option<NewArchivedSegment>, RecreateSegmentError>
 where
     Block: GenericOwnedBlock,
     CI: ChainInfo<Block>,
-    EBO: FnMut(&Block) -> Vec<BlockObject>,
+    EBO: FnMut(&Block) -> Vec<BlockObjectCandidate>,
 {
-    let best_block_root = chain_info.best_root();
+    let best_header = chain_info.best_header();
+    let best_block_root = *best_header.header().root();
 
     let (start_block_number, mut archiver) = if let Some(last_segment_header) = last_segment_header
     {
@@ -114,9 +117,10 @@ where
             Archiver::with_initial_state(
                 shard_index,
                 erasure_coding,
+                object_mapping_policy.clone(),
                 last_segment_header,
                 &encoded_block,
-                extract_block_objects(&block),
+                object_mapping_policy.apply(extract_block_objects(&block)),
             )?
         };
 
@@ -125,23 +129,37 @@ where
             archiver,
         )
     } else {
-        let best_header = chain_info.best_header();
-        let archiver = Archiver::new(best_header.header().prefix.shard_index, erasure_coding);
+        let archiver = Archiver::new(best_header.header().prefix.shard_index, erasure_coding)
+            .with_object_mapping_policy(object_mapping_policy.clone());
 
         (BlockNumber::ZERO, archiver)
     };
 
-    for block_number in start_block_number.. {
+    // Collect all roots in a single walk from the best block, blocks are then added to the archiver
+    // from the oldest to the newest
+    let mut blocks_to_archive = iter::once(best_header)
+        .chain(chain_info.ancestors(&best_block_root))
+        .map(|header| (header.header().prefix.number, *header.header().root()))
+        .take_while(|&(block_number, _)| block_number >= start_block_number)
+        .collect::<Vec<_>>();
+    blocks_to_archive.reverse();
+
+    if blocks_to_archive
+        .first()
+        .map(|&(block_number, _)| block_number)
+        != Some(start_block_number)
+    {
+        return Ok(None);
+    }
+
+    for (_block_number, block_root) in blocks_to_archive {
         let (encoded_block, block_objects) = {
-            let Some(block_root) = chain_info
-                .ancestor_header(block_number, &best_block_root)
-                .map(|header| *header.header().root())
-            else {
-                return Ok(None);
-            };
             let block = chain_info.block(&block_root).await?;
 
-            (encode_block(&block), extract_block_objects(&block))
+            (
+                encode_block(&block),
+                object_mapping_policy.apply(extract_block_objects(&block)),
+            )
         };
 
         let task_fut = spawn_blocking(move || {
@@ -13,31 +13,50 @@
 //! need to know what the correct archival history of the blockchain looks like through
 //! [`ChainInfo`]. For example, it is used during node sync and farmer plotting to verify pieces of
 //! archival history received from other network participants. Future segment header might also be
-//! already known in the case of syncing from DSN.
+//! already known in the case of syncing from DSN, in which case newly archived segment headers must
+//! match them, otherwise archiving stops with [`SegmentArchiverTaskError::HistoryDivergence`].
+//!
+//! Statistics of every archived segment (number of blocks and bytes included, time spent on
+//! encoding, erasure coding and waiting for acknowledgements) are reported as [`SegmentStats`]
+//! and, optionally, recorded in the metrics registry.
 //!
 //! [`encode_block`] and [`decode_block`] are symmetric encoding/decoding functions turning
 //! Blocks into bytes and back.
 
+mod metrics;
+mod read_ahead;
+
+use crate::task::metrics::SegmentArchiverMetrics;
+use crate::task::read_ahead::BlockReadAhead;
 use ab_aligned_buffer::SharedAlignedBuffer;
 use ab_archiving::archiver::{Archiver, ArchiverInstantiationError, NewArchivedSegment};
+use ab_archiving::objects::ObjectMappingPolicy;
 use ab_client_api::{ChainInfo, ChainInfoWrite, PersistSegmentHeadersError};
 use ab_client_consensus_common::{BlockImportingNotification, ConsensusConstants};
+use ab_client_notifications::{NotificationBus, Topic};
 use ab_core_primitives::block::body::owned::GenericOwnedBlockBody;
 use ab_core_primitives::block::header::GenericBlockHeader;
 use ab_core_primitives::block::header::owned::GenericOwnedBlockHeader;
 use ab_core_primitives::block::owned::GenericOwnedBlock;
 use ab_core_primitives::block::{BlockNumber, BlockRoot, GenericBlock};
-use ab_core_primitives::segments::{LocalSegmentIndex, RecordedHistorySegment, SegmentHeader};
+use ab_core_primitives::segments::{
+    ArchivedHistorySegment, LocalSegmentIndex, RecordedHistorySegment, SegmentHeader,
+};
 use ab_core_primitives::shard::RealShardKind;
 use ab_erasure_coding::ErasureCoding;
 use bytesize::ByteSize;
 use chacha20::ChaCha8Rng;
 use chacha20::rand_core::{Rng, SeedableRng};
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use futures::prelude::*;
+use futures::select;
+use prometheus_client::registry::Registry;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
-use std::time::Duration;
-use tracing::{debug, info, trace, warn};
+use std::time::{Duration, Instant};
+use std::{iter, mem};
+use tokio::sync::watch;
+use tracing::{debug, error, info, trace, warn};
 
 /// Do not wait for acknowledgements beyond this time limit
 const ACKNOWLEDGEMENT_TIMEOUT: Duration = Duration::from_mins(2);
@@ -54,16 +73,187 @@ const ACKNOWLEDGEMENT_TIMEOUT: Duration = Duration::from_mins(2);
 // const FINALIZATION_DEPTH_IN_SEGMENTS: SegmentIndex = SegmentIndex::from(5);
 
 /// Notification with a new archived segment that was just archived
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ArchivedSegmentNotification {
     /// Archived segment.
     pub archived_segment: Arc<NewArchivedSegment>,
     /// Sender that signified the fact of receiving an archived segment by farmer.
     ///
-    /// This must be used to send a message, or else the block import pipeline will get stuck.
+    /// This must be used to send a message (or dropped), or else the block import pipeline will
+    /// get stuck.
     pub acknowledgement_sender: mpsc::Sender<()>,
 }
 
+/// Topic of notifications about new archived segments
+#[derive(Debug)]
+pub struct NewSegmentTopic;
+
+impl Topic for NewSegmentTopic {
+    const NAME: &'static str = "new-segment";
+    type Message = ArchivedSegmentNotification;
+}
+
+/// Control message for the segment archiver task
+#[derive(Debug)]
+pub enum ArchiverControlMessage {
+    /// Re-initialize archiver from segment headers and blocks in [`ChainInfo`] the same way as it
+    /// is done on startup.
+    ///
+    /// Useful after blocks were inserted into the database in a special way (manually or by a
+    /// special sync mode), so that archiver doesn't need to wait for a block gap to be detected.
+    Reinitialize {
+        /// Receives the best archived block number once re-initialization is done, dropped if it
+        /// failed
+        result_sender: oneshot::Sender<BlockNumber>,
+    },
+}
+
+/// Progress of archiver catching up with blocks that were produced before it started (or after a
+/// gap in blockchain history)
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ArchiverProgress {
+    /// Whether archiver caught up with already produced blocks and archives new blocks as they are
+    /// imported
+    pub caught_up: bool,
+    /// The last block that was archived
+    pub current_block: BlockNumber,
+    /// The block archiver is catching up to
+    pub target_block: BlockNumber,
+    /// Number of segments produced during catch-up
+    pub segments_produced: u64,
+    /// Estimated time remaining until catch-up is finished, `None` if unknown
+    pub eta: Option<Duration>,
+}
+
+/// Statistics of archiving a single segment
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SegmentStats {
+    /// Index of the segment
+    pub segment_index: LocalSegmentIndex,
+    /// Number of blocks added to the archiver since the previous segment, the last of which
+    /// completed this segment.
+    ///
+    /// Can be zero when a single large block completed multiple segments at once.
+    pub blocks: u32,
+    /// Size of encoded blocks added to the archiver since the previous segment
+    pub bytes: u64,
+    /// Time spent encoding blocks added to the archiver since the previous segment
+    pub encoding_time: Duration,
+    /// Time spent erasure coding and committing to the segment
+    pub erasure_coding_time: Duration,
+    /// Time spent waiting for acknowledgement of the archived segment notification, `None` for
+    /// segments produced while catching up with already produced blocks (no notification is sent
+    /// for those)
+    pub acknowledgement_wait_time: Option<Duration>,
+}
+
+/// Accumulates statistics of the segment that is currently being assembled
+#[derive(Debug, Default)]
+struct SegmentStatsCollector {
+    blocks: u32,
+    bytes: u64,
+    encoding_time: Duration,
+}
+
+impl SegmentStatsCollector {
+    fn add_block(&mut self, bytes: usize, encoding_time: Duration) {
+        self.blocks = self.blocks.saturating_add(1);
+        self.bytes = self.bytes.saturating_add(bytes as u64);
+        self.encoding_time += encoding_time;
+    }
+
+    /// Produce statistics of a newly archived segment and reset accumulated values
+    fn finish(
+        &mut self,
+        segment_index: LocalSegmentIndex,
+        erasure_coding_time: Duration,
+    ) -> SegmentStats {
+        let Self {
+            blocks,
+            bytes,
+            encoding_time,
+        } = mem::take(self);
+
+        SegmentStats {
+            segment_index,
+            blocks,
+            bytes,
+            encoding_time,
+            erasure_coding_time,
+            acknowledgement_wait_time: None,
+        }
+    }
+}
+
+/// Reports statistics of archived segments to subscribers and metrics
+#[derive(Debug)]
+struct SegmentStatsReporter {
+    sender: watch::Sender<Option<SegmentStats>>,
+    metrics: Option<SegmentArchiverMetrics>,
+}
+
+impl SegmentStatsReporter {
+    fn report(&self, segment_stats: SegmentStats) {
+        debug!(?segment_stats, "Segment archived");
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&segment_stats);
+        }
+        self.sender.send_replace(Some(segment_stats));
+    }
+}
+
+/// Memory budget for blocks and segments held in flight by the archiver while catching up with
+/// already produced blocks.
+///
+/// Part of the budget is reserved for the segment that is being assembled and the archived segment
+/// produced from it, the rest is used for reading blocks ahead of archiving. Actual sizes of blocks
+/// that were read are accounted against the budget, blocks that are still being read are assumed
+/// to be as large as the largest block read so far. Only a few blocks are read concurrently
+/// regardless of the budget, but at least one block is always read.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CatchUpMemoryBudget {
+    bytes: u64,
+}
+
+impl Default for CatchUpMemoryBudget {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::MIN
+    }
+}
+
+impl CatchUpMemoryBudget {
+    /// Memory reserved for the segment that is being assembled and the archived segment produced
+    /// from it
+    const RESERVED: u64 = (RecordedHistorySegment::SIZE + ArchivedHistorySegment::SIZE) as u64;
+    /// The smallest budget, enough for reading one segment worth of blocks ahead
+    pub const MIN: Self = Self {
+        bytes: Self::RESERVED + RecordedHistorySegment::SIZE as u64,
+    };
+
+    /// Create a new budget with a specified number of bytes, values below [`Self::MIN`] are
+    /// rounded up
+    #[inline]
+    pub fn new(bytes: u64) -> Self {
+        Self {
+            bytes: bytes.max(Self::MIN.bytes),
+        }
+    }
+
+    /// Total budget in bytes
+    #[inline(always)]
+    pub fn bytes(self) -> u64 {
+        self.bytes
+    }
+
+    /// Part of the budget used for reading blocks ahead of archiving
+    #[inline(always)]
+    fn read_ahead_bytes(self) -> u64 {
+        self.bytes - Self::RESERVED
+    }
+}
+
 async fn find_last_archived_block<Block, CI>(
     chain_info: &CI,
     best_block_number_to_archive: BlockNumber,
@@ -202,6 +392,18 @@ pub enum SegmentArchiverTaskError {
         #[from]
         error: PersistSegmentHeadersError,
     },
+    /// Block confirmation depth of consensus constants doesn't match block confirmation depth of
+    /// chain info
+    #[error(
+        "Block confirmation depth of consensus constants {consensus} doesn't match block \
+        confirmation depth of chain info {chain_info}"
+    )]
+    BlockConfirmationDepthMismatch {
+        /// Block confirmation depth of consensus constants
+        consensus: BlockNumber,
+        /// Block confirmation depth of chain info
+        chain_info: BlockNumber,
+    },
     /// Attempt to switch to a different fork beyond archiving depth
     #[error(
         "Attempt to switch to a different fork beyond archiving depth: parent block root \
@@ -213,6 +415,19 @@ pub enum SegmentArchiverTaskError {
         /// Best archived block root
         best_archived_block_root: BlockRoot,
     },
+    /// Archived segment header diverges from an already known segment header at the same index
+    #[error(
+        "Archived segment header {} diverges from an already known segment header, the first \
+        differing field is {:?}, contributing blocks {}..={}",
+        .divergence.local_segment_index,
+        .divergence.first_differing_field,
+        .divergence.block_range.start(),
+        .divergence.block_range.end()
+    )]
+    HistoryDivergence {
+        /// Details of the divergence
+        divergence: Box<HistoryDivergence>,
+    },
     /// There was a gap in blockchain history, and the last contiguous series of blocks doesn't
     /// start with the archived segment
     #[error(
@@ -231,15 +446,157 @@ pub enum SegmentArchiverTaskError {
     },
 }
 
+/// Field of a segment header
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SegmentHeaderField {
+    /// [`SegmentHeader::root`]
+    Root,
+    /// [`SegmentHeader::prev_segment_header_hash`]
+    PrevSegmentHeaderHash,
+    /// Block number of [`SegmentHeader::last_archived_block`]
+    LastArchivedBlockNumber,
+    /// Archived progress of [`SegmentHeader::last_archived_block`]
+    LastArchivedBlockProgress,
+}
+
+/// Report of divergence between a segment header produced by the archiver and an already known
+/// segment header at the same index
+#[derive(Debug, Clone)]
+pub struct HistoryDivergence {
+    /// Local segment index
+    pub local_segment_index: LocalSegmentIndex,
+    /// Segment header that was already known
+    pub known_segment_header: SegmentHeader,
+    /// Segment header produced by the archiver
+    pub archived_segment_header: SegmentHeader,
+    /// The first field that differs between segment headers
+    pub first_differing_field: SegmentHeaderField,
+    /// Blocks that contributed to the archived segment, the first and the last blocks might have
+    /// been archived partially
+    pub block_range: RangeInclusive<BlockNumber>,
+}
+
+impl HistoryDivergence {
+    /// Returns `None` if segment headers are the same
+    fn new(
+        known_segment_header: SegmentHeader,
+        archived_segment_header: SegmentHeader,
+        previous_segment_header: Option<SegmentHeader>,
+    ) -> Option<Self> {
+        let first_differing_field = if known_segment_header.root != archived_segment_header.root {
+            SegmentHeaderField::Root
+        } else if known_segment_header.prev_segment_header_hash
+            != archived_segment_header.prev_segment_header_hash
+        {
+            SegmentHeaderField::PrevSegmentHeaderHash
+        } else if known_segment_header.last_archived_block.number()
+            != archived_segment_header.last_archived_block.number()
+        {
+            SegmentHeaderField::LastArchivedBlockNumber
+        } else if known_segment_header.last_archived_block.archived_progress
+            != archived_segment_header
+                .last_archived_block
+                .archived_progress
+        {
+            SegmentHeaderField::LastArchivedBlockProgress
+        } else {
+            return None;
+        };
+
+        let first_block_number =
+            previous_segment_header.map_or(BlockNumber::ZERO, |previous_segment_header| {
+                let last_archived_block = previous_segment_header.last_archived_block;
+                if last_archived_block.partial_archived().is_some() {
+                    last_archived_block.number()
+                } else {
+                    last_archived_block.number() + BlockNumber::ONE
+                }
+            });
+
+        Some(Self {
+            local_segment_index: archived_segment_header.index.as_inner(),
+            known_segment_header,
+            archived_segment_header,
+            first_differing_field,
+            block_range: first_block_number..=archived_segment_header.last_archived_block.number(),
+        })
+    }
+}
+
+/// Check newly archived segment headers against segment headers that are already known (for
+/// example, from DSN sync), such that divergent history is never persisted silently
+fn check_history_divergence<Block, CI>(
+    chain_info: &CI,
+    segment_headers: &[SegmentHeader],
+) -> Result<(), SegmentArchiverTaskError>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfo<Block>,
+{
+    for &archived_segment_header in segment_headers {
+        let local_segment_index = archived_segment_header.index.as_inner();
+        let Some(known_segment_header) = chain_info.get_segment_header(local_segment_index) else {
+            continue;
+        };
+        // Known segment headers are contiguous, so the previous one is known too
+        let previous_segment_header = local_segment_index
+            .checked_sub(LocalSegmentIndex::ONE)
+            .and_then(|local_segment_index| chain_info.get_segment_header(local_segment_index));
+
+        if let Some(divergence) = HistoryDivergence::new(
+            known_segment_header,
+            archived_segment_header,
+            previous_segment_header,
+        ) {
+            error!(
+                %local_segment_index,
+                first_differing_field = ?divergence.first_differing_field,
+                first_block_number = %divergence.block_range.start(),
+                last_block_number = %divergence.block_range.end(),
+                known_segment_header = ?divergence.known_segment_header,
+                archived_segment_header = ?divergence.archived_segment_header,
+                "Archived history diverges from already known segment header"
+            );
+
+            return Err(SegmentArchiverTaskError::HistoryDivergence {
+                divergence: Box::new(divergence),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+async fn read_block_to_archive<Block, CI>(
+    chain_info: &CI,
+    block_number: BlockNumber,
+    block_root: BlockRoot,
+) -> (BlockNumber, BlockRoot, Block)
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfo<Block>,
+{
+    let block = chain_info
+        .block(&block_root)
+        .await
+        .expect("All blocks since last archived must be present; qed");
+
+    (block_number, block_root, block)
+}
+
 struct InitializedArchiver {
     archiver: Archiver,
     best_archived_block: (BlockRoot, BlockNumber),
+    segment_stats: SegmentStatsCollector,
 }
 
 async fn initialize_archiver<Block, CI>(
     chain_info: &CI,
     block_confirmation_depth: BlockNumber,
     erasure_coding: ErasureCoding,
+    memory_budget: CatchUpMemoryBudget,
+    progress_sender: &watch::Sender<ArchiverProgress>,
+    segment_stats_reporter: &SegmentStatsReporter,
 ) -> Result<InitializedArchiver, SegmentArchiverTaskError>
 where
     Block: GenericOwnedBlock,
@@ -251,11 +608,13 @@ where
 
     let mut best_block_to_archive = best_block_number.saturating_sub(block_confirmation_depth);
 
-    if (best_block_to_archive..best_block_number).any(|block_number| {
-        chain_info
-            .ancestor_header(block_number, &best_block_root)
-            .is_none()
-    }) {
+    let num_ancestors_to_check = u64::from(best_block_number - best_block_to_archive) as usize;
+    if chain_info
+        .ancestors(&best_block_root)
+        .take(num_ancestors_to_check)
+        .count()
+        < num_ancestors_to_check
+    {
         // If there are blocks missing headers between best block to archive and best block of the
         // blockchain it means newer block was inserted in some special way and as such is by
         // definition valid, so we can simply assume that is our best block to archive instead
@@ -266,7 +625,18 @@ where
         find_last_archived_block(chain_info, best_block_to_archive, &best_block_root).await;
 
     let have_last_segment_header = maybe_last_archived_block.is_some();
+    // Blocks since the last archived block are read again after restart, archiving from genesis
+    // needs all blocks
+    chain_info.retain_blocks_for_archiving(
+        maybe_last_archived_block
+            .as_ref()
+            .map(|(last_segment_header, _last_archived_block)| {
+                last_segment_header.last_archived_block.number()
+            })
+            .unwrap_or_default(),
+    );
     let mut best_archived_block = None::<(BlockRoot, BlockNumber)>;
+    let mut segment_stats = SegmentStatsCollector::default();
 
     let mut archiver =
         if let Some((last_segment_header, last_archived_block)) = maybe_last_archived_block {
@@ -290,6 +660,7 @@ where
             Archiver::with_initial_state(
                 best_block_header.header().prefix.shard_index,
                 erasure_coding,
+                ObjectMappingPolicy::default(),
                 last_segment_header,
                 &last_archived_block_encoded,
                 Vec::new(),
@@ -327,17 +698,48 @@ where
                 blocks_to_archive_from, blocks_to_archive_to,
             );
 
-            for block_number_to_archive in blocks_to_archive_from..=blocks_to_archive_to {
-                let header = chain_info
-                    .ancestor_header(block_number_to_archive, &best_block_root)
-                    .expect("All blocks since last archived must be present; qed");
+            let started_at = Instant::now();
+            let mut segments_produced = 0;
+            progress_sender.send_replace(ArchiverProgress {
+                caught_up: false,
+                current_block: blocks_to_archive_from.saturating_sub(BlockNumber::ONE),
+                target_block: blocks_to_archive_to,
+                segments_produced,
+                eta: None,
+            });
 
-                let block = chain_info
-                    .block(&header.header().root())
-                    .await
-                    .expect("All blocks since last archived must be present; qed");
+            // Collect all roots in a single walk from the best block, blocks are then read from
+            // the oldest to the newest
+            let mut blocks_to_read = iter::once(best_block_header.clone())
+                .chain(chain_info.ancestors(&best_block_root))
+                .map(|header| (header.header().prefix.number, *header.header().root()))
+                .skip_while(|&(block_number, _)| block_number > blocks_to_archive_to)
+                .take_while(|&(block_number, _)| block_number >= blocks_to_archive_from)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                blocks_to_read.len() as u64,
+                u64::from(blocks_to_archive_to - blocks_to_archive_from) + 1,
+                "All blocks since last archived must be present; qed"
+            );
+            blocks_to_read.reverse();
+            let mut block_read_ahead = BlockReadAhead::new(
+                blocks_to_read.into_iter(),
+                |(block_number, block_root)| {
+                    read_block_to_archive(chain_info, block_number, block_root)
+                },
+                |(_block_number, _block_root, block): &(BlockNumber, BlockRoot, Block)| {
+                    u64::from(block.header().buffer().len())
+                        + u64::from(block.body().buffer().len())
+                },
+                memory_budget.read_ahead_bytes(),
+            );
 
+            while let Some((block_number_to_archive, block_root, block)) =
+                block_read_ahead.next().await
+            {
+                let encoding_started_at = Instant::now();
                 let encoded_block = encode_block(&block);
+                segment_stats.add_block(encoded_block.len(), encoding_started_at.elapsed());
 
                 debug!(
                     "Encoded block {} has size of {}",
@@ -345,32 +747,75 @@ where
                     ByteSize::b(encoded_block.len() as u64).display().iec(),
                 );
 
+                let erasure_coding_started_at = Instant::now();
                 let block_outcome = archiver
                     .add_block(encoded_block, Vec::new())
                     .expect("Block is never empty and doesn't exceed u32; qed");
+                let erasure_coding_time = per_segment_erasure_coding_time(
+                    erasure_coding_started_at.elapsed(),
+                    block_outcome.archived_segments.len(),
+                );
                 let new_segment_headers: Vec<SegmentHeader> = block_outcome
                     .archived_segments
                     .iter()
                     .map(|archived_segment| archived_segment.segment_header)
                     .collect();
 
-                if !new_segment_headers.is_empty() {
+                segments_produced += new_segment_headers.len() as u64;
+                for segment_header in &new_segment_headers {
+                    segment_stats_reporter.report(
+                        segment_stats.finish(segment_header.index.as_inner(), erasure_coding_time),
+                    );
+                }
+
+                if let Some(last_segment_header) = new_segment_headers.last().copied() {
+                    check_history_divergence(chain_info, &new_segment_headers)?;
                     chain_info
                         .persist_segment_headers(new_segment_headers)
                         .await?;
+                    chain_info.retain_blocks_for_archiving(
+                        last_segment_header.last_archived_block.number(),
+                    );
                 }
 
                 if block_number_to_archive == blocks_to_archive_to {
-                    best_archived_block.replace((*header.header().root(), block_number_to_archive));
+                    best_archived_block.replace((block_root, block_number_to_archive));
                 }
+
+                let archived_blocks =
+                    u64::from(block_number_to_archive - blocks_to_archive_from) + 1;
+                let remaining_blocks = u64::from(blocks_to_archive_to - block_number_to_archive);
+                progress_sender.send_replace(ArchiverProgress {
+                    caught_up: false,
+                    current_block: block_number_to_archive,
+                    target_block: blocks_to_archive_to,
+                    segments_produced,
+                    eta: Some(
+                        started_at
+                            .elapsed()
+                            .mul_f64(remaining_blocks as f64 / archived_blocks as f64),
+                    ),
+                });
             }
         }
     }
 
+    let (best_archived_block_root, best_archived_block_number) =
+        best_archived_block.expect("Must always set if there is no logical error; qed");
+    progress_sender.send_modify(|progress| {
+        *progress = ArchiverProgress {
+            caught_up: true,
+            current_block: best_archived_block_number,
+            target_block: best_archived_block_number,
+            segments_produced: progress.segments_produced,
+            eta: None,
+        };
+    });
+
     Ok(InitializedArchiver {
         archiver,
-        best_archived_block: best_archived_block
-            .expect("Must always set if there is no logical error; qed"),
+        best_archived_block: (best_archived_block_root, best_archived_block_number),
+        segment_stats,
     })
 }
 
@@ -388,47 +833,91 @@ where
 /// the last shutdown and continue incrementally archiving blockchain history from there.
 ///
 /// Archiving is triggered by block importing notification (`block_importing_notification_receiver`)
-/// and tries to archive the block at [`ConsensusConstants::block_confirmation_depth`] depth from
-/// the block being imported. Block importing will then wait for archiver to acknowledge processing,
-/// which is necessary for ensuring that when the next block is imported, the newly archived segment
-/// is already available deterministically.
+/// and tries to archive the block at [`ChainInfo::block_confirmation_depth()`] depth from the block
+/// being imported, which must match [`ConsensusConstants::block_confirmation_depth`]. Blocks that
+/// archiver still needs are retained by [`ChainInfoWrite::retain_blocks_for_archiving()`], such
+/// that they are not pruned regardless of how many blocks are retained otherwise. Block importing
+/// will then wait for archiver to acknowledge processing, which is necessary for ensuring that when
+/// the next block is imported, the newly archived segment is already available deterministically.
+///
+/// Once a new segment is archived, a notification will be published to [`NewSegmentTopic`] of
+/// `notification_bus` and archiver will be paused until all subscribers have provided an
+/// acknowledgement for it (or a very generous timeout has passed).
 ///
-/// Once a new segment is archived, a notification (`archived_segment_notification_sender`) will be
-/// sent and archiver will be paused until all receivers have provided an acknowledgement for it (or
-/// a very generous timeout has passed).
+/// Archiver can be controlled with [`ArchiverControlMessage`]s sent to
+/// `archiver_control_receiver`, which are processed between blocks.
+///
+/// Returned watch channel receivers report the progress of archiving of already produced blocks,
+/// which can take a long time after restart or when a gap in blockchain history is encountered,
+/// and statistics of the last archived segment. Memory used while archiving already produced
+/// blocks is limited by `catch_up_memory_budget`.
+///
+/// Segment statistics are additionally recorded as metrics if `registry` is provided.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "Explicit inputs of the archiver task"
+)]
 pub async fn create_segment_archiver_task<Block, CI>(
     chain_info: CI,
     mut block_importing_notification_receiver: mpsc::Receiver<BlockImportingNotification>,
-    mut archived_segment_notification_sender: mpsc::Sender<ArchivedSegmentNotification>,
+    mut archiver_control_receiver: mpsc::Receiver<ArchiverControlMessage>,
+    notification_bus: NotificationBus,
     consensus_constants: ConsensusConstants,
     erasure_coding: ErasureCoding,
+    catch_up_memory_budget: CatchUpMemoryBudget,
+    registry: Option<&mut Registry>,
 ) -> Result<
-    impl Future<Output = Result<(), SegmentArchiverTaskError>> + Send + 'static,
+    (
+        impl Future<Output = Result<(), SegmentArchiverTaskError>> + Send + 'static,
+        watch::Receiver<ArchiverProgress>,
+        watch::Receiver<Option<SegmentStats>>,
+    ),
     SegmentArchiverTaskError,
 >
 where
     Block: GenericOwnedBlock,
     CI: ChainInfoWrite<Block> + 'static,
 {
+    let block_confirmation_depth = chain_info.block_confirmation_depth();
+    if block_confirmation_depth != consensus_constants.block_confirmation_depth {
+        return Err(SegmentArchiverTaskError::BlockConfirmationDepthMismatch {
+            consensus: consensus_constants.block_confirmation_depth,
+            chain_info: block_confirmation_depth,
+        });
+    }
+
+    let (progress_sender, progress_receiver) = watch::channel(ArchiverProgress::default());
+    let (segment_stats_sender, segment_stats_receiver) = watch::channel(None);
+    let segment_stats_reporter = SegmentStatsReporter {
+        sender: segment_stats_sender,
+        metrics: registry.map(SegmentArchiverMetrics::new),
+    };
+
     let maybe_archiver = if chain_info.last_segment_header().is_none() {
         let initialize_archiver_fut = initialize_archiver(
             &chain_info,
-            consensus_constants.block_confirmation_depth,
+            block_confirmation_depth,
             erasure_coding.clone(),
+            catch_up_memory_budget,
+            &progress_sender,
+            &segment_stats_reporter,
         );
         Some(initialize_archiver_fut.await?)
     } else {
         None
     };
 
-    Ok(async move {
+    let archiver_task = async move {
         let archiver = if let Some(archiver) = maybe_archiver {
             archiver
         } else {
             let initialize_archiver_fut = initialize_archiver(
                 &chain_info,
-                consensus_constants.block_confirmation_depth,
+                block_confirmation_depth,
                 erasure_coding.clone(),
+                catch_up_memory_budget,
+                &progress_sender,
+                &segment_stats_reporter,
             );
             initialize_archiver_fut.await?
         };
@@ -436,15 +925,61 @@ where
         let InitializedArchiver {
             mut archiver,
             best_archived_block,
+            mut segment_stats,
         } = archiver;
         let (mut best_archived_block_root, mut best_archived_block_number) = best_archived_block;
 
-        while let Some(block_importing_notification) =
-            block_importing_notification_receiver.next().await
-        {
+        loop {
+            let block_importing_notification = select! {
+                maybe_block_importing_notification =
+                    block_importing_notification_receiver.next() => {
+                    let Some(block_importing_notification) = maybe_block_importing_notification
+                    else {
+                        break;
+                    };
+
+                    block_importing_notification
+                }
+                maybe_control_message = archiver_control_receiver.next() => {
+                    let Some(control_message) = maybe_control_message else {
+                        // Nobody can send control messages anymore, terminated stream is not
+                        // polled again
+                        continue;
+                    };
+
+                    match control_message {
+                        ArchiverControlMessage::Reinitialize { result_sender } => {
+                            info!("Re-initializing archiver on request");
+
+                            let initialize_archiver_fut = initialize_archiver(
+                                &chain_info,
+                                block_confirmation_depth,
+                                erasure_coding.clone(),
+                                catch_up_memory_budget,
+                                &progress_sender,
+                                &segment_stats_reporter,
+                            );
+                            InitializedArchiver {
+                                archiver,
+                                best_archived_block: (
+                                    best_archived_block_root,
+                                    best_archived_block_number,
+                                ),
+                                segment_stats,
+                            } = initialize_archiver_fut.await?;
+
+                            info!(%best_archived_block_number, "Archiver re-initialized");
+                            let _: Result<(), _> = result_sender.send(best_archived_block_number);
+                        }
+                    }
+
+                    continue;
+                }
+            };
+
             let importing_block_number = block_importing_notification.block_number;
             let Some(block_number_to_archive) =
-                importing_block_number.checked_sub(consensus_constants.block_confirmation_depth)
+                importing_block_number.checked_sub(block_confirmation_depth)
             else {
                 // Too early to archive blocks
                 continue;
@@ -486,12 +1021,16 @@ where
             if best_archived_block_number + BlockNumber::ONE != block_number_to_archive {
                 let initialize_archiver_fut = initialize_archiver(
                     &chain_info,
-                    consensus_constants.block_confirmation_depth,
+                    block_confirmation_depth,
                     erasure_coding.clone(),
+                    catch_up_memory_budget,
+                    &progress_sender,
+                    &segment_stats_reporter,
                 );
                 InitializedArchiver {
                     archiver,
                     best_archived_block: (best_archived_block_root, best_archived_block_number),
+                    segment_stats,
                 } = initialize_archiver_fut.await?;
 
                 if best_archived_block_number + BlockNumber::ONE == block_number_to_archive {
@@ -521,23 +1060,35 @@ where
             (best_archived_block_root, best_archived_block_number) = archive_block(
                 &mut archiver,
                 &chain_info,
-                &mut archived_segment_notification_sender,
+                &notification_bus,
+                &mut segment_stats,
+                &segment_stats_reporter,
                 best_archived_block_root,
                 block_number_to_archive,
                 &best_block_root,
             )
             .await?;
+
+            progress_sender.send_modify(|progress| {
+                progress.current_block = best_archived_block_number;
+                progress.target_block = best_archived_block_number;
+            });
         }
 
         Ok(())
-    })
+    };
+
+    Ok((archiver_task, progress_receiver, segment_stats_receiver))
 }
 
 /// Tries to archive `block_number` and returns new (or old if not changed) best archived block
+#[expect(clippy::too_many_arguments, reason = "Internal API")]
 async fn archive_block<Block, CI>(
     archiver: &mut Archiver,
     chain_info: &CI,
-    archived_segment_notification_sender: &mut mpsc::Sender<ArchivedSegmentNotification>,
+    notification_bus: &NotificationBus,
+    segment_stats: &mut SegmentStatsCollector,
+    segment_stats_reporter: &SegmentStatsReporter,
     best_archived_block_root: BlockRoot,
     block_number_to_archive: BlockNumber,
     best_block_root: &BlockRoot,
@@ -567,33 +1118,57 @@ where
 
     debug!("Archiving block {block_number_to_archive} ({block_root_to_archive})");
 
+    let encoding_started_at = Instant::now();
     let encoded_block = encode_block(&block);
+    segment_stats.add_block(encoded_block.len(), encoding_started_at.elapsed());
     debug!(
         "Encoded block {block_number_to_archive} has size of {}",
         ByteSize::b(encoded_block.len() as u64).display().iec(),
     );
 
+    let erasure_coding_started_at = Instant::now();
     let block_outcome = archiver
         .add_block(encoded_block, Vec::new())
         .expect("Block is never empty and doesn't exceed u32; qed");
+    let erasure_coding_time = per_segment_erasure_coding_time(
+        erasure_coding_started_at.elapsed(),
+        block_outcome.archived_segments.len(),
+    );
     for archived_segment in block_outcome.archived_segments {
         let segment_header = archived_segment.segment_header;
+        let mut new_segment_stats =
+            segment_stats.finish(segment_header.index.as_inner(), erasure_coding_time);
 
+        check_history_divergence(chain_info, &[segment_header])?;
         chain_info
             .persist_segment_headers(vec![segment_header])
             .await?;
+        chain_info.retain_blocks_for_archiving(segment_header.last_archived_block.number());
+
+        let acknowledgement_wait_time =
+            send_archived_segment_notification(notification_bus, archived_segment).await;
 
-        send_archived_segment_notification(archived_segment_notification_sender, archived_segment)
-            .await;
+        new_segment_stats.acknowledgement_wait_time = Some(acknowledgement_wait_time);
+        segment_stats_reporter.report(new_segment_stats);
     }
 
     Ok((block_root_to_archive, block_number_to_archive))
 }
 
+/// Time spent by the archiver on a block, split evenly between segments it produced.
+///
+/// Segment production (erasure coding and commitments) dominates the cost of adding a block to the
+/// archiver when segments are produced.
+fn per_segment_erasure_coding_time(add_block_time: Duration, num_segments: usize) -> Duration {
+    add_block_time / u32::try_from(num_segments.max(1)).unwrap_or(u32::MAX)
+}
+
+/// Publish archived segment notification and wait for acknowledgements, returns time spent waiting
 async fn send_archived_segment_notification(
-    archived_segment_notification_sender: &mut mpsc::Sender<ArchivedSegmentNotification>,
+    notification_bus: &NotificationBus,
     archived_segment: NewArchivedSegment,
-) {
+) -> Duration {
+    let started_at = Instant::now();
     let segment_index = archived_segment.segment_header.index;
     let (acknowledgement_sender, mut acknowledgement_receiver) = mpsc::channel(1);
     // Keep `archived_segment` around until all acknowledgements are received since some receivers
@@ -604,15 +1179,11 @@ async fn send_archived_segment_notification(
         acknowledgement_sender,
     };
 
-    if let Err(error) = archived_segment_notification_sender
-        .send(archived_segment_notification)
-        .await
-    {
-        warn!(
-            %error,
-            "Failed to send archived segment notification"
-        );
-    }
+    // Subscribers receive clones of the acknowledgement sender, the original is dropped once
+    // published
+    notification_bus
+        .publish::<NewSegmentTopic>(archived_segment_notification)
+        .await;
 
     let wait_fut = async {
         while acknowledgement_receiver.next().await.is_some() {
@@ -632,4 +1203,6 @@ async fn send_archived_segment_notification(
             regardless"
         );
     }
+
+    started_at.elapsed()
 }
@@ -0,0 +1,106 @@
+//! Metrics for segment archiver
+
+use crate::task::SegmentStats;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::histogram::{Histogram, exponential_buckets};
+use prometheus_client::registry::{Registry, Unit};
+use std::sync::atomic::AtomicU64;
+
+/// Metrics for segment archiver
+#[derive(Debug)]
+pub(super) struct SegmentArchiverMetrics {
+    segment_encoding_time: Histogram,
+    segment_erasure_coding_time: Histogram,
+    segment_acknowledgement_wait_time: Histogram,
+    segments_archived: Counter<u64, AtomicU64>,
+    blocks_archived: Counter<u64, AtomicU64>,
+    bytes_archived: Counter<u64, AtomicU64>,
+}
+
+impl SegmentArchiverMetrics {
+    /// Create a new instance
+    pub(super) fn new(registry: &mut Registry) -> Self {
+        let registry = registry.sub_registry_with_prefix("archiver");
+
+        let segment_encoding_time = Histogram::new(exponential_buckets(0.001, 2.0, 15));
+        registry.register_with_unit(
+            "segment_encoding_time",
+            "Time spent encoding blocks included in a segment",
+            Unit::Seconds,
+            segment_encoding_time.clone(),
+        );
+
+        let segment_erasure_coding_time = Histogram::new(exponential_buckets(0.001, 2.0, 15));
+        registry.register_with_unit(
+            "segment_erasure_coding_time",
+            "Time spent erasure coding and committing to a segment",
+            Unit::Seconds,
+            segment_erasure_coding_time.clone(),
+        );
+
+        let segment_acknowledgement_wait_time = Histogram::new(exponential_buckets(0.001, 2.0, 18));
+        registry.register_with_unit(
+            "segment_acknowledgement_wait_time",
+            "Time spent waiting for acknowledgement of an archived segment",
+            Unit::Seconds,
+            segment_acknowledgement_wait_time.clone(),
+        );
+
+        let segments_archived = Counter::default();
+        registry.register_with_unit(
+            "segments_archived_counter",
+            "Number of archived segments",
+            Unit::Other("Segments".to_string()),
+            segments_archived.clone(),
+        );
+
+        let blocks_archived = Counter::default();
+        registry.register_with_unit(
+            "blocks_archived_counter",
+            "Number of blocks added to the archiver",
+            Unit::Other("Blocks".to_string()),
+            blocks_archived.clone(),
+        );
+
+        let bytes_archived = Counter::default();
+        registry.register_with_unit(
+            "bytes_archived_counter",
+            "Size of encoded blocks added to the archiver",
+            Unit::Bytes,
+            bytes_archived.clone(),
+        );
+
+        Self {
+            segment_encoding_time,
+            segment_erasure_coding_time,
+            segment_acknowledgement_wait_time,
+            segments_archived,
+            blocks_archived,
+            bytes_archived,
+        }
+    }
+
+    /// Record statistics of a newly archived segment
+    pub(super) fn record(&self, segment_stats: &SegmentStats) {
+        let SegmentStats {
+            segment_index: _,
+            blocks,
+            bytes,
+            encoding_time,
+            erasure_coding_time,
+            acknowledgement_wait_time,
+        } = *segment_stats;
+
+        self.segment_encoding_time
+            .observe(encoding_time.as_secs_f64());
+        self.segment_erasure_coding_time
+            .observe(erasure_coding_time.as_secs_f64());
+        if let Some(acknowledgement_wait_time) = acknowledgement_wait_time {
+            self.segment_acknowledgement_wait_time
+                .observe(acknowledgement_wait_time.as_secs_f64());
+        }
+        self.segments_archived.inc();
+        self.blocks_archived.inc_by(u64::from(blocks));
+        self.bytes_archived.inc_by(bytes);
+    }
+}
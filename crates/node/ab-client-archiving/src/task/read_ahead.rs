@@ -0,0 +1,112 @@
+//! Reading of blocks ahead of archiving within a memory budget, see [`BlockReadAhead`].
+
+#[cfg(test)]
+mod tests;
+
+use futures::future::{Join, Ready, join, ready};
+use futures::stream::FuturesUnordered;
+use futures::{Future, StreamExt};
+use std::collections::BTreeMap;
+use std::mem;
+
+/// Max number of blocks read concurrently, regardless of the memory budget
+pub(super) const MAX_CONCURRENT_BLOCK_READS: usize = 8;
+
+/// Reads blocks ahead of archiving, returning them in the original order.
+///
+/// Memory is accounted using actual sizes of blocks that were already read, including blocks that
+/// wait for earlier blocks to be read and the block returned by the last [`Self::next()`] call
+/// (until the next call). Reads in flight are assumed to be as large as the largest block read so
+/// far (or the whole budget before the first block is read). New reads are only started while
+/// the total fits into the budget and there are fewer than [`MAX_CONCURRENT_BLOCK_READS`] reads in
+/// flight, but at least one block is always read.
+pub(super) struct BlockReadAhead<Items, Read, Fut, Size>
+where
+    Fut: Future,
+{
+    items: Items,
+    read: Read,
+    size: Size,
+    read_ahead_bytes: u64,
+    reads: FuturesUnordered<Join<Ready<u64>, Fut>>,
+    /// Blocks that were read, but not returned yet, with their sizes
+    ready: BTreeMap<u64, (Fut::Output, u64)>,
+    next_read_index: u64,
+    next_return_index: u64,
+    /// Total size of blocks in `ready` and the last returned block
+    held_bytes: u64,
+    returned_block_size: u64,
+    max_block_size: Option<u64>,
+}
+
+impl<Items, Read, Fut, Size> BlockReadAhead<Items, Read, Fut, Size>
+where
+    Items: Iterator,
+    Read: FnMut(Items::Item) -> Fut,
+    Fut: Future,
+    Size: Fn(&Fut::Output) -> u64,
+{
+    /// Create a new instance that reads `items` with `read`, `size` returns the size of the block
+    /// in memory
+    pub(super) fn new(items: Items, read: Read, size: Size, read_ahead_bytes: u64) -> Self {
+        Self {
+            items,
+            read,
+            size,
+            read_ahead_bytes,
+            reads: FuturesUnordered::new(),
+            ready: BTreeMap::new(),
+            next_read_index: 0,
+            next_return_index: 0,
+            held_bytes: 0,
+            returned_block_size: 0,
+            max_block_size: None,
+        }
+    }
+
+    /// Next block, `None` once all blocks were returned
+    pub(super) async fn next(&mut self) -> Option<Fut::Output> {
+        // Block returned last time is no longer held by the caller
+        self.held_bytes -= mem::take(&mut self.returned_block_size);
+
+        loop {
+            while self.can_start_read()
+                && let Some(item) = self.items.next()
+            {
+                self.reads
+                    .push(join(ready(self.next_read_index), (self.read)(item)));
+                self.next_read_index += 1;
+            }
+
+            if let Some((block, size)) = self.ready.remove(&self.next_return_index) {
+                self.next_return_index += 1;
+                // Still accounted in `held_bytes` until the next call
+                self.returned_block_size = size;
+                return Some(block);
+            }
+
+            let (index, block) = self.reads.next().await?;
+            let size = (self.size)(&block);
+            self.max_block_size = Some(self.max_block_size.unwrap_or_default().max(size));
+            self.held_bytes += size;
+            self.ready.insert(index, (block, size));
+        }
+    }
+
+    fn can_start_read(&self) -> bool {
+        let reads_in_flight = self.reads.len();
+        if reads_in_flight == 0 && self.ready.is_empty() {
+            // Always make progress
+            return true;
+        }
+        if reads_in_flight >= MAX_CONCURRENT_BLOCK_READS {
+            return false;
+        }
+
+        let estimated_block_size = self.max_block_size.unwrap_or(self.read_ahead_bytes);
+        let estimated_in_flight_bytes =
+            (reads_in_flight as u64 + 1).saturating_mul(estimated_block_size);
+
+        self.held_bytes.saturating_add(estimated_in_flight_bytes) <= self.read_ahead_bytes
+    }
+}
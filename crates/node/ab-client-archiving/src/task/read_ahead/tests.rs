@@ -0,0 +1,113 @@
+use crate::task::read_ahead::{BlockReadAhead, MAX_CONCURRENT_BLOCK_READS};
+use futures::executor::block_on;
+use futures::future::{FutureExt, ready};
+use std::cell::Cell;
+
+/// Tracks bytes of blocks that are alive and the peak value
+#[derive(Default)]
+struct MemoryTracker {
+    live_bytes: Cell<u64>,
+    peak_bytes: Cell<u64>,
+    reads_in_flight: Cell<usize>,
+    peak_reads_in_flight: Cell<usize>,
+}
+
+struct Block<'a> {
+    index: usize,
+    size: u64,
+    tracker: &'a MemoryTracker,
+}
+
+impl Drop for Block<'_> {
+    fn drop(&mut self) {
+        self.tracker
+            .live_bytes
+            .set(self.tracker.live_bytes.get() - self.size);
+    }
+}
+
+fn read_all(sizes: &[u64], read_ahead_bytes: u64) -> MemoryTracker {
+    let tracker = MemoryTracker::default();
+
+    block_on(async {
+        let mut read_ahead = BlockReadAhead::new(
+            sizes.iter().copied().enumerate(),
+            |(index, size)| {
+                let tracker = &tracker;
+                tracker
+                    .reads_in_flight
+                    .set(tracker.reads_in_flight.get() + 1);
+                tracker.peak_reads_in_flight.set(
+                    tracker
+                        .peak_reads_in_flight
+                        .get()
+                        .max(tracker.reads_in_flight.get()),
+                );
+
+                // Block is only allocated once read is polled
+                ready(()).map(move |()| {
+                    tracker
+                        .reads_in_flight
+                        .set(tracker.reads_in_flight.get() - 1);
+                    tracker.live_bytes.set(tracker.live_bytes.get() + size);
+                    tracker
+                        .peak_bytes
+                        .set(tracker.peak_bytes.get().max(tracker.live_bytes.get()));
+
+                    Block {
+                        index,
+                        size,
+                        tracker,
+                    }
+                })
+            },
+            |block: &Block<'_>| block.size,
+            read_ahead_bytes,
+        );
+
+        let mut next_index = 0;
+        while let Some(block) = read_ahead.next().await {
+            assert_eq!(block.index, next_index);
+            assert_eq!(block.size, sizes[next_index]);
+            next_index += 1;
+        }
+        assert_eq!(next_index, sizes.len());
+    });
+
+    assert_eq!(tracker.live_bytes.get(), 0);
+    tracker
+}
+
+#[test]
+fn limit_respected() {
+    let tracker = read_all(&[100; 50], 1_000);
+    assert!(tracker.peak_bytes.get() <= 1_000);
+    // Budget is actually used for reading ahead
+    assert!(tracker.peak_reads_in_flight.get() > 1);
+    assert!(tracker.peak_reads_in_flight.get() <= MAX_CONCURRENT_BLOCK_READS);
+}
+
+#[test]
+fn concurrency_limit_respected() {
+    // Budget is large enough for all blocks, but only a few reads are allowed at a time
+    let tracker = read_all(&[1; 50], u64::MAX);
+    assert_eq!(
+        tracker.peak_reads_in_flight.get(),
+        MAX_CONCURRENT_BLOCK_READS
+    );
+    assert!(tracker.peak_bytes.get() <= MAX_CONCURRENT_BLOCK_READS as u64 * 2);
+}
+
+#[test]
+fn large_blocks_read_one_at_a_time() {
+    // Blocks larger than the whole budget are still read, but one at a time
+    let tracker = read_all(&[2_000, 100, 2_000, 100], 1_000);
+    assert_eq!(tracker.peak_reads_in_flight.get(), 1);
+    assert_eq!(tracker.peak_bytes.get(), 2_000);
+}
+
+#[test]
+fn empty() {
+    let tracker = read_all(&[], 1_000);
+    assert_eq!(tracker.peak_reads_in_flight.get(), 0);
+}
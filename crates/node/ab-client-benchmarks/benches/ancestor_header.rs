@@ -0,0 +1,69 @@
+#![expect(incomplete_features, reason = "generic_const_exprs")]
+// TODO: This feature is not actually used in this crate, but is added as a workaround for
+//  https://github.com/rust-lang/rust/issues/141492
+#![feature(generic_const_exprs)]
+
+use ab_client_api::ChainInfo;
+use ab_client_benchmarks::{BlockPipeline, BlockPipelineOptions};
+use ab_core_primitives::block::BlockNumber;
+use ab_erasure_coding::ErasureCoding;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use futures::executor::block_on;
+use std::hint::black_box;
+
+/// Number of blocks in the database (excluding genesis)
+const NUM_BLOCKS: u64 = 100;
+const BLOCK_CONFIRMATION_DEPTH: BlockNumber = BlockNumber::from(10);
+
+/// Reads all ancestors of a block one by one, like archiver does during initialization
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut pipeline = block_on(BlockPipeline::new(
+        BlockPipelineOptions {
+            num_transactions: 0,
+            block_confirmation_depth: BLOCK_CONFIRMATION_DEPTH,
+        },
+        ErasureCoding::new(),
+    ))
+    .unwrap();
+    for _ in 0..NUM_BLOCKS {
+        block_on(pipeline.process_block()).unwrap();
+    }
+
+    let database = pipeline.database();
+    let best_header = database.best_header();
+    let best_header = best_header.header();
+    let best_root = *best_header.root();
+    let parent_root = best_header.prefix.parent_root;
+
+    let mut group = c.benchmark_group("ancestor-header");
+    group.throughput(Throughput::Elements(NUM_BLOCKS));
+
+    // Ancestors of the best block take the fast path
+    group.bench_function("best-block", |b| {
+        b.iter(|| {
+            for block_number in 0..NUM_BLOCKS {
+                black_box(
+                    database
+                        .ancestor_header(BlockNumber::from(block_number), black_box(&best_root)),
+                );
+            }
+        });
+    });
+
+    // Ancestors of any other block require looking up the descendant first
+    group.bench_function("parent-of-best-block", |b| {
+        b.iter(|| {
+            for block_number in 0..NUM_BLOCKS {
+                black_box(
+                    database
+                        .ancestor_header(BlockNumber::from(block_number), black_box(&parent_root)),
+                );
+            }
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+#![expect(incomplete_features, reason = "generic_const_exprs")]
+// TODO: This feature is not actually used in this crate, but is added as a workaround for
+//  https://github.com/rust-lang/rust/issues/141492
+#![feature(generic_const_exprs)]
+
+use ab_client_benchmarks::{BlockPipeline, BlockPipelineOptions};
+use ab_core_primitives::block::BlockNumber;
+use ab_erasure_coding::ErasureCoding;
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use futures::executor::block_on;
+
+/// Number of blocks processed in each iteration
+const NUM_BLOCKS: u64 = 30;
+const BLOCK_CONFIRMATION_DEPTH: BlockNumber = BlockNumber::from(10);
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let erasure_coding = ErasureCoding::new();
+
+    let mut group = c.benchmark_group("block-pipeline");
+    group.throughput(Throughput::Elements(NUM_BLOCKS));
+
+    for num_transactions in [0, 100, 1_000, 10_000] {
+        group.bench_with_input(
+            BenchmarkId::new("transactions", num_transactions),
+            &num_transactions,
+            |b, &num_transactions| {
+                b.iter_batched(
+                    || {
+                        block_on(BlockPipeline::new(
+                            BlockPipelineOptions {
+                                num_transactions,
+                                block_confirmation_depth: BLOCK_CONFIRMATION_DEPTH,
+                            },
+                            erasure_coding.clone(),
+                        ))
+                        .unwrap()
+                    },
+                    |mut pipeline| {
+                        block_on(async {
+                            for _ in 0..NUM_BLOCKS {
+                                pipeline.process_block().await.unwrap();
+                            }
+                        });
+                    },
+                    BatchSize::PerIteration,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,522 @@
+//! Cross-crate client benchmarks.
+//!
+//! [`BlockPipeline`] drives a block through the same sequence of steps it goes through in the
+//! node, using components of multiple client crates together:
+//! * build: select transactions against recent history, create a block with proof of time
+//!   checkpoints from [`MockTimekeeper`] and seal it
+//! * verify: check the seal, proof of time checkpoints and transactions against recent history
+//! * persist: write the block into [`ClientDatabase`] backed by [`MemoryStorageBackend`]
+//! * archive: archive blocks once they are `block_confirmation_depth` blocks deep
+//!
+//! This allows catching performance regressions in any of the cooperating crates. Consensus
+//! checks that require a plotted sector (solution verification, consensus parameters derivation)
+//! are not included, and since beacon chain blocks do not contain transactions yet, hashes of
+//! selected transactions are committed to the state root instead.
+//!
+//! Results can be tracked over time with Criterion baselines, for example
+//! `cargo bench -p ab-client-benchmarks -- --save-baseline main` on one revision followed by
+//! `cargo bench -p ab-client-benchmarks -- --baseline main` on another.
+
+#![expect(incomplete_features, reason = "generic_const_exprs")]
+// TODO: This feature is not actually used in this crate, but is added as a workaround for
+//  https://github.com/rust-lang/rust/issues/141492
+#![feature(generic_const_exprs)]
+
+pub mod memory_storage_backend;
+
+use crate::memory_storage_backend::MemoryStorageBackend;
+use ab_archiving::archiver::{ArchiveBlockOutcome, Archiver};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfoWrite, PersistBlockError};
+use ab_client_archiving::task::encode_block;
+use ab_client_block_builder::transaction_selection::{
+    CandidateTransaction, TransactionSelectionLimits, select_transactions,
+};
+use ab_client_consensus_common::recent_transactions::{
+    DuplicateTransactionError, RecentTransactions,
+};
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseError, ClientDatabaseFormatError, ClientDatabaseFormatOptions,
+    ClientDatabaseOptions, GenesisBlockBuilderResult,
+};
+use ab_client_proof_of_time::PotNextSlotInput;
+use ab_client_proof_of_time::source::mock_timekeeper::MockTimekeeper;
+use ab_client_proof_of_time::source::state::PotState;
+use ab_client_proof_of_time::source::timekeeper::TimekeeperProof;
+use ab_client_proof_of_time::verifier::PotVerifier;
+use ab_core_primitives::balance::Balance;
+use ab_core_primitives::block::body::owned::OwnedBeaconChainBodyError;
+use ab_core_primitives::block::header::owned::OwnedBeaconChainHeaderError;
+use ab_core_primitives::block::header::{
+    BlockHeaderConsensusInfo, BlockHeaderConsensusParameters, BlockHeaderEd25519Seal,
+    BlockHeaderExtensions, BlockHeaderFixedConsensusParameters, BlockHeaderPrefix, BlockHeaderSeal,
+};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot, BlockTimestamp};
+use ab_core_primitives::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::pot::{PotCheckpoints, PotOutput, PotSeed, SlotNumber};
+use ab_core_primitives::shard::{NumShards, ShardIndex};
+use ab_core_primitives::solutions::{Solution, SolutionRange};
+use ab_core_primitives::transaction::{Gas, TransactionHash};
+use ab_erasure_coding::ErasureCoding;
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use futures::StreamExt;
+use futures::channel::mpsc;
+use rclite::Arc;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::num::{NonZeroU16, NonZeroU32, NonZeroU64};
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 256;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+/// Large enough to contain checkpoints of all blocks produced by a single pipeline, otherwise
+/// verification would fall back to proving
+const POT_VERIFIER_CACHE_SIZE: u32 = 1_000;
+/// Checkpoints are produced by [`MockTimekeeper`], so the number of iterations doesn't matter
+const SLOT_ITERATIONS: NonZeroU32 = NonZeroU32::new(u32::MAX).expect("Not zero; qed");
+const TIMESTAMP_STEP: BlockTimestamp = BlockTimestamp::from_millis(1_000);
+/// One in this many candidate transactions is a resubmission of a transaction from the parent
+/// block, which is expected to be skipped as a duplicate
+const RESUBMISSION_INTERVAL: usize = 10;
+
+/// Errors for [`BlockPipeline`]
+#[derive(Debug, thiserror::Error)]
+pub enum BlockPipelineError {
+    /// Failed to format the database
+    #[error("Failed to format the database: {error}")]
+    FormatDatabase {
+        /// Low-level error
+        #[from]
+        error: ClientDatabaseFormatError,
+    },
+    /// Failed to open the database
+    #[error("Failed to open the database: {error}")]
+    OpenDatabase {
+        /// Low-level error
+        #[from]
+        error: ClientDatabaseError,
+    },
+    /// Mock timekeeper stopped producing proofs
+    #[error("Mock timekeeper stopped producing proofs")]
+    TimekeeperStopped,
+    /// Failed to create block body
+    #[error("Failed to create block body: {error}")]
+    CreateBody {
+        /// Low-level error
+        #[from]
+        error: OwnedBeaconChainBodyError,
+    },
+    /// Failed to create block header
+    #[error("Failed to create block header: {error}")]
+    CreateHeader {
+        /// Low-level error
+        #[from]
+        error: OwnedBeaconChainHeaderError,
+    },
+    /// Can't extend MMR, too many blocks
+    #[error("Can't extend MMR, too many blocks")]
+    CantExtendMmr,
+    /// Invalid seal
+    #[error("Invalid seal")]
+    InvalidSeal,
+    /// Invalid proof of time checkpoints
+    #[error("Invalid proof of time checkpoints")]
+    InvalidPotCheckpoints,
+    /// Duplicate transaction
+    #[error("Duplicate transaction: {error}")]
+    DuplicateTransaction {
+        /// Low-level error
+        #[from]
+        error: DuplicateTransactionError,
+    },
+    /// Failed to persist block
+    #[error("Failed to persist block: {error}")]
+    PersistBlock {
+        /// Low-level error
+        #[from]
+        error: PersistBlockError,
+    },
+}
+
+/// Options for [`BlockPipeline`]
+#[derive(Debug, Copy, Clone)]
+pub struct BlockPipelineOptions {
+    /// Number of new candidate transactions for each block
+    pub num_transactions: usize,
+    /// Blocks are archived once they are this many blocks deep, also used as the depth of recent
+    /// transactions
+    pub block_confirmation_depth: BlockNumber,
+}
+
+/// End-to-end block pipeline, see the module-level documentation for details
+#[derive(Debug)]
+pub struct BlockPipeline {
+    options: BlockPipelineOptions,
+    database: ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    pot_verifier: PotVerifier,
+    timekeeper: MockTimekeeper,
+    proof_receiver: mpsc::Receiver<TimekeeperProof>,
+    signing_key: SigningKey,
+    recent_transactions: RecentTransactions,
+    archiver: Archiver,
+    /// Persisted blocks that are not archived yet, oldest first
+    unarchived_blocks: VecDeque<OwnedBeaconChainBlock>,
+    best_block: OwnedBeaconChainBlock,
+    best_block_mmr: BlockMerkleMountainRange,
+    /// Transactions included in the best block
+    best_block_tx_hashes: Vec<TransactionHash>,
+    num_archived_blocks: usize,
+}
+
+impl BlockPipeline {
+    /// Create a new pipeline with a freshly formatted in-memory database that only contains the
+    /// genesis block
+    pub async fn new(
+        options: BlockPipelineOptions,
+        erasure_coding: ErasureCoding,
+    ) -> Result<Self, BlockPipelineError> {
+        let signing_key = SigningKey::from_bytes(&[1; _]);
+        let genesis_block = seal_block(
+            &signing_key,
+            BlockToSeal {
+                number: BlockNumber::ZERO,
+                parent_root: BlockRoot::default(),
+                parent_mmr: &BlockMerkleMountainRange::new(),
+                timestamp: BlockTimestamp::default(),
+                slot: SlotNumber::ZERO,
+                proof_of_time: PotOutput::default(),
+                checkpoints: &[],
+                tx_hashes: &[],
+            },
+        )?;
+
+        let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+        ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+            &storage_backend,
+            ClientDatabaseFormatOptions {
+                page_group_size: PAGE_GROUP_SIZE,
+                force: false,
+                known_segment_headers: Vec::new(),
+                ..
+            },
+        )
+        .await?;
+
+        let database = ClientDatabase::open(ClientDatabaseOptions {
+            block_confirmation_depth: options.block_confirmation_depth,
+            genesis_block_builder: || GenesisBlockBuilderResult {
+                block: genesis_block.clone(),
+                system_contract_states: StdArc::new([]),
+            },
+            storage_backend,
+            ..
+        })
+        .await?;
+
+        let genesis_seed = PotSeed::from([1; _]);
+        let pot_verifier = PotVerifier::new(genesis_seed, POT_VERIFIER_CACHE_SIZE);
+        let pot_state = Arc::new(PotState::new(
+            PotNextSlotInput {
+                slot: SlotNumber::ONE,
+                slot_iterations: SLOT_ITERATIONS,
+                seed: genesis_seed,
+            },
+            None,
+            pot_verifier.clone(),
+        ));
+        let (timekeeper, proof_receiver) = MockTimekeeper::new(pot_state, pot_verifier.clone());
+
+        let mut best_block_mmr = BlockMerkleMountainRange::new();
+        if !best_block_mmr.add_leaf(&genesis_block.header.header().root()) {
+            return Err(BlockPipelineError::CantExtendMmr);
+        }
+
+        let recent_transactions_depth =
+            NonZeroU64::new(u64::from(options.block_confirmation_depth)).unwrap_or(NonZeroU64::MIN);
+
+        Ok(Self {
+            options,
+            database,
+            pot_verifier,
+            timekeeper,
+            proof_receiver,
+            signing_key,
+            recent_transactions: RecentTransactions::new(recent_transactions_depth),
+            // Genesis block is not archived, its encoding is padded to a whole segment, which
+            // would dominate the benchmark
+            archiver: Archiver::new(ShardIndex::BEACON_CHAIN, erasure_coding),
+            unarchived_blocks: VecDeque::new(),
+            best_block: genesis_block,
+            best_block_mmr,
+            best_block_tx_hashes: Vec::new(),
+            num_archived_blocks: 0,
+        })
+    }
+
+    /// Client database used by the pipeline
+    pub fn database(&self) -> &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+        &self.database
+    }
+
+    /// Best block number
+    pub fn best_block_number(&self) -> BlockNumber {
+        self.best_block.header.header().prefix.number
+    }
+
+    /// Number of blocks archived so far
+    pub fn num_archived_blocks(&self) -> usize {
+        self.num_archived_blocks
+    }
+
+    /// Build, verify, persist and archive (if deep enough) the next block on top of the best block
+    pub async fn process_block(&mut self) -> Result<(), BlockPipelineError> {
+        let (block, block_mmr, tx_hashes) = self.build_block().await?;
+        self.verify_block(&block, &tx_hashes)?;
+
+        self.database
+            .persist_block(
+                block.clone(),
+                BlockDetails {
+                    mmr_with_block: Arc::new(block_mmr),
+                    system_contract_states: StdArc::new([]),
+                },
+            )
+            .await?;
+
+        let block_number = block.header.header().prefix.number;
+        self.recent_transactions
+            .add_block(block_number, tx_hashes.iter().copied());
+        self.best_block = block.clone();
+        self.best_block_mmr = block_mmr;
+        self.best_block_tx_hashes = tx_hashes;
+        self.unarchived_blocks.push_back(block);
+
+        self.archive_blocks();
+
+        Ok(())
+    }
+
+    async fn build_block(
+        &mut self,
+    ) -> Result<
+        (
+            OwnedBeaconChainBlock,
+            BlockMerkleMountainRange,
+            Vec<TransactionHash>,
+        ),
+        BlockPipelineError,
+    > {
+        self.timekeeper
+            .advance_by_slot()
+            .await
+            .map_err(|_error| BlockPipelineError::TimekeeperStopped)?;
+        let proof = self
+            .proof_receiver
+            .next()
+            .await
+            .ok_or(BlockPipelineError::TimekeeperStopped)?;
+
+        let parent_header = self.best_block.header.header();
+        let block_number = parent_header.prefix.number + BlockNumber::ONE;
+
+        let selection = select_transactions(
+            self.candidate_transactions(block_number),
+            TransactionSelectionLimits {
+                max_gas: Gas::from(u64::MAX),
+                max_size: u32::MAX,
+            },
+            &self.recent_transactions,
+            |_candidate| Ok::<_, Infallible>(()),
+        );
+        let tx_hashes = selection
+            .included
+            .iter()
+            .map(|candidate| candidate.tx_hash)
+            .collect::<Vec<_>>();
+
+        let block = seal_block(
+            &self.signing_key,
+            BlockToSeal {
+                number: block_number,
+                parent_root: *parent_header.root(),
+                parent_mmr: &self.best_block_mmr,
+                timestamp: parent_header
+                    .prefix
+                    .timestamp
+                    .saturating_add(TIMESTAMP_STEP),
+                slot: proof.slot,
+                proof_of_time: proof.checkpoints.output(),
+                checkpoints: &[proof.checkpoints],
+                tx_hashes: &tx_hashes,
+            },
+        )?;
+
+        let mut block_mmr = self.best_block_mmr;
+        if !block_mmr.add_leaf(&block.header.header().root()) {
+            return Err(BlockPipelineError::CantExtendMmr);
+        }
+
+        Ok((block, block_mmr, tx_hashes))
+    }
+
+    /// New candidate transactions for the block plus resubmissions of some transactions included
+    /// in the parent block
+    fn candidate_transactions(&self, block_number: BlockNumber) -> Vec<CandidateTransaction<()>> {
+        let new_tx_hashes = (0..self.options.num_transactions as u64).map(|index| {
+            let mut bytes = [0; BlockNumber::SIZE + size_of::<u64>()];
+            bytes[..BlockNumber::SIZE].copy_from_slice(&block_number.to_bytes());
+            bytes[BlockNumber::SIZE..].copy_from_slice(&index.to_le_bytes());
+
+            TransactionHash::from(Blake3Hash::from(blake3::hash(&bytes)))
+        });
+        let resubmitted_tx_hashes = self
+            .best_block_tx_hashes
+            .iter()
+            .step_by(RESUBMISSION_INTERVAL)
+            .copied();
+
+        new_tx_hashes
+            .chain(resubmitted_tx_hashes)
+            .enumerate()
+            .map(|(index, tx_hash)| CandidateTransaction {
+                tx_hash,
+                gas_limit: Gas::from(1_000),
+                size: 200,
+                fee: Balance::from(index as u128),
+                tx: (),
+            })
+            .collect()
+    }
+
+    fn verify_block(
+        &self,
+        block: &OwnedBeaconChainBlock,
+        tx_hashes: &[TransactionHash],
+    ) -> Result<(), BlockPipelineError> {
+        let header = block.header.header();
+
+        if !header.is_sealed_correctly() {
+            return Err(BlockPipelineError::InvalidSeal);
+        }
+
+        let parent_header = self.best_block.header.header();
+        let mut seed = if parent_header.prefix.number == BlockNumber::ZERO {
+            self.pot_verifier.genesis_seed()
+        } else {
+            parent_header.consensus_info.proof_of_time.seed()
+        };
+        for checkpoints in block.body.body().pot_checkpoints() {
+            if !self
+                .pot_verifier
+                .verify_checkpoints(seed, SLOT_ITERATIONS, checkpoints)
+            {
+                return Err(BlockPipelineError::InvalidPotCheckpoints);
+            }
+            seed = checkpoints.output().seed();
+        }
+
+        self.recent_transactions.check_transactions(tx_hashes)?;
+
+        Ok(())
+    }
+
+    fn archive_blocks(&mut self) {
+        let best_block_number = self.best_block_number();
+
+        while let Some(block) = self.unarchived_blocks.front()
+            && block.header.header().prefix.number + self.options.block_confirmation_depth
+                <= best_block_number
+        {
+            let block = self
+                .unarchived_blocks
+                .pop_front()
+                .expect("Just checked that front element exists; qed");
+
+            // Blocks are small, so segments are not produced until enough blocks are archived
+            let _: Option<ArchiveBlockOutcome> =
+                self.archiver.add_block(encode_block(&block), Vec::new());
+            self.num_archived_blocks += 1;
+        }
+    }
+}
+
+struct BlockToSeal<'a> {
+    number: BlockNumber,
+    parent_root: BlockRoot,
+    parent_mmr: &'a BlockMerkleMountainRange,
+    timestamp: BlockTimestamp,
+    slot: SlotNumber,
+    proof_of_time: PotOutput,
+    checkpoints: &'a [PotCheckpoints],
+    tx_hashes: &'a [TransactionHash],
+}
+
+fn seal_block(
+    signing_key: &SigningKey,
+    block_to_seal: BlockToSeal<'_>,
+) -> Result<OwnedBeaconChainBlock, BlockPipelineError> {
+    let BlockToSeal {
+        number,
+        parent_root,
+        parent_mmr,
+        timestamp,
+        slot,
+        proof_of_time,
+        checkpoints,
+        tx_hashes,
+    } = block_to_seal;
+
+    let public_key = Ed25519PublicKey::from(VerifyingKey::from(signing_key));
+
+    let mut state_root_hasher = blake3::Hasher::new();
+    for tx_hash in tx_hashes {
+        state_root_hasher.update(tx_hash.as_ref());
+    }
+
+    let block_unsealed = OwnedBeaconChainBlock::init([].into_iter(), [].into_iter(), checkpoints)?
+        .with_header(
+            &BlockHeaderPrefix {
+                number,
+                shard_index: ShardIndex::BEACON_CHAIN,
+                padding_0: [0; _],
+                timestamp,
+                parent_root,
+                // MMR of the genesis block's parent is empty
+                mmr_root: parent_mmr.root().map(Blake3Hash::new).unwrap_or_default(),
+            },
+            Blake3Hash::from(state_root_hasher.finalize()),
+            Blake3Hash::default(),
+            &BlockHeaderConsensusInfo {
+                slot,
+                proof_of_time,
+                future_proof_of_time: proof_of_time,
+                solution: Solution {
+                    public_key_hash: public_key.hash(),
+                    ..Solution::genesis_solution()
+                },
+            },
+            &BlockHeaderConsensusParameters {
+                fixed_parameters: BlockHeaderFixedConsensusParameters {
+                    solution_range: SolutionRange::MAX,
+                    slot_iterations: SLOT_ITERATIONS,
+                    num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN)
+                        .expect("Values are statically known to be valid; qed"),
+                },
+                super_segment_root: None,
+                next_solution_range: None,
+                pot_parameters_change: None,
+            },
+            &BlockHeaderExtensions::EMPTY,
+        )?;
+
+    let signature =
+        Ed25519Signature::from(signing_key.sign(block_unsealed.pre_seal_hash().as_ref()));
+
+    Ok(
+        block_unsealed.with_seal(BlockHeaderSeal::Ed25519(&BlockHeaderEd25519Seal {
+            public_key,
+            signature,
+        })),
+    )
+}
@@ -0,0 +1,86 @@
+//! In-memory storage backend for the client database
+
+use ab_client_database::storage_backend::{AlignedPage, ClientDatabaseStorageBackend};
+use futures::channel::oneshot;
+use std::io;
+use std::sync::{Arc as StdArc, Mutex};
+
+/// Storage backend that keeps the database image in memory
+#[derive(Debug)]
+pub struct MemoryStorageBackend {
+    pages: Mutex<Vec<AlignedPage>>,
+}
+
+impl ClientDatabaseStorageBackend for MemoryStorageBackend {
+    fn num_pages(&self) -> u32 {
+        self.pages.lock().expect("Not poisoned; qed").len() as u32
+    }
+
+    fn read(
+        &self,
+        mut buffer: Vec<AlignedPage>,
+        length: u32,
+        offset: u32,
+    ) -> oneshot::Receiver<io::Result<Vec<AlignedPage>>> {
+        let (sender, receiver) = oneshot::channel();
+
+        let pages = self.pages.lock().expect("Not poisoned; qed");
+        let result = match pages
+            .get(offset as usize..)
+            .and_then(|pages| pages.get(..length as usize))
+        {
+            Some(pages) => {
+                buffer.extend_from_slice(pages);
+                Ok(buffer)
+            }
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        };
+        // Receiver is never dropped before the result is sent
+        let _: Result<(), _> = sender.send(result);
+
+        receiver
+    }
+
+    fn write(
+        &self,
+        buffer: StdArc<Vec<AlignedPage>>,
+        offset: u32,
+    ) -> oneshot::Receiver<io::Result<StdArc<Vec<AlignedPage>>>> {
+        let (sender, receiver) = oneshot::channel();
+
+        let mut pages = self.pages.lock().expect("Not poisoned; qed");
+        let result = match pages
+            .get_mut(offset as usize..)
+            .and_then(|pages| pages.get_mut(..buffer.len()))
+        {
+            Some(pages) => {
+                pages.copy_from_slice(&buffer);
+                Ok(buffer)
+            }
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        };
+        // Receiver is never dropped before the result is sent
+        let _: Result<(), _> = sender.send(result);
+
+        receiver
+    }
+
+    fn sync(&self) -> oneshot::Receiver<io::Result<()>> {
+        let (sender, receiver) = oneshot::channel();
+
+        // Memory is always in sync
+        // Receiver is never dropped before the result is sent
+        let _: Result<(), _> = sender.send(Ok(()));
+
+        receiver
+    }
+}
+
+impl MemoryStorageBackend {
+    /// Create a new zero-initialized instance with the specified number of pages
+    pub fn new(num_pages: u32) -> Self {
+        Self {
+            pages: Mutex::new(vec![AlignedPage::default(); num_pages as usize]),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+#![expect(incomplete_features, reason = "generic_const_exprs")]
+// TODO: This feature is not actually used in this crate, but is added as a workaround for
+//  https://github.com/rust-lang/rust/issues/141492
+#![feature(generic_const_exprs)]
+
+use ab_client_api::ChainInfo;
+use ab_client_benchmarks::{BlockPipeline, BlockPipelineOptions};
+use ab_core_primitives::block::BlockNumber;
+use ab_erasure_coding::ErasureCoding;
+use futures::executor::block_on;
+
+#[test]
+fn process_blocks() {
+    let block_confirmation_depth = BlockNumber::from(4);
+    let mut pipeline = block_on(BlockPipeline::new(
+        BlockPipelineOptions {
+            num_transactions: 20,
+            block_confirmation_depth,
+        },
+        ErasureCoding::new(),
+    ))
+    .unwrap();
+
+    for _ in 0..10 {
+        block_on(pipeline.process_block()).unwrap();
+    }
+
+    assert_eq!(pipeline.best_block_number(), BlockNumber::from(10));
+    assert_eq!(
+        pipeline.database().best_header().header().prefix.number,
+        BlockNumber::from(10)
+    );
+    // Blocks 1..=6 are deep enough to be archived
+    assert_eq!(pipeline.num_archived_blocks(), 6);
+}
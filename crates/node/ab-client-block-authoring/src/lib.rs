@@ -3,6 +3,7 @@
 #![feature(async_fn_traits, unboxed_closures)]
 
 pub mod beacon_chain;
+pub mod simulation;
 pub mod slot_worker;
 
 use ab_core_primitives::block::header::{
@@ -10,9 +11,12 @@ use ab_core_primitives::block::header::{
 };
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pot::PotCheckpoints;
+use ab_core_primitives::shard::ShardIndex;
 
 #[derive(Debug)]
 pub struct ClaimedSlot {
+    /// Shard for which the slot was claimed
+    pub shard_index: ShardIndex,
     /// Consensus info for a block header
     pub consensus_info: BlockHeaderConsensusInfo,
     /// Proof of time checkpoints from after future proof of the parent beacon chain block to
@@ -0,0 +1,298 @@
+//! Simulation of block authoring for protocol parameters tuning.
+//!
+//! [`simulate_block_authoring()`] simulates authoring over a configurable number of slots by a
+//! virtual farmer with a given space pledge competing with the rest of the network, without
+//! running a network, plotting or proving. Slots are derived from mock proof of time (see
+//! [`mock_checkpoints()`]) and the chain with all forks is tracked in memory.
+//!
+//! Each slot both the farmer and the rest of the network win with a probability proportional to
+//! their share of the total space pledged, scaled by slot probability, and author a block on top
+//! of the best block they know about. Blocks of the other party are received after a propagation
+//! delay, longest chain wins and ties are resolved in favor of the block that was seen first. This
+//! is a model of the protocol rather than an exact reproduction, but it is enough to see how block
+//! share and reorgs depend on slot probability and propagation delay.
+
+use ab_blake3::single_block_hash;
+use ab_client_proof_of_time::source::mock_timekeeper::mock_checkpoints;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::pot::{PotOutput, PotSeed, SlotNumber};
+use std::num::NonZeroU32;
+
+/// Checkpoints are derived with [`mock_checkpoints()`], so the number of iterations doesn't matter
+const SLOT_ITERATIONS: NonZeroU32 = NonZeroU32::new(u32::MAX).expect("Not zero; qed");
+
+/// Options for [`simulate_block_authoring()`]
+#[derive(Debug, Copy, Clone)]
+pub struct BlockAuthoringSimulationOptions {
+    /// Number of slots to simulate
+    pub num_slots: u64,
+    /// Space pledged by the simulated farmer in bytes
+    pub farmer_space_pledged: u64,
+    /// Space pledged by the rest of the network in bytes
+    pub network_space_pledged: u64,
+    /// Slot probability, expressed as `(numerator, denominator)`
+    pub slot_probability: (u64, u64),
+    /// Number of slots after which a block authored by one party is received by the other
+    pub propagation_delay: u64,
+    /// Seed of mock proof of time, different seeds result in different winning slots
+    pub pot_seed: PotSeed,
+}
+
+/// Result of [`simulate_block_authoring()`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BlockAuthoringSimulationReport {
+    /// Number of simulated slots
+    pub num_slots: u64,
+    /// Total number of blocks authored, including blocks that ended up on forks
+    pub blocks_authored: u64,
+    /// Number of blocks on the canonical chain (excluding genesis)
+    pub canonical_blocks: u64,
+    /// Number of blocks authored by the farmer
+    pub farmer_blocks_authored: u64,
+    /// Number of blocks authored by the farmer on the canonical chain
+    pub farmer_canonical_blocks: u64,
+    /// Expected share of canonical blocks authored by the farmer based on the space pledged
+    pub expected_farmer_block_share: f64,
+    /// Actual share of canonical blocks authored by the farmer
+    pub farmer_block_share: f64,
+    /// Number of reorgs observed by the farmer
+    pub reorgs: u64,
+    /// Max number of blocks reverted by a single reorg observed by the farmer
+    pub max_reorg_depth: u64,
+}
+
+/// Party authoring blocks in the simulation
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Author {
+    Genesis,
+    Farmer,
+    Network,
+}
+
+#[derive(Debug)]
+struct SimulatedBlock {
+    parent: usize,
+    number: u64,
+    author: Author,
+}
+
+/// View of the chain by one of the parties
+#[derive(Debug)]
+struct ChainView {
+    /// Index of the best block
+    best_block: usize,
+    /// Blocks authored by the other party that were not received yet, as `(slot, block)` where
+    /// `slot` is the slot when the block is received
+    incoming_blocks: Vec<(u64, usize)>,
+}
+
+impl ChainView {
+    /// Receive blocks that arrived by `slot` and return depths of resulting reorgs
+    fn receive_blocks(&mut self, blocks: &[SimulatedBlock], slot: u64) -> Vec<u64> {
+        let mut reorg_depths = Vec::new();
+
+        self.incoming_blocks.retain(|&(arrival_slot, block)| {
+            if arrival_slot > slot {
+                return true;
+            }
+
+            if blocks[block].number > blocks[self.best_block].number {
+                let common_ancestor = common_ancestor(blocks, self.best_block, block);
+                let reorg_depth = blocks[self.best_block].number - blocks[common_ancestor].number;
+                if reorg_depth > 0 {
+                    reorg_depths.push(reorg_depth);
+                }
+                self.best_block = block;
+            }
+
+            false
+        });
+
+        reorg_depths
+    }
+}
+
+/// Simulate block authoring, see the module-level documentation for details
+pub fn simulate_block_authoring(
+    options: BlockAuthoringSimulationOptions,
+) -> BlockAuthoringSimulationReport {
+    let BlockAuthoringSimulationOptions {
+        num_slots,
+        farmer_space_pledged,
+        network_space_pledged,
+        slot_probability,
+        propagation_delay,
+        pot_seed,
+    } = options;
+
+    let total_space_pledged = farmer_space_pledged.saturating_add(network_space_pledged);
+    let farmer_threshold =
+        win_threshold(farmer_space_pledged, total_space_pledged, slot_probability);
+    let network_threshold =
+        win_threshold(network_space_pledged, total_space_pledged, slot_probability);
+
+    let mut blocks = vec![SimulatedBlock {
+        parent: 0,
+        number: 0,
+        author: Author::Genesis,
+    }];
+    let mut farmer_view = ChainView {
+        best_block: 0,
+        incoming_blocks: Vec::new(),
+    };
+    let mut network_view = ChainView {
+        best_block: 0,
+        incoming_blocks: Vec::new(),
+    };
+    let mut reorgs = 0;
+    let mut max_reorg_depth = 0;
+    let mut seed = pot_seed;
+
+    for slot in 1..=num_slots {
+        let proof_of_time = mock_checkpoints(seed, SLOT_ITERATIONS).output();
+        seed = proof_of_time.seed();
+
+        for reorg_depth in farmer_view.receive_blocks(&blocks, slot) {
+            reorgs += 1;
+            max_reorg_depth = max_reorg_depth.max(reorg_depth);
+        }
+        network_view.receive_blocks(&blocks, slot);
+
+        // Both parties decide whether they won the slot before authoring any blocks
+        let farmer_won = audit(proof_of_time, slot, Author::Farmer) < farmer_threshold;
+        let network_won = audit(proof_of_time, slot, Author::Network) < network_threshold;
+
+        if farmer_won {
+            author_block(
+                &mut blocks,
+                Author::Farmer,
+                &mut farmer_view,
+                &mut network_view,
+                slot.saturating_add(propagation_delay),
+            );
+        }
+        if network_won {
+            author_block(
+                &mut blocks,
+                Author::Network,
+                &mut network_view,
+                &mut farmer_view,
+                slot.saturating_add(propagation_delay),
+            );
+        }
+    }
+
+    // Deliver blocks that are still in flight
+    for reorg_depth in farmer_view.receive_blocks(&blocks, u64::MAX) {
+        reorgs += 1;
+        max_reorg_depth = max_reorg_depth.max(reorg_depth);
+    }
+    network_view.receive_blocks(&blocks, u64::MAX);
+
+    // Both views converge after all blocks are delivered, unless there is a tie, in which case
+    // the network decides which chain is canonical
+    let mut canonical_blocks = 0;
+    let mut farmer_canonical_blocks = 0;
+    let mut block = network_view.best_block;
+    while blocks[block].author != Author::Genesis {
+        canonical_blocks += 1;
+        if blocks[block].author == Author::Farmer {
+            farmer_canonical_blocks += 1;
+        }
+        block = blocks[block].parent;
+    }
+
+    let farmer_blocks_authored = blocks
+        .iter()
+        .filter(|block| block.author == Author::Farmer)
+        .count() as u64;
+
+    BlockAuthoringSimulationReport {
+        num_slots,
+        blocks_authored: blocks.len() as u64 - 1,
+        canonical_blocks,
+        farmer_blocks_authored,
+        farmer_canonical_blocks,
+        expected_farmer_block_share: if total_space_pledged == 0 {
+            0.0
+        } else {
+            farmer_space_pledged as f64 / total_space_pledged as f64
+        },
+        farmer_block_share: if canonical_blocks == 0 {
+            0.0
+        } else {
+            farmer_canonical_blocks as f64 / canonical_blocks as f64
+        },
+        reorgs,
+        max_reorg_depth,
+    }
+}
+
+/// Author a block on top of the best block of `own_view`, the block is received by `other_view` at
+/// `arrival_slot`
+fn author_block(
+    blocks: &mut Vec<SimulatedBlock>,
+    author: Author,
+    own_view: &mut ChainView,
+    other_view: &mut ChainView,
+    arrival_slot: u64,
+) {
+    let parent = own_view.best_block;
+    let block = blocks.len();
+    blocks.push(SimulatedBlock {
+        parent,
+        number: blocks[parent].number + 1,
+        author,
+    });
+    own_view.best_block = block;
+    other_view.incoming_blocks.push((arrival_slot, block));
+}
+
+/// Threshold below which an audit result wins the slot for a party with `space_pledged` bytes out
+/// of `total_space_pledged` bytes
+fn win_threshold(
+    space_pledged: u64,
+    total_space_pledged: u64,
+    slot_probability: (u64, u64),
+) -> u64 {
+    if total_space_pledged == 0 || slot_probability.1 == 0 {
+        return 0;
+    }
+
+    let threshold = u128::from(u64::MAX)
+        .saturating_mul(u128::from(space_pledged))
+        .saturating_mul(u128::from(slot_probability.0))
+        / u128::from(total_space_pledged)
+        / u128::from(slot_probability.1);
+
+    u64::try_from(threshold).unwrap_or(u64::MAX)
+}
+
+/// Uniformly distributed audit result of a party for a slot
+fn audit(proof_of_time: PotOutput, slot: u64, author: Author) -> u64 {
+    let global_challenge = proof_of_time.derive_global_challenge(SlotNumber::from(slot));
+
+    let mut bytes_to_hash = [0; Blake3Hash::SIZE + 1];
+    bytes_to_hash[..Blake3Hash::SIZE].copy_from_slice(global_challenge.as_ref());
+    bytes_to_hash[Blake3Hash::SIZE] = author as u8;
+    let hash =
+        single_block_hash(&bytes_to_hash).expect("Less than a single block worth of bytes; qed");
+
+    u64::from_le_bytes(
+        hash[..size_of::<u64>()]
+            .try_into()
+            .expect("Correct length; qed"),
+    )
+}
+
+fn common_ancestor(blocks: &[SimulatedBlock], mut a: usize, mut b: usize) -> usize {
+    while a != b {
+        if blocks[a].number >= blocks[b].number {
+            a = blocks[a].parent;
+        } else {
+            b = blocks[b].parent;
+        }
+    }
+
+    a
+}
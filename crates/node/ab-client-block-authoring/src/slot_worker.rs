@@ -5,6 +5,7 @@ use crate::{BlockProducer, ClaimedSlot};
 use ab_client_api::{ChainInfo, ChainSyncStatus};
 use ab_client_consensus_common::ConsensusConstants;
 use ab_client_consensus_common::consensus_parameters::shard_membership_entropy_source;
+use ab_client_notifications::{NotificationBus, Topic};
 use ab_client_proof_of_time::PotNextSlotInput;
 use ab_client_proof_of_time::source::{PotSlotInfo, PotSlotInfoStream};
 use ab_client_proof_of_time::verifier::PotVerifier;
@@ -15,11 +16,12 @@ use ab_core_primitives::block::header::{
 use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pot::{PotCheckpoints, PotOutput, PotParametersChange, SlotNumber};
-use ab_core_primitives::shard::NumShards;
+use ab_core_primitives::shard::{NumShards, RealShardKind, ShardIndex, ShardKind};
 use ab_core_primitives::solutions::{ShardMembershipEntropy, Solution, SolutionRange};
 use ab_proof_of_space::Table;
 use futures::StreamExt;
-use futures::channel::{mpsc, oneshot};
+use futures::channel::mpsc;
+use futures::future::join_all;
 use send_future::SendFuture;
 use std::collections::BTreeMap;
 use std::marker::PhantomData;
@@ -54,51 +56,80 @@ pub struct NewSlotNotification {
     /// Sender that can be used to send solutions for the slot.
     pub solution_sender: mpsc::Sender<Solution>,
 }
+
+/// Topic of notifications about new slots
+#[derive(Debug)]
+pub struct NewSlotTopic;
+
+impl Topic for NewSlotTopic {
+    const NAME: &'static str = "new-slot";
+    type Message = NewSlotNotification;
+}
+
 /// Notification with a pre-seal hash that needs to be sealed (signed) to create a block and receive
 /// a block reward
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlockSealNotification {
+    /// Shard for which the block is produced
+    pub shard_index: ShardIndex,
     /// Hash to be signed.
     pub pre_seal_hash: Blake3Hash,
     /// Public key hash of the plot identity that should create signature
     pub public_key_hash: Blake3Hash,
-    /// Sender that can be used to send the seal
-    pub seal_sender: oneshot::Sender<OwnedBlockHeaderSeal>,
+    /// Sender that can be used to send the seal, only the first seal is used
+    pub seal_sender: mpsc::Sender<OwnedBlockHeaderSeal>,
+}
+
+/// Topic of requests to seal a block
+#[derive(Debug)]
+pub struct SealRequestTopic;
+
+impl Topic for SealRequestTopic {
+    const NAME: &'static str = "seal-request";
+    type Message = BlockSealNotification;
 }
 
 /// Options for [`SlotWorker`]
 #[derive(Debug)]
 pub struct SlotWorkerOptions<BP, BCI, CSS> {
-    /// Producer of a new block
-    pub block_producer: BP,
+    /// Producers of new blocks for each shard the node produces blocks for.
+    ///
+    /// Each solution is used to claim a slot on at most one shard, the shard with the lowest index
+    /// that the solution is eligible for is used first.
+    pub block_producers: BTreeMap<ShardIndex, BP>,
     /// Beacon chain info
     pub beacon_chain_info: BCI,
     /// Chain sync status
     pub chain_sync_status: CSS,
     /// Force authoring of blocks even if we are offline
     pub force_authoring: bool,
-    /// Sender for new slot notifications
-    pub new_slot_notification_sender: mpsc::Sender<NewSlotNotification>,
-    /// Sender for block sealing notifications
-    pub block_sealing_notification_sender: mpsc::Sender<BlockSealNotification>,
+    /// Notification bus for publishing [`NewSlotTopic`] and [`SealRequestTopic`] notifications
+    pub notification_bus: NotificationBus,
     /// Consensus constants
     pub consensus_constants: ConsensusConstants,
     /// Proof of time verifier
     pub pot_verifier: PotVerifier,
 }
 
+/// Solutions that were requested from farmers for a slot
+#[derive(Debug)]
+struct PendingSolutions {
+    solution_receiver: mpsc::Receiver<Solution>,
+    shard_membership_entropy: ShardMembershipEntropy,
+    num_shards: NumShards,
+}
+
 /// Slot worker responsible for block production
 #[derive(Debug)]
 pub struct SlotWorker<PosTable, BP, BCI, CSS> {
-    block_producer: BP,
+    block_producers: BTreeMap<ShardIndex, BP>,
     beacon_chain_info: BCI,
     chain_sync_status: CSS,
     force_authoring: bool,
-    new_slot_notification_sender: mpsc::Sender<NewSlotNotification>,
-    block_sealing_notification_sender: mpsc::Sender<BlockSealNotification>,
+    notification_bus: NotificationBus,
     /// Solution receivers for challenges that were sent to farmers and expected to be received
     /// eventually
-    pending_solutions: BTreeMap<SlotNumber, mpsc::Receiver<Solution>>,
+    pending_solutions: BTreeMap<SlotNumber, PendingSolutions>,
     /// Collection of PoT slots that can be retrieved later if needed by block production
     pot_checkpoints: BTreeMap<SlotNumber, PotCheckpoints>,
     consensus_constants: ConsensusConstants,
@@ -116,23 +147,21 @@ where
     /// Create a new slot worker
     pub fn new(
         SlotWorkerOptions {
-            block_producer,
+            block_producers,
             beacon_chain_info,
             chain_sync_status,
             force_authoring,
-            new_slot_notification_sender,
-            block_sealing_notification_sender,
+            notification_bus,
             consensus_constants,
             pot_verifier,
         }: SlotWorkerOptions<BP, BCI, CSS>,
     ) -> Self {
         Self {
-            block_producer,
+            block_producers,
             beacon_chain_info,
             chain_sync_status,
             force_authoring,
-            new_slot_notification_sender,
-            block_sealing_notification_sender,
+            notification_bus,
             pending_solutions: BTreeMap::new(),
             pot_checkpoints: BTreeMap::new(),
             consensus_constants,
@@ -216,27 +245,32 @@ where
                 let solution_range = consensus_parameters
                     .next_solution_range
                     .unwrap_or(consensus_parameters.fixed_parameters.solution_range);
+                let num_shards = consensus_parameters.fixed_parameters.num_shards;
                 let new_slot_info = NewSlotInfo {
                     slot,
                     proof_of_time,
                     solution_range,
                     shard_membership_entropy,
-                    num_shards: consensus_parameters.fixed_parameters.num_shards,
+                    num_shards,
                 };
                 let (solution_sender, solution_receiver) =
                     mpsc::channel(PENDING_SOLUTIONS_CHANNEL_CAPACITY);
 
-                if let Err(error) =
-                    self.new_slot_notification_sender
-                        .try_send(NewSlotNotification {
-                            new_slot_info,
-                            solution_sender,
-                        })
-                {
-                    warn!(%error, "Failed to send a new slot notification");
-                }
+                self.notification_bus
+                    .publish::<NewSlotTopic>(NewSlotNotification {
+                        new_slot_info,
+                        solution_sender,
+                    })
+                    .await;
 
-                self.pending_solutions.insert(slot, solution_receiver);
+                self.pending_solutions.insert(
+                    slot,
+                    PendingSolutions {
+                        solution_receiver,
+                        shard_membership_entropy,
+                        num_shards,
+                    },
+                );
             }
 
             // Slots that we claim must be `block_authoring_delay` behind the best slot we know of
@@ -253,56 +287,78 @@ where
                 continue;
             }
 
-            let Some(claimed_slot) = self
+            let Some(mut claimed_slots) = self
                 .claim_slot(best_beacon_chain_header, slot_to_claim)
                 .await
             else {
                 continue;
             };
 
-            debug!(
-                slot = %claimed_slot.consensus_info.slot,
-                "Starting block authorship"
-            );
-
-            let seal_block = {
-                let block_sealing_notification_sender = &mut self.block_sealing_notification_sender;
-                let public_key_hash = claimed_slot.consensus_info.solution.public_key_hash;
-
-                move |pre_seal_hash| async move {
-                    let (seal_sender, seal_receiver) = oneshot::channel::<OwnedBlockHeaderSeal>();
-
-                    if let Err(error) =
-                        block_sealing_notification_sender.try_send(BlockSealNotification {
-                            pre_seal_hash,
-                            public_key_hash,
-                            seal_sender,
-                        })
-                    {
-                        warn!(%error, "Failed to send block sealing notification");
-                    }
+            let notification_bus = &self.notification_bus;
+            // Blocks for different shards are produced and sealed concurrently
+            let block_production =
+                self.block_producers
+                    .iter_mut()
+                    .filter_map(|(shard_index, block_producer)| {
+                        let claimed_slot = claimed_slots.remove(shard_index)?;
 
-                    match tokio::time::timeout(BLOCK_SEALING_TIMEOUT, seal_receiver).await {
-                        Ok(Ok(seal)) => Some(seal),
-                        _ => None,
-                    }
-                }
-            };
+                        debug!(
+                            slot = %claimed_slot.consensus_info.slot,
+                            %shard_index,
+                            "Starting block authorship"
+                        );
 
-            // TODO: `.send()` is a hack for compiler bug, see:
-            //  https://github.com/rust-lang/rust/issues/100013#issuecomment-2210995259
-            self.block_producer
-                .produce_block(claimed_slot, best_beacon_chain_header, seal_block)
-                .send()
-                .await;
+                        let seal_block = {
+                            let shard_index = *shard_index;
+                            let public_key_hash =
+                                claimed_slot.consensus_info.solution.public_key_hash;
+
+                            move |pre_seal_hash| async move {
+                                let (seal_sender, mut seal_receiver) =
+                                    mpsc::channel::<OwnedBlockHeaderSeal>(1);
+
+                                let num_subscribers = notification_bus
+                                    .publish::<SealRequestTopic>(BlockSealNotification {
+                                        shard_index,
+                                        pre_seal_hash,
+                                        public_key_hash,
+                                        seal_sender,
+                                    })
+                                    .await;
+                                if num_subscribers == 0 {
+                                    warn!(
+                                        %shard_index,
+                                        "Nobody to send block sealing notification to"
+                                    );
+                                }
+
+                                tokio::time::timeout(BLOCK_SEALING_TIMEOUT, seal_receiver.next())
+                                    .await
+                                    .ok()
+                                    .flatten()
+                            }
+                        };
+
+                        // TODO: `.send()` is a hack for compiler bug, see:
+                        //  https://github.com/rust-lang/rust/issues/100013#issuecomment-2210995259
+                        Some(
+                            block_producer
+                                .produce_block(claimed_slot, best_beacon_chain_header, seal_block)
+                                .send(),
+                        )
+                    });
+
+            join_all(block_production).await;
         }
     }
 
+    /// Claim a slot for as many shards as possible, returns `None` if the slot was not claimed for
+    /// any shard
     async fn claim_slot(
         &mut self,
         parent_beacon_chain_header: &BeaconChainHeader<'_>,
         slot: SlotNumber,
-    ) -> Option<ClaimedSlot> {
+    ) -> Option<BTreeMap<ShardIndex, ClaimedSlot>> {
         let parent_number = parent_beacon_chain_header.prefix.number;
         let parent_slot = parent_beacon_chain_header.consensus_info.slot;
 
@@ -417,40 +473,91 @@ where
             (proof_of_time, future_proof_of_time, checkpoints)
         };
 
-        let mut solution_receiver = {
+        let PendingSolutions {
+            mut solution_receiver,
+            shard_membership_entropy,
+            num_shards,
+        } = {
             // Remove receivers for old slots we will not need anymore
             self.pending_solutions
-                .retain(|&stored_slot, _solution_receiver| stored_slot >= slot);
+                .retain(|&stored_slot, _pending_solutions| stored_slot >= slot);
 
-            let mut solution_receiver = self.pending_solutions.remove(&slot)?;
+            let mut pending_solutions = self.pending_solutions.remove(&slot)?;
             // Time is out, we will not accept any more solutions
-            solution_receiver.close();
-            solution_receiver
+            pending_solutions.solution_receiver.close();
+            pending_solutions
         };
 
-        let mut maybe_consensus_info = None;
+        let mut consensus_infos = BTreeMap::<ShardIndex, BlockHeaderConsensusInfo>::new();
 
         while let Some(solution) = solution_receiver.next().await {
-            if maybe_consensus_info.is_none() {
-                debug!(%slot, "🚜 Claimed slot");
-                maybe_consensus_info.replace(BlockHeaderConsensusInfo {
-                    slot,
-                    proof_of_time,
-                    future_proof_of_time,
-                    solution,
-                });
-            } else {
+            let solution_shard_index = num_shards.derive_shard_index(
+                &solution.public_key_hash,
+                &solution.shard_commitment.root,
+                &shard_membership_entropy,
+                solution.history_size,
+            );
+
+            let maybe_shard_index = self
+                .block_producers
+                .keys()
+                .find(|&&shard_index| {
+                    !consensus_infos.contains_key(&shard_index)
+                        && is_solution_for_shard(shard_index, solution_shard_index)
+                })
+                .copied();
+            let Some(shard_index) = maybe_shard_index else {
                 debug!(
                     %slot,
+                    %solution_shard_index,
                     "Skipping a solution that has quality sufficient for block because \
-                    slot has already been claimed",
+                    slot has already been claimed for all eligible shards",
                 );
-            }
+                continue;
+            };
+
+            debug!(%slot, %shard_index, "🚜 Claimed slot");
+            consensus_infos.insert(
+                shard_index,
+                BlockHeaderConsensusInfo {
+                    slot,
+                    proof_of_time,
+                    future_proof_of_time,
+                    solution,
+                },
+            );
+        }
+
+        if consensus_infos.is_empty() {
+            return None;
         }
 
-        maybe_consensus_info.map(|consensus_info| ClaimedSlot {
-            consensus_info,
-            checkpoints,
-        })
+        Some(
+            consensus_infos
+                .into_iter()
+                .map(|(shard_index, consensus_info)| {
+                    let claimed_slot = ClaimedSlot {
+                        shard_index,
+                        consensus_info,
+                        checkpoints: checkpoints.clone(),
+                    };
+
+                    (shard_index, claimed_slot)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Whether a solution that belongs to `solution_shard_index` can be used to claim a slot on
+/// `shard_index`
+fn is_solution_for_shard(shard_index: ShardIndex, solution_shard_index: ShardIndex) -> bool {
+    match shard_index.shard_kind().and_then(ShardKind::to_real) {
+        Some(RealShardKind::BeaconChain) => true,
+        Some(RealShardKind::IntermediateShard) => {
+            solution_shard_index.parent_shard() == Some(shard_index)
+        }
+        Some(RealShardKind::LeafShard) => solution_shard_index == shard_index,
+        None => false,
     }
 }
@@ -1,5 +1,11 @@
 //! Block building for the beacon chain
 
+#[cfg(test)]
+mod tests;
+
+use crate::transaction_selection::{
+    TransactionSelection, TransactionSelectionLimits, TransactionSource, select_transactions,
+};
 use crate::{BlockBuilder, BlockBuilderError, BlockBuilderResult};
 use ab_client_api::{BeaconChainInfo, BlockDetails, BlockMerkleMountainRange, ContractSlotState};
 use ab_client_consensus_common::ConsensusConstants;
@@ -9,11 +15,12 @@ use ab_client_consensus_common::consensus_parameters::{
 };
 use ab_client_consensus_common::state::GlobalState;
 use ab_core_primitives::block::body::owned::OwnedBeaconChainBodyError;
+use ab_core_primitives::block::execution::{ExecutionReceipt, compute_execution_receipts_root};
 use ab_core_primitives::block::header::owned::{
     GenericOwnedBlockHeader, OwnedBeaconChainHeader, OwnedBeaconChainHeaderError,
 };
 use ab_core_primitives::block::header::{
-    BeaconChainHeader, BlockHeaderConsensusInfo, BlockHeaderPrefix,
+    BeaconChainHeader, BlockHeaderConsensusInfo, BlockHeaderExtensions, BlockHeaderPrefix,
     OwnedBlockHeaderConsensusParameters, OwnedBlockHeaderSeal,
 };
 use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
@@ -23,6 +30,7 @@ use ab_core_primitives::pot::{PotCheckpoints, SlotNumber};
 use ab_core_primitives::segments::SuperSegmentRoot;
 use ab_core_primitives::shard::ShardIndex;
 use rclite::Arc;
+use std::convert::Infallible;
 use std::iter;
 use std::sync::Arc as StdArc;
 use std::time::SystemTime;
@@ -72,16 +80,26 @@ impl From<BeaconChainBlockBuilderError> for BlockBuilderError {
 // TODO: Another domain-specific abstraction over `ChainInfo`, which will be implemented for
 //  `ChainInfo`, but could also be implemented in simpler way directly for tests without dealing
 //  with complete headers, etc.
+/// Result of block execution
+#[derive(Debug)]
+struct BlockExecutionResult {
+    state_root: Blake3Hash,
+    execution_receipts_root: Blake3Hash,
+    system_contract_states: StdArc<[ContractSlotState]>,
+}
+
 /// Beacon chain block builder
 #[derive(Debug)]
-pub struct BeaconChainBlockBuilder<BCI> {
+pub struct BeaconChainBlockBuilder<BCI, TS = ()> {
     consensus_constants: ConsensusConstants,
     chain_info: BCI,
+    transaction_source: TS,
 }
 
-impl<CI> BlockBuilder<OwnedBeaconChainBlock> for BeaconChainBlockBuilder<CI>
+impl<CI, TS> BlockBuilder<OwnedBeaconChainBlock> for BeaconChainBlockBuilder<CI, TS>
 where
     CI: BeaconChainInfo,
+    TS: TransactionSource,
 {
     async fn build<SealBlock>(
         &mut self,
@@ -125,7 +143,11 @@ where
                 .map(|super_segment| super_segment.header.root),
         )?;
 
-        let (state_root, system_contract_states) = self.execute_block(parent_block_details);
+        let BlockExecutionResult {
+            state_root,
+            execution_receipts_root,
+            system_contract_states,
+        } = self.execute_block(parent_block_root, parent_block_details);
 
         let block_builder = OwnedBeaconChainBlock::init(
             self.chain_info
@@ -142,8 +164,11 @@ where
             .with_header(
                 &header_prefix,
                 state_root,
+                execution_receipts_root,
                 consensus_info,
                 &consensus_parameters.as_ref(),
+                // TODO: Extensions once they are defined
+                &BlockHeaderExtensions::EMPTY,
             )
             .map_err(BeaconChainBlockBuilderError::from)?;
 
@@ -169,15 +194,24 @@ where
     }
 }
 
-impl<BCI> BeaconChainBlockBuilder<BCI>
+impl<BCI, TS> BeaconChainBlockBuilder<BCI, TS>
 where
     BCI: BeaconChainInfo,
+    TS: TransactionSource,
 {
-    /// Create a new instance
-    pub fn new(consensus_constants: ConsensusConstants, chain_info: BCI) -> Self {
+    /// Create a new instance.
+    ///
+    /// `transaction_source` provides candidate transactions, use `()` to build blocks without
+    /// transactions.
+    pub fn new(
+        consensus_constants: ConsensusConstants,
+        chain_info: BCI,
+        transaction_source: TS,
+    ) -> Self {
         Self {
             consensus_constants,
             chain_info,
+            transaction_source,
         }
     }
 
@@ -241,15 +275,44 @@ where
 
     fn execute_block(
         &self,
+        parent_block_root: &BlockRoot,
         parent_block_details: &BlockDetails,
-    ) -> (Blake3Hash, StdArc<[ContractSlotState]>) {
+    ) -> BlockExecutionResult {
         let global_state = GlobalState::new(&parent_block_details.system_contract_states);
 
-        // TODO: Execute block
+        // TODO: Execution receipts for included transactions once they are executed and the body
+        //  of the beacon chain block is able to contain them
+        let _selection = self.select_transactions(parent_block_root);
+        let execution_receipts = Vec::<ExecutionReceipt>::new();
 
-        let state_root = global_state.root();
-        let system_contract_states = global_state.to_system_contract_states();
+        BlockExecutionResult {
+            state_root: global_state.root(),
+            execution_receipts_root: compute_execution_receipts_root(&execution_receipts),
+            system_contract_states: global_state.to_system_contract_states(),
+        }
+    }
+}
 
-        (state_root, system_contract_states)
+impl<BCI, TS> BeaconChainBlockBuilder<BCI, TS>
+where
+    TS: TransactionSource,
+{
+    /// Select transactions for a block built on top of the parent block, in order of inclusion
+    fn select_transactions(
+        &self,
+        parent_block_root: &BlockRoot,
+    ) -> TransactionSelection<TS::Transaction, Infallible> {
+        select_transactions(
+            self.transaction_source
+                .candidate_transactions(parent_block_root),
+            TransactionSelectionLimits::from(&self.consensus_constants),
+            &self
+                .transaction_source
+                .recent_transactions(parent_block_root),
+            |_candidate| {
+                // TODO: Apply transaction to the state, skip it if it fails
+                Ok(())
+            },
+        )
     }
 }
@@ -0,0 +1,134 @@
+use crate::beacon_chain::BeaconChainBlockBuilder;
+use crate::transaction_selection::{CandidateTransaction, SkipReason, TransactionSource};
+use ab_client_consensus_common::recent_transactions::RecentTransactions;
+use ab_client_consensus_common::{ConsensusConstants, PotConsensusConstants};
+use ab_core_primitives::balance::Balance;
+use ab_core_primitives::block::{BlockNumber, BlockRoot, BlockTimestamp};
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::pot::{SlotDuration, SlotNumber};
+use ab_core_primitives::segments::HistorySize;
+use ab_core_primitives::transaction::{Gas, TransactionHash};
+use std::num::NonZeroU64;
+
+fn history_size(value: u64) -> HistorySize {
+    HistorySize::new(NonZeroU64::new(value).unwrap())
+}
+
+fn consensus_constants() -> ConsensusConstants {
+    ConsensusConstants {
+        block_confirmation_depth: BlockNumber::from(100),
+        shard_confirmation_depth: BlockNumber::from(72),
+        block_authoring_delay: SlotNumber::from(4),
+        pot: PotConsensusConstants {
+            entropy_injection_interval: BlockNumber::from(50),
+            entropy_injection_lookback_depth: 2,
+            entropy_injection_delay: SlotNumber::from(15),
+        },
+        retarget_interval: BlockNumber::from(180),
+        slot_probability: (1, 10),
+        slot_duration: SlotDuration::from_millis(1000),
+        recent_segments: history_size(5),
+        recent_history_fraction: (history_size(1), history_size(10)),
+        min_sector_lifetime: history_size(4),
+        max_block_timestamp_drift: BlockTimestamp::from_millis(30_000),
+        shard_rotation_interval: BlockNumber::from(36),
+        shard_rotation_delay: BlockNumber::from(18),
+        max_block_transactions_gas: Gas::from(100),
+        max_block_transactions_size: 1000,
+    }
+}
+
+fn candidate(id: u8, gas_limit: u64, size: u32, fee: u128) -> CandidateTransaction<u8> {
+    CandidateTransaction {
+        tx_hash: TransactionHash::from(Blake3Hash::new([id; _])),
+        gas_limit: Gas::from(gas_limit),
+        size,
+        fee: Balance::from(fee),
+        tx: id,
+    }
+}
+
+/// Transactions in the order they were submitted
+#[derive(Debug)]
+struct TestTransactionSource {
+    parent_block_root: BlockRoot,
+    candidates: Vec<CandidateTransaction<u8>>,
+    recent_tx_hashes: Vec<TransactionHash>,
+}
+
+impl TransactionSource for TestTransactionSource {
+    type Transaction = u8;
+
+    fn candidate_transactions(
+        &self,
+        parent_block_root: &BlockRoot,
+    ) -> Vec<CandidateTransaction<Self::Transaction>> {
+        assert_eq!(parent_block_root, &self.parent_block_root);
+        self.candidates.clone()
+    }
+
+    fn recent_transactions(&self, parent_block_root: &BlockRoot) -> RecentTransactions {
+        assert_eq!(parent_block_root, &self.parent_block_root);
+        let mut recent_transactions = RecentTransactions::new(NonZeroU64::new(10).unwrap());
+        recent_transactions.add_block(BlockNumber::ONE, self.recent_tx_hashes.iter().copied());
+        recent_transactions
+    }
+}
+
+#[test]
+fn transactions_are_selected_by_fee_per_gas() {
+    let parent_block_root = BlockRoot::default();
+    let block_builder = BeaconChainBlockBuilder {
+        consensus_constants: consensus_constants(),
+        chain_info: (),
+        transaction_source: TestTransactionSource {
+            parent_block_root,
+            candidates: vec![
+                // Fee per gas 1, submitted first
+                candidate(1, 10, 100, 10),
+                // Fee per gas 2
+                candidate(2, 40, 100, 80),
+                // Fee per gas 10, but doesn't fit by size
+                candidate(3, 10, 1001, 100),
+                // Fee per gas 20, but was included in one of the recent blocks
+                candidate(4, 10, 100, 200),
+                // Fee per gas 4, but doesn't fit by gas after the previous transaction
+                candidate(5, 90, 100, 360),
+                // Fee per gas 5, submitted last
+                candidate(6, 20, 100, 100),
+            ],
+            recent_tx_hashes: vec![candidate(4, 0, 0, 0).tx_hash],
+        },
+    };
+
+    let selection = block_builder.select_transactions(&parent_block_root);
+
+    // Included by fee per gas rather than in order of submission
+    assert_eq!(
+        selection
+            .included
+            .iter()
+            .map(|candidate| candidate.tx)
+            .collect::<Vec<_>>(),
+        vec![6, 2, 1]
+    );
+    assert_eq!(
+        selection
+            .skipped
+            .iter()
+            .map(|(candidate, _reason)| candidate.tx)
+            .collect::<Vec<_>>(),
+        vec![4, 3, 5]
+    );
+    assert!(matches!(selection.skipped[0].1, SkipReason::Duplicate));
+    assert!(matches!(
+        selection.skipped[1].1,
+        SkipReason::SizeLimitExceeded
+    ));
+    assert!(matches!(
+        selection.skipped[2].1,
+        SkipReason::GasLimitExceeded
+    ));
+    assert_eq!(selection.gas, Gas::from(70));
+    assert_eq!(selection.size, 300);
+}
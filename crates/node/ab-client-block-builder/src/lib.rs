@@ -7,6 +7,7 @@
 #![feature(async_fn_traits, unboxed_closures)]
 
 pub mod beacon_chain;
+pub mod transaction_selection;
 
 use ab_client_api::BlockDetails;
 use ab_core_primitives::block::BlockRoot;
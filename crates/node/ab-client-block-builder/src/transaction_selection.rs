@@ -0,0 +1,210 @@
+//! Selection of transactions for inclusion in a block.
+//!
+//! Transactions are ordered by fee per unit of gas (highest first, ties are broken by transaction
+//! hash) and included while they fit into per-block gas and size limits. Transactions that fail
+//! during block building are skipped and do not consume block resources, such that the result is
+//! deterministic for the same set of candidates and the same application outcomes.
+//!
+//! Transactions that were included in one of the recent blocks (see [`RecentTransactions`]) or
+//! duplicate an already selected transaction are skipped as well.
+
+#[cfg(test)]
+mod tests;
+
+use ab_client_consensus_common::ConsensusConstants;
+use ab_client_consensus_common::recent_transactions::RecentTransactions;
+use ab_core_primitives::balance::Balance;
+use ab_core_primitives::block::BlockRoot;
+use ab_core_primitives::transaction::{Gas, TransactionHash};
+use core::cmp::Ordering;
+use std::collections::HashSet;
+use std::num::NonZeroU64;
+
+/// Per-block limits for transaction selection
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TransactionSelectionLimits {
+    /// Max total gas limit of all included transactions
+    pub max_gas: Gas,
+    /// Max total size of all included transactions in bytes
+    pub max_size: u32,
+}
+
+impl From<&ConsensusConstants> for TransactionSelectionLimits {
+    #[inline]
+    fn from(consensus_constants: &ConsensusConstants) -> Self {
+        Self {
+            max_gas: consensus_constants.max_block_transactions_gas,
+            max_size: consensus_constants.max_block_transactions_size,
+        }
+    }
+}
+
+/// Transaction that is a candidate for inclusion in a block
+#[derive(Debug, Clone)]
+pub struct CandidateTransaction<Tx> {
+    /// Transaction hash
+    pub tx_hash: TransactionHash,
+    /// Gas limit of the transaction
+    pub gas_limit: Gas,
+    /// Transaction size in bytes
+    pub size: u32,
+    /// Fee the transaction pays for inclusion
+    pub fee: Balance,
+    /// Transaction itself
+    pub tx: Tx,
+}
+
+impl<Tx> CandidateTransaction<Tx> {
+    /// Compare fee per gas of two transactions without loss of precision.
+    ///
+    /// Transactions with zero gas limit compare as having infinite fee per gas.
+    fn cmp_fee_per_gas(&self, other: &Self) -> Ordering {
+        // `a / b` vs `c / d` is equivalent to `a * d` vs `c * b` for non-negative numbers
+        widening_mul(u128::from(self.fee), u64::from(other.gas_limit)).cmp(&widening_mul(
+            u128::from(other.fee),
+            u64::from(self.gas_limit),
+        ))
+    }
+}
+
+/// Source of transactions for block building
+pub trait TransactionSource: Send {
+    /// Transaction type
+    type Transaction;
+
+    /// Candidate transactions for a block built on top of the parent block, in any order
+    fn candidate_transactions(
+        &self,
+        parent_block_root: &BlockRoot,
+    ) -> Vec<CandidateTransaction<Self::Transaction>>;
+
+    /// Transactions included in recent blocks of the chain that ends with the parent block
+    fn recent_transactions(&self, parent_block_root: &BlockRoot) -> RecentTransactions;
+}
+
+/// No transactions are included in blocks
+impl TransactionSource for () {
+    type Transaction = ();
+
+    #[inline(always)]
+    fn candidate_transactions(
+        &self,
+        _parent_block_root: &BlockRoot,
+    ) -> Vec<CandidateTransaction<Self::Transaction>> {
+        Vec::new()
+    }
+
+    #[inline(always)]
+    fn recent_transactions(&self, _parent_block_root: &BlockRoot) -> RecentTransactions {
+        RecentTransactions::new(NonZeroU64::MIN)
+    }
+}
+
+/// Reason for skipping a candidate transaction
+#[derive(Debug)]
+pub enum SkipReason<E> {
+    /// Transaction was included in one of the recent blocks or was already selected
+    Duplicate,
+    /// Gas limit of the transaction doesn't fit into remaining block gas
+    GasLimitExceeded,
+    /// Size of the transaction doesn't fit into remaining block size
+    SizeLimitExceeded,
+    /// Transaction failed during block building
+    Failed(E),
+}
+
+/// Result of transaction selection
+#[derive(Debug)]
+pub struct TransactionSelection<Tx, E> {
+    /// Transactions included in the block, in order of inclusion
+    pub included: Vec<CandidateTransaction<Tx>>,
+    /// Transactions that were skipped, in order of consideration
+    pub skipped: Vec<(CandidateTransaction<Tx>, SkipReason<E>)>,
+    /// Total gas limit of included transactions
+    pub gas: Gas,
+    /// Total size of included transactions in bytes
+    pub size: u32,
+}
+
+/// Select transactions for inclusion in a block.
+///
+/// Candidates are considered in order of decreasing fee per gas, `apply` is called for every
+/// candidate that fits into remaining limits and is expected to apply the transaction to the block
+/// being built. Candidates that don't fit or for which `apply` returns an error are skipped, after
+/// which selection continues with the next candidate.
+///
+/// Candidates that are present in `recent_transactions` or have the same hash as an already
+/// selected transaction are skipped without calling `apply`.
+pub fn select_transactions<Tx, E, Apply>(
+    mut candidates: Vec<CandidateTransaction<Tx>>,
+    limits: TransactionSelectionLimits,
+    recent_transactions: &RecentTransactions,
+    mut apply: Apply,
+) -> TransactionSelection<Tx, E>
+where
+    Apply: FnMut(&CandidateTransaction<Tx>) -> Result<(), E>,
+{
+    candidates
+        .sort_unstable_by(|a, b| b.cmp_fee_per_gas(a).then_with(|| a.tx_hash.cmp(&b.tx_hash)));
+
+    let mut selection = TransactionSelection {
+        included: Vec::new(),
+        skipped: Vec::new(),
+        gas: Gas::ZERO,
+        size: 0,
+    };
+    let mut included_tx_hashes = HashSet::new();
+
+    for candidate in candidates {
+        if recent_transactions.contains(&candidate.tx_hash)
+            || included_tx_hashes.contains(&candidate.tx_hash)
+        {
+            selection.skipped.push((candidate, SkipReason::Duplicate));
+            continue;
+        }
+        let Some(gas) = selection
+            .gas
+            .checked_add(candidate.gas_limit)
+            .filter(|gas| *gas <= limits.max_gas)
+        else {
+            selection
+                .skipped
+                .push((candidate, SkipReason::GasLimitExceeded));
+            continue;
+        };
+        let Some(size) = selection
+            .size
+            .checked_add(candidate.size)
+            .filter(|size| *size <= limits.max_size)
+        else {
+            selection
+                .skipped
+                .push((candidate, SkipReason::SizeLimitExceeded));
+            continue;
+        };
+
+        if let Err(error) = apply(&candidate) {
+            selection
+                .skipped
+                .push((candidate, SkipReason::Failed(error)));
+            continue;
+        }
+
+        selection.gas = gas;
+        selection.size = size;
+        included_tx_hashes.insert(candidate.tx_hash);
+        selection.included.push(candidate);
+    }
+
+    selection
+}
+
+/// Multiply `u128` by `u64` without overflow, returns `(high, low)` parts of the result
+fn widening_mul(a: u128, b: u64) -> (u128, u64) {
+    let b = u128::from(b);
+    // Neither of these multiplications can overflow since both operands fit into `u64`
+    let low = (a & u128::from(u64::MAX)) * b;
+    let high = (a >> u64::BITS) * b + (low >> u64::BITS);
+
+    (high, low as u64)
+}
@@ -0,0 +1,166 @@
+use crate::transaction_selection::{
+    CandidateTransaction, SkipReason, TransactionSelectionLimits, select_transactions, widening_mul,
+};
+use ab_client_consensus_common::recent_transactions::RecentTransactions;
+use ab_core_primitives::balance::Balance;
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::transaction::{Gas, TransactionHash};
+use std::num::NonZeroU64;
+
+fn candidate(id: u8, gas_limit: u64, size: u32, fee: u128) -> CandidateTransaction<u8> {
+    CandidateTransaction {
+        tx_hash: TransactionHash::from(Blake3Hash::new([id; _])),
+        gas_limit: Gas::from(gas_limit),
+        size,
+        fee: Balance::from(fee),
+        tx: id,
+    }
+}
+
+fn no_recent_transactions() -> RecentTransactions {
+    RecentTransactions::new(NonZeroU64::MIN)
+}
+
+fn ids<E>(transactions: &[(CandidateTransaction<u8>, SkipReason<E>)]) -> Vec<u8> {
+    transactions
+        .iter()
+        .map(|(candidate, _)| candidate.tx)
+        .collect()
+}
+
+#[test]
+fn widening_multiplication() {
+    assert_eq!(widening_mul(0, u64::MAX), (0, 0));
+    assert_eq!(widening_mul(u128::from(u64::MAX), 2), (1, u64::MAX - 1));
+    assert_eq!(
+        widening_mul(u128::MAX, u64::MAX),
+        (u128::MAX - u128::from(u64::MAX) - 1, 1)
+    );
+}
+
+#[test]
+fn ordering_and_limits() {
+    let limits = TransactionSelectionLimits {
+        max_gas: Gas::from(100),
+        max_size: 1000,
+    };
+    let candidates = vec![
+        // Fee per gas 1
+        candidate(1, 10, 100, 10),
+        // Fee per gas 5
+        candidate(2, 10, 100, 50),
+        // Fee per gas 5, same as above, ordered by hash
+        candidate(0, 20, 100, 100),
+        // Fee per gas 10, but doesn't fit by size
+        candidate(3, 10, 1001, 100),
+        // Fee per gas 4, but doesn't fit by gas after previous transactions
+        candidate(4, 80, 100, 320),
+        // Fee per gas 2
+        candidate(5, 40, 100, 80),
+    ];
+
+    let selection = select_transactions::<_, (), _>(
+        candidates,
+        limits,
+        &no_recent_transactions(),
+        |_candidate| Ok(()),
+    );
+
+    assert_eq!(
+        selection
+            .included
+            .iter()
+            .map(|candidate| candidate.tx)
+            .collect::<Vec<_>>(),
+        vec![0, 2, 5, 1]
+    );
+    assert_eq!(ids(&selection.skipped), vec![3, 4]);
+    assert!(matches!(
+        selection.skipped[0].1,
+        SkipReason::SizeLimitExceeded
+    ));
+    assert!(matches!(
+        selection.skipped[1].1,
+        SkipReason::GasLimitExceeded
+    ));
+    assert_eq!(selection.gas, Gas::from(80));
+    assert_eq!(selection.size, 400);
+}
+
+#[test]
+fn failed_transactions_are_skipped() {
+    let limits = TransactionSelectionLimits {
+        max_gas: Gas::from(20),
+        max_size: 1000,
+    };
+    let candidates = vec![
+        candidate(1, 10, 100, 30),
+        candidate(2, 10, 100, 20),
+        candidate(3, 10, 100, 10),
+    ];
+
+    let selection =
+        select_transactions(candidates, limits, &no_recent_transactions(), |candidate| {
+            if candidate.tx == 1 {
+                Err("failed")
+            } else {
+                Ok(())
+            }
+        });
+
+    // Failed transaction doesn't consume block gas, so the last transaction fits
+    assert_eq!(
+        selection
+            .included
+            .iter()
+            .map(|candidate| candidate.tx)
+            .collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+    assert_eq!(ids(&selection.skipped), vec![1]);
+    assert!(matches!(
+        selection.skipped[0].1,
+        SkipReason::Failed("failed")
+    ));
+    assert_eq!(selection.gas, Gas::from(20));
+}
+
+#[test]
+fn duplicates_are_skipped() {
+    let limits = TransactionSelectionLimits {
+        max_gas: Gas::from(100),
+        max_size: 1000,
+    };
+    let mut recent_transactions = RecentTransactions::new(NonZeroU64::new(10).unwrap());
+    recent_transactions.add_block(BlockNumber::from(1), [candidate(1, 0, 0, 0).tx_hash]);
+    let candidates = vec![
+        // Included in one of the recent blocks
+        candidate(1, 10, 100, 30),
+        candidate(2, 10, 100, 20),
+        // Same transaction twice
+        candidate(2, 10, 100, 20),
+    ];
+
+    let selection =
+        select_transactions::<_, (), _>(candidates, limits, &recent_transactions, |_candidate| {
+            Ok(())
+        });
+
+    assert_eq!(
+        selection
+            .included
+            .iter()
+            .map(|candidate| candidate.tx)
+            .collect::<Vec<_>>(),
+        vec![2]
+    );
+    assert_eq!(ids(&selection.skipped), vec![1, 2]);
+    assert!(
+        selection
+            .skipped
+            .iter()
+            .all(|(_candidate, reason)| matches!(reason, SkipReason::Duplicate))
+    );
+    assert_eq!(selection.gas, Gas::from(10));
+}
@@ -9,7 +9,11 @@ use ab_client_consensus_common::consensus_parameters::{
     DeriveConsensusParametersChainInfo, DeriveConsensusParametersConsensusInfo,
     ShardMembershipEntropySourceChainInfo,
 };
+use ab_client_consensus_common::slot_subscriptions::SystemContractSlotSubscriptions;
 use ab_client_consensus_common::state::GlobalState;
+use ab_client_consensus_common::state_cache::StateCache;
+use ab_client_notifications::{NotificationBus, Topic};
+use ab_core_primitives::block::execution::{ExecutionReceipt, compute_execution_receipts_root};
 use ab_core_primitives::block::header::owned::OwnedBeaconChainHeader;
 use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
 use ab_core_primitives::block::{BlockNumber, BlockRoot};
@@ -138,14 +142,56 @@ where
     }
 }
 
+/// Topic of notifications about imported beacon chain blocks
+#[derive(Debug)]
+pub struct NewBeaconChainBlockTopic;
+
+impl Topic for NewBeaconChainBlockTopic {
+    const NAME: &'static str = "new-beacon-chain-block";
+    type Message = OwnedBeaconChainBlock;
+}
+
+/// Notification about a beacon chain reorg
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BeaconChainReorgNotification {
+    /// Root of the best block before the reorg
+    pub old_best_root: BlockRoot,
+    /// Number of the new best block
+    pub new_best_number: BlockNumber,
+    /// Root of the new best block
+    pub new_best_root: BlockRoot,
+}
+
+/// Topic of notifications about the best beacon chain block switching to a block that is not a
+/// descendant of the previous best block
+#[derive(Debug)]
+pub struct BeaconChainReorgTopic;
+
+impl Topic for BeaconChainReorgTopic {
+    const NAME: &'static str = "beacon-chain-reorg";
+    type Message = BeaconChainReorgNotification;
+}
+
+/// Topic of notifications about new super segments included in imported beacon chain blocks
+#[derive(Debug)]
+pub struct NewSuperSegmentTopic;
+
+impl Topic for NewSuperSegmentTopic {
+    const NAME: &'static str = "new-super-segment";
+    type Message = SuperSegment;
+}
+
 #[derive(Debug)]
 pub struct BeaconChainBlockImport<PosTable, CI, BV> {
     chain_info: CI,
     block_verification: BV,
     importing_blocks: ImportingBlocks<OwnedBeaconChainHeader>,
     block_importing_notification_sender: mpsc::Sender<BlockImportingNotification>,
-    super_segments_sender: AsyncMutex<mpsc::Sender<SuperSegment>>,
-    block_import_notification_sender: mpsc::Sender<OwnedBeaconChainBlock>,
+    /// Ensures super segments are published in the same order as they are persisted
+    super_segments_lock: AsyncMutex<()>,
+    notification_bus: NotificationBus,
+    state_cache: StdArc<StateCache>,
+    slot_subscriptions: StdArc<SystemContractSlotSubscriptions>,
     _pos_table: PhantomData<PosTable>,
 }
 
@@ -229,22 +275,28 @@ where
     CI: BeaconChainInfoWrite,
     BV: BlockVerification<OwnedBeaconChainBlock, Option<SuperSegment>>,
 {
-    /// Create a new instance
+    /// Create a new instance.
+    ///
+    /// Notifications about imported blocks, reorgs and super segments are published to
+    /// `notification_bus`.
     #[inline(always)]
     pub fn new(
         chain_info: CI,
         block_verification: BV,
         block_importing_notification_sender: mpsc::Sender<BlockImportingNotification>,
-        super_segments_sender: mpsc::Sender<SuperSegment>,
-        block_import_notification_sender: mpsc::Sender<OwnedBeaconChainBlock>,
+        notification_bus: NotificationBus,
+        state_cache: StdArc<StateCache>,
+        slot_subscriptions: StdArc<SystemContractSlotSubscriptions>,
     ) -> Self {
         Self {
             chain_info,
             block_verification,
             importing_blocks: ImportingBlocks::new(),
             block_importing_notification_sender,
-            super_segments_sender: AsyncMutex::new(super_segments_sender),
-            block_import_notification_sender,
+            super_segments_lock: AsyncMutex::new(()),
+            notification_bus,
+            state_cache,
+            slot_subscriptions,
             _pos_table: PhantomData,
         }
     }
@@ -286,7 +338,7 @@ where
             .await
             .map_err(BeaconChainBlockImportError::from)?;
 
-        let Some(system_contract_states) = parent_block_import_status.wait().await else {
+        let Some(parent_system_contract_states) = parent_block_import_status.wait().await else {
             return Err(BlockImportError::ParentBlockImportFailed);
         };
 
@@ -299,9 +351,10 @@ where
             .await
             .map_err(BeaconChainBlockImportError::from)?;
 
-        let global_state = GlobalState::new(&system_contract_states);
+        let global_state = GlobalState::new(&parent_system_contract_states);
 
         // TODO: Execute block
+        let execution_receipts = Vec::<ExecutionReceipt>::new();
 
         let state_root = global_state.root();
 
@@ -312,6 +365,15 @@ where
             });
         }
 
+        let execution_receipts_root = compute_execution_receipts_root(&execution_receipts);
+
+        if header.result.execution_receipts_root != execution_receipts_root {
+            return Err(BlockImportError::InvalidExecutionReceiptsRoot {
+                expected: execution_receipts_root,
+                actual: header.result.execution_receipts_root,
+            });
+        }
+
         let system_contract_states = global_state.to_system_contract_states();
 
         let (acknowledgement_sender, mut acknowledgement_receiver) = mpsc::channel(0);
@@ -333,9 +395,10 @@ where
 
         let number = header.prefix.number;
         let root = *header.root();
+        let parent_root = header.prefix.parent_root;
 
         if let Some(super_segment) = maybe_super_segment {
-            let mut super_segment_sender = self.super_segments_sender.lock().await;
+            let _guard = self.super_segments_lock.lock().await;
             if self
                 .chain_info
                 .persist_super_segment_header(super_segment.header)
@@ -343,12 +406,15 @@ where
                 .map_err(
                     |error| BeaconChainBlockImportError::PersistSuperSegmentHeaders { error },
                 )?
-                && let Err(error) = super_segment_sender.send(super_segment).await
             {
-                warn!(%error, "Failed to send a super segment notification");
+                self.notification_bus
+                    .publish::<NewSuperSegmentTopic>(super_segment)
+                    .await;
             }
         }
 
+        let old_best_root = self.chain_info.best_root();
+
         self.chain_info
             .persist_block(
                 block.clone(),
@@ -359,22 +425,37 @@ where
             )
             .await?;
 
+        self.state_cache
+            .on_block_import(root, &system_contract_states);
+        self.slot_subscriptions.on_block_import(
+            number,
+            root,
+            &parent_system_contract_states,
+            &system_contract_states,
+        );
         importing_handle.set_success(system_contract_states);
 
-        if let Err(error) = self
-            .block_import_notification_sender
-            .clone()
-            .send(block)
-            .await
-        {
-            warn!(
-                %error,
-                block_number = %number,
-                block_root = %root,
-                "Failed to send block import notification"
+        if parent_root != old_best_root && self.chain_info.best_root() == root {
+            info!(
+                %old_best_root,
+                new_best_number = %number,
+                new_best_root = %root,
+                "Beacon chain reorg"
             );
+
+            self.notification_bus
+                .publish::<BeaconChainReorgTopic>(BeaconChainReorgNotification {
+                    old_best_root,
+                    new_best_number: number,
+                    new_best_root: root,
+                })
+                .await;
         }
 
+        self.notification_bus
+            .publish::<NewBeaconChainBlockTopic>(block)
+            .await;
+
         if log_block_import {
             info!(
                 %number,
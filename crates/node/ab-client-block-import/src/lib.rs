@@ -48,6 +48,12 @@ pub enum BlockImportError {
         expected: Blake3Hash,
         actual: Blake3Hash,
     },
+    /// Invalid execution receipts root
+    #[error("Invalid execution receipts root: expected {expected}, actual {actual}")]
+    InvalidExecutionReceiptsRoot {
+        expected: Blake3Hash,
+        actual: Blake3Hash,
+    },
     /// Block persisting error
     #[error("Block persisting error: {error}")]
     PersistBlockError {
@@ -1,6 +1,9 @@
 use crate::{BlockVerification, BlockVerificationError, GenericBody, GenericHeader};
 use ab_client_api::{BeaconChainInfo, BlockOrigin, ChainSyncStatus};
 use ab_client_consensus_common::ConsensusConstants;
+use ab_client_consensus_common::consensus_constants::{
+    ConsensusConstantsSchedule, ConsensusConstantsScheduleError,
+};
 use ab_client_consensus_common::consensus_parameters::{
     DeriveConsensusParametersChainInfo, DeriveConsensusParametersError,
     DeriveSuperSegmentForBlockError, ShardMembershipEntropySourceChainInfo,
@@ -18,13 +21,16 @@ use ab_core_primitives::block::{BlockNumber, BlockRoot, BlockTimestamp};
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pot::{PotCheckpoints, PotOutput, PotParametersChange, SlotNumber};
 use ab_core_primitives::segments::{
-    HistorySize, SuperSegment, SuperSegmentIndex, SuperSegmentRoot,
+    HistorySize, SegmentIndex, SuperSegment, SuperSegmentHeader, SuperSegmentIndex,
+    SuperSegmentRoot,
 };
 use ab_core_primitives::shard::ShardIndex;
-use ab_core_primitives::solutions::{
-    SolutionVerifyError, SolutionVerifyPieceParams, SolutionVerifyStatelessParams,
-};
+use ab_core_primitives::solutions::Solution;
 use ab_proof_of_space::Table;
+use ab_solution_verification::{
+    SolutionChallenges, SolutionVerifyError, SolutionVerifyPieceParams,
+    SolutionVerifyStatelessParams, verify_piece, verify_stateless,
+};
 use rand::prelude::*;
 use rayon::prelude::*;
 use std::future::ready;
@@ -33,6 +39,9 @@ use std::marker::PhantomData;
 use std::time::SystemTime;
 use tracing::{debug, trace};
 
+/// How many slots ahead of the current slot block announcements are accepted
+const MAX_ANNOUNCEMENT_SLOTS_IN_THE_FUTURE: u64 = 10;
+
 /// Errors for [`BeaconChainBlockVerification`]
 #[derive(Debug, thiserror::Error)]
 pub enum BeaconChainBlockVerificationError {
@@ -58,6 +67,16 @@ pub enum BeaconChainBlockVerificationError {
         /// Actual consensus parameters
         actual: Box<OwnedBlockHeaderConsensusParameters>,
     },
+    /// Parent block details not found
+    #[error("Parent block details not found")]
+    ParentBlockDetailsNotFound,
+    /// Invalid consensus constants schedule in the parent block state
+    #[error("Invalid consensus constants schedule in the parent block state: {error}")]
+    InvalidConsensusConstantsSchedule {
+        /// Low-level error
+        #[from]
+        error: ConsensusConstantsScheduleError,
+    },
     /// Missing a super segment in the first block
     #[error("Missing super segment in the first block")]
     MissingSuperSegmentInFirstBlock,
@@ -110,9 +129,97 @@ impl From<BeaconChainBlockVerificationError> for BlockVerificationError {
     }
 }
 
+/// Misbehavior observed during block announcement pre-checks, see
+/// [`BeaconChainBlockVerification::pre_check_announcement()`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BlockAnnouncementMisbehavior {
+    /// Header prefix doesn't correctly extend the parent block
+    InvalidHeaderPrefix,
+    /// Timestamp is too far in the future
+    TimestampTooFarInTheFuture,
+    /// Slot is not after the parent block's slot
+    InvalidSlot,
+    /// Slot is too far in the future
+    SlotTooFarInTheFuture,
+    /// Consensus parameters (including solution range) don't match expected values
+    InvalidConsensusParameters,
+    /// Solution is outside the solution range
+    OutsideSolutionRange,
+    /// Invalid seal
+    InvalidSeal,
+    /// Invalid header extensions
+    InvalidHeaderExtensions,
+}
+
+impl BlockAnnouncementMisbehavior {
+    /// Whether misbehavior is severe enough for the peer to be banned.
+    ///
+    /// Non-fatal misbehavior can be caused by honest peers with clock drift or PoT chain being
+    /// slightly ahead of the local one.
+    pub fn is_fatal(self) -> bool {
+        match self {
+            Self::InvalidHeaderPrefix
+            | Self::InvalidSlot
+            | Self::InvalidConsensusParameters
+            | Self::OutsideSolutionRange
+            | Self::InvalidSeal
+            | Self::InvalidHeaderExtensions => true,
+            Self::TimestampTooFarInTheFuture | Self::SlotTooFarInTheFuture => false,
+        }
+    }
+}
+
+/// Successful result of block announcement pre-checks, see
+/// [`BeaconChainBlockVerification::pre_check_announcement()`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BlockAnnouncementPreCheck {
+    /// All pre-checks passed
+    Passed,
+    /// Parent block is not known, the announcement can't be pre-checked
+    UnknownParent,
+    /// Some pre-checks are inconclusive due to missing local information (like proofs of time that
+    /// were not seen yet), the body can be requested, but with lower priority
+    Inconclusive,
+}
+
+/// Options for [`BeaconChainBlockVerification`]
+#[derive(Debug, Copy, Clone)]
+pub struct BeaconChainBlockVerificationOptions {
+    /// Blocks that are more than this many blocks below the sync target skip some of the
+    /// verification checks that are implied by their descendants.
+    ///
+    /// Specifically, proof of time checkpoints of such blocks are only verified for a random
+    /// sample of blocks, the rest are injected into the verifier as-is. This is safe because
+    /// blocks that far below the tip are confirmed by many descendants whose proofs of time
+    /// extend these checkpoints, so an invalid checkpoint would require the attacker to
+    /// control a chain longer than the confirmation depth. The probability of checking each
+    /// block decreases with the distance from the sync target, but there are still thousands
+    /// of blocks checked in total (see `sample_size` in `full_pot_verification()`). Other
+    /// checks and state transitions are still applied to every block.
+    ///
+    /// Checkpoints verification is expected to dominate the cost of block verification during
+    /// sync, but the actual speedup depends on hardware and chain parameters and has not been
+    /// measured yet.
+    ///
+    /// `None` disables deferred verification and every block is fully verified.
+    ///
+    /// The default value is 1581 blocks.
+    pub deferred_verification_depth: Option<BlockNumber>,
+}
+
+impl Default for BeaconChainBlockVerificationOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            deferred_verification_depth: Some(BlockNumber::from(1_581)),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BeaconChainBlockVerification<PosTable, CI, CSS> {
     consensus_constants: ConsensusConstants,
+    options: BeaconChainBlockVerificationOptions,
     pot_verifier: PotVerifier,
     chain_info: CI,
     chain_sync_status: CSS,
@@ -172,12 +279,14 @@ where
     #[inline(always)]
     pub fn new(
         consensus_constants: ConsensusConstants,
+        options: BeaconChainBlockVerificationOptions,
         pot_verifier: PotVerifier,
         chain_info: CI,
         chain_sync_status: CSS,
     ) -> Self {
         Self {
             consensus_constants,
+            options,
             pot_verifier,
             chain_info,
             chain_sync_status,
@@ -187,17 +296,20 @@ where
 
     /// Determine if full proof of time verification is needed for this block number
     fn full_pot_verification(&self, block_number: BlockNumber) -> bool {
+        let Some(deferred_verification_depth) = self.options.deferred_verification_depth else {
+            return true;
+        };
         let sync_target_block_number = self.chain_sync_status.target_block_number();
         let Some(diff) = sync_target_block_number.checked_sub(block_number) else {
             return true;
         };
+        if diff <= deferred_verification_depth {
+            return true;
+        }
         let diff = u64::from(diff);
 
         let sample_size = match diff {
-            ..=1_581 => {
-                return true;
-            }
-            1_582..=6_234 => 1_581,
+            ..=6_234 => 1_581,
             6_235..=63_240 => 3_162 * (diff - 3_162) / (diff - 1),
             63_241..=3_162_000 => 3_162,
             _ => diff / 1_000,
@@ -208,252 +320,138 @@ where
         n < sample_size
     }
 
-    fn check_header_prefix(
+    /// Consensus constants for the block with changes scheduled in the state of the parent block
+    /// applied.
+    ///
+    /// Parent block is expected to be already imported.
+    fn consensus_constants_for_block(
         &self,
-        parent_header_prefix: &BlockHeaderPrefix,
-        parent_block_mmr_root: &Blake3Hash,
-        header_prefix: &BlockHeaderPrefix,
-    ) -> Result<(), BlockVerificationError> {
-        let basic_valid = header_prefix.number == parent_header_prefix.number + BlockNumber::ONE
-            && header_prefix.shard_index == parent_header_prefix.shard_index
-            && &header_prefix.mmr_root == parent_block_mmr_root
-            && header_prefix.timestamp > parent_header_prefix.timestamp;
-
-        if !basic_valid {
-            return Err(BlockVerificationError::InvalidHeaderPrefix);
-        }
-
-        let timestamp_now = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis();
-        let timestamp_now =
-            BlockTimestamp::from_millis(u64::try_from(timestamp_now).unwrap_or(u64::MAX));
-
-        if header_prefix.timestamp
-            > timestamp_now.saturating_add(self.consensus_constants.max_block_timestamp_drift)
-        {
-            return Err(BlockVerificationError::TimestampTooFarInTheFuture);
-        }
-
-        Ok(())
+        parent_block_root: &BlockRoot,
+        block_number: BlockNumber,
+    ) -> Result<ConsensusConstants, BeaconChainBlockVerificationError> {
+        let (_parent_header, parent_block_details) = self
+            .chain_info
+            .header_with_details(parent_block_root)
+            .ok_or(BeaconChainBlockVerificationError::ParentBlockDetailsNotFound)?;
+
+        Ok(ConsensusConstantsSchedule::from_system_contract_states(
+            &parent_block_details.system_contract_states,
+        )?
+        .consensus_constants_at(&self.consensus_constants, block_number))
     }
 
-    fn check_consensus_parameters_concurrent<BCI>(
+    /// Pre-check block announcement before requesting the block body.
+    ///
+    /// Only cheap checks of the header are done: header prefix, slot range, consensus parameters
+    /// (including solution range), solution distance, seal and proof of time linkage (if the
+    /// corresponding proofs are already known locally). The block still needs to be fully verified
+    /// once the body is downloaded.
+    ///
+    /// `current_slot` is the slot of the local proof of time chain.
+    pub fn pre_check_announcement(
         &self,
-        parent_block_root: &BlockRoot,
-        parent_header: &BeaconChainHeader<'_>,
         header: &BeaconChainHeader<'_>,
-        beacon_chain_info: &BCI,
-    ) -> Result<(), BeaconChainBlockVerificationError>
-    where
-        BCI: DeriveConsensusParametersChainInfo,
-    {
-        let derived_consensus_parameters = derive_consensus_parameters(
-            &self.consensus_constants,
-            beacon_chain_info,
-            parent_block_root,
-            parent_header.consensus_parameters(),
-            parent_header.consensus_info.slot,
-            header.prefix.number,
-            header.consensus_info.slot,
-        )?;
-
-        let expected_consensus_parameters = OwnedBlockHeaderConsensusParameters {
-            fixed_parameters: derived_consensus_parameters.fixed_parameters,
-            // TODO: This field is verified separately in the sequential part
-            super_segment_root: header.consensus_parameters().super_segment_root.copied(),
-            next_solution_range: derived_consensus_parameters.next_solution_range,
-            pot_parameters_change: derived_consensus_parameters.pot_parameters_change,
+        current_slot: SlotNumber,
+    ) -> Result<BlockAnnouncementPreCheck, BlockAnnouncementMisbehavior> {
+        let Some((parent_header, parent_block_details)) = self
+            .chain_info
+            .header_with_details(&header.prefix.parent_root)
+        else {
+            return Ok(BlockAnnouncementPreCheck::UnknownParent);
+        };
+        let parent_header = parent_header.header();
+        let Some(parent_block_mmr_root) = parent_block_details.mmr_with_block.root() else {
+            return Ok(BlockAnnouncementPreCheck::Inconclusive);
         };
 
-        if header.consensus_parameters() != &expected_consensus_parameters.as_ref() {
-            return Err(
-                BeaconChainBlockVerificationError::InvalidConsensusParameters {
-                    expected: Box::new(expected_consensus_parameters),
-                    actual: Box::new(OwnedBlockHeaderConsensusParameters {
-                        fixed_parameters: header.consensus_parameters().fixed_parameters,
-                        super_segment_root: header
-                            .consensus_parameters()
-                            .super_segment_root
-                            .copied(),
-                        next_solution_range: header.consensus_parameters().next_solution_range,
-                        pot_parameters_change: header
-                            .consensus_parameters()
-                            .pot_parameters_change
-                            .copied(),
-                    }),
-                },
-            );
-        }
+        check_header_prefix(
+            self.consensus_constants.max_block_timestamp_drift,
+            parent_header.prefix,
+            &Blake3Hash::from(parent_block_mmr_root),
+            header.prefix,
+        )
+        .map_err(|error| match error {
+            BlockVerificationError::TimestampTooFarInTheFuture => {
+                BlockAnnouncementMisbehavior::TimestampTooFarInTheFuture
+            }
+            _ => BlockAnnouncementMisbehavior::InvalidHeaderPrefix,
+        })?;
 
-        Ok(())
-    }
+        let consensus_info = header.consensus_info;
+        let parent_consensus_info = parent_header.consensus_info;
+        let slot = consensus_info.slot;
 
-    // TODO: This is a blocking function, but ideally wouldn't be block an executor
-    /// Checks current/future proof of time in the consensus info for the slot and corresponding
-    /// checkpoints.
-    ///
-    /// `consensus_parameters` is assumed to be correct and needs to be verified separately.
-    ///
-    /// When `verify_checkpoints == false` checkpoints are assumed to be correct and verification
-    /// for them is skipped.
-    #[expect(
-        clippy::too_many_arguments,
-        reason = "Explicit minimal input for better testability"
-    )]
-    fn check_proof_of_time(
-        pot_verifier: &PotVerifier,
-        block_authoring_delay: SlotNumber,
-        parent_slot: SlotNumber,
-        parent_proof_of_time: PotOutput,
-        parent_future_proof_of_time: PotOutput,
-        parent_consensus_parameters: &BlockHeaderConsensusParameters<'_>,
-        slot: SlotNumber,
-        proof_of_time: PotOutput,
-        future_proof_of_time: PotOutput,
-        checkpoints: &[PotCheckpoints],
-        verify_checkpoints: bool,
-    ) -> Result<(), BeaconChainBlockVerificationError> {
-        let parent_pot_parameters_change = parent_consensus_parameters
-            .pot_parameters_change
-            .copied()
-            .map(PotParametersChange::from);
-
-        // The last checkpoint must be the future proof of time
-        if checkpoints.last().map(PotCheckpoints::output) != Some(future_proof_of_time) {
-            return Err(BeaconChainBlockVerificationError::InvalidPotCheckpoints);
+        let Some(slots_between_blocks) = slot
+            .checked_sub(parent_consensus_info.slot)
+            .filter(|slots| *slots > SlotNumber::ZERO)
+        else {
+            return Err(BlockAnnouncementMisbehavior::InvalidSlot);
+        };
+        if slot > current_slot + SlotNumber::from(MAX_ANNOUNCEMENT_SLOTS_IN_THE_FUTURE) {
+            return Err(BlockAnnouncementMisbehavior::SlotTooFarInTheFuture);
         }
 
-        let future_slot = slot + block_authoring_delay;
-        let parent_future_slot = if parent_slot == SlotNumber::ZERO {
-            parent_slot
-        } else {
-            parent_slot + block_authoring_delay
-        };
+        match check_consensus_parameters_concurrent(
+            &self.consensus_constants,
+            &parent_header.root(),
+            parent_header,
+            header,
+            &self.chain_info,
+        ) {
+            Ok(()) => {}
+            Err(BeaconChainBlockVerificationError::InvalidConsensusParameters { .. }) => {
+                return Err(BlockAnnouncementMisbehavior::InvalidConsensusParameters);
+            }
+            Err(error) => {
+                debug!(%error, "Failed to check consensus parameters of block announcement");
+                return Ok(BlockAnnouncementPreCheck::Inconclusive);
+            }
+        }
 
-        let slots_between_blocks = slot
-            .checked_sub(parent_slot)
-            .ok_or(BeaconChainBlockVerificationError::InvalidPotCheckpoints)?;
-        // The number of checkpoints must match the difference between parent's and this block's
-        // future slots. This also implicitly checks that there is a non-zero number of slots
-        // between this and parent block because the list of checkpoints is already known to be not
-        // empty from the check above.
-        //
-        // The first block after genesis is a special case and is handled separately here.
-        if !(u64::from(slots_between_blocks) == checkpoints.len() as u64
-            || (parent_slot == SlotNumber::ZERO
-                && u64::from(future_slot) == checkpoints.len() as u64))
+        let solution_range = header
+            .consensus_parameters()
+            .fixed_parameters
+            .solution_range;
+        if SolutionChallenges::derive(
+            &consensus_info.solution,
+            &consensus_info.proof_of_time.derive_global_challenge(slot),
+        )
+        .check_solution_range(&consensus_info.solution, solution_range)
+        .is_err()
         {
-            return Err(BeaconChainBlockVerificationError::InvalidPotCheckpoints);
+            return Err(BlockAnnouncementMisbehavior::OutsideSolutionRange);
         }
 
-        let mut pot_input = if parent_slot == SlotNumber::ZERO {
-            PotNextSlotInput {
-                slot: parent_slot + SlotNumber::ONE,
-                slot_iterations: parent_consensus_parameters.fixed_parameters.slot_iterations,
-                seed: pot_verifier.genesis_seed(),
-            }
-        } else {
-            // Calculate slot iterations as of parent future slot
-            let slot_iterations = parent_pot_parameters_change
-                .and_then(|parameters_change| {
-                    (parameters_change.slot <= parent_future_slot)
-                        .then_some(parameters_change.slot_iterations)
-                })
-                .unwrap_or(parent_consensus_parameters.fixed_parameters.slot_iterations);
-            // Derive inputs to the slot, which follows the parent future slot
-            PotNextSlotInput::derive(
-                slot_iterations,
-                parent_future_slot,
-                parent_future_proof_of_time,
-                &parent_pot_parameters_change,
-            )
-        };
+        if !header.is_sealed_correctly() {
+            return Err(BlockAnnouncementMisbehavior::InvalidSeal);
+        }
 
-        // Collect all the data we will use for verification so we can process it in parallel
-        let checkpoints_verification_input = iter::once((
-            pot_input,
-            *checkpoints
-                .first()
-                .expect("Not empty, contents was checked above; qed"),
-        ));
-        let checkpoints_verification_input = checkpoints_verification_input
-            .chain(checkpoints.array_windows::<2>().map(|[left, right]| {
-                pot_input = PotNextSlotInput::derive(
-                    pot_input.slot_iterations,
-                    pot_input.slot,
-                    left.output(),
-                    &parent_pot_parameters_change,
-                );
-
-                (pot_input, *right)
-            }))
-            // TODO: Would be nice to avoid extra allocation here
-            .collect::<Vec<_>>();
-
-        // All checkpoints must be valid, search for the first verification failure
-        let all_checkpoints_valid =
-            checkpoints_verification_input
-                .into_par_iter()
-                .all(|(pot_input, checkpoints)| {
-                    if verify_checkpoints {
-                        pot_verifier.verify_checkpoints(
-                            pot_input.seed,
-                            pot_input.slot_iterations,
-                            &checkpoints,
-                        )
-                    } else {
-                        // Store checkpoints as verified when verification is skipped
-                        pot_verifier.inject_verified_checkpoints(
-                            pot_input.seed,
-                            pot_input.slot_iterations,
-                            checkpoints,
-                        );
-                        true
-                    }
-                });
-
-        if !all_checkpoints_valid {
-            return Err(BeaconChainBlockVerificationError::InvalidPotCheckpoints);
+        if !header.extensions().is_allowed() {
+            return Err(BlockAnnouncementMisbehavior::InvalidHeaderExtensions);
         }
 
-        // Make sure proof of time of this block correctly extends proof of time of the parent block
-        {
-            let pot_input = if parent_slot == SlotNumber::ZERO {
-                PotNextSlotInput {
-                    slot: parent_slot + SlotNumber::ONE,
-                    slot_iterations: parent_consensus_parameters.fixed_parameters.slot_iterations,
-                    seed: pot_verifier.genesis_seed(),
-                }
-            } else {
-                // Calculate slot iterations as of the parent slot
-                let slot_iterations = parent_pot_parameters_change
-                    .and_then(|parameters_change| {
-                        (parameters_change.slot <= parent_slot)
-                            .then_some(parameters_change.slot_iterations)
-                    })
-                    .unwrap_or(parent_consensus_parameters.fixed_parameters.slot_iterations);
-                // Derive inputs to the slot, which follows the parent slot
-                PotNextSlotInput::derive(
-                    slot_iterations,
-                    parent_slot,
-                    parent_proof_of_time,
-                    &parent_pot_parameters_change,
-                )
-            };
-
-            if !pot_verifier.is_output_valid(
-                pot_input,
-                slots_between_blocks,
-                proof_of_time,
-                parent_pot_parameters_change,
-            ) {
-                return Err(BeaconChainBlockVerificationError::InvalidProofOfTime);
-            }
+        let pot_input = pot_input_after_parent_slot(
+            &self.pot_verifier,
+            parent_consensus_info.slot,
+            parent_consensus_info.proof_of_time,
+            parent_header.consensus_parameters(),
+        );
+        // Proofs that are not known locally can't be checked cheaply, in which case the result is
+        // inconclusive rather than invalid
+        if !self.pot_verifier.try_is_output_valid(
+            pot_input,
+            slots_between_blocks,
+            consensus_info.proof_of_time,
+            parent_header
+                .consensus_parameters()
+                .pot_parameters_change
+                .copied()
+                .map(PotParametersChange::from),
+        ) {
+            return Ok(BlockAnnouncementPreCheck::Inconclusive);
         }
 
-        Ok(())
+        Ok(BlockAnnouncementPreCheck::Passed)
     }
 
     fn check_body(
@@ -535,9 +533,15 @@ where
             return Err(BlockVerificationError::BelowArchivingPoint);
         }
 
-        self.check_header_prefix(parent_header.prefix, parent_block_mmr_root, header.prefix)?;
+        check_header_prefix(
+            self.consensus_constants.max_block_timestamp_drift,
+            parent_header.prefix,
+            parent_block_mmr_root,
+            header.prefix,
+        )?;
 
-        self.check_consensus_parameters_concurrent(
+        check_consensus_parameters_concurrent(
+            &self.consensus_constants,
             &parent_block_root,
             parent_header,
             header,
@@ -548,6 +552,10 @@ where
             return Err(BlockVerificationError::InvalidSeal);
         }
 
+        if !header.extensions().is_allowed() {
+            return Err(BlockVerificationError::InvalidHeaderExtensions);
+        }
+
         // Find shard membership entropy for the slot
         let shard_membership_entropy = shard_membership_entropy_source(
             header.prefix.number,
@@ -558,21 +566,19 @@ where
         )?;
 
         // Verify that the solution is valid (stateless half)
-        consensus_info
-            .solution
-            .verify_stateless::<PosTable>(
-                slot,
-                &SolutionVerifyStatelessParams {
-                    shard_index: ShardIndex::BEACON_CHAIN,
-                    proof_of_time: consensus_info.proof_of_time,
-                    solution_range: consensus_parameters.fixed_parameters.solution_range,
-                    shard_membership_entropy,
-                    num_shards: consensus_parameters.fixed_parameters.num_shards,
-                },
-            )
-            .map_err(BeaconChainBlockVerificationError::from)?;
+        verify_stateless::<PosTable>(
+            &consensus_info.solution,
+            &SolutionVerifyStatelessParams {
+                shard_index: ShardIndex::BEACON_CHAIN,
+                global_challenge: consensus_info.proof_of_time.derive_global_challenge(slot),
+                solution_range: consensus_parameters.fixed_parameters.solution_range,
+                shard_membership_entropy,
+                num_shards: consensus_parameters.fixed_parameters.num_shards,
+            },
+        )
+        .map_err(BeaconChainBlockVerificationError::from)?;
 
-        Self::check_proof_of_time(
+        check_proof_of_time(
             &self.pot_verifier,
             self.consensus_constants.block_authoring_delay,
             parent_header.consensus_info.slot,
@@ -640,6 +646,9 @@ where
             ));
         }
 
+        let consensus_constants =
+            self.consensus_constants_for_block(&parent_header.root(), block_number)?;
+
         // Verify that the solution is valid (piece verification half)
         {
             let (current_history_size, solution_num_segments, solution_super_segment_root) =
@@ -680,35 +689,17 @@ where
                     )
                 };
 
-            let sector_expiration_check_super_segment_root = self
-                .chain_info
-                .get_super_segment_header_for_segment_index(
-                    consensus_info
-                        .solution
-                        .history_size
-                        .sector_expiration_check(self.consensus_constants.min_sector_lifetime)
-                        .ok_or(BeaconChainBlockVerificationError::InvalidHistorySize {
-                            history_size: consensus_info.solution.history_size,
-                            current_history_size,
-                        })?
-                        .segment_index(),
-                )
-                .map(|super_segment_header| super_segment_header.root);
-
-            consensus_info
-                .solution
-                .verify_piece(&SolutionVerifyPieceParams {
-                    // TODO: Query it from an actual chain
-                    max_pieces_in_sector: 1000,
-                    super_segment_root: solution_super_segment_root,
-                    num_segments: solution_num_segments,
-                    recent_segments: self.consensus_constants.recent_segments,
-                    recent_history_fraction: self.consensus_constants.recent_history_fraction,
-                    min_sector_lifetime: self.consensus_constants.min_sector_lifetime,
-                    current_history_size,
-                    sector_expiration_check_super_segment_root,
-                })
-                .map_err(BeaconChainBlockVerificationError::from)?;
+            check_solution_piece(
+                &consensus_constants,
+                &consensus_info.solution,
+                current_history_size,
+                solution_num_segments,
+                solution_super_segment_root,
+                |segment_index| {
+                    self.chain_info
+                        .get_super_segment_header_for_segment_index(segment_index)
+                },
+            )?;
         }
 
         self.check_body(
@@ -720,3 +711,311 @@ where
         Ok(maybe_super_segment)
     }
 }
+
+/// Check header prefix against the parent block
+pub(crate) fn check_header_prefix(
+    max_block_timestamp_drift: BlockTimestamp,
+    parent_header_prefix: &BlockHeaderPrefix,
+    parent_block_mmr_root: &Blake3Hash,
+    header_prefix: &BlockHeaderPrefix,
+) -> Result<(), BlockVerificationError> {
+    let basic_valid = header_prefix.number == parent_header_prefix.number + BlockNumber::ONE
+        && header_prefix.shard_index == parent_header_prefix.shard_index
+        && &header_prefix.mmr_root == parent_block_mmr_root
+        && header_prefix.timestamp > parent_header_prefix.timestamp;
+
+    if !basic_valid {
+        return Err(BlockVerificationError::InvalidHeaderPrefix);
+    }
+
+    let timestamp_now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let timestamp_now =
+        BlockTimestamp::from_millis(u64::try_from(timestamp_now).unwrap_or(u64::MAX));
+
+    if header_prefix.timestamp > timestamp_now.saturating_add(max_block_timestamp_drift) {
+        return Err(BlockVerificationError::TimestampTooFarInTheFuture);
+    }
+
+    Ok(())
+}
+
+/// Check consensus parameters in the header, except for the super segment root, which is derived
+/// from the chain state and needs to be checked separately
+pub(crate) fn check_consensus_parameters_concurrent<BCI>(
+    consensus_constants: &ConsensusConstants,
+    parent_block_root: &BlockRoot,
+    parent_header: &BeaconChainHeader<'_>,
+    header: &BeaconChainHeader<'_>,
+    beacon_chain_info: &BCI,
+) -> Result<(), BeaconChainBlockVerificationError>
+where
+    BCI: DeriveConsensusParametersChainInfo,
+{
+    let derived_consensus_parameters = derive_consensus_parameters(
+        consensus_constants,
+        beacon_chain_info,
+        parent_block_root,
+        parent_header.consensus_parameters(),
+        parent_header.consensus_info.slot,
+        header.prefix.number,
+        header.consensus_info.slot,
+    )?;
+
+    let expected_consensus_parameters = OwnedBlockHeaderConsensusParameters {
+        fixed_parameters: derived_consensus_parameters.fixed_parameters,
+        // TODO: This field is verified separately in the sequential part
+        super_segment_root: header.consensus_parameters().super_segment_root.copied(),
+        next_solution_range: derived_consensus_parameters.next_solution_range,
+        pot_parameters_change: derived_consensus_parameters.pot_parameters_change,
+    };
+
+    if header.consensus_parameters() != &expected_consensus_parameters.as_ref() {
+        return Err(
+            BeaconChainBlockVerificationError::InvalidConsensusParameters {
+                expected: Box::new(expected_consensus_parameters),
+                actual: Box::new(OwnedBlockHeaderConsensusParameters {
+                    fixed_parameters: header.consensus_parameters().fixed_parameters,
+                    super_segment_root: header.consensus_parameters().super_segment_root.copied(),
+                    next_solution_range: header.consensus_parameters().next_solution_range,
+                    pot_parameters_change: header
+                        .consensus_parameters()
+                        .pot_parameters_change
+                        .copied(),
+                }),
+            },
+        );
+    }
+
+    Ok(())
+}
+
+// TODO: This is a blocking function, but ideally wouldn't be block an executor
+/// Checks current/future proof of time in the consensus info for the slot and corresponding
+/// checkpoints.
+///
+/// `consensus_parameters` is assumed to be correct and needs to be verified separately.
+///
+/// When `verify_checkpoints == false` checkpoints are assumed to be correct and verification
+/// for them is skipped.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "Explicit minimal input for better testability"
+)]
+pub(crate) fn check_proof_of_time(
+    pot_verifier: &PotVerifier,
+    block_authoring_delay: SlotNumber,
+    parent_slot: SlotNumber,
+    parent_proof_of_time: PotOutput,
+    parent_future_proof_of_time: PotOutput,
+    parent_consensus_parameters: &BlockHeaderConsensusParameters<'_>,
+    slot: SlotNumber,
+    proof_of_time: PotOutput,
+    future_proof_of_time: PotOutput,
+    checkpoints: &[PotCheckpoints],
+    verify_checkpoints: bool,
+) -> Result<(), BeaconChainBlockVerificationError> {
+    let parent_pot_parameters_change = parent_consensus_parameters
+        .pot_parameters_change
+        .copied()
+        .map(PotParametersChange::from);
+
+    // The last checkpoint must be the future proof of time
+    if checkpoints.last().map(PotCheckpoints::output) != Some(future_proof_of_time) {
+        return Err(BeaconChainBlockVerificationError::InvalidPotCheckpoints);
+    }
+
+    let future_slot = slot + block_authoring_delay;
+    let parent_future_slot = if parent_slot == SlotNumber::ZERO {
+        parent_slot
+    } else {
+        parent_slot + block_authoring_delay
+    };
+
+    let slots_between_blocks = slot
+        .checked_sub(parent_slot)
+        .ok_or(BeaconChainBlockVerificationError::InvalidPotCheckpoints)?;
+    // The number of checkpoints must match the difference between parent's and this block's
+    // future slots. This also implicitly checks that there is a non-zero number of slots
+    // between this and parent block because the list of checkpoints is already known to be not
+    // empty from the check above.
+    //
+    // The first block after genesis is a special case and is handled separately here.
+    if !(u64::from(slots_between_blocks) == checkpoints.len() as u64
+        || (parent_slot == SlotNumber::ZERO && u64::from(future_slot) == checkpoints.len() as u64))
+    {
+        return Err(BeaconChainBlockVerificationError::InvalidPotCheckpoints);
+    }
+
+    let mut pot_input = if parent_slot == SlotNumber::ZERO {
+        PotNextSlotInput {
+            slot: parent_slot + SlotNumber::ONE,
+            slot_iterations: parent_consensus_parameters.fixed_parameters.slot_iterations,
+            seed: pot_verifier.genesis_seed(),
+        }
+    } else {
+        // Calculate slot iterations as of parent future slot
+        let slot_iterations = parent_pot_parameters_change
+            .and_then(|parameters_change| {
+                (parameters_change.slot <= parent_future_slot)
+                    .then_some(parameters_change.slot_iterations)
+            })
+            .unwrap_or(parent_consensus_parameters.fixed_parameters.slot_iterations);
+        // Derive inputs to the slot, which follows the parent future slot
+        PotNextSlotInput::derive(
+            slot_iterations,
+            parent_future_slot,
+            parent_future_proof_of_time,
+            &parent_pot_parameters_change,
+        )
+    };
+
+    // Collect all the data we will use for verification so we can process it in parallel
+    let checkpoints_verification_input = iter::once((
+        pot_input,
+        *checkpoints
+            .first()
+            .expect("Not empty, contents was checked above; qed"),
+    ));
+    let checkpoints_verification_input = checkpoints_verification_input
+        .chain(checkpoints.array_windows::<2>().map(|[left, right]| {
+            pot_input = PotNextSlotInput::derive(
+                pot_input.slot_iterations,
+                pot_input.slot,
+                left.output(),
+                &parent_pot_parameters_change,
+            );
+
+            (pot_input, *right)
+        }))
+        // TODO: Would be nice to avoid extra allocation here
+        .collect::<Vec<_>>();
+
+    // All checkpoints must be valid, search for the first verification failure
+    let all_checkpoints_valid =
+        checkpoints_verification_input
+            .into_par_iter()
+            .all(|(pot_input, checkpoints)| {
+                if verify_checkpoints {
+                    pot_verifier.verify_checkpoints(
+                        pot_input.seed,
+                        pot_input.slot_iterations,
+                        &checkpoints,
+                    )
+                } else {
+                    // Store checkpoints as verified when verification is skipped
+                    pot_verifier.inject_verified_checkpoints(
+                        pot_input.seed,
+                        pot_input.slot_iterations,
+                        checkpoints,
+                    );
+                    true
+                }
+            });
+
+    if !all_checkpoints_valid {
+        return Err(BeaconChainBlockVerificationError::InvalidPotCheckpoints);
+    }
+
+    // Make sure proof of time of this block correctly extends proof of time of the parent block
+    {
+        let pot_input = pot_input_after_parent_slot(
+            pot_verifier,
+            parent_slot,
+            parent_proof_of_time,
+            parent_consensus_parameters,
+        );
+
+        if !pot_verifier.is_output_valid(
+            pot_input,
+            slots_between_blocks,
+            proof_of_time,
+            parent_pot_parameters_change,
+        ) {
+            return Err(BeaconChainBlockVerificationError::InvalidProofOfTime);
+        }
+    }
+
+    Ok(())
+}
+
+/// Derive proof of time input for the slot that follows the parent block's slot
+pub(crate) fn pot_input_after_parent_slot(
+    pot_verifier: &PotVerifier,
+    parent_slot: SlotNumber,
+    parent_proof_of_time: PotOutput,
+    parent_consensus_parameters: &BlockHeaderConsensusParameters<'_>,
+) -> PotNextSlotInput {
+    if parent_slot == SlotNumber::ZERO {
+        return PotNextSlotInput {
+            slot: parent_slot + SlotNumber::ONE,
+            slot_iterations: parent_consensus_parameters.fixed_parameters.slot_iterations,
+            seed: pot_verifier.genesis_seed(),
+        };
+    }
+
+    let parent_pot_parameters_change = parent_consensus_parameters
+        .pot_parameters_change
+        .copied()
+        .map(PotParametersChange::from);
+    // Calculate slot iterations as of the parent slot
+    let slot_iterations = parent_pot_parameters_change
+        .and_then(|parameters_change| {
+            (parameters_change.slot <= parent_slot).then_some(parameters_change.slot_iterations)
+        })
+        .unwrap_or(parent_consensus_parameters.fixed_parameters.slot_iterations);
+    // Derive inputs to the slot, which follows the parent slot
+    PotNextSlotInput::derive(
+        slot_iterations,
+        parent_slot,
+        parent_proof_of_time,
+        &parent_pot_parameters_change,
+    )
+}
+
+/// Verify the piece verification half of the solution.
+///
+/// `get_super_segment_header_for_segment_index` returns the super segment header that includes the
+/// specified segment index (if known).
+pub(crate) fn check_solution_piece<SSH>(
+    consensus_constants: &ConsensusConstants,
+    solution: &Solution,
+    current_history_size: HistorySize,
+    solution_num_segments: u32,
+    solution_super_segment_root: SuperSegmentRoot,
+    get_super_segment_header_for_segment_index: SSH,
+) -> Result<(), BeaconChainBlockVerificationError>
+where
+    SSH: FnOnce(SegmentIndex) -> Option<SuperSegmentHeader>,
+{
+    let sector_expiration_check_super_segment_root = get_super_segment_header_for_segment_index(
+        solution
+            .history_size
+            .sector_expiration_check(consensus_constants.min_sector_lifetime)
+            .ok_or(BeaconChainBlockVerificationError::InvalidHistorySize {
+                history_size: solution.history_size,
+                current_history_size,
+            })?
+            .segment_index(),
+    )
+    .map(|super_segment_header| super_segment_header.root);
+
+    verify_piece(
+        solution,
+        &SolutionVerifyPieceParams {
+            // TODO: Query it from an actual chain
+            max_pieces_in_sector: 1000,
+            super_segment_root: solution_super_segment_root,
+            num_segments: solution_num_segments,
+            recent_segments: consensus_constants.recent_segments,
+            recent_history_fraction: consensus_constants.recent_history_fraction,
+            min_sector_lifetime: consensus_constants.min_sector_lifetime,
+            current_history_size,
+            sector_expiration_check_super_segment_root,
+        },
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! Verification of beacon chain references in intermediate and leaf shard blocks.
+//!
+//! Every shard block references a beacon chain block (number and root) in its header. The
+//! reference must point to a known beacon chain block, which is either on the canonical beacon
+//! chain or recent enough to potentially become canonical after a reorg. References must also
+//! never go backwards: the beacon chain block referenced by the parent shard block must be an
+//! ancestor of (or the same as) the beacon chain block referenced by its child.
+
+use ab_client_api::ChainInfo;
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::block::header::BlockHeaderBeaconChainInfo;
+use ab_core_primitives::block::header::owned::GenericOwnedBlockHeader;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use tracing::trace;
+
+/// Errors for [`verify_beacon_chain_ref()`]
+#[derive(Debug, thiserror::Error)]
+pub enum BeaconChainRefVerificationError {
+    /// Referenced beacon chain block is not known
+    #[error("Referenced beacon chain block {reference:?} is not known")]
+    UnknownBlock {
+        /// Beacon chain reference
+        reference: BlockHeaderBeaconChainInfo,
+    },
+    /// Referenced beacon chain block number doesn't match the actual number of the block
+    #[error(
+        "Referenced beacon chain block {reference:?} has number {actual_number} instead of \
+        {expected_number}"
+    )]
+    NumberMismatch {
+        /// Beacon chain reference
+        reference: BlockHeaderBeaconChainInfo,
+        /// Number in the reference
+        expected_number: BlockNumber,
+        /// Actual number of the block with referenced root
+        actual_number: BlockNumber,
+    },
+    /// Referenced beacon chain block is neither canonical nor recent enough to become canonical
+    #[error(
+        "Referenced beacon chain block {reference:?} is not canonical and too deep (best block \
+        {best_number}, max depth {max_non_canonical_depth})"
+    )]
+    NotCanonical {
+        /// Beacon chain reference
+        reference: BlockHeaderBeaconChainInfo,
+        /// Best beacon chain block number
+        best_number: BlockNumber,
+        /// Max depth at which non-canonical beacon chain blocks can be referenced
+        max_non_canonical_depth: BlockNumber,
+    },
+    /// Beacon chain reference goes backwards compared to the parent shard block
+    #[error(
+        "Beacon chain reference {reference:?} goes backwards compared to parent reference \
+        {parent_reference:?}"
+    )]
+    NotMonotonic {
+        /// Beacon chain reference
+        reference: BlockHeaderBeaconChainInfo,
+        /// Beacon chain reference of the parent shard block
+        parent_reference: BlockHeaderBeaconChainInfo,
+    },
+    /// Beacon chain block referenced by the parent shard block is not an ancestor of the
+    /// referenced beacon chain block
+    #[error(
+        "Parent beacon chain reference {parent_reference:?} is not an ancestor of \
+        {reference:?}"
+    )]
+    ParentReferenceNotAncestor {
+        /// Beacon chain reference
+        reference: BlockHeaderBeaconChainInfo,
+        /// Beacon chain reference of the parent shard block
+        parent_reference: BlockHeaderBeaconChainInfo,
+    },
+}
+
+/// Verify beacon chain reference of a shard block.
+///
+/// `parent_reference` is the beacon chain reference of the parent shard block, `None` for the
+/// first block of the shard. `max_non_canonical_depth` is the max depth relative to the best
+/// beacon chain block at which beacon chain blocks that are not on the canonical chain can be
+/// referenced, typically block confirmation depth.
+///
+/// When the beacon chain block referenced by the parent is no longer known (was pruned), only the
+/// monotonicity of block numbers is checked.
+pub fn verify_beacon_chain_ref<BCI>(
+    beacon_chain_info: &BCI,
+    reference: &BlockHeaderBeaconChainInfo,
+    parent_reference: Option<&BlockHeaderBeaconChainInfo>,
+    max_non_canonical_depth: BlockNumber,
+) -> Result<(), BeaconChainRefVerificationError>
+where
+    BCI: ChainInfo<OwnedBeaconChainBlock>,
+{
+    let reference = *reference;
+
+    let header = beacon_chain_info
+        .header(&reference.root)
+        .ok_or(BeaconChainRefVerificationError::UnknownBlock { reference })?;
+    let actual_number = header.header().prefix.number;
+    if actual_number != reference.number {
+        return Err(BeaconChainRefVerificationError::NumberMismatch {
+            reference,
+            expected_number: reference.number,
+            actual_number,
+        });
+    }
+
+    let best_root = beacon_chain_info.best_root();
+    let is_canonical = reference.root == best_root
+        || beacon_chain_info
+            .ancestor_header(reference.number, &best_root)
+            .is_some_and(|ancestor_header| *ancestor_header.header().root() == reference.root);
+    if !is_canonical {
+        let best_number = beacon_chain_info.best_header().header().prefix.number;
+
+        if best_number.saturating_sub(reference.number) > max_non_canonical_depth {
+            return Err(BeaconChainRefVerificationError::NotCanonical {
+                reference,
+                best_number,
+                max_non_canonical_depth,
+            });
+        }
+
+        trace!(?reference, %best_number, "Beacon chain reference is not canonical, but recent");
+    }
+
+    let Some(&parent_reference) = parent_reference else {
+        return Ok(());
+    };
+
+    if reference.number < parent_reference.number
+        || (reference.number == parent_reference.number && reference.root != parent_reference.root)
+    {
+        return Err(BeaconChainRefVerificationError::NotMonotonic {
+            reference,
+            parent_reference,
+        });
+    }
+
+    if reference.number > parent_reference.number
+        && let Some(ancestor_header) =
+            beacon_chain_info.ancestor_header(parent_reference.number, &reference.root)
+        && *ancestor_header.header().root() != parent_reference.root
+    {
+        return Err(
+            BeaconChainRefVerificationError::ParentReferenceNotAncestor {
+                reference,
+                parent_reference,
+            },
+        );
+    }
+
+    Ok(())
+}
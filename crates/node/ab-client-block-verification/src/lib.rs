@@ -1,5 +1,13 @@
+#![expect(incomplete_features, reason = "generic_const_exprs")]
+// TODO: This feature is not actually used in this crate, but is added as a workaround for
+//  https://github.com/rust-lang/rust/issues/141492
+#![feature(generic_const_exprs)]
+
 pub mod beacon_chain;
+pub mod beacon_chain_ref;
+pub mod stateless;
 
+use crate::beacon_chain_ref::BeaconChainRefVerificationError;
 use ab_client_api::BlockOrigin;
 use ab_client_consensus_common::consensus_parameters::{
     DeriveConsensusParametersChainInfo, ShardMembershipEntropySourceChainInfo,
@@ -31,6 +39,9 @@ pub enum BlockVerificationError {
     /// Invalid seal
     #[error("Invalid seal")]
     InvalidSeal,
+    /// Invalid header extensions
+    #[error("Invalid header extensions")]
+    InvalidHeaderExtensions,
     /// Invalid own segments
     #[error("Invalid own segments")]
     InvalidOwnSegments {
@@ -43,6 +54,13 @@ pub enum BlockVerificationError {
         /// Actual segment roots (invalid)
         actual_segment_roots: Vec<SegmentRoot>,
     },
+    /// Invalid beacon chain reference
+    #[error("Invalid beacon chain reference: {error}")]
+    InvalidBeaconChainRef {
+        /// Low-level error
+        #[from]
+        error: BeaconChainRefVerificationError,
+    },
     /// Shard membership entropy source error
     #[error("Shard membership entropy source error: {error}")]
     ShardMembershipEntropySource {
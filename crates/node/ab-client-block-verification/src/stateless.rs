@@ -0,0 +1,646 @@
+//! Stateless verification of beacon chain blocks.
+//!
+//! [`BlockVerificationBundle`] packages everything necessary to verify a single beacon chain block
+//! without access to the rest of the chain, which allows verification services and bridges to
+//! verify blocks without running a full node. Bundles are created by a node with
+//! [`create_verification_bundle()`] and verified with [`verify_bundle()`], which only trusts
+//! consensus constants and the genesis seed of the proof of time chain (through [`PotVerifier`]).
+//!
+//! The bundle contains:
+//! * the block itself
+//! * contiguous ancestor headers ending with the parent block header, authenticated by the parent
+//!   block root in the block header
+//! * peaks of the Merkle Mountain Range with all blocks before the parent block, authenticated by
+//!   the MMR root in the parent block header, which allows checking the MMR root in the block
+//!   header
+//! * system contracts state after the parent block (including consensus constants schedule),
+//!   authenticated by the state root in the parent block header
+//! * contiguous super segment headers up to the latest super segment before the block,
+//!   authenticated by hashes of previous super segment headers and the ancestor block header that
+//!   committed to the latest super segment root
+//!
+//! Ancestor headers go back far enough to derive consensus parameters, shard membership entropy
+//! and to include the block that committed to the latest super segment root, which means bundles
+//! can be large when super segments are created infrequently.
+//!
+//! Proof of time is verified fully, including all checkpoints. Shard membership entropy is derived
+//! relative to the parent block rather than the best block.
+//!
+//! The following is not verified and needs to be checked separately: own segments in the block
+//! body and the super segment root committed to in the block header (both require access to the
+//! archived history) and the state root (requires block execution).
+
+use crate::BlockVerificationError;
+use crate::beacon_chain::{
+    BeaconChainBlockVerificationError, check_consensus_parameters_concurrent, check_header_prefix,
+    check_proof_of_time, check_solution_piece,
+};
+use ab_client_api::{BeaconChainInfo, BlockMerkleMountainRange, BlockMmrPeaks, ContractSlotState};
+use ab_client_consensus_common::ConsensusConstants;
+use ab_client_consensus_common::consensus_constants::{
+    ConsensusConstantsSchedule, ConsensusConstantsScheduleError,
+};
+use ab_client_consensus_common::consensus_parameters::{
+    DeriveConsensusParametersChainInfo, DeriveConsensusParametersConsensusInfo,
+    DeriveConsensusParametersError, ShardMembershipEntropySourceChainInfo,
+    ShardMembershipEntropySourceError, derive_consensus_parameters,
+    shard_membership_entropy_source,
+};
+use ab_client_consensus_common::state::GlobalState;
+use ab_client_proof_of_time::verifier::PotVerifier;
+use ab_core_primitives::block::header::BeaconChainHeader;
+use ab_core_primitives::block::header::owned::OwnedBeaconChainHeader;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::pot::PotOutput;
+use ab_core_primitives::segments::{
+    HistorySize, SegmentIndex, SuperSegmentHeader, SuperSegmentIndex,
+};
+use ab_core_primitives::shard::ShardIndex;
+use ab_proof_of_space::Table;
+use ab_solution_verification::{SolutionVerifyStatelessParams, verify_stateless};
+use std::sync::Arc as StdArc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Everything necessary to verify a single beacon chain block statelessly, see
+/// [module documentation](self) for details
+#[derive(Debug, Clone)]
+pub struct BlockVerificationBundle {
+    /// Block to verify
+    pub block: OwnedBeaconChainBlock,
+    /// Contiguous ancestor headers in ascending order, the last one is the parent block header
+    pub ancestor_headers: Vec<OwnedBeaconChainHeader>,
+    /// Peaks of the Merkle Mountain Range with all blocks before the parent block
+    pub parent_mmr_peaks: BlockMmrPeaks,
+    /// System contracts state after the parent block
+    pub parent_system_contract_states: StdArc<[ContractSlotState]>,
+    /// Contiguous super segment headers in ascending order, the last one is the latest super
+    /// segment header before the block
+    pub super_segment_headers: Vec<SuperSegmentHeader>,
+}
+
+/// Error for [`create_verification_bundle()`]
+#[derive(Debug, thiserror::Error)]
+pub enum CreateVerificationBundleError {
+    /// Blocks before block #2 are not supported
+    #[error("Block #{block_number} is not supported, only blocks after block #1 are")]
+    UnsupportedBlock {
+        /// Block number
+        block_number: BlockNumber,
+    },
+    /// Parent block not found
+    #[error("Parent block {parent_root} not found")]
+    UnknownParent {
+        /// Parent block root
+        parent_root: BlockRoot,
+    },
+    /// Block details are not available (anymore)
+    #[error("Block details of {block_root} are not available")]
+    MissingBlockDetails {
+        /// Block root
+        block_root: BlockRoot,
+    },
+    /// Ancestor header not found
+    #[error("Ancestor header #{block_number} not found")]
+    MissingAncestorHeader {
+        /// Block number
+        block_number: BlockNumber,
+    },
+    /// Previous super segment header not found
+    #[error("Previous super segment header not found")]
+    PreviousSuperSegmentHeaderNotFound,
+    /// Super segment header not found
+    #[error("Super segment header {index} not found")]
+    MissingSuperSegmentHeader {
+        /// Super segment index
+        index: SuperSegmentIndex,
+    },
+    /// Invalid consensus constants schedule in the parent block state
+    #[error("Invalid consensus constants schedule in the parent block state: {error}")]
+    InvalidConsensusConstantsSchedule {
+        /// Low-level error
+        #[from]
+        error: ConsensusConstantsScheduleError,
+    },
+    /// Consensus parameters derivation error
+    #[error("Consensus parameters derivation error: {error}")]
+    ConsensusParametersDerivation {
+        /// Low-level error
+        #[from]
+        error: DeriveConsensusParametersError,
+    },
+    /// Shard membership entropy source error
+    #[error("Shard membership entropy source error: {error}")]
+    ShardMembershipEntropySource {
+        /// Low-level error
+        #[from]
+        error: ShardMembershipEntropySourceError,
+    },
+}
+
+/// Error for [`verify_bundle()`]
+#[derive(Debug, thiserror::Error)]
+pub enum BundleVerificationError {
+    /// Blocks before block #2 are not supported
+    #[error("Block #{block_number} is not supported, only blocks after block #1 are")]
+    UnsupportedBlock {
+        /// Block number
+        block_number: BlockNumber,
+    },
+    /// Ancestor headers do not form a chain that ends with the parent block
+    #[error("Ancestor headers do not form a chain that ends with the parent block")]
+    InvalidAncestorHeaders,
+    /// MMR peaks do not match the MMR root in the parent block header
+    #[error("MMR peaks do not match the MMR root in the parent block header")]
+    InvalidMmrPeaks,
+    /// State doesn't match the state root in the parent block header
+    #[error("Parent state root mismatch: expected {expected}, actual {actual}")]
+    ParentStateRootMismatch {
+        /// State root in the parent block header
+        expected: Blake3Hash,
+        /// State root of the state
+        actual: Blake3Hash,
+    },
+    /// Super segment headers do not form a chain
+    #[error("Super segment headers do not form a chain")]
+    InvalidSuperSegmentHeaders,
+    /// The last super segment header is not the latest super segment header committed to by
+    /// ancestor headers
+    #[error("The last super segment header is not the latest one committed to by ancestor headers")]
+    UncommittedSuperSegmentHeader,
+    /// Super segment header necessary for verification is missing
+    #[error("Super segment header for segment index {segment_index} is missing")]
+    MissingSuperSegmentHeader {
+        /// Segment index
+        segment_index: SegmentIndex,
+    },
+    /// Block verification error
+    #[error("Block verification error: {error}")]
+    BlockVerification {
+        /// Low-level error
+        #[from]
+        error: BlockVerificationError,
+    },
+    /// Beacon chain block verification error
+    #[error("Beacon chain block verification error: {error}")]
+    BeaconChainBlockVerification {
+        /// Low-level error
+        #[from]
+        error: BeaconChainBlockVerificationError,
+    },
+}
+
+/// Ancestor headers as a source of information for consensus parameters derivation
+struct AncestorHeaders<'a> {
+    /// Contiguous headers in ascending order
+    headers: &'a [OwnedBeaconChainHeader],
+}
+
+impl DeriveConsensusParametersChainInfo for AncestorHeaders<'_> {
+    fn ancestor_header_consensus_info(
+        &self,
+        ancestor_block_number: BlockNumber,
+        descendant_block_root: &BlockRoot,
+    ) -> Option<DeriveConsensusParametersConsensusInfo> {
+        let header = self.ancestor_header(ancestor_block_number, descendant_block_root)?;
+
+        Some(DeriveConsensusParametersConsensusInfo::from_consensus_info(
+            header.consensus_info,
+        ))
+    }
+}
+
+impl ShardMembershipEntropySourceChainInfo for AncestorHeaders<'_> {
+    fn ancestor_header_proof_of_time(
+        &self,
+        ancestor_block_number: BlockNumber,
+        descendant_block_root: &BlockRoot,
+    ) -> Option<PotOutput> {
+        let header = self.ancestor_header(ancestor_block_number, descendant_block_root)?;
+
+        Some(header.consensus_info.proof_of_time)
+    }
+}
+
+impl AncestorHeaders<'_> {
+    fn ancestor_header(
+        &self,
+        ancestor_block_number: BlockNumber,
+        descendant_block_root: &BlockRoot,
+    ) -> Option<&BeaconChainHeader<'_>> {
+        let descendant_offset = self
+            .headers
+            .iter()
+            .rposition(|header| &*header.header().root() == descendant_block_root)?;
+        let first_block_number = self.headers.first()?.header().prefix.number;
+        let offset = u64::from(ancestor_block_number.checked_sub(first_block_number)?) as usize;
+
+        (offset <= descendant_offset).then(|| self.headers[offset].header())
+    }
+}
+
+/// Records the lowest ancestor block number requested through it
+struct RecordingChainInfo<'a, CI> {
+    chain_info: &'a CI,
+    lowest_block_number: AtomicU64,
+}
+
+impl<CI> DeriveConsensusParametersChainInfo for RecordingChainInfo<'_, CI>
+where
+    CI: BeaconChainInfo,
+{
+    fn ancestor_header_consensus_info(
+        &self,
+        ancestor_block_number: BlockNumber,
+        descendant_block_root: &BlockRoot,
+    ) -> Option<DeriveConsensusParametersConsensusInfo> {
+        self.lowest_block_number
+            .fetch_min(u64::from(ancestor_block_number), Ordering::Relaxed);
+
+        DeriveConsensusParametersChainInfo::ancestor_header_consensus_info(
+            self.chain_info,
+            ancestor_block_number,
+            descendant_block_root,
+        )
+    }
+}
+
+impl<CI> ShardMembershipEntropySourceChainInfo for RecordingChainInfo<'_, CI>
+where
+    CI: BeaconChainInfo,
+{
+    fn ancestor_header_proof_of_time(
+        &self,
+        ancestor_block_number: BlockNumber,
+        descendant_block_root: &BlockRoot,
+    ) -> Option<PotOutput> {
+        self.lowest_block_number
+            .fetch_min(u64::from(ancestor_block_number), Ordering::Relaxed);
+
+        ShardMembershipEntropySourceChainInfo::ancestor_header_proof_of_time(
+            self.chain_info,
+            ancestor_block_number,
+            descendant_block_root,
+        )
+    }
+}
+
+/// Create a bundle for stateless verification of the `block`, see [module documentation](self)
+/// for details.
+///
+/// The parent block and its parent must not be confirmed yet since their details (state and MMR)
+/// are only retained until then.
+pub fn create_verification_bundle<CI>(
+    chain_info: &CI,
+    consensus_constants: &ConsensusConstants,
+    block: OwnedBeaconChainBlock,
+) -> Result<BlockVerificationBundle, CreateVerificationBundleError>
+where
+    CI: BeaconChainInfo,
+{
+    let header = block.header.header();
+    let block_number = header.prefix.number;
+    if block_number <= BlockNumber::ONE {
+        return Err(CreateVerificationBundleError::UnsupportedBlock { block_number });
+    }
+
+    let parent_root = header.prefix.parent_root;
+    let (parent_header, parent_block_details) = chain_info
+        .header_with_details(&parent_root)
+        .ok_or(CreateVerificationBundleError::UnknownParent { parent_root })?;
+    let grandparent_root = parent_header.header().prefix.parent_root;
+    let (_grandparent_header, grandparent_block_details) = chain_info
+        .header_with_details(&grandparent_root)
+        .ok_or(CreateVerificationBundleError::MissingBlockDetails {
+            block_root: grandparent_root,
+        })?;
+
+    // Find the lowest ancestor necessary for consensus parameters derivation and shard membership
+    // entropy by running derivation against chain info that records requested ancestors
+    let recording_chain_info = RecordingChainInfo {
+        chain_info,
+        lowest_block_number: AtomicU64::new(u64::MAX),
+    };
+    derive_consensus_parameters(
+        consensus_constants,
+        &recording_chain_info,
+        &parent_root,
+        parent_header.header().consensus_parameters(),
+        parent_header.header().consensus_info.slot,
+        block_number,
+        header.consensus_info.slot,
+    )?;
+    shard_membership_entropy_source(
+        block_number,
+        parent_header.header(),
+        consensus_constants.shard_rotation_interval,
+        consensus_constants.shard_rotation_delay,
+        &recording_chain_info,
+    )?;
+
+    let previous_super_segment_header = chain_info
+        .previous_super_segment_header(block_number)
+        .ok_or(CreateVerificationBundleError::PreviousSuperSegmentHeaderNotFound)?;
+
+    let parent_number = parent_header.header().prefix.number;
+    let first_block_number = BlockNumber::from(
+        recording_chain_info
+            .lowest_block_number
+            .into_inner()
+            .min(u64::from(
+                previous_super_segment_header
+                    .target_beacon_chain_block_number
+                    .as_inner(),
+            ))
+            .min(u64::from(parent_number)),
+    );
+
+    let mut ancestor_headers =
+        Vec::with_capacity(u64::from(parent_number - first_block_number) as usize + 1);
+    // Ancestors are collected from the parent block backwards
+    ancestor_headers.push(parent_header);
+    while let Some(last_header) = ancestor_headers.last()
+        && last_header.header().prefix.number > first_block_number
+    {
+        let block_number = last_header.header().prefix.number - BlockNumber::ONE;
+        let ancestor_header = chain_info
+            .header(&last_header.header().prefix.parent_root)
+            .ok_or(CreateVerificationBundleError::MissingAncestorHeader { block_number })?;
+        ancestor_headers.push(ancestor_header);
+    }
+    ancestor_headers.reverse();
+
+    let consensus_constants = ConsensusConstantsSchedule::from_system_contract_states(
+        &parent_block_details.system_contract_states,
+    )?
+    .consensus_constants_at(consensus_constants, block_number);
+
+    // Super segment headers necessary for solution verification
+    let solution = &header.consensus_info.solution;
+    let mut first_super_segment_index = solution
+        .piece_super_segment_index
+        .min(previous_super_segment_header.index.as_inner());
+    if let Some(sector_expiration_check_segment_index) =
+        sector_expiration_check_segment_index(&consensus_constants, solution.history_size)
+        && let Some(super_segment_header) = chain_info
+            .get_super_segment_header_for_segment_index(sector_expiration_check_segment_index)
+    {
+        first_super_segment_index =
+            first_super_segment_index.min(super_segment_header.index.as_inner());
+    }
+    let super_segment_headers = (first_super_segment_index
+        ..=previous_super_segment_header.index.as_inner())
+        .map(|index| {
+            chain_info
+                .get_super_segment_header(index)
+                .ok_or(CreateVerificationBundleError::MissingSuperSegmentHeader { index })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(BlockVerificationBundle {
+        block,
+        ancestor_headers,
+        parent_mmr_peaks: grandparent_block_details.mmr_with_block.peaks(),
+        parent_system_contract_states: parent_block_details.system_contract_states,
+        super_segment_headers,
+    })
+}
+
+/// Verify a bundle created with [`create_verification_bundle()`], see
+/// [module documentation](self) for details
+pub fn verify_bundle<PosTable>(
+    bundle: &BlockVerificationBundle,
+    consensus_constants: &ConsensusConstants,
+    pot_verifier: &PotVerifier,
+) -> Result<(), BundleVerificationError>
+where
+    PosTable: Table,
+{
+    let BlockVerificationBundle {
+        block,
+        ancestor_headers,
+        parent_mmr_peaks,
+        parent_system_contract_states,
+        super_segment_headers,
+    } = bundle;
+
+    let header = block.header.header();
+    let body = block.body.body();
+    let block_number = header.prefix.number;
+    if block_number <= BlockNumber::ONE {
+        return Err(BundleVerificationError::UnsupportedBlock { block_number });
+    }
+
+    // Ancestor headers must form a chain that ends with the parent block
+    let Some(parent_header) = ancestor_headers.last() else {
+        return Err(BundleVerificationError::InvalidAncestorHeaders);
+    };
+    let parent_header = parent_header.header();
+    let parent_root = *parent_header.root();
+    if parent_root != header.prefix.parent_root
+        || !ancestor_headers.array_windows::<2>().all(|[left, right]| {
+            let left = left.header();
+            let right = right.header();
+
+            right.prefix.parent_root == *left.root()
+                && right.prefix.number == left.prefix.number + BlockNumber::ONE
+        })
+    {
+        return Err(BundleVerificationError::InvalidAncestorHeaders);
+    }
+    let ancestors = AncestorHeaders {
+        headers: ancestor_headers,
+    };
+
+    // MMR peaks must match the MMR root in the parent header, the parent is then appended to get
+    // the MMR root for the block header
+    let parent_block_mmr_root = {
+        let mut mmr = BlockMerkleMountainRange::from_peaks(parent_mmr_peaks)
+            .ok_or(BundleVerificationError::InvalidMmrPeaks)?;
+        if mmr.num_leaves() != u64::from(parent_header.prefix.number)
+            || mmr.root().map(Blake3Hash::from) != Some(parent_header.prefix.mmr_root)
+            || !mmr.add_leaf(&parent_root)
+        {
+            return Err(BundleVerificationError::InvalidMmrPeaks);
+        }
+
+        Blake3Hash::from(
+            mmr.root()
+                .expect("Leaf was just added successfully, hence root exists; qed"),
+        )
+    };
+
+    // System contracts state must match the state root in the parent header
+    let parent_state_root = GlobalState::new(parent_system_contract_states).root();
+    if parent_state_root != parent_header.result.state_root {
+        return Err(BundleVerificationError::ParentStateRootMismatch {
+            expected: parent_header.result.state_root,
+            actual: parent_state_root,
+        });
+    }
+
+    check_header_prefix(
+        consensus_constants.max_block_timestamp_drift,
+        parent_header.prefix,
+        &parent_block_mmr_root,
+        header.prefix,
+    )?;
+
+    check_consensus_parameters_concurrent(
+        consensus_constants,
+        &parent_root,
+        parent_header,
+        header,
+        &ancestors,
+    )?;
+
+    if !header.is_sealed_correctly() {
+        return Err(BlockVerificationError::InvalidSeal.into());
+    }
+
+    if !header.extensions().is_allowed() {
+        return Err(BlockVerificationError::InvalidHeaderExtensions.into());
+    }
+
+    let consensus_info = header.consensus_info;
+    let consensus_parameters = header.consensus_parameters();
+    let slot = consensus_info.slot;
+
+    let shard_membership_entropy = shard_membership_entropy_source(
+        block_number,
+        parent_header,
+        consensus_constants.shard_rotation_interval,
+        consensus_constants.shard_rotation_delay,
+        &ancestors,
+    )
+    .map_err(BlockVerificationError::from)?;
+
+    // Verify that the solution is valid (stateless half)
+    verify_stateless::<PosTable>(
+        &consensus_info.solution,
+        &SolutionVerifyStatelessParams {
+            shard_index: ShardIndex::BEACON_CHAIN,
+            global_challenge: consensus_info.proof_of_time.derive_global_challenge(slot),
+            solution_range: consensus_parameters.fixed_parameters.solution_range,
+            shard_membership_entropy,
+            num_shards: consensus_parameters.fixed_parameters.num_shards,
+        },
+    )
+    .map_err(BeaconChainBlockVerificationError::from)?;
+
+    check_proof_of_time(
+        pot_verifier,
+        consensus_constants.block_authoring_delay,
+        parent_header.consensus_info.slot,
+        parent_header.consensus_info.proof_of_time,
+        parent_header.consensus_info.future_proof_of_time,
+        parent_header.consensus_parameters(),
+        slot,
+        consensus_info.proof_of_time,
+        consensus_info.future_proof_of_time,
+        body.pot_checkpoints(),
+        true,
+    )?;
+
+    // Super segment headers must form a chain, the last of which is committed to by an ancestor
+    // header and no later ancestor header commits to another super segment
+    let Some(latest_super_segment_header) = super_segment_headers.last() else {
+        return Err(BundleVerificationError::InvalidSuperSegmentHeaders);
+    };
+    if !super_segment_headers
+        .array_windows::<2>()
+        .all(|[left, right]| {
+            right.index.as_inner() == left.index.as_inner() + SuperSegmentIndex::ONE
+                && right.prev_super_segment_header_hash == left.hash()
+        })
+    {
+        return Err(BundleVerificationError::InvalidSuperSegmentHeaders);
+    }
+    {
+        let target_block_number = latest_super_segment_header
+            .target_beacon_chain_block_number
+            .as_inner();
+        let mut committing_headers = ancestor_headers
+            .iter()
+            .map(|header| header.header())
+            .filter(|header| {
+                header.prefix.number >= target_block_number
+                    && header.consensus_parameters().super_segment_root.is_some()
+            });
+        // The first committing header at or after the target block must be the target block
+        // itself and there must be no other committing headers after it
+        let committed_correctly = committing_headers.next().is_some_and(|header| {
+            header.prefix.number == target_block_number
+                && header.consensus_parameters().super_segment_root
+                    == Some(&latest_super_segment_header.root)
+        });
+        if !committed_correctly || committing_headers.next().is_some() {
+            return Err(BundleVerificationError::UncommittedSuperSegmentHeader);
+        }
+    }
+
+    let consensus_constants =
+        ConsensusConstantsSchedule::from_system_contract_states(parent_system_contract_states)
+            .map_err(BeaconChainBlockVerificationError::from)?
+            .consensus_constants_at(consensus_constants, block_number);
+
+    // Verify that the solution is valid (piece verification half)
+    {
+        let solution = &consensus_info.solution;
+        let find_super_segment_header = |segment_index: SegmentIndex| {
+            super_segment_headers
+                .iter()
+                .find(|super_segment_header| {
+                    let max_segment_index =
+                        u64::from(super_segment_header.max_segment_index.as_inner());
+                    let first_segment_index = (max_segment_index + 1)
+                        .saturating_sub(u64::from(super_segment_header.num_segments));
+
+                    (first_segment_index..=max_segment_index).contains(&u64::from(segment_index))
+                })
+                .copied()
+        };
+
+        let max_segment_index = latest_super_segment_header.max_segment_index.as_inner();
+        // Super segment header must be present in the bundle if it exists before the block
+        if let Some(segment_index) =
+            sector_expiration_check_segment_index(&consensus_constants, solution.history_size)
+            && segment_index <= max_segment_index
+            && find_super_segment_header(segment_index).is_none()
+        {
+            return Err(BundleVerificationError::MissingSuperSegmentHeader { segment_index });
+        }
+
+        let solution_super_segment_header = super_segment_headers
+            .iter()
+            .find(|super_segment_header| {
+                super_segment_header.index.as_inner() == solution.piece_super_segment_index
+            })
+            .ok_or(
+                BeaconChainBlockVerificationError::SolutionSuperSegmentNotFound {
+                    index: solution.piece_super_segment_index,
+                },
+            )?;
+
+        check_solution_piece(
+            &consensus_constants,
+            solution,
+            HistorySize::from(max_segment_index),
+            solution_super_segment_header.num_segments,
+            solution_super_segment_header.root,
+            find_super_segment_header,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn sector_expiration_check_segment_index(
+    consensus_constants: &ConsensusConstants,
+    history_size: HistorySize,
+) -> Option<SegmentIndex> {
+    history_size
+        .sector_expiration_check(consensus_constants.min_sector_lifetime)
+        .map(|history_size| history_size.segment_index())
+}
@@ -0,0 +1,312 @@
+//! Scheduled changes of consensus constants.
+//!
+//! Some consensus constants can be changed on a live network without a coordinated upgrade of all
+//! node binaries. Changes are stored with their activation block numbers in a dedicated system
+//! contract slot (see [`CONSENSUS_CONSTANTS_SLOT_KEY`]), constants for a block are then derived
+//! from the schedule in the state of its parent block.
+
+#[cfg(test)]
+mod tests;
+
+use crate::ConsensusConstants;
+use ab_client_api::ContractSlotState;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::segments::HistorySize;
+use ab_core_primitives::transaction::Gas;
+use ab_executor_slots::SlotKey;
+use std::num::NonZeroU64;
+
+/// System contract slot that stores scheduled changes of consensus constants.
+///
+/// Contents are encoded with [`ConsensusConstantsSchedule::encode()`], missing slot corresponds
+/// to an empty schedule.
+pub const CONSENSUS_CONSTANTS_SLOT_KEY: SlotKey = SlotKey {
+    owner: Address::SYSTEM_BLOCK,
+    contract: Address::SYSTEM_BLOCK,
+};
+
+/// Kind of consensus constant change, used in the encoding
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u8)]
+enum ConsensusConstantKind {
+    RecentSegments = 0,
+    RecentHistoryFraction = 1,
+    MinSectorLifetime = 2,
+    MaxBlockTransactionsGas = 3,
+    MaxBlockTransactionsSize = 4,
+}
+
+impl ConsensusConstantKind {
+    fn try_from_byte(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0 => Self::RecentSegments,
+            1 => Self::RecentHistoryFraction,
+            2 => Self::MinSectorLifetime,
+            3 => Self::MaxBlockTransactionsGas,
+            4 => Self::MaxBlockTransactionsSize,
+            _ => {
+                return None;
+            }
+        })
+    }
+}
+
+/// Change of a single consensus constant.
+///
+/// Only constants that are checked with the state of the parent block available can be changed,
+/// other constants (like those affecting the proof of time chain) are fixed for the lifetime of the
+/// network.
+// TODO: Support changing `block_authoring_delay`, which requires proof of time verification to
+//  handle the transition between old and new delay
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ConsensusConstantChange {
+    /// New value of [`ConsensusConstants::recent_segments`]
+    RecentSegments(HistorySize),
+    /// New value of [`ConsensusConstants::recent_history_fraction`]
+    RecentHistoryFraction((HistorySize, HistorySize)),
+    /// New value of [`ConsensusConstants::min_sector_lifetime`]
+    MinSectorLifetime(HistorySize),
+    /// New value of [`ConsensusConstants::max_block_transactions_gas`]
+    MaxBlockTransactionsGas(Gas),
+    /// New value of [`ConsensusConstants::max_block_transactions_size`]
+    MaxBlockTransactionsSize(u32),
+}
+
+impl ConsensusConstantChange {
+    fn apply(self, consensus_constants: &mut ConsensusConstants) {
+        match self {
+            Self::RecentSegments(recent_segments) => {
+                consensus_constants.recent_segments = recent_segments;
+            }
+            Self::RecentHistoryFraction(recent_history_fraction) => {
+                consensus_constants.recent_history_fraction = recent_history_fraction;
+            }
+            Self::MinSectorLifetime(min_sector_lifetime) => {
+                consensus_constants.min_sector_lifetime = min_sector_lifetime;
+            }
+            Self::MaxBlockTransactionsGas(max_block_transactions_gas) => {
+                consensus_constants.max_block_transactions_gas = max_block_transactions_gas;
+            }
+            Self::MaxBlockTransactionsSize(max_block_transactions_size) => {
+                consensus_constants.max_block_transactions_size = max_block_transactions_size;
+            }
+        }
+    }
+}
+
+/// Change of a consensus constant that takes effect starting with the specified block
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ScheduledConsensusConstantChange {
+    /// Number of the first block that uses the new value
+    pub activation_block_number: BlockNumber,
+    /// Change of a consensus constant
+    pub change: ConsensusConstantChange,
+}
+
+/// Errors for [`ConsensusConstantsSchedule`]
+#[derive(Debug, thiserror::Error)]
+pub enum ConsensusConstantsScheduleError {
+    /// Unexpected end of the encoded schedule
+    #[error("Unexpected end of the encoded schedule at offset {offset}")]
+    UnexpectedEnd {
+        /// Offset of the incomplete change
+        offset: usize,
+    },
+    /// Unknown consensus constant kind
+    #[error("Unknown consensus constant kind {kind} at offset {offset}")]
+    UnknownKind {
+        /// Consensus constant kind
+        kind: u8,
+        /// Offset of the change
+        offset: usize,
+    },
+    /// Invalid value of consensus constant
+    #[error("Invalid value of consensus constant at offset {offset}")]
+    InvalidValue {
+        /// Offset of the change
+        offset: usize,
+    },
+    /// Changes are not sorted by activation block number
+    #[error("Changes are not sorted by activation block number: {activation_block_number}")]
+    NotSorted {
+        /// Activation block number of the change that is out of order
+        activation_block_number: BlockNumber,
+    },
+}
+
+/// Schedule of consensus constants changes.
+///
+/// Changes are sorted by activation block number, changes with the same activation block number
+/// are applied in order.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ConsensusConstantsSchedule {
+    changes: Vec<ScheduledConsensusConstantChange>,
+}
+
+impl ConsensusConstantsSchedule {
+    /// Create a new instance from changes sorted by activation block number
+    pub fn new(
+        changes: Vec<ScheduledConsensusConstantChange>,
+    ) -> Result<Self, ConsensusConstantsScheduleError> {
+        if let Some(out_of_order) = changes.array_windows::<2>().find(|[previous, next]| {
+            previous.activation_block_number > next.activation_block_number
+        }) {
+            return Err(ConsensusConstantsScheduleError::NotSorted {
+                activation_block_number: out_of_order[1].activation_block_number,
+            });
+        }
+
+        Ok(Self { changes })
+    }
+
+    /// Read schedule from system contract states, an empty schedule is returned if the
+    /// corresponding slot doesn't exist
+    pub fn from_system_contract_states(
+        system_contract_states: &[ContractSlotState],
+    ) -> Result<Self, ConsensusConstantsScheduleError> {
+        system_contract_states
+            .iter()
+            .find(|state| {
+                state.owner == CONSENSUS_CONSTANTS_SLOT_KEY.owner
+                    && state.contract == CONSENSUS_CONSTANTS_SLOT_KEY.contract
+            })
+            .map_or_else(
+                || Ok(Self::default()),
+                |state| Self::decode(state.contents.as_slice()),
+            )
+    }
+
+    /// Decode schedule from bytes.
+    ///
+    /// Each change is encoded as activation block number (`u64` little-endian), consensus
+    /// constant kind (`u8`) and value of the constant (little-endian integers).
+    pub fn decode(mut bytes: &[u8]) -> Result<Self, ConsensusConstantsScheduleError> {
+        let total_len = bytes.len();
+        let mut changes = Vec::new();
+
+        while !bytes.is_empty() {
+            let offset = total_len - bytes.len();
+            let unexpected_end = || ConsensusConstantsScheduleError::UnexpectedEnd { offset };
+            let read_history_size = |bytes: &mut &[u8]| {
+                let value = take_chunk(bytes).ok_or_else(unexpected_end)?;
+                NonZeroU64::new(u64::from_le_bytes(value))
+                    .map(HistorySize::new)
+                    .ok_or(ConsensusConstantsScheduleError::InvalidValue { offset })
+            };
+
+            let activation_block_number = BlockNumber::from(u64::from_le_bytes(
+                take_chunk(&mut bytes).ok_or_else(unexpected_end)?,
+            ));
+            let [kind] = take_chunk(&mut bytes).ok_or_else(unexpected_end)?;
+
+            let change = match ConsensusConstantKind::try_from_byte(kind)
+                .ok_or(ConsensusConstantsScheduleError::UnknownKind { kind, offset })?
+            {
+                ConsensusConstantKind::RecentSegments => {
+                    ConsensusConstantChange::RecentSegments(read_history_size(&mut bytes)?)
+                }
+                ConsensusConstantKind::RecentHistoryFraction => {
+                    let numerator = read_history_size(&mut bytes)?;
+                    let denominator = read_history_size(&mut bytes)?;
+                    if numerator > denominator {
+                        return Err(ConsensusConstantsScheduleError::InvalidValue { offset });
+                    }
+                    ConsensusConstantChange::RecentHistoryFraction((numerator, denominator))
+                }
+                ConsensusConstantKind::MinSectorLifetime => {
+                    ConsensusConstantChange::MinSectorLifetime(read_history_size(&mut bytes)?)
+                }
+                ConsensusConstantKind::MaxBlockTransactionsGas => {
+                    ConsensusConstantChange::MaxBlockTransactionsGas(Gas::from(u64::from_le_bytes(
+                        take_chunk(&mut bytes).ok_or_else(unexpected_end)?,
+                    )))
+                }
+                ConsensusConstantKind::MaxBlockTransactionsSize => {
+                    ConsensusConstantChange::MaxBlockTransactionsSize(u32::from_le_bytes(
+                        take_chunk(&mut bytes).ok_or_else(unexpected_end)?,
+                    ))
+                }
+            };
+
+            changes.push(ScheduledConsensusConstantChange {
+                activation_block_number,
+                change,
+            });
+        }
+
+        Self::new(changes)
+    }
+
+    /// Encode schedule into bytes, see [`Self::decode()`] for details
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for scheduled_change in &self.changes {
+            bytes.extend_from_slice(
+                &u64::from(scheduled_change.activation_block_number).to_le_bytes(),
+            );
+
+            match scheduled_change.change {
+                ConsensusConstantChange::RecentSegments(recent_segments) => {
+                    bytes.push(ConsensusConstantKind::RecentSegments as u8);
+                    bytes.extend_from_slice(&recent_segments.as_non_zero_u64().get().to_le_bytes());
+                }
+                ConsensusConstantChange::RecentHistoryFraction((numerator, denominator)) => {
+                    bytes.push(ConsensusConstantKind::RecentHistoryFraction as u8);
+                    bytes.extend_from_slice(&numerator.as_non_zero_u64().get().to_le_bytes());
+                    bytes.extend_from_slice(&denominator.as_non_zero_u64().get().to_le_bytes());
+                }
+                ConsensusConstantChange::MinSectorLifetime(min_sector_lifetime) => {
+                    bytes.push(ConsensusConstantKind::MinSectorLifetime as u8);
+                    bytes.extend_from_slice(
+                        &min_sector_lifetime.as_non_zero_u64().get().to_le_bytes(),
+                    );
+                }
+                ConsensusConstantChange::MaxBlockTransactionsGas(max_block_transactions_gas) => {
+                    bytes.push(ConsensusConstantKind::MaxBlockTransactionsGas as u8);
+                    bytes.extend_from_slice(&u64::from(max_block_transactions_gas).to_le_bytes());
+                }
+                ConsensusConstantChange::MaxBlockTransactionsSize(max_block_transactions_size) => {
+                    bytes.push(ConsensusConstantKind::MaxBlockTransactionsSize as u8);
+                    bytes.extend_from_slice(&max_block_transactions_size.to_le_bytes());
+                }
+            }
+        }
+
+        bytes
+    }
+
+    /// Scheduled changes
+    pub fn changes(&self) -> &[ScheduledConsensusConstantChange] {
+        &self.changes
+    }
+
+    /// Consensus constants that apply to the block with specified number, derived from `base`
+    /// constants with all changes activated at or before `block_number` applied
+    pub fn consensus_constants_at(
+        &self,
+        base: &ConsensusConstants,
+        block_number: BlockNumber,
+    ) -> ConsensusConstants {
+        let mut consensus_constants = *base;
+
+        for scheduled_change in &self.changes {
+            if scheduled_change.activation_block_number > block_number {
+                break;
+            }
+
+            scheduled_change.change.apply(&mut consensus_constants);
+        }
+
+        consensus_constants
+    }
+}
+
+/// Take `N` bytes from the beginning of `bytes`, advancing it
+fn take_chunk<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+    let (chunk, remainder) = bytes.split_first_chunk::<N>()?;
+    *bytes = remainder;
+    Some(*chunk)
+}
@@ -0,0 +1,194 @@
+use crate::consensus_constants::{
+    CONSENSUS_CONSTANTS_SLOT_KEY, ConsensusConstantChange, ConsensusConstantsSchedule,
+    ConsensusConstantsScheduleError, ScheduledConsensusConstantChange,
+};
+use crate::{ConsensusConstants, PotConsensusConstants};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::ContractSlotState;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::{BlockNumber, BlockTimestamp};
+use ab_core_primitives::pot::{SlotDuration, SlotNumber};
+use ab_core_primitives::segments::HistorySize;
+use ab_core_primitives::transaction::Gas;
+use std::num::NonZeroU64;
+
+fn history_size(value: u64) -> HistorySize {
+    HistorySize::new(NonZeroU64::new(value).unwrap())
+}
+
+fn base_consensus_constants() -> ConsensusConstants {
+    ConsensusConstants {
+        block_confirmation_depth: BlockNumber::from(100),
+        shard_confirmation_depth: BlockNumber::from(72),
+        block_authoring_delay: SlotNumber::from(4),
+        pot: PotConsensusConstants {
+            entropy_injection_interval: BlockNumber::from(50),
+            entropy_injection_lookback_depth: 2,
+            entropy_injection_delay: SlotNumber::from(15),
+        },
+        retarget_interval: BlockNumber::from(180),
+        slot_probability: (1, 10),
+        slot_duration: SlotDuration::from_millis(1000),
+        recent_segments: history_size(5),
+        recent_history_fraction: (history_size(1), history_size(10)),
+        min_sector_lifetime: history_size(4),
+        max_block_timestamp_drift: BlockTimestamp::from_millis(30_000),
+        shard_rotation_interval: BlockNumber::from(36),
+        shard_rotation_delay: BlockNumber::from(18),
+        max_block_transactions_gas: Gas::from(500_000_000),
+        max_block_transactions_size: 4 * 1024 * 1024,
+    }
+}
+
+fn schedule() -> ConsensusConstantsSchedule {
+    ConsensusConstantsSchedule::new(vec![
+        ScheduledConsensusConstantChange {
+            activation_block_number: BlockNumber::from(10),
+            change: ConsensusConstantChange::RecentSegments(history_size(6)),
+        },
+        ScheduledConsensusConstantChange {
+            activation_block_number: BlockNumber::from(10),
+            change: ConsensusConstantChange::MaxBlockTransactionsSize(1024),
+        },
+        ScheduledConsensusConstantChange {
+            activation_block_number: BlockNumber::from(20),
+            change: ConsensusConstantChange::RecentHistoryFraction((
+                history_size(1),
+                history_size(5),
+            )),
+        },
+        ScheduledConsensusConstantChange {
+            activation_block_number: BlockNumber::from(20),
+            change: ConsensusConstantChange::MinSectorLifetime(history_size(8)),
+        },
+        ScheduledConsensusConstantChange {
+            activation_block_number: BlockNumber::from(30),
+            change: ConsensusConstantChange::MaxBlockTransactionsGas(Gas::from(1_000)),
+        },
+        ScheduledConsensusConstantChange {
+            activation_block_number: BlockNumber::from(30),
+            change: ConsensusConstantChange::RecentSegments(history_size(7)),
+        },
+    ])
+    .unwrap()
+}
+
+#[test]
+fn encoding() {
+    let schedule = schedule();
+
+    let encoded = schedule.encode();
+    assert_eq!(
+        ConsensusConstantsSchedule::decode(&encoded).unwrap(),
+        schedule
+    );
+
+    assert_eq!(
+        ConsensusConstantsSchedule::decode(&[]).unwrap(),
+        ConsensusConstantsSchedule::default()
+    );
+
+    // Truncated change
+    assert!(matches!(
+        ConsensusConstantsSchedule::decode(&encoded[..encoded.len() - 1]),
+        Err(ConsensusConstantsScheduleError::UnexpectedEnd { .. })
+    ));
+
+    // Unknown kind
+    let mut bytes = 1_u64.to_le_bytes().to_vec();
+    bytes.push(u8::MAX);
+    bytes.extend_from_slice(&1_u64.to_le_bytes());
+    assert!(matches!(
+        ConsensusConstantsSchedule::decode(&bytes),
+        Err(ConsensusConstantsScheduleError::UnknownKind {
+            kind: u8::MAX,
+            offset: 0
+        })
+    ));
+
+    // Zero history size
+    let mut bytes = 1_u64.to_le_bytes().to_vec();
+    bytes.push(0);
+    bytes.extend_from_slice(&0_u64.to_le_bytes());
+    assert!(matches!(
+        ConsensusConstantsSchedule::decode(&bytes),
+        Err(ConsensusConstantsScheduleError::InvalidValue { offset: 0 })
+    ));
+
+    // Not sorted
+    let mut changes = schedule.changes().to_vec();
+    changes.reverse();
+    assert!(matches!(
+        ConsensusConstantsSchedule::new(changes),
+        Err(ConsensusConstantsScheduleError::NotSorted { .. })
+    ));
+}
+
+#[test]
+fn consensus_constants_at() {
+    let base = base_consensus_constants();
+    let schedule = schedule();
+
+    assert_eq!(
+        schedule.consensus_constants_at(&base, BlockNumber::from(9)),
+        base
+    );
+
+    let consensus_constants = schedule.consensus_constants_at(&base, BlockNumber::from(10));
+    assert_eq!(consensus_constants.recent_segments, history_size(6));
+    assert_eq!(consensus_constants.max_block_transactions_size, 1024);
+    assert_eq!(
+        consensus_constants.recent_history_fraction,
+        base.recent_history_fraction
+    );
+
+    let consensus_constants = schedule.consensus_constants_at(&base, BlockNumber::from(25));
+    assert_eq!(consensus_constants.recent_segments, history_size(6));
+    assert_eq!(
+        consensus_constants.recent_history_fraction,
+        (history_size(1), history_size(5))
+    );
+    assert_eq!(consensus_constants.min_sector_lifetime, history_size(8));
+    assert_eq!(
+        consensus_constants.max_block_transactions_gas,
+        base.max_block_transactions_gas
+    );
+
+    // Later changes override earlier changes of the same constant
+    let consensus_constants = schedule.consensus_constants_at(&base, BlockNumber::from(100));
+    assert_eq!(consensus_constants.recent_segments, history_size(7));
+    assert_eq!(
+        consensus_constants.max_block_transactions_gas,
+        Gas::from(1_000)
+    );
+    // Other constants are not affected
+    assert_eq!(
+        consensus_constants.block_authoring_delay,
+        base.block_authoring_delay
+    );
+}
+
+#[test]
+fn from_system_contract_states() {
+    let schedule = schedule();
+
+    assert_eq!(
+        ConsensusConstantsSchedule::from_system_contract_states(&[ContractSlotState {
+            owner: Address::SYSTEM_STATE,
+            contract: Address::SYSTEM_STATE,
+            contents: SharedAlignedBuffer::from_bytes(&[1, 2, 3]),
+        }])
+        .unwrap(),
+        ConsensusConstantsSchedule::default()
+    );
+
+    assert_eq!(
+        ConsensusConstantsSchedule::from_system_contract_states(&[ContractSlotState {
+            owner: CONSENSUS_CONSTANTS_SLOT_KEY.owner,
+            contract: CONSENSUS_CONSTANTS_SLOT_KEY.contract,
+            contents: SharedAlignedBuffer::from_bytes(&schedule.encode()),
+        }])
+        .unwrap(),
+        schedule
+    );
+}
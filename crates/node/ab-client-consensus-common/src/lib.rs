@@ -4,12 +4,18 @@
 //  https://github.com/rust-lang/rust/issues/141492
 #![feature(generic_const_exprs, get_mut_unchecked)]
 
+pub mod consensus_constants;
 pub mod consensus_parameters;
+pub mod recent_transactions;
+pub mod slot_subscriptions;
 pub mod state;
+pub mod state_cache;
+pub mod state_export;
 
 use ab_core_primitives::block::{BlockNumber, BlockTimestamp};
 use ab_core_primitives::pot::{SlotDuration, SlotNumber};
 use ab_core_primitives::segments::HistorySize;
+use ab_core_primitives::transaction::Gas;
 use futures::channel::mpsc;
 
 /// Proof-of-time consensus constants
@@ -23,7 +29,9 @@ pub struct PotConsensusConstants {
     pub entropy_injection_delay: SlotNumber,
 }
 
-/// Consensus constants
+/// Consensus constants.
+///
+/// Some of the constants can be changed at specific block numbers, see [`consensus_constants`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct ConsensusConstants {
     /// Depth after which a block enters the recorded history.
@@ -52,9 +60,15 @@ pub struct ConsensusConstants {
     pub slot_duration: SlotDuration,
     /// Number of latest archived segments that are considered "recent history"
     pub recent_segments: HistorySize,
-    /// Fraction of pieces from the "recent history" (`recent_segments`) in each sector
+    /// Fraction of pieces from the "recent history" (`recent_segments`) in each sector.
+    ///
+    /// Expressed as `(numerator, denominator)`, only applies once history is large enough for
+    /// `recent_segments` to be at most this fraction of the whole history.
     pub recent_history_fraction: (HistorySize, HistorySize),
-    /// Minimum lifetime of a plotted sector, measured in archived segments
+    /// Minimum lifetime of a plotted sector, measured in archived segments.
+    ///
+    /// Sector expiration is determined once history grows by this many segments since sector
+    /// creation, see `SectorExpiration` for details.
     pub min_sector_lifetime: HistorySize,
     /// Max block timestamp drift allowed
     pub max_block_timestamp_drift: BlockTimestamp,
@@ -69,6 +83,10 @@ pub struct ConsensusConstants {
     /// Delay after shard assignment is revealed before it actually takes effect (essentially the
     /// amount of time for a node to sync the corresponding shard).
     pub shard_rotation_delay: BlockNumber,
+    /// Max total gas limit of all transactions in a block
+    pub max_block_transactions_gas: Gas,
+    /// Max total size of all transactions in a block in bytes
+    pub max_block_transactions_size: u32,
 }
 
 /// Notification with information about the block that is about to be imported and acknowledgement
@@ -0,0 +1,231 @@
+//! Deduplication of transactions against recent history.
+//!
+//! [`RecentTransactions`] maintains a rolling set of hashes of transactions included in the last
+//! `depth` confirmed blocks. Block builder skips transactions that are already in the set and block
+//! verification rejects blocks that include them (or include the same transaction twice). This
+//! complements nonce-based replay protection, which doesn't apply to fee-less system transactions.
+//!
+//! The set is persisted as auxiliary data of the last added block (see
+//! [`RECENT_TRANSACTIONS_AUX_DATA_NAMESPACE`]), such that it doesn't need to be reconstructed from
+//! block bodies after restart.
+
+#[cfg(test)]
+mod tests;
+
+use ab_client_api::BlockAuxDataNamespace;
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::transaction::TransactionHash;
+use std::collections::{HashSet, VecDeque};
+use std::num::NonZeroU64;
+
+/// Namespace of block auxiliary data, in which [`RecentTransactions`] are persisted
+pub const RECENT_TRANSACTIONS_AUX_DATA_NAMESPACE: BlockAuxDataNamespace =
+    BlockAuxDataNamespace::new(*b"recenttx");
+
+/// Transaction is a duplicate
+#[derive(Debug, thiserror::Error)]
+#[error("Transaction {tx_hash} is a duplicate")]
+pub struct DuplicateTransactionError {
+    /// Hash of the duplicate transaction
+    pub tx_hash: TransactionHash,
+}
+
+/// Errors for [`RecentTransactions::from_bytes()`]
+#[derive(Debug, thiserror::Error)]
+pub enum RecentTransactionsDecodingError {
+    /// Unexpected end of encoded recent transactions
+    #[error("Unexpected end of encoded recent transactions at offset {offset}")]
+    UnexpectedEnd {
+        /// Offset of the incomplete block
+        offset: usize,
+    },
+    /// Blocks are not sorted by block number
+    #[error("Blocks are not sorted by block number: {block_number}")]
+    NotSorted {
+        /// Block number that is out of order
+        block_number: BlockNumber,
+    },
+    /// Duplicate transaction
+    #[error("Duplicate transaction {tx_hash} in block {block_number}")]
+    DuplicateTransaction {
+        /// Block number
+        block_number: BlockNumber,
+        /// Hash of the duplicate transaction
+        tx_hash: TransactionHash,
+    },
+}
+
+/// Rolling set of transactions included in the last `depth` blocks
+#[derive(Debug, Clone)]
+pub struct RecentTransactions {
+    depth: NonZeroU64,
+    /// Transactions of each block, ordered by block number
+    blocks: VecDeque<(BlockNumber, Vec<TransactionHash>)>,
+    tx_hashes: HashSet<TransactionHash>,
+}
+
+impl RecentTransactions {
+    /// Create a new empty instance that tracks transactions of the last `depth` blocks
+    pub fn new(depth: NonZeroU64) -> Self {
+        Self {
+            depth,
+            blocks: VecDeque::new(),
+            tx_hashes: HashSet::new(),
+        }
+    }
+
+    /// Number of the last added block, `None` if no blocks were added yet
+    pub fn last_block_number(&self) -> Option<BlockNumber> {
+        self.blocks
+            .back()
+            .map(|(block_number, _tx_hashes)| *block_number)
+    }
+
+    /// Number of tracked transactions
+    pub fn len(&self) -> usize {
+        self.tx_hashes.len()
+    }
+
+    /// Whether there are no tracked transactions
+    pub fn is_empty(&self) -> bool {
+        self.tx_hashes.is_empty()
+    }
+
+    /// Whether transaction was included in one of the recent blocks
+    pub fn contains(&self, tx_hash: &TransactionHash) -> bool {
+        self.tx_hashes.contains(tx_hash)
+    }
+
+    /// Check transactions of a block that is about to be built on top of or imported after the last
+    /// added block.
+    ///
+    /// Returns an error if any of the transactions was included in one of the recent blocks or if
+    /// the same transaction is present more than once.
+    pub fn check_transactions<'a, I>(&self, tx_hashes: I) -> Result<(), DuplicateTransactionError>
+    where
+        I: IntoIterator<Item = &'a TransactionHash>,
+    {
+        let mut block_tx_hashes = HashSet::new();
+
+        for tx_hash in tx_hashes {
+            if self.tx_hashes.contains(tx_hash) || !block_tx_hashes.insert(tx_hash) {
+                return Err(DuplicateTransactionError { tx_hash: *tx_hash });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add transactions of a confirmed block.
+    ///
+    /// Blocks must be added in order, block numbers that are not larger than the last added block
+    /// number are ignored. Transactions of blocks that are `depth` or more blocks older than the
+    /// added block are evicted.
+    pub fn add_block<I>(&mut self, block_number: BlockNumber, tx_hashes: I)
+    where
+        I: IntoIterator<Item = TransactionHash>,
+    {
+        if let Some(last_block_number) = self.last_block_number()
+            && block_number <= last_block_number
+        {
+            return;
+        }
+
+        let tx_hashes = tx_hashes.into_iter().collect::<Vec<_>>();
+        self.tx_hashes.extend(tx_hashes.iter().copied());
+        self.blocks.push_back((block_number, tx_hashes));
+
+        let oldest_block_number =
+            block_number.saturating_sub(BlockNumber::from(self.depth.get() - 1));
+        while let Some((front_block_number, _tx_hashes)) = self.blocks.front()
+            && *front_block_number < oldest_block_number
+        {
+            let (_block_number, tx_hashes) = self
+                .blocks
+                .pop_front()
+                .expect("Just checked that front element exists; qed");
+            for tx_hash in &tx_hashes {
+                self.tx_hashes.remove(tx_hash);
+            }
+        }
+    }
+
+    /// Encode recent transactions for persistence.
+    ///
+    /// Each block is encoded as block number (`u64` little-endian), number of transactions (`u32`
+    /// little-endian) and transaction hashes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            self.blocks.len() * (BlockNumber::SIZE + size_of::<u32>())
+                + self.tx_hashes.len() * Blake3Hash::SIZE,
+        );
+
+        for (block_number, tx_hashes) in &self.blocks {
+            bytes.extend_from_slice(&block_number.to_bytes());
+            bytes.extend_from_slice(
+                &u32::try_from(tx_hashes.len())
+                    .expect("Number of transactions in a block fits into `u32`; qed")
+                    .to_le_bytes(),
+            );
+            for tx_hash in tx_hashes {
+                bytes.extend_from_slice(tx_hash.as_ref());
+            }
+        }
+
+        bytes
+    }
+
+    /// Decode recent transactions encoded with [`Self::to_bytes()`].
+    ///
+    /// Blocks that are too old for provided `depth` are evicted.
+    pub fn from_bytes(
+        depth: NonZeroU64,
+        mut bytes: &[u8],
+    ) -> Result<Self, RecentTransactionsDecodingError> {
+        let total_len = bytes.len();
+        let mut recent_transactions = Self::new(depth);
+
+        while !bytes.is_empty() {
+            let offset = total_len - bytes.len();
+            let unexpected_end = || RecentTransactionsDecodingError::UnexpectedEnd { offset };
+
+            let block_number =
+                BlockNumber::from_bytes(take_chunk(&mut bytes).ok_or_else(unexpected_end)?);
+            let num_transactions =
+                u32::from_le_bytes(take_chunk(&mut bytes).ok_or_else(unexpected_end)?);
+
+            if let Some(last_block_number) = recent_transactions.last_block_number()
+                && block_number <= last_block_number
+            {
+                return Err(RecentTransactionsDecodingError::NotSorted { block_number });
+            }
+
+            let mut tx_hashes =
+                Vec::with_capacity((num_transactions as usize).min(bytes.len() / Blake3Hash::SIZE));
+            for _ in 0..num_transactions {
+                tx_hashes.push(TransactionHash::from(Blake3Hash::new(
+                    take_chunk(&mut bytes).ok_or_else(unexpected_end)?,
+                )));
+            }
+
+            recent_transactions.check_transactions(&tx_hashes).map_err(
+                |DuplicateTransactionError { tx_hash }| {
+                    RecentTransactionsDecodingError::DuplicateTransaction {
+                        block_number,
+                        tx_hash,
+                    }
+                },
+            )?;
+            recent_transactions.add_block(block_number, tx_hashes);
+        }
+
+        Ok(recent_transactions)
+    }
+}
+
+fn take_chunk<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+    let (chunk, remainder) = bytes.split_first_chunk::<N>()?;
+    *bytes = remainder;
+    Some(*chunk)
+}
@@ -0,0 +1,108 @@
+use crate::recent_transactions::{RecentTransactions, RecentTransactionsDecodingError};
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::transaction::TransactionHash;
+use std::num::NonZeroU64;
+
+fn tx_hash(id: u8) -> TransactionHash {
+    TransactionHash::from(Blake3Hash::new([id; Blake3Hash::SIZE]))
+}
+
+#[test]
+fn rolling_window() {
+    let mut recent_transactions = RecentTransactions::new(NonZeroU64::new(2).unwrap());
+    assert!(recent_transactions.is_empty());
+
+    recent_transactions.add_block(BlockNumber::from(1), [tx_hash(1), tx_hash(2)]);
+    recent_transactions.add_block(BlockNumber::from(2), [tx_hash(3)]);
+    assert_eq!(recent_transactions.len(), 3);
+    assert!(recent_transactions.contains(&tx_hash(1)));
+
+    // Blocks that are not newer than the last added block are ignored
+    recent_transactions.add_block(BlockNumber::from(2), [tx_hash(4)]);
+    assert!(!recent_transactions.contains(&tx_hash(4)));
+
+    // The first block falls out of the window
+    recent_transactions.add_block(BlockNumber::from(3), [tx_hash(4)]);
+    assert_eq!(
+        recent_transactions.last_block_number(),
+        Some(BlockNumber::from(3))
+    );
+    assert_eq!(recent_transactions.len(), 2);
+    assert!(!recent_transactions.contains(&tx_hash(1)));
+    assert!(!recent_transactions.contains(&tx_hash(2)));
+    assert!(recent_transactions.contains(&tx_hash(3)));
+    assert!(recent_transactions.contains(&tx_hash(4)));
+
+    // Gaps in block numbers evict everything that is too old
+    recent_transactions.add_block(BlockNumber::from(10), []);
+    assert!(recent_transactions.is_empty());
+}
+
+#[test]
+fn check_transactions() {
+    let mut recent_transactions = RecentTransactions::new(NonZeroU64::new(10).unwrap());
+    recent_transactions.add_block(BlockNumber::from(1), [tx_hash(1)]);
+
+    recent_transactions
+        .check_transactions(&[tx_hash(2), tx_hash(3)])
+        .unwrap();
+    // Included in recent history
+    assert_eq!(
+        recent_transactions
+            .check_transactions(&[tx_hash(2), tx_hash(1)])
+            .unwrap_err()
+            .tx_hash,
+        tx_hash(1)
+    );
+    // Included twice in the same block
+    assert_eq!(
+        recent_transactions
+            .check_transactions(&[tx_hash(2), tx_hash(3), tx_hash(2)])
+            .unwrap_err()
+            .tx_hash,
+        tx_hash(2)
+    );
+}
+
+#[test]
+fn encoding() {
+    let depth = NonZeroU64::new(3).unwrap();
+    let mut recent_transactions = RecentTransactions::new(depth);
+    recent_transactions.add_block(BlockNumber::from(5), [tx_hash(1), tx_hash(2)]);
+    recent_transactions.add_block(BlockNumber::from(6), []);
+    recent_transactions.add_block(BlockNumber::from(7), [tx_hash(3)]);
+
+    let bytes = recent_transactions.to_bytes();
+    let decoded = RecentTransactions::from_bytes(depth, &bytes).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(decoded.last_block_number(), Some(BlockNumber::from(7)));
+    assert_eq!(decoded.len(), 3);
+
+    // Smaller depth evicts older blocks
+    let decoded = RecentTransactions::from_bytes(NonZeroU64::new(1).unwrap(), &bytes).unwrap();
+    assert_eq!(decoded.len(), 1);
+    assert!(decoded.contains(&tx_hash(3)));
+
+    assert!(matches!(
+        RecentTransactions::from_bytes(depth, &bytes[..bytes.len() - 1]),
+        Err(RecentTransactionsDecodingError::UnexpectedEnd { offset: 88 })
+    ));
+
+    let mut not_sorted = recent_transactions.to_bytes();
+    not_sorted.extend_from_slice(&BlockNumber::from(7).to_bytes());
+    not_sorted.extend_from_slice(&0u32.to_le_bytes());
+    assert!(matches!(
+        RecentTransactions::from_bytes(depth, &not_sorted),
+        Err(RecentTransactionsDecodingError::NotSorted { .. })
+    ));
+
+    let mut duplicate = recent_transactions.to_bytes();
+    duplicate.extend_from_slice(&BlockNumber::from(8).to_bytes());
+    duplicate.extend_from_slice(&1u32.to_le_bytes());
+    duplicate.extend_from_slice(tx_hash(3).as_ref());
+    assert!(matches!(
+        RecentTransactions::from_bytes(depth, &duplicate),
+        Err(RecentTransactionsDecodingError::DuplicateTransaction { .. })
+    ));
+}
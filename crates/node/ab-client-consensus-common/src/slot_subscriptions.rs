@@ -0,0 +1,139 @@
+//! Subscriptions to changes of system contract slots
+
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::ContractSlotState;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_executor_slots::SlotKey;
+use futures::channel::mpsc;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+
+/// Number of slot change notifications that can be buffered for each subscriber
+const SUBSCRIBER_BUFFER_SIZE: usize = 100;
+
+/// Change of a system contract slot in an imported block
+#[derive(Debug, Clone)]
+pub struct SystemContractSlotChange {
+    /// Number of the block that changed the slot
+    pub block_number: BlockNumber,
+    /// Root of the block that changed the slot
+    pub block_root: BlockRoot,
+    /// Slot that was changed
+    pub slot_key: SlotKey,
+    /// Slot contents before the block, `None` if the slot didn't exist
+    pub old_contents: Option<SharedAlignedBuffer>,
+    /// Slot contents after the block, `None` if the slot was removed
+    pub new_contents: Option<SharedAlignedBuffer>,
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    slot_keys: HashSet<SlotKey>,
+    sender: mpsc::Sender<SystemContractSlotChange>,
+}
+
+/// Subscriptions to changes of designated system contract slots.
+///
+/// Changes are derived from the difference between system contract states of the parent block and
+/// the imported block (see [`Self::on_block_import()`]), which allows subsystems to react to
+/// changes of parameters stored in system contracts without re-reading state on every block.
+#[derive(Debug, Default)]
+pub struct SystemContractSlotSubscriptions {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl SystemContractSlotSubscriptions {
+    /// Subscribe to changes of specified slots.
+    ///
+    /// Notifications are dropped for subscribers that do not keep up with block import.
+    pub fn subscribe<I>(&self, slot_keys: I) -> mpsc::Receiver<SystemContractSlotChange>
+    where
+        I: IntoIterator<Item = SlotKey>,
+    {
+        let (sender, receiver) = mpsc::channel(SUBSCRIBER_BUFFER_SIZE);
+
+        self.subscribers.lock().push(Subscriber {
+            slot_keys: slot_keys.into_iter().collect(),
+            sender,
+        });
+
+        receiver
+    }
+
+    /// Notify subscribers about slots that were changed by the imported block
+    pub fn on_block_import(
+        &self,
+        block_number: BlockNumber,
+        block_root: BlockRoot,
+        parent_system_contract_states: &[ContractSlotState],
+        system_contract_states: &[ContractSlotState],
+    ) {
+        let mut subscribers = self.subscribers.lock();
+
+        subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+
+        if subscribers.is_empty() {
+            return;
+        }
+
+        let mut old_states = parent_system_contract_states
+            .iter()
+            .map(|state| {
+                (
+                    SlotKey {
+                        owner: state.owner,
+                        contract: state.contract,
+                    },
+                    &state.contents,
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut changes = Vec::new();
+        for state in system_contract_states {
+            let slot_key = SlotKey {
+                owner: state.owner,
+                contract: state.contract,
+            };
+            let old_contents = old_states.remove(&slot_key);
+
+            if old_contents.map(SharedAlignedBuffer::as_slice) != Some(state.contents.as_slice()) {
+                changes.push(SystemContractSlotChange {
+                    block_number,
+                    block_root,
+                    slot_key,
+                    old_contents: old_contents.cloned(),
+                    new_contents: Some(state.contents.clone()),
+                });
+            }
+        }
+        // Remaining slots no longer exist
+        changes.extend(old_states.into_iter().map(|(slot_key, old_contents)| {
+            SystemContractSlotChange {
+                block_number,
+                block_root,
+                slot_key,
+                old_contents: Some(old_contents.clone()),
+                new_contents: None,
+            }
+        }));
+
+        for change in changes {
+            for subscriber in subscribers.iter_mut() {
+                if !subscriber.slot_keys.contains(&change.slot_key) {
+                    continue;
+                }
+
+                if let Err(error) = subscriber.sender.try_send(change.clone()) {
+                    warn!(
+                        %error,
+                        %block_number,
+                        %block_root,
+                        "Failed to send system contract slot change notification"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Cache of contract slots state
+
+#[cfg(test)]
+mod tests;
+
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::ContractSlotState;
+use ab_core_primitives::block::BlockRoot;
+use ab_executor_slots::SlotKey;
+use parking_lot::Mutex;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::registry::Registry;
+use schnellru::{ByLength, LruMap};
+use std::sync::atomic::AtomicU64;
+
+/// Statistics of [`StateCache`] usage
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct StateCacheStats {
+    /// Number of lookups that were served from the cache
+    pub hits: u64,
+    /// Number of lookups that were not served from the cache
+    pub misses: u64,
+}
+
+#[derive(Debug)]
+struct Inner {
+    /// Block whose state is cached
+    block_root: Option<BlockRoot>,
+    slots: LruMap<SlotKey, SharedAlignedBuffer>,
+}
+
+/// LRU cache of hot contract slots that sits between the executor and persistent state.
+///
+/// The cache corresponds to the state after a specific block, it is invalidated and warmed up with
+/// system contract states on every block import (see [`Self::on_block_import()`]).
+///
+/// Hits and misses are always counted, but only exposed as metrics once registered with
+/// [`Self::register_metrics()`].
+#[derive(Debug)]
+pub struct StateCache {
+    inner: Mutex<Inner>,
+    hits: Counter<u64, AtomicU64>,
+    misses: Counter<u64, AtomicU64>,
+}
+
+impl StateCache {
+    /// Create a new instance that caches up to `capacity` slots
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                block_root: None,
+                slots: LruMap::new(ByLength::new(capacity)),
+            }),
+            hits: Counter::default(),
+            misses: Counter::default(),
+        }
+    }
+
+    /// Register hit and miss counters in the registry
+    pub fn register_metrics(&self, registry: &mut Registry) {
+        let registry = registry.sub_registry_with_prefix("state_cache");
+
+        registry.register(
+            "hits_counter",
+            "Number of contract slot lookups that were served from the cache",
+            self.hits.clone(),
+        );
+        registry.register(
+            "misses_counter",
+            "Number of contract slot lookups that were not served from the cache",
+            self.misses.clone(),
+        );
+    }
+
+    /// Invalidate the cache and warm it up with system contract states of the imported block
+    pub fn on_block_import(
+        &self,
+        block_root: BlockRoot,
+        system_contract_states: &[ContractSlotState],
+    ) {
+        let mut inner = self.inner.lock();
+
+        inner.block_root.replace(block_root);
+        inner.slots.clear();
+
+        for system_contract_state in system_contract_states {
+            inner.slots.insert(
+                SlotKey {
+                    owner: system_contract_state.owner,
+                    contract: system_contract_state.contract,
+                },
+                system_contract_state.contents.clone(),
+            );
+        }
+    }
+
+    /// Get slot contents in the state after block `block_root`.
+    ///
+    /// Returns `None` if slot is not cached or cache corresponds to a different block.
+    pub fn get(&self, block_root: &BlockRoot, slot_key: &SlotKey) -> Option<SharedAlignedBuffer> {
+        let maybe_contents = {
+            let mut inner = self.inner.lock();
+
+            if inner.block_root.as_ref() == Some(block_root) {
+                inner.slots.get(slot_key).cloned()
+            } else {
+                None
+            }
+        };
+
+        if maybe_contents.is_some() {
+            self.hits.inc();
+        } else {
+            self.misses.inc();
+        }
+
+        maybe_contents
+    }
+
+    /// Get slot contents in the state after block `block_root`, reading them with `read` on cache
+    /// miss.
+    ///
+    /// Contents returned by `read` are inserted into the cache, see [`Self::insert()`].
+    pub fn get_or_read<F>(
+        &self,
+        block_root: &BlockRoot,
+        slot_key: SlotKey,
+        read: F,
+    ) -> Option<SharedAlignedBuffer>
+    where
+        F: FnOnce() -> Option<SharedAlignedBuffer>,
+    {
+        if let Some(contents) = self.get(block_root, &slot_key) {
+            return Some(contents);
+        }
+
+        let contents = read()?;
+        self.insert(block_root, slot_key, contents.clone());
+
+        Some(contents)
+    }
+
+    /// Insert slot contents that were read from persistent state after block `block_root`.
+    ///
+    /// Ignored if the cache corresponds to a different block.
+    pub fn insert(&self, block_root: &BlockRoot, slot_key: SlotKey, contents: SharedAlignedBuffer) {
+        let mut inner = self.inner.lock();
+
+        if inner.block_root.as_ref() == Some(block_root) {
+            inner.slots.insert(slot_key, contents);
+        }
+    }
+
+    /// Cache usage statistics
+    pub fn stats(&self) -> StateCacheStats {
+        StateCacheStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+        }
+    }
+}
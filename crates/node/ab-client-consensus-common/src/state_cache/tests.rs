@@ -0,0 +1,173 @@
+use crate::state_cache::{StateCache, StateCacheStats};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::ContractSlotState;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::BlockRoot;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_executor_slots::SlotKey;
+
+fn block_root(id: u8) -> BlockRoot {
+    BlockRoot::new(Blake3Hash::new([id; Blake3Hash::SIZE]))
+}
+
+fn slot_key(owner: u128) -> SlotKey {
+    SlotKey {
+        owner: Address::from(owner),
+        contract: Address::SYSTEM_METADATA,
+    }
+}
+
+fn slot_state(owner: u128, contents: &[u8]) -> ContractSlotState {
+    ContractSlotState {
+        owner: Address::from(owner),
+        contract: Address::SYSTEM_METADATA,
+        contents: SharedAlignedBuffer::from_bytes(contents),
+    }
+}
+
+fn get(state_cache: &StateCache, block_root: &BlockRoot, owner: u128) -> Option<Vec<u8>> {
+    state_cache
+        .get(block_root, &slot_key(owner))
+        .map(|contents| contents.as_slice().to_vec())
+}
+
+#[test]
+fn invalidation_on_import() {
+    let state_cache = StateCache::new(10);
+
+    // Nothing is cached before the first import
+    assert_eq!(get(&state_cache, &block_root(1), 1), None);
+
+    state_cache.on_block_import(block_root(1), &[slot_state(1, b"a"), slot_state(2, b"b")]);
+    assert_eq!(
+        get(&state_cache, &block_root(1), 1).as_deref(),
+        Some(&b"a"[..])
+    );
+    assert_eq!(
+        get(&state_cache, &block_root(1), 2).as_deref(),
+        Some(&b"b"[..])
+    );
+
+    state_cache.insert(
+        &block_root(1),
+        slot_key(3),
+        SharedAlignedBuffer::from_bytes(b"c"),
+    );
+    assert_eq!(
+        get(&state_cache, &block_root(1), 3).as_deref(),
+        Some(&b"c"[..])
+    );
+
+    // Import of the next block replaces everything, including inserted slots
+    state_cache.on_block_import(block_root(2), &[slot_state(1, b"d")]);
+    assert_eq!(
+        get(&state_cache, &block_root(2), 1).as_deref(),
+        Some(&b"d"[..])
+    );
+    assert_eq!(get(&state_cache, &block_root(2), 2), None);
+    assert_eq!(get(&state_cache, &block_root(2), 3), None);
+
+    assert_eq!(state_cache.stats(), StateCacheStats { hits: 4, misses: 3 });
+}
+
+#[test]
+fn block_root_mismatch() {
+    let state_cache = StateCache::new(10);
+    state_cache.on_block_import(block_root(1), &[slot_state(1, b"a")]);
+
+    // Slot is cached, but for a different block
+    assert_eq!(get(&state_cache, &block_root(2), 1), None);
+
+    // Inserting slots of a different block is ignored
+    state_cache.insert(
+        &block_root(2),
+        slot_key(2),
+        SharedAlignedBuffer::from_bytes(b"b"),
+    );
+    assert_eq!(get(&state_cache, &block_root(1), 2), None);
+    assert_eq!(get(&state_cache, &block_root(2), 2), None);
+
+    // Contents read on a miss for a different block are returned, but not cached
+    let contents = state_cache.get_or_read(&block_root(2), slot_key(1), || {
+        Some(SharedAlignedBuffer::from_bytes(b"c"))
+    });
+    assert_eq!(contents.unwrap().as_slice(), b"c");
+    assert_eq!(
+        get(&state_cache, &block_root(1), 1).as_deref(),
+        Some(&b"a"[..])
+    );
+
+    assert_eq!(state_cache.stats(), StateCacheStats { hits: 1, misses: 4 });
+}
+
+#[test]
+fn get_or_read() {
+    let state_cache = StateCache::new(10);
+    state_cache.on_block_import(block_root(1), &[]);
+
+    // Missing slot is not cached
+    assert!(
+        state_cache
+            .get_or_read(&block_root(1), slot_key(1), || None)
+            .is_none()
+    );
+
+    // Read on miss and served from the cache afterwards
+    let contents = state_cache.get_or_read(&block_root(1), slot_key(1), || {
+        Some(SharedAlignedBuffer::from_bytes(b"a"))
+    });
+    assert_eq!(contents.unwrap().as_slice(), b"a");
+    let contents = state_cache.get_or_read(&block_root(1), slot_key(1), || {
+        panic!("Must be served from the cache")
+    });
+    assert_eq!(contents.unwrap().as_slice(), b"a");
+
+    assert_eq!(state_cache.stats(), StateCacheStats { hits: 1, misses: 2 });
+}
+
+#[test]
+fn lru_eviction() {
+    let state_cache = StateCache::new(2);
+    state_cache.on_block_import(block_root(1), &[slot_state(1, b"a"), slot_state(2, b"b")]);
+
+    // Make the first slot the most recently used one
+    assert_eq!(
+        get(&state_cache, &block_root(1), 1).as_deref(),
+        Some(&b"a"[..])
+    );
+
+    // The least recently used slot is evicted
+    state_cache.insert(
+        &block_root(1),
+        slot_key(3),
+        SharedAlignedBuffer::from_bytes(b"c"),
+    );
+    assert_eq!(get(&state_cache, &block_root(1), 2), None);
+    assert_eq!(
+        get(&state_cache, &block_root(1), 1).as_deref(),
+        Some(&b"a"[..])
+    );
+    assert_eq!(
+        get(&state_cache, &block_root(1), 3).as_deref(),
+        Some(&b"c"[..])
+    );
+
+    // Warming up with more system contract states than capacity retains the last ones
+    state_cache.on_block_import(
+        block_root(2),
+        &[
+            slot_state(1, b"d"),
+            slot_state(2, b"e"),
+            slot_state(3, b"f"),
+        ],
+    );
+    assert_eq!(get(&state_cache, &block_root(2), 1), None);
+    assert_eq!(
+        get(&state_cache, &block_root(2), 2).as_deref(),
+        Some(&b"e"[..])
+    );
+    assert_eq!(
+        get(&state_cache, &block_root(2), 3).as_deref(),
+        Some(&b"f"[..])
+    );
+}
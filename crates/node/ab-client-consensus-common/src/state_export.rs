@@ -0,0 +1,378 @@
+//! Reproducible export of contract slots state at a confirmed block, see [`export_state()`] and
+//! [`import_state()`].
+//!
+//! The same state always results in byte-for-byte identical exports, which makes them suitable for
+//! third-party audits, while [`import_state()`] verifies the export against the state root it
+//! contains, allowing to bootstrap a test network from the exported state.
+//!
+//! The layout is as follows:
+//! * [`MAGIC`] bytes
+//! * [`VERSION`] byte
+//! * header:
+//!   * block number: `u64` as little-endian bytes
+//!   * block root: 32 bytes
+//!   * state root: 32 bytes
+//!   * number of slots: `u64` as little-endian bytes
+//! * a sequence of slots sorted by owner and then by contract, each consisting of:
+//!   * owner: `u128` as little-endian bytes
+//!   * contract: `u128` as little-endian bytes
+//!   * contents size: `u32` as little-endian bytes
+//!   * contents bytes
+//! * BLAKE3 checksum of everything above
+
+#[cfg(test)]
+mod tests;
+
+use crate::state::GlobalState;
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{ChainInfo, ContractSlotState};
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::header::owned::GenericOwnedBlockHeader;
+use ab_core_primitives::block::owned::GenericOwnedBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::hashes::Blake3Hash;
+use blake3::Hasher;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc as StdArc;
+use std::task::{Context, Poll};
+
+/// Magic bytes a state export starts with
+pub const MAGIC: [u8; 8] = *b"ab-state";
+/// Current state export version
+pub const VERSION: u8 = 1;
+/// Max number of slots to pre-allocate memory for, the number of slots in the header is not
+/// trusted before the checksum is verified
+const MAX_PREALLOCATED_SLOTS: usize = 1024;
+
+/// Error for [`export_state()`]
+#[derive(Debug, thiserror::Error)]
+pub enum StateExportError {
+    /// I/O error
+    #[error("I/O error: {error}")]
+    Io {
+        /// Low-level error
+        #[from]
+        error: io::Error,
+    },
+    /// Unknown block
+    #[error("Unknown block {block_root}")]
+    UnknownBlock {
+        /// Block root
+        block_root: BlockRoot,
+    },
+    /// Block is not confirmed
+    #[error("Block {block_root} at {block_number} is not confirmed")]
+    NotConfirmed {
+        /// Block root
+        block_root: BlockRoot,
+        /// Block number
+        block_number: BlockNumber,
+    },
+    /// State doesn't match the state root in the block header
+    #[error("State root mismatch: expected {expected}, actual {actual}")]
+    StateRootMismatch {
+        /// State root in the block header
+        expected: Blake3Hash,
+        /// State root of the state
+        actual: Blake3Hash,
+    },
+}
+
+/// Error for [`import_state()`]
+#[derive(Debug, thiserror::Error)]
+pub enum StateImportError {
+    /// I/O error
+    #[error("I/O error: {error}")]
+    Io {
+        /// Low-level error
+        #[from]
+        error: io::Error,
+    },
+    /// Not a state export
+    #[error("Not a state export")]
+    InvalidMagic,
+    /// Unsupported state export version
+    #[error("Unsupported state export version: {version}")]
+    UnsupportedVersion {
+        /// State export version
+        version: u8,
+    },
+    /// Slots are not sorted or contain duplicates
+    #[error("Slot #{index} is not sorted or is a duplicate")]
+    NotSorted {
+        /// Index of the slot in the state export
+        index: u64,
+    },
+    /// State export checksum mismatch
+    #[error("State export checksum mismatch: expected {expected}, actual {actual}")]
+    ChecksumMismatch {
+        /// Checksum of the state export contents
+        expected: Blake3Hash,
+        /// Checksum stored in the state export
+        actual: Blake3Hash,
+    },
+    /// State doesn't match the state root in the header of the state export
+    #[error("State root mismatch: expected {expected}, actual {actual}")]
+    StateRootMismatch {
+        /// State root in the header of the state export
+        expected: Blake3Hash,
+        /// State root of the state
+        actual: Blake3Hash,
+    },
+}
+
+/// State imported with [`import_state()`]
+#[derive(Debug, Clone)]
+pub struct ImportedState {
+    /// Number of the block the state was exported at
+    pub block_number: BlockNumber,
+    /// Root of the block the state was exported at
+    pub block_root: BlockRoot,
+    /// State root (commitment) of the state
+    pub state_root: Blake3Hash,
+    /// Contract slots sorted by owner and then by contract
+    pub system_contract_states: StdArc<[ContractSlotState]>,
+}
+
+/// Export the state of all contract slots after the block with `block_root` into `writer`, see
+/// [module documentation](self) for details.
+///
+/// The block must be on the canonical chain and at least `block_confirmation_depth` blocks deep.
+pub async fn export_state<Block, CI, W>(
+    chain_info: &CI,
+    block_confirmation_depth: BlockNumber,
+    block_root: &BlockRoot,
+    writer: &mut W,
+) -> Result<(), StateExportError>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfo<Block>,
+    W: AsyncWrite + Unpin,
+{
+    let (header, block_details) =
+        chain_info
+            .header_with_details(block_root)
+            .ok_or(StateExportError::UnknownBlock {
+                block_root: *block_root,
+            })?;
+    let header = header.header();
+    let block_number = header.prefix.number;
+
+    let best_number = chain_info.best_header().header().prefix.number;
+    if best_number.saturating_sub(block_number) < block_confirmation_depth
+        || chain_info.canonical_root(block_number).as_ref() != Some(block_root)
+    {
+        return Err(StateExportError::NotConfirmed {
+            block_root: *block_root,
+            block_number,
+        });
+    }
+
+    // Slots are sorted by owner and contract
+    let global_state = GlobalState::new(&block_details.system_contract_states);
+    let state_root = global_state.root();
+    if state_root != header.result.state_root {
+        return Err(StateExportError::StateRootMismatch {
+            expected: header.result.state_root,
+            actual: state_root,
+        });
+    }
+
+    write_state(
+        writer,
+        block_number,
+        block_root,
+        &state_root,
+        &global_state.to_system_contract_states(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Import the state exported with [`export_state()`] from `reader`.
+///
+/// The checksum and the state root are verified, but it is up to the caller to check that the
+/// block root and state root match expectations, for example, by comparing them with the
+/// corresponding block header.
+pub async fn import_state<R>(reader: &mut R) -> Result<ImportedState, StateImportError>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut reader = HashingReader {
+        reader,
+        hasher: Hasher::new(),
+    };
+
+    let mut magic = [0; MAGIC.len()];
+    reader.read_exact(&mut magic).await?;
+    if magic != MAGIC {
+        return Err(StateImportError::InvalidMagic);
+    }
+
+    let [version] = reader.read_array().await?;
+    if version != VERSION {
+        return Err(StateImportError::UnsupportedVersion { version });
+    }
+
+    let block_number = BlockNumber::from(u64::from_le_bytes(reader.read_array().await?));
+    let block_root = BlockRoot::new(Blake3Hash::new(reader.read_array().await?));
+    let state_root = Blake3Hash::new(reader.read_array().await?);
+    let num_slots = u64::from_le_bytes(reader.read_array().await?);
+
+    let mut system_contract_states =
+        Vec::with_capacity((num_slots as usize).min(MAX_PREALLOCATED_SLOTS));
+    let mut previous_slot = None;
+    for index in 0..num_slots {
+        let owner = Address::from(u128::from_le_bytes(reader.read_array().await?));
+        let contract = Address::from(u128::from_le_bytes(reader.read_array().await?));
+        if let Some(previous_slot) = previous_slot
+            && previous_slot >= (owner, contract)
+        {
+            return Err(StateImportError::NotSorted { index });
+        }
+        previous_slot.replace((owner, contract));
+
+        let contents_size = u32::from_le_bytes(reader.read_array().await?);
+        // Size is not trusted, hence reading incrementally instead of allocating upfront
+        let mut contents = Vec::new();
+        (&mut reader)
+            .take(u64::from(contents_size))
+            .read_to_end(&mut contents)
+            .await?;
+        if contents.len() != contents_size as usize {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        system_contract_states.push(ContractSlotState {
+            owner,
+            contract,
+            contents: SharedAlignedBuffer::from_bytes(&contents),
+        });
+    }
+
+    let expected = Blake3Hash::from(reader.hasher.finalize());
+    let mut actual = [0; Blake3Hash::SIZE];
+    reader.reader.read_exact(&mut actual).await?;
+    let actual = Blake3Hash::new(actual);
+    if expected != actual {
+        return Err(StateImportError::ChecksumMismatch { expected, actual });
+    }
+
+    let actual_state_root = GlobalState::new(&system_contract_states).root();
+    if actual_state_root != state_root {
+        return Err(StateImportError::StateRootMismatch {
+            expected: state_root,
+            actual: actual_state_root,
+        });
+    }
+
+    Ok(ImportedState {
+        block_number,
+        block_root,
+        state_root,
+        system_contract_states: system_contract_states.into(),
+    })
+}
+
+/// Write state export, slots must be sorted and deduplicated
+async fn write_state<W>(
+    writer: &mut W,
+    block_number: BlockNumber,
+    block_root: &BlockRoot,
+    state_root: &Blake3Hash,
+    system_contract_states: &[ContractSlotState],
+) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut writer = HashingWriter {
+        writer,
+        hasher: Hasher::new(),
+    };
+
+    writer.write_all(&MAGIC).await?;
+    writer.write_all(&[VERSION]).await?;
+    writer
+        .write_all(&u64::from(block_number).to_le_bytes())
+        .await?;
+    writer.write_all(block_root.as_ref()).await?;
+    writer.write_all(state_root.as_ref()).await?;
+    writer
+        .write_all(&(system_contract_states.len() as u64).to_le_bytes())
+        .await?;
+
+    for system_contract_state in system_contract_states {
+        writer
+            .write_all(&u128::from(system_contract_state.owner).to_le_bytes())
+            .await?;
+        writer
+            .write_all(&u128::from(system_contract_state.contract).to_le_bytes())
+            .await?;
+        writer
+            .write_all(&system_contract_state.contents.len().to_le_bytes())
+            .await?;
+        writer
+            .write_all(system_contract_state.contents.as_slice())
+            .await?;
+    }
+
+    let checksum = writer.hasher.finalize();
+    writer.writer.write_all(checksum.as_bytes()).await?;
+    writer.writer.flush().await
+}
+
+/// Writer that hashes everything written through it
+struct HashingWriter<'a, W> {
+    writer: &'a mut W,
+    hasher: Hasher,
+}
+
+impl<W> HashingWriter<'_, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    async fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.hasher.update(bytes);
+        self.writer.write_all(bytes).await
+    }
+}
+
+/// Reader that hashes everything read through it
+struct HashingReader<'a, R> {
+    reader: &'a mut R,
+    hasher: Hasher,
+}
+
+impl<R> HashingReader<'_, R>
+where
+    R: AsyncRead + Unpin,
+{
+    async fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0; N];
+        self.read_exact(&mut bytes).await?;
+
+        Ok(bytes)
+    }
+}
+
+impl<R> AsyncRead for HashingReader<'_, R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        let result = Pin::new(&mut *this.reader).poll_read(cx, buf);
+        if let Poll::Ready(Ok(read)) = &result {
+            this.hasher.update(&buf[..*read]);
+        }
+
+        result
+    }
+}
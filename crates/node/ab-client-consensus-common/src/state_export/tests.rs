@@ -0,0 +1,138 @@
+use crate::state::GlobalState;
+use crate::state_export::{StateImportError, import_state, write_state};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::ContractSlotState;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::hashes::Blake3Hash;
+use futures::executor::block_on;
+
+fn slot(owner: u128, contract: u128, contents: &[u8]) -> ContractSlotState {
+    ContractSlotState {
+        owner: Address::from(owner),
+        contract: Address::from(contract),
+        contents: SharedAlignedBuffer::from_bytes(contents),
+    }
+}
+
+fn export(state_root: &Blake3Hash, system_contract_states: &[ContractSlotState]) -> Vec<u8> {
+    let mut export = Vec::new();
+    block_on(write_state(
+        &mut export,
+        BlockNumber::from(42),
+        &BlockRoot::new(Blake3Hash::new([1; Blake3Hash::SIZE])),
+        state_root,
+        system_contract_states,
+    ))
+    .unwrap();
+
+    export
+}
+
+#[test]
+fn round_trip() {
+    let global_state = GlobalState::new(&[
+        slot(5, 1, &[3; 100]),
+        slot(1, 2, &[]),
+        slot(1, 1, &[1, 2, 3]),
+    ]);
+    let state_root = global_state.root();
+    let system_contract_states = global_state.to_system_contract_states();
+
+    let export = export(&state_root, &system_contract_states);
+    // Export is reproducible
+    assert_eq!(export, self::export(&state_root, &system_contract_states));
+
+    let imported_state = block_on(import_state(&mut export.as_slice())).unwrap();
+    assert_eq!(imported_state.block_number, BlockNumber::from(42));
+    assert_eq!(
+        imported_state.block_root,
+        BlockRoot::new(Blake3Hash::new([1; Blake3Hash::SIZE]))
+    );
+    assert_eq!(imported_state.state_root, state_root);
+    assert_eq!(
+        imported_state.system_contract_states.len(),
+        system_contract_states.len()
+    );
+    for (imported, original) in imported_state
+        .system_contract_states
+        .iter()
+        .zip(system_contract_states.iter())
+    {
+        assert_eq!(imported.owner, original.owner);
+        assert_eq!(imported.contract, original.contract);
+        assert_eq!(imported.contents.as_slice(), original.contents.as_slice());
+    }
+}
+
+#[test]
+fn invalid_exports() {
+    let system_contract_states = [slot(1, 1, &[1, 2, 3]), slot(1, 2, &[4])];
+    let state_root = GlobalState::new(&system_contract_states).root();
+    let export = export(&state_root, &system_contract_states);
+
+    {
+        let mut export = export.clone();
+        export[0] ^= 1;
+        assert!(matches!(
+            block_on(import_state(&mut export.as_slice())),
+            Err(StateImportError::InvalidMagic)
+        ));
+    }
+
+    {
+        // Corrupted contents of the last slot
+        let mut export = export.clone();
+        let last_contents_byte = export.len() - Blake3Hash::SIZE - 1;
+        export[last_contents_byte] ^= 1;
+        assert!(matches!(
+            block_on(import_state(&mut export.as_slice())),
+            Err(StateImportError::ChecksumMismatch { .. })
+        ));
+    }
+
+    {
+        // Truncated export
+        let export = &export[..export.len() - 1];
+        assert!(matches!(
+            block_on(import_state(&mut &*export)),
+            Err(StateImportError::Io { .. })
+        ));
+    }
+
+    {
+        let export = self::export(
+            &state_root,
+            &[
+                system_contract_states[1].clone(),
+                system_contract_states[0].clone(),
+            ],
+        );
+        assert!(matches!(
+            block_on(import_state(&mut export.as_slice())),
+            Err(StateImportError::NotSorted { index: 1 })
+        ));
+    }
+
+    {
+        let export = self::export(
+            &state_root,
+            &[
+                system_contract_states[0].clone(),
+                system_contract_states[0].clone(),
+            ],
+        );
+        assert!(matches!(
+            block_on(import_state(&mut export.as_slice())),
+            Err(StateImportError::NotSorted { index: 1 })
+        ));
+    }
+
+    {
+        let export = self::export(&state_root, &system_contract_states[..1]);
+        assert!(matches!(
+            block_on(import_state(&mut export.as_slice())),
+            Err(StateImportError::StateRootMismatch { .. })
+        ));
+    }
+}
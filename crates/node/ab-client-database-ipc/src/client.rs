@@ -0,0 +1,606 @@
+#[cfg(test)]
+mod tests;
+
+use crate::protocol::{
+    Handshake, Request, Response, WireBlockDetails, WireBlockOutcome, WireContractSlotState,
+    decode_message, decode_message_size, encode_message,
+};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockAuxDataNamespace, BlockDetails, BlockMmrProof, BlockOutcome, ChainInfo, ChainInfoWrite,
+    ContractSlotState, PersistBlockAuxDataError, PersistBlockError, PersistBlockOutcomeError,
+    PersistContractSlotsError, PersistSegmentHeadersError, ReadBlockError, ReadContractSlotError,
+    ReadMmrProofError, TransactionLocation,
+};
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::body::owned::GenericOwnedBlockBody;
+use ab_core_primitives::block::header::owned::GenericOwnedBlockHeader;
+use ab_core_primitives::block::owned::GenericOwnedBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::segments::{LocalSegmentIndex, SegmentHeader};
+use ab_core_primitives::transaction::TransactionHash;
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::{Arc as StdArc, mpsc};
+use std::time::Duration;
+use std::{fmt, io, thread};
+use tracing::{debug, warn};
+
+/// Delay before retrying a failed request of an infallible method
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+struct PendingRequest {
+    /// Encoded request message
+    message: Vec<u8>,
+    response_sender: oneshot::Sender<io::Result<Response>>,
+}
+
+/// [`ChainInfo`] and [`ChainInfoWrite`] implementation that forwards calls to a server started
+/// with [`run_server()`](crate::run_server()) over a UNIX socket.
+///
+/// Requests are sent one at a time by a dedicated background thread, which exits once all clones of
+/// the client are dropped. After the loss of connection, the background thread reconnects to the
+/// server on the next request.
+///
+/// NOTE:
+/// <div class="warning">
+/// Most of the [`ChainInfo`] methods are infallible, so when the server is not reachable or
+/// responds with an invalid response, they log a warning and retry until the request succeeds,
+/// blocking the caller in the meantime. Fallible methods return an error instead.
+/// </div>
+pub struct ChainInfoClient<Block> {
+    request_sender: mpsc::Sender<PendingRequest>,
+    _block: PhantomData<fn() -> Block>,
+}
+
+impl<Block> fmt::Debug for ChainInfoClient<Block> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainInfoClient").finish_non_exhaustive()
+    }
+}
+
+impl<Block> Clone for ChainInfoClient<Block> {
+    fn clone(&self) -> Self {
+        Self {
+            request_sender: self.request_sender.clone(),
+            _block: PhantomData,
+        }
+    }
+}
+
+impl<Block> ChainInfo<Block> for ChainInfoClient<Block>
+where
+    Block: GenericOwnedBlock,
+{
+    fn best_root(&self) -> BlockRoot {
+        self.request_blocking(Request::BestRoot, |response| match response {
+            Response::BestRoot(block_root) => Ok(block_root),
+            _ => Err(unexpected_response()),
+        })
+    }
+
+    fn best_header(&self) -> Block::Header {
+        self.request_blocking(Request::BestHeader, |response| match response {
+            Response::Header(Some(header)) => decode_header::<Block>(header),
+            _ => Err(unexpected_response()),
+        })
+    }
+
+    fn best_header_with_details(&self) -> (Block::Header, BlockDetails) {
+        self.request_blocking(Request::BestHeaderWithDetails, |response| match response {
+            Response::HeaderWithDetails(Some((header, block_details))) => {
+                decode_header_with_details::<Block>(header, block_details)
+            }
+            _ => Err(unexpected_response()),
+        })
+    }
+
+    fn block_confirmation_depth(&self) -> BlockNumber {
+        self.request_blocking(Request::BlockConfirmationDepth, |response| match response {
+            Response::BlockConfirmationDepth(block_confirmation_depth) => {
+                Ok(block_confirmation_depth)
+            }
+            _ => Err(unexpected_response()),
+        })
+    }
+
+    fn ancestor_header(
+        &self,
+        ancestor_block_number: BlockNumber,
+        descendant_block_root: &BlockRoot,
+    ) -> Option<Block::Header> {
+        self.request_blocking(
+            Request::AncestorHeader {
+                ancestor_block_number,
+                descendant_block_root: *descendant_block_root,
+            },
+            |response| match response {
+                Response::Header(maybe_header) => {
+                    maybe_header.map(decode_header::<Block>).transpose()
+                }
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn canonical_header(&self, block_number: BlockNumber) -> Option<Block::Header> {
+        self.request_blocking(
+            Request::CanonicalHeader { block_number },
+            |response| match response {
+                Response::Header(maybe_header) => {
+                    maybe_header.map(decode_header::<Block>).transpose()
+                }
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn header(&self, block_root: &BlockRoot) -> Option<Block::Header> {
+        self.request_blocking(
+            Request::Header {
+                block_root: *block_root,
+            },
+            |response| match response {
+                Response::Header(maybe_header) => {
+                    maybe_header.map(decode_header::<Block>).transpose()
+                }
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn header_with_details(&self, block_root: &BlockRoot) -> Option<(Block::Header, BlockDetails)> {
+        self.request_blocking(
+            Request::HeaderWithDetails {
+                block_root: *block_root,
+            },
+            |response| match response {
+                Response::HeaderWithDetails(maybe_header_with_details) => maybe_header_with_details
+                    .map(|(header, block_details)| {
+                        decode_header_with_details::<Block>(header, block_details)
+                    })
+                    .transpose(),
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    async fn block(&self, block_root: &BlockRoot) -> Result<Block, ReadBlockError> {
+        match self
+            .request(Request::Block {
+                block_root: *block_root,
+            })
+            .await?
+        {
+            Response::Block(result) => {
+                let (header, body) = result?;
+
+                Block::from_buffers(
+                    SharedAlignedBuffer::from_bytes(&header),
+                    SharedAlignedBuffer::from_bytes(&body),
+                )
+                .ok_or(ReadBlockError::FailedToDecode)
+            }
+            _ => Err(unexpected_response().into()),
+        }
+    }
+
+    fn last_segment_header(&self) -> Option<SegmentHeader> {
+        self.request_blocking(Request::LastSegmentHeader, |response| match response {
+            Response::SegmentHeader(maybe_segment_header) => Ok(maybe_segment_header),
+            _ => Err(unexpected_response()),
+        })
+    }
+
+    fn get_segment_header(&self, segment_index: LocalSegmentIndex) -> Option<SegmentHeader> {
+        self.request_blocking(
+            Request::GetSegmentHeader { segment_index },
+            |response| match response {
+                Response::SegmentHeader(maybe_segment_header) => Ok(maybe_segment_header),
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn segment_headers_for_block(&self, block_number: BlockNumber) -> Vec<SegmentHeader> {
+        self.request_blocking(
+            Request::SegmentHeadersForBlock { block_number },
+            |response| match response {
+                Response::SegmentHeaders(segment_headers) => Ok(segment_headers),
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn block_aux_data(
+        &self,
+        block_root: &BlockRoot,
+        namespace: BlockAuxDataNamespace,
+    ) -> Option<SharedAlignedBuffer> {
+        self.request_blocking(
+            Request::BlockAuxData {
+                block_root: *block_root,
+                namespace: *namespace.as_bytes(),
+            },
+            |response| match response {
+                Response::BlockAuxData(maybe_data) => {
+                    Ok(maybe_data.map(|data| SharedAlignedBuffer::from_bytes(&data)))
+                }
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn block_outcome(&self, block_root: &BlockRoot) -> Option<BlockOutcome> {
+        self.request_blocking(
+            Request::BlockOutcome {
+                block_root: *block_root,
+            },
+            |response| match response {
+                Response::BlockOutcome(maybe_outcome) => Ok(maybe_outcome.map(BlockOutcome::from)),
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn find_transaction(&self, tx_hash: &TransactionHash) -> Option<TransactionLocation> {
+        self.request_blocking(
+            Request::FindTransaction {
+                tx_hash: Blake3Hash::from(*tx_hash),
+            },
+            |response| match response {
+                Response::FindTransaction(maybe_location) => Ok(maybe_location
+                    .map(|(block_root, offset)| TransactionLocation { block_root, offset })),
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn contract_slot(
+        &self,
+        block_root: &BlockRoot,
+        owner: &Address,
+        contract: &Address,
+    ) -> Result<Option<SharedAlignedBuffer>, ReadContractSlotError> {
+        self.request_blocking(
+            Request::ContractSlot {
+                block_root: *block_root,
+                owner: u128::from(*owner),
+                contract: u128::from(*contract),
+            },
+            |response| match response {
+                Response::ContractSlot(result) => Ok(result
+                    .map(|maybe_contents| {
+                        maybe_contents.map(|contents| SharedAlignedBuffer::from_bytes(&contents))
+                    })
+                    .map_err(Into::into)),
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+
+    fn mmr_proof(&self, block_number: BlockNumber) -> Result<BlockMmrProof, ReadMmrProofError> {
+        self.request_blocking(
+            Request::MmrProof { block_number },
+            |response| match response {
+                Response::MmrProof(result) => Ok(result.map(Into::into).map_err(Into::into)),
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+}
+
+impl<Block> ChainInfoWrite<Block> for ChainInfoClient<Block>
+where
+    Block: GenericOwnedBlock,
+{
+    async fn persist_block(
+        &self,
+        block: Block,
+        block_details: BlockDetails,
+    ) -> Result<(), PersistBlockError> {
+        match self
+            .request(Request::PersistBlock {
+                header: block.header().buffer().as_slice().to_vec(),
+                body: block.body().buffer().as_slice().to_vec(),
+                block_details: WireBlockDetails::from(&block_details),
+            })
+            .await?
+        {
+            Response::PersistBlock(result) => result.map_err(Into::into),
+            Response::ReadOnly => Err(read_only().into()),
+            _ => Err(unexpected_response().into()),
+        }
+    }
+
+    async fn persist_blocks(
+        &self,
+        blocks: Vec<(Block, BlockDetails)>,
+    ) -> Result<(), PersistBlockError> {
+        let blocks = blocks
+            .iter()
+            .map(|(block, block_details)| {
+                (
+                    block.header().buffer().as_slice().to_vec(),
+                    block.body().buffer().as_slice().to_vec(),
+                    WireBlockDetails::from(block_details),
+                )
+            })
+            .collect();
+
+        match self.request(Request::PersistBlocks { blocks }).await? {
+            Response::PersistBlocks(result) => result.map_err(Into::into),
+            Response::ReadOnly => Err(read_only().into()),
+            _ => Err(unexpected_response().into()),
+        }
+    }
+
+    async fn persist_segment_headers(
+        &self,
+        segment_headers: Vec<SegmentHeader>,
+    ) -> Result<(), PersistSegmentHeadersError> {
+        match self
+            .request(Request::PersistSegmentHeaders { segment_headers })
+            .await?
+        {
+            Response::PersistSegmentHeaders(result) => result.map_err(Into::into),
+            Response::ReadOnly => Err(read_only().into()),
+            _ => Err(unexpected_response().into()),
+        }
+    }
+
+    async fn persist_block_aux_data(
+        &self,
+        block_root: &BlockRoot,
+        namespace: BlockAuxDataNamespace,
+        data: SharedAlignedBuffer,
+    ) -> Result<(), PersistBlockAuxDataError> {
+        match self
+            .request(Request::PersistBlockAuxData {
+                block_root: *block_root,
+                namespace: *namespace.as_bytes(),
+                data: data.as_slice().to_vec(),
+            })
+            .await?
+        {
+            Response::PersistBlockAuxData(result) => result.map_err(Into::into),
+            Response::ReadOnly => Err(read_only().into()),
+            _ => Err(unexpected_response().into()),
+        }
+    }
+
+    async fn persist_block_outcome(
+        &self,
+        block_root: &BlockRoot,
+        outcome: BlockOutcome,
+    ) -> Result<(), PersistBlockOutcomeError> {
+        match self
+            .request(Request::PersistBlockOutcome {
+                block_root: *block_root,
+                outcome: WireBlockOutcome::from(&outcome),
+            })
+            .await?
+        {
+            Response::PersistBlockOutcome(result) => result.map_err(Into::into),
+            Response::ReadOnly => Err(read_only().into()),
+            _ => Err(unexpected_response().into()),
+        }
+    }
+
+    async fn persist_contract_slots(
+        &self,
+        block_root: &BlockRoot,
+        slots: StdArc<[ContractSlotState]>,
+    ) -> Result<(), PersistContractSlotsError> {
+        match self
+            .request(Request::PersistContractSlots {
+                block_root: *block_root,
+                slots: slots.iter().map(WireContractSlotState::from).collect(),
+            })
+            .await?
+        {
+            Response::PersistContractSlots(result) => result.map_err(Into::into),
+            Response::ReadOnly => Err(read_only().into()),
+            _ => Err(unexpected_response().into()),
+        }
+    }
+
+    fn retain_blocks_for_archiving(&self, first_block_number: BlockNumber) {
+        self.request_blocking(
+            Request::RetainBlocksForArchiving { first_block_number },
+            |response| match response {
+                // Read-only clients can't affect pruning on the server
+                Response::RetainBlocksForArchiving | Response::ReadOnly => Ok(()),
+                _ => Err(unexpected_response()),
+            },
+        )
+    }
+}
+
+impl<Block> ChainInfoClient<Block>
+where
+    Block: GenericOwnedBlock,
+{
+    /// Connect to the server listening on the specified UNIX socket path.
+    ///
+    /// Returns an error if the server is not reachable, speaks a different protocol version or
+    /// serves blocks of a different shard kind.
+    pub fn connect<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let local_handshake = Handshake::new(Block::SHARD_KIND);
+        let stream = connect_stream(&path, local_handshake)?;
+
+        let (request_sender, request_receiver) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("chain-info-client".to_string())
+            .spawn(move || process_requests(&path, local_handshake, stream, request_receiver))?;
+
+        Ok(Self {
+            request_sender,
+            _block: PhantomData,
+        })
+    }
+
+    async fn request(&self, request: Request) -> io::Result<Response> {
+        self.send_message(encode_message(&request)?).await
+    }
+
+    async fn send_message(&self, message: Vec<u8>) -> io::Result<Response> {
+        let (response_sender, response_receiver) = oneshot::channel();
+
+        self.request_sender
+            .send(PendingRequest {
+                message,
+                response_sender,
+            })
+            .map_err(|_error| io::Error::from(io::ErrorKind::BrokenPipe))?;
+
+        response_receiver
+            .await
+            .map_err(|_canceled| io::Error::from(io::ErrorKind::BrokenPipe))?
+    }
+
+    /// Send a request of an infallible method, retrying until the response is successfully
+    /// processed with `process_response`
+    fn request_blocking<T, F>(&self, request: Request, process_response: F) -> T
+    where
+        F: Fn(Response) -> io::Result<T>,
+    {
+        loop {
+            let result = encode_message(&request)
+                .and_then(|message| block_on(self.send_message(message)))
+                .and_then(&process_response);
+
+            match result {
+                Ok(result) => {
+                    return result;
+                }
+                Err(error) => {
+                    warn!(
+                        %error,
+                        ?request,
+                        "Chain info server request failed, retrying in {RETRY_DELAY:?}"
+                    );
+                    thread::sleep(RETRY_DELAY);
+                }
+            }
+        }
+    }
+}
+
+fn connect_stream(path: &Path, local_handshake: Handshake) -> io::Result<UnixStream> {
+    let mut stream = UnixStream::connect(path)?;
+
+    stream.write_all(&local_handshake.to_bytes())?;
+    let mut remote_handshake = [0; Handshake::SIZE];
+    stream.read_exact(&mut remote_handshake)?;
+
+    let Some(remote_handshake) = Handshake::from_bytes(remote_handshake) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Remote side is not a chain info server",
+        ));
+    };
+    if remote_handshake != local_handshake {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Incompatible chain info server: protocol version {} (expected {}), shard kind \
+                {:?} (expected {:?})",
+                remote_handshake.version,
+                local_handshake.version,
+                remote_handshake.shard_kind,
+                local_handshake.shard_kind,
+            ),
+        ));
+    }
+
+    Ok(stream)
+}
+
+fn process_requests(
+    path: &Path,
+    local_handshake: Handshake,
+    stream: UnixStream,
+    request_receiver: mpsc::Receiver<PendingRequest>,
+) {
+    let mut maybe_stream = Some(stream);
+
+    for PendingRequest {
+        message,
+        response_sender,
+    } in request_receiver
+    {
+        let maybe_connected_stream = maybe_stream.take().map_or_else(
+            || {
+                debug!(path = %path.display(), "Reconnecting to chain info server");
+                connect_stream(path, local_handshake)
+            },
+            Ok,
+        );
+        let result = maybe_connected_stream.and_then(|mut stream| {
+            // Stream is in an unknown state after a failed write or read, so it is dropped on error
+            // and a new connection is established for the next request instead of reading
+            // unrelated responses
+            let response = send_request(&mut stream, &message)?;
+            maybe_stream.replace(stream);
+            Ok(response)
+        });
+
+        // Caller might have given up on the response already
+        let _: Result<(), _> = response_sender.send(result);
+    }
+}
+
+fn send_request(stream: &mut UnixStream, message: &[u8]) -> io::Result<Response> {
+    stream.write_all(message)?;
+
+    let mut length_prefix = [0; size_of::<u32>()];
+    stream.read_exact(&mut length_prefix)?;
+    let mut message = vec![0; decode_message_size(length_prefix)?];
+    stream.read_exact(&mut message)?;
+
+    decode_message(&message)
+}
+
+fn decode_header<Block>(header: Vec<u8>) -> io::Result<Block::Header>
+where
+    Block: GenericOwnedBlock,
+{
+    Block::Header::from_buffer(SharedAlignedBuffer::from_bytes(&header))
+        .map_err(|_buffer| io::Error::new(io::ErrorKind::InvalidData, "Invalid block header"))
+}
+
+fn decode_header_with_details<Block>(
+    header: Vec<u8>,
+    block_details: WireBlockDetails,
+) -> io::Result<(Block::Header, BlockDetails)>
+where
+    Block: GenericOwnedBlock,
+{
+    Ok((
+        decode_header::<Block>(header)?,
+        BlockDetails::try_from(block_details)?,
+    ))
+}
+
+fn unexpected_response() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Unexpected response type")
+}
+
+fn read_only() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "Chain info server only allows reads",
+    )
+}
@@ -0,0 +1,60 @@
+use crate::ChainInfoClient;
+use crate::protocol::{
+    Handshake, Request, Response, decode_message, decode_message_size, encode_message,
+};
+use ab_client_api::ChainInfo;
+use ab_core_primitives::block::BlockRoot;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::shard::RealShardKind;
+use futures::executor::block_on;
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::thread;
+use tempfile::TempDir;
+
+/// Answer a single [`Request::BestRoot`] request and close the connection
+fn serve_best_root(mut stream: UnixStream, best_root: BlockRoot) {
+    let mut handshake = [0; Handshake::SIZE];
+    stream.read_exact(&mut handshake).unwrap();
+    stream
+        .write_all(&Handshake::new(RealShardKind::BeaconChain).to_bytes())
+        .unwrap();
+
+    let mut length_prefix = [0; size_of::<u32>()];
+    stream.read_exact(&mut length_prefix).unwrap();
+    let mut message = vec![0; decode_message_size(length_prefix).unwrap()];
+    stream.read_exact(&mut message).unwrap();
+    assert!(matches!(
+        decode_message::<Request>(&message).unwrap(),
+        Request::BestRoot
+    ));
+
+    stream
+        .write_all(&encode_message(&Response::BestRoot(best_root)).unwrap())
+        .unwrap();
+}
+
+#[test]
+fn reconnect_after_connection_loss() {
+    let directory = TempDir::new().unwrap();
+    let path = directory.path().join("chain-info.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let best_root = BlockRoot::new(Blake3Hash::from([1; _]));
+
+    let server = thread::spawn(move || {
+        for _ in 0..2 {
+            let (stream, _address) = listener.accept().unwrap();
+            serve_best_root(stream, best_root);
+        }
+    });
+
+    let client = ChainInfoClient::<OwnedBeaconChainBlock>::connect(&path).unwrap();
+    assert_eq!(client.best_root(), best_root);
+    // Server closed the connection, the client reconnects and retries
+    assert_eq!(client.best_root(), best_root);
+    server.join().unwrap();
+
+    // Server is gone, fallible methods return an error
+    block_on(client.block(&best_root)).unwrap_err();
+}
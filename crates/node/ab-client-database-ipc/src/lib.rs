@@ -0,0 +1,24 @@
+//! Access to client database from other processes over a UNIX socket.
+//!
+//! Client database can't be opened by more than one process at a time, so auxiliary processes
+//! (indexers, exporters, etc.) that need access to the node's data connect to the node instead.
+//! The node runs [`run_server()`] with its [`ChainInfo`] implementation, and other processes use
+//! [`ChainInfoClient`], which implements [`ChainInfo`] and [`ChainInfoWrite`] by forwarding calls
+//! to the server.
+//!
+//! Since the server allows writes, only processes running as the same user as the node are
+//! allowed to connect.
+//!
+//! [`ChainInfo`]: ab_client_api::ChainInfo
+//! [`ChainInfoWrite`]: ab_client_api::ChainInfoWrite
+
+#![expect(incomplete_features, reason = "generic_const_exprs")]
+#![feature(generic_const_exprs)]
+
+mod client;
+mod protocol;
+mod server;
+
+pub use client::ChainInfoClient;
+pub use protocol::{MAX_MESSAGE_SIZE, PROTOCOL_VERSION};
+pub use server::{ServerAccess, bind, run_server};
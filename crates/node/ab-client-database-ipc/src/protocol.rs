@@ -0,0 +1,760 @@
+//! Compact binary protocol spoken between server and client.
+//!
+//! After connecting, the client sends [`Handshake`] and the server responds with its own
+//! [`Handshake`], the connection is closed if they are not compatible. After that the client sends
+//! requests and the server sends exactly one response for each request in the same order.
+//!
+//! Each message is a SCALE-encoded [`Request`] or [`Response`] prefixed with its length as `u32`
+//! little-endian bytes.
+
+#[cfg(test)]
+mod tests;
+
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockDetails, BlockMerkleMountainRange, BlockMmrProof, BlockOutcome, ContractSlotState,
+    PersistBlockAuxDataError, PersistBlockError, PersistBlockOutcomeError,
+    PersistContractSlotsError, PersistSegmentHeadersError, ReadBlockError, ReadContractSlotError,
+    ReadMmrProofError, TransactionOutcome, TransactionStatus,
+};
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::execution::ExecutionReceipt;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::segments::{LocalSegmentIndex, SegmentHeader};
+use ab_core_primitives::shard::RealShardKind;
+use ab_core_primitives::transaction::{Gas, TransactionHash};
+use parity_scale_codec::{Decode, DecodeAll, Encode};
+use rclite::Arc;
+use std::io;
+
+/// Version of the protocol, incremented on every incompatible change
+pub const PROTOCOL_VERSION: u8 = 7;
+/// Max size of a single message in bytes
+pub const MAX_MESSAGE_SIZE: u32 = 32 * 1024 * 1024;
+/// Magic bytes at the beginning of the handshake
+const HANDSHAKE_MAGIC: [u8; 4] = *b"abdb";
+
+/// Handshake exchanged by both sides right after connecting
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct Handshake {
+    /// Protocol version
+    pub(crate) version: u8,
+    /// Kind of the shard whose blocks are stored in the database
+    pub(crate) shard_kind: RealShardKind,
+}
+
+impl Handshake {
+    pub(crate) const SIZE: usize = HANDSHAKE_MAGIC.len() + 2;
+
+    pub(crate) fn new(shard_kind: RealShardKind) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            shard_kind,
+        }
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        let (magic, remainder) = bytes.split_at_mut(HANDSHAKE_MAGIC.len());
+        magic.copy_from_slice(&HANDSHAKE_MAGIC);
+        remainder[0] = self.version;
+        remainder[1] = match self.shard_kind {
+            RealShardKind::BeaconChain => 0,
+            RealShardKind::IntermediateShard => 1,
+            RealShardKind::LeafShard => 2,
+        };
+        bytes
+    }
+
+    /// Returns `None` if bytes do not represent a valid handshake
+    pub(crate) fn from_bytes(bytes: [u8; Self::SIZE]) -> Option<Self> {
+        let (magic, remainder) = bytes.split_at(HANDSHAKE_MAGIC.len());
+        if magic != HANDSHAKE_MAGIC {
+            return None;
+        }
+
+        let shard_kind = match remainder[1] {
+            0 => RealShardKind::BeaconChain,
+            1 => RealShardKind::IntermediateShard,
+            2 => RealShardKind::LeafShard,
+            _ => {
+                return None;
+            }
+        };
+
+        Some(Self {
+            version: remainder[0],
+            shard_kind,
+        })
+    }
+}
+
+/// State of a contract slot, see [`ContractSlotState`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) struct WireContractSlotState {
+    owner: u128,
+    contract: u128,
+    contents: Vec<u8>,
+}
+
+impl From<&ContractSlotState> for WireContractSlotState {
+    fn from(contract_slot_state: &ContractSlotState) -> Self {
+        Self {
+            owner: u128::from(contract_slot_state.owner),
+            contract: u128::from(contract_slot_state.contract),
+            contents: contract_slot_state.contents.as_slice().to_vec(),
+        }
+    }
+}
+
+impl From<WireContractSlotState> for ContractSlotState {
+    fn from(contract_slot_state: WireContractSlotState) -> Self {
+        Self {
+            owner: Address::from(contract_slot_state.owner),
+            contract: Address::from(contract_slot_state.contract),
+            contents: SharedAlignedBuffer::from_bytes(&contract_slot_state.contents),
+        }
+    }
+}
+
+/// Status of a transaction execution, see [`TransactionStatus`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WireTransactionStatus {
+    Success,
+    Failed,
+}
+
+impl From<TransactionStatus> for WireTransactionStatus {
+    fn from(status: TransactionStatus) -> Self {
+        match status {
+            TransactionStatus::Success => Self::Success,
+            TransactionStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<WireTransactionStatus> for TransactionStatus {
+    fn from(status: WireTransactionStatus) -> Self {
+        match status {
+            WireTransactionStatus::Success => Self::Success,
+            WireTransactionStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+/// Outcome of a single transaction execution, see [`TransactionOutcome`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) struct WireTransactionOutcome {
+    transaction_hash: Blake3Hash,
+    pre_state_root: Blake3Hash,
+    post_state_root: Blake3Hash,
+    gas_used: u64,
+    status: WireTransactionStatus,
+    events: Vec<u8>,
+}
+
+impl From<&TransactionOutcome> for WireTransactionOutcome {
+    fn from(transaction_outcome: &TransactionOutcome) -> Self {
+        let receipt = &transaction_outcome.receipt;
+
+        Self {
+            transaction_hash: Blake3Hash::from(receipt.transaction_hash),
+            pre_state_root: receipt.pre_state_root,
+            post_state_root: receipt.post_state_root,
+            gas_used: u64::from(receipt.gas_used),
+            status: WireTransactionStatus::from(transaction_outcome.status),
+            events: transaction_outcome.events.as_slice().to_vec(),
+        }
+    }
+}
+
+impl From<WireTransactionOutcome> for TransactionOutcome {
+    fn from(transaction_outcome: WireTransactionOutcome) -> Self {
+        Self {
+            receipt: ExecutionReceipt {
+                transaction_hash: TransactionHash::from(transaction_outcome.transaction_hash),
+                pre_state_root: transaction_outcome.pre_state_root,
+                post_state_root: transaction_outcome.post_state_root,
+                gas_used: Gas::from(transaction_outcome.gas_used),
+            },
+            status: TransactionStatus::from(transaction_outcome.status),
+            events: SharedAlignedBuffer::from_bytes(&transaction_outcome.events),
+        }
+    }
+}
+
+/// Outcome of a block execution, see [`BlockOutcome`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) struct WireBlockOutcome {
+    transactions: Vec<WireTransactionOutcome>,
+}
+
+impl From<&BlockOutcome> for WireBlockOutcome {
+    fn from(block_outcome: &BlockOutcome) -> Self {
+        Self {
+            transactions: block_outcome
+                .transactions
+                .iter()
+                .map(WireTransactionOutcome::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<WireBlockOutcome> for BlockOutcome {
+    fn from(block_outcome: WireBlockOutcome) -> Self {
+        Self {
+            transactions: block_outcome
+                .transactions
+                .into_iter()
+                .map(TransactionOutcome::from)
+                .collect(),
+        }
+    }
+}
+
+/// Additional details about a block, see [`BlockDetails`].
+///
+/// MMR is sent as its occupied peaks, which is much more compact than its in-memory
+/// representation.
+#[derive(Debug, Encode, Decode)]
+pub(crate) struct WireBlockDetails {
+    mmr_num_leaves: u64,
+    mmr_peaks: Vec<[u8; 32]>,
+    system_contract_states: Vec<WireContractSlotState>,
+}
+
+impl From<&BlockDetails> for WireBlockDetails {
+    fn from(block_details: &BlockDetails) -> Self {
+        let peaks = block_details.mmr_with_block.peaks();
+
+        Self {
+            mmr_num_leaves: peaks.num_leaves,
+            mmr_peaks: peaks.peaks[..usize::from(peaks.num_peaks())].to_vec(),
+            system_contract_states: block_details
+                .system_contract_states
+                .iter()
+                .map(WireContractSlotState::from)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<WireBlockDetails> for BlockDetails {
+    type Error = io::Error;
+
+    fn try_from(block_details: WireBlockDetails) -> Result<Self, Self::Error> {
+        let mut peaks = BlockMerkleMountainRange::new().peaks();
+        peaks.num_leaves = block_details.mmr_num_leaves;
+
+        let invalid_peaks = || io::Error::new(io::ErrorKind::InvalidData, "Invalid MMR peaks");
+
+        if usize::from(peaks.num_peaks()) != block_details.mmr_peaks.len() {
+            return Err(invalid_peaks());
+        }
+        peaks
+            .peaks
+            .get_mut(..block_details.mmr_peaks.len())
+            .ok_or_else(invalid_peaks)?
+            .copy_from_slice(&block_details.mmr_peaks);
+
+        let mmr_with_block =
+            BlockMerkleMountainRange::from_peaks(&peaks).ok_or_else(invalid_peaks)?;
+
+        Ok(Self {
+            mmr_with_block: Arc::new(mmr_with_block),
+            system_contract_states: block_details
+                .system_contract_states
+                .into_iter()
+                .map(ContractSlotState::from)
+                .collect(),
+        })
+    }
+}
+
+/// Inclusion proof of a block root, see [`BlockMmrProof`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) struct WireBlockMmrProof {
+    block_number: BlockNumber,
+    block_root: BlockRoot,
+    num_leaves: u64,
+    mmr_root: [u8; 32],
+    proof: Vec<[u8; 32]>,
+}
+
+impl From<BlockMmrProof> for WireBlockMmrProof {
+    fn from(proof: BlockMmrProof) -> Self {
+        Self {
+            block_number: proof.block_number,
+            block_root: proof.block_root,
+            num_leaves: proof.num_leaves,
+            mmr_root: proof.mmr_root,
+            proof: proof.proof,
+        }
+    }
+}
+
+impl From<WireBlockMmrProof> for BlockMmrProof {
+    fn from(proof: WireBlockMmrProof) -> Self {
+        Self {
+            block_number: proof.block_number,
+            block_root: proof.block_root,
+            num_leaves: proof.num_leaves,
+            mmr_root: proof.mmr_root,
+            proof: proof.proof,
+        }
+    }
+}
+
+/// Error for [`ReadBlockError`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WireReadBlockError {
+    UnknownBlockRoot,
+    FailedToDecode,
+    Io(String),
+}
+
+impl From<ReadBlockError> for WireReadBlockError {
+    fn from(error: ReadBlockError) -> Self {
+        match error {
+            ReadBlockError::UnknownBlockRoot => Self::UnknownBlockRoot,
+            ReadBlockError::FailedToDecode => Self::FailedToDecode,
+            ReadBlockError::StorageItemReadError { error } => Self::Io(error.to_string()),
+            ReadBlockError::CorruptedStorageItem { error } => Self::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<WireReadBlockError> for ReadBlockError {
+    fn from(error: WireReadBlockError) -> Self {
+        match error {
+            WireReadBlockError::UnknownBlockRoot => Self::UnknownBlockRoot,
+            WireReadBlockError::FailedToDecode => Self::FailedToDecode,
+            WireReadBlockError::Io(error) => Self::StorageItemReadError {
+                error: io::Error::other(error),
+            },
+        }
+    }
+}
+
+/// Error for [`PersistBlockError`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WirePersistBlockError {
+    MissingParent,
+    OutsideAcceptableRange,
+    UnsupportedForkChoice,
+    Io(String),
+}
+
+impl From<PersistBlockError> for WirePersistBlockError {
+    fn from(error: PersistBlockError) -> Self {
+        match error {
+            PersistBlockError::MissingParent => Self::MissingParent,
+            PersistBlockError::OutsideAcceptableRange => Self::OutsideAcceptableRange,
+            PersistBlockError::UnsupportedForkChoice => Self::UnsupportedForkChoice,
+            PersistBlockError::StorageItemWriteError { error } => Self::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<WirePersistBlockError> for PersistBlockError {
+    fn from(error: WirePersistBlockError) -> Self {
+        match error {
+            WirePersistBlockError::MissingParent => Self::MissingParent,
+            WirePersistBlockError::OutsideAcceptableRange => Self::OutsideAcceptableRange,
+            WirePersistBlockError::UnsupportedForkChoice => Self::UnsupportedForkChoice,
+            WirePersistBlockError::Io(error) => Self::StorageItemWriteError {
+                error: io::Error::other(error),
+            },
+        }
+    }
+}
+
+/// Error for [`PersistSegmentHeadersError`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WirePersistSegmentHeadersError {
+    MustFollowLastSegmentIndex {
+        local_segment_index: LocalSegmentIndex,
+        last_local_segment_index: LocalSegmentIndex,
+    },
+    FirstSegmentIndexZero {
+        local_segment_index: LocalSegmentIndex,
+    },
+    SegmentHeaderMismatch {
+        local_segment_index: LocalSegmentIndex,
+    },
+    Io(String),
+}
+
+impl From<PersistSegmentHeadersError> for WirePersistSegmentHeadersError {
+    fn from(error: PersistSegmentHeadersError) -> Self {
+        match error {
+            PersistSegmentHeadersError::MustFollowLastSegmentIndex {
+                local_segment_index,
+                last_local_segment_index,
+            } => Self::MustFollowLastSegmentIndex {
+                local_segment_index,
+                last_local_segment_index,
+            },
+            PersistSegmentHeadersError::FirstSegmentIndexZero {
+                local_segment_index,
+            } => Self::FirstSegmentIndexZero {
+                local_segment_index,
+            },
+            PersistSegmentHeadersError::SegmentHeaderMismatch {
+                local_segment_index,
+            } => Self::SegmentHeaderMismatch {
+                local_segment_index,
+            },
+            PersistSegmentHeadersError::StorageItemWriteError { error } => {
+                Self::Io(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<WirePersistSegmentHeadersError> for PersistSegmentHeadersError {
+    fn from(error: WirePersistSegmentHeadersError) -> Self {
+        match error {
+            WirePersistSegmentHeadersError::MustFollowLastSegmentIndex {
+                local_segment_index,
+                last_local_segment_index,
+            } => Self::MustFollowLastSegmentIndex {
+                local_segment_index,
+                last_local_segment_index,
+            },
+            WirePersistSegmentHeadersError::FirstSegmentIndexZero {
+                local_segment_index,
+            } => Self::FirstSegmentIndexZero {
+                local_segment_index,
+            },
+            WirePersistSegmentHeadersError::SegmentHeaderMismatch {
+                local_segment_index,
+            } => Self::SegmentHeaderMismatch {
+                local_segment_index,
+            },
+            WirePersistSegmentHeadersError::Io(error) => Self::StorageItemWriteError {
+                error: io::Error::other(error),
+            },
+        }
+    }
+}
+
+/// Error for [`PersistBlockAuxDataError`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WirePersistBlockAuxDataError {
+    UnknownBlockRoot,
+    TooLarge { size: u32 },
+    Io(String),
+}
+
+impl From<PersistBlockAuxDataError> for WirePersistBlockAuxDataError {
+    fn from(error: PersistBlockAuxDataError) -> Self {
+        match error {
+            PersistBlockAuxDataError::UnknownBlockRoot => Self::UnknownBlockRoot,
+            PersistBlockAuxDataError::TooLarge { size } => Self::TooLarge { size },
+            PersistBlockAuxDataError::StorageItemWriteError { error } => {
+                Self::Io(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<WirePersistBlockAuxDataError> for PersistBlockAuxDataError {
+    fn from(error: WirePersistBlockAuxDataError) -> Self {
+        match error {
+            WirePersistBlockAuxDataError::UnknownBlockRoot => Self::UnknownBlockRoot,
+            WirePersistBlockAuxDataError::TooLarge { size } => Self::TooLarge { size },
+            WirePersistBlockAuxDataError::Io(error) => Self::StorageItemWriteError {
+                error: io::Error::other(error),
+            },
+        }
+    }
+}
+
+/// Error for [`PersistBlockOutcomeError`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WirePersistBlockOutcomeError {
+    UnknownBlockRoot,
+    Io(String),
+}
+
+impl From<PersistBlockOutcomeError> for WirePersistBlockOutcomeError {
+    fn from(error: PersistBlockOutcomeError) -> Self {
+        match error {
+            PersistBlockOutcomeError::UnknownBlockRoot => Self::UnknownBlockRoot,
+            PersistBlockOutcomeError::StorageItemWriteError { error } => {
+                Self::Io(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<WirePersistBlockOutcomeError> for PersistBlockOutcomeError {
+    fn from(error: WirePersistBlockOutcomeError) -> Self {
+        match error {
+            WirePersistBlockOutcomeError::UnknownBlockRoot => Self::UnknownBlockRoot,
+            WirePersistBlockOutcomeError::Io(error) => Self::StorageItemWriteError {
+                error: io::Error::other(error),
+            },
+        }
+    }
+}
+
+/// Error for [`ReadContractSlotError`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WireReadContractSlotError {
+    UnknownBlockRoot,
+}
+
+impl From<ReadContractSlotError> for WireReadContractSlotError {
+    fn from(error: ReadContractSlotError) -> Self {
+        match error {
+            ReadContractSlotError::UnknownBlockRoot => Self::UnknownBlockRoot,
+        }
+    }
+}
+
+impl From<WireReadContractSlotError> for ReadContractSlotError {
+    fn from(error: WireReadContractSlotError) -> Self {
+        match error {
+            WireReadContractSlotError::UnknownBlockRoot => Self::UnknownBlockRoot,
+        }
+    }
+}
+
+/// Error for [`ReadMmrProofError`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WireReadMmrProofError {
+    UnknownBlockNumber {
+        block_number: BlockNumber,
+        best_block_number: BlockNumber,
+    },
+    BlockRootsUnavailable,
+}
+
+impl From<ReadMmrProofError> for WireReadMmrProofError {
+    fn from(error: ReadMmrProofError) -> Self {
+        match error {
+            ReadMmrProofError::UnknownBlockNumber {
+                block_number,
+                best_block_number,
+            } => Self::UnknownBlockNumber {
+                block_number,
+                best_block_number,
+            },
+            ReadMmrProofError::BlockRootsUnavailable => Self::BlockRootsUnavailable,
+        }
+    }
+}
+
+impl From<WireReadMmrProofError> for ReadMmrProofError {
+    fn from(error: WireReadMmrProofError) -> Self {
+        match error {
+            WireReadMmrProofError::UnknownBlockNumber {
+                block_number,
+                best_block_number,
+            } => Self::UnknownBlockNumber {
+                block_number,
+                best_block_number,
+            },
+            WireReadMmrProofError::BlockRootsUnavailable => Self::BlockRootsUnavailable,
+        }
+    }
+}
+
+/// Error for [`PersistContractSlotsError`]
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum WirePersistContractSlotsError {
+    UnknownBlockRoot,
+    Io(String),
+}
+
+impl From<PersistContractSlotsError> for WirePersistContractSlotsError {
+    fn from(error: PersistContractSlotsError) -> Self {
+        match error {
+            PersistContractSlotsError::UnknownBlockRoot => Self::UnknownBlockRoot,
+            PersistContractSlotsError::StorageItemWriteError { error } => {
+                Self::Io(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<WirePersistContractSlotsError> for PersistContractSlotsError {
+    fn from(error: WirePersistContractSlotsError) -> Self {
+        match error {
+            WirePersistContractSlotsError::UnknownBlockRoot => Self::UnknownBlockRoot,
+            WirePersistContractSlotsError::Io(error) => Self::StorageItemWriteError {
+                error: io::Error::other(error),
+            },
+        }
+    }
+}
+
+/// Request sent by the client, each variant corresponds to a method of `ChainInfo` or
+/// `ChainInfoWrite`
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum Request {
+    BestRoot,
+    BestHeader,
+    BestHeaderWithDetails,
+    BlockConfirmationDepth,
+    AncestorHeader {
+        ancestor_block_number: BlockNumber,
+        descendant_block_root: BlockRoot,
+    },
+    CanonicalHeader {
+        block_number: BlockNumber,
+    },
+    Header {
+        block_root: BlockRoot,
+    },
+    HeaderWithDetails {
+        block_root: BlockRoot,
+    },
+    Block {
+        block_root: BlockRoot,
+    },
+    LastSegmentHeader,
+    GetSegmentHeader {
+        segment_index: LocalSegmentIndex,
+    },
+    SegmentHeadersForBlock {
+        block_number: BlockNumber,
+    },
+    BlockAuxData {
+        block_root: BlockRoot,
+        namespace: [u8; 8],
+    },
+    BlockOutcome {
+        block_root: BlockRoot,
+    },
+    FindTransaction {
+        tx_hash: Blake3Hash,
+    },
+    ContractSlot {
+        block_root: BlockRoot,
+        owner: u128,
+        contract: u128,
+    },
+    MmrProof {
+        block_number: BlockNumber,
+    },
+    PersistBlock {
+        header: Vec<u8>,
+        body: Vec<u8>,
+        block_details: WireBlockDetails,
+    },
+    PersistBlocks {
+        blocks: Vec<(Vec<u8>, Vec<u8>, WireBlockDetails)>,
+    },
+    PersistSegmentHeaders {
+        segment_headers: Vec<SegmentHeader>,
+    },
+    PersistBlockAuxData {
+        block_root: BlockRoot,
+        namespace: [u8; 8],
+        data: Vec<u8>,
+    },
+    PersistBlockOutcome {
+        block_root: BlockRoot,
+        outcome: WireBlockOutcome,
+    },
+    PersistContractSlots {
+        block_root: BlockRoot,
+        slots: Vec<WireContractSlotState>,
+    },
+    RetainBlocksForArchiving {
+        first_block_number: BlockNumber,
+    },
+}
+
+impl Request {
+    /// Whether the request modifies the database
+    pub(crate) fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Self::PersistBlock { .. }
+                | Self::PersistBlocks { .. }
+                | Self::PersistSegmentHeaders { .. }
+                | Self::PersistBlockAuxData { .. }
+                | Self::PersistBlockOutcome { .. }
+                | Self::PersistContractSlots { .. }
+                | Self::RetainBlocksForArchiving { .. }
+        )
+    }
+}
+
+/// Response sent by the server
+#[derive(Debug, Encode, Decode)]
+pub(crate) enum Response {
+    BestRoot(BlockRoot),
+    BlockConfirmationDepth(BlockNumber),
+    /// Encoded block header
+    Header(Option<Vec<u8>>),
+    /// Encoded block header with details
+    HeaderWithDetails(Option<(Vec<u8>, WireBlockDetails)>),
+    /// Encoded block header and body
+    Block(Result<(Vec<u8>, Vec<u8>), WireReadBlockError>),
+    SegmentHeader(Option<SegmentHeader>),
+    SegmentHeaders(Vec<SegmentHeader>),
+    BlockAuxData(Option<Vec<u8>>),
+    BlockOutcome(Option<WireBlockOutcome>),
+    /// Block root and offset of the transaction in the block body
+    FindTransaction(Option<(BlockRoot, u32)>),
+    ContractSlot(Result<Option<Vec<u8>>, WireReadContractSlotError>),
+    MmrProof(Result<WireBlockMmrProof, WireReadMmrProofError>),
+    PersistBlock(Result<(), WirePersistBlockError>),
+    PersistBlocks(Result<(), WirePersistBlockError>),
+    PersistSegmentHeaders(Result<(), WirePersistSegmentHeadersError>),
+    PersistBlockAuxData(Result<(), WirePersistBlockAuxDataError>),
+    PersistBlockOutcome(Result<(), WirePersistBlockOutcomeError>),
+    PersistContractSlots(Result<(), WirePersistContractSlotsError>),
+    RetainBlocksForArchiving,
+    /// Write request was rejected because the server only allows reads
+    ReadOnly,
+}
+
+/// Encode message together with its length prefix
+pub(crate) fn encode_message<T>(message: &T) -> io::Result<Vec<u8>>
+where
+    T: Encode,
+{
+    let message_size = message.encoded_size();
+    if message_size > MAX_MESSAGE_SIZE as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Message size {message_size} exceeds max message size {MAX_MESSAGE_SIZE}"),
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(size_of::<u32>() + message_size);
+    bytes.extend_from_slice(&(message_size as u32).to_le_bytes());
+    message.encode_to(&mut bytes);
+
+    Ok(bytes)
+}
+
+/// Decode message length from its length prefix
+pub(crate) fn decode_message_size(length_prefix: [u8; size_of::<u32>()]) -> io::Result<usize> {
+    let message_size = u32::from_le_bytes(length_prefix);
+    if message_size > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Message size {message_size} exceeds max message size {MAX_MESSAGE_SIZE}"),
+        ));
+    }
+
+    Ok(message_size as usize)
+}
+
+/// Decode message without its length prefix
+pub(crate) fn decode_message<T>(mut bytes: &[u8]) -> io::Result<T>
+where
+    T: Decode,
+{
+    T::decode_all(&mut bytes)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))
+}
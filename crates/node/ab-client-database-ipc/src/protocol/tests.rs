@@ -0,0 +1,146 @@
+use crate::protocol::{
+    Handshake, MAX_MESSAGE_SIZE, Request, WireBlockDetails, WireBlockOutcome, decode_message,
+    decode_message_size, encode_message,
+};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockDetails, BlockMerkleMountainRange, BlockOutcome, ContractSlotState, TransactionOutcome,
+    TransactionStatus,
+};
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::BlockRoot;
+use ab_core_primitives::block::execution::ExecutionReceipt;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::shard::RealShardKind;
+use ab_core_primitives::transaction::{Gas, TransactionHash};
+use rclite::Arc;
+use std::sync::Arc as StdArc;
+
+#[test]
+fn handshake() {
+    for shard_kind in [
+        RealShardKind::BeaconChain,
+        RealShardKind::IntermediateShard,
+        RealShardKind::LeafShard,
+    ] {
+        let handshake = Handshake::new(shard_kind);
+        assert_eq!(Handshake::from_bytes(handshake.to_bytes()), Some(handshake));
+    }
+
+    let mut bytes = Handshake::new(RealShardKind::BeaconChain).to_bytes();
+    // Invalid shard kind
+    bytes[Handshake::SIZE - 1] = 3;
+    assert_eq!(Handshake::from_bytes(bytes), None);
+
+    let mut bytes = Handshake::new(RealShardKind::BeaconChain).to_bytes();
+    // Invalid magic
+    bytes[0] = 0;
+    assert_eq!(Handshake::from_bytes(bytes), None);
+}
+
+#[test]
+fn block_details() {
+    for num_leaves in [0_u8, 1, 2, 7, 8, 100] {
+        let mut mmr = BlockMerkleMountainRange::new();
+        for leaf in 0..num_leaves {
+            assert!(mmr.add_leaf(&[leaf; _]));
+        }
+
+        let block_details = BlockDetails {
+            mmr_with_block: Arc::new(mmr),
+            system_contract_states: StdArc::new([ContractSlotState {
+                owner: Address::from(1_u128),
+                contract: Address::from(2_u128),
+                contents: SharedAlignedBuffer::from_bytes(&[1, 2, 3]),
+            }]),
+        };
+
+        let decoded_block_details =
+            BlockDetails::try_from(WireBlockDetails::from(&block_details)).unwrap();
+
+        assert_eq!(
+            decoded_block_details.mmr_with_block.root(),
+            block_details.mmr_with_block.root()
+        );
+        assert_eq!(
+            decoded_block_details.mmr_with_block.num_leaves(),
+            u64::from(num_leaves)
+        );
+        assert_eq!(decoded_block_details.system_contract_states.len(), 1);
+        let system_contract_state = &decoded_block_details.system_contract_states[0];
+        assert_eq!(system_contract_state.owner, Address::from(1_u128));
+        assert_eq!(system_contract_state.contract, Address::from(2_u128));
+        assert_eq!(system_contract_state.contents.as_slice(), &[1, 2, 3]);
+    }
+
+    // Number of peaks doesn't match the number of leaves
+    let mut wire_block_details = WireBlockDetails::from(&BlockDetails {
+        mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+        system_contract_states: StdArc::new([]),
+    });
+    wire_block_details.mmr_num_leaves = 3;
+    BlockDetails::try_from(wire_block_details).unwrap_err();
+}
+
+#[test]
+fn block_outcome() {
+    let block_outcome = BlockOutcome {
+        transactions: [TransactionStatus::Success, TransactionStatus::Failed]
+            .into_iter()
+            .enumerate()
+            .map(|(index, status)| TransactionOutcome {
+                receipt: ExecutionReceipt {
+                    transaction_hash: TransactionHash::from(Blake3Hash::new([index as u8; _])),
+                    pre_state_root: Blake3Hash::new([1; _]),
+                    post_state_root: Blake3Hash::new([2; _]),
+                    gas_used: Gas::from(index as u64 + 10),
+                },
+                status,
+                events: SharedAlignedBuffer::from_bytes(&[index as u8; 3]),
+            })
+            .collect(),
+    };
+
+    let decoded_block_outcome = BlockOutcome::from(WireBlockOutcome::from(&block_outcome));
+
+    assert_eq!(decoded_block_outcome.transactions.len(), 2);
+    assert_eq!(decoded_block_outcome.gas_used(), Gas::from(21));
+    for (decoded, original) in decoded_block_outcome
+        .transactions
+        .iter()
+        .zip(block_outcome.transactions.iter())
+    {
+        assert_eq!(decoded.receipt, original.receipt);
+        assert_eq!(decoded.status, original.status);
+        assert_eq!(decoded.events.as_slice(), original.events.as_slice());
+    }
+}
+
+#[test]
+fn message_framing() {
+    let request = Request::Header {
+        block_root: BlockRoot::default(),
+    };
+    let bytes = encode_message(&request).unwrap();
+    let (length_prefix, message) = bytes.split_first_chunk::<{ size_of::<u32>() }>().unwrap();
+
+    assert_eq!(decode_message_size(*length_prefix).unwrap(), message.len());
+    assert!(matches!(
+        decode_message::<Request>(message).unwrap(),
+        Request::Header { block_root } if block_root == BlockRoot::default()
+    ));
+
+    // Trailing bytes are not allowed
+    let mut message = message.to_vec();
+    message.push(0);
+    decode_message::<Request>(&message).unwrap_err();
+
+    // Too large messages are rejected
+    decode_message_size((MAX_MESSAGE_SIZE + 1).to_le_bytes()).unwrap_err();
+    encode_message(&Request::PersistBlockAuxData {
+        block_root: BlockRoot::default(),
+        namespace: [0; 8],
+        data: vec![0; MAX_MESSAGE_SIZE as usize],
+    })
+    .unwrap_err();
+}
@@ -0,0 +1,324 @@
+#[cfg(test)]
+mod tests;
+
+use crate::protocol::{
+    Handshake, Request, Response, WireBlockDetails, WireBlockOutcome, decode_message,
+    decode_message_size, encode_message,
+};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockAuxDataNamespace, BlockDetails, BlockOutcome, ChainInfoWrite, ContractSlotState,
+};
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::body::owned::GenericOwnedBlockBody;
+use ab_core_primitives::block::header::owned::GenericOwnedBlockHeader;
+use ab_core_primitives::block::owned::GenericOwnedBlock;
+use ab_core_primitives::transaction::TransactionHash;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::{fs, io};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::debug;
+
+/// Access to the database allowed to connected clients
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ServerAccess {
+    /// Only reads are allowed, write requests are rejected
+    ReadOnly,
+    /// Both reads and writes are allowed
+    ReadWrite,
+}
+
+/// Create a listener for [`run_server()`] bound to the specified path.
+///
+/// The socket is only accessible by its owner. Must be called within Tokio runtime.
+pub fn bind<P>(path: P) -> io::Result<UnixListener>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+    Ok(listener)
+}
+
+/// Accept connections on the listener and serve requests of connected clients using provided
+/// chain info.
+///
+/// Each connection is handled in a separate task, so this must be called within Tokio runtime.
+/// Returns an error only if accepting new connections fails or the listener is not bound to a file
+/// system path, errors of individual connections are logged and only result in that connection
+/// being closed.
+///
+/// Only processes running as the owner of the socket are allowed to connect, connections from other
+/// users are rejected based on peer credentials. Use [`bind()`] to create a listener with a socket
+/// that is not accessible by other users in the first place.
+pub async fn run_server<Block, CI>(
+    listener: UnixListener,
+    chain_info: CI,
+    access: ServerAccess,
+) -> io::Result<()>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfoWrite<Block>,
+{
+    let local_address = listener.local_addr()?;
+    let Some(socket_path) = local_address.as_pathname() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Listener must be bound to a file system path",
+        ));
+    };
+    let owner_uid = fs::metadata(socket_path)?.uid();
+
+    loop {
+        let (stream, _address) = listener.accept().await?;
+        let chain_info = chain_info.clone();
+
+        tokio::spawn(async move {
+            if let Err(error) =
+                handle_connection::<Block, _>(stream, owner_uid, &chain_info, access).await
+            {
+                debug!(%error, "Chain info client connection closed with error");
+            }
+        });
+    }
+}
+
+async fn handle_connection<Block, CI>(
+    mut stream: UnixStream,
+    owner_uid: u32,
+    chain_info: &CI,
+    access: ServerAccess,
+) -> io::Result<()>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfoWrite<Block>,
+{
+    let peer_uid = stream.peer_cred()?.uid();
+    if peer_uid != owner_uid {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Peer user {peer_uid} is not the owner of the socket ({owner_uid})"),
+        ));
+    }
+
+    let local_handshake = Handshake::new(Block::SHARD_KIND);
+    let mut remote_handshake = [0; Handshake::SIZE];
+    stream.read_exact(&mut remote_handshake).await?;
+    // Local handshake is sent even if the remote one is not compatible, such that the client can
+    // produce a meaningful error
+    stream.write_all(&local_handshake.to_bytes()).await?;
+
+    if Handshake::from_bytes(remote_handshake) != Some(local_handshake) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Incompatible handshake",
+        ));
+    }
+
+    loop {
+        let mut length_prefix = [0; size_of::<u32>()];
+        if let Err(error) = stream.read_exact(&mut length_prefix).await {
+            if error.kind() == io::ErrorKind::UnexpectedEof {
+                // Client disconnected
+                return Ok(());
+            }
+
+            return Err(error);
+        }
+
+        let mut message = vec![0; decode_message_size(length_prefix)?];
+        stream.read_exact(&mut message).await?;
+        let request = decode_message::<Request>(&message)?;
+
+        let response = if request.is_write() && access == ServerAccess::ReadOnly {
+            Response::ReadOnly
+        } else {
+            handle_request::<Block, _>(chain_info, request).await?
+        };
+
+        stream.write_all(&encode_message(&response)?).await?;
+    }
+}
+
+async fn handle_request<Block, CI>(chain_info: &CI, request: Request) -> io::Result<Response>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfoWrite<Block>,
+{
+    let encode_header = |header: Block::Header| header.buffer().as_slice().to_vec();
+
+    Ok(match request {
+        Request::BestRoot => Response::BestRoot(chain_info.best_root()),
+        Request::BestHeader => Response::Header(Some(encode_header(chain_info.best_header()))),
+        Request::BestHeaderWithDetails => {
+            let (header, block_details) = chain_info.best_header_with_details();
+            Response::HeaderWithDetails(Some((
+                encode_header(header),
+                WireBlockDetails::from(&block_details),
+            )))
+        }
+        Request::BlockConfirmationDepth => {
+            Response::BlockConfirmationDepth(chain_info.block_confirmation_depth())
+        }
+        Request::AncestorHeader {
+            ancestor_block_number,
+            descendant_block_root,
+        } => Response::Header(
+            chain_info
+                .ancestor_header(ancestor_block_number, &descendant_block_root)
+                .map(encode_header),
+        ),
+        Request::CanonicalHeader { block_number } => {
+            Response::Header(chain_info.canonical_header(block_number).map(encode_header))
+        }
+        Request::Header { block_root } => {
+            Response::Header(chain_info.header(&block_root).map(encode_header))
+        }
+        Request::HeaderWithDetails { block_root } => {
+            Response::HeaderWithDetails(chain_info.header_with_details(&block_root).map(
+                |(header, block_details)| {
+                    (
+                        encode_header(header),
+                        WireBlockDetails::from(&block_details),
+                    )
+                },
+            ))
+        }
+        Request::Block { block_root } => Response::Block(
+            chain_info
+                .block(&block_root)
+                .await
+                .map(|block| {
+                    (
+                        block.header().buffer().as_slice().to_vec(),
+                        block.body().buffer().as_slice().to_vec(),
+                    )
+                })
+                .map_err(Into::into),
+        ),
+        Request::LastSegmentHeader => Response::SegmentHeader(chain_info.last_segment_header()),
+        Request::GetSegmentHeader { segment_index } => {
+            Response::SegmentHeader(chain_info.get_segment_header(segment_index))
+        }
+        Request::SegmentHeadersForBlock { block_number } => {
+            Response::SegmentHeaders(chain_info.segment_headers_for_block(block_number))
+        }
+        Request::BlockAuxData {
+            block_root,
+            namespace,
+        } => Response::BlockAuxData(
+            chain_info
+                .block_aux_data(&block_root, BlockAuxDataNamespace::new(namespace))
+                .map(|data| data.as_slice().to_vec()),
+        ),
+        Request::BlockOutcome { block_root } => Response::BlockOutcome(
+            chain_info
+                .block_outcome(&block_root)
+                .as_ref()
+                .map(WireBlockOutcome::from),
+        ),
+        Request::FindTransaction { tx_hash } => Response::FindTransaction(
+            chain_info
+                .find_transaction(&TransactionHash::from(tx_hash))
+                .map(|location| (location.block_root, location.offset)),
+        ),
+        Request::ContractSlot {
+            block_root,
+            owner,
+            contract,
+        } => Response::ContractSlot(
+            chain_info
+                .contract_slot(&block_root, &Address::from(owner), &Address::from(contract))
+                .map(|maybe_contents| maybe_contents.map(|contents| contents.as_slice().to_vec()))
+                .map_err(Into::into),
+        ),
+        Request::MmrProof { block_number } => Response::MmrProof(
+            chain_info
+                .mmr_proof(block_number)
+                .map(Into::into)
+                .map_err(Into::into),
+        ),
+        Request::PersistBlock {
+            header,
+            body,
+            block_details,
+        } => {
+            let block = Block::from_buffers(
+                SharedAlignedBuffer::from_bytes(&header),
+                SharedAlignedBuffer::from_bytes(&body),
+            )
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid block"))?;
+            let block_details = BlockDetails::try_from(block_details)?;
+
+            Response::PersistBlock(
+                chain_info
+                    .persist_block(block, block_details)
+                    .await
+                    .map_err(Into::into),
+            )
+        }
+        Request::PersistBlocks { blocks } => {
+            let blocks = blocks
+                .into_iter()
+                .map(|(header, body, block_details)| {
+                    let block = Block::from_buffers(
+                        SharedAlignedBuffer::from_bytes(&header),
+                        SharedAlignedBuffer::from_bytes(&body),
+                    )
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid block"))?;
+                    let block_details = BlockDetails::try_from(block_details)?;
+
+                    Ok((block, block_details))
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+
+            Response::PersistBlocks(chain_info.persist_blocks(blocks).await.map_err(Into::into))
+        }
+        Request::PersistSegmentHeaders { segment_headers } => Response::PersistSegmentHeaders(
+            chain_info
+                .persist_segment_headers(segment_headers)
+                .await
+                .map_err(Into::into),
+        ),
+        Request::PersistBlockAuxData {
+            block_root,
+            namespace,
+            data,
+        } => Response::PersistBlockAuxData(
+            chain_info
+                .persist_block_aux_data(
+                    &block_root,
+                    BlockAuxDataNamespace::new(namespace),
+                    SharedAlignedBuffer::from_bytes(&data),
+                )
+                .await
+                .map_err(Into::into),
+        ),
+        Request::PersistBlockOutcome {
+            block_root,
+            outcome,
+        } => Response::PersistBlockOutcome(
+            chain_info
+                .persist_block_outcome(&block_root, BlockOutcome::from(outcome))
+                .await
+                .map_err(Into::into),
+        ),
+        Request::PersistContractSlots { block_root, slots } => Response::PersistContractSlots(
+            chain_info
+                .persist_contract_slots(
+                    &block_root,
+                    slots.into_iter().map(ContractSlotState::from).collect(),
+                )
+                .await
+                .map_err(Into::into),
+        ),
+        Request::RetainBlocksForArchiving { first_block_number } => {
+            chain_info.retain_blocks_for_archiving(first_block_number);
+            Response::RetainBlocksForArchiving
+        }
+    })
+}
@@ -0,0 +1,16 @@
+use crate::server::bind;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn bind_restricts_permissions() {
+    let directory = TempDir::new().unwrap();
+    let path = directory.path().join("chain-info.sock");
+    let _listener = bind(&path).unwrap();
+
+    assert_eq!(
+        fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+        0o600
+    );
+}
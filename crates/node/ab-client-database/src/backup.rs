@@ -0,0 +1,294 @@
+//! Portable backup of the database, see [`ClientDatabase::backup()`] and
+//! [`ClientDatabase::restore()`].
+//!
+//! A backup contains all live storage items (segment headers, super segment headers, blocks with
+//! their state and auxiliary data of blocks) without page alignment, page groups or storage items
+//! that are no longer needed. As a result, it is much more compact than the database itself and
+//! can be restored into a database of a different size or with a different page group size.
+//!
+//! The layout is as follows:
+//! * [`MAGIC`] bytes
+//! * [`VERSION`] byte
+//! * a sequence of records, each consisting of:
+//!   * storage item variant: `u8`
+//!   * storage item size: `u32` as little-endian bytes
+//!   * storage item bytes, encoded the same way as in the database
+//! * end marker: `u8::MAX`
+//! * BLAKE3 checksum of everything above
+//!
+//! [`ClientDatabase::backup()`]: crate::ClientDatabase::backup
+//! [`ClientDatabase::restore()`]: crate::ClientDatabase::restore
+
+use crate::page_group::temporary::StorageItemTemporary;
+use crate::storage_backend::AlignedPage;
+use crate::storage_backend_adapter::storage_item::{StorageItem, StorageItemWriteResult};
+use crate::{ClientDatabaseError, ClientDatabaseFormatError};
+use ab_client_api::{
+    PersistSegmentHeadersError, PersistSuperSegmentHeadersError, StorageItemCorruptionError,
+};
+use ab_core_primitives::hashes::Blake3Hash;
+use blake3::Hasher;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use std::mem::MaybeUninit;
+use std::{io, mem};
+
+/// Magic bytes a backup starts with
+pub const MAGIC: [u8; 8] = *b"abdb-bkp";
+/// Current backup version
+pub const VERSION: u8 = 1;
+/// Marker that follows the last record, never used as a storage item variant
+const END_MARKER: u8 = u8::MAX;
+
+/// Max number of headers in a single record, such that a storage item with them fits into a single
+/// page regardless of the page group size of the database
+pub(crate) const fn max_headers_per_record<Header>() -> usize {
+    // Generous allowance for the storage item prefix and suffix
+    const OVERHEAD: usize = 256;
+
+    (AlignedPage::SIZE - OVERHEAD) / size_of::<Header>()
+}
+
+/// Error for [`ClientDatabase::backup()`] and [`ClientDatabase::restore()`]
+///
+/// [`ClientDatabase::backup()`]: crate::ClientDatabase::backup
+/// [`ClientDatabase::restore()`]: crate::ClientDatabase::restore
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    /// I/O error
+    #[error("I/O error: {error}")]
+    Io {
+        /// Low-level error
+        #[from]
+        error: io::Error,
+    },
+    /// Not a backup of the client database
+    #[error("Not a backup of the client database")]
+    InvalidMagic,
+    /// Unsupported backup version
+    #[error("Unsupported backup version: {version}")]
+    UnsupportedVersion {
+        /// Backup version
+        version: u8,
+    },
+    /// Invalid storage item in the backup
+    #[error("Invalid storage item #{index} in the backup: {reason}")]
+    InvalidStorageItem {
+        /// Index of the storage item in the backup
+        index: u64,
+        /// Reason why the storage item is invalid
+        reason: String,
+    },
+    /// Unexpected storage item in the backup
+    #[error("Unexpected storage item #{index} in the backup: {storage_item_type}")]
+    UnexpectedStorageItem {
+        /// Index of the storage item in the backup
+        index: u64,
+        /// Type of the storage item
+        storage_item_type: &'static str,
+    },
+    /// Backup checksum mismatch
+    #[error("Backup checksum mismatch: expected {expected}, actual {actual}")]
+    ChecksumMismatch {
+        /// Checksum of the backup contents
+        expected: Blake3Hash,
+        /// Checksum stored in the backup
+        actual: Blake3Hash,
+    },
+    /// Corrupted storage item in the database
+    #[error("Corrupted storage item in the database: {error}")]
+    CorruptedStorageItem {
+        /// Low-level error
+        #[from]
+        error: StorageItemCorruptionError,
+    },
+    /// Failed to format the database
+    #[error("Failed to format the database: {error}")]
+    Format {
+        /// Low-level error
+        #[from]
+        error: ClientDatabaseFormatError,
+    },
+    /// Failed to open the database
+    #[error("Failed to open the database: {error}")]
+    Open {
+        /// Low-level error
+        #[from]
+        error: ClientDatabaseError,
+    },
+    /// Invalid segment headers in the backup
+    #[error("Invalid segment headers in the backup: {error}")]
+    InvalidSegmentHeaders {
+        /// Low-level error
+        #[from]
+        error: PersistSegmentHeadersError,
+    },
+    /// Invalid super segment headers in the backup
+    #[error("Invalid super segment headers in the backup: {error}")]
+    InvalidSuperSegmentHeaders {
+        /// Low-level error
+        #[from]
+        error: PersistSuperSegmentHeadersError,
+    },
+}
+
+/// Writes records of a backup
+pub(crate) struct BackupWriter<'a, W> {
+    writer: &'a mut W,
+    hasher: Hasher,
+}
+
+impl<'a, W> BackupWriter<'a, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Create a new instance and write the header of the backup
+    pub(crate) async fn new(writer: &'a mut W) -> io::Result<Self> {
+        let mut backup_writer = Self {
+            writer,
+            hasher: Hasher::new(),
+        };
+
+        backup_writer.write_all(&MAGIC).await?;
+        backup_writer.write_all(&[VERSION]).await?;
+
+        Ok(backup_writer)
+    }
+
+    pub(crate) async fn write_storage_item(
+        &mut self,
+        storage_item: &StorageItemTemporary,
+    ) -> io::Result<()> {
+        let mut buffer =
+            vec![AlignedPage::default(); storage_item.total_bytes().div_ceil(AlignedPage::SIZE)];
+        let buffer =
+            AlignedPage::uninit_slice_mut_to_repr(AlignedPage::as_uninit_slice_mut(&mut buffer));
+        // SAFETY: Same size and alignment
+        let buffer_bytes = unsafe {
+            mem::transmute::<
+                &mut [MaybeUninit<[u8; AlignedPage::SIZE]>],
+                &mut [[MaybeUninit<u8>; AlignedPage::SIZE]],
+            >(buffer)
+        };
+
+        let StorageItemWriteResult {
+            storage_item_variant,
+            storage_item_bytes,
+            buffer: _,
+        } = storage_item
+            .write(buffer_bytes.as_flattened_mut())
+            .map_err(io::Error::other)?;
+
+        self.write_all(&[storage_item_variant]).await?;
+        self.write_all(&(storage_item_bytes.len() as u32).to_le_bytes())
+            .await?;
+        self.write_all(storage_item_bytes).await
+    }
+
+    /// Write the end marker and checksum of the backup
+    pub(crate) async fn finish(mut self) -> io::Result<()> {
+        self.write_all(&[END_MARKER]).await?;
+
+        let checksum = self.hasher.finalize();
+        self.writer.write_all(checksum.as_bytes()).await?;
+        self.writer.flush().await
+    }
+
+    async fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.hasher.update(bytes);
+        self.writer.write_all(bytes).await
+    }
+}
+
+/// Reads records of a backup
+pub(crate) struct BackupReader<'a, R> {
+    reader: &'a mut R,
+    hasher: Hasher,
+    /// Index of the next storage item
+    next_index: u64,
+}
+
+impl<'a, R> BackupReader<'a, R>
+where
+    R: AsyncRead + Unpin,
+{
+    /// Create a new instance and check the header of the backup
+    pub(crate) async fn new(reader: &'a mut R) -> Result<Self, BackupError> {
+        let mut backup_reader = Self {
+            reader,
+            hasher: Hasher::new(),
+            next_index: 0,
+        };
+
+        let mut magic = [0; MAGIC.len()];
+        backup_reader.read_exact(&mut magic).await?;
+        if magic != MAGIC {
+            return Err(BackupError::InvalidMagic);
+        }
+
+        let mut version = [0];
+        backup_reader.read_exact(&mut version).await?;
+        let [version] = version;
+        if version != VERSION {
+            return Err(BackupError::UnsupportedVersion { version });
+        }
+
+        Ok(backup_reader)
+    }
+
+    /// Index of the last storage item returned by [`Self::next_storage_item()`]
+    pub(crate) fn last_index(&self) -> u64 {
+        self.next_index.saturating_sub(1)
+    }
+
+    /// Read the next storage item.
+    ///
+    /// Returns `None` once the end of the backup is reached and its checksum was verified.
+    pub(crate) async fn next_storage_item(
+        &mut self,
+    ) -> Result<Option<StorageItemTemporary>, BackupError> {
+        let mut storage_item_variant = [0];
+        self.read_exact(&mut storage_item_variant).await?;
+        let [storage_item_variant] = storage_item_variant;
+
+        if storage_item_variant == END_MARKER {
+            let expected = Blake3Hash::from(self.hasher.finalize());
+            let mut actual = [0; Blake3Hash::SIZE];
+            self.reader.read_exact(&mut actual).await?;
+            let actual = Blake3Hash::new(actual);
+
+            if expected != actual {
+                return Err(BackupError::ChecksumMismatch { expected, actual });
+            }
+
+            return Ok(None);
+        }
+
+        let index = self.next_index;
+        self.next_index += 1;
+
+        let mut storage_item_size = [0; size_of::<u32>()];
+        self.read_exact(&mut storage_item_size).await?;
+        let storage_item_size = u32::from_le_bytes(storage_item_size) as usize;
+
+        // Storage items expect aligned buffers
+        let mut buffer =
+            vec![AlignedPage::default(); storage_item_size.div_ceil(AlignedPage::SIZE)];
+        let storage_item_bytes = &mut AlignedPage::slice_mut_to_repr(&mut buffer)
+            .as_flattened_mut()[..storage_item_size];
+        self.read_exact(storage_item_bytes).await?;
+
+        StorageItemTemporary::read(storage_item_variant, storage_item_bytes)
+            .map(Some)
+            .map_err(|error| BackupError::InvalidStorageItem {
+                index,
+                reason: error.to_string(),
+            })
+    }
+
+    async fn read_exact(&mut self, bytes: &mut [u8]) -> io::Result<()> {
+        self.reader.read_exact(bytes).await?;
+        self.hasher.update(bytes);
+
+        Ok(())
+    }
+}
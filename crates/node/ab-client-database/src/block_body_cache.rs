@@ -0,0 +1,115 @@
+//! LRU cache of bodies of persisted blocks, see [`ClientDatabaseOptions::block_body_cache`].
+//!
+//! [`ClientDatabaseOptions::block_body_cache`]: crate::ClientDatabaseOptions::block_body_cache
+
+use crate::BlockBodyCacheOptions;
+use crate::storage_backend_adapter::WriteLocation;
+use ab_aligned_buffer::SharedAlignedBuffer;
+use parking_lot::Mutex;
+use schnellru::{Limiter, LruMap};
+
+/// Limits both the number of cached block bodies and their total size
+#[derive(Debug)]
+struct BlockBodyLimiter {
+    max_blocks: usize,
+    max_bytes: usize,
+    bytes: usize,
+}
+
+impl Limiter<WriteLocation, SharedAlignedBuffer> for BlockBodyLimiter {
+    type KeyToInsert<'a> = WriteLocation;
+    type LinkType = u32;
+
+    #[inline]
+    fn is_over_the_limit(&self, length: usize) -> bool {
+        length > self.max_blocks || self.bytes > self.max_bytes
+    }
+
+    #[inline]
+    fn on_insert(
+        &mut self,
+        _length: usize,
+        key: Self::KeyToInsert<'_>,
+        value: SharedAlignedBuffer,
+    ) -> Option<(WriteLocation, SharedAlignedBuffer)> {
+        let size = value.len() as usize;
+        if self.max_blocks == 0 || size > self.max_bytes {
+            return None;
+        }
+
+        self.bytes += size;
+        Some((key, value))
+    }
+
+    #[inline]
+    fn on_replace(
+        &mut self,
+        _length: usize,
+        _old_key: &mut WriteLocation,
+        _new_key: Self::KeyToInsert<'_>,
+        old_value: &mut SharedAlignedBuffer,
+        new_value: &mut SharedAlignedBuffer,
+    ) -> bool {
+        let new_size = new_value.len() as usize;
+        if new_size > self.max_bytes {
+            return false;
+        }
+
+        self.bytes = self.bytes - old_value.len() as usize + new_size;
+        true
+    }
+
+    #[inline]
+    fn on_removed(&mut self, _key: &mut WriteLocation, value: &mut SharedAlignedBuffer) {
+        self.bytes -= value.len() as usize;
+    }
+
+    #[inline]
+    fn on_cleared(&mut self) {
+        self.bytes = 0;
+    }
+
+    #[inline]
+    fn on_grow(&mut self, _new_memory_usage: usize) -> bool {
+        true
+    }
+}
+
+/// Cache of block bodies keyed by their write location.
+///
+/// Write locations include a unique sequence number of the storage item, so a storage item
+/// relocated by compaction or a new storage item written into a reused page group never matches a
+/// stale cache entry.
+#[derive(Debug)]
+pub(crate) struct BlockBodyCache {
+    lru: Option<Mutex<LruMap<WriteLocation, SharedAlignedBuffer, BlockBodyLimiter>>>,
+}
+
+impl BlockBodyCache {
+    pub(crate) fn new(options: BlockBodyCacheOptions) -> Self {
+        let BlockBodyCacheOptions {
+            max_blocks,
+            max_bytes,
+        } = options;
+
+        let lru = (max_blocks > 0 && max_bytes > 0).then(|| {
+            Mutex::new(LruMap::new(BlockBodyLimiter {
+                max_blocks,
+                max_bytes,
+                bytes: 0,
+            }))
+        });
+
+        Self { lru }
+    }
+
+    pub(crate) fn get(&self, write_location: &WriteLocation) -> Option<SharedAlignedBuffer> {
+        self.lru.as_ref()?.lock().get(write_location).cloned()
+    }
+
+    pub(crate) fn insert(&self, write_location: WriteLocation, body: SharedAlignedBuffer) {
+        if let Some(lru) = &self.lru {
+            lru.lock().insert(write_location, body);
+        }
+    }
+}
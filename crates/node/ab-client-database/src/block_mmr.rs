@@ -0,0 +1,167 @@
+//! Leaves of the Merkle Mountain Range of canonical block roots, see [`BlockMmrLeaves`].
+
+use crate::page_group::block_mmr::StorageItemBlockMmr;
+use ab_client_api::{BlockMerkleMountainRange, BlockMmrPeaks, BlockMmrProof};
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_merkle_tree::unbalanced::UnbalancedMerkleTree;
+
+/// Maximum number of block roots in a single storage item, such that it fits into a few pages
+const MAX_BLOCK_ROOTS_PER_STORAGE_ITEM: usize = 1024;
+
+/// Error for [`BlockMmrLeaves::add_storage_item()`]
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum BlockMmrLeavesError {
+    /// Block roots do not follow previously added block roots
+    #[error("Expected first block number {expected}, found {actual}")]
+    NotContiguous {
+        /// Expected first block number
+        expected: BlockNumber,
+        /// Actual first block number
+        actual: BlockNumber,
+    },
+    /// Too many leaves
+    #[error("Too many leaves")]
+    TooManyLeaves,
+    /// Peaks don't match block roots
+    #[error("Peaks don't match block roots")]
+    PeaksMismatch,
+}
+
+/// Leaves of the Merkle Mountain Range of canonical block roots, starting with the genesis block.
+///
+/// Roots of blocks are added here when blocks are pruned, such that inclusion proofs can still be
+/// generated for any block of the canonical chain afterward. Roots are persisted in batches before
+/// page groups with pruned blocks are freed, until then pruned blocks are still stored and their
+/// roots are added again after restart.
+// TODO: All leaves are kept in memory (32 bytes per block), proofs could be generated from leaves
+//  stored on disk instead
+#[derive(Debug, Default)]
+pub(crate) struct BlockMmrLeaves {
+    /// Merkle Mountain Range with persisted leaves
+    persisted_mmr: BlockMerkleMountainRange,
+    /// Persisted block roots followed by block roots that are not persisted yet
+    block_roots: Vec<BlockRoot>,
+}
+
+impl BlockMmrLeaves {
+    /// Number of the block whose root is expected to be added next
+    #[inline(always)]
+    pub(crate) fn next_block_number(&self) -> BlockNumber {
+        BlockNumber::from(self.block_roots.len() as u64)
+    }
+
+    /// Whether there are block roots that were not persisted yet
+    #[inline(always)]
+    pub(crate) fn has_pending(&self) -> bool {
+        self.persisted_mmr.num_leaves() < self.block_roots.len() as u64
+    }
+
+    /// Add the root of a pruned block.
+    ///
+    /// Roots of blocks that were already added are ignored. Blocks that don't follow previously
+    /// added blocks are ignored too, which only happens when blocks were pruned before their roots
+    /// were persisted (by older versions of the database).
+    pub(crate) fn add_pruned_block(&mut self, block_number: BlockNumber, block_root: BlockRoot) {
+        if block_number == self.next_block_number() {
+            self.block_roots.push(block_root);
+        }
+    }
+
+    /// Add leaves from a storage item read from the storage, must be called before roots of any
+    /// pruned blocks are added
+    pub(crate) fn add_storage_item(
+        &mut self,
+        storage_item: StorageItemBlockMmr,
+    ) -> Result<(), BlockMmrLeavesError> {
+        let StorageItemBlockMmr {
+            first_block_number,
+            block_roots,
+            peaks,
+        } = storage_item;
+
+        let expected_first_block_number = self.next_block_number();
+        if first_block_number != expected_first_block_number {
+            return Err(BlockMmrLeavesError::NotContiguous {
+                expected: expected_first_block_number,
+                actual: first_block_number,
+            });
+        }
+
+        let mut mmr = self.persisted_mmr;
+        if !mmr.add_leaves(BlockRoot::repr_from_slice(&block_roots).iter().copied()) {
+            return Err(BlockMmrLeavesError::TooManyLeaves);
+        }
+        if mmr.peaks() != peaks {
+            return Err(BlockMmrLeavesError::PeaksMismatch);
+        }
+
+        self.persisted_mmr = mmr;
+        self.block_roots.extend(block_roots);
+
+        Ok(())
+    }
+
+    /// Storage item with the next batch of block roots that were not persisted yet, `None` if all
+    /// block roots are persisted.
+    ///
+    /// Block roots are considered persisted once [`Self::mark_persisted()`] is called.
+    pub(crate) fn pending_storage_item(&self) -> Option<StorageItemBlockMmr> {
+        let first_block_number = self.persisted_mmr.num_leaves();
+        let block_roots = self.block_roots.get(first_block_number as usize..)?;
+        let block_roots = &block_roots[..block_roots.len().min(MAX_BLOCK_ROOTS_PER_STORAGE_ITEM)];
+        if block_roots.is_empty() {
+            return None;
+        }
+
+        let mut mmr = self.persisted_mmr;
+        if !mmr.add_leaves(BlockRoot::repr_from_slice(block_roots).iter().copied()) {
+            return None;
+        }
+
+        Some(StorageItemBlockMmr {
+            first_block_number: BlockNumber::from(first_block_number),
+            block_roots: block_roots.to_vec(),
+            peaks: mmr.peaks(),
+        })
+    }
+
+    /// Mark block roots of a storage item created by [`Self::pending_storage_item()`] as persisted
+    pub(crate) fn mark_persisted(&mut self, peaks: &BlockMmrPeaks) {
+        self.persisted_mmr = BlockMerkleMountainRange::from_peaks(peaks)
+            .expect("Peaks were created from a valid MMR; qed");
+    }
+
+    /// Inclusion proof of the block at `block_number` against the Merkle Mountain Range with all
+    /// added leaves followed by `next_block_roots`.
+    ///
+    /// `next_block_roots` must start at [`Self::next_block_number()`]. Returns `None` if there is
+    /// no block root at `block_number`.
+    pub(crate) fn proof(
+        &self,
+        block_number: BlockNumber,
+        next_block_roots: &[BlockRoot],
+    ) -> Option<BlockMmrProof> {
+        let leaves = BlockRoot::repr_from_slice(&self.block_roots)
+            .iter()
+            .chain(BlockRoot::repr_from_slice(next_block_roots))
+            .copied();
+        let leaf_index = usize::try_from(u64::from(block_number)).ok()?;
+        let block_root = *self
+            .block_roots
+            .iter()
+            .chain(next_block_roots)
+            .nth(leaf_index)?;
+
+        let (mmr_root, proof) = UnbalancedMerkleTree::compute_root_and_proof::<4_294_967_295, _, _>(
+            leaves, leaf_index,
+        )?;
+
+        Some(BlockMmrProof {
+            block_number,
+            block_root,
+            num_leaves: (self.block_roots.len() + next_block_roots.len()) as u64,
+            mmr_root,
+            proof,
+        })
+    }
+}
@@ -0,0 +1,46 @@
+//! Events about changes of the canonical chain and its forks, see
+//! [`ClientDatabase::subscribe_chain_events()`].
+//!
+//! [`ClientDatabase::subscribe_chain_events()`]: crate::ClientDatabase::subscribe_chain_events
+
+use ab_client_notifications::Topic;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+
+/// Event about a change of the canonical chain or its forks
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ChainEvent {
+    /// Best block was updated
+    BestBlockUpdated {
+        /// Root of the previous best block
+        old_best_root: BlockRoot,
+        /// Number of the new best block
+        new_best_number: BlockNumber,
+        /// Root of the new best block
+        new_best_root: BlockRoot,
+        /// Whether the new best block is not a descendant of the previous best block
+        reorg: bool,
+    },
+    /// A block that doesn't become the new best block was added, creating or extending a fork
+    ForkCreated {
+        /// Block number
+        number: BlockNumber,
+        /// Block root
+        root: BlockRoot,
+        /// Root of the parent block
+        parent_root: BlockRoot,
+    },
+    /// Blocks of non-canonical forks were pruned and are no longer known to the database
+    ForksPruned {
+        /// Roots of pruned blocks
+        roots: Vec<BlockRoot>,
+    },
+}
+
+/// Topic of [`ChainEvent`] notifications
+#[derive(Debug)]
+pub struct ChainEventsTopic;
+
+impl Topic for ChainEventsTopic {
+    const NAME: &'static str = "chain-events";
+    type Message = ChainEvent;
+}
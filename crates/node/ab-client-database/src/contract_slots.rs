@@ -0,0 +1,171 @@
+//! Index of contract slots persisted for blocks, see [`ContractSlots`].
+
+use crate::BlockRootHasher;
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::ContractSlotState;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use smallvec::SmallVec;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+
+/// Key of a contract slot
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub(crate) struct ContractSlotKey {
+    /// Owner of the slot
+    pub(crate) owner: Address,
+    /// Contract that manages the slot
+    pub(crate) contract: Address,
+}
+
+/// Contents of a contract slot written by a specific block
+#[derive(Debug, Clone)]
+struct ContractSlotVersion {
+    block_number: BlockNumber,
+    block_root: BlockRoot,
+    contents: SharedAlignedBuffer,
+}
+
+/// Versions of contract slots written by blocks.
+///
+/// Every block only has versions of slots it modified, contents of other slots are inherited from
+/// its ancestors. Versions of blocks that were already pruned from the canonical chain are called
+/// "base" versions, only the latest base version of every slot is retained since anything older is
+/// no longer reachable from any known block.
+#[derive(Debug, Default)]
+pub(crate) struct ContractSlots {
+    /// Versions of every slot, sorted by block number from the newest to the oldest
+    slots: HashMap<ContractSlotKey, SmallVec<[ContractSlotVersion; 2]>>,
+    /// Keys of slots modified by blocks that were not pruned yet
+    blocks: HashMap<
+        BlockRoot,
+        (BlockNumber, Vec<ContractSlotKey>),
+        BuildHasherDefault<BlockRootHasher>,
+    >,
+}
+
+impl ContractSlots {
+    /// Insert slots modified by a block, replacing previously inserted contents of the same slots
+    /// in the same block
+    pub(crate) fn insert(
+        &mut self,
+        block_number: BlockNumber,
+        block_root: BlockRoot,
+        slots: &[ContractSlotState],
+    ) {
+        let (_block_number, block_keys) = self
+            .blocks
+            .entry(block_root)
+            .or_insert_with(|| (block_number, Vec::new()));
+
+        for slot in slots {
+            let key = ContractSlotKey {
+                owner: slot.owner,
+                contract: slot.contract,
+            };
+            let versions = self.slots.entry(key).or_default();
+
+            if let Some(version) = versions
+                .iter_mut()
+                .find(|version| version.block_root == block_root)
+            {
+                version.contents = slot.contents.clone();
+                continue;
+            }
+
+            let position = versions
+                .iter()
+                .position(|version| version.block_number <= block_number)
+                .unwrap_or(versions.len());
+            versions.insert(
+                position,
+                ContractSlotVersion {
+                    block_number,
+                    block_root,
+                    contents: slot.contents.clone(),
+                },
+            );
+            block_keys.push(key);
+        }
+    }
+
+    /// Contents of a slot as of block at `block_number`.
+    ///
+    /// `is_ancestor` is called with block number and root of versions of blocks that were not
+    /// pruned yet and must return `true` if it is the block itself or its ancestor. Base versions
+    /// are inherited by all known blocks.
+    pub(crate) fn get<F>(
+        &self,
+        key: &ContractSlotKey,
+        block_number: BlockNumber,
+        mut is_ancestor: F,
+    ) -> Option<SharedAlignedBuffer>
+    where
+        F: FnMut(BlockNumber, &BlockRoot) -> bool,
+    {
+        self.slots
+            .get(key)?
+            .iter()
+            .filter(|version| version.block_number <= block_number)
+            .find(|version| {
+                !self.blocks.contains_key(&version.block_root)
+                    || is_ancestor(version.block_number, &version.block_root)
+            })
+            .map(|version| version.contents.clone())
+    }
+
+    /// Block numbers and roots of all blocks with versions that were not pruned yet
+    pub(crate) fn block_roots(&self) -> impl Iterator<Item = (BlockNumber, BlockRoot)> {
+        self.blocks
+            .iter()
+            .map(|(block_root, (block_number, _keys))| (*block_number, *block_root))
+    }
+
+    /// Remove versions of a block that will never become canonical.
+    ///
+    /// Returns `true` if the block had any versions.
+    pub(crate) fn discard_block(&mut self, block_root: &BlockRoot) -> bool {
+        let Some((_block_number, keys)) = self.blocks.remove(block_root) else {
+            return false;
+        };
+
+        for key in keys {
+            if let Some(versions) = self.slots.get_mut(&key) {
+                versions.retain(|version| version.block_root != *block_root);
+                if versions.is_empty() {
+                    self.slots.remove(&key);
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Turn versions of a block that was pruned from the canonical chain into base versions,
+    /// removing older versions of the same slots that are superseded by them
+    pub(crate) fn prune_block(&mut self, block_root: &BlockRoot) {
+        let Some((_block_number, keys)) = self.blocks.remove(block_root) else {
+            return;
+        };
+
+        for key in keys {
+            let Some(versions) = self.slots.get_mut(&key) else {
+                continue;
+            };
+            let Some(position) = versions
+                .iter()
+                .position(|version| version.block_root == *block_root)
+            else {
+                continue;
+            };
+
+            for superseded_version in versions.drain(position + 1..) {
+                if let Some((_block_number, block_keys)) =
+                    self.blocks.get_mut(&superseded_version.block_root)
+                {
+                    block_keys.retain(|block_key| *block_key != key);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Fork choice rules used by [`ClientDatabase`] to decide which block is the best.
+//!
+//! [`ClientDatabase`]: crate::ClientDatabase
+
+use ab_core_primitives::block::header::SharedBlockHeader;
+use std::fmt;
+
+/// Fork choice rule.
+///
+/// The best block is always the highest known block, so the database only supports new best
+/// blocks that either directly extend the current best block or are at the same height as the
+/// current best block (which results in a reorg). Returning `true` for a candidate below the
+/// current best block or `false` for a candidate that directly extends the best block will result
+/// in [`PersistBlockError::UnsupportedForkChoice`] error.
+///
+/// As a consequence, rules that may prefer a shorter fork over the current best block (like the
+/// heaviest chain rule, where a lower block with more cumulative weight wins) can't be expressed
+/// yet. Such rules can only be used to choose between blocks at the same height, for example as a
+/// tie-breaker for the longest chain rule.
+///
+/// [`PersistBlockError::UnsupportedForkChoice`]: ab_client_api::PersistBlockError::UnsupportedForkChoice
+pub trait ForkChoice: fmt::Debug + Send + Sync {
+    /// Returns `true` if the `candidate` block should become the new best block instead of the
+    /// current `best` block
+    fn is_new_best(&self, best: &SharedBlockHeader<'_>, candidate: &SharedBlockHeader<'_>) -> bool;
+}
+
+/// Longest chain fork choice rule.
+///
+/// A block with a larger block number becomes the new best block, the first seen block wins among
+/// blocks with the same block number.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LongestChainForkChoice;
+
+impl ForkChoice for LongestChainForkChoice {
+    #[inline]
+    fn is_new_best(&self, best: &SharedBlockHeader<'_>, candidate: &SharedBlockHeader<'_>) -> bool {
+        candidate.prefix.number > best.prefix.number
+    }
+}
@@ -0,0 +1,73 @@
+//! Coalescing of concurrent reads of the same persisted block, see [`InFlightReads`].
+
+use crate::storage_backend_adapter::WriteLocation;
+use async_lock::OnceCell;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Reads of persisted blocks that are currently in progress.
+///
+/// When the same block is requested concurrently, only the first caller reads and decodes it,
+/// while others wait for and share the result. If the read fails, the next waiting caller tries
+/// again, so errors are never shared.
+#[derive(Debug)]
+pub(crate) struct InFlightReads<T> {
+    reads: Mutex<HashMap<WriteLocation, Arc<OnceCell<T>>>>,
+}
+
+impl<T> Default for InFlightReads<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            reads: Mutex::default(),
+        }
+    }
+}
+
+impl<T> InFlightReads<T>
+where
+    T: Clone,
+{
+    /// Read the value at `write_location` with `read` unless the same read is already in progress,
+    /// in which case its result is used instead
+    pub(crate) async fn read<F, Fut, E>(
+        &self,
+        write_location: WriteLocation,
+        read: F,
+    ) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let read_cell = Arc::clone(self.reads.lock().entry(write_location).or_default());
+        let _guard = InFlightReadGuard {
+            reads: &self.reads,
+            write_location,
+            read_cell: &read_cell,
+        };
+
+        read_cell.get_or_try_init(read).await.cloned()
+    }
+}
+
+/// Removes the read from the map once the last caller interested in it is done
+struct InFlightReadGuard<'a, T> {
+    reads: &'a Mutex<HashMap<WriteLocation, Arc<OnceCell<T>>>>,
+    write_location: WriteLocation,
+    read_cell: &'a Arc<OnceCell<T>>,
+}
+
+impl<T> Drop for InFlightReadGuard<'_, T> {
+    fn drop(&mut self) {
+        let mut reads = self.reads.lock();
+
+        if let Some(existing_read_cell) = reads.get(&self.write_location)
+            && Arc::ptr_eq(existing_read_cell, self.read_cell)
+            // One reference is in the map and another one is held by this caller
+            && Arc::strong_count(self.read_cell) == 2
+        {
+            reads.remove(&self.write_location);
+        }
+    }
+}
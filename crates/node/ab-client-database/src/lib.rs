@@ -24,7 +24,11 @@
 //! complete storage item, after which the next page group is started.
 //!
 //! Ephemeral page groups can be freed only when they contain 100% outdated storage items.
-//! Individual pages can't be freed.
+//! Individual pages can't be freed. To avoid a few long-lived storage items holding otherwise
+//! unused page groups, compaction (see [`ClientDatabase::compact()`]) copies live storage items out
+//! of mostly outdated page groups, after which those page groups can be freed. As a result,
+//! storage items in ephemeral page groups are not necessarily ordered by the time they were
+//! originally written.
 //!
 //! Each storage item has a sequence number and checksums that help to define the global ordering
 //! and check whether a storage item was written fully. Upon restart, the page group containing the
@@ -54,23 +58,60 @@
     maybe_uninit_fill
 )]
 
+pub mod backup;
+mod block_body_cache;
+mod block_mmr;
+pub mod chain_events;
+mod contract_slots;
+pub mod fork_choice;
+mod in_flight_reads;
+mod metrics;
 mod page_group;
+pub mod pruning_holds;
+pub mod stats;
 pub mod storage_backend;
 mod storage_backend_adapter;
-
+mod transaction_index;
+pub mod verification;
+
+use crate::backup::{BackupError, BackupReader, BackupWriter, max_headers_per_record};
+use crate::block_body_cache::BlockBodyCache;
+use crate::block_mmr::BlockMmrLeaves;
+use crate::chain_events::{ChainEvent, ChainEventsTopic};
+use crate::contract_slots::{ContractSlotKey, ContractSlots};
+use crate::fork_choice::{ForkChoice, LongestChainForkChoice};
+use crate::in_flight_reads::InFlightReads;
+use crate::metrics::ClientDatabaseMetrics;
+use crate::page_group::permanent::StorageItemPermanent;
+use crate::page_group::segment_headers::StorageItemSegmentHeaders;
+use crate::page_group::state::StorageItemState;
+use crate::page_group::state::contract_slots::StorageItemStateContractSlots;
+use crate::page_group::state::discarded_contract_slots::StorageItemStateDiscardedContractSlots;
 use crate::page_group::temporary::StorageItemTemporary;
 use crate::page_group::temporary::block::StorageItemTemporaryBlock;
-use crate::page_group::temporary::segment_headers::StorageItemTemporarySegmentHeaders;
+use crate::page_group::temporary::block_aux_data::StorageItemTemporaryBlockAuxData;
+use crate::page_group::temporary::block_outcome::StorageItemTemporaryBlockOutcome;
 use crate::page_group::temporary::super_segment_headers::StorageItemTemporarySuperSegmentHeaders;
+use crate::pruning_holds::{PruningHold, PruningHoldInfo, PruningHoldTarget, PruningHolds};
+use crate::stats::ClientDatabaseStats;
 use crate::storage_backend::ClientDatabaseStorageBackend;
 use crate::storage_backend_adapter::{
-    StorageBackendAdapter, StorageItemHandlerArg, StorageItemHandlers, WriteLocation,
+    InactivePageGroup, StorageBackendAdapter, StorageItemHandlerArg, StorageItemHandlers,
+    WriteLocation,
 };
+use crate::transaction_index::TransactionIndex;
+use crate::verification::{VerificationIssue, VerificationReport};
+use ab_aligned_buffer::SharedAlignedBuffer;
 use ab_client_api::{
-    BeaconChainInfo, BeaconChainInfoWrite, BlockDetails, BlockMerkleMountainRange, ChainInfo,
-    ChainInfoWrite, ContractSlotState, PersistBlockError, PersistSegmentHeadersError,
-    PersistSuperSegmentHeadersError, ReadBlockError, ShardSegmentRoot, ShardSegmentRootsError,
+    BeaconChainInfo, BeaconChainInfoWrite, BlockAuxDataNamespace, BlockDetails,
+    BlockMerkleMountainRange, BlockMmrProof, BlockOutcome, ChainInfo, ChainInfoWrite,
+    ContractSlotState, MAX_BLOCK_AUX_DATA_SIZE, PersistBlockAuxDataError, PersistBlockError,
+    PersistBlockOutcomeError, PersistContractSlotsError, PersistSegmentHeadersError,
+    PersistSuperSegmentHeadersError, ReadBlockError, ReadContractSlotError, ReadMmrProofError,
+    ShardSegmentRoot, ShardSegmentRootsError, StorageItemCorruptionError, TransactionLocation,
 };
+use ab_client_notifications::{BufferingPolicy, NotificationBus, Subscription};
+use ab_core_primitives::address::Address;
 use ab_core_primitives::block::body::BeaconChainBody;
 use ab_core_primitives::block::body::owned::{GenericOwnedBlockBody, OwnedBeaconChainBody};
 use ab_core_primitives::block::header::GenericBlockHeader;
@@ -81,22 +122,36 @@ use ab_core_primitives::segments::{
     LocalSegmentIndex, SegmentHeader, SegmentIndex, SuperSegmentHeader, SuperSegmentIndex,
 };
 use ab_core_primitives::shard::RealShardKind;
+use ab_core_primitives::transaction::TransactionHash;
 use ab_io_type::trivial_type::TrivialType;
 use async_lock::{
     RwLock as AsyncRwLock, RwLockUpgradableReadGuard, RwLockWriteGuard as AsyncRwLockWriteGuard,
 };
+use futures::io::{AsyncRead, AsyncWrite};
+use futures_timer::Delay;
+use parking_lot::Mutex;
+use prometheus_client::registry::Registry;
 use rand::rngs::SysError;
 use rclite::Arc;
 use replace_with::replace_with_or_abort;
 use smallvec::{SmallVec, smallvec};
 use std::any::Any;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::hash::{BuildHasherDefault, Hasher};
 use std::num::{NonZeroU32, NonZeroUsize};
-use std::ops::Deref;
+use std::ops::{Deref, RangeInclusive};
 use std::sync::Arc as StdArc;
-use std::{fmt, io};
-use tracing::error;
+use std::time::Duration;
+use std::{fmt, io, iter};
+use strum::FromRepr;
+use tracing::{debug, error, warn};
+
+/// Version of the database format written by this version of the code, databases of other versions
+/// are rejected with [`ClientDatabaseError::UnsupportedDatabaseVersion`]
+pub const DATABASE_VERSION: u8 = 1;
+
+/// Owner of the pruning hold placed by [`ChainInfoWrite::retain_blocks_for_archiving()`]
+const ARCHIVING_PRUNING_HOLD_OWNER: &str = "archiver";
 
 /// Unique identifier for a database
 #[derive(Debug, Copy, Clone, Eq, PartialEq, TrivialType)]
@@ -153,8 +208,100 @@ pub struct GenesisBlockBuilderResult<Block> {
     pub system_contract_states: StdArc<[ContractSlotState]>,
 }
 
-/// Options for [`ClientDatabase`]
+/// Durability policy of [`ClientDatabase`], determines when written data is flushed to durable
+/// storage (see [`ClientDatabaseStorageBackend::sync()`]).
+///
+/// Regardless of the policy, [`ClientDatabase::flush()`] can be called explicitly at any time.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DurabilityPolicy {
+    /// Flush after every written storage item.
+    ///
+    /// Nothing is lost on crash or power loss, but every write waits for the flush, increasing
+    /// write latency.
+    EveryItem,
+    /// Flush periodically with the specified interval, see
+    /// [`ClientDatabase::run_periodic_flush()`].
+    ///
+    /// Up to `interval` worth of writes might be lost on crash or power loss.
+    Periodic {
+        /// Interval between flushes
+        interval: Duration,
+    },
+    /// Flush when a block is confirmed (see [`ClientDatabaseOptions::block_confirmation_depth`]).
+    ///
+    /// Everything written before the last confirmed block survives crash or power loss, more recent
+    /// writes might be lost.
+    OnConfirmation,
+}
+
+/// Options for compaction of temporary page groups, see [`ClientDatabase::compact()`]
+#[derive(Debug, Copy, Clone)]
+pub struct CompactionOptions {
+    /// Interval between compactions in [`ClientDatabase::run_compaction()`].
+    ///
+    /// The default is 10 minutes.
+    pub interval: Duration = Duration::from_mins(10),
+    /// Page group is only compacted when its live storage items occupy at most this percentage of
+    /// used pages.
+    ///
+    /// A smaller value reduces write amplification, a larger value reclaims space more
+    /// aggressively. The default is 25%.
+    pub max_live_percentage: u8 = 25,
+    /// Max number of pages read and written by compaction per second, limits the impact of
+    /// compaction on other operations.
+    ///
+    /// The default is 2048 pages (8 MiB) per second.
+    pub max_pages_per_second: NonZeroU32 = NonZeroU32::new(2048).expect("Not zero; qed"),
+}
+
+impl CompactionOptions {
+    /// Wait long enough to not exceed [`Self::max_pages_per_second`] after processing
+    /// `num_pages` pages
+    async fn throttle(&self, num_pages: u32) {
+        let delay = Duration::from_secs(1) * num_pages / self.max_pages_per_second.get();
+
+        if !delay.is_zero() {
+            Delay::new(delay).await;
+        }
+    }
+}
+
+/// Options for reclamation of storage used by blocks that are no longer necessary, see
+/// [`ClientDatabase::reclaim()`]
+#[derive(Debug, Copy, Clone)]
+pub struct ReclamationOptions {
+    /// Number of blocks below the best block to retain, older confirmed blocks are pruned and
+    /// page groups they were stored in are eventually reclaimed for new writes.
+    ///
+    /// Blocks covered by pruning holds (see [`ClientDatabase::hold_block_range()`]), including
+    /// blocks that archiver still needs (see [`ChainInfoWrite::retain_blocks_for_archiving()`]),
+    /// are retained along with all blocks above them. The value must be larger than
+    /// [`ClientDatabaseOptions::block_confirmation_depth`].
+    ///
+    /// The default is `None`, which retains all blocks.
+    pub retained_blocks: Option<BlockNumber> = None,
+    /// Interval between reclamation passes in [`ClientDatabase::run_reclamation()`].
+    ///
+    /// The default is 1 minute.
+    pub interval: Duration = Duration::from_mins(1),
+}
+
+/// Options for the cache of bodies of persisted blocks, see
+/// [`ClientDatabaseOptions::block_body_cache`]
 #[derive(Debug, Copy, Clone)]
+pub struct BlockBodyCacheOptions {
+    /// Max number of cached block bodies, zero disables the cache.
+    ///
+    /// The default is 0 (disabled).
+    pub max_blocks: usize = 0,
+    /// Max total size of cached block bodies in bytes, bodies larger than this are not cached.
+    ///
+    /// The default is 32 MiB.
+    pub max_bytes: usize = 32 * 1024 * 1024,
+}
+
+/// Options for [`ClientDatabase`]
+#[derive(Debug, Clone)]
 pub struct ClientDatabaseOptions<GBB, StorageBackend> {
     /// Write buffer size.
     ///
@@ -164,6 +311,15 @@ pub struct ClientDatabaseOptions<GBB, StorageBackend> {
     ///
     /// The recommended value is 5.
     pub write_buffer_size: usize = 5,
+    /// Max number of pages of consecutive storage items coalesced into a single write.
+    ///
+    /// Storage items are typically small, and writing each of them separately results in many
+    /// small writes. With coalescing, consecutive storage items are accumulated in memory (and
+    /// served from there for reads) until the limit is reached, a non-consecutive storage item is
+    /// written, blocks are soft-confirmed or data is flushed.
+    ///
+    /// The default is 0 (disabled).
+    pub max_coalesced_write_pages: u32 = 0,
     /// Blocks at this depth are considered to be "confirmed" and irreversible from the consensus
     /// perspective.
     ///
@@ -206,6 +362,41 @@ pub struct ClientDatabaseOptions<GBB, StorageBackend> {
     ///
     /// The recommended value is 5 blocks.
     pub max_fork_tip_distance: BlockNumber = BlockNumber::from(5),
+    /// Fork choice rule that decides which block is the best.
+    ///
+    /// [`LongestChainForkChoice`] is used when `None` (default).
+    pub fork_choice: Option<StdArc<dyn ForkChoice>> = None,
+    /// Durability policy that determines when written data is flushed to durable storage.
+    ///
+    /// The default is [`DurabilityPolicy::OnConfirmation`].
+    pub durability_policy: DurabilityPolicy = DurabilityPolicy::OnConfirmation,
+    /// Options for compaction of temporary page groups, see [`ClientDatabase::compact()`]
+    pub compaction: CompactionOptions = CompactionOptions { .. },
+    /// Options for reclamation of storage used by blocks that are no longer necessary, see
+    /// [`ClientDatabase::reclaim()`]
+    pub reclamation: ReclamationOptions = ReclamationOptions { .. },
+    /// Maintain a compact bloom filter of block roots for every temporary page group and persist
+    /// it once the page group is filled.
+    ///
+    /// This allows [`ClientDatabase::find_stored_block()`] to skip reading page groups that
+    /// definitely don't contain a block, at the cost of a small storage item per page group.
+    ///
+    /// Disabled by default.
+    pub block_roots_filters: bool = false,
+    /// LRU cache of bodies of persisted blocks.
+    ///
+    /// Sync and archiving often request the same recent blocks repeatedly, the cache allows
+    /// [`ChainInfo::block()`] to avoid reading them from the storage backend every time.
+    ///
+    /// Disabled by default.
+    pub block_body_cache: BlockBodyCacheOptions = BlockBodyCacheOptions { .. },
+    /// Maintain an in-memory index of transactions in bodies of known blocks.
+    ///
+    /// This allows [`ChainInfo::find_transaction()`] to locate transactions without scanning block
+    /// bodies. The index is rebuilt from stored blocks when the database is opened.
+    ///
+    /// Disabled by default.
+    pub transaction_index: bool = false,
     /// Genesis block builder is responsible to create genesis block and corresponding state for
     /// bootstrapping purposes.
     pub genesis_block_builder: GBB,
@@ -214,7 +405,7 @@ pub struct ClientDatabaseOptions<GBB, StorageBackend> {
 }
 
 /// Options for [`ClientDatabase`]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct ClientDatabaseFormatOptions {
     /// The number of [`AlignedPage`]s in a single page group.
     ///
@@ -237,6 +428,50 @@ pub struct ClientDatabaseFormatOptions {
     ///
     /// Setting this option to `true` skips the check and formats the database anyway.
     pub force: bool,
+    /// Segment headers that are known ahead of time, typically from the chain spec of a network
+    /// that was bootstrapped from an existing history snapshot.
+    ///
+    /// Must start with segment index zero and have no gaps. These are written to permanent storage
+    /// and are available right after the database is opened, even before any blocks are archived.
+    pub known_segment_headers: Vec<SegmentHeader>,
+    /// Compression of block storage items.
+    ///
+    /// Stored in page group headers during formatting and can't be changed afterward. Blocks
+    /// written with a different compression are still readable, so this doesn't affect
+    /// compatibility with existing databases.
+    pub block_compression: BlockCompressionOptions = BlockCompressionOptions { .. },
+}
+
+/// Compression algorithm for block storage items, see [`BlockCompressionOptions`]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, FromRepr)]
+#[repr(u8)]
+pub enum BlockCompression {
+    /// Blocks are stored as is
+    #[default]
+    None = 0,
+    /// LZ4, very fast with a moderate compression ratio
+    Lz4 = 1,
+    /// Zstd, slower than LZ4, but with a better compression ratio
+    Zstd = 2,
+}
+
+/// Compression of block storage items by page group kind, see
+/// [`ClientDatabaseFormatOptions::block_compression`].
+///
+/// Only block payloads are compressed, storage item containers and page group headers are always
+/// stored uncompressed, such that the database can be scanned and recovered without decompressing
+/// anything.
+#[derive(Debug, Copy, Clone)]
+pub struct BlockCompressionOptions {
+    /// Compression of blocks stored in permanent page groups.
+    ///
+    /// Blocks are not stored in permanent page groups yet, so this has no effect at the moment.
+    /// The default is no compression.
+    pub permanent: BlockCompression = BlockCompression::None,
+    /// Compression of blocks stored in temporary page groups.
+    ///
+    /// The default is no compression.
+    pub temporary: BlockCompression = BlockCompression::None,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -247,6 +482,9 @@ pub enum ClientDatabaseError {
     /// Invalid max fork tip distance, it must be smaller or equal to confirmation depth k
     #[error("Invalid max fork tip distance, it must be smaller or equal to confirmation depth k")]
     InvalidMaxForkTipDistance,
+    /// Invalid number of retained blocks, it must be larger than confirmation depth k
+    #[error("Invalid number of retained blocks, it must be larger than confirmation depth k")]
+    InvalidRetainedBlocks,
     /// Storage backend has canceled read request
     #[error("Storage backend has canceled read request")]
     ReadRequestCancelled,
@@ -301,6 +539,12 @@ pub enum ClientDatabaseError {
         /// Page offset where storage item is found
         page_offset: u32,
     },
+    /// Invalid block MMR
+    #[error("Invalid block MMR at offset {page_offset}")]
+    InvalidBlockMmr {
+        /// Page offset where storage item is found
+        page_offset: u32,
+    },
     /// Failed to adjust ancestor block forks
     #[error("Failed to adjust ancestor block forks")]
     FailedToAdjustAncestorBlockForks,
@@ -344,6 +588,18 @@ pub enum ClientDatabaseFormatError {
         #[from]
         error: io::Error,
     },
+    /// Invalid known segment headers
+    #[error("Invalid known segment headers: {error}")]
+    InvalidKnownSegmentHeaders {
+        /// Low-level error
+        error: PersistSegmentHeadersError,
+    },
+    /// Known segment headers do not fit into a single page group
+    #[error("Known segment headers do not fit into a single page group ({num_pages} pages)")]
+    KnownSegmentHeadersTooLarge {
+        /// Number of pages known segment headers occupy
+        num_pages: u32,
+    },
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -426,6 +682,7 @@ where
 {
     /// Block is stored in memory and wasn't persisted yet
     InMemory {
+        fork_ordinal: u32,
         block: Block,
         block_details: BlockDetails,
         /// Only present for beacon chain blocks
@@ -433,6 +690,7 @@ where
     },
     /// Block was persisted (likely on disk)
     Persisted {
+        fork_ordinal: u32,
         header: Block::Header,
         block_details: BlockDetails,
         /// Only present for beacon chain blocks
@@ -442,6 +700,7 @@ where
     /// Block was persisted (likely on disk) and is irreversibly "confirmed" from the consensus
     /// perspective
     PersistedConfirmed {
+        fork_ordinal: u32,
         header: Block::Header,
         /// Only present for beacon chain blocks
         beacon_chain_block_details: Option<BeaconChainBlockDetails>,
@@ -461,6 +720,15 @@ where
         }
     }
 
+    #[inline(always)]
+    fn fork_ordinal(&self) -> u32 {
+        match self {
+            Self::InMemory { fork_ordinal, .. }
+            | Self::Persisted { fork_ordinal, .. }
+            | Self::PersistedConfirmed { fork_ordinal, .. } => *fork_ordinal,
+        }
+    }
+
     #[inline(always)]
     fn full_block(&self) -> FullBlock<'_, Block> {
         match self {
@@ -510,6 +778,9 @@ where
     }
 }
 
+/// Auxiliary data attached to a single block
+type BlockAuxDataEntries = SmallVec<[(BlockAuxDataNamespace, SharedAlignedBuffer); 1]>;
+
 #[derive(Debug)]
 struct StateData<Block>
 where
@@ -537,14 +808,109 @@ where
     /// A position withing this data structure is called "block offset". This is an ephemeral value
     /// and changes as new best blocks are added. Blocks at the same height are collectively called
     /// "block forks" and the position of the block within the same block height is called
-    /// "fork offset". While fork offset `0` always corresponds to the canonical version of the
-    /// blockchain, other offsets are not guaranteed to follow any particular ordering rules.
+    /// "fork offset". Fork offset `0` always corresponds to the canonical version of the
+    /// blockchain, other forks are sorted by their fork ordinal (see [`BlockPosition`]).
     blocks: VecDeque<SmallVec<[ClientDatabaseBlock<Block>; 2]>>,
+    /// Fork ordinal to be assigned to the next block at each block number that is not confirmed
+    /// yet, see [`BlockPosition`]
+    next_fork_ordinals: BTreeMap<BlockNumber, u32>,
+    /// Auxiliary data attached to blocks, pruned together with corresponding blocks
+    block_aux_data: HashMap<BlockRoot, BlockAuxDataEntries, BuildHasherDefault<BlockRootHasher>>,
+    /// Execution outcomes of blocks, pruned together with corresponding blocks
+    block_outcomes: HashMap<BlockRoot, BlockOutcome, BuildHasherDefault<BlockRootHasher>>,
+    /// Index of transactions in block bodies (if enabled), pruned together with corresponding
+    /// blocks
+    transaction_index: Option<TransactionIndex>,
+    /// Contract slots modified by blocks, pruned together with corresponding blocks
+    contract_slots: ContractSlots,
+    /// Blocks with discarded contract slots that are not yet marked as such in the storage
+    discarded_contract_slots: Vec<BlockRoot>,
+    /// Leaves of the block MMR with roots of pruned blocks of the canonical chain
+    block_mmr_leaves: BlockMmrLeaves,
+    /// Generation of the canonical chain.
+    ///
+    /// Bumped every time the best block changes, see [`ClientDatabaseSnapshot`].
+    generation: u64,
+    /// Headers of canonical blocks with the best block at the front.
+    ///
+    /// Headers are stored behind a shared pointer, such that snapshots can cheaply hold a
+    /// consistent view, while updates only copy the list when there is a snapshot still holding
+    /// it.
+    canonical_headers: StdArc<VecDeque<Block::Header>>,
 }
 
-#[derive(Debug)]
+impl<Block> StateData<Block>
+where
+    Block: GenericOwnedBlock,
+{
+    /// Update canonical headers after the canonical chain has changed or old blocks were pruned.
+    ///
+    /// Only headers of blocks that are different from those already known are updated.
+    fn update_canonical_headers(&mut self) {
+        let canonical_blocks = self
+            .blocks
+            .iter()
+            .map_while(|block_forks| block_forks.first());
+        let known_best_number = self
+            .canonical_headers
+            .front()
+            .map(|header| header.header().prefix.number);
+
+        // Number of blocks at the tip of the canonical chain that are not known yet, everything
+        // below the first known block is known as well since it is its ancestor
+        let mut num_new_blocks = 0;
+        let mut known_number = None;
+        for block in canonical_blocks.clone() {
+            let header = block.header().header();
+            let block_number = header.prefix.number;
+
+            let known = known_best_number
+                .and_then(|known_best_number| known_best_number.checked_sub(block_number))
+                .and_then(|block_offset| {
+                    self.canonical_headers.get(u64::from(block_offset) as usize)
+                })
+                .is_some_and(|known_header| *known_header.header().root() == *header.root());
+            if known {
+                known_number.replace(block_number);
+                break;
+            }
+
+            num_new_blocks += 1;
+        }
+        let num_canonical_blocks = canonical_blocks.count();
+
+        if num_new_blocks == 0
+            && known_best_number == known_number
+            && self.canonical_headers.len() == num_canonical_blocks
+        {
+            return;
+        }
+
+        let canonical_headers = StdArc::make_mut(&mut self.canonical_headers);
+        // Remove headers of blocks that are no longer canonical
+        while let Some(header) = canonical_headers.front()
+            && known_number.is_none_or(|known_number| header.header().prefix.number > known_number)
+        {
+            canonical_headers.pop_front();
+        }
+        for block_forks in self.blocks.range(..num_new_blocks).rev() {
+            let block = block_forks
+                .first()
+                .expect("Only counted blocks that are present; qed");
+            canonical_headers.push_front(block.header().clone());
+        }
+        // Remove headers of pruned blocks
+        canonical_headers.truncate(num_canonical_blocks);
+    }
+}
+
+/// Append-only cache of segment headers.
+///
+/// Headers are stored behind a shared pointer, such that snapshots can cheaply hold a consistent
+/// view, while appending only copies the list when there is a snapshot still holding it.
+#[derive(Debug, Clone)]
 struct SegmentHeadersCache {
-    segment_headers_cache: Vec<SegmentHeader>,
+    segment_headers_cache: StdArc<Vec<SegmentHeader>>,
 }
 
 impl SegmentHeadersCache {
@@ -572,16 +938,35 @@ impl SegmentHeadersCache {
         &mut self,
         mut segment_headers: Vec<SegmentHeader>,
     ) -> Result<Vec<SegmentHeader>, PersistSegmentHeadersError> {
-        self.segment_headers_cache.reserve(segment_headers.len());
-
         let mut maybe_last_local_segment_index = self.max_local_segment_index();
 
         if let Some(last_segment_index) = maybe_last_local_segment_index {
+            // Re-inserting already stored segment headers is fine, but their contents must never
+            // change since that would rewrite history
+            for segment_header in &segment_headers {
+                let local_segment_index = segment_header.index.as_inner();
+
+                if let Some(stored_segment_header) = self.get_segment_header(local_segment_index)
+                    && stored_segment_header != *segment_header
+                {
+                    return Err(PersistSegmentHeadersError::SegmentHeaderMismatch {
+                        local_segment_index,
+                    });
+                }
+            }
+
             // Skip already stored segment headers
             segment_headers
                 .retain(|segment_header| segment_header.index.as_inner() > last_segment_index);
         }
 
+        if segment_headers.is_empty() {
+            return Ok(segment_headers);
+        }
+
+        let segment_headers_cache = StdArc::make_mut(&mut self.segment_headers_cache);
+        segment_headers_cache.reserve(segment_headers.len());
+
         // Check all input segment headers to see which ones are not stored yet and verifying that
         // segment indices are monotonically increasing
         for segment_header in segment_headers.iter().copied() {
@@ -594,7 +979,7 @@ impl SegmentHeadersCache {
                     });
                 }
 
-                self.segment_headers_cache.push(segment_header);
+                segment_headers_cache.push(segment_header);
                 maybe_last_local_segment_index.replace(local_segment_index);
             } else {
                 if local_segment_index != LocalSegmentIndex::ZERO {
@@ -603,7 +988,7 @@ impl SegmentHeadersCache {
                     });
                 }
 
-                self.segment_headers_cache.push(segment_header);
+                segment_headers_cache.push(segment_header);
                 maybe_last_local_segment_index.replace(local_segment_index);
             }
         }
@@ -612,9 +997,13 @@ impl SegmentHeadersCache {
     }
 }
 
-#[derive(Debug)]
+/// Append-only cache of super segment headers.
+///
+/// Similarly to [`SegmentHeadersCache`], headers are stored behind a shared pointer to make
+/// snapshots cheap.
+#[derive(Debug, Clone)]
 struct SuperSegmentHeadersCache {
-    super_segment_headers_cache: Vec<SuperSegmentHeader>,
+    super_segment_headers_cache: StdArc<Vec<SuperSegmentHeader>>,
 }
 
 impl SuperSegmentHeadersCache {
@@ -683,9 +1072,6 @@ impl SuperSegmentHeadersCache {
         &mut self,
         mut super_segment_headers: Vec<SuperSegmentHeader>,
     ) -> Result<Vec<SuperSegmentHeader>, PersistSuperSegmentHeadersError> {
-        self.super_segment_headers_cache
-            .reserve(super_segment_headers.len());
-
         let mut maybe_last_super_segment_index = self
             .super_segment_headers_cache
             .last()
@@ -698,6 +1084,13 @@ impl SuperSegmentHeadersCache {
             });
         }
 
+        if super_segment_headers.is_empty() {
+            return Ok(super_segment_headers);
+        }
+
+        let super_segment_headers_cache = StdArc::make_mut(&mut self.super_segment_headers_cache);
+        super_segment_headers_cache.reserve(super_segment_headers.len());
+
         // Check all input super segment headers to see which ones are not stored yet and verifying
         // that super segment indices are monotonically increasing
         for super_segment_header in super_segment_headers.iter().copied() {
@@ -712,7 +1105,7 @@ impl SuperSegmentHeadersCache {
                     );
                 }
 
-                self.super_segment_headers_cache.push(super_segment_header);
+                super_segment_headers_cache.push(super_segment_header);
                 maybe_last_super_segment_index.replace(super_segment_index);
             } else {
                 if super_segment_index != SuperSegmentIndex::ZERO {
@@ -721,7 +1114,7 @@ impl SuperSegmentHeadersCache {
                     });
                 }
 
-                self.super_segment_headers_cache.push(super_segment_header);
+                super_segment_headers_cache.push(super_segment_header);
                 maybe_last_super_segment_index.replace(super_segment_index);
             }
         }
@@ -763,6 +1156,66 @@ where
             .first()
             .expect("The best block is always present; qed")
     }
+
+    /// Contents of a contract slot as of the block with `block_root`
+    fn contract_slot(
+        &self,
+        block_root: &BlockRoot,
+        key: &ContractSlotKey,
+    ) -> Result<Option<SharedAlignedBuffer>, ReadContractSlotError> {
+        let block_number = *self
+            .data
+            .block_roots
+            .get(block_root)
+            .ok_or(ReadContractSlotError::UnknownBlockRoot)?;
+        let best_number = self.best_tip().number;
+        let blocks = &self.data.blocks;
+        let block_forks = |block_number: BlockNumber| {
+            let block_offset = u64::from(best_number.checked_sub(block_number)?) as usize;
+            blocks.get(block_offset)
+        };
+
+        // Ancestors of the block are only walked as far as necessary, versions are checked from
+        // the newest to the oldest
+        let mut ancestor_number = block_number;
+        let mut ancestor_root = *block_root;
+        let is_ancestor = |version_block_number: BlockNumber, version_block_root: &BlockRoot| {
+            while ancestor_number >= version_block_number {
+                let Some(ancestor_block_forks) = block_forks(ancestor_number) else {
+                    return false;
+                };
+
+                // Once the canonical chain is reached, the rest of ancestors are canonical blocks
+                if ancestor_block_forks
+                    .first()
+                    .is_some_and(|block| *block.header().header().root() == ancestor_root)
+                {
+                    return block_forks(version_block_number)
+                        .and_then(|block_forks| block_forks.first())
+                        .is_some_and(|block| {
+                            &*block.header().header().root() == version_block_root
+                        });
+                }
+
+                if ancestor_number == version_block_number {
+                    return ancestor_root == *version_block_root;
+                }
+
+                let Some(block) = ancestor_block_forks
+                    .iter()
+                    .find(|block| *block.header().header().root() == ancestor_root)
+                else {
+                    return false;
+                };
+                ancestor_root = block.header().header().prefix.parent_root;
+                ancestor_number -= BlockNumber::ONE;
+            }
+
+            false
+        };
+
+        Ok(self.data.contract_slots.get(key, block_number, is_ancestor))
+    }
 }
 
 #[derive(Debug)]
@@ -772,6 +1225,7 @@ where
 {
     block_offset: usize,
     fork_offset: usize,
+    fork_ordinal: u32,
     block: &'a Block,
     block_details: &'a BlockDetails,
 }
@@ -783,12 +1237,30 @@ struct PersistedBlock {
     write_location: WriteLocation,
 }
 
+/// Block read from a temporary storage item while opening the database
+#[derive(Debug)]
+struct StoredBlock<Block>
+where
+    Block: GenericOwnedBlock,
+{
+    header: Block::Header,
+    block_details: BlockDetails,
+    /// Only present for beacon chain blocks
+    beacon_chain_block_details: Option<BeaconChainBlockDetails>,
+    write_location: WriteLocation,
+    fork_ordinal: u32,
+}
+
 #[derive(Debug)]
 struct ClientDatabaseInnerOptions {
     block_confirmation_depth: BlockNumber,
     soft_confirmation_depth: BlockNumber,
     max_fork_tips: NonZeroUsize,
     max_fork_tip_distance: BlockNumber,
+    fork_choice: StdArc<dyn ForkChoice>,
+    durability_policy: DurabilityPolicy,
+    compaction: CompactionOptions,
+    reclamation: ReclamationOptions,
 }
 
 #[derive(Debug)]
@@ -798,6 +1270,15 @@ where
 {
     state: AsyncRwLock<State<Block, StorageBackend>>,
     options: ClientDatabaseInnerOptions,
+    /// Only used for [`ChainEventsTopic`]
+    notification_bus: NotificationBus,
+    pruning_holds: PruningHolds,
+    /// Hold on blocks that archiver still needs, see
+    /// [`ChainInfoWrite::retain_blocks_for_archiving()`]
+    archiving_pruning_hold: Mutex<Option<PruningHold>>,
+    block_body_cache: BlockBodyCache,
+    in_flight_block_reads: InFlightReads<Block>,
+    metrics: ClientDatabaseMetrics,
 }
 
 /// Client database
@@ -830,6 +1311,181 @@ where
     }
 }
 
+/// Stable position of a block in the database.
+///
+/// Unlike offsets used internally, which change as new blocks are added, the position of a block
+/// doesn't change for as long as the block is known to the database (including after restart and
+/// after the block is confirmed). This makes it suitable for external indexes that need to
+/// reference blocks that are not necessarily on the canonical chain.
+///
+/// Fork ordinal is assigned when the block is added to the database: blocks with the same number
+/// get ordinals `0`, `1`, `2`, etc. in the order they were added, regardless of which one of them
+/// is canonical. Ordinals are persisted together with blocks, but ordinals of blocks that were
+/// pruned before being persisted might be assigned to different blocks after restart.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct BlockPosition {
+    /// Block number
+    pub number: BlockNumber,
+    /// Ordinal of the block among blocks with the same number
+    pub fork_ordinal: u32,
+}
+
+/// Consistent read view of the client database at a specific generation of the canonical chain.
+///
+/// Created with [`ClientDatabase::snapshot()`]. A snapshot is cheap to create and doesn't hold any
+/// locks, so long-running readers (like archiver catch-up or RPC range scans) neither block nor
+/// race with concurrent [`ChainInfoWrite::persist_block()`] calls. Headers of canonical blocks
+/// retained in memory, segment headers and super segment headers are pinned to those known at the
+/// time of snapshot creation, such that reorgs and pruning that happen afterward are not visible
+/// through the snapshot.
+///
+/// NOTE: Block bodies are still read from the database, blocks that were not yet confirmed at
+/// snapshot creation might be pruned later if they end up on a losing fork, in which case reads of
+/// those blocks will fail. [`Self::is_current()`] can be used to check whether the canonical chain
+/// has changed since the snapshot was created.
+#[derive(Debug)]
+pub struct ClientDatabaseSnapshot<Block, StorageBackend>
+where
+    Block: GenericOwnedBlock,
+{
+    database: ClientDatabase<Block, StorageBackend>,
+    generation: u64,
+    best_number: BlockNumber,
+    best_root: BlockRoot,
+    /// Headers of canonical blocks with the best block at the front
+    canonical_headers: StdArc<VecDeque<Block::Header>>,
+    segment_headers_cache: SegmentHeadersCache,
+    super_segment_headers_cache: SuperSegmentHeadersCache,
+}
+
+impl<Block, StorageBackend> ClientDatabaseSnapshot<Block, StorageBackend>
+where
+    Block: GenericOwnedBlock,
+    StorageBackend: ClientDatabaseStorageBackend,
+{
+    /// Generation of the canonical chain this snapshot corresponds to
+    #[inline(always)]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Whether the canonical chain is still the same as at the time of snapshot creation
+    #[inline]
+    pub fn is_current(&self) -> bool {
+        self.database.generation() == self.generation
+    }
+
+    /// Best block number at the time of snapshot creation
+    #[inline(always)]
+    pub fn best_number(&self) -> BlockNumber {
+        self.best_number
+    }
+
+    /// Best block root at the time of snapshot creation
+    #[inline(always)]
+    pub fn best_root(&self) -> BlockRoot {
+        self.best_root
+    }
+
+    /// Header of the canonical block at `block_number`
+    #[inline]
+    pub fn header(&self, block_number: BlockNumber) -> Option<Block::Header> {
+        let block_offset = u64::from(self.best_number.checked_sub(block_number)?) as usize;
+
+        self.canonical_headers.get(block_offset).cloned()
+    }
+
+    /// Canonical block at `block_number`
+    #[inline]
+    pub async fn block(&self, block_number: BlockNumber) -> Result<Block, ReadBlockError> {
+        let block_root = *self
+            .header(block_number)
+            .ok_or(ReadBlockError::UnknownBlockRoot)?
+            .header()
+            .root();
+
+        self.database.block(&block_root).await
+    }
+
+    /// Last segment header known at the time of snapshot creation
+    #[inline]
+    pub fn last_segment_header(&self) -> Option<SegmentHeader> {
+        self.segment_headers_cache.last_segment_header()
+    }
+
+    /// Segment header with specified index
+    #[inline]
+    pub fn get_segment_header(&self, segment_index: LocalSegmentIndex) -> Option<SegmentHeader> {
+        self.segment_headers_cache.get_segment_header(segment_index)
+    }
+
+    /// Last super segment header known at the time of snapshot creation
+    #[inline]
+    pub fn last_super_segment_header(&self) -> Option<SuperSegmentHeader> {
+        self.super_segment_headers_cache.last_super_segment_header()
+    }
+
+    /// Super segment header with specified index
+    #[inline]
+    pub fn get_super_segment_header(
+        &self,
+        super_segment_index: SuperSegmentIndex,
+    ) -> Option<SuperSegmentHeader> {
+        self.super_segment_headers_cache
+            .get_super_segment_header(super_segment_index)
+    }
+}
+
+/// Reader of contract state as of a specific block.
+///
+/// Created with [`ClientDatabase::state_at()`]. Contents of contract slots are reconstructed from
+/// versions written by the block and its ancestors, falling back to versions of blocks that were
+/// already pruned from the canonical chain. The block is held from pruning (see [`pruning_holds`])
+/// for as long as the reader exists, such that its state remains readable even after the block
+/// becomes older than retained blocks.
+///
+/// NOTE: Blocks that were not yet confirmed at reader creation might be pruned later if they end
+/// up on a losing fork, in which case reads will fail.
+#[derive(Debug)]
+pub struct StateReader<Block, StorageBackend>
+where
+    Block: GenericOwnedBlock,
+{
+    database: ClientDatabase<Block, StorageBackend>,
+    block_number: BlockNumber,
+    block_root: BlockRoot,
+    _pruning_hold: PruningHold,
+}
+
+impl<Block, StorageBackend> StateReader<Block, StorageBackend>
+where
+    Block: GenericOwnedBlock,
+    StorageBackend: ClientDatabaseStorageBackend,
+{
+    /// Number of the block whose state is read
+    #[inline(always)]
+    pub fn block_number(&self) -> BlockNumber {
+        self.block_number
+    }
+
+    /// Root of the block whose state is read
+    #[inline(always)]
+    pub fn block_root(&self) -> BlockRoot {
+        self.block_root
+    }
+
+    /// Contents of a contract slot as of the block, `None` if the slot was never written
+    #[inline]
+    pub fn contract_slot(
+        &self,
+        owner: &Address,
+        contract: &Address,
+    ) -> Result<Option<SharedAlignedBuffer>, ReadContractSlotError> {
+        self.database
+            .contract_slot(&self.block_root, owner, contract)
+    }
+}
+
 impl<Block, StorageBackend> ChainInfo<Block> for ClientDatabase<Block, StorageBackend>
 where
     Block: GenericOwnedBlock,
@@ -854,6 +1510,11 @@ where
             .clone()
     }
 
+    #[inline(always)]
+    fn block_confirmation_depth(&self) -> BlockNumber {
+        self.inner.options.block_confirmation_depth
+    }
+
     #[inline]
     fn best_header_with_details(&self) -> (Block::Header, BlockDetails) {
         // Blocking read lock is fine because where a write lock is only taken for a short time and
@@ -869,7 +1530,6 @@ where
         )
     }
 
-    // TODO: Add fast path when `descendant_block_root` is the best block
     #[inline]
     fn ancestor_header(
         &self,
@@ -879,7 +1539,17 @@ where
         // Blocking read lock is fine because where a write lock is only taken for a short time and
         // most locks are read locks
         let state = self.inner.state.read_blocking();
-        let best_number = state.best_tip().number;
+        let best_tip = state.best_tip();
+
+        // Fast path: ancestors of the best block are on the canonical chain, so there is no need
+        // to walk forks (archiver, for example, reads many ancestors of the best block during
+        // initialization)
+        if best_tip.root == *descendant_block_root {
+            return Self::canonical_block(&state, ancestor_block_number)
+                .map(|block| block.header().clone());
+        }
+
+        let best_number = best_tip.number;
 
         let ancestor_block_offset =
             u64::from(best_number.checked_sub(ancestor_block_number)?) as usize;
@@ -938,6 +1608,24 @@ where
         None
     }
 
+    #[inline]
+    fn canonical_root(&self, block_number: BlockNumber) -> Option<BlockRoot> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+
+        Self::canonical_block(&state, block_number).map(|block| *block.header().header().root())
+    }
+
+    #[inline]
+    fn canonical_header(&self, block_number: BlockNumber) -> Option<Block::Header> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+
+        Self::canonical_block(&state, block_number).map(|block| block.header().clone())
+    }
+
     #[inline]
     fn header(&self, block_root: &BlockRoot) -> Option<Block::Header> {
         // Blocking read lock is fine because where a write lock is only taken for a short time and
@@ -1014,39 +1702,18 @@ where
                         header,
                         write_location,
                     } => {
-                        let storage_backend_adapter = state.storage_backend_adapter.read().await;
-
-                        let storage_item = storage_backend_adapter
-                            .read_storage_item::<StorageItemTemporary>(write_location)
-                            .await?;
-
-                        let storage_item_block = match storage_item {
-                            StorageItemTemporary::Block(storage_item_block) => storage_item_block,
-                            StorageItemTemporary::SegmentHeaders(_) => {
-                                return Err(ReadBlockError::StorageItemReadError {
-                                    error: io::Error::other(
-                                        "Unexpected storage item: `SegmentHeaders`",
-                                    ),
-                                });
-                            }
-                            StorageItemTemporary::SuperSegmentHeaders(_) => {
-                                return Err(ReadBlockError::StorageItemReadError {
-                                    error: io::Error::other(
-                                        "Unexpected storage item: `SuperSegmentHeaders`",
-                                    ),
-                                });
-                            }
-                        };
-
-                        let StorageItemTemporaryBlock {
-                            header: _,
-                            body,
-                            mmr_with_block: _,
-                            system_contract_states: _,
-                        } = storage_item_block;
+                        if let Some(body) = self.inner.block_body_cache.get(&write_location) {
+                            return Block::from_buffers(header.buffer().clone(), body)
+                                .ok_or(ReadBlockError::FailedToDecode);
+                        }
 
-                        Block::from_buffers(header.buffer().clone(), body)
-                            .ok_or(ReadBlockError::FailedToDecode)
+                        // Concurrent reads of the same block are coalesced into a single read
+                        self.inner
+                            .in_flight_block_reads
+                            .read(write_location, || {
+                                self.read_persisted_block(&state, header, write_location)
+                            })
+                            .await
                     }
                 };
             }
@@ -1156,13 +1823,117 @@ where
         // No segment headers required
         Vec::new()
     }
-}
 
-impl<Block, StorageBackend> ChainInfoWrite<Block> for ClientDatabase<Block, StorageBackend>
-where
-    Block: GenericOwnedBlock,
-    StorageBackend: ClientDatabaseStorageBackend,
-{
+    fn block_aux_data(
+        &self,
+        block_root: &BlockRoot,
+        namespace: BlockAuxDataNamespace,
+    ) -> Option<SharedAlignedBuffer> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+
+        state
+            .data
+            .block_aux_data
+            .get(block_root)?
+            .iter()
+            .find(|(entry_namespace, _data)| *entry_namespace == namespace)
+            .map(|(_namespace, data)| data.clone())
+    }
+
+    fn block_outcome(&self, block_root: &BlockRoot) -> Option<BlockOutcome> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+
+        state.data.block_outcomes.get(block_root).cloned()
+    }
+
+    fn find_transaction(&self, tx_hash: &TransactionHash) -> Option<TransactionLocation> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+
+        let locations = state.data.transaction_index.as_ref()?.locations(tx_hash);
+
+        // Prefer the block of the canonical chain if the transaction is included in multiple blocks
+        locations
+            .iter()
+            .find(|location| {
+                state
+                    .data
+                    .block_roots
+                    .get(&location.block_root)
+                    .and_then(|&block_number| Self::canonical_block(&state, block_number))
+                    .is_some_and(|block| *block.header().header().root() == location.block_root)
+            })
+            .or_else(|| locations.first())
+            .copied()
+    }
+
+    fn contract_slot(
+        &self,
+        block_root: &BlockRoot,
+        owner: &Address,
+        contract: &Address,
+    ) -> Result<Option<SharedAlignedBuffer>, ReadContractSlotError> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+
+        state.contract_slot(
+            block_root,
+            &ContractSlotKey {
+                owner: *owner,
+                contract: *contract,
+            },
+        )
+    }
+
+    fn mmr_proof(&self, block_number: BlockNumber) -> Result<BlockMmrProof, ReadMmrProofError> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        // TODO: Proof generation hashes all leaves while holding the lock
+        let state = self.inner.state.read_blocking();
+        let best_block_number = state.best_tip().number;
+
+        if block_number > best_block_number {
+            return Err(ReadMmrProofError::UnknownBlockNumber {
+                block_number,
+                best_block_number,
+            });
+        }
+
+        // Roots of canonical blocks that were not pruned yet, from the oldest to the best block
+        let num_next_blocks = best_block_number
+            .checked_sub(state.data.block_mmr_leaves.next_block_number())
+            .map_or(0, |block_offset| u64::from(block_offset) as usize + 1);
+        let next_block_roots = (0..num_next_blocks)
+            .rev()
+            .map(|block_offset| {
+                let block = state.data.blocks.get(block_offset)?.first()?;
+                // Type inference is not working here for some reason
+                let header: &Block::Header = block.header();
+
+                Some(*header.header().root())
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ReadMmrProofError::BlockRootsUnavailable)?;
+
+        state
+            .data
+            .block_mmr_leaves
+            .proof(block_number, &next_block_roots)
+            .ok_or(ReadMmrProofError::BlockRootsUnavailable)
+    }
+}
+
+impl<Block, StorageBackend> ChainInfoWrite<Block> for ClientDatabase<Block, StorageBackend>
+where
+    Block: GenericOwnedBlock,
+    StorageBackend: ClientDatabaseStorageBackend,
+{
     async fn persist_block(
         &self,
         block: Block,
@@ -1176,14 +1947,43 @@ where
         let block_number = header.prefix.number;
 
         if best_number == BlockNumber::ZERO && block_number != BlockNumber::ONE {
+            let old_best_root = state.best_tip().root;
+            let new_best_root = *header.root();
+
             // Special case when syncing on top of the fresh database
             Self::insert_first_block(&mut state.data, block, block_details);
+            Self::update_state_metrics(&self.inner, &state.data);
+            drop(state);
+
+            self.publish_chain_events([ChainEvent::BestBlockUpdated {
+                old_best_root,
+                new_best_number: block_number,
+                new_best_root,
+                reorg: true,
+            }])
+            .await;
 
             return Ok(());
         }
 
+        let is_new_best = self
+            .inner
+            .options
+            .fork_choice
+            .is_new_best(state.best_block().header().header(), header);
+
         if block_number == best_number + BlockNumber::ONE {
-            return Self::insert_new_best_block(state, &self.inner, block, block_details).await;
+            if is_new_best {
+                let chain_events =
+                    Self::insert_new_best_blocks(state, &self.inner, vec![(block, block_details)])
+                        .await?;
+                self.publish_chain_events(chain_events).await;
+
+                return Ok(());
+            }
+
+            // TODO: Support blocks above the best block that do not become the new best block
+            return Err(PersistBlockError::UnsupportedForkChoice);
         }
 
         let block_offset = u64::from(
@@ -1196,47 +1996,211 @@ where
             return Err(PersistBlockError::OutsideAcceptableRange);
         }
 
-        let state = &mut *state;
-
-        let block_forks = state.data.blocks.get_mut(block_offset).ok_or_else(|| {
-            error!(
-                %block_number,
-                %block_offset,
-                "Failed to store block fork, header offset is missing despite being within \
-                acceptable range"
-            );
+        let block_root = *header.root();
+        let parent_root = header.prefix.parent_root;
 
-            PersistBlockError::OutsideAcceptableRange
-        })?;
+        if is_new_best {
+            // The best block is always the highest block, so only a block at the same height can
+            // replace it
+            if block_offset != 0 {
+                // TODO: Support reorgs to blocks below the best block
+                return Err(PersistBlockError::UnsupportedForkChoice);
+            }
 
-        for (index, fork_tip) in state.data.fork_tips.iter_mut().enumerate() {
-            // Block's parent is no longer a fork tip, remove it
-            if fork_tip.root == header.prefix.parent_root {
-                state.data.fork_tips.remove(index);
-                break;
+            let parent_known = state.data.blocks.get(1).is_some_and(|parent_blocks| {
+                parent_blocks
+                    .iter()
+                    .any(|parent_block| *parent_block.header().header().root() == parent_root)
+            });
+            if !parent_known {
+                return Err(PersistBlockError::MissingParent);
             }
         }
 
-        let block_root = *header.root();
-        // Insert at position 1, which means the most recent tip, which doesn't correspond to
-        // the best block
-        state.data.fork_tips.insert(
-            1,
-            ForkTip {
+        let old_best_root = state.best_tip().root;
+        let pruned_block_roots = {
+            let state = &mut *state;
+
+            let block_forks = state.data.blocks.get_mut(block_offset).ok_or_else(|| {
+                error!(
+                    %block_number,
+                    %block_offset,
+                    "Failed to store block fork, header offset is missing despite being within \
+                    acceptable range"
+                );
+
+                PersistBlockError::OutsideAcceptableRange
+            })?;
+
+            for (index, fork_tip) in state.data.fork_tips.iter_mut().enumerate() {
+                // Block's parent is no longer a fork tip, remove it
+                if fork_tip.root == header.prefix.parent_root {
+                    state.data.fork_tips.remove(index);
+                    break;
+                }
+            }
+
+            let fork_tip = ForkTip {
                 number: block_number,
                 root: block_root,
-            },
-        );
-        state.data.block_roots.insert(block_root, block_number);
-        let beacon_chain_block_details = <dyn Any>::downcast_ref::<OwnedBeaconChainBlock>(&block)
-            .map(|block| BeaconChainBlockDetails::from_body(block.body.body()));
-        block_forks.push(ClientDatabaseBlock::InMemory {
-            block,
-            block_details,
-            beacon_chain_block_details,
+            };
+            if is_new_best {
+                state.data.fork_tips.push_front(fork_tip);
+            } else {
+                // Insert at position 1, which means the most recent tip, which doesn't correspond
+                // to the best block
+                state.data.fork_tips.insert(1, fork_tip);
+            }
+            state.data.block_roots.insert(block_root, block_number);
+            if let Some(transaction_index) = &mut state.data.transaction_index {
+                transaction_index.add_block(block_root, block.body());
+            }
+            let beacon_chain_block_details =
+                <dyn Any>::downcast_ref::<OwnedBeaconChainBlock>(&block)
+                    .map(|block| BeaconChainBlockDetails::from_body(block.body.body()));
+            // New fork ordinal is larger than any existing one, so forks remain sorted
+            block_forks.push(ClientDatabaseBlock::InMemory {
+                fork_ordinal: Self::allocate_fork_ordinal(
+                    &mut state.data.next_fork_ordinals,
+                    block_number,
+                ),
+                block,
+                block_details,
+                beacon_chain_block_details,
+            });
+
+            if is_new_best {
+                // Move the new best block and its ancestors to the first index, the parent is known
+                // to exist
+                let _adjusted: bool =
+                    Self::adjust_ancestor_block_forks(&mut state.data.blocks, block_root);
+                state.data.generation += 1;
+                state.data.update_canonical_headers();
+            }
+
+            let mut pruned_block_roots = Vec::new();
+            Self::prune_outdated_fork_tips(
+                best_number,
+                &mut state.data,
+                &self.inner.options,
+                &self.inner.pruning_holds,
+                &mut pruned_block_roots,
+            );
+            Self::persist_discarded_contract_slots(state).await?;
+            Self::update_state_metrics(&self.inner, &state.data);
+
+            pruned_block_roots
+        };
+        drop(state);
+
+        let block_event = if is_new_best {
+            ChainEvent::BestBlockUpdated {
+                old_best_root,
+                new_best_number: block_number,
+                new_best_root: block_root,
+                reorg: true,
+            }
+        } else {
+            ChainEvent::ForkCreated {
+                number: block_number,
+                root: block_root,
+                parent_root,
+            }
+        };
+        let forks_pruned = (!pruned_block_roots.is_empty()).then_some(ChainEvent::ForksPruned {
+            roots: pruned_block_roots,
         });
+        self.publish_chain_events(iter::once(block_event).chain(forks_pruned))
+            .await;
+
+        Ok(())
+    }
+
+    async fn persist_blocks(
+        &self,
+        mut blocks: Vec<(Block, BlockDetails)>,
+    ) -> Result<(), PersistBlockError> {
+        // Every block after the first one must extend the previous one and become the new best
+        // block
+        for window in blocks.windows(2) {
+            let [(parent_block, _), (block, _)] = window else {
+                unreachable!("Window size is 2; qed");
+            };
+            let parent_header = parent_block.header().header();
+            let header = block.header().header();
+
+            if header.prefix.number != parent_header.prefix.number + BlockNumber::ONE
+                || header.prefix.parent_root != *parent_header.root()
+            {
+                return Err(PersistBlockError::MissingParent);
+            }
+
+            if !self
+                .inner
+                .options
+                .fork_choice
+                .is_new_best(parent_header, header)
+            {
+                return Err(PersistBlockError::UnsupportedForkChoice);
+            }
+        }
+
+        let Some((first_block, _)) = blocks.first() else {
+            return Ok(());
+        };
+
+        let mut state = self.inner.state.write().await;
+        let best_number = state.best_tip().number;
+
+        let header = first_block.header().header();
+        let block_number = header.prefix.number;
+
+        let mut chain_events = Vec::new();
+        if best_number == BlockNumber::ZERO && block_number != BlockNumber::ONE {
+            let old_best_root = state.best_tip().root;
+            let new_best_root = *header.root();
+
+            // Special case when syncing on top of the fresh database
+            let (first_block, first_block_details) = blocks.remove(0);
+            Self::insert_first_block(&mut state.data, first_block, first_block_details);
+
+            chain_events.push(ChainEvent::BestBlockUpdated {
+                old_best_root,
+                new_best_number: block_number,
+                new_best_root,
+                reorg: true,
+            });
+        } else {
+            if block_number != best_number + BlockNumber::ONE {
+                return Err(if block_number > best_number {
+                    PersistBlockError::MissingParent
+                } else {
+                    // TODO: Support batches that start at or below the best block
+                    PersistBlockError::UnsupportedForkChoice
+                });
+            }
 
-        Self::prune_outdated_fork_tips(block_number, &mut state.data, &self.inner.options);
+            if !state
+                .data
+                .block_roots
+                .contains_key(&header.prefix.parent_root)
+            {
+                return Err(PersistBlockError::MissingParent);
+            }
+
+            if !self
+                .inner
+                .options
+                .fork_choice
+                .is_new_best(state.best_block().header().header(), header)
+            {
+                // TODO: Support blocks above the best block that do not become the new best block
+                return Err(PersistBlockError::UnsupportedForkChoice);
+            }
+        }
+
+        chain_events.extend(Self::insert_new_best_blocks(state, &self.inner, blocks).await?);
+        self.publish_chain_events(chain_events).await;
 
         Ok(())
     }
@@ -1265,7 +2229,7 @@ where
 
         storage_backend_adapter
             .write_storage_item(StorageItemTemporary::SegmentHeaders(
-                StorageItemTemporarySegmentHeaders {
+                StorageItemSegmentHeaders {
                     segment_headers: added_segment_headers,
                 },
             ))
@@ -1273,6 +2237,125 @@ where
 
         Ok(())
     }
+
+    async fn persist_block_aux_data(
+        &self,
+        block_root: &BlockRoot,
+        namespace: BlockAuxDataNamespace,
+        data: SharedAlignedBuffer,
+    ) -> Result<(), PersistBlockAuxDataError> {
+        if data.len() > MAX_BLOCK_AUX_DATA_SIZE {
+            return Err(PersistBlockAuxDataError::TooLarge { size: data.len() });
+        }
+
+        // Upgradable read lock allows reads, while preventing the block from being pruned
+        // concurrently
+        let state = self.inner.state.upgradable_read().await;
+
+        if !state.data.block_roots.contains_key(block_root) {
+            return Err(PersistBlockAuxDataError::UnknownBlockRoot);
+        }
+
+        {
+            let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+
+            storage_backend_adapter
+                .write_storage_item(StorageItemTemporary::BlockAuxData(
+                    StorageItemTemporaryBlockAuxData {
+                        block_root: *block_root,
+                        namespace,
+                        data: data.clone(),
+                    },
+                ))
+                .await?;
+        }
+
+        let mut state = RwLockUpgradableReadGuard::upgrade(state).await;
+        Self::insert_block_aux_data(&mut state.data, *block_root, namespace, data);
+
+        Ok(())
+    }
+
+    async fn persist_block_outcome(
+        &self,
+        block_root: &BlockRoot,
+        outcome: BlockOutcome,
+    ) -> Result<(), PersistBlockOutcomeError> {
+        // Upgradable read lock allows reads, while preventing the block from being pruned
+        // concurrently
+        let state = self.inner.state.upgradable_read().await;
+
+        if !state.data.block_roots.contains_key(block_root) {
+            return Err(PersistBlockOutcomeError::UnknownBlockRoot);
+        }
+
+        {
+            let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+
+            storage_backend_adapter
+                .write_storage_item(StorageItemTemporary::BlockOutcome(
+                    StorageItemTemporaryBlockOutcome {
+                        block_root: *block_root,
+                        outcome: outcome.clone(),
+                    },
+                ))
+                .await?;
+        }
+
+        let mut state = RwLockUpgradableReadGuard::upgrade(state).await;
+        state.data.block_outcomes.insert(*block_root, outcome);
+
+        Ok(())
+    }
+
+    async fn persist_contract_slots(
+        &self,
+        block_root: &BlockRoot,
+        slots: StdArc<[ContractSlotState]>,
+    ) -> Result<(), PersistContractSlotsError> {
+        // Upgradable read lock allows reads, while preventing the block from being pruned
+        // concurrently
+        let state = self.inner.state.upgradable_read().await;
+
+        let Some(&block_number) = state.data.block_roots.get(block_root) else {
+            return Err(PersistContractSlotsError::UnknownBlockRoot);
+        };
+
+        {
+            let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+
+            storage_backend_adapter
+                .write_storage_item(StorageItemState::ContractSlots(
+                    StorageItemStateContractSlots {
+                        block_root: *block_root,
+                        block_number,
+                        slots: StdArc::clone(&slots),
+                    },
+                ))
+                .await?;
+        }
+
+        let mut state = RwLockUpgradableReadGuard::upgrade(state).await;
+        state
+            .data
+            .contract_slots
+            .insert(block_number, *block_root, &slots);
+
+        Ok(())
+    }
+
+    fn retain_blocks_for_archiving(&self, first_block_number: BlockNumber) {
+        let pruning_hold = self.hold_block_range(
+            ARCHIVING_PRUNING_HOLD_OWNER,
+            first_block_number..=BlockNumber::MAX,
+        );
+        // New hold is placed before the previous one is released, such that there is no window
+        // where blocks could be pruned
+        self.inner
+            .archiving_pruning_hold
+            .lock()
+            .replace(pruning_hold);
+    }
 }
 
 impl<StorageBackend> BeaconChainInfo for ClientDatabase<OwnedBeaconChainBlock, StorageBackend>
@@ -1450,10 +2533,18 @@ where
     {
         let ClientDatabaseOptions {
             write_buffer_size,
+            max_coalesced_write_pages,
             block_confirmation_depth,
             soft_confirmation_depth,
             max_fork_tips,
             max_fork_tip_distance,
+            fork_choice,
+            durability_policy,
+            compaction,
+            reclamation,
+            block_roots_filters,
+            block_body_cache,
+            transaction_index,
             genesis_block_builder,
             storage_backend,
         } = options;
@@ -1465,16 +2556,31 @@ where
             return Err(ClientDatabaseError::InvalidMaxForkTipDistance);
         }
 
+        if let Some(retained_blocks) = reclamation.retained_blocks
+            && retained_blocks <= block_confirmation_depth
+        {
+            return Err(ClientDatabaseError::InvalidRetainedBlocks);
+        }
+
         let mut state_data = StateData {
             fork_tips: VecDeque::new(),
             block_roots: HashMap::default(),
             blocks: VecDeque::new(),
+            next_fork_ordinals: BTreeMap::new(),
+            block_aux_data: HashMap::default(),
+            block_outcomes: HashMap::default(),
+            transaction_index: transaction_index.then(TransactionIndex::default),
+            contract_slots: ContractSlots::default(),
+            discarded_contract_slots: Vec::new(),
+            block_mmr_leaves: BlockMmrLeaves::default(),
+            generation: 0,
+            canonical_headers: StdArc::default(),
         };
         let mut segment_headers_cache = SegmentHeadersCache {
-            segment_headers_cache: Vec::new(),
+            segment_headers_cache: StdArc::default(),
         };
         let mut super_segment_headers_cache = SuperSegmentHeadersCache {
-            super_segment_headers_cache: Vec::new(),
+            super_segment_headers_cache: StdArc::default(),
         };
 
         let options = ClientDatabaseInnerOptions {
@@ -1482,24 +2588,37 @@ where
             soft_confirmation_depth,
             max_fork_tips,
             max_fork_tip_distance,
+            fork_choice: fork_choice.unwrap_or_else(|| StdArc::new(LongestChainForkChoice)),
+            durability_policy,
+            compaction,
+            reclamation,
         };
 
+        // Temporary storage items might have been relocated by compaction, so their order in the
+        // database doesn't necessarily match the order in which they were originally written.
+        // Blocks, segment headers and super segment headers are collected first and inserted in
+        // order after all storage items are read.
+        let mut stored_blocks = BTreeMap::<BlockNumber, SmallVec<[StoredBlock<Block>; 2]>>::new();
+        let mut stored_segment_headers = Vec::<(SegmentHeader, u32)>::new();
+        let mut stored_super_segment_headers = Vec::<(SuperSegmentHeader, u32)>::new();
+        let mut stored_block_roots_filters = Vec::new();
+        let mut block_mmr_leaves = BlockMmrLeaves::default();
+        // State storage items are never relocated, so they are stored in the order in which they
+        // were written
+        let mut stored_state_items = Vec::<StorageItemState>::new();
+
         let storage_item_handlers = StorageItemHandlers {
-            permanent: |_arg| {
-                // TODO
-                Ok(())
-            },
-            temporary: |arg| {
+            permanent: |arg| {
                 let StorageItemHandlerArg {
                     storage_item,
                     page_offset,
-                    num_pages,
+                    num_pages: _,
+                    sequence_number: _,
                 } = arg;
-                let storage_item_block = match storage_item {
-                    StorageItemTemporary::Block(storage_item_block) => storage_item_block,
-                    StorageItemTemporary::SegmentHeaders(segment_headers) => {
+                match storage_item {
+                    StorageItemPermanent::KnownSegmentHeaders(segment_headers) => {
                         let num_segment_headers = segment_headers.segment_headers.len();
-                        return match segment_headers_cache
+                        match segment_headers_cache
                             .add_segment_headers(segment_headers.segment_headers)
                         {
                             Ok(_) => Ok(()),
@@ -1508,31 +2627,81 @@ where
                                     %page_offset,
                                     %num_segment_headers,
                                     %error,
-                                    "Failed to add segment headers from storage item"
+                                    "Failed to add known segment headers from storage item"
                                 );
 
                                 Err(ClientDatabaseError::InvalidSegmentHeaders { page_offset })
                             }
-                        };
+                        }
                     }
-                    StorageItemTemporary::SuperSegmentHeaders(super_segment_headers) => {
-                        let num_super_segment_headers =
-                            super_segment_headers.super_segment_headers.len();
-                        return match super_segment_headers_cache
-                            .add_super_segment_headers(super_segment_headers.super_segment_headers)
-                        {
-                            Ok(_) => Ok(()),
-                            Err(error) => {
+                    StorageItemPermanent::BlockMmr(block_mmr) => {
+                        let first_block_number = block_mmr.first_block_number;
+                        let num_block_roots = block_mmr.block_roots.len();
+                        block_mmr_leaves
+                            .add_storage_item(*block_mmr)
+                            .map_err(|error| {
                                 error!(
                                     %page_offset,
-                                    %num_super_segment_headers,
+                                    %first_block_number,
+                                    %num_block_roots,
                                     %error,
-                                    "Failed to add segment headers from storage item"
+                                    "Failed to add block MMR leaves from storage item"
                                 );
 
-                                Err(ClientDatabaseError::InvalidSegmentHeaders { page_offset })
-                            }
-                        };
+                                ClientDatabaseError::InvalidBlockMmr { page_offset }
+                            })
+                    }
+                }
+            },
+            temporary: |arg| {
+                let StorageItemHandlerArg {
+                    storage_item,
+                    page_offset,
+                    num_pages,
+                    sequence_number,
+                } = arg;
+                let storage_item_block = match storage_item {
+                    StorageItemTemporary::Block(storage_item_block) => storage_item_block,
+                    StorageItemTemporary::BlockAuxData(block_aux_data) => {
+                        // Block might be stored after its auxiliary data, entries of unknown blocks
+                        // are removed after all storage items are read. Compaction only relocates
+                        // the latest data, so the latest storage item always wins.
+                        Self::insert_block_aux_data(
+                            &mut state_data,
+                            block_aux_data.block_root,
+                            block_aux_data.namespace,
+                            block_aux_data.data,
+                        );
+                        return Ok(());
+                    }
+                    StorageItemTemporary::BlockOutcome(block_outcome) => {
+                        // Same as auxiliary data above, the latest storage item always wins
+                        state_data
+                            .block_outcomes
+                            .insert(block_outcome.block_root, block_outcome.outcome);
+                        return Ok(());
+                    }
+                    StorageItemTemporary::SegmentHeaders(segment_headers) => {
+                        stored_segment_headers.extend(
+                            segment_headers
+                                .segment_headers
+                                .into_iter()
+                                .map(|segment_header| (segment_header, page_offset)),
+                        );
+                        return Ok(());
+                    }
+                    StorageItemTemporary::SuperSegmentHeaders(super_segment_headers) => {
+                        stored_super_segment_headers.extend(
+                            super_segment_headers
+                                .super_segment_headers
+                                .into_iter()
+                                .map(|super_segment_header| (super_segment_header, page_offset)),
+                        );
+                        return Ok(());
+                    }
+                    StorageItemTemporary::BlockRootsFilter(block_roots_filter) => {
+                        stored_block_roots_filters.push(*block_roots_filter);
+                        return Ok(());
                     }
                 };
 
@@ -1543,6 +2712,8 @@ where
                     body,
                     mmr_with_block,
                     system_contract_states,
+                    fork_ordinal,
+                    compressed: _,
                 } = storage_item_block;
 
                 let header = Block::Header::from_buffer(header).map_err(|_buffer| {
@@ -1559,57 +2730,16 @@ where
                 let block_root = *header.header().root();
                 let block_number = header.header().prefix.number;
 
-                state_data.block_roots.insert(block_root, block_number);
-
-                let maybe_best_number = state_data
-                    .blocks
-                    .front()
-                    .and_then(|block_forks| block_forks.first())
-                    .map(|best_block| {
-                        // Type inference is not working here for some reason
-                        let header: &Block::Header = best_block.header();
-
-                        header.header().prefix.number
-                    });
-
-                let block_offset = if let Some(best_number) = maybe_best_number {
-                    if block_number <= best_number {
-                        u64::from(best_number - block_number) as usize
-                    } else {
-                        // The new best block must follow the previous best block
-                        if block_number - best_number != BlockNumber::ONE {
-                            error!(
-                                %page_offset,
-                                %best_number,
-                                %block_number,
-                                "Invalid new best block number, it must be only one block \
-                                higher than the best block"
-                            );
-
-                            return Err(ClientDatabaseError::InvalidBlock { page_offset });
-                        }
-
-                        state_data.blocks.push_front(SmallVec::new());
-                        // Will insert a new block at the front
-                        0
-                    }
-                } else {
-                    state_data.blocks.push_front(SmallVec::new());
-                    // Will insert a new block at the front
-                    0
-                };
-
-                let Some(block_forks) = state_data.blocks.get_mut(block_offset) else {
-                    // Ignore the older block, other blocks at its height were already pruned
-                    // anyway
-                    return Ok(());
-                };
+                // Same as auxiliary data above, entries of blocks that turn out to be unknown are
+                // removed after all storage items are read
+                if let Some(transaction_index) = &mut state_data.transaction_index {
+                    transaction_index.add_block(block_root, &body);
+                }
 
-                // Push a new block to the end of the list, we'll fix it up later
                 let beacon_chain_block_details =
                     <dyn Any>::downcast_ref::<OwnedBeaconChainBody>(&body)
                         .map(|body| BeaconChainBlockDetails::from_body(body.body()));
-                block_forks.push(ClientDatabaseBlock::Persisted {
+                let stored_block = StoredBlock {
                     header,
                     block_details: BlockDetails {
                         mmr_with_block,
@@ -1619,22 +2749,191 @@ where
                     write_location: WriteLocation {
                         page_offset,
                         num_pages,
+                        sequence_number,
                     },
-                });
+                    fork_ordinal,
+                };
 
-                // If a new block was inserted, confirm a new canonical block to prune extra
-                // in-memory information
-                if block_offset == 0 && block_forks.len() == 1 {
-                    Self::confirm_canonical_block(block_number, &mut state_data, &options);
+                let block_forks = stored_blocks.entry(block_number).or_default();
+                // A block relocated by compaction is stored more than once if the original was not
+                // freed yet, the latest copy is used, but the original position among forks is
+                // retained
+                if let Some(existing_stored_block) = block_forks.iter_mut().find(|stored_block| {
+                    // Type inference is not working here for some reason
+                    let header: &Block::Header = &stored_block.header;
+
+                    *header.header().root() == block_root
+                }) {
+                    *existing_stored_block = stored_block;
+                } else {
+                    block_forks.push(stored_block);
                 }
 
+                Ok(())
+            },
+            state: |arg| {
+                let StorageItemHandlerArg {
+                    storage_item,
+                    page_offset: _,
+                    num_pages: _,
+                    sequence_number: _,
+                } = arg;
+                stored_state_items.push(storage_item);
+
                 Ok(())
             },
         };
 
-        let storage_backend_adapter =
-            StorageBackendAdapter::open(write_buffer_size, storage_item_handlers, storage_backend)
-                .await?;
+        let metrics = ClientDatabaseMetrics::default();
+        let mut storage_backend_adapter = StorageBackendAdapter::open(
+            write_buffer_size,
+            max_coalesced_write_pages,
+            durability_policy,
+            block_roots_filters,
+            storage_item_handlers,
+            storage_backend,
+            metrics.clone(),
+        )
+        .await?;
+
+        // Persisted filters are restored first, filters of page groups that were filled before
+        // filters were enabled and the filter of the active page group are reconstructed from
+        // stored blocks
+        for block_roots_filter in &stored_block_roots_filters {
+            storage_backend_adapter.restore_block_roots_filter(block_roots_filter);
+        }
+        for stored_block in stored_blocks.values().flatten() {
+            // Type inference is not working here for some reason
+            let header: &Block::Header = &stored_block.header;
+
+            storage_backend_adapter
+                .add_block_root(stored_block.write_location, &header.header().root());
+        }
+
+        // Duplicates are possible due to relocation by compaction, stable sort retains the order
+        // in which they were read
+        stored_segment_headers
+            .sort_by_key(|(segment_header, _page_offset)| segment_header.index.as_inner());
+        for (segment_header, page_offset) in stored_segment_headers {
+            if let Err(error) = segment_headers_cache.add_segment_headers(vec![segment_header]) {
+                error!(
+                    %page_offset,
+                    %error,
+                    "Failed to add segment headers from storage item"
+                );
+
+                return Err(ClientDatabaseError::InvalidSegmentHeaders { page_offset });
+            }
+        }
+
+        stored_super_segment_headers.sort_by_key(|(super_segment_header, _page_offset)| {
+            super_segment_header.index.as_inner()
+        });
+        for (super_segment_header, page_offset) in stored_super_segment_headers {
+            if let Err(error) =
+                super_segment_headers_cache.add_super_segment_headers(vec![super_segment_header])
+            {
+                error!(
+                    %page_offset,
+                    %error,
+                    "Failed to add super segment headers from storage item"
+                );
+
+                return Err(ClientDatabaseError::InvalidSegmentHeaders { page_offset });
+            }
+        }
+
+        for stored_block in stored_blocks.into_values().flatten() {
+            Self::insert_stored_block(&mut state_data, stored_block, &options)?;
+        }
+
+        state_data.block_mmr_leaves = block_mmr_leaves;
+
+        let pruning_holds = PruningHolds::default();
+        // Archiver reads blocks since the last archived segment again after restart, retain them
+        // until archiver reports its progress with `retain_blocks_for_archiving()`
+        let archiving_pruning_hold =
+            segment_headers_cache
+                .last_segment_header()
+                .map(|segment_header| {
+                    pruning_holds.add(
+                        ARCHIVING_PRUNING_HOLD_OWNER,
+                        PruningHoldTarget::BlockRange(
+                            segment_header.last_archived_block.number()..=BlockNumber::MAX,
+                        ),
+                    )
+                });
+        // Blocks that were pruned before, but whose page groups were not reclaimed yet
+        Self::prune_old_blocks(&mut state_data, &options, &pruning_holds);
+
+        if let Some(oldest_block_number) = state_data
+            .blocks
+            .back()
+            .and_then(|block_forks| block_forks.first())
+            .map(|block| block.header().header().prefix.number)
+            && oldest_block_number > state_data.block_mmr_leaves.next_block_number()
+        {
+            warn!(
+                %oldest_block_number,
+                next_block_number = %state_data.block_mmr_leaves.next_block_number(),
+                "Some blocks were pruned before their roots were persisted, MMR proofs are not \
+                available"
+            );
+        }
+
+        // Auxiliary data, outcomes and indexed transactions of blocks that were not persisted or
+        // were pruned are no longer needed
+        let StateData {
+            block_roots,
+            block_aux_data,
+            block_outcomes,
+            transaction_index,
+            ..
+        } = &mut state_data;
+        block_aux_data.retain(|block_root, _| block_roots.contains_key(block_root));
+        block_outcomes.retain(|block_root, _| block_roots.contains_key(block_root));
+        if let Some(transaction_index) = transaction_index {
+            transaction_index.retain_blocks(|block_root| block_roots.contains_key(block_root));
+        }
+
+        for storage_item in stored_state_items {
+            match storage_item {
+                StorageItemState::ContractSlots(contract_slots) => {
+                    state_data.contract_slots.insert(
+                        contract_slots.block_number,
+                        contract_slots.block_root,
+                        &contract_slots.slots,
+                    );
+                }
+                StorageItemState::DiscardedContractSlots(discarded_contract_slots) => {
+                    state_data
+                        .contract_slots
+                        .discard_block(&discarded_contract_slots.block_root);
+                }
+            }
+        }
+        // Contract slots of blocks that are no longer known were either pruned from the canonical
+        // chain (older than any known block) or belong to blocks that were not persisted
+        let oldest_block_number = state_data
+            .blocks
+            .back()
+            .and_then(|block_forks| block_forks.first())
+            .map(|block| block.header().header().prefix.number);
+        let mut unknown_contract_slots_blocks = state_data
+            .contract_slots
+            .block_roots()
+            .filter(|(_block_number, block_root)| !state_data.block_roots.contains_key(block_root))
+            .collect::<Vec<_>>();
+        unknown_contract_slots_blocks.sort_by_key(|(block_number, _block_root)| *block_number);
+        for (block_number, block_root) in unknown_contract_slots_blocks {
+            if oldest_block_number
+                .is_some_and(|oldest_block_number| block_number < oldest_block_number)
+            {
+                state_data.contract_slots.prune_block(&block_root);
+            } else {
+                state_data.contract_slots.discard_block(&block_root);
+            }
+        }
 
         if let Some(best_block) = state_data.blocks.front().and_then(|block_forks| {
             // The best block is last in the list here because that is how it was inserted while
@@ -1650,70 +2949,1124 @@ where
             if !Self::adjust_ancestor_block_forks(&mut state_data.blocks, block_root) {
                 return Err(ClientDatabaseError::FailedToAdjustAncestorBlockForks);
             }
+            // Stored blocks were inserted in the order they were written, which doesn't
+            // necessarily match the order of fork ordinals
+            for block_forks in &mut state_data.blocks {
+                Self::sort_non_canonical_block_forks(block_forks);
+            }
 
             // Store the best block as the first and only fork tip
             state_data.fork_tips.push_front(ForkTip {
                 number: block_number,
                 root: block_root,
             });
+            state_data.update_canonical_headers();
         } else {
             let GenesisBlockBuilderResult {
                 block,
                 system_contract_states,
             } = genesis_block_builder();
 
-            // If the database is empty, initialize everything with the genesis block
-            let header = block.header().header();
-            let block_number = header.prefix.number;
-            let block_root = *header.root();
+            // If the database is empty, initialize everything with the genesis block
+            let header = block.header().header();
+            let block_number = header.prefix.number;
+            let block_root = *header.root();
+
+            state_data.fork_tips.push_front(ForkTip {
+                number: block_number,
+                root: block_root,
+            });
+            state_data.block_roots.insert(block_root, block_number);
+            if let Some(transaction_index) = &mut state_data.transaction_index {
+                transaction_index.add_block(block_root, block.body());
+            }
+            let beacon_chain_block_details =
+                <dyn Any>::downcast_ref::<OwnedBeaconChainBlock>(&block)
+                    .map(|block| BeaconChainBlockDetails::from_body(block.body.body()));
+            state_data
+                .blocks
+                .push_front(smallvec![ClientDatabaseBlock::InMemory {
+                    fork_ordinal: Self::allocate_fork_ordinal(
+                        &mut state_data.next_fork_ordinals,
+                        block_number,
+                    ),
+                    block,
+                    block_details: BlockDetails {
+                        system_contract_states,
+                        mmr_with_block: Arc::new({
+                            let mut mmr = BlockMerkleMountainRange::new();
+                            mmr.add_leaf(&block_root);
+                            mmr
+                        })
+                    },
+                    beacon_chain_block_details,
+                }]);
+            state_data.update_canonical_headers();
+        }
+
+        let state = State {
+            data: state_data,
+            segment_headers_cache,
+            super_segment_headers_cache,
+            storage_backend_adapter: AsyncRwLock::new(storage_backend_adapter),
+        };
+
+        let inner = Inner {
+            state: AsyncRwLock::new(state),
+            options,
+            notification_bus: NotificationBus::new(None),
+            pruning_holds,
+            archiving_pruning_hold: Mutex::new(archiving_pruning_hold),
+            block_body_cache: BlockBodyCache::new(block_body_cache),
+            in_flight_block_reads: InFlightReads::default(),
+            metrics,
+        };
+        Self::update_state_metrics(&inner, &inner.state.read_blocking().data);
+
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Format a new database
+    pub async fn format(
+        storage_backend: &StorageBackend,
+        options: ClientDatabaseFormatOptions,
+    ) -> Result<(), ClientDatabaseFormatError> {
+        // Ensure known segment headers are valid, so they can be loaded after the database is
+        // opened
+        SegmentHeadersCache {
+            segment_headers_cache: StdArc::default(),
+        }
+        .add_segment_headers(options.known_segment_headers.clone())
+        .map_err(|error| ClientDatabaseFormatError::InvalidKnownSegmentHeaders { error })?;
+
+        StorageBackendAdapter::format(storage_backend, options).await
+    }
+
+    /// Restore the database from a backup created with [`Self::backup()`].
+    ///
+    /// The storage backend is formatted with the provided options first, such that neither the
+    /// size of the database nor its page group size need to match the database the backup was
+    /// created from. Segment headers that are already among known segment headers are not written
+    /// again.
+    ///
+    /// The storage backend is returned once all storage items are written and flushed, after which
+    /// the database can be opened with [`Self::open()`].
+    pub async fn restore<R>(
+        storage_backend: StorageBackend,
+        options: ClientDatabaseFormatOptions,
+        reader: &mut R,
+    ) -> Result<StorageBackend, BackupError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        // Check the backup header before anything is written to the storage backend
+        let mut backup_reader = BackupReader::new(reader).await?;
+
+        let known_segment_headers = options.known_segment_headers.clone();
+        Self::format(&storage_backend, options).await?;
+
+        // Known segment headers were validated during formatting
+        let mut segment_headers_cache = SegmentHeadersCache {
+            segment_headers_cache: StdArc::new(known_segment_headers),
+        };
+        let mut super_segment_headers_cache = SuperSegmentHeadersCache {
+            super_segment_headers_cache: StdArc::default(),
+        };
+
+        let mut storage_backend_adapter = StorageBackendAdapter::open(
+            // Allow some writes to happen in the background while the backup is being read
+            5,
+            // Backup is restored with many consecutive writes that benefit from coalescing
+            64,
+            // Everything is flushed explicitly at the end
+            DurabilityPolicy::OnConfirmation,
+            // Filters are reconstructed from stored blocks when the database is opened
+            false,
+            // The database was just formatted and only contains known segment headers, which were
+            // added to the cache above already
+            StorageItemHandlers {
+                permanent: |_arg: StorageItemHandlerArg<StorageItemPermanent>| Ok(()),
+                temporary: |_arg: StorageItemHandlerArg<StorageItemTemporary>| Ok(()),
+                state: |_arg: StorageItemHandlerArg<StorageItemState>| Ok(()),
+            },
+            storage_backend,
+            // Metrics of the restored database are collected once it is opened
+            ClientDatabaseMetrics::default(),
+        )
+        .await?;
+
+        while let Some(storage_item) = backup_reader.next_storage_item().await? {
+            let storage_item = match storage_item {
+                StorageItemTemporary::SegmentHeaders(segment_headers) => {
+                    let segment_headers = segment_headers_cache
+                        .add_segment_headers(segment_headers.segment_headers)?;
+
+                    if segment_headers.is_empty() {
+                        continue;
+                    }
+
+                    StorageItemTemporary::SegmentHeaders(StorageItemSegmentHeaders {
+                        segment_headers,
+                    })
+                }
+                StorageItemTemporary::SuperSegmentHeaders(super_segment_headers) => {
+                    let super_segment_headers = super_segment_headers_cache
+                        .add_super_segment_headers(super_segment_headers.super_segment_headers)?;
+
+                    if super_segment_headers.is_empty() {
+                        continue;
+                    }
+
+                    StorageItemTemporary::SuperSegmentHeaders(
+                        StorageItemTemporarySuperSegmentHeaders {
+                            super_segment_headers,
+                        },
+                    )
+                }
+                storage_item @ (StorageItemTemporary::Block(_)
+                | StorageItemTemporary::BlockAuxData(_)
+                | StorageItemTemporary::BlockOutcome(_)) => storage_item,
+                storage_item @ StorageItemTemporary::BlockRootsFilter(_) => {
+                    return Err(BackupError::UnexpectedStorageItem {
+                        index: backup_reader.last_index(),
+                        storage_item_type: storage_item.type_name(),
+                    });
+                }
+            };
+
+            storage_backend_adapter
+                .write_storage_item(storage_item)
+                .await?;
+        }
+
+        storage_backend_adapter.flush().await?;
+
+        Ok(storage_backend_adapter.into_storage_backend())
+    }
+
+    /// Current generation of the canonical chain.
+    ///
+    /// Generation is bumped every time the best block changes.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        self.inner.state.read_blocking().data.generation
+    }
+
+    /// Wait for all buffered writes to finish and flush them to durable storage.
+    ///
+    /// After a flush failure, all further writes are rejected until restart, the same way as after
+    /// a write failure.
+    pub async fn flush(&self) -> io::Result<()> {
+        let state = self.inner.state.read().await;
+        let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+
+        storage_backend_adapter.flush().await
+    }
+
+    /// Statistics of space usage by the database.
+    ///
+    /// Space is reported for pages and page groups, as well as for each kind of storage item. Used
+    /// space includes storage items that are no longer needed, but were not reclaimed by
+    /// compaction yet.
+    pub async fn stats(&self) -> ClientDatabaseStats {
+        let state = self.inner.state.read().await;
+        let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+        storage_backend_adapter.stats()
+    }
+
+    /// Verify integrity of the database.
+    ///
+    /// Every page group is read, storage items are checked against their checksums and sequence
+    /// numbers, and non-empty pages that are not reachable as storage items are reported as
+    /// orphaned. Additionally, parent links of all known blocks are cross-checked against headers
+    /// of blocks at the previous block number, and persisted blocks are read back from storage.
+    ///
+    /// Issues are collected into a report instead of failing early, only I/O errors are returned
+    /// as errors. This is an expensive operation that blocks writes while page groups are read.
+    pub async fn verify(&self) -> io::Result<VerificationReport> {
+        let mut report = VerificationReport::default();
+
+        let blocks = {
+            let state = self.inner.state.read().await;
+
+            state
+                .storage_backend_adapter
+                .read()
+                .await
+                .verify(&mut report)
+                .await?;
+
+            let mut blocks = Vec::new();
+            for (block_offset, fork_blocks) in state.data.blocks.iter().enumerate() {
+                // The oldest block in memory doesn't have a parent to check against
+                let maybe_parent_blocks = state.data.blocks.get(block_offset + 1);
+
+                for block in fork_blocks {
+                    let header = block.header().header();
+                    let block_number = header.prefix.number;
+                    let block_root = *header.root();
+                    let parent_root = header.prefix.parent_root;
+
+                    if let Some(parent_blocks) = maybe_parent_blocks
+                        && !parent_blocks.iter().any(|parent_block| {
+                            *parent_block.header().header().root() == parent_root
+                        })
+                    {
+                        report.issues.push(VerificationIssue::MissingParentBlock {
+                            block_number,
+                            block_root,
+                            parent_root,
+                        });
+                    }
+
+                    blocks.push((block_number, block_root));
+                }
+            }
+
+            blocks
+        };
+
+        for (block_number, block_root) in blocks {
+            let reason = match self.block(&block_root).await {
+                Ok(_block) => None,
+                // Pruned concurrently
+                Err(ReadBlockError::UnknownBlockRoot) => {
+                    continue;
+                }
+                Err(ReadBlockError::StorageItemReadError { error }) => {
+                    return Err(error);
+                }
+                Err(ReadBlockError::CorruptedStorageItem { error }) => Some(error.to_string()),
+                Err(error @ ReadBlockError::FailedToDecode) => Some(error.to_string()),
+            };
+            report.checked_blocks += 1;
+
+            if let Some(reason) = reason {
+                report.issues.push(VerificationIssue::CorruptedBlock {
+                    block_number,
+                    block_root,
+                    reason,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Back up all live storage items of the database into a portable stream, see
+    /// [module documentation](crate::backup) for details.
+    ///
+    /// The backup can be restored with [`Self::restore()`]. Blocks that were not persisted yet are
+    /// included in the backup as well. Writes are blocked while the backup is in progress.
+    pub async fn backup<W>(&self, writer: &mut W) -> Result<(), BackupError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let state = self.inner.state.read().await;
+        let mut backup_writer = BackupWriter::new(writer).await?;
+
+        for segment_headers in state
+            .segment_headers_cache
+            .segment_headers_cache
+            .chunks(max_headers_per_record::<SegmentHeader>())
+        {
+            backup_writer
+                .write_storage_item(&StorageItemTemporary::SegmentHeaders(
+                    StorageItemSegmentHeaders {
+                        segment_headers: segment_headers.to_vec(),
+                    },
+                ))
+                .await?;
+        }
+
+        for super_segment_headers in state
+            .super_segment_headers_cache
+            .super_segment_headers_cache
+            .chunks(max_headers_per_record::<SuperSegmentHeader>())
+        {
+            backup_writer
+                .write_storage_item(&StorageItemTemporary::SuperSegmentHeaders(
+                    StorageItemTemporarySuperSegmentHeaders {
+                        super_segment_headers: super_segment_headers.to_vec(),
+                    },
+                ))
+                .await?;
+        }
+
+        {
+            let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+            // Blocks are written from the oldest to the newest, with the canonical block written
+            // last at every block number, such that the best block is the last one read when the
+            // restored database is opened
+            for block_forks in state.data.blocks.iter().rev() {
+                for block in block_forks.iter().rev() {
+                    let storage_item_block = match block {
+                        ClientDatabaseBlock::InMemory {
+                            fork_ordinal,
+                            block,
+                            block_details,
+                            beacon_chain_block_details: _,
+                        } => StorageItemTemporaryBlock {
+                            header: block.header().buffer().clone(),
+                            body: block.body().buffer().clone(),
+                            mmr_with_block: Arc::clone(&block_details.mmr_with_block),
+                            system_contract_states: StdArc::clone(
+                                &block_details.system_contract_states,
+                            ),
+                            fork_ordinal: *fork_ordinal,
+                            compressed: None,
+                        },
+                        ClientDatabaseBlock::Persisted { write_location, .. }
+                        | ClientDatabaseBlock::PersistedConfirmed { write_location, .. } => {
+                            // Confirmed blocks don't retain their state in memory, so blocks are
+                            // always read back from storage in full
+                            match storage_backend_adapter
+                                .read_storage_item::<StorageItemTemporary>(*write_location)
+                                .await?
+                            {
+                                StorageItemTemporary::Block(storage_item_block) => {
+                                    storage_item_block
+                                }
+                                storage_item => {
+                                    return Err(
+                                        StorageItemCorruptionError::UnexpectedStorageItemType {
+                                            page_offset: write_location.page_offset,
+                                            expected: "Block",
+                                            actual: storage_item.type_name(),
+                                        }
+                                        .into(),
+                                    );
+                                }
+                            }
+                        }
+                    };
+
+                    backup_writer
+                        .write_storage_item(&StorageItemTemporary::Block(storage_item_block))
+                        .await?;
+                }
+            }
+        }
+
+        for (block_root, entries) in &state.data.block_aux_data {
+            for (namespace, data) in entries {
+                backup_writer
+                    .write_storage_item(&StorageItemTemporary::BlockAuxData(
+                        StorageItemTemporaryBlockAuxData {
+                            block_root: *block_root,
+                            namespace: *namespace,
+                            data: data.clone(),
+                        },
+                    ))
+                    .await?;
+            }
+        }
+
+        for (block_root, outcome) in &state.data.block_outcomes {
+            backup_writer
+                .write_storage_item(&StorageItemTemporary::BlockOutcome(
+                    StorageItemTemporaryBlockOutcome {
+                        block_root: *block_root,
+                        outcome: outcome.clone(),
+                    },
+                ))
+                .await?;
+        }
+
+        // TODO: Include contract slots in backups
+        backup_writer.finish().await?;
+
+        Ok(())
+    }
+
+    /// Flush the database periodically according to [`DurabilityPolicy::Periodic`].
+    ///
+    /// Returns immediately if a different durability policy is used, otherwise only returns on
+    /// flush failure. Must be polled for [`DurabilityPolicy::Periodic`] to have any effect.
+    pub async fn run_periodic_flush(&self) -> io::Result<()> {
+        let DurabilityPolicy::Periodic { interval } = self.inner.options.durability_policy else {
+            return Ok(());
+        };
+
+        loop {
+            Delay::new(interval).await;
+
+            self.flush().await?;
+        }
+    }
+
+    /// Find a block in storage, including blocks that are no longer retained by the database (like
+    /// blocks on pruned forks), but whose storage items were not reclaimed by compaction yet.
+    ///
+    /// Known blocks are returned the same way as with [`ChainInfo::block()`]. Otherwise, temporary
+    /// page groups are scanned from newest to oldest, with page groups that definitely don't
+    /// contain the block skipped when [`ClientDatabaseOptions::block_roots_filters`] is enabled.
+    /// This is an expensive operation that is only meant for rare lookups.
+    pub async fn find_stored_block(
+        &self,
+        block_root: &BlockRoot,
+    ) -> Result<Option<Block>, ReadBlockError> {
+        match self.block(block_root).await {
+            Ok(block) => {
+                return Ok(Some(block));
+            }
+            Err(ReadBlockError::UnknownBlockRoot) => {
+                // Not retained, check storage below
+            }
+            Err(error) => {
+                return Err(error);
+            }
+        }
+
+        let state = self.inner.state.read().await;
+        // Holding a read lock prevents page groups from being freed while they are being read
+        let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+        for page_group in storage_backend_adapter.temporary_page_groups_with_block_root(block_root)
+        {
+            let storage_items = storage_backend_adapter
+                .read_page_group::<StorageItemTemporary>(page_group)
+                .await?;
+
+            for (storage_item, _write_location) in storage_items {
+                let StorageItemTemporary::Block(storage_item_block) = storage_item else {
+                    continue;
+                };
+
+                if Self::stored_block_root(&storage_item_block).as_ref() == Some(block_root) {
+                    return Block::from_buffers(storage_item_block.header, storage_item_block.body)
+                        .map(Some)
+                        .ok_or(ReadBlockError::FailedToDecode);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Compact inactive temporary page groups.
+    ///
+    /// Live storage items of page groups where they occupy at most
+    /// [`CompactionOptions::max_live_percentage`] of used pages are written again (ending up in the
+    /// active page group), after which the original page group is freed for reuse. This prevents
+    /// a few long-lived storage items from holding otherwise unused page groups indefinitely.
+    ///
+    /// Compaction only copies storage items, originals are freed only after new copies are
+    /// flushed to durable storage. An interruption at any point results in at most duplicate
+    /// storage items, which are deduplicated when the database is opened.
+    ///
+    /// Reads and writes are throttled according to [`CompactionOptions::max_pages_per_second`].
+    pub async fn compact(&self) -> io::Result<()> {
+        let inactive_page_groups = {
+            let state = self.inner.state.read().await;
+            let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+            storage_backend_adapter.inactive_temporary_page_groups()
+        };
+
+        for page_group in inactive_page_groups {
+            self.compact_page_group(page_group).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Compact the database periodically according to [`CompactionOptions::interval`], see
+    /// [`Self::compact()`] for details.
+    ///
+    /// Only returns on compaction failure.
+    pub async fn run_compaction(&self) -> io::Result<()> {
+        loop {
+            Delay::new(self.inner.options.compaction.interval).await;
+
+            self.compact().await?;
+        }
+    }
+
+    /// Reclaim inactive temporary page groups whose storage items are all outdated.
+    ///
+    /// Storage items become outdated when blocks are pruned, either because they belong to pruned
+    /// forks or are older than [`ReclamationOptions::retained_blocks`]. Unlike [`Self::compact()`],
+    /// nothing is written, page groups without live storage items are freed for reuse right away.
+    ///
+    /// Reads are throttled according to [`CompactionOptions::max_pages_per_second`].
+    pub async fn reclaim(&self) -> io::Result<()> {
+        let inactive_page_groups = {
+            let state = self.inner.state.read().await;
+            let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+            storage_backend_adapter.inactive_temporary_page_groups()
+        };
+
+        for page_group in inactive_page_groups {
+            let storage_items = {
+                let state = self.inner.state.read().await;
+                let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+                if !storage_backend_adapter.is_inactive_temporary_page_group(page_group) {
+                    continue;
+                }
+
+                storage_backend_adapter
+                    .read_page_group::<StorageItemTemporary>(page_group)
+                    .await?
+            };
+            self.inner
+                .options
+                .compaction
+                .throttle(page_group.num_pages)
+                .await;
+
+            let has_pending_block_mmr_leaves = {
+                let state = self.inner.state.read().await;
+                let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+                let has_live_storage_items =
+                    storage_items.iter().any(|(storage_item, write_location)| {
+                        Self::is_temporary_storage_item_live(
+                            &state,
+                            &storage_backend_adapter,
+                            storage_item,
+                            *write_location,
+                        )
+                    });
+                if has_live_storage_items {
+                    continue;
+                }
+
+                state.data.block_mmr_leaves.has_pending()
+            };
+            // The page group might contain pruned blocks whose roots were not persisted yet, they
+            // would be lost after restart otherwise. Storage items that are no longer live never
+            // become live again, so there is no need to check them again afterward.
+            if has_pending_block_mmr_leaves {
+                self.persist_block_mmr_leaves().await?;
+            }
+
+            let state = self.inner.state.read().await;
+            let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+
+            // Page group might have been freed by compaction concurrently
+            if !storage_backend_adapter.is_inactive_temporary_page_group(page_group) {
+                continue;
+            }
+
+            storage_backend_adapter
+                .free_temporary_page_group(page_group)
+                .await?;
+
+            debug!(
+                first_page_offset = page_group.first_page_offset,
+                "Reclaimed page group"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Reclaim page groups periodically according to [`ReclamationOptions::interval`], see
+    /// [`Self::reclaim()`] for details.
+    ///
+    /// Only returns on reclamation failure.
+    pub async fn run_reclamation(&self) -> io::Result<()> {
+        loop {
+            Delay::new(self.inner.options.reclamation.interval).await;
+
+            self.reclaim().await?;
+        }
+    }
+
+    /// Persist roots of pruned blocks as leaves of the block MMR.
+    ///
+    /// Must be called before page groups with pruned blocks are freed, such that inclusion proofs
+    /// can still be generated for them after restart. Roots are accumulated until then to amortize
+    /// the cost of storing MMR peaks.
+    async fn persist_block_mmr_leaves(&self) -> io::Result<()> {
+        if !self
+            .inner
+            .state
+            .read()
+            .await
+            .data
+            .block_mmr_leaves
+            .has_pending()
+        {
+            return Ok(());
+        }
+
+        let mut state = self.inner.state.write().await;
+        let state = &mut *state;
+        let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+
+        while let Some(storage_item) = state.data.block_mmr_leaves.pending_storage_item() {
+            let peaks = storage_item.peaks;
+            storage_backend_adapter
+                .write_storage_item(StorageItemPermanent::BlockMmr(Box::new(storage_item)))
+                .await?;
+            state.data.block_mmr_leaves.mark_persisted(&peaks);
+        }
+
+        Ok(())
+    }
+
+    async fn compact_page_group(&self, page_group: InactivePageGroup) -> io::Result<()> {
+        let compaction = &self.inner.options.compaction;
+
+        let storage_items = {
+            let state = self.inner.state.read().await;
+            let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+            // Page group might have been reclaimed concurrently
+            if !storage_backend_adapter.is_inactive_temporary_page_group(page_group) {
+                return Ok(());
+            }
+
+            storage_backend_adapter
+                .read_page_group::<StorageItemTemporary>(page_group)
+                .await?
+        };
+        compaction.throttle(page_group.num_pages).await;
+
+        let (live_storage_items, has_free_page_groups) = {
+            let state = self.inner.state.read().await;
+            let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+            let live_storage_items = storage_items
+                .into_iter()
+                .filter(|(storage_item, write_location)| {
+                    Self::is_temporary_storage_item_live(
+                        &state,
+                        &storage_backend_adapter,
+                        storage_item,
+                        *write_location,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let has_free_page_groups = storage_backend_adapter.has_free_page_groups();
+
+            (live_storage_items, has_free_page_groups)
+        };
+
+        let live_pages = live_storage_items
+            .iter()
+            .map(|(_storage_item, write_location)| u64::from(write_location.num_pages))
+            .sum::<u64>();
+        // `-1` accounts for the page group header
+        let used_pages = u64::from(page_group.num_pages - 1);
+        if live_pages * 100 > used_pages * u64::from(compaction.max_live_percentage) {
+            return Ok(());
+        }
+        // Live storage items always fit into a free page group, but there might not be enough
+        // space for them in the active page group
+        if live_pages > 0 && !has_free_page_groups {
+            debug!(
+                first_page_offset = page_group.first_page_offset,
+                "Not enough free space to compact page group"
+            );
+            return Ok(());
+        }
+
+        for (storage_item, write_location) in live_storage_items {
+            // Upgradable read lock allows reads, while preventing concurrent modifications of
+            // blocks and their auxiliary data
+            let state = self.inner.state.upgradable_read().await;
+
+            let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+
+            // The storage item might have become outdated since the check above
+            if !Self::is_temporary_storage_item_live(
+                &state,
+                &storage_backend_adapter,
+                &storage_item,
+                write_location,
+            ) {
+                continue;
+            }
+
+            let maybe_block_root = match &storage_item {
+                StorageItemTemporary::Block(storage_item_block) => {
+                    Self::stored_block_root(storage_item_block)
+                }
+                StorageItemTemporary::SegmentHeaders(_)
+                | StorageItemTemporary::SuperSegmentHeaders(_)
+                | StorageItemTemporary::BlockAuxData(_)
+                | StorageItemTemporary::BlockRootsFilter(_)
+                | StorageItemTemporary::BlockOutcome(_) => None,
+            };
+
+            let new_write_location = storage_backend_adapter
+                .write_storage_item(storage_item)
+                .await?;
+            if let Some(block_root) = &maybe_block_root {
+                storage_backend_adapter.add_block_root(new_write_location, block_root);
+            }
+            drop(storage_backend_adapter);
+
+            if let Some(block_root) = maybe_block_root {
+                let mut state = RwLockUpgradableReadGuard::upgrade(state).await;
+
+                if let Some(
+                    ClientDatabaseBlock::Persisted { write_location, .. }
+                    | ClientDatabaseBlock::PersistedConfirmed { write_location, .. },
+                ) = Self::find_block_mut(&mut state, &block_root)
+                {
+                    *write_location = new_write_location;
+                }
+            }
+
+            compaction.throttle(new_write_location.num_pages).await;
+        }
+
+        // The page group might contain pruned blocks whose roots were not persisted yet, they
+        // would be lost after restart otherwise
+        self.persist_block_mmr_leaves().await?;
+
+        {
+            // All readers of relocated storage items are holding a read lock, hence they are
+            // guaranteed to be using new write locations at this point
+            let state = self.inner.state.read().await;
+            let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+
+            // Page group might have been reclaimed concurrently
+            if !storage_backend_adapter.is_inactive_temporary_page_group(page_group) {
+                return Ok(());
+            }
+
+            storage_backend_adapter
+                .free_temporary_page_group(page_group)
+                .await?;
+        }
+        compaction.throttle(page_group.num_pages).await;
+
+        debug!(
+            first_page_offset = page_group.first_page_offset,
+            live_pages, used_pages, "Compacted page group"
+        );
+
+        Ok(())
+    }
+
+    /// Check whether a temporary storage item at the specified write location is still in use
+    fn is_temporary_storage_item_live(
+        state: &State<Block, StorageBackend>,
+        storage_backend_adapter: &StorageBackendAdapter<StorageBackend>,
+        storage_item: &StorageItemTemporary,
+        write_location: WriteLocation,
+    ) -> bool {
+        match storage_item {
+            StorageItemTemporary::Block(storage_item_block) => {
+                let Some(block_root) = Self::stored_block_root(storage_item_block) else {
+                    return false;
+                };
+
+                // A block might have been relocated already, only the current copy is live
+                Self::find_block(state, &block_root).is_some_and(|block| {
+                    matches!(
+                        block.full_block(),
+                        FullBlock::Persisted {
+                            write_location: block_write_location,
+                            ..
+                        } if block_write_location.page_offset == write_location.page_offset
+                    )
+                })
+            }
+            // Segment headers and super segment headers are never pruned
+            StorageItemTemporary::SegmentHeaders(_)
+            | StorageItemTemporary::SuperSegmentHeaders(_) => true,
+            StorageItemTemporary::BlockAuxData(block_aux_data) => state
+                .data
+                .block_aux_data
+                .get(&block_aux_data.block_root)
+                .is_some_and(|entries| {
+                    entries.iter().any(|(namespace, data)| {
+                        *namespace == block_aux_data.namespace
+                            && data.as_slice() == block_aux_data.data.as_slice()
+                    })
+                }),
+            StorageItemTemporary::BlockRootsFilter(block_roots_filter) => {
+                storage_backend_adapter.is_block_roots_filter_live(block_roots_filter)
+            }
+            StorageItemTemporary::BlockOutcome(block_outcome) => state
+                .data
+                .block_outcomes
+                .get(&block_outcome.block_root)
+                .is_some_and(|outcome| block_outcome.has_outcome(outcome)),
+        }
+    }
+
+    /// Read persisted block from the storage backend and add its body to the cache
+    async fn read_persisted_block(
+        &self,
+        state: &State<Block, StorageBackend>,
+        header: &Block::Header,
+        write_location: WriteLocation,
+    ) -> Result<Block, ReadBlockError> {
+        let storage_backend_adapter = state.storage_backend_adapter.read().await;
+
+        let storage_item = storage_backend_adapter
+            .read_storage_item::<StorageItemTemporary>(write_location)
+            .await?;
+
+        let storage_item_block = match storage_item {
+            StorageItemTemporary::Block(storage_item_block) => storage_item_block,
+            storage_item => {
+                return Err(StorageItemCorruptionError::UnexpectedStorageItemType {
+                    page_offset: write_location.page_offset,
+                    expected: "Block",
+                    actual: storage_item.type_name(),
+                }
+                .into());
+            }
+        };
+
+        let StorageItemTemporaryBlock {
+            header: stored_header,
+            body,
+            mmr_with_block: _,
+            system_contract_states: _,
+            fork_ordinal: _,
+            compressed: _,
+        } = storage_item_block;
+
+        if stored_header.as_slice() != header.buffer().as_slice() {
+            return Err(StorageItemCorruptionError::UnexpectedContents {
+                page_offset: write_location.page_offset,
+            }
+            .into());
+        }
+
+        self.inner
+            .block_body_cache
+            .insert(write_location, body.clone());
+
+        Block::from_buffers(header.buffer().clone(), body).ok_or(ReadBlockError::FailedToDecode)
+    }
+
+    fn stored_block_root(storage_item_block: &StorageItemTemporaryBlock) -> Option<BlockRoot> {
+        let header = Block::Header::from_buffer(storage_item_block.header.clone()).ok()?;
+
+        Some(*header.header().root())
+    }
+
+    /// Canonical block at `block_number`.
+    ///
+    /// The first entry among block forks at every block number is the canonical block, which
+    /// makes `blocks` the index of the canonical chain by block number. It covers both unconfirmed
+    /// and confirmed blocks (stored in page groups) and is reconstructed from persisted blocks on
+    /// database opening.
+    fn canonical_block(
+        state: &State<Block, StorageBackend>,
+        block_number: BlockNumber,
+    ) -> Option<&ClientDatabaseBlock<Block>> {
+        let best_number = state.best_tip().number;
+        let block_offset = u64::from(best_number.checked_sub(block_number)?) as usize;
+
+        state.data.blocks.get(block_offset)?.first()
+    }
+
+    fn find_block<'a>(
+        state: &'a State<Block, StorageBackend>,
+        block_root: &BlockRoot,
+    ) -> Option<&'a ClientDatabaseBlock<Block>> {
+        let block_number = *state.data.block_roots.get(block_root)?;
+        let block_offset = u64::from(state.best_tip().number.checked_sub(block_number)?) as usize;
+
+        state
+            .data
+            .blocks
+            .get(block_offset)?
+            .iter()
+            .find(|block| &*block.header().header().root() == block_root)
+    }
+
+    fn find_block_mut<'a>(
+        state: &'a mut State<Block, StorageBackend>,
+        block_root: &BlockRoot,
+    ) -> Option<&'a mut ClientDatabaseBlock<Block>> {
+        let block_number = *state.data.block_roots.get(block_root)?;
+        let block_offset = u64::from(state.best_tip().number.checked_sub(block_number)?) as usize;
+
+        state
+            .data
+            .blocks
+            .get_mut(block_offset)?
+            .iter_mut()
+            .find(|block| &*block.header().header().root() == block_root)
+    }
+
+    /// Stable position of a known block, see [`BlockPosition`] for details
+    pub fn block_position(&self, block_root: &BlockRoot) -> Option<BlockPosition> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+        let block = Self::find_block(&state, block_root)?;
+
+        Some(BlockPosition {
+            number: block.header().header().prefix.number,
+            fork_ordinal: block.fork_ordinal(),
+        })
+    }
+
+    /// Root of a known block at the specified position, see [`BlockPosition`] for details
+    pub fn block_root_at(&self, position: BlockPosition) -> Option<BlockRoot> {
+        self.block_forks(position.number)
+            .into_iter()
+            .find_map(|(block_position, block_root)| {
+                (block_position == position).then_some(block_root)
+            })
+    }
+
+    /// Positions and roots of all known blocks with the specified number.
+    ///
+    /// The canonical block comes first, followed by the rest of the blocks sorted by fork ordinal.
+    /// As a result, the order only depends on the blocks that were added to the database and the
+    /// current best block, but not on the order of reorgs that happened in between.
+    pub fn block_forks(&self, block_number: BlockNumber) -> Vec<(BlockPosition, BlockRoot)> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+        let Some(block_offset) = state.best_tip().number.checked_sub(block_number) else {
+            return Vec::new();
+        };
+        let Some(block_forks) = state.data.blocks.get(u64::from(block_offset) as usize) else {
+            return Vec::new();
+        };
+
+        block_forks
+            .iter()
+            .map(|block| {
+                let position = BlockPosition {
+                    number: block_number,
+                    fork_ordinal: block.fork_ordinal(),
+                };
 
-            state_data.fork_tips.push_front(ForkTip {
-                number: block_number,
-                root: block_root,
-            });
-            state_data.block_roots.insert(block_root, block_number);
-            let beacon_chain_block_details =
-                <dyn Any>::downcast_ref::<OwnedBeaconChainBlock>(&block)
-                    .map(|block| BeaconChainBlockDetails::from_body(block.body.body()));
-            state_data
-                .blocks
-                .push_front(smallvec![ClientDatabaseBlock::InMemory {
-                    block,
-                    block_details: BlockDetails {
-                        system_contract_states,
-                        mmr_with_block: Arc::new({
-                            let mut mmr = BlockMerkleMountainRange::new();
-                            mmr.add_leaf(&block_root);
-                            mmr
-                        })
-                    },
-                    beacon_chain_block_details,
-                }]);
+                (position, *block.header().header().root())
+            })
+            .collect()
+    }
+
+    /// Create a cheap consistent read view of the database at the current generation of the
+    /// canonical chain, see [`ClientDatabaseSnapshot`] for details
+    pub fn snapshot(&self) -> ClientDatabaseSnapshot<Block, StorageBackend> {
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let state = self.inner.state.read_blocking();
+        let best_tip = state.best_tip();
+
+        ClientDatabaseSnapshot {
+            database: self.clone(),
+            generation: state.data.generation,
+            best_number: best_tip.number,
+            best_root: best_tip.root,
+            canonical_headers: StdArc::clone(&state.data.canonical_headers),
+            segment_headers_cache: state.segment_headers_cache.clone(),
+            super_segment_headers_cache: state.super_segment_headers_cache.clone(),
         }
+    }
 
-        let state = State {
-            data: state_data,
-            segment_headers_cache,
-            super_segment_headers_cache,
-            storage_backend_adapter: AsyncRwLock::new(storage_backend_adapter),
-        };
+    /// Reader of contract state as of the block with `block_root`, see [`StateReader`] for
+    /// details
+    pub fn state_at(
+        &self,
+        block_root: &BlockRoot,
+    ) -> Result<StateReader<Block, StorageBackend>, ReadContractSlotError> {
+        // Hold is placed before checking the block, such that it can't be pruned in between
+        let pruning_hold = self.hold_block_roots("state-reader", [*block_root]);
 
-        let inner = Inner {
-            state: AsyncRwLock::new(state),
-            options,
-        };
+        // Blocking read lock is fine because where a write lock is only taken for a short time and
+        // most locks are read locks
+        let block_number = *self
+            .inner
+            .state
+            .read_blocking()
+            .data
+            .block_roots
+            .get(block_root)
+            .ok_or(ReadContractSlotError::UnknownBlockRoot)?;
 
-        Ok(Self {
-            inner: Arc::new(inner),
+        Ok(StateReader {
+            database: self.clone(),
+            block_number,
+            block_root: *block_root,
+            _pruning_hold: pruning_hold,
         })
     }
 
-    /// Format a new database
-    pub async fn format(
-        storage_backend: &StorageBackend,
-        options: ClientDatabaseFormatOptions,
-    ) -> Result<(), ClientDatabaseFormatError> {
-        StorageBackendAdapter::format(storage_backend, options).await
+    /// Place a hold on blocks with block numbers within `block_range`, preventing their pruning
+    /// until the returned hold is dropped, see [`pruning_holds`] module for details.
+    ///
+    /// `owner` is the name of the component placing the hold, used for diagnostics.
+    pub fn hold_block_range(
+        &self,
+        owner: &'static str,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> PruningHold {
+        self.inner
+            .pruning_holds
+            .add(owner, PruningHoldTarget::BlockRange(block_range))
+    }
+
+    /// Place a hold on blocks with specified roots, preventing their pruning until the returned
+    /// hold is dropped, see [`pruning_holds`] module for details.
+    ///
+    /// `owner` is the name of the component placing the hold, used for diagnostics.
+    pub fn hold_block_roots<I>(&self, owner: &'static str, block_roots: I) -> PruningHold
+    where
+        I: IntoIterator<Item = BlockRoot>,
+    {
+        self.inner.pruning_holds.add(
+            owner,
+            PruningHoldTarget::BlockRoots(block_roots.into_iter().collect()),
+        )
+    }
+
+    /// Currently active pruning holds
+    pub fn pruning_holds(&self) -> Vec<PruningHoldInfo> {
+        self.inner.pruning_holds.list()
+    }
+
+    /// Register database metrics in the registry.
+    ///
+    /// Metrics are collected regardless, registration only exposes them. Must be called at most
+    /// once per registry.
+    pub fn register_metrics(&self, registry: &mut Registry) {
+        self.inner.metrics.register(registry);
+    }
+
+    /// Subscribe to events about changes of the canonical chain and its forks.
+    ///
+    /// Events are emitted after the corresponding changes are applied to the database. With
+    /// [`BufferingPolicy::Backpressure`] persisting of blocks waits for the subscriber to make
+    /// space in its buffer, so such subscriptions must be polled regularly.
+    pub fn subscribe_chain_events(
+        &self,
+        policy: BufferingPolicy,
+    ) -> Subscription<ChainEventsTopic> {
+        self.inner.notification_bus.subscribe(policy)
+    }
+
+    async fn publish_chain_events<I>(&self, events: I)
+    where
+        I: IntoIterator<Item = ChainEvent>,
+    {
+        Self::publish_chain_events_with(&self.inner.notification_bus, events).await;
+    }
+
+    /// Publish chain events, must not be called while holding the state lock since subscribers
+    /// might read from the database before making space in their buffers
+    async fn publish_chain_events_with<I>(notification_bus: &NotificationBus, events: I)
+    where
+        I: IntoIterator<Item = ChainEvent>,
+    {
+        for event in events {
+            notification_bus.publish::<ChainEventsTopic>(event).await;
+        }
     }
 
     fn insert_first_block(state: &mut StateData<Block>, block: Block, block_details: BlockDetails) {
@@ -1730,67 +4083,289 @@ where
         state.block_roots.clear();
         state.block_roots.insert(block_root, block_number);
         state.blocks.clear();
+        state.next_fork_ordinals.clear();
+        state.block_aux_data.clear();
+        state.block_outcomes.clear();
+        if let Some(transaction_index) = &mut state.transaction_index {
+            transaction_index.clear();
+            transaction_index.add_block(block_root, block.body());
+        }
+        state.contract_slots = ContractSlots::default();
+        state.discarded_contract_slots.clear();
         let beacon_chain_block_details = <dyn Any>::downcast_ref::<OwnedBeaconChainBlock>(&block)
             .map(|block| BeaconChainBlockDetails::from_body(block.body.body()));
         state
             .blocks
             .push_front(smallvec![ClientDatabaseBlock::InMemory {
+                fork_ordinal: Self::allocate_fork_ordinal(
+                    &mut state.next_fork_ordinals,
+                    block_number,
+                ),
                 block,
                 block_details,
                 beacon_chain_block_details,
             }]);
+        state.generation += 1;
+        state.update_canonical_headers();
     }
 
-    async fn insert_new_best_block(
-        mut state: AsyncRwLockWriteGuard<'_, State<Block, StorageBackend>>,
-        inner: &Inner<Block, StorageBackend>,
-        block: Block,
-        block_details: BlockDetails,
-    ) -> Result<(), PersistBlockError> {
-        let header = block.header().header();
-        let block_number = header.prefix.number;
-        let block_root = *header.root();
-        let parent_root = header.prefix.parent_root;
+    /// Insert auxiliary data of a block, replacing previous data in the same namespace (if any)
+    fn insert_block_aux_data(
+        state: &mut StateData<Block>,
+        block_root: BlockRoot,
+        namespace: BlockAuxDataNamespace,
+        data: SharedAlignedBuffer,
+    ) {
+        let entries = state.block_aux_data.entry(block_root).or_default();
 
-        // Adjust the relative order of forks to ensure the first index always corresponds to
-        // ancestors of the new best block
-        if !Self::adjust_ancestor_block_forks(&mut state.data.blocks, parent_root) {
-            return Err(PersistBlockError::MissingParent);
+        if let Some((_namespace, existing_data)) = entries
+            .iter_mut()
+            .find(|(entry_namespace, _data)| *entry_namespace == namespace)
+        {
+            *existing_data = data;
+        } else {
+            entries.push((namespace, data));
         }
+    }
 
-        // Store new block in the state
-        {
-            for (index, fork_tip) in state.data.fork_tips.iter_mut().enumerate() {
-                // Block's parent is no longer a fork tip, remove it
-                if fork_tip.root == parent_root {
-                    state.data.fork_tips.remove(index);
-                    break;
+    /// Insert a block read from the database while opening it.
+    ///
+    /// Blocks must be inserted in the order of increasing block numbers.
+    fn insert_stored_block(
+        state_data: &mut StateData<Block>,
+        stored_block: StoredBlock<Block>,
+        options: &ClientDatabaseInnerOptions,
+    ) -> Result<(), ClientDatabaseError> {
+        let StoredBlock {
+            header,
+            block_details,
+            beacon_chain_block_details,
+            write_location,
+            fork_ordinal,
+        } = stored_block;
+        let page_offset = write_location.page_offset;
+
+        let block_root = *header.header().root();
+        let block_number = header.header().prefix.number;
+
+        state_data.block_roots.insert(block_root, block_number);
+
+        let maybe_best_number = state_data
+            .blocks
+            .front()
+            .and_then(|block_forks| block_forks.first())
+            .map(|best_block| {
+                // Type inference is not working here for some reason
+                let header: &Block::Header = best_block.header();
+
+                header.header().prefix.number
+            });
+
+        let block_offset = if let Some(best_number) = maybe_best_number {
+            if block_number <= best_number {
+                u64::from(best_number - block_number) as usize
+            } else {
+                // The new best block must follow the previous best block
+                if block_number - best_number != BlockNumber::ONE {
+                    error!(
+                        %page_offset,
+                        %best_number,
+                        %block_number,
+                        "Invalid new best block number, it must be only one block higher than \
+                        the best block"
+                    );
+
+                    return Err(ClientDatabaseError::InvalidBlock { page_offset });
                 }
+
+                state_data.blocks.push_front(SmallVec::new());
+                // Will insert a new block at the front
+                0
             }
+        } else {
+            state_data.blocks.push_front(SmallVec::new());
+            // Will insert a new block at the front
+            0
+        };
 
-            state.data.fork_tips.push_front(ForkTip {
-                number: block_number,
-                root: block_root,
-            });
-            state.data.block_roots.insert(block_root, block_number);
-            let beacon_chain_block_details =
-                <dyn Any>::downcast_ref::<OwnedBeaconChainBlock>(&block)
-                    .map(|block| BeaconChainBlockDetails::from_body(block.body.body()));
-            state
-                .data
-                .blocks
-                .push_front(smallvec![ClientDatabaseBlock::InMemory {
-                    block,
-                    block_details: block_details.clone(),
-                    beacon_chain_block_details,
-                }]);
+        let Some(block_forks) = state_data.blocks.get_mut(block_offset) else {
+            // Ignore the older block, other blocks at its height were already pruned anyway
+            return Ok(());
+        };
+
+        // Ordinals of blocks that were never persisted are not reused if possible
+        let next_fork_ordinal = state_data
+            .next_fork_ordinals
+            .entry(block_number)
+            .or_default();
+        *next_fork_ordinal = (*next_fork_ordinal).max(fork_ordinal.saturating_add(1));
+
+        // Push a new block to the end of the list, we'll fix it up later
+        block_forks.push(ClientDatabaseBlock::Persisted {
+            fork_ordinal,
+            header,
+            block_details,
+            beacon_chain_block_details,
+            write_location,
+        });
+
+        // If a new block was inserted, confirm a new canonical block to prune extra in-memory
+        // information
+        if block_offset == 0 && block_forks.len() == 1 {
+            // There can't be any subscribers to chain events while the database is being opened
+            let _confirmed: bool =
+                Self::confirm_canonical_block(block_number, state_data, options, &mut Vec::new());
         }
 
+        Ok(())
+    }
+
+    /// Insert blocks that become the new best block one after another.
+    ///
+    /// Soft-confirmed blocks are persisted in as few passes as possible while holding the lock,
+    /// such that no block is confirmed before it is persisted. Returns chain events that need to be
+    /// published after the lock is released.
+    async fn insert_new_best_blocks(
+        mut state: AsyncRwLockWriteGuard<'_, State<Block, StorageBackend>>,
+        inner: &Inner<Block, StorageBackend>,
+        blocks: Vec<(Block, BlockDetails)>,
+    ) -> Result<Vec<ChainEvent>, PersistBlockError> {
         let options = &inner.options;
+        // Blocks that were not soft-confirmed during the previous pass must not reach the
+        // confirmation depth before the next pass
+        let max_blocks_per_pass = (u64::from(options.block_confirmation_depth)
+            - u64::from(options.soft_confirmation_depth))
+            as usize
+            + 1;
+
+        let mut chain_events = Vec::with_capacity(blocks.len());
+        let mut confirmed = false;
+        let mut blocks = blocks.into_iter().peekable();
+        while blocks.peek().is_some() {
+            for (block, block_details) in blocks.by_ref().take(max_blocks_per_pass) {
+                let header = block.header().header();
+                let block_number = header.prefix.number;
+                let block_root = *header.root();
+                let parent_root = header.prefix.parent_root;
+                let old_best_root = state.best_tip().root;
+
+                // Adjust the relative order of forks to ensure the first index always corresponds
+                // to ancestors of the new best block
+                if !Self::adjust_ancestor_block_forks(&mut state.data.blocks, parent_root) {
+                    return Err(PersistBlockError::MissingParent);
+                }
+
+                // Store new block in the state
+                {
+                    for (index, fork_tip) in state.data.fork_tips.iter_mut().enumerate() {
+                        // Block's parent is no longer a fork tip, remove it
+                        if fork_tip.root == parent_root {
+                            state.data.fork_tips.remove(index);
+                            break;
+                        }
+                    }
+
+                    state.data.fork_tips.push_front(ForkTip {
+                        number: block_number,
+                        root: block_root,
+                    });
+                    state.data.block_roots.insert(block_root, block_number);
+                    if let Some(transaction_index) = &mut state.data.transaction_index {
+                        transaction_index.add_block(block_root, block.body());
+                    }
+                    let beacon_chain_block_details =
+                        <dyn Any>::downcast_ref::<OwnedBeaconChainBlock>(&block)
+                            .map(|block| BeaconChainBlockDetails::from_body(block.body.body()));
+                    let fork_ordinal = Self::allocate_fork_ordinal(
+                        &mut state.data.next_fork_ordinals,
+                        block_number,
+                    );
+                    state
+                        .data
+                        .blocks
+                        .push_front(smallvec![ClientDatabaseBlock::InMemory {
+                            fork_ordinal,
+                            block,
+                            block_details,
+                            beacon_chain_block_details,
+                        }]);
+                    state.data.generation += 1;
+                    state.data.update_canonical_headers();
+                }
+
+                let mut pruned_block_roots = Vec::new();
+                confirmed |= Self::confirm_canonical_block(
+                    block_number,
+                    &mut state.data,
+                    options,
+                    &mut pruned_block_roots,
+                );
+                Self::prune_outdated_fork_tips(
+                    block_number,
+                    &mut state.data,
+                    options,
+                    &inner.pruning_holds,
+                    &mut pruned_block_roots,
+                );
+
+                chain_events.push(ChainEvent::BestBlockUpdated {
+                    old_best_root,
+                    new_best_number: block_number,
+                    new_best_root: block_root,
+                    reorg: parent_root != old_best_root,
+                });
+                if !pruned_block_roots.is_empty() {
+                    chain_events.push(ChainEvent::ForksPruned {
+                        roots: pruned_block_roots,
+                    });
+                }
+            }
+
+            let flush = blocks.peek().is_none()
+                && confirmed
+                && options.durability_policy == DurabilityPolicy::OnConfirmation;
+            state = Self::persist_soft_confirmed_blocks(state, options, flush).await?;
+        }
+
+        Self::persist_discarded_contract_slots(&mut state).await?;
+        Self::prune_old_blocks(&mut state.data, options, &inner.pruning_holds);
+
+        Self::update_state_metrics(inner, &state.data);
+        drop(state);
+
+        Ok(chain_events)
+    }
+
+    /// Mark contract slots of discarded blocks as such in the storage, such that they are not
+    /// restored after restart
+    async fn persist_discarded_contract_slots(
+        state: &mut State<Block, StorageBackend>,
+    ) -> io::Result<()> {
+        if state.data.discarded_contract_slots.is_empty() {
+            return Ok(());
+        }
+
+        let mut storage_backend_adapter = state.storage_backend_adapter.write().await;
+        for block_root in state.data.discarded_contract_slots.drain(..) {
+            storage_backend_adapter
+                .write_storage_item(StorageItemState::DiscardedContractSlots(
+                    StorageItemStateDiscardedContractSlots { block_root },
+                ))
+                .await?;
+        }
 
-        Self::confirm_canonical_block(block_number, &mut state.data, options);
-        Self::prune_outdated_fork_tips(block_number, &mut state.data, options);
+        Ok(())
+    }
 
+    /// Persist soft-confirmed blocks that are still in memory, optionally flushing the storage
+    /// backend afterward.
+    ///
+    /// The lock is downgraded while writing to allow concurrent reads.
+    async fn persist_soft_confirmed_blocks<'a>(
+        state: AsyncRwLockWriteGuard<'a, State<Block, StorageBackend>>,
+        options: &ClientDatabaseInnerOptions,
+        flush: bool,
+    ) -> Result<AsyncRwLockWriteGuard<'a, State<Block, StorageBackend>>, PersistBlockError> {
         // Convert write lock into upgradable read lock to allow reads, while preventing concurrent
         // block modifications
         // TODO: This assumes both guarantees in https://github.com/smol-rs/async-lock/issues/100
@@ -1810,12 +4385,14 @@ where
                 .filter_map(|(fork_offset, client_database_block)| {
                     match client_database_block {
                         ClientDatabaseBlock::InMemory {
+                            fork_ordinal,
                             block,
                             block_details,
                             beacon_chain_block_details: _,
                         } => Some(BlockToPersist {
                             block_offset,
                             fork_offset,
+                            fork_ordinal: *fork_ordinal,
                             block,
                             block_details,
                         }),
@@ -1842,6 +4419,7 @@ where
                 let BlockToPersist {
                     block_offset,
                     fork_offset,
+                    fork_ordinal,
                     block,
                     block_details,
                 } = block_to_persist;
@@ -1854,8 +4432,12 @@ where
                         system_contract_states: StdArc::clone(
                             &block_details.system_contract_states,
                         ),
+                        fork_ordinal,
+                        compressed: None,
                     }))
                     .await?;
+                storage_backend_adapter
+                    .add_block_root(write_location, &block.header().header().root());
 
                 persisted_blocks.push(PersistedBlock {
                     block_offset,
@@ -1863,6 +4445,14 @@ where
                     write_location,
                 });
             }
+
+            if flush {
+                storage_backend_adapter.flush().await?;
+            } else {
+                // Soft-confirmed blocks are not waited for, but are submitted to the storage
+                // backend without waiting for more writes to coalesce with
+                storage_backend_adapter.submit_coalesced_write().await?;
+            }
         }
 
         // Convert blocks to persisted
@@ -1884,6 +4474,7 @@ where
 
             replace_with_or_abort(block, |block| {
                 if let ClientDatabaseBlock::InMemory {
+                    fork_ordinal,
                     block,
                     block_details,
                     beacon_chain_block_details,
@@ -1892,6 +4483,7 @@ where
                     let (header, _body) = block.split();
 
                     ClientDatabaseBlock::Persisted {
+                        fork_ordinal,
                         header,
                         block_details,
                         beacon_chain_block_details,
@@ -1903,10 +4495,7 @@ where
             });
         }
 
-        // TODO: Prune blocks that are no longer necessary
-        // TODO: Prune unused page groups here or elsewhere?
-
-        Ok(())
+        Ok(state)
     }
 
     /// Adjust the relative order of forks to ensure the first index always corresponds to
@@ -1950,12 +4539,112 @@ where
             let fork_offset;
             (fork_offset, parent_block_root) = fork_offset_parent_block_root;
 
-            parent_blocks.swap(0, fork_offset);
+            if fork_offset != 0 {
+                parent_blocks.swap(0, fork_offset);
+                Self::sort_non_canonical_block_forks(parent_blocks);
+            }
         }
 
         true
     }
 
+    /// Sort forks that do not correspond to the canonical version of the blockchain by fork
+    /// ordinal, such that iteration order only depends on the set of blocks and not on the order of
+    /// reorgs
+    fn sort_non_canonical_block_forks(block_forks: &mut [ClientDatabaseBlock<Block>]) {
+        if let Some(non_canonical_forks) = block_forks.get_mut(1..) {
+            non_canonical_forks.sort_unstable_by_key(ClientDatabaseBlock::fork_ordinal);
+        }
+    }
+
+    /// Allocate fork ordinal for a new block at `block_number`
+    fn allocate_fork_ordinal(
+        next_fork_ordinals: &mut BTreeMap<BlockNumber, u32>,
+        block_number: BlockNumber,
+    ) -> u32 {
+        let next_fork_ordinal = next_fork_ordinals.entry(block_number).or_default();
+        let fork_ordinal = *next_fork_ordinal;
+        *next_fork_ordinal += 1;
+        fork_ordinal
+    }
+
+    /// Update metrics derived from the state, must be called after the state is modified
+    fn update_state_metrics(inner: &Inner<Block, StorageBackend>, state: &StateData<Block>) {
+        // Blocks at and beyond confirmation depth are always persisted
+        let in_memory_blocks = state
+            .blocks
+            .iter()
+            .take(u64::from(inner.options.block_confirmation_depth) as usize)
+            .flatten()
+            .filter(|block| matches!(block, ClientDatabaseBlock::InMemory { .. }))
+            .count();
+
+        inner
+            .metrics
+            .in_memory_blocks
+            .set(i64::try_from(in_memory_blocks).unwrap_or(i64::MAX));
+        inner
+            .metrics
+            .fork_tips
+            .set(i64::try_from(state.fork_tips.len()).unwrap_or(i64::MAX));
+    }
+
+    /// Prune the oldest confirmed blocks beyond [`ReclamationOptions::retained_blocks`], such that
+    /// page groups they are stored in can be reclaimed by [`ClientDatabase::reclaim()`].
+    ///
+    /// Only the oldest blocks are pruned, so pruning stops at the first block covered by a pruning
+    /// hold.
+    fn prune_old_blocks(
+        state_data: &mut StateData<Block>,
+        options: &ClientDatabaseInnerOptions,
+        pruning_holds: &PruningHolds,
+    ) {
+        let Some(retained_blocks) = options.reclamation.retained_blocks else {
+            return;
+        };
+        // `+1` accounts for the best block
+        let max_block_numbers = u64::from(retained_blocks) as usize + 1;
+
+        while state_data.blocks.len() > max_block_numbers
+            && let Some(block_forks) = state_data.blocks.back()
+        {
+            // Confirmed block numbers only have a single (canonical) block
+            let [ClientDatabaseBlock::PersistedConfirmed { header, .. }] = block_forks.as_slice()
+            else {
+                break;
+            };
+            // Type inference is not working here for some reason
+            let header: &Block::Header = header;
+            let header = header.header();
+            let block_number = header.prefix.number;
+            let block_root = *header.root();
+
+            if let Some(owner) = pruning_holds.held_by(block_number, &block_root) {
+                debug!(
+                    %block_number,
+                    %block_root,
+                    %owner,
+                    "Not pruning old block due to pruning hold"
+                );
+                break;
+            }
+
+            state_data.blocks.pop_back();
+            state_data.block_roots.remove(&block_root);
+            state_data.block_aux_data.remove(&block_root);
+            state_data.block_outcomes.remove(&block_root);
+            if let Some(transaction_index) = &mut state_data.transaction_index {
+                transaction_index.remove_block(&block_root);
+            }
+            state_data.contract_slots.prune_block(&block_root);
+            state_data
+                .block_mmr_leaves
+                .add_pruned_block(block_number, block_root);
+        }
+
+        state_data.update_canonical_headers();
+    }
+
     /// Prune outdated fork tips that are too deep and have not been updated for a long time.
     ///
     /// Note that actual headers, blocks and MMRs could remain if they are currently used by
@@ -1966,11 +4655,13 @@ where
         best_number: BlockNumber,
         state: &mut StateData<Block>,
         options: &ClientDatabaseInnerOptions,
+        pruning_holds: &PruningHolds,
+        pruned_block_roots: &mut Vec<BlockRoot>,
     ) {
         let state = &mut *state;
 
-        // These forks are just candidates because they will not be pruned if the reference count is
-        // not 1, indicating they are still in use by something
+        // These forks are just candidates because they will not be pruned if they are covered by
+        // a pruning hold
         let mut candidate_forks_to_remove = Vec::with_capacity(options.max_fork_tips.get());
 
         // Prune forks that are too far away from the best block
@@ -1991,8 +4682,15 @@ where
         }
 
         // Prune all possible candidates
-        candidate_forks_to_remove
-            .retain(|fork_tip| !Self::prune_outdated_fork(best_number, fork_tip, state));
+        candidate_forks_to_remove.retain(|fork_tip| {
+            !Self::prune_outdated_fork(
+                best_number,
+                fork_tip,
+                state,
+                pruning_holds,
+                pruned_block_roots,
+            )
+        });
         // Return those that were not pruned back to the list of tips
         state.fork_tips.extend(candidate_forks_to_remove);
     }
@@ -2004,6 +4702,8 @@ where
         best_number: BlockNumber,
         fork_tip: &ForkTip,
         state: &mut StateData<Block>,
+        pruning_holds: &PruningHolds,
+        pruned_block_roots: &mut Vec<BlockRoot>,
     ) -> bool {
         let block_offset = u64::from(best_number - fork_tip.number) as usize;
 
@@ -2063,8 +4763,15 @@ where
                 break;
             };
 
-            // More than one instance means something somewhere is using or depends on this block
-            if block.header().ref_count() > 1 {
+            // Something still needs this block
+            let block_number = block.header().header().prefix.number;
+            if let Some(owner) = pruning_holds.held_by(block_number, &block_root_to_prune) {
+                debug!(
+                    %block_number,
+                    block_root = %block_root_to_prune,
+                    %owner,
+                    "Pruning of outdated fork block delayed by a hold"
+                );
                 break;
             }
 
@@ -2081,9 +4788,19 @@ where
                 }
             }
 
-            state.block_roots.get_mut(&block_root_to_prune);
+            state.block_roots.remove(&block_root_to_prune);
+            state.block_aux_data.remove(&block_root_to_prune);
+            state.block_outcomes.remove(&block_root_to_prune);
+            if let Some(transaction_index) = &mut state.transaction_index {
+                transaction_index.remove_block(&block_root_to_prune);
+            }
+            if state.contract_slots.discard_block(&block_root_to_prune) {
+                state.discarded_contract_slots.push(block_root_to_prune);
+            }
+            pruned_block_roots.push(block_root_to_prune);
             block_root_to_prune = block.header().header().prefix.parent_root;
-            fork_blocks.swap_remove(fork_offset);
+            // Retain the order of the remaining forks
+            fork_blocks.remove(fork_offset);
 
             pruned_tip = true;
         }
@@ -2092,12 +4809,16 @@ where
     }
 
     /// Confirm a block at confirmation depth k and prune any other blocks at the same depth with
-    /// their descendants
+    /// their descendants.
+    ///
+    /// Roots of pruned blocks are appended to `pruned_block_roots`. Returns `true` if a block was
+    /// confirmed.
     fn confirm_canonical_block(
         best_number: BlockNumber,
         state_data: &mut StateData<Block>,
         options: &ClientDatabaseInnerOptions,
-    ) {
+        pruned_block_roots: &mut Vec<BlockRoot>,
+    ) -> bool {
         // `+1` means it effectively confirms parent blocks instead. This is done to keep the parent
         // of the confirmed block with its MMR in memory due to confirmed blocks not storing their
         // MMRs, which might be needed for reorgs at the lowest possible depth.
@@ -2105,7 +4826,7 @@ where
 
         let Some(fork_blocks) = state_data.blocks.get_mut(block_offset) else {
             // Nothing to confirm yet
-            return;
+            return false;
         };
 
         // Mark the canonical block as confirmed
@@ -2116,7 +4837,7 @@ where
                     block_offset,
                     "Have not found a canonical block to confirm, this is an implementation bug"
                 );
-                return;
+                return false;
             };
 
             replace_with_or_abort(canonical_block, |block| match block {
@@ -2130,11 +4851,13 @@ where
                     block
                 }
                 ClientDatabaseBlock::Persisted {
+                    fork_ordinal,
                     header,
                     block_details: _,
                     beacon_chain_block_details,
                     write_location,
                 } => ClientDatabaseBlock::PersistedConfirmed {
+                    fork_ordinal,
                     header,
                     beacon_chain_block_details,
                     write_location,
@@ -2152,6 +4875,18 @@ where
             });
         }
 
+        // No more blocks can be added at confirmed block numbers
+        let confirmed_block_number = fork_blocks
+            .first()
+            .expect("Canonical block was just confirmed; qed")
+            .header()
+            .header()
+            .prefix
+            .number;
+        state_data.next_fork_ordinals = state_data
+            .next_fork_ordinals
+            .split_off(&(confirmed_block_number + BlockNumber::ONE));
+
         // Prune the rest of the blocks and their descendants
         let mut block_roots_to_prune = fork_blocks
             .drain(1..)
@@ -2167,7 +4902,16 @@ where
             // Prune removed block roots
             for block_root in &block_roots_to_prune {
                 state_data.block_roots.remove(block_root);
+                state_data.block_aux_data.remove(block_root);
+                state_data.block_outcomes.remove(block_root);
+                if let Some(transaction_index) = &mut state_data.transaction_index {
+                    transaction_index.remove_block(block_root);
+                }
+                if state_data.contract_slots.discard_block(block_root) {
+                    state_data.discarded_contract_slots.push(*block_root);
+                }
             }
+            pruned_block_roots.extend_from_slice(&block_roots_to_prune);
 
             // Block offset for direct descendants
             if let Some(next_block_offset) = current_block_offset.checked_sub(1) {
@@ -2192,5 +4936,7 @@ where
                 .map(|block| *block.header().header().root())
                 .collect();
         }
+
+        true
     }
 }
@@ -0,0 +1,87 @@
+//! Metrics for client database, see [`ClientDatabase::register_metrics()`].
+//!
+//! [`ClientDatabase::register_metrics()`]: crate::ClientDatabase::register_metrics
+
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::{Histogram, exponential_buckets};
+use prometheus_client::registry::{Registry, Unit};
+use std::sync::atomic::{AtomicI64, AtomicU64};
+
+/// Metrics for client database.
+///
+/// Metrics are always collected, but only exposed once registered in a registry. All clones share
+/// the same underlying metrics.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientDatabaseMetrics {
+    pub(crate) pages_written: Counter<u64, AtomicU64>,
+    pub(crate) page_groups_recycled: Counter<u64, AtomicU64>,
+    pub(crate) in_memory_blocks: Gauge<i64, AtomicI64>,
+    pub(crate) fork_tips: Gauge<i64, AtomicI64>,
+    pub(crate) read_time: Histogram,
+    pub(crate) write_time: Histogram,
+    pub(crate) write_buffer_occupancy: Gauge<i64, AtomicI64>,
+}
+
+impl Default for ClientDatabaseMetrics {
+    fn default() -> Self {
+        Self {
+            pages_written: Counter::default(),
+            page_groups_recycled: Counter::default(),
+            in_memory_blocks: Gauge::default(),
+            fork_tips: Gauge::default(),
+            read_time: Histogram::new(exponential_buckets(0.000_01, 2.0, 20)),
+            write_time: Histogram::new(exponential_buckets(0.000_01, 2.0, 20)),
+            write_buffer_occupancy: Gauge::default(),
+        }
+    }
+}
+
+impl ClientDatabaseMetrics {
+    /// Register metrics in the registry
+    pub(crate) fn register(&self, registry: &mut Registry) {
+        let registry = registry.sub_registry_with_prefix("database");
+
+        registry.register_with_unit(
+            "pages_written_counter",
+            "Number of pages written to the storage backend",
+            Unit::Other("Pages".to_string()),
+            self.pages_written.clone(),
+        );
+        registry.register_with_unit(
+            "page_groups_recycled_counter",
+            "Number of temporary page groups freed for reuse by compaction or reclamation",
+            Unit::Other("PageGroups".to_string()),
+            self.page_groups_recycled.clone(),
+        );
+        registry.register_with_unit(
+            "in_memory_blocks",
+            "Number of blocks that are not persisted yet",
+            Unit::Other("Blocks".to_string()),
+            self.in_memory_blocks.clone(),
+        );
+        registry.register_with_unit(
+            "fork_tips",
+            "Number of fork tips, including the best block",
+            Unit::Other("Blocks".to_string()),
+            self.fork_tips.clone(),
+        );
+        registry.register_with_unit(
+            "storage_item_read_time",
+            "Time spent reading a storage item",
+            Unit::Seconds,
+            self.read_time.clone(),
+        );
+        registry.register_with_unit(
+            "storage_item_write_time",
+            "Time spent writing a storage item, including waiting for a free write buffer entry",
+            Unit::Seconds,
+            self.write_time.clone(),
+        );
+        registry.register(
+            "write_buffer_occupancy",
+            "Number of write buffer entries with writes that are not known to be finished yet",
+            self.write_buffer_occupancy.clone(),
+        );
+    }
+}
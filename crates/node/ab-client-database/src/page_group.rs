@@ -1,2 +1,5 @@
+pub(crate) mod block_mmr;
 pub(crate) mod permanent;
+pub(crate) mod segment_headers;
+pub(crate) mod state;
 pub(crate) mod temporary;
@@ -0,0 +1,122 @@
+use crate::storage_backend_adapter::storage_item::StorageItemError;
+use ab_client_api::BlockMmrPeaks;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use std::mem::MaybeUninit;
+
+/// Leaves of the Merkle Mountain Range of block roots.
+///
+/// Roots of consecutive blocks on the canonical chain starting with `first_block_number` (which is
+/// also the index of the first leaf), followed by MMR peaks after all of them were added.
+#[derive(Debug)]
+pub(crate) struct StorageItemBlockMmr {
+    pub(crate) first_block_number: BlockNumber,
+    pub(crate) block_roots: Vec<BlockRoot>,
+    pub(crate) peaks: BlockMmrPeaks,
+}
+
+impl StorageItemBlockMmr {
+    /// First block number, number of block roots as `u32` and padding as `u32`
+    const PREFIX_SIZE: usize = BlockNumber::SIZE + size_of::<u32>() * 2;
+
+    fn num_peaks(&self) -> usize {
+        usize::from(self.peaks.num_peaks())
+    }
+
+    pub(crate) fn total_bytes(&self) -> usize {
+        Self::PREFIX_SIZE + BlockRoot::SIZE * (self.num_peaks() + self.block_roots.len())
+    }
+
+    pub(crate) fn write(
+        &self,
+        mut buffer: &mut [MaybeUninit<u8>],
+    ) -> Result<usize, StorageItemError> {
+        // The layout here is as follows:
+        // * first block number
+        // * number of block roots: u32 as little-endian bytes
+        // * padding: u32
+        // * MMR peaks (their number is derived from the number of leaves)
+        // * block roots
+
+        let buffer_len = buffer.len();
+        let total_bytes = self.total_bytes();
+
+        if buffer_len < total_bytes {
+            return Err(StorageItemError::BufferTooSmall {
+                expected: total_bytes,
+                actual: buffer_len,
+            });
+        }
+
+        {
+            let prefix = buffer
+                .split_off_mut(..Self::PREFIX_SIZE)
+                .expect("Total length checked above; qed");
+            let (first_block_number, prefix) = prefix.split_at_mut(BlockNumber::SIZE);
+            let (num_block_roots, padding) = prefix.split_at_mut(size_of::<u32>());
+
+            first_block_number.write_copy_of_slice(&self.first_block_number.to_bytes());
+            num_block_roots.write_copy_of_slice(&(self.block_roots.len() as u32).to_le_bytes());
+            padding.write_filled(0);
+        }
+
+        for hash in self.peaks.peaks[..self.num_peaks()]
+            .iter()
+            .chain(BlockRoot::repr_from_slice(&self.block_roots))
+        {
+            buffer
+                .split_off_mut(..BlockRoot::SIZE)
+                .expect("Total length checked above; qed")
+                .write_copy_of_slice(hash);
+        }
+
+        Ok(total_bytes)
+    }
+
+    pub(crate) fn read(mut buffer: &[u8]) -> Result<Self, StorageItemError> {
+        let buffer_len = buffer.len();
+        let prefix = buffer
+            .split_off(..Self::PREFIX_SIZE)
+            .ok_or_else(|| StorageItemError::NeedMoreBytes(Self::PREFIX_SIZE - buffer_len))?;
+
+        let first_block_number = BlockNumber::from_bytes(
+            prefix[..BlockNumber::SIZE]
+                .try_into()
+                .expect("Correct length; qed"),
+        );
+        let num_block_roots = u32::from_le_bytes(
+            prefix[BlockNumber::SIZE..][..size_of::<u32>()]
+                .try_into()
+                .expect("Correct length; qed"),
+        ) as usize;
+
+        let num_leaves = u64::from(first_block_number).saturating_add(num_block_roots as u64);
+        let mut peaks = BlockMmrPeaks {
+            num_leaves,
+            peaks: [[0; BlockRoot::SIZE]; _],
+        };
+        let num_peaks = usize::from(peaks.num_peaks());
+        if num_peaks > peaks.peaks.len() {
+            return Err(StorageItemError::InvalidDataLength {
+                data_type: "BlockMmrPeaks",
+                expected: peaks.peaks.len(),
+                actual: num_peaks,
+            });
+        }
+
+        let buffer_len = buffer.len();
+        let hashes_size = BlockRoot::SIZE * (num_peaks + num_block_roots);
+        let hashes = buffer
+            .split_off(..hashes_size)
+            .ok_or_else(|| StorageItemError::NeedMoreBytes(hashes_size - buffer_len))?;
+        let (hashes, _remainder) = hashes.as_chunks::<{ BlockRoot::SIZE }>();
+        let (peak_hashes, block_roots) = hashes.split_at(num_peaks);
+
+        peaks.peaks[..num_peaks].copy_from_slice(peak_hashes);
+
+        Ok(Self {
+            first_block_number,
+            block_roots: BlockRoot::slice_from_repr(block_roots).to_vec(),
+            peaks,
+        })
+    }
+}
@@ -1,26 +1,92 @@
+use crate::page_group::block_mmr::StorageItemBlockMmr;
+use crate::page_group::segment_headers::StorageItemSegmentHeaders;
+use crate::stats::StorageItemKind;
+use crate::storage_backend_adapter::PageGroupKind;
 use crate::storage_backend_adapter::storage_item::{
-    StorageItem, StorageItemError, StorageItemWriteResult,
+    StorageItem, StorageItemError, StorageItemWriteResult, UniqueStorageItem,
 };
 use std::mem::MaybeUninit;
+use strum::FromRepr;
 
+#[derive(Debug, FromRepr)]
+#[repr(u8)]
+enum StorageItemPermanentVariant {
+    KnownSegmentHeaders = 0,
+    BlockMmr = 1,
+}
+
+/// Permanent storage items that are never removed from the database
 #[derive(Debug)]
 pub(crate) enum StorageItemPermanent {
-    // TODO
+    /// Segment headers known ahead of time (from the chain spec), written once during formatting
+    KnownSegmentHeaders(StorageItemSegmentHeaders),
+    /// Leaves and peaks of the Merkle Mountain Range of block roots, written as blocks are pruned
+    BlockMmr(Box<StorageItemBlockMmr>),
 }
 
 impl StorageItem for StorageItemPermanent {
+    #[inline(always)]
+    fn kind(&self) -> StorageItemKind {
+        match self {
+            Self::KnownSegmentHeaders(_) => StorageItemKind::KnownSegmentHeaders,
+            Self::BlockMmr(_) => StorageItemKind::BlockMmr,
+        }
+    }
+
+    #[inline(always)]
     fn total_bytes(&self) -> usize {
-        unreachable!()
+        match self {
+            Self::KnownSegmentHeaders(segment_headers) => segment_headers.total_bytes(),
+            Self::BlockMmr(block_mmr) => block_mmr.total_bytes(),
+        }
     }
 
+    #[inline(always)]
     fn write<'a>(
         &self,
-        _buffer: &'a mut [MaybeUninit<u8>],
+        buffer: &'a mut [MaybeUninit<u8>],
     ) -> Result<StorageItemWriteResult<'a>, StorageItemError> {
-        unreachable!()
+        let (variant, storage_item_size) = match self {
+            Self::KnownSegmentHeaders(segment_headers) => (
+                StorageItemPermanentVariant::KnownSegmentHeaders,
+                segment_headers.write(buffer)?,
+            ),
+            Self::BlockMmr(block_mmr) => (
+                StorageItemPermanentVariant::BlockMmr,
+                block_mmr.write(buffer)?,
+            ),
+        };
+
+        let (storage_item_bytes, buffer) = buffer.split_at_mut(storage_item_size);
+        // SAFETY: Storage item bytes were just written to
+        let storage_item_bytes = unsafe { storage_item_bytes.assume_init_mut() };
+
+        Ok(StorageItemWriteResult {
+            storage_item_variant: variant as u8,
+            storage_item_bytes,
+            buffer,
+        })
     }
 
-    fn read(variant: u8, _buffer: &[u8]) -> Result<Self, StorageItemError> {
-        Err(StorageItemError::UnknownStorageItemVariant(variant))
+    #[inline(always)]
+    fn read(variant: u8, buffer: &[u8]) -> Result<Self, StorageItemError> {
+        let variant = StorageItemPermanentVariant::from_repr(variant)
+            .ok_or(StorageItemError::UnknownStorageItemVariant(variant))?;
+
+        Ok(match variant {
+            StorageItemPermanentVariant::KnownSegmentHeaders => {
+                Self::KnownSegmentHeaders(StorageItemSegmentHeaders::read(buffer)?)
+            }
+            StorageItemPermanentVariant::BlockMmr => {
+                Self::BlockMmr(Box::new(StorageItemBlockMmr::read(buffer)?))
+            }
+        })
+    }
+}
+
+impl UniqueStorageItem for StorageItemPermanent {
+    #[inline(always)]
+    fn page_group_kind() -> PageGroupKind {
+        PageGroupKind::Permanent
     }
 }
@@ -4,12 +4,12 @@ use ab_io_type::trivial_type::TrivialType;
 use std::mem::MaybeUninit;
 
 #[derive(Debug)]
-pub(crate) struct StorageItemTemporarySegmentHeaders {
+pub(crate) struct StorageItemSegmentHeaders {
     pub(crate) segment_headers: Vec<SegmentHeader>,
 }
 
-impl StorageItemTemporarySegmentHeaders {
-    pub(super) fn total_bytes(&self) -> usize {
+impl StorageItemSegmentHeaders {
+    pub(crate) fn total_bytes(&self) -> usize {
         Self::prefix_size() + size_of_val(self.segment_headers.as_slice())
     }
 
@@ -23,7 +23,7 @@ impl StorageItemTemporarySegmentHeaders {
         PREFIX_SIZE
     }
 
-    pub(super) fn write(
+    pub(crate) fn write(
         &self,
         mut buffer: &mut [MaybeUninit<u8>],
     ) -> Result<usize, StorageItemError> {
@@ -72,26 +72,26 @@ impl StorageItemTemporarySegmentHeaders {
         Ok(total_bytes)
     }
 
-    pub(super) fn read(mut buffer: &[u8]) -> Result<Self, StorageItemError> {
+    pub(crate) fn read(mut buffer: &[u8]) -> Result<Self, StorageItemError> {
         let buffer_len = buffer.len();
         let prefix_bytes = buffer
             .split_off(..Self::prefix_size())
             .ok_or_else(|| StorageItemError::NeedMoreBytes(Self::prefix_size() - buffer_len))?;
 
-        let (num_segment_headers, mut remainder) = prefix_bytes.split_at(size_of::<u32>());
-
         // Read the number of segment headers
         let num_segment_headers =
-            u32::from_le_bytes(num_segment_headers.try_into().expect("Correct length; qed"))
-                as usize;
+            u32::from_le_bytes(prefix_bytes.try_into().expect("Correct length; qed")) as usize;
 
         let mut segment_headers = Vec::with_capacity(num_segment_headers);
 
         for _ in 0..num_segment_headers {
             let buffer_len = buffer.len();
-            let segment_header_bytes = remainder.split_off(..size_of::<SegmentHeader>()).ok_or(
-                StorageItemError::NeedMoreBytes(size_of::<SegmentHeader>() - buffer_len),
-            )?;
+            let segment_header_bytes =
+                buffer
+                    .split_off(..size_of::<SegmentHeader>())
+                    .ok_or_else(|| {
+                        StorageItemError::NeedMoreBytes(size_of::<SegmentHeader>() - buffer_len)
+                    })?;
             // TODO: Would be nice to have slice API in `TrivialType`
             // SAFETY: This is a local database, so anything that is read that passes checksum
             // verification is valid
@@ -0,0 +1,95 @@
+pub(crate) mod contract_slots;
+pub(crate) mod discarded_contract_slots;
+
+use crate::page_group::state::contract_slots::StorageItemStateContractSlots;
+use crate::page_group::state::discarded_contract_slots::StorageItemStateDiscardedContractSlots;
+use crate::stats::StorageItemKind;
+use crate::storage_backend_adapter::PageGroupKind;
+use crate::storage_backend_adapter::storage_item::{
+    StorageItem, StorageItemError, StorageItemWriteResult, UniqueStorageItem,
+};
+use std::mem::MaybeUninit;
+use strum::FromRepr;
+
+#[derive(Debug, FromRepr)]
+#[repr(u8)]
+enum StorageItemStateVariant {
+    ContractSlots = 0,
+    DiscardedContractSlots = 1,
+}
+
+/// Contract state storage items
+#[derive(Debug)]
+pub(crate) enum StorageItemState {
+    ContractSlots(StorageItemStateContractSlots),
+    DiscardedContractSlots(StorageItemStateDiscardedContractSlots),
+}
+
+impl StorageItem for StorageItemState {
+    #[inline(always)]
+    fn kind(&self) -> StorageItemKind {
+        match self {
+            Self::ContractSlots(_) => StorageItemKind::ContractSlots,
+            Self::DiscardedContractSlots(_) => StorageItemKind::DiscardedContractSlots,
+        }
+    }
+
+    #[inline(always)]
+    fn total_bytes(&self) -> usize {
+        match self {
+            Self::ContractSlots(contract_slots) => contract_slots.total_bytes(),
+            Self::DiscardedContractSlots(discarded_contract_slots) => {
+                discarded_contract_slots.total_bytes()
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn write<'a>(
+        &self,
+        buffer: &'a mut [MaybeUninit<u8>],
+    ) -> Result<StorageItemWriteResult<'a>, StorageItemError> {
+        let (variant, storage_item_size) = match self {
+            Self::ContractSlots(contract_slots) => (
+                StorageItemStateVariant::ContractSlots,
+                contract_slots.write(buffer)?,
+            ),
+            Self::DiscardedContractSlots(discarded_contract_slots) => (
+                StorageItemStateVariant::DiscardedContractSlots,
+                discarded_contract_slots.write(buffer)?,
+            ),
+        };
+
+        let (storage_item_bytes, buffer) = buffer.split_at_mut(storage_item_size);
+        // SAFETY: Storage item bytes were just written to
+        let storage_item_bytes = unsafe { storage_item_bytes.assume_init_mut() };
+
+        Ok(StorageItemWriteResult {
+            storage_item_variant: variant as u8,
+            storage_item_bytes,
+            buffer,
+        })
+    }
+
+    #[inline(always)]
+    fn read(variant: u8, buffer: &[u8]) -> Result<Self, StorageItemError> {
+        let variant = StorageItemStateVariant::from_repr(variant)
+            .ok_or(StorageItemError::UnknownStorageItemVariant(variant))?;
+
+        Ok(match variant {
+            StorageItemStateVariant::ContractSlots => {
+                Self::ContractSlots(StorageItemStateContractSlots::read(buffer)?)
+            }
+            StorageItemStateVariant::DiscardedContractSlots => {
+                Self::DiscardedContractSlots(StorageItemStateDiscardedContractSlots::read(buffer)?)
+            }
+        })
+    }
+}
+
+impl UniqueStorageItem for StorageItemState {
+    #[inline(always)]
+    fn page_group_kind() -> PageGroupKind {
+        PageGroupKind::State
+    }
+}
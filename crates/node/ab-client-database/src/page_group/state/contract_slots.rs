@@ -0,0 +1,189 @@
+use crate::storage_backend_adapter::storage_item::StorageItemError;
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::ContractSlotState;
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_io_type::trivial_type::TrivialType;
+use std::mem::MaybeUninit;
+use std::sync::Arc as StdArc;
+
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+struct ContractSlotsPrefix {
+    block_root: BlockRoot,
+    block_number: BlockNumber,
+    num_slots: u32,
+    padding: [u8; 4],
+}
+
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+struct ContractSlotPrefix {
+    owner: Address,
+    contract: Address,
+    contents_len: u32,
+    padding: [u8; 12],
+}
+
+const {
+    // Ensure data that follows prefixes is aligned to `u128`
+    assert!(size_of::<ContractSlotsPrefix>().is_multiple_of(size_of::<u128>()));
+    assert!(size_of::<ContractSlotPrefix>().is_multiple_of(size_of::<u128>()));
+}
+
+/// Contract slots modified by a block
+#[derive(Debug)]
+pub(crate) struct StorageItemStateContractSlots {
+    pub(crate) block_root: BlockRoot,
+    pub(crate) block_number: BlockNumber,
+    pub(crate) slots: StdArc<[ContractSlotState]>,
+}
+
+impl StorageItemStateContractSlots {
+    pub(super) fn total_bytes(&self) -> usize {
+        size_of::<ContractSlotsPrefix>()
+            + self
+                .slots
+                .iter()
+                .map(|slot| {
+                    size_of::<ContractSlotPrefix>()
+                        + (slot.contents.len() as usize).next_multiple_of(size_of::<u128>())
+                })
+                .sum::<usize>()
+    }
+
+    pub(super) fn write(
+        &self,
+        mut buffer: &mut [MaybeUninit<u8>],
+    ) -> Result<usize, StorageItemError> {
+        // The layout here is as follows:
+        // * prefix: ContractSlotsPrefix
+        // * for each slot:
+        //   * prefix: ContractSlotPrefix
+        //   * contents bytes
+        //   * padding to 16-bytes boundary (if needed)
+
+        let buffer_len = buffer.len();
+        let total_bytes = self.total_bytes();
+
+        if buffer_len < total_bytes {
+            return Err(StorageItemError::BufferTooSmall {
+                expected: total_bytes,
+                actual: buffer_len,
+            });
+        }
+
+        {
+            let prefix_bytes = buffer
+                .split_off_mut(..size_of::<ContractSlotsPrefix>())
+                .expect("Total length checked above; qed");
+            prefix_bytes.write_copy_of_slice(
+                ContractSlotsPrefix {
+                    block_root: self.block_root,
+                    block_number: self.block_number,
+                    num_slots: self.slots.len() as u32,
+                    padding: [0; _],
+                }
+                .as_bytes(),
+            );
+        }
+
+        for slot in self.slots.iter() {
+            {
+                let prefix_bytes = buffer
+                    .split_off_mut(..size_of::<ContractSlotPrefix>())
+                    .expect("Total length checked above; qed");
+                prefix_bytes.write_copy_of_slice(
+                    ContractSlotPrefix {
+                        owner: slot.owner,
+                        contract: slot.contract,
+                        contents_len: slot.contents.len(),
+                        padding: [0; _],
+                    }
+                    .as_bytes(),
+                );
+            }
+
+            {
+                let contents_len = slot.contents.len() as usize;
+                let contents_bytes = buffer
+                    .split_off_mut(..contents_len.next_multiple_of(size_of::<u128>()))
+                    .expect("Total length checked above; qed");
+
+                // Sub-slice due to possible trailing alignment bytes
+                contents_bytes[..contents_len].write_copy_of_slice(slot.contents.as_slice());
+                contents_bytes[contents_len..].write_filled(0);
+            }
+        }
+
+        Ok(total_bytes)
+    }
+
+    pub(super) fn read(mut buffer: &[u8]) -> Result<Self, StorageItemError> {
+        let prefix = {
+            let buffer_len = buffer.len();
+            let prefix_bytes = buffer
+                .split_off(..size_of::<ContractSlotsPrefix>())
+                .ok_or_else(|| {
+                    StorageItemError::NeedMoreBytes(size_of::<ContractSlotsPrefix>() - buffer_len)
+                })?;
+            // SAFETY: This is a local database, so anything that is read that passes checksum
+            // verification is valid
+            *unsafe {
+                ContractSlotsPrefix::from_bytes(prefix_bytes).ok_or(
+                    StorageItemError::InvalidDataAlignment {
+                        data_type: "ContractSlotsPrefix",
+                    },
+                )?
+            }
+        };
+
+        let mut slots = Vec::with_capacity(prefix.num_slots as usize);
+        for _ in 0..prefix.num_slots {
+            let slot_prefix = {
+                let buffer_len = buffer.len();
+                let prefix_bytes = buffer
+                    .split_off(..size_of::<ContractSlotPrefix>())
+                    .ok_or_else(|| {
+                        StorageItemError::NeedMoreBytes(
+                            size_of::<ContractSlotPrefix>() - buffer_len,
+                        )
+                    })?;
+                // SAFETY: This is a local database, so anything that is read that passes checksum
+                // verification is valid
+                *unsafe {
+                    ContractSlotPrefix::from_bytes(prefix_bytes).ok_or(
+                        StorageItemError::InvalidDataAlignment {
+                            data_type: "ContractSlotPrefix",
+                        },
+                    )?
+                }
+            };
+
+            let contents = {
+                let contents_len = slot_prefix.contents_len as usize;
+                let buffer_len = buffer.len();
+                let contents_bytes = buffer
+                    .split_off(..contents_len.next_multiple_of(size_of::<u128>()))
+                    .ok_or_else(|| {
+                        StorageItemError::NeedMoreBytes(
+                            contents_len.next_multiple_of(size_of::<u128>()) - buffer_len,
+                        )
+                    })?;
+                SharedAlignedBuffer::from_bytes(&contents_bytes[..contents_len])
+            };
+
+            slots.push(ContractSlotState {
+                owner: slot_prefix.owner,
+                contract: slot_prefix.contract,
+                contents,
+            });
+        }
+
+        Ok(Self {
+            block_root: prefix.block_root,
+            block_number: prefix.block_number,
+            slots: slots.into(),
+        })
+    }
+}
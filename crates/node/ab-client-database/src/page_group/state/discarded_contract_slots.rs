@@ -0,0 +1,54 @@
+use crate::storage_backend_adapter::storage_item::StorageItemError;
+use ab_core_primitives::block::BlockRoot;
+use ab_io_type::trivial_type::TrivialType;
+use std::mem::MaybeUninit;
+
+/// Marker that contract slots of a block must be ignored because the block was pruned from a fork
+/// that didn't become canonical.
+///
+/// Contract slots of canonical blocks remain in use even after blocks themselves are pruned, so
+/// without this marker slots of blocks on different forks would be indistinguishable after
+/// restart.
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+pub(crate) struct StorageItemStateDiscardedContractSlots {
+    pub(crate) block_root: BlockRoot,
+}
+
+impl StorageItemStateDiscardedContractSlots {
+    pub(super) fn total_bytes(&self) -> usize {
+        size_of::<Self>()
+    }
+
+    pub(super) fn write(&self, buffer: &mut [MaybeUninit<u8>]) -> Result<usize, StorageItemError> {
+        let total_bytes = self.total_bytes();
+
+        if buffer.len() < total_bytes {
+            return Err(StorageItemError::BufferTooSmall {
+                expected: total_bytes,
+                actual: buffer.len(),
+            });
+        }
+
+        buffer[..total_bytes].write_copy_of_slice(self.as_bytes());
+
+        Ok(total_bytes)
+    }
+
+    pub(super) fn read(buffer: &[u8]) -> Result<Self, StorageItemError> {
+        if buffer.len() < size_of::<Self>() {
+            return Err(StorageItemError::NeedMoreBytes(
+                size_of::<Self>() - buffer.len(),
+            ));
+        }
+
+        // SAFETY: All bit patterns are valid
+        let maybe_item = unsafe { Self::from_bytes(&buffer[..size_of::<Self>()]) };
+
+        maybe_item
+            .copied()
+            .ok_or(StorageItemError::InvalidDataAlignment {
+                data_type: "StorageItemStateDiscardedContractSlots",
+            })
+    }
+}
@@ -1,10 +1,17 @@
 pub(crate) mod block;
-pub(crate) mod segment_headers;
+pub(crate) mod block_aux_data;
+pub(crate) mod block_outcome;
+pub(crate) mod block_roots_filter;
 pub(crate) mod super_segment_headers;
 
+use crate::BlockCompression;
+use crate::page_group::segment_headers::StorageItemSegmentHeaders;
 use crate::page_group::temporary::block::StorageItemTemporaryBlock;
-use crate::page_group::temporary::segment_headers::StorageItemTemporarySegmentHeaders;
+use crate::page_group::temporary::block_aux_data::StorageItemTemporaryBlockAuxData;
+use crate::page_group::temporary::block_outcome::StorageItemTemporaryBlockOutcome;
+use crate::page_group::temporary::block_roots_filter::StorageItemTemporaryBlockRootsFilter;
 use crate::page_group::temporary::super_segment_headers::StorageItemTemporarySuperSegmentHeaders;
+use crate::stats::StorageItemKind;
 use crate::storage_backend_adapter::PageGroupKind;
 use crate::storage_backend_adapter::storage_item::{
     StorageItem, StorageItemError, StorageItemWriteResult, UniqueStorageItem,
@@ -18,23 +25,59 @@ enum StorageItemBlockVariant {
     Block = 0,
     SegmentHeaders = 1,
     SuperSegmentHeaders = 2,
+    BlockAuxData = 3,
+    BlockRootsFilter = 4,
+    CompressedBlock = 5,
+    BlockOutcome = 6,
 }
 
 /// Temporary storage items that will be pruned from the database eventually
 #[derive(Debug)]
 pub(crate) enum StorageItemTemporary {
     Block(StorageItemTemporaryBlock),
-    SegmentHeaders(StorageItemTemporarySegmentHeaders),
+    SegmentHeaders(StorageItemSegmentHeaders),
     SuperSegmentHeaders(StorageItemTemporarySuperSegmentHeaders),
+    BlockAuxData(StorageItemTemporaryBlockAuxData),
+    BlockRootsFilter(Box<StorageItemTemporaryBlockRootsFilter>),
+    BlockOutcome(StorageItemTemporaryBlockOutcome),
+}
+
+impl StorageItemTemporary {
+    /// Name of the storage item type
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Self::Block(_) => "Block",
+            Self::SegmentHeaders(_) => "SegmentHeaders",
+            Self::SuperSegmentHeaders(_) => "SuperSegmentHeaders",
+            Self::BlockAuxData(_) => "BlockAuxData",
+            Self::BlockRootsFilter(_) => "BlockRootsFilter",
+            Self::BlockOutcome(_) => "BlockOutcome",
+        }
+    }
 }
 
 impl StorageItem for StorageItemTemporary {
+    #[inline(always)]
+    fn kind(&self) -> StorageItemKind {
+        match self {
+            Self::Block(_) => StorageItemKind::Block,
+            Self::SegmentHeaders(_) => StorageItemKind::SegmentHeaders,
+            Self::SuperSegmentHeaders(_) => StorageItemKind::SuperSegmentHeaders,
+            Self::BlockAuxData(_) => StorageItemKind::BlockAuxData,
+            Self::BlockRootsFilter(_) => StorageItemKind::BlockRootsFilter,
+            Self::BlockOutcome(_) => StorageItemKind::BlockOutcome,
+        }
+    }
+
     #[inline(always)]
     fn total_bytes(&self) -> usize {
         match self {
             Self::Block(block) => block.total_bytes(),
             Self::SegmentHeaders(segment_headers) => segment_headers.total_bytes(),
             Self::SuperSegmentHeaders(super_segment_headers) => super_segment_headers.total_bytes(),
+            Self::BlockAuxData(block_aux_data) => block_aux_data.total_bytes(),
+            Self::BlockRootsFilter(block_roots_filter) => block_roots_filter.total_bytes(),
+            Self::BlockOutcome(block_outcome) => block_outcome.total_bytes(),
         }
     }
 
@@ -44,7 +87,14 @@ impl StorageItem for StorageItemTemporary {
         buffer: &'a mut [MaybeUninit<u8>],
     ) -> Result<StorageItemWriteResult<'a>, StorageItemError> {
         let (variant, storage_item_size) = match self {
-            Self::Block(block) => (StorageItemBlockVariant::Block, block.write(buffer)?),
+            Self::Block(block) => (
+                if block.is_compressed() {
+                    StorageItemBlockVariant::CompressedBlock
+                } else {
+                    StorageItemBlockVariant::Block
+                },
+                block.write(buffer)?,
+            ),
             Self::SegmentHeaders(segment_headers) => (
                 StorageItemBlockVariant::SegmentHeaders,
                 segment_headers.write(buffer)?,
@@ -53,6 +103,18 @@ impl StorageItem for StorageItemTemporary {
                 StorageItemBlockVariant::SuperSegmentHeaders,
                 super_segment_headers.write(buffer)?,
             ),
+            Self::BlockAuxData(block_aux_data) => (
+                StorageItemBlockVariant::BlockAuxData,
+                block_aux_data.write(buffer)?,
+            ),
+            Self::BlockRootsFilter(block_roots_filter) => (
+                StorageItemBlockVariant::BlockRootsFilter,
+                block_roots_filter.write(buffer)?,
+            ),
+            Self::BlockOutcome(block_outcome) => (
+                StorageItemBlockVariant::BlockOutcome,
+                block_outcome.write(buffer)?,
+            ),
         };
 
         let (storage_item_bytes, buffer) = buffer.split_at_mut(storage_item_size);
@@ -74,13 +136,37 @@ impl StorageItem for StorageItemTemporary {
         Ok(match variant {
             StorageItemBlockVariant::Block => Self::Block(StorageItemTemporaryBlock::read(buffer)?),
             StorageItemBlockVariant::SegmentHeaders => {
-                Self::SegmentHeaders(StorageItemTemporarySegmentHeaders::read(buffer)?)
+                Self::SegmentHeaders(StorageItemSegmentHeaders::read(buffer)?)
             }
             StorageItemBlockVariant::SuperSegmentHeaders => {
                 Self::SuperSegmentHeaders(StorageItemTemporarySuperSegmentHeaders::read(buffer)?)
             }
+            StorageItemBlockVariant::BlockAuxData => {
+                Self::BlockAuxData(StorageItemTemporaryBlockAuxData::read(buffer)?)
+            }
+            StorageItemBlockVariant::BlockRootsFilter => Self::BlockRootsFilter(Box::new(
+                StorageItemTemporaryBlockRootsFilter::read(buffer)?,
+            )),
+            StorageItemBlockVariant::CompressedBlock => {
+                Self::Block(StorageItemTemporaryBlock::read_compressed(buffer)?)
+            }
+            StorageItemBlockVariant::BlockOutcome => {
+                Self::BlockOutcome(StorageItemTemporaryBlockOutcome::read(buffer)?)
+            }
         })
     }
+
+    #[inline(always)]
+    fn compress(&mut self, block_compression: BlockCompression) -> Result<(), StorageItemError> {
+        match self {
+            Self::Block(block) => block.compress(block_compression),
+            Self::SegmentHeaders(_)
+            | Self::SuperSegmentHeaders(_)
+            | Self::BlockAuxData(_)
+            | Self::BlockRootsFilter(_)
+            | Self::BlockOutcome(_) => Ok(()),
+        }
+    }
 }
 
 impl UniqueStorageItem for StorageItemTemporary {
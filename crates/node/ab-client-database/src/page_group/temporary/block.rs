@@ -1,5 +1,6 @@
+use crate::BlockCompression;
 use crate::storage_backend_adapter::storage_item::StorageItemError;
-use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_aligned_buffer::{OwnedAlignedBuffer, SharedAlignedBuffer};
 use ab_client_api::{BlockMerkleMountainRange, ContractSlotState};
 use ab_core_primitives::address::Address;
 use ab_io_type::trivial_type::TrivialType;
@@ -7,6 +8,7 @@ use ab_merkle_tree::mmr::MerkleMountainRangeBytes;
 use rclite::Arc;
 use std::mem::MaybeUninit;
 use std::sync::Arc as StdArc;
+use std::{io, slice};
 
 #[derive(Debug, Copy, Clone, TrivialType)]
 #[repr(C)]
@@ -21,17 +23,43 @@ const {
     assert!(align_of::<SystemContractStatePrefix>() == align_of::<u64>());
 }
 
+/// Compressed representation of [`StorageItemTemporaryBlock`]
+#[derive(Debug)]
+pub(crate) struct CompressedBlock {
+    compression: BlockCompression,
+    /// Size of the block before compression
+    uncompressed_len: u32,
+    bytes: Vec<u8>,
+}
+
 #[derive(Debug)]
 pub(crate) struct StorageItemTemporaryBlock {
     pub(crate) header: SharedAlignedBuffer,
     pub(crate) body: SharedAlignedBuffer,
     pub(crate) mmr_with_block: Arc<BlockMerkleMountainRange>,
     pub(crate) system_contract_states: StdArc<[ContractSlotState]>,
+    /// Stable ordinal of the block among blocks at the same height
+    pub(crate) fork_ordinal: u32,
+    /// Compressed block, written instead of the block as is when present
+    pub(crate) compressed: Option<CompressedBlock>,
     // TODO: State, segment headers
 }
 
 impl StorageItemTemporaryBlock {
     pub(super) fn total_bytes(&self) -> usize {
+        if let Some(compressed) = &self.compressed {
+            return Self::compressed_prefix_size() + compressed.bytes.len();
+        }
+
+        self.uncompressed_total_bytes()
+    }
+
+    /// Whether the block is written in compressed form
+    pub(super) fn is_compressed(&self) -> bool {
+        self.compressed.is_some()
+    }
+
+    fn uncompressed_total_bytes(&self) -> usize {
         Self::total_bytes_inner(
             self.header.len(),
             self.body.len(),
@@ -62,11 +90,11 @@ impl StorageItemTemporaryBlock {
     }
 
     const fn prefix_size() -> usize {
-        // 4 lengths of header/block/mmr/num system contracts states
-        const PREFIX_SIZE: usize = size_of::<u32>() * 4;
+        // 4 lengths of header/block/mmr/num system contracts states, fork ordinal and padding
+        const PREFIX_SIZE: usize = (size_of::<u32>() * 5).next_multiple_of(size_of::<u128>());
         const {
             // Ensure always aligned to `u128`
-            assert!(PREFIX_SIZE == size_of::<u128>());
+            assert!(PREFIX_SIZE.is_multiple_of(size_of::<u128>()));
         }
         PREFIX_SIZE
     }
@@ -84,7 +112,124 @@ impl StorageItemTemporaryBlock {
         len.next_multiple_of(size_of::<u64>()) + system_contract_states_len as usize
     }
 
-    pub(super) fn write(
+    const fn compressed_prefix_size() -> usize {
+        // Compression, uncompressed length, compressed length and padding
+        const PREFIX_SIZE: usize =
+            (size_of::<u8>() + size_of::<u32>() * 2).next_multiple_of(size_of::<u128>());
+        PREFIX_SIZE
+    }
+
+    /// Compress the block with the specified compression, such that it is written in compressed
+    /// form.
+    ///
+    /// A block that was already compressed with a different compression is compressed again.
+    pub(crate) fn compress(
+        &mut self,
+        compression: BlockCompression,
+    ) -> Result<(), StorageItemError> {
+        if let Some(compressed) = &self.compressed
+            && compressed.compression == compression
+        {
+            return Ok(());
+        }
+        self.compressed = None;
+
+        if compression == BlockCompression::None {
+            return Ok(());
+        }
+
+        let uncompressed_len = self.uncompressed_total_bytes();
+        let mut uncompressed = OwnedAlignedBuffer::with_capacity(uncompressed_len as u32);
+        {
+            // SAFETY: Buffer has enough capacity and is aligned as required by `Self::write()`
+            let buffer = unsafe {
+                slice::from_raw_parts_mut(
+                    uncompressed.as_mut_ptr().cast::<MaybeUninit<u8>>(),
+                    uncompressed_len,
+                )
+            };
+            self.write_uncompressed(buffer)?;
+            // SAFETY: All bytes were written above
+            unsafe {
+                uncompressed.set_len(uncompressed_len as u32);
+            }
+        }
+        let uncompressed = uncompressed.as_slice();
+
+        let bytes = match compression {
+            BlockCompression::None => unreachable!("Returned early above; qed"),
+            BlockCompression::Lz4 => lz4_flex::compress(uncompressed),
+            BlockCompression::Zstd => {
+                zstd::bulk::compress(uncompressed, zstd::DEFAULT_COMPRESSION_LEVEL)
+                    .map_err(StorageItemError::Compression)?
+            }
+        };
+
+        // Incompressible blocks are stored as is
+        if Self::compressed_prefix_size() + bytes.len() < uncompressed_len {
+            self.compressed = Some(CompressedBlock {
+                compression,
+                uncompressed_len: uncompressed_len as u32,
+                bytes,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn write(&self, buffer: &mut [MaybeUninit<u8>]) -> Result<usize, StorageItemError> {
+        match &self.compressed {
+            Some(compressed) => Self::write_compressed(compressed, buffer),
+            None => self.write_uncompressed(buffer),
+        }
+    }
+
+    fn write_compressed(
+        compressed: &CompressedBlock,
+        mut buffer: &mut [MaybeUninit<u8>],
+    ) -> Result<usize, StorageItemError> {
+        // The layout here is as follows:
+        // * compression: u8
+        // * padding to 4-bytes boundary
+        // * uncompressed length: u32 as aligned little-endian bytes
+        // * compressed length: u32 as aligned little-endian bytes
+        // * padding to 16-bytes boundary
+        // * compressed bytes
+
+        let buffer_len = buffer.len();
+        let total_bytes = Self::compressed_prefix_size() + compressed.bytes.len();
+
+        if buffer_len < total_bytes {
+            return Err(StorageItemError::BufferTooSmall {
+                expected: total_bytes,
+                actual: buffer_len,
+            });
+        }
+
+        {
+            let prefix_bytes = buffer
+                .split_off_mut(..Self::compressed_prefix_size())
+                .expect("Total length checked above; qed");
+            let (compression, remainder) = prefix_bytes.split_at_mut(size_of::<u32>());
+            let (uncompressed_len, remainder) = remainder.split_at_mut(size_of::<u32>());
+            let (compressed_len, padding) = remainder.split_at_mut(size_of::<u32>());
+
+            compression[0].write(compressed.compression as u8);
+            compression[1..].write_filled(0);
+            uncompressed_len.write_copy_of_slice(&compressed.uncompressed_len.to_le_bytes());
+            compressed_len.write_copy_of_slice(&(compressed.bytes.len() as u32).to_le_bytes());
+            padding.write_filled(0);
+        }
+
+        buffer
+            .split_off_mut(..compressed.bytes.len())
+            .expect("Total length checked above; qed")
+            .write_copy_of_slice(&compressed.bytes);
+
+        Ok(total_bytes)
+    }
+
+    fn write_uncompressed(
         &self,
         mut buffer: &mut [MaybeUninit<u8>],
     ) -> Result<usize, StorageItemError> {
@@ -93,6 +238,8 @@ impl StorageItemTemporaryBlock {
         // * body length: u32 as aligned little-endian bytes
         // * MMR with block length: u32 as aligned little-endian bytes
         // * number of system contract states: u32 as aligned little-endian bytes
+        // * fork ordinal: u32 as aligned little-endian bytes
+        // * padding to 16-bytes boundary
         // * block header: naturally aligned to 16-bytes boundary
         // * padding to 16-bytes boundary (if needed)
         // * block body
@@ -104,7 +251,7 @@ impl StorageItemTemporaryBlock {
         //   * contents: slot contents bytes
 
         let buffer_len = buffer.len();
-        let total_bytes = self.total_bytes();
+        let total_bytes = self.uncompressed_total_bytes();
 
         if buffer_len < total_bytes {
             return Err(StorageItemError::BufferTooSmall {
@@ -126,13 +273,17 @@ impl StorageItemTemporaryBlock {
                 .expect("Total length checked above; qed");
             let (header_len, remainder) = prefix_bytes.split_at_mut(size_of::<u32>());
             let (body_len, remainder) = remainder.split_at_mut(size_of::<u32>());
-            let (mmr_len, num_system_contract_states) = remainder.split_at_mut(size_of::<u32>());
+            let (mmr_len, remainder) = remainder.split_at_mut(size_of::<u32>());
+            let (num_system_contract_states, remainder) = remainder.split_at_mut(size_of::<u32>());
+            let (fork_ordinal, padding) = remainder.split_at_mut(size_of::<u32>());
 
             header_len.write_copy_of_slice(&(header.len() as u32).to_le_bytes());
             body_len.write_copy_of_slice(&(body.len() as u32).to_le_bytes());
             mmr_len.write_copy_of_slice(&(mmr_with_block.len() as u32).to_le_bytes());
             num_system_contract_states
                 .write_copy_of_slice(&(system_contract_states.len() as u32).to_le_bytes());
+            fork_ordinal.write_copy_of_slice(&self.fork_ordinal.to_le_bytes());
+            padding.write_filled(0);
 
             written_len += prefix_bytes.len();
         }
@@ -224,6 +375,66 @@ impl StorageItemTemporaryBlock {
         Ok(total_bytes)
     }
 
+    /// The inverse of [`Self::write()`] for compressed blocks
+    pub(super) fn read_compressed(mut buffer: &[u8]) -> Result<Self, StorageItemError> {
+        let buffer_len = buffer.len();
+        let prefix_bytes = buffer
+            .split_off(..Self::compressed_prefix_size())
+            .ok_or_else(|| {
+                StorageItemError::NeedMoreBytes(Self::compressed_prefix_size() - buffer_len)
+            })?;
+
+        let (compression, remainder) = prefix_bytes.split_at(size_of::<u32>());
+        let (uncompressed_len, remainder) = remainder.split_at(size_of::<u32>());
+        let compressed_len = &remainder[..size_of::<u32>()];
+
+        let compression = BlockCompression::from_repr(compression[0])
+            .ok_or(StorageItemError::InvalidBufferContents)?;
+        let uncompressed_len =
+            u32::from_le_bytes(uncompressed_len.try_into().expect("Correct length; qed"));
+        let compressed_len =
+            u32::from_le_bytes(compressed_len.try_into().expect("Correct length; qed")) as usize;
+
+        let buffer_len = buffer.len();
+        let bytes = buffer
+            .split_off(..compressed_len)
+            .ok_or_else(|| StorageItemError::NeedMoreBytes(compressed_len - buffer_len))?;
+
+        let uncompressed = match compression {
+            BlockCompression::None => {
+                return Err(StorageItemError::InvalidBufferContents);
+            }
+            BlockCompression::Lz4 => lz4_flex::decompress(bytes, uncompressed_len as usize)
+                .map_err(|error| {
+                    StorageItemError::Decompression(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        error,
+                    ))
+                })?,
+            BlockCompression::Zstd => zstd::bulk::decompress(bytes, uncompressed_len as usize)
+                .map_err(StorageItemError::Decompression)?,
+        };
+
+        if uncompressed.len() != uncompressed_len as usize {
+            return Err(StorageItemError::InvalidDataLength {
+                data_type: "StorageItemTemporaryBlock",
+                expected: uncompressed_len as usize,
+                actual: uncompressed.len(),
+            });
+        }
+
+        // Copy into an aligned buffer since some parts of the block are read in place
+        let uncompressed = OwnedAlignedBuffer::from_bytes(&uncompressed);
+        let mut block = Self::read(uncompressed.as_slice())?;
+        block.compressed = Some(CompressedBlock {
+            compression,
+            uncompressed_len,
+            bytes: bytes.to_vec(),
+        });
+
+        Ok(block)
+    }
+
     pub(super) fn read(mut buffer: &[u8]) -> Result<Self, StorageItemError> {
         let buffer_len = buffer.len();
         let prefix_bytes = buffer
@@ -233,7 +444,9 @@ impl StorageItemTemporaryBlock {
 
         let (header_len, remainder) = prefix_bytes.split_at(size_of::<u32>());
         let (body_len, remainder) = remainder.split_at(size_of::<u32>());
-        let (mmr_len, num_system_contract_states) = remainder.split_at(size_of::<u32>());
+        let (mmr_len, remainder) = remainder.split_at(size_of::<u32>());
+        let (num_system_contract_states, remainder) = remainder.split_at(size_of::<u32>());
+        let fork_ordinal = &remainder[..size_of::<u32>()];
 
         // Read lengths
         let header_len =
@@ -246,6 +459,8 @@ impl StorageItemTemporaryBlock {
                 .try_into()
                 .expect("Correct length; qed"),
         );
+        let fork_ordinal =
+            u32::from_le_bytes(fork_ordinal.try_into().expect("Correct length; qed"));
 
         let header = {
             let buffer_len = buffer.len();
@@ -370,6 +585,8 @@ impl StorageItemTemporaryBlock {
             body,
             mmr_with_block: Arc::new(mmr),
             system_contract_states,
+            fork_ordinal,
+            compressed: None,
         })
     }
 }
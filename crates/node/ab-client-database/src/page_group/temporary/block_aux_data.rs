@@ -0,0 +1,110 @@
+use crate::storage_backend_adapter::storage_item::StorageItemError;
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::BlockAuxDataNamespace;
+use ab_core_primitives::block::BlockRoot;
+use ab_io_type::trivial_type::TrivialType;
+use std::mem::MaybeUninit;
+
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+struct BlockAuxDataPrefix {
+    block_root: BlockRoot,
+    namespace: [u8; 8],
+    data_len: u32,
+    padding: [u8; 4],
+}
+
+const {
+    // Ensure data that follows the prefix is aligned to `u128`
+    assert!(size_of::<BlockAuxDataPrefix>().is_multiple_of(size_of::<u128>()));
+}
+
+#[derive(Debug)]
+pub(crate) struct StorageItemTemporaryBlockAuxData {
+    pub(crate) block_root: BlockRoot,
+    pub(crate) namespace: BlockAuxDataNamespace,
+    pub(crate) data: SharedAlignedBuffer,
+}
+
+impl StorageItemTemporaryBlockAuxData {
+    pub(super) fn total_bytes(&self) -> usize {
+        size_of::<BlockAuxDataPrefix>() + self.data.len() as usize
+    }
+
+    pub(super) fn write(
+        &self,
+        mut buffer: &mut [MaybeUninit<u8>],
+    ) -> Result<usize, StorageItemError> {
+        // The layout here is as follows:
+        // * prefix: BlockAuxDataPrefix
+        // * data bytes
+
+        let buffer_len = buffer.len();
+        let total_bytes = self.total_bytes();
+
+        if buffer_len < total_bytes {
+            return Err(StorageItemError::BufferTooSmall {
+                expected: total_bytes,
+                actual: buffer_len,
+            });
+        }
+
+        {
+            let prefix_bytes = buffer
+                .split_off_mut(..size_of::<BlockAuxDataPrefix>())
+                .expect("Total length checked above; qed");
+            prefix_bytes.write_copy_of_slice(
+                BlockAuxDataPrefix {
+                    block_root: self.block_root,
+                    namespace: *self.namespace.as_bytes(),
+                    data_len: self.data.len(),
+                    padding: [0; _],
+                }
+                .as_bytes(),
+            );
+        }
+
+        {
+            let data_bytes = buffer
+                .split_off_mut(..self.data.len() as usize)
+                .expect("Total length checked above; qed");
+            data_bytes.write_copy_of_slice(self.data.as_slice());
+        }
+
+        Ok(total_bytes)
+    }
+
+    pub(super) fn read(mut buffer: &[u8]) -> Result<Self, StorageItemError> {
+        let prefix = {
+            let buffer_len = buffer.len();
+            let prefix_bytes = buffer
+                .split_off(..size_of::<BlockAuxDataPrefix>())
+                .ok_or_else(|| {
+                    StorageItemError::NeedMoreBytes(size_of::<BlockAuxDataPrefix>() - buffer_len)
+                })?;
+            // SAFETY: This is a local database, so anything that is read that passes checksum
+            // verification is valid
+            *unsafe {
+                BlockAuxDataPrefix::from_bytes(prefix_bytes).ok_or(
+                    StorageItemError::InvalidDataAlignment {
+                        data_type: "BlockAuxDataPrefix",
+                    },
+                )?
+            }
+        };
+
+        let data = {
+            let buffer_len = buffer.len();
+            let data_bytes = buffer.split_off(..prefix.data_len as usize).ok_or(
+                StorageItemError::NeedMoreBytes(prefix.data_len as usize - buffer_len),
+            )?;
+            SharedAlignedBuffer::from_bytes(data_bytes)
+        };
+
+        Ok(Self {
+            block_root: prefix.block_root,
+            namespace: BlockAuxDataNamespace::new(prefix.namespace),
+            data,
+        })
+    }
+}
@@ -0,0 +1,207 @@
+use crate::storage_backend_adapter::storage_item::StorageItemError;
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{BlockOutcome, TransactionOutcome, TransactionStatus};
+use ab_core_primitives::block::BlockRoot;
+use ab_core_primitives::block::execution::ExecutionReceipt;
+use ab_io_type::trivial_type::TrivialType;
+use std::iter;
+use std::mem::MaybeUninit;
+
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+struct BlockOutcomePrefix {
+    block_root: BlockRoot,
+    num_transactions: u32,
+    padding: [u8; 12],
+}
+
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+struct TransactionOutcomePrefix {
+    receipt: ExecutionReceipt,
+    events_len: u32,
+    status: u8,
+    padding: [u8; 19],
+}
+
+const {
+    // Ensure data that follows prefixes is aligned to `u128`
+    assert!(size_of::<BlockOutcomePrefix>().is_multiple_of(size_of::<u128>()));
+    assert!(size_of::<TransactionOutcomePrefix>().is_multiple_of(size_of::<u128>()));
+}
+
+#[derive(Debug)]
+pub(crate) struct StorageItemTemporaryBlockOutcome {
+    pub(crate) block_root: BlockRoot,
+    pub(crate) outcome: BlockOutcome,
+}
+
+impl StorageItemTemporaryBlockOutcome {
+    /// Whether the storage item contains the same outcome as `outcome`
+    pub(crate) fn has_outcome(&self, outcome: &BlockOutcome) -> bool {
+        self.outcome.transactions.len() == outcome.transactions.len()
+            && self
+                .outcome
+                .transactions
+                .iter()
+                .zip(outcome.transactions.iter())
+                .all(|(a, b)| {
+                    a.receipt == b.receipt
+                        && a.status == b.status
+                        && a.events.as_slice() == b.events.as_slice()
+                })
+    }
+
+    pub(super) fn total_bytes(&self) -> usize {
+        size_of::<BlockOutcomePrefix>()
+            + self
+                .outcome
+                .transactions
+                .iter()
+                .map(|transaction| {
+                    size_of::<TransactionOutcomePrefix>()
+                        + (transaction.events.len() as usize).next_multiple_of(size_of::<u128>())
+                })
+                .sum::<usize>()
+    }
+
+    pub(super) fn write(
+        &self,
+        mut buffer: &mut [MaybeUninit<u8>],
+    ) -> Result<usize, StorageItemError> {
+        // The layout here is as follows:
+        // * prefix: BlockOutcomePrefix
+        // * for each transaction:
+        //   * prefix: TransactionOutcomePrefix
+        //   * events bytes
+        //   * padding to 16-bytes boundary (if needed)
+
+        let buffer_len = buffer.len();
+        let total_bytes = self.total_bytes();
+
+        if buffer_len < total_bytes {
+            return Err(StorageItemError::BufferTooSmall {
+                expected: total_bytes,
+                actual: buffer_len,
+            });
+        }
+
+        {
+            let prefix_bytes = buffer
+                .split_off_mut(..size_of::<BlockOutcomePrefix>())
+                .expect("Total length checked above; qed");
+            prefix_bytes.write_copy_of_slice(
+                BlockOutcomePrefix {
+                    block_root: self.block_root,
+                    num_transactions: self.outcome.transactions.len() as u32,
+                    padding: [0; _],
+                }
+                .as_bytes(),
+            );
+        }
+
+        for transaction in self.outcome.transactions.iter() {
+            {
+                let prefix_bytes = buffer
+                    .split_off_mut(..size_of::<TransactionOutcomePrefix>())
+                    .expect("Total length checked above; qed");
+                prefix_bytes.write_copy_of_slice(
+                    TransactionOutcomePrefix {
+                        receipt: transaction.receipt,
+                        events_len: transaction.events.len(),
+                        status: transaction.status as u8,
+                        padding: [0; _],
+                    }
+                    .as_bytes(),
+                );
+            }
+
+            {
+                let events = transaction.events.as_slice();
+                let events_bytes = buffer
+                    .split_off_mut(..events.len().next_multiple_of(size_of::<u128>()))
+                    .expect("Total length checked above; qed");
+
+                // Sub-slice due to possible trailing alignment bytes
+                events_bytes[..events.len()].write_copy_of_slice(events);
+                events_bytes[events.len()..].write_filled(0);
+            }
+        }
+
+        Ok(total_bytes)
+    }
+
+    pub(super) fn read(mut buffer: &[u8]) -> Result<Self, StorageItemError> {
+        let prefix = {
+            let buffer_len = buffer.len();
+            let prefix_bytes = buffer
+                .split_off(..size_of::<BlockOutcomePrefix>())
+                .ok_or_else(|| {
+                    StorageItemError::NeedMoreBytes(size_of::<BlockOutcomePrefix>() - buffer_len)
+                })?;
+            // SAFETY: This is a local database, so anything that is read that passes checksum
+            // verification is valid
+            *unsafe {
+                BlockOutcomePrefix::from_bytes(prefix_bytes).ok_or(
+                    StorageItemError::InvalidDataAlignment {
+                        data_type: "BlockOutcomePrefix",
+                    },
+                )?
+            }
+        };
+
+        let transactions = iter::repeat_with(|| {
+            let transaction_prefix = {
+                let buffer_len = buffer.len();
+                let prefix_bytes = buffer
+                    .split_off(..size_of::<TransactionOutcomePrefix>())
+                    .ok_or_else(|| {
+                        StorageItemError::NeedMoreBytes(
+                            size_of::<TransactionOutcomePrefix>() - buffer_len,
+                        )
+                    })?;
+                // SAFETY: This is a local database, so anything that is read that passes
+                // checksum verification is valid
+                *unsafe {
+                    TransactionOutcomePrefix::from_bytes(prefix_bytes).ok_or(
+                        StorageItemError::InvalidDataAlignment {
+                            data_type: "TransactionOutcomePrefix",
+                        },
+                    )?
+                }
+            };
+
+            let status = match transaction_prefix.status {
+                0 => TransactionStatus::Success,
+                1 => TransactionStatus::Failed,
+                _ => {
+                    return Err(StorageItemError::InvalidBufferContents);
+                }
+            };
+
+            let events = {
+                let events_len = transaction_prefix.events_len as usize;
+                let events_len_with_padding = events_len.next_multiple_of(size_of::<u128>());
+                let buffer_len = buffer.len();
+                let events_bytes =
+                    buffer.split_off(..events_len_with_padding).ok_or_else(|| {
+                        StorageItemError::NeedMoreBytes(events_len_with_padding - buffer_len)
+                    })?;
+                SharedAlignedBuffer::from_bytes(&events_bytes[..events_len])
+            };
+
+            Ok(TransactionOutcome {
+                receipt: transaction_prefix.receipt,
+                status,
+                events,
+            })
+        })
+        .take(prefix.num_transactions as usize)
+        .collect::<Result<_, StorageItemError>>()?;
+
+        Ok(Self {
+            block_root: prefix.block_root,
+            outcome: BlockOutcome { transactions },
+        })
+    }
+}
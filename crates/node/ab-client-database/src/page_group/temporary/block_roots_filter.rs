@@ -0,0 +1,88 @@
+use crate::storage_backend_adapter::storage_item::StorageItemError;
+use ab_core_primitives::block::BlockRoot;
+use ab_io_type::trivial_type::TrivialType;
+use std::mem::MaybeUninit;
+
+/// 8192 bits, which results in ~2% false positive rate with 1000 block roots
+const NUM_WORDS: usize = 128;
+
+/// Bloom filter of block roots contained in a page group.
+///
+/// Block roots are already uniformly distributed hashes, so bit indices are derived directly from
+/// their bytes without additional hashing.
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+pub(crate) struct BlockRootsFilter {
+    bits: [u64; NUM_WORDS],
+}
+
+impl Default for BlockRootsFilter {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            bits: [0; NUM_WORDS],
+        }
+    }
+}
+
+impl BlockRootsFilter {
+    const NUM_BITS: u64 = NUM_WORDS as u64 * u64::BITS as u64;
+
+    /// Add block root to the filter
+    pub(crate) fn insert(&mut self, block_root: &BlockRoot) {
+        for bit in Self::bit_indices(block_root) {
+            self.bits[bit / u64::BITS as usize] |= 1 << (bit % u64::BITS as usize);
+        }
+    }
+
+    /// Returns `false` if the block root is definitely not in the filter
+    pub(crate) fn may_contain(&self, block_root: &BlockRoot) -> bool {
+        Self::bit_indices(block_root)
+            .all(|bit| self.bits[bit / u64::BITS as usize] & (1 << (bit % u64::BITS as usize)) != 0)
+    }
+
+    fn bit_indices(block_root: &BlockRoot) -> impl Iterator<Item = usize> {
+        block_root
+            .as_chunks::<{ size_of::<u64>() }>()
+            .0
+            .iter()
+            .map(|chunk| (u64::from_le_bytes(*chunk) % Self::NUM_BITS) as usize)
+    }
+}
+
+#[derive(Debug, Copy, Clone, TrivialType)]
+#[repr(C)]
+pub(crate) struct StorageItemTemporaryBlockRootsFilter {
+    /// Sequence number of the page group header, which uniquely identifies a page group
+    pub(crate) page_group_first_sequence_number: u64,
+    /// Filter of block roots stored in the page group
+    pub(crate) filter: BlockRootsFilter,
+}
+
+impl StorageItemTemporaryBlockRootsFilter {
+    pub(super) fn total_bytes(&self) -> usize {
+        size_of::<Self>()
+    }
+
+    pub(super) fn write(&self, buffer: &mut [MaybeUninit<u8>]) -> Result<usize, StorageItemError> {
+        let total_bytes = self.total_bytes();
+
+        let Some(storage_item_bytes) = buffer.get_mut(..total_bytes) else {
+            return Err(StorageItemError::BufferTooSmall {
+                expected: total_bytes,
+                actual: buffer.len(),
+            });
+        };
+        storage_item_bytes.write_copy_of_slice(self.as_bytes());
+
+        Ok(total_bytes)
+    }
+
+    pub(super) fn read(buffer: &[u8]) -> Result<Self, StorageItemError> {
+        // SAFETY: This is a local database, so anything that is read that passes checksum
+        // verification is valid
+        unsafe { Self::read_unaligned(buffer) }.ok_or(StorageItemError::NeedMoreBytes(
+            size_of::<Self>().saturating_sub(buffer.len()),
+        ))
+    }
+}
@@ -0,0 +1,115 @@
+//! Holds that delay pruning of blocks for external consumers, see
+//! [`ClientDatabase::hold_block_range()`] and [`ClientDatabase::hold_block_roots()`].
+//!
+//! Components like archiver or indexers might need blocks of non-canonical forks for some time
+//! after they are no longer interesting to the database itself. Such components place a hold on
+//! a range of block numbers or specific block roots, and blocks covered by at least one hold are
+//! not pruned until all holds covering them are released.
+//!
+//! NOTE: Blocks that conflict with a confirmed block can never become canonical and are pruned
+//! regardless of holds.
+//!
+//! [`ClientDatabase::hold_block_range()`]: crate::ClientDatabase::hold_block_range
+//! [`ClientDatabase::hold_block_roots()`]: crate::ClientDatabase::hold_block_roots
+
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use parking_lot::Mutex;
+use rclite::Arc;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Blocks covered by a pruning hold
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PruningHoldTarget {
+    /// All blocks with block numbers within the range
+    BlockRange(RangeInclusive<BlockNumber>),
+    /// Blocks with specific roots
+    BlockRoots(Vec<BlockRoot>),
+}
+
+impl PruningHoldTarget {
+    fn covers(&self, block_number: BlockNumber, block_root: &BlockRoot) -> bool {
+        match self {
+            Self::BlockRange(range) => range.contains(&block_number),
+            Self::BlockRoots(block_roots) => block_roots.contains(block_root),
+        }
+    }
+}
+
+/// Information about an active pruning hold
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PruningHoldInfo {
+    /// Unique (within the database instance) identifier of the hold
+    pub id: u64,
+    /// Name of the component that placed the hold
+    pub owner: &'static str,
+    /// Blocks covered by the hold
+    pub target: PruningHoldTarget,
+}
+
+#[derive(Debug, Default)]
+struct PruningHoldsInner {
+    next_id: u64,
+    holds: BTreeMap<u64, PruningHoldInfo>,
+}
+
+/// Registry of active pruning holds
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PruningHolds {
+    inner: Arc<Mutex<PruningHoldsInner>>,
+}
+
+impl PruningHolds {
+    pub(crate) fn add(&self, owner: &'static str, target: PruningHoldTarget) -> PruningHold {
+        let mut inner = self.inner.lock();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner
+            .holds
+            .insert(id, PruningHoldInfo { id, owner, target });
+
+        PruningHold {
+            id,
+            pruning_holds: self.clone(),
+        }
+    }
+
+    /// Returns the owner of the first hold that covers the specified block (if any)
+    pub(crate) fn held_by(
+        &self,
+        block_number: BlockNumber,
+        block_root: &BlockRoot,
+    ) -> Option<&'static str> {
+        self.inner
+            .lock()
+            .holds
+            .values()
+            .find(|hold| hold.target.covers(block_number, block_root))
+            .map(|hold| hold.owner)
+    }
+
+    pub(crate) fn list(&self) -> Vec<PruningHoldInfo> {
+        self.inner.lock().holds.values().cloned().collect()
+    }
+}
+
+/// Pruning hold, which is released when dropped
+#[derive(Debug)]
+#[must_use = "Hold is released when dropped"]
+pub struct PruningHold {
+    id: u64,
+    pruning_holds: PruningHolds,
+}
+
+impl PruningHold {
+    /// Unique (within the database instance) identifier of the hold
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Drop for PruningHold {
+    fn drop(&mut self) {
+        self.pruning_holds.inner.lock().holds.remove(&self.id);
+    }
+}
@@ -0,0 +1,100 @@
+//! Database statistics, see [`ClientDatabase::stats()`].
+//!
+//! [`ClientDatabase::stats()`]: crate::ClientDatabase::stats
+
+use enum_map::EnumMap;
+use std::ops::AddAssign;
+
+/// Kind of storage items
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, enum_map::Enum)]
+pub enum StorageItemKind {
+    /// Header that each page group starts with
+    PageGroupHeader,
+    /// Segment headers known ahead of time, stored permanently
+    KnownSegmentHeaders,
+    /// Leaves and peaks of the Merkle Mountain Range of block roots, stored permanently
+    BlockMmr,
+    /// Block
+    Block,
+    /// Segment headers
+    SegmentHeaders,
+    /// Super segment headers
+    SuperSegmentHeaders,
+    /// Auxiliary data of a block
+    BlockAuxData,
+    /// Filter of block roots of a page group
+    BlockRootsFilter,
+    /// Execution outcome of a block
+    BlockOutcome,
+    /// Contract slots modified by a block
+    ContractSlots,
+    /// Marker that contract slots of a pruned fork block must be ignored
+    DiscardedContractSlots,
+}
+
+/// Statistics of storage items of a single kind
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct StorageItemStats {
+    /// Number of storage items
+    pub count: u64,
+    /// Number of pages occupied by storage items
+    pub pages: u64,
+    /// Size of storage items in bytes, excluding per-item overhead and padding to page boundary
+    pub bytes: u64,
+}
+
+impl AddAssign for StorageItemStats {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.count += rhs.count;
+        self.pages += rhs.pages;
+        self.bytes += rhs.bytes;
+    }
+}
+
+/// Kind of page group
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PageGroupKind {
+    /// Page group with storage items that are never removed
+    Permanent,
+    /// Page group with storage items that are pruned over time
+    Temporary,
+    /// Page group with contract state
+    State,
+}
+
+/// Occupancy of a single page group
+#[derive(Debug, Clone)]
+pub struct PageGroupStats {
+    /// Kind of page group
+    pub kind: PageGroupKind,
+    /// Offset of the first page of this page group in the storage backend
+    pub first_page_offset: u32,
+    /// Whether new storage items are appended to this page group
+    pub active: bool,
+    /// Number of used pages, including the page group header
+    pub used_pages: u32,
+    /// Storage items contained in this page group.
+    ///
+    /// Includes storage items that are no longer used, but were not reclaimed by compaction yet.
+    pub storage_items: EnumMap<StorageItemKind, StorageItemStats>,
+}
+
+/// Database statistics
+#[derive(Debug, Clone)]
+pub struct ClientDatabaseStats {
+    /// Total number of pages in the storage backend
+    pub total_pages: u32,
+    /// Number of pages used by storage items in all page groups
+    pub used_pages: u32,
+    /// Number of pages available for new storage items, including unused pages of page groups
+    pub free_pages: u32,
+    /// Page group size in pages
+    pub page_group_size: u32,
+    /// Number of page groups that are not used at all
+    pub free_page_groups: u32,
+    /// Occupancy of used page groups, ordered by the offset of the first page
+    pub page_groups: Vec<PageGroupStats>,
+    /// Storage items in all page groups combined
+    pub storage_items: EnumMap<StorageItemKind, StorageItemStats>,
+}
@@ -1,5 +1,6 @@
 use futures::channel::oneshot;
 use std::mem::MaybeUninit;
+use std::sync::Arc as StdArc;
 use std::{fmt, io, mem};
 
 /// A wrapper data structure with 4096 bytes alignment, which is the most common alignment for
@@ -148,14 +149,22 @@ pub trait ClientDatabaseStorageBackend: fmt::Debug + Send + Sync + 'static {
         offset: u32,
     ) -> oneshot::Receiver<io::Result<Vec<AlignedPage>>>;
 
-    // TODO: Think whether `Vec` is the right wrapper here to avoid reallocations
     /// Writing from aligned memory.
     ///
     /// `offset` is in [`AlignedPage`] units (pages). After successful writing returns allocated
     /// pages back to the caller.
+    ///
+    /// The caller may keep a reference to `buffer` to read pages from it while the write is in
+    /// progress, so the buffer is shared rather than owned.
     fn write(
         &self,
-        buffer: Vec<AlignedPage>,
+        buffer: StdArc<Vec<AlignedPage>>,
         offset: u32,
-    ) -> oneshot::Receiver<io::Result<Vec<AlignedPage>>>;
+    ) -> oneshot::Receiver<io::Result<StdArc<Vec<AlignedPage>>>>;
+
+    /// Flush previously completed writes to durable storage.
+    ///
+    /// Once successful result is returned, all writes that completed before this call must
+    /// survive a crash or power loss.
+    fn sync(&self) -> oneshot::Receiver<io::Result<()>>;
 }
@@ -1,15 +1,27 @@
 pub(crate) mod page_group_header;
 pub(crate) mod storage_item;
 
+use crate::backup::BackupError;
+use crate::metrics::ClientDatabaseMetrics;
 use crate::page_group::permanent::StorageItemPermanent;
+use crate::page_group::segment_headers::StorageItemSegmentHeaders;
+use crate::page_group::state::StorageItemState;
 use crate::page_group::temporary::StorageItemTemporary;
+use crate::page_group::temporary::block_roots_filter::{
+    BlockRootsFilter, StorageItemTemporaryBlockRootsFilter,
+};
+use crate::stats::{ClientDatabaseStats, PageGroupStats, StorageItemKind, StorageItemStats};
 use crate::storage_backend::{AlignedPage, ClientDatabaseStorageBackend};
 use crate::storage_backend_adapter::storage_item::{
     StorageItem, StorageItemContainer, UniqueStorageItem,
 };
+use crate::verification::{VerificationIssue, VerificationReport};
 use crate::{
-    ClientDatabaseError, ClientDatabaseFormatError, ClientDatabaseFormatOptions, DatabaseId,
+    BlockCompression, ClientDatabaseError, ClientDatabaseFormatError, ClientDatabaseFormatOptions,
+    DATABASE_VERSION, DatabaseId, DurabilityPolicy,
 };
+use ab_client_api::{ReadBlockError, StorageItemCorruptionError};
+use ab_core_primitives::block::BlockRoot;
 use ab_io_type::trivial_type::TrivialType;
 use enum_map::{EnumMap, enum_map};
 use futures::FutureExt;
@@ -20,8 +32,10 @@ use rand::rngs::SysRng;
 use replace_with::replace_with_or_abort_and_return;
 use std::cmp::Reverse;
 use std::collections::VecDeque;
+use std::sync::Arc as StdArc;
 use std::task::Poll;
-use std::{future, io, iter};
+use std::time::Instant;
+use std::{future, io, iter, mem, slice};
 use strum::FromRepr;
 use tracing::{Instrument, debug, error, info_span};
 
@@ -33,6 +47,20 @@ pub(crate) enum PageGroupKind {
     /// These pages are temporary and are pruned over time as blocks become buried deeper in
     /// blockchain history
     Temporary = 1,
+    /// These pages contain contract state, which is superseded by newer versions over time
+    // TODO: Compaction and reclamation of state page groups
+    State = 2,
+}
+
+impl From<PageGroupKind> for crate::stats::PageGroupKind {
+    #[inline(always)]
+    fn from(page_group_kind: PageGroupKind) -> Self {
+        match page_group_kind {
+            PageGroupKind::Permanent => Self::Permanent,
+            PageGroupKind::Temporary => Self::Temporary,
+            PageGroupKind::State => Self::State,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -43,6 +71,26 @@ struct PageGroup {
     inner_next_page_offset: u32,
     /// Offset of the first page of this page group in the storage backend
     first_page_offset: u32,
+    /// Filter of block roots stored in this page group, `None` if not known.
+    ///
+    /// Only used for temporary page groups.
+    block_roots_filter: Option<BlockRootsFilter>,
+    /// Statistics of storage items stored in this page group, including the page group header
+    storage_items: EnumMap<StorageItemKind, StorageItemStats>,
+}
+
+impl PageGroup {
+    /// Account for a storage item that occupies `num_pages` pages of this page group
+    fn record_storage_item<SI>(&mut self, storage_item: &SI, num_pages: u32)
+    where
+        SI: StorageItem,
+    {
+        self.storage_items[storage_item.kind()] += StorageItemStats {
+            count: 1,
+            pages: u64::from(num_pages),
+            bytes: storage_item.total_bytes() as u64,
+        };
+    }
 }
 
 #[derive(Debug)]
@@ -54,20 +102,140 @@ struct PageGroups {
     /// The front page is the active one, meaning it is being appended to, the back page is the
     /// oldest page.
     ///
-    /// If pruning is needed, old pages are freed from back to front without gaps.
+    /// Any page group except the active one can be freed once its live storage items were
+    /// relocated by compaction, leaving a gap in sequence numbers between page groups.
     list: VecDeque<PageGroup>,
 }
 
+/// Contiguous pages of consecutive storage items that were not submitted to the storage backend yet
+#[derive(Debug, Default)]
+struct CoalescedWrite {
+    /// Offset of the first page in the storage backend
+    page_offset: u32,
+    pages: Vec<AlignedPage>,
+}
+
+impl CoalescedWrite {
+    fn buffered_pages(&self) -> BufferedPages<'_> {
+        BufferedPages {
+            page_offset: self.page_offset,
+            pages: &self.pages,
+        }
+    }
+}
+
+/// Contiguous pages that are not written to the storage backend yet
+#[derive(Debug, Copy, Clone)]
+struct BufferedPages<'a> {
+    /// Offset of the first page in the storage backend
+    page_offset: u32,
+    pages: &'a [AlignedPage],
+}
+
+impl BufferedPages<'_> {
+    /// Whether all `num_pages` pages starting at `page_offset` are being written
+    fn covers(&self, page_offset: u32, num_pages: u32) -> bool {
+        page_offset >= self.page_offset
+            && page_offset + num_pages <= self.page_offset + self.pages.len() as u32
+    }
+
+    /// Copy pages being written over `pages` that start at `page_offset` wherever they overlap
+    fn copy_overlapping(&self, pages: &mut [AlignedPage], page_offset: u32) {
+        let start = page_offset.max(self.page_offset);
+        let end =
+            (page_offset + pages.len() as u32).min(self.page_offset + self.pages.len() as u32);
+
+        if start < end {
+            pages[(start - page_offset) as usize..(end - page_offset) as usize].copy_from_slice(
+                &self.pages[(start - self.page_offset) as usize..(end - self.page_offset) as usize],
+            );
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PendingWrite {
+    receiver: oneshot::Receiver<io::Result<StdArc<Vec<AlignedPage>>>>,
+    /// Offset of the first written page in the storage backend
+    page_offset: u32,
+    /// Written pages shared with the storage backend, such that storage items can be read before
+    /// the write is finished
+    pages: StdArc<Vec<AlignedPage>>,
+}
+
+impl PendingWrite {
+    fn buffered_pages(&self) -> BufferedPages<'_> {
+        BufferedPages {
+            page_offset: self.page_offset,
+            pages: &self.pages,
+        }
+    }
+
+    /// Reclaim the buffer for reuse once the storage backend returned it back after the write
+    fn reclaim_buffer(&mut self, buffer: StdArc<Vec<AlignedPage>>) -> Vec<AlignedPage> {
+        // Release own reference, such that the buffer can be unwrapped without copying
+        self.pages = StdArc::default();
+
+        let mut buffer = StdArc::try_unwrap(buffer).unwrap_or_default();
+        buffer.clear();
+        buffer
+    }
+}
+
 #[derive(Debug)]
 enum WriteBufferEntry {
     Free(Vec<AlignedPage>),
-    Occupied(oneshot::Receiver<io::Result<Vec<AlignedPage>>>),
+    Occupied(PendingWrite),
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub(crate) struct WriteLocation {
     pub(crate) page_offset: u32,
     pub(crate) num_pages: u32,
+    /// Sequence number of the storage item, verified when the storage item is read back
+    pub(crate) sequence_number: u64,
+}
+
+/// Error for [`StorageBackendAdapter::read_storage_item()`]
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ReadStorageItemError {
+    /// I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// Storage item doesn't match the write location
+    #[error("Corrupted storage item: {0}")]
+    Corrupted(#[from] StorageItemCorruptionError),
+}
+
+impl From<ReadStorageItemError> for ReadBlockError {
+    #[inline]
+    fn from(error: ReadStorageItemError) -> Self {
+        match error {
+            ReadStorageItemError::Io(error) => Self::StorageItemReadError { error },
+            ReadStorageItemError::Corrupted(error) => Self::CorruptedStorageItem { error },
+        }
+    }
+}
+
+impl From<ReadStorageItemError> for BackupError {
+    #[inline]
+    fn from(error: ReadStorageItemError) -> Self {
+        match error {
+            ReadStorageItemError::Io(error) => Self::Io { error },
+            ReadStorageItemError::Corrupted(error) => Self::CorruptedStorageItem { error },
+        }
+    }
+}
+
+/// Page group that is no longer appended to
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct InactivePageGroup {
+    /// Sequence number of the page group header
+    pub(crate) first_sequence_number: u64,
+    /// Offset of the first page of this page group in the storage backend
+    pub(crate) first_page_offset: u32,
+    /// Number of used pages, including the page group header
+    pub(crate) num_pages: u32,
 }
 
 #[derive(Debug)]
@@ -75,16 +243,19 @@ pub(crate) struct StorageItemHandlerArg<SI> {
     pub(crate) storage_item: SI,
     pub(crate) page_offset: u32,
     pub(crate) num_pages: u32,
+    pub(crate) sequence_number: u64,
 }
 
 /// Storage item handlers are called on every storage item, storage items are read in the same order
 /// they are defined in this data structure
 #[derive(Debug)]
-pub(crate) struct StorageItemHandlers<P, T> {
+pub(crate) struct StorageItemHandlers<P, T, S> {
     /// Handler for storage items in permanent storage groups
     pub(crate) permanent: P,
     /// Handler for storage items in temporary storage groups
     pub(crate) temporary: T,
+    /// Handler for storage items in state storage groups
+    pub(crate) state: S,
 }
 
 #[derive(Debug)]
@@ -93,35 +264,55 @@ pub(crate) struct StorageBackendAdapter<StorageBackend> {
     database_version: u8,
     /// Page group size in pages
     page_group_size: u32,
+    /// Compression of blocks written to page groups of each kind
+    block_compression: EnumMap<PageGroupKind, BlockCompression>,
     storage_backend: StorageBackend,
     write_buffer: Box<[WriteBufferEntry]>,
+    /// Max number of pages in [`Self::coalesced_write`], zero disables coalescing
+    max_coalesced_write_pages: u32,
+    /// Pages of consecutive storage items that were not submitted to the storage backend yet
+    coalesced_write: CoalescedWrite,
     page_groups: EnumMap<PageGroupKind, PageGroups>,
     /// Offsets of the first pages that correspond to free page groups.
     ///
     /// Newly freed pages are added to the back, the oldest freed pages are pulled from the front.
     free_page_groups: VecDeque<u32>,
+    durability_policy: DurabilityPolicy,
+    /// Whether to maintain and persist filters of block roots for temporary page groups
+    block_roots_filters: bool,
+    /// Filter of the temporary page group that was just filled and needs to be persisted
+    pending_block_roots_filter: Option<StorageItemTemporaryBlockRootsFilter>,
+    /// Whether there were writes since the last sync
+    has_unsynced_writes: bool,
     had_write_failure: bool,
+    metrics: ClientDatabaseMetrics,
 }
 
 impl<StorageBackend> StorageBackendAdapter<StorageBackend>
 where
     StorageBackend: ClientDatabaseStorageBackend,
 {
-    /// Current database version
-    const VERSION: u8 = 0;
+    /// Max number of pages zeroed with a single write when freeing a page group
+    const ZEROING_BATCH_PAGES: u32 = 256;
 
-    pub(crate) async fn open<SIHP, SIHT>(
+    pub(crate) async fn open<SIHP, SIHT, SIHS>(
         write_buffer_size: usize,
-        mut storage_item_handlers: StorageItemHandlers<SIHP, SIHT>,
+        max_coalesced_write_pages: u32,
+        durability_policy: DurabilityPolicy,
+        block_roots_filters: bool,
+        mut storage_item_handlers: StorageItemHandlers<SIHP, SIHT, SIHS>,
         storage_backend: StorageBackend,
+        metrics: ClientDatabaseMetrics,
     ) -> Result<Self, ClientDatabaseError>
     where
         SIHP: FnMut(StorageItemHandlerArg<StorageItemPermanent>) -> Result<(), ClientDatabaseError>,
         SIHT: FnMut(StorageItemHandlerArg<StorageItemTemporary>) -> Result<(), ClientDatabaseError>,
+        SIHS: FnMut(StorageItemHandlerArg<StorageItemState>) -> Result<(), ClientDatabaseError>,
     {
         let database_id;
         let database_version;
         let page_group_size;
+        let block_compression;
         let num_page_groups;
 
         let mut page_groups = enum_map! {
@@ -133,6 +324,10 @@ where
                 next_sequence_number: 0,
                 list: VecDeque::new(),
             },
+            PageGroupKind::State => PageGroups {
+                next_sequence_number: 0,
+                list: VecDeque::new(),
+            },
         };
         let mut free_page_groups = VecDeque::new();
 
@@ -158,7 +353,7 @@ where
                 };
 
             let page_group_header = &container.storage_item;
-            if page_group_header.database_version != Self::VERSION {
+            if page_group_header.database_version != DATABASE_VERSION {
                 return Err(ClientDatabaseError::UnsupportedDatabaseVersion {
                     database_version: page_group_header.database_version,
                 });
@@ -166,6 +361,7 @@ where
             database_id = page_group_header.database_id;
             database_version = page_group_header.database_version;
             page_group_size = page_group_header.page_group_size;
+            block_compression = page_group_header.block_compression();
             if page_group_size < 2 {
                 return Err(ClientDatabaseError::PageGroupSizeTooSmall { page_group_size });
             }
@@ -175,13 +371,16 @@ where
                 PageGroupKind::Permanent => {
                     page_groups[PageGroupKind::Permanent]
                         .list
-                        .push_front(PageGroup {
+                        .push_front_mut(PageGroup {
                             first_sequence_number: container.sequence_number,
                             inner_next_page_offset: container.num_pages(),
                             first_page_offset: 0,
-                        });
+                            block_roots_filter: None,
+                            storage_items: EnumMap::default(),
+                        })
+                        .record_storage_item(page_group_header, container.num_pages());
                 }
-                PageGroupKind::Temporary => {
+                PageGroupKind::Temporary | PageGroupKind::State => {
                     return Err(ClientDatabaseError::NonPermanentFirstPageGroup);
                 }
             }
@@ -215,11 +414,14 @@ where
                 continue;
             }
 
-            let page_group = PageGroup {
+            let mut page_group = PageGroup {
                 first_sequence_number: container.sequence_number,
                 inner_next_page_offset: container.num_pages(),
                 first_page_offset,
+                block_roots_filter: None,
+                storage_items: EnumMap::default(),
             };
+            page_group.record_storage_item(page_group_header, container.num_pages());
             page_groups[page_group_header.page_group_kind]
                 .list
                 .push_front(page_group);
@@ -246,6 +448,7 @@ where
                     storage_item: container.storage_item,
                     page_offset,
                     num_pages,
+                    sequence_number: container.sequence_number,
                 })
             },
         )
@@ -253,7 +456,7 @@ where
         .await?;
 
         // Read all temporary storage groups
-        let _: Vec<_> = StorageBackendAdapter::read_page_groups(
+        buffer = StorageBackendAdapter::read_page_groups(
             &mut page_groups[PageGroupKind::Temporary],
             page_group_size,
             &storage_backend,
@@ -265,23 +468,52 @@ where
                     storage_item: container.storage_item,
                     page_offset,
                     num_pages,
+                    sequence_number: container.sequence_number,
                 })
             },
         )
         .instrument(info_span!("", page_group_kind = ?PageGroupKind::Temporary))
         .await?;
 
+        // Read all state storage groups
+        let _: Vec<_> = StorageBackendAdapter::read_page_groups(
+            &mut page_groups[PageGroupKind::State],
+            page_group_size,
+            &storage_backend,
+            buffer,
+            |container, page_offset| {
+                let num_pages = container.num_pages();
+
+                (storage_item_handlers.state)(StorageItemHandlerArg {
+                    storage_item: container.storage_item,
+                    page_offset,
+                    num_pages,
+                    sequence_number: container.sequence_number,
+                })
+            },
+        )
+        .instrument(info_span!("", page_group_kind = ?PageGroupKind::State))
+        .await?;
+
         Ok(Self {
             database_id,
             database_version,
             page_group_size,
+            block_compression,
             storage_backend,
             write_buffer: iter::repeat_with(|| WriteBufferEntry::Free(Vec::new()))
                 .take(write_buffer_size)
                 .collect(),
+            max_coalesced_write_pages,
+            coalesced_write: CoalescedWrite::default(),
             page_groups,
             free_page_groups,
+            durability_policy,
+            block_roots_filters,
+            pending_block_roots_filter: None,
+            has_unsynced_writes: false,
             had_write_failure: false,
+            metrics,
         })
     }
 
@@ -297,12 +529,12 @@ where
                 .await
                 .map_err(|_error| ClientDatabaseFormatError::ReadRequestCancelled)?
                 .map_err(|error| ClientDatabaseFormatError::ReadError { error })?;
-            buffer.clear();
 
             if StorageItemContainer::<StorageItemPageGroupHeader>::read_from_pages(&buffer).is_ok()
             {
                 return Err(ClientDatabaseFormatError::AlreadyFormatted);
             }
+            buffer.clear();
         }
 
         let container = StorageItemContainer {
@@ -313,16 +545,45 @@ where
                     SysRng.try_fill_bytes(&mut id)?;
                     id
                 }),
-                database_version: Self::VERSION,
+                database_version: DATABASE_VERSION,
                 page_group_kind: PageGroupKind::Permanent,
-                padding: [0; _],
+                block_compression: StorageItemPageGroupHeader::block_compression_bytes(enum_map! {
+                    PageGroupKind::Permanent => options.block_compression.permanent,
+                    PageGroupKind::Temporary => options.block_compression.temporary,
+                    PageGroupKind::State => BlockCompression::None,
+                }),
                 page_group_size: options.page_group_size.get(),
             },
         };
-        Self::write_pages_to_buffer(&container, None, &mut buffer, 0)?;
 
-        let _buffer: Vec<AlignedPage> = storage_backend
-            .write(buffer, 0)
+        if options.known_segment_headers.is_empty() {
+            Self::write_pages_to_buffer(&container, None, &mut buffer, 0)?;
+        } else {
+            // Known segment headers are written into the first page group right after its header
+            let known_segment_headers = StorageItemContainer {
+                sequence_number: 1,
+                storage_item: StorageItemPermanent::KnownSegmentHeaders(
+                    StorageItemSegmentHeaders {
+                        segment_headers: options.known_segment_headers,
+                    },
+                ),
+            };
+
+            let num_pages = known_segment_headers.num_pages();
+            // `-1` accounts for the page group header
+            if num_pages > options.page_group_size.get() - 1 {
+                return Err(ClientDatabaseFormatError::KnownSegmentHeadersTooLarge { num_pages });
+            }
+
+            Self::write_pages_to_buffer(&known_segment_headers, Some(&container), &mut buffer, 0)?;
+        }
+
+        let _buffer: StdArc<Vec<AlignedPage>> = storage_backend
+            .write(StdArc::new(buffer), 0)
+            .await
+            .map_err(|_cancelled| ClientDatabaseFormatError::WriteRequestCancelled)??;
+        storage_backend
+            .sync()
             .await
             .map_err(|_cancelled| ClientDatabaseFormatError::WriteRequestCancelled)??;
 
@@ -346,10 +607,6 @@ where
 
         // Read all page groups from oldest to newest
         for page_group in target_page_groups.list.iter_mut().rev() {
-            if next_sequence_number == 0 {
-                next_sequence_number = page_group.first_sequence_number;
-            }
-
             buffer.clear();
             buffer = storage_backend
                 .read(
@@ -362,9 +619,11 @@ where
                 .map_err(|_error| ClientDatabaseError::ReadRequestCancelled)?
                 .map_err(|error| ClientDatabaseError::ReadError { error })?;
 
-            // Account for the page group header that was already read
-            if page_group.first_sequence_number == next_sequence_number {
-                next_sequence_number += 1;
+            // Page groups that were freed after compaction leave gaps in sequence numbers between
+            // page groups, but sequence numbers must still be increasing
+            if page_group.first_sequence_number >= next_sequence_number {
+                // Account for the page group header that was already read
+                next_sequence_number = page_group.first_sequence_number + 1;
             } else {
                 error!(
                     actual = page_group.first_sequence_number,
@@ -414,6 +673,7 @@ where
                     });
                 }
 
+                page_group.record_storage_item(&container.storage_item, num_pages);
                 storage_item_handler(container, page_offset)?;
 
                 pages = &pages[num_pages as usize..];
@@ -426,19 +686,75 @@ where
         Ok(buffer)
     }
 
+    /// Read a storage item at the specified write location.
+    ///
+    /// Storage item is verified against the write location, a typed corruption error is returned
+    /// if it doesn't match, for example, due to a stale write location.
     pub(super) async fn read_storage_item<SI>(
         &self,
         write_location: WriteLocation,
-    ) -> io::Result<SI>
+    ) -> Result<SI, ReadStorageItemError>
     where
         SI: UniqueStorageItem,
     {
         let WriteLocation {
             page_offset,
             num_pages,
+            sequence_number,
         } = write_location;
 
-        let pages = self
+        let start = Instant::now();
+        let pages = self.read_pages(num_pages, page_offset).await?;
+        self.metrics
+            .read_time
+            .observe(start.elapsed().as_secs_f64());
+
+        let container = StorageItemContainer::<SI>::read_from_pages(&pages).map_err(|error| {
+            StorageItemCorruptionError::InvalidStorageItem {
+                page_offset,
+                reason: error.to_string(),
+            }
+        })?;
+
+        if container.sequence_number != sequence_number {
+            return Err(StorageItemCorruptionError::UnexpectedSequenceNumber {
+                page_offset,
+                expected: sequence_number,
+                actual: container.sequence_number,
+            }
+            .into());
+        }
+
+        Ok(container.storage_item)
+    }
+
+    /// Read pages the same way as [`ClientDatabaseStorageBackend::read()`], but taking buffered
+    /// writes into account.
+    ///
+    /// Location of a storage item is returned as soon as its write is accepted into the write
+    /// buffer (or coalesced with other writes), but the storage backend might process reads and
+    /// writes out of order, so pages that are still being written are served from the write buffer
+    /// instead.
+    async fn read_pages(&self, num_pages: u32, page_offset: u32) -> io::Result<Vec<AlignedPage>> {
+        let pending_writes = self
+            .write_buffer
+            .iter()
+            .filter_map(|entry| match entry {
+                WriteBufferEntry::Free(_) => None,
+                WriteBufferEntry::Occupied(pending_write) => Some(pending_write.buffered_pages()),
+            })
+            .chain(iter::once(self.coalesced_write.buffered_pages()));
+
+        for pending_write in pending_writes.clone() {
+            if pending_write.covers(page_offset, num_pages) {
+                let mut pages = vec![AlignedPage::default(); num_pages as usize];
+                pending_write.copy_overlapping(&mut pages, page_offset);
+
+                return Ok(pages);
+            }
+        }
+
+        let mut pages = self
             .storage_backend
             .read(Vec::new(), num_pages, page_offset)
             .await
@@ -450,10 +766,498 @@ where
             })
             .flatten()?;
 
-        let container = StorageItemContainer::read_from_pages(&pages)
-            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        for pending_write in pending_writes {
+            pending_write.copy_overlapping(&mut pages, page_offset);
+        }
 
-        Ok(container.storage_item)
+        Ok(pages)
+    }
+
+    /// Temporary page groups that are no longer appended to, from oldest to newest
+    pub(super) fn inactive_temporary_page_groups(&self) -> Vec<InactivePageGroup> {
+        self.page_groups[PageGroupKind::Temporary]
+            .list
+            .iter()
+            // The front page group is the active one
+            .skip(1)
+            .rev()
+            .map(|page_group| InactivePageGroup {
+                first_sequence_number: page_group.first_sequence_number,
+                first_page_offset: page_group.first_page_offset,
+                num_pages: page_group.inner_next_page_offset,
+            })
+            .collect()
+    }
+
+    /// Whether the page group is still an inactive temporary page group, it might have been freed
+    /// concurrently since it was returned by [`Self::inactive_temporary_page_groups()`]
+    pub(super) fn is_inactive_temporary_page_group(&self, page_group: InactivePageGroup) -> bool {
+        self.page_groups[PageGroupKind::Temporary]
+            .list
+            .iter()
+            // The front page group is the active one
+            .skip(1)
+            .any(|existing_page_group| {
+                existing_page_group.first_page_offset == page_group.first_page_offset
+                    && existing_page_group.first_sequence_number == page_group.first_sequence_number
+            })
+    }
+
+    /// Statistics of space usage by page groups and storage items
+    pub(super) fn stats(&self) -> ClientDatabaseStats {
+        let mut page_groups = self
+            .page_groups
+            .iter()
+            .flat_map(|(page_group_kind, page_groups)| {
+                page_groups
+                    .list
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, page_group)| PageGroupStats {
+                        kind: page_group_kind.into(),
+                        first_page_offset: page_group.first_page_offset,
+                        // The front page group is the active one
+                        active: index == 0,
+                        used_pages: page_group.inner_next_page_offset,
+                        storage_items: page_group.storage_items,
+                    })
+            })
+            .collect::<Vec<_>>();
+        page_groups.sort_by_key(|page_group| page_group.first_page_offset);
+
+        let mut storage_items = EnumMap::<StorageItemKind, StorageItemStats>::default();
+        for page_group in &page_groups {
+            for (storage_item_kind, storage_item_stats) in &page_group.storage_items {
+                storage_items[storage_item_kind] += *storage_item_stats;
+            }
+        }
+
+        let total_pages = self.storage_backend.num_pages();
+        let used_pages = page_groups
+            .iter()
+            .map(|page_group| page_group.used_pages)
+            .sum::<u32>();
+
+        ClientDatabaseStats {
+            total_pages,
+            used_pages,
+            free_pages: total_pages - used_pages,
+            page_group_size: self.page_group_size,
+            free_page_groups: self.free_page_groups.len() as u32,
+            page_groups,
+            storage_items,
+        }
+    }
+
+    /// Verify integrity of all page groups, including free ones.
+    ///
+    /// Found issues are added to the report, only I/O errors are returned as errors.
+    pub(super) async fn verify(&self, report: &mut VerificationReport) -> io::Result<()> {
+        self.verify_page_groups::<StorageItemPermanent>(
+            &self.page_groups[PageGroupKind::Permanent],
+            report,
+        )
+        .await?;
+        self.verify_page_groups::<StorageItemTemporary>(
+            &self.page_groups[PageGroupKind::Temporary],
+            report,
+        )
+        .await?;
+        self.verify_page_groups::<StorageItemState>(
+            &self.page_groups[PageGroupKind::State],
+            report,
+        )
+        .await?;
+
+        // Free page groups are zeroed, any data in them is not reachable
+        for &first_page_offset in &self.free_page_groups {
+            let pages = self
+                .read_pages(self.page_group_size, first_page_offset)
+                .await?;
+            report.checked_page_groups += 1;
+
+            Self::check_orphaned_pages(&pages, first_page_offset, report);
+        }
+
+        Ok(())
+    }
+
+    async fn verify_page_groups<SI>(
+        &self,
+        page_groups: &PageGroups,
+        report: &mut VerificationReport,
+    ) -> io::Result<()>
+    where
+        SI: StorageItem,
+    {
+        let mut next_sequence_number = 0;
+
+        // Check page groups from oldest to newest
+        for page_group in page_groups.list.iter().rev() {
+            let first_page_offset = page_group.first_page_offset;
+            let pages = self
+                .read_pages(self.page_group_size, first_page_offset)
+                .await?;
+            report.checked_page_groups += 1;
+
+            let mut page_index =
+                match StorageItemContainer::<StorageItemPageGroupHeader>::read_from_pages(&pages) {
+                    Ok(container) => {
+                        report.checked_storage_items += 1;
+
+                        // Page groups that were freed after compaction leave gaps in sequence
+                        // numbers between page groups, but sequence numbers
+                        // must still be increasing
+                        if container.sequence_number < next_sequence_number {
+                            report
+                                .issues
+                                .push(VerificationIssue::UnexpectedSequenceNumber {
+                                    page_offset: first_page_offset,
+                                    expected: next_sequence_number,
+                                    actual: container.sequence_number,
+                                });
+                        }
+                        next_sequence_number = container.sequence_number + 1;
+
+                        container.num_pages() as usize
+                    }
+                    Err(error) => {
+                        report.issues.push(VerificationIssue::CorruptedStorageItem {
+                            page_offset: first_page_offset,
+                            reason: error.to_string(),
+                        });
+
+                        Self::check_orphaned_pages(&pages[1..], first_page_offset + 1, report);
+                        continue;
+                    }
+                };
+
+            while let Some(remaining_pages) = pages.get(page_index..)
+                && !remaining_pages.is_empty()
+            {
+                let page_offset = first_page_offset + page_index as u32;
+
+                match StorageItemContainer::<SI>::read_from_pages(remaining_pages) {
+                    Ok(container) => {
+                        report.checked_storage_items += 1;
+
+                        if container.sequence_number != next_sequence_number {
+                            report
+                                .issues
+                                .push(VerificationIssue::UnexpectedSequenceNumber {
+                                    page_offset,
+                                    expected: next_sequence_number,
+                                    actual: container.sequence_number,
+                                });
+                        }
+                        next_sequence_number = container.sequence_number + 1;
+
+                        page_index += container.num_pages() as usize;
+                    }
+                    Err(error) => {
+                        // An empty page is the regular end of the page group
+                        if !Self::is_empty_page(&remaining_pages[0]) {
+                            report.issues.push(VerificationIssue::CorruptedStorageItem {
+                                page_offset,
+                                reason: error.to_string(),
+                            });
+                            page_index += 1;
+                        }
+
+                        Self::check_orphaned_pages(
+                            &pages[page_index..],
+                            first_page_offset + page_index as u32,
+                            report,
+                        );
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Report non-empty pages among `pages` that start at `page_offset` as orphaned
+    fn check_orphaned_pages(
+        pages: &[AlignedPage],
+        page_offset: u32,
+        report: &mut VerificationReport,
+    ) {
+        let Some(first_non_empty) = pages.iter().position(|page| !Self::is_empty_page(page)) else {
+            return;
+        };
+
+        let num_pages = pages[first_non_empty..]
+            .iter()
+            .filter(|page| !Self::is_empty_page(page))
+            .count();
+
+        report.issues.push(VerificationIssue::OrphanedPages {
+            page_offset: page_offset + first_non_empty as u32,
+            num_pages: num_pages as u32,
+        });
+    }
+
+    fn is_empty_page(page: &AlignedPage) -> bool {
+        AlignedPage::slice_to_repr(slice::from_ref(page))[0]
+            .iter()
+            .all(|&byte| byte == 0)
+    }
+
+    /// Storage backend the adapter was created with.
+    ///
+    /// Writes must be flushed with [`Self::flush()`] beforehand.
+    pub(super) fn into_storage_backend(self) -> StorageBackend {
+        self.storage_backend
+    }
+
+    /// Whether there are free page groups that new storage items can be written to once the
+    /// active page group is full
+    pub(super) fn has_free_page_groups(&self) -> bool {
+        !self.free_page_groups.is_empty()
+    }
+
+    /// Add the root of a block stored at `write_location` to the filter of the corresponding
+    /// temporary page group.
+    ///
+    /// Does nothing unless filters of block roots are enabled.
+    pub(super) fn add_block_root(&mut self, write_location: WriteLocation, block_root: &BlockRoot) {
+        if !self.block_roots_filters {
+            return;
+        }
+
+        let page_group_size = self.page_group_size;
+        if let Some(page_group) = self.page_groups[PageGroupKind::Temporary]
+            .list
+            .iter_mut()
+            .find(|page_group| {
+                (page_group.first_page_offset..page_group.first_page_offset + page_group_size)
+                    .contains(&write_location.page_offset)
+            })
+        {
+            page_group
+                .block_roots_filter
+                .get_or_insert_default()
+                .insert(block_root);
+        }
+    }
+
+    /// Restore a previously persisted filter of block roots.
+    ///
+    /// Does nothing unless filters of block roots are enabled or if the page group no longer
+    /// exists.
+    pub(super) fn restore_block_roots_filter(
+        &mut self,
+        block_roots_filter: &StorageItemTemporaryBlockRootsFilter,
+    ) {
+        if !self.block_roots_filters {
+            return;
+        }
+
+        if let Some(page_group) = self.page_groups[PageGroupKind::Temporary]
+            .list
+            .iter_mut()
+            .find(|page_group| {
+                page_group.first_sequence_number
+                    == block_roots_filter.page_group_first_sequence_number
+            })
+        {
+            page_group.block_roots_filter = Some(block_roots_filter.filter);
+        }
+    }
+
+    /// Whether a persisted filter of block roots still corresponds to an existing page group
+    pub(super) fn is_block_roots_filter_live(
+        &self,
+        block_roots_filter: &StorageItemTemporaryBlockRootsFilter,
+    ) -> bool {
+        self.page_groups[PageGroupKind::Temporary]
+            .list
+            .iter()
+            .any(|page_group| {
+                page_group.first_sequence_number
+                    == block_roots_filter.page_group_first_sequence_number
+            })
+    }
+
+    /// Temporary page groups that might contain a block with the specified root, from newest to
+    /// oldest.
+    ///
+    /// Page groups without a known filter of block roots are always included. The active page
+    /// group is included with pages that were used so far.
+    pub(super) fn temporary_page_groups_with_block_root(
+        &self,
+        block_root: &BlockRoot,
+    ) -> Vec<InactivePageGroup> {
+        self.page_groups[PageGroupKind::Temporary]
+            .list
+            .iter()
+            .filter(|page_group| {
+                page_group
+                    .block_roots_filter
+                    .as_ref()
+                    .is_none_or(|block_roots_filter| block_roots_filter.may_contain(block_root))
+            })
+            .map(|page_group| InactivePageGroup {
+                first_sequence_number: page_group.first_sequence_number,
+                first_page_offset: page_group.first_page_offset,
+                num_pages: page_group.inner_next_page_offset,
+            })
+            .collect()
+    }
+
+    /// Read all storage items of a page group except the page group header
+    pub(super) async fn read_page_group<SI>(
+        &self,
+        page_group: InactivePageGroup,
+    ) -> io::Result<Vec<(SI, WriteLocation)>>
+    where
+        SI: UniqueStorageItem,
+    {
+        // `+1` and `-1` account for the page group header
+        let first_item_page_offset = page_group.first_page_offset + 1;
+        let pages = self
+            .read_pages(page_group.num_pages - 1, first_item_page_offset)
+            .await?;
+
+        let mut storage_items = Vec::new();
+        let mut remaining_pages = pages.as_slice();
+        while !remaining_pages.is_empty() {
+            let container = StorageItemContainer::<SI>::read_from_pages(remaining_pages)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            let num_pages = container.num_pages();
+
+            storage_items.push((
+                container.storage_item,
+                WriteLocation {
+                    page_offset: first_item_page_offset
+                        + (pages.len() - remaining_pages.len()) as u32,
+                    num_pages,
+                    sequence_number: container.sequence_number,
+                },
+            ));
+
+            remaining_pages = remaining_pages.get(num_pages as usize..).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Storage item exceeds used pages of the page group",
+                )
+            })?;
+        }
+
+        Ok(storage_items)
+    }
+
+    /// Free an inactive temporary page group, such that it can be reused for new storage items.
+    ///
+    /// All previous writes are flushed first, such that storage items that were relocated out of
+    /// this page group are durable before the originals are erased. Used pages are zeroed before
+    /// the page group header, such that storage items from the previous use of the page group are
+    /// never mistaken for valid ones once it is reused.
+    pub(super) async fn free_temporary_page_group(
+        &mut self,
+        page_group: InactivePageGroup,
+    ) -> io::Result<()> {
+        if self.had_write_failure {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "Previous write operation failed, writes are not allowed until restart",
+            ));
+        }
+
+        let position = self.page_groups[PageGroupKind::Temporary]
+            .list
+            .iter()
+            .position(|existing_page_group| {
+                existing_page_group.first_page_offset == page_group.first_page_offset
+                    && existing_page_group.first_sequence_number == page_group.first_sequence_number
+            })
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Page group not found"))?;
+        if position == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Active page group can't be freed",
+            ));
+        }
+
+        self.flush().await?;
+
+        // Failure leaves the page group in an unknown state, so it is treated the same way as a
+        // failed write
+        self.free_page_group_inner(page_group)
+            .await
+            .inspect_err(|_error| {
+                self.had_write_failure = true;
+            })?;
+
+        self.page_groups[PageGroupKind::Temporary]
+            .list
+            .remove(position);
+        self.free_page_groups
+            .push_back(page_group.first_page_offset);
+        self.metrics.page_groups_recycled.inc();
+
+        Ok(())
+    }
+
+    async fn free_page_group_inner(&self, page_group: InactivePageGroup) -> io::Result<()> {
+        let mut buffer = Vec::new();
+
+        // Zero storage items first and the page group header last, such that an interrupted
+        // operation leaves a valid (but shorter) page group behind
+        for batch_page_offset in
+            (1..page_group.num_pages).step_by(Self::ZEROING_BATCH_PAGES as usize)
+        {
+            let batch_num_pages =
+                (page_group.num_pages - batch_page_offset).min(Self::ZEROING_BATCH_PAGES);
+            buffer.clear();
+            buffer.resize(batch_num_pages as usize, AlignedPage::default());
+
+            buffer = self
+                .write_and_wait(buffer, page_group.first_page_offset + batch_page_offset)
+                .await?;
+        }
+        self.sync_and_wait().await?;
+
+        buffer.clear();
+        buffer.push(AlignedPage::default());
+        let _buffer: Vec<_> = self
+            .write_and_wait(buffer, page_group.first_page_offset)
+            .await?;
+        self.sync_and_wait().await
+    }
+
+    async fn write_and_wait(
+        &self,
+        buffer: Vec<AlignedPage>,
+        page_offset: u32,
+    ) -> io::Result<Vec<AlignedPage>> {
+        self.metrics.pages_written.inc_by(buffer.len() as u64);
+        self.storage_backend
+            .write(StdArc::new(buffer), page_offset)
+            .await
+            .map_err(|_cancelled| {
+                io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "Storage backend write was aborted",
+                )
+            })
+            .flatten()
+            .map(|buffer| StdArc::try_unwrap(buffer).unwrap_or_default())
+    }
+
+    async fn sync_and_wait(&self) -> io::Result<()> {
+        self.storage_backend
+            .sync()
+            .await
+            .map_err(|_cancelled| {
+                io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "Storage backend sync was aborted",
+                )
+            })
+            .flatten()
     }
 
     pub(super) async fn write_storage_item<SI>(
@@ -470,18 +1274,97 @@ where
             ));
         }
 
-        self.write_storage_item_inner(storage_item)
+        let start = Instant::now();
+        let write_location = self
+            .write_storage_item_inner(storage_item)
             .await
             .inspect_err(|_error| {
                 self.had_write_failure = true;
-            })
+            })?;
+        self.metrics
+            .write_time
+            .observe(start.elapsed().as_secs_f64());
+        self.has_unsynced_writes = true;
+
+        // Persist the filter of block roots of the page group that was just filled, which might in
+        // turn fill another page group
+        while let Some(block_roots_filter) = self.pending_block_roots_filter.take() {
+            self.write_storage_item_inner(StorageItemTemporary::BlockRootsFilter(Box::new(
+                block_roots_filter,
+            )))
+            .await
+            .inspect_err(|_error| {
+                self.had_write_failure = true;
+            })?;
+        }
+
+        if matches!(self.durability_policy, DurabilityPolicy::EveryItem) {
+            self.flush().await?;
+        }
+
+        self.update_write_buffer_occupancy();
+
+        Ok(write_location)
+    }
+
+    /// Wait for all buffered writes to finish and flush them to durable storage
+    pub(super) async fn flush(&mut self) -> io::Result<()> {
+        if self.had_write_failure {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "Previous write operation failed, writes are not allowed until restart",
+            ));
+        }
+
+        // Failed sync might leave written data in an unknown state, so it is treated the same way
+        // as a failed write
+        self.flush_inner().await.inspect_err(|_error| {
+            self.had_write_failure = true;
+        })
     }
 
-    async fn write_storage_item_inner<SI>(&mut self, storage_item: SI) -> io::Result<WriteLocation>
+    async fn flush_inner(&mut self) -> io::Result<()> {
+        self.submit_coalesced_write_inner().await?;
+
+        for entry in &mut self.write_buffer {
+            if let WriteBufferEntry::Occupied(pending_write) = entry {
+                let buffer = (&mut pending_write.receiver)
+                    .await
+                    .map_err(|_cancelled| {
+                        io::Error::new(
+                            io::ErrorKind::Interrupted,
+                            "Storage backend write was aborted",
+                        )
+                    })
+                    .flatten()?;
+
+                *entry = WriteBufferEntry::Free(pending_write.reclaim_buffer(buffer));
+            }
+        }
+
+        self.update_write_buffer_occupancy();
+
+        if !self.has_unsynced_writes {
+            return Ok(());
+        }
+
+        self.sync_and_wait().await?;
+        self.has_unsynced_writes = false;
+
+        Ok(())
+    }
+
+    async fn write_storage_item_inner<SI>(
+        &mut self,
+        mut storage_item: SI,
+    ) -> io::Result<WriteLocation>
     where
         SI: UniqueStorageItem,
     {
         let page_group_kind = SI::page_group_kind();
+        storage_item
+            .compress(self.block_compression[page_group_kind])
+            .map_err(io::Error::other)?;
         let target_page_groups = &mut self.page_groups[page_group_kind];
 
         let sequence_number = target_page_groups.next_sequence_number;
@@ -530,7 +1413,9 @@ where
                     database_id: self.database_id,
                     database_version: self.database_version,
                     page_group_kind,
-                    padding: [0; _],
+                    block_compression: StorageItemPageGroupHeader::block_compression_bytes(
+                        self.block_compression,
+                    ),
                     page_group_size: self.page_group_size,
                 },
             };
@@ -542,34 +1427,147 @@ where
             // Add a page that corresponds to the page group header
             num_pages_to_write += 1;
 
+            let block_roots_filters =
+                self.block_roots_filters && matches!(page_group_kind, PageGroupKind::Temporary);
+            if block_roots_filters
+                && let Some(previous_page_group) = target_page_groups.list.front()
+                && let Some(filter) = previous_page_group.block_roots_filter
+            {
+                self.pending_block_roots_filter = Some(StorageItemTemporaryBlockRootsFilter {
+                    page_group_first_sequence_number: previous_page_group.first_sequence_number,
+                    filter,
+                });
+            }
+
             let active_page_group = target_page_groups.list.push_front_mut(PageGroup {
                 first_sequence_number: sequence_number,
                 inner_next_page_offset: 0,
                 first_page_offset,
+                block_roots_filter: block_roots_filters.then(BlockRootsFilter::default),
+                storage_items: EnumMap::default(),
             });
+            active_page_group.record_storage_item(
+                &page_group_header.storage_item,
+                page_group_header.num_pages(),
+            );
 
             (active_page_group, Some(page_group_header))
         };
+        active_page_group.record_storage_item(&container.storage_item, container.num_pages());
 
         let write_page_offset =
             active_page_group.first_page_offset + active_page_group.inner_next_page_offset;
         active_page_group.inner_next_page_offset += num_pages_to_write;
+        self.metrics
+            .pages_written
+            .inc_by(u64::from(num_pages_to_write));
+
+        let write_location = WriteLocation {
+            page_offset: if maybe_page_group_header.is_some() {
+                // +1 because the page header is written in front of the storage item
+                write_page_offset + 1
+            } else {
+                write_page_offset
+            },
+            num_pages: container.num_pages(),
+            sequence_number: container.sequence_number,
+        };
 
-        // In case buffering is disabled, allocate a buffer on demand and wait for write to
-        // finish
-        if self.write_buffer.is_empty() {
-            let mut buffer = Vec::new();
+        if self.max_coalesced_write_pages > 0 {
+            if !self.coalesced_write.pages.is_empty()
+                && self.coalesced_write.page_offset + self.coalesced_write.pages.len() as u32
+                    != write_page_offset
+            {
+                self.submit_coalesced_write_inner().await?;
+            }
 
-            let page_offset = Self::write_pages_to_buffer(
+            if self.coalesced_write.pages.is_empty() {
+                self.coalesced_write.page_offset = write_page_offset;
+            }
+            Self::write_pages_to_buffer(
                 &container,
                 maybe_page_group_header.as_ref(),
-                &mut buffer,
+                &mut self.coalesced_write.pages,
                 write_page_offset,
             )?;
 
-            let _buffer: Vec<_> = self
+            if self.coalesced_write.pages.len() as u32 >= self.max_coalesced_write_pages {
+                self.submit_coalesced_write_inner().await?;
+            }
+        } else {
+            self.submit_write(write_page_offset, |buffer| {
+                Self::write_pages_to_buffer(
+                    &container,
+                    maybe_page_group_header.as_ref(),
+                    buffer,
+                    write_page_offset,
+                )
+                .map(|_page_offset| ())
+            })
+            .await?;
+        }
+
+        Ok(write_location)
+    }
+
+    /// Submit pages of storage items that were coalesced so far to the storage backend.
+    ///
+    /// Doesn't wait for the write to finish, see [`Self::flush()`] for that.
+    pub(super) async fn submit_coalesced_write(&mut self) -> io::Result<()> {
+        if self.had_write_failure {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "Previous write operation failed, writes are not allowed until restart",
+            ));
+        }
+
+        self.submit_coalesced_write_inner()
+            .await
+            .inspect_err(|_error| {
+                self.had_write_failure = true;
+            })?;
+        self.update_write_buffer_occupancy();
+
+        Ok(())
+    }
+
+    async fn submit_coalesced_write_inner(&mut self) -> io::Result<()> {
+        if self.coalesced_write.pages.is_empty() {
+            return Ok(());
+        }
+
+        let page_offset = self.coalesced_write.page_offset;
+        let mut pages = mem::take(&mut self.coalesced_write.pages);
+        // Buffer of the write buffer entry is reused for the next coalesced write
+        let reused_buffer = self
+            .submit_write(page_offset, |buffer| {
+                mem::swap(buffer, &mut pages);
+                Ok(())
+            })
+            .await;
+        pages.clear();
+        self.coalesced_write.pages = pages;
+
+        reused_buffer
+    }
+
+    /// Write pages produced by `write_pages` into a buffer starting at `page_offset`.
+    ///
+    /// The write is submitted to the write buffer if there is one (waiting for a free entry if
+    /// necessary), otherwise the write is done immediately.
+    async fn submit_write<WP>(&mut self, page_offset: u32, write_pages: WP) -> io::Result<()>
+    where
+        WP: FnOnce(&mut Vec<AlignedPage>) -> io::Result<()>,
+    {
+        // In case buffering is disabled, allocate a buffer on demand and wait for write to
+        // finish
+        if self.write_buffer.is_empty() {
+            let mut buffer = Vec::new();
+            write_pages(&mut buffer)?;
+
+            let _buffer: StdArc<Vec<_>> = self
                 .storage_backend
-                .write(buffer, write_page_offset)
+                .write(StdArc::new(buffer), page_offset)
                 .await
                 .map_err(|_cancelled| {
                     io::Error::new(
@@ -579,12 +1577,10 @@ where
                 })
                 .flatten()?;
 
-            return Ok(WriteLocation {
-                page_offset,
-                num_pages: container.num_pages(),
-            });
+            return Ok(());
         }
 
+        let mut write_pages = Some(write_pages);
         let write_fut = future::poll_fn(|cx| {
             // Find a free write buffer entry among those that are either completely free or already
             // finished and can be reused
@@ -593,21 +1589,17 @@ where
                     let mut buffer = match entry {
                         // Already free buffer
                         WriteBufferEntry::Free(buffer) => buffer,
-                        WriteBufferEntry::Occupied(mut receiver) => {
+                        WriteBufferEntry::Occupied(mut pending_write) => {
                             // Poll pending write attempt
-                            match receiver.poll_unpin(cx) {
+                            match pending_write.receiver.poll_unpin(cx) {
                                 Poll::Ready(Ok(write_result)) => match write_result {
                                     // Write succeeded, reuse buffer
-                                    Ok(mut buffer) => {
-                                        buffer.clear();
-
-                                        buffer
-                                    }
+                                    Ok(buffer) => pending_write.reclaim_buffer(buffer),
                                     // Write failed
                                     Err(error) => {
                                         return (
                                             Some(Err(error)),
-                                            WriteBufferEntry::Occupied(receiver),
+                                            WriteBufferEntry::Occupied(pending_write),
                                         );
                                     }
                                 },
@@ -618,41 +1610,38 @@ where
                                             io::ErrorKind::Interrupted,
                                             "Storage backend write was aborted",
                                         ))),
-                                        WriteBufferEntry::Occupied(receiver),
+                                        WriteBufferEntry::Occupied(pending_write),
                                     );
                                 }
                                 // Still in progress
                                 Poll::Pending => {
-                                    return (None, WriteBufferEntry::Occupied(receiver));
+                                    return (None, WriteBufferEntry::Occupied(pending_write));
                                 }
                             }
                         }
                     };
 
-                    // Write storage item pages to the buffer
-                    let page_offset = match Self::write_pages_to_buffer(
-                        &container,
-                        maybe_page_group_header.as_ref(),
-                        &mut buffer,
-                        write_page_offset,
-                    ) {
-                        Ok(page_offset) => page_offset,
-                        Err(error) => {
-                            buffer.clear();
-                            return (
-                                Some(Err(io::Error::other(error))),
-                                WriteBufferEntry::Free(buffer),
-                            );
-                        }
-                    };
+                    // Write pages to the buffer
+                    let write_pages = write_pages
+                        .take()
+                        .expect("Only called once before returning `Poll::Ready`; qed");
+                    if let Err(error) = write_pages(&mut buffer) {
+                        buffer.clear();
+                        return (Some(Err(error)), WriteBufferEntry::Free(buffer));
+                    }
 
-                    let receiver = self.storage_backend.write(buffer, write_page_offset);
+                    // Keep a reference to written pages for reads until the write is finished
+                    let pages = StdArc::new(buffer);
+                    let receiver = self
+                        .storage_backend
+                        .write(StdArc::clone(&pages), page_offset);
                     (
-                        Some(Ok(WriteLocation {
+                        Some(Ok(())),
+                        WriteBufferEntry::Occupied(PendingWrite {
+                            receiver,
                             page_offset,
-                            num_pages: container.num_pages(),
-                        })),
-                        WriteBufferEntry::Occupied(receiver),
+                            pages,
+                        }),
                     )
                 })
             });
@@ -666,6 +1655,17 @@ where
         write_fut.await
     }
 
+    fn update_write_buffer_occupancy(&self) {
+        let occupied = self
+            .write_buffer
+            .iter()
+            .filter(|entry| matches!(entry, WriteBufferEntry::Occupied(_)))
+            .count();
+        self.metrics
+            .write_buffer_occupancy
+            .set(i64::try_from(occupied).unwrap_or(i64::MAX));
+    }
+
     /// Write (append) a storage item with an optional page group header in front of it.
     ///
     /// Returns page offset at which an item is written based on `preliminary_page_offset`.
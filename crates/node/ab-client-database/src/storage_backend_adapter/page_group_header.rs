@@ -1,9 +1,11 @@
-use crate::DatabaseId;
+use crate::stats::StorageItemKind;
 use crate::storage_backend_adapter::PageGroupKind;
 use crate::storage_backend_adapter::storage_item::{
     StorageItem, StorageItemError, StorageItemWriteResult,
 };
+use crate::{BlockCompression, DatabaseId};
 use ab_io_type::trivial_type::TrivialType;
+use enum_map::EnumMap;
 use std::mem;
 use std::mem::MaybeUninit;
 
@@ -18,13 +20,49 @@ pub(crate) struct StorageItemPageGroupHeader {
     pub(crate) database_version: u8,
     /// The kind of page group
     pub(crate) page_group_kind: PageGroupKind,
-    // Padding for data alignment
-    pub(crate) padding: [u8; 2],
+    /// [`BlockCompression`] of blocks in permanent and temporary page groups respectively.
+    ///
+    /// Zero (no compression) in databases formatted before block compression was introduced.
+    pub(crate) block_compression: [u8; 2],
     /// The number of pages in a page group
     pub(crate) page_group_size: u32,
 }
 
+impl StorageItemPageGroupHeader {
+    /// Block compression for each page group kind
+    pub(crate) fn block_compression(&self) -> EnumMap<PageGroupKind, BlockCompression> {
+        EnumMap::from_fn(|page_group_kind| {
+            let block_compression_byte = match page_group_kind {
+                PageGroupKind::Permanent => self.block_compression[0],
+                PageGroupKind::Temporary => self.block_compression[1],
+                // Blocks are never stored in state page groups
+                PageGroupKind::State => {
+                    return BlockCompression::None;
+                }
+            };
+
+            BlockCompression::from_repr(block_compression_byte)
+                .expect("Checked when reading page group header; qed")
+        })
+    }
+
+    /// The inverse of [`Self::block_compression()`]
+    pub(crate) fn block_compression_bytes(
+        block_compression: EnumMap<PageGroupKind, BlockCompression>,
+    ) -> [u8; 2] {
+        [
+            block_compression[PageGroupKind::Permanent] as u8,
+            block_compression[PageGroupKind::Temporary] as u8,
+        ]
+    }
+}
+
 impl StorageItem for StorageItemPageGroupHeader {
+    #[inline(always)]
+    fn kind(&self) -> StorageItemKind {
+        StorageItemKind::PageGroupHeader
+    }
+
     #[inline(always)]
     fn total_bytes(&self) -> usize {
         size_of::<Self>()
@@ -68,6 +106,12 @@ impl StorageItem for StorageItemPageGroupHeader {
         }
         let kind_byte = buffer[mem::offset_of!(Self, page_group_kind)];
         PageGroupKind::from_repr(kind_byte).ok_or(StorageItemError::InvalidBufferContents)?;
+        for block_compression_byte in
+            &buffer[mem::offset_of!(Self, block_compression)..][..size_of::<[u8; 2]>()]
+        {
+            BlockCompression::from_repr(*block_compression_byte)
+                .ok_or(StorageItemError::InvalidBufferContents)?;
+        }
 
         // SAFETY: `PageGroupKind` checked above, all other bit pattens are valid
         let maybe_item = unsafe { Self::from_bytes(buffer) };
@@ -1,10 +1,12 @@
+use crate::BlockCompression;
+use crate::stats::StorageItemKind;
 use crate::storage_backend::AlignedPage;
 use crate::storage_backend_adapter::PageGroupKind;
 use ab_blake3::single_block_hash;
 use ab_core_primitives::hashes::Blake3Hash;
 use blake3::hash;
 use std::mem::MaybeUninit;
-use std::{fmt, mem};
+use std::{fmt, io, mem};
 
 // TODO: use this
 /// The minimum overhead that the storage item will have due to the way it is stored on disk
@@ -61,6 +63,12 @@ pub(crate) enum StorageItemError {
     /// Invalid buffer contents
     #[error("Invalid buffer contents")]
     InvalidBufferContents,
+    /// Storage item compression failed
+    #[error("Storage item compression failed: {0}")]
+    Compression(io::Error),
+    /// Storage item decompression failed
+    #[error("Storage item decompression failed: {0}")]
+    Decompression(io::Error),
 }
 
 /// The result of [`StorageItem::write()`] call
@@ -74,6 +82,9 @@ pub(crate) struct StorageItemWriteResult<'a> {
 }
 
 pub(crate) trait StorageItem: fmt::Debug + Send + Sync + Sized + 'static {
+    /// Kind of storage item for statistics
+    fn kind(&self) -> StorageItemKind;
+
     /// Total number of bytes
     fn total_bytes(&self) -> usize;
 
@@ -89,6 +100,13 @@ pub(crate) trait StorageItem: fmt::Debug + Send + Sync + Sized + 'static {
 
     /// The inverse of [`Self::write_to_pages()`]
     fn read(variant: u8, buffer: &[u8]) -> Result<Self, StorageItemError>;
+
+    /// Compress the storage item before it is written to a page group with the specified block
+    /// compression, most storage items are never compressed
+    #[inline(always)]
+    fn compress(&mut self, _block_compression: BlockCompression) -> Result<(), StorageItemError> {
+        Ok(())
+    }
 }
 
 /// Storage item that maps to a unique page group kind
@@ -0,0 +1,106 @@
+//! Index of transactions in bodies of known blocks, see [`TransactionIndex`].
+
+use crate::BlockRootHasher;
+use ab_client_api::TransactionLocation;
+use ab_core_primitives::block::BlockRoot;
+use ab_core_primitives::block::body::BlockBody;
+use ab_core_primitives::block::body::owned::GenericOwnedBlockBody;
+use ab_core_primitives::transaction::TransactionHash;
+use smallvec::SmallVec;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+
+/// Index of transactions in bodies of known blocks.
+///
+/// The same transaction can be included in multiple blocks on different forks, so there might be
+/// more than one location for the same transaction hash.
+#[derive(Debug, Default)]
+pub(crate) struct TransactionIndex {
+    /// Locations of every transaction
+    transactions: HashMap<TransactionHash, SmallVec<[TransactionLocation; 1]>>,
+    /// Hashes of transactions included in blocks, only blocks with transactions are present
+    blocks: HashMap<BlockRoot, Box<[TransactionHash]>, BuildHasherDefault<BlockRootHasher>>,
+}
+
+impl TransactionIndex {
+    /// Add transactions of a block, adding the same block again is a no-op
+    pub(crate) fn add_block<Body>(&mut self, block_root: BlockRoot, body: &Body)
+    where
+        Body: GenericOwnedBlockBody,
+    {
+        // Only leaf shards have transactions
+        let BlockBody::LeafShard(body) = (*body.body()).into() else {
+            return;
+        };
+
+        if body.transactions().is_empty() || self.blocks.contains_key(&block_root) {
+            return;
+        }
+
+        let tx_hashes = body
+            .transactions()
+            .iter()
+            .enumerate()
+            .map(|(offset, transaction)| {
+                let tx_hash = transaction.hash();
+                self.transactions
+                    .entry(tx_hash)
+                    .or_default()
+                    .push(TransactionLocation {
+                        block_root,
+                        offset: offset as u32,
+                    });
+                tx_hash
+            })
+            .collect();
+
+        self.blocks.insert(block_root, tx_hashes);
+    }
+
+    /// Remove transactions of a block
+    pub(crate) fn remove_block(&mut self, block_root: &BlockRoot) {
+        let Some(tx_hashes) = self.blocks.remove(block_root) else {
+            return;
+        };
+
+        for tx_hash in tx_hashes {
+            if let Some(locations) = self.transactions.get_mut(&tx_hash) {
+                locations.retain(|location| location.block_root != *block_root);
+                if locations.is_empty() {
+                    self.transactions.remove(&tx_hash);
+                }
+            }
+        }
+    }
+
+    /// Retain only blocks for which `f` returns `true`
+    pub(crate) fn retain_blocks<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&BlockRoot) -> bool,
+    {
+        let block_roots_to_remove = self
+            .blocks
+            .keys()
+            .filter(|block_root| !f(block_root))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for block_root in &block_roots_to_remove {
+            self.remove_block(block_root);
+        }
+    }
+
+    /// Locations of a transaction, in the order in which corresponding blocks were added
+    pub(crate) fn locations(&self, tx_hash: &TransactionHash) -> &[TransactionLocation] {
+        self.transactions
+            .get(tx_hash)
+            .map(SmallVec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Remove all blocks
+    pub(crate) fn clear(&mut self) {
+        self.transactions.clear();
+        self.blocks.clear();
+    }
+}
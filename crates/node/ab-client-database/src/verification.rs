@@ -0,0 +1,73 @@
+//! Database integrity verification, see [`ClientDatabase::verify()`].
+//!
+//! [`ClientDatabase::verify()`]: crate::ClientDatabase::verify
+
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+
+/// Integrity issue found during verification
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VerificationIssue {
+    /// Storage item can't be decoded or its checksums do not match
+    CorruptedStorageItem {
+        /// Offset of the first page of the storage item
+        page_offset: u32,
+        /// Reason why the storage item is considered corrupted
+        reason: String,
+    },
+    /// Storage item has an unexpected sequence number
+    UnexpectedSequenceNumber {
+        /// Offset of the first page of the storage item
+        page_offset: u32,
+        /// Expected sequence number (minimum expected for page group headers)
+        expected: u64,
+        /// Actual sequence number
+        actual: u64,
+    },
+    /// Pages that contain data, but are not reachable as storage items of any page group
+    OrphanedPages {
+        /// Offset of the first orphaned page
+        page_offset: u32,
+        /// Number of non-empty pages starting with `page_offset` and up to the end of the page
+        /// group
+        num_pages: u32,
+    },
+    /// Parent of the block is not among known blocks at the previous block number
+    MissingParentBlock {
+        /// Block number
+        block_number: BlockNumber,
+        /// Block root
+        block_root: BlockRoot,
+        /// Root of the parent block according to the block header
+        parent_root: BlockRoot,
+    },
+    /// Block known to the database can't be read back from storage
+    CorruptedBlock {
+        /// Block number
+        block_number: BlockNumber,
+        /// Block root
+        block_root: BlockRoot,
+        /// Reason why the block is considered corrupted
+        reason: String,
+    },
+}
+
+/// Report produced by database integrity verification
+#[derive(Debug, Default, Clone)]
+pub struct VerificationReport {
+    /// Number of page groups that were checked, including free page groups
+    pub checked_page_groups: u32,
+    /// Number of storage items that were checked, including page group headers
+    pub checked_storage_items: u64,
+    /// Number of blocks that were checked
+    pub checked_blocks: u64,
+    /// Issues found during verification
+    pub issues: Vec<VerificationIssue>,
+}
+
+impl VerificationReport {
+    /// Whether no issues were found
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
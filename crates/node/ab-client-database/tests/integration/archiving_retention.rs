@@ -0,0 +1,111 @@
+//! Blocks that archiver still needs must not be pruned regardless of how many blocks are retained
+
+use crate::memory_storage_backend::{
+    BLOCK_CONFIRMATION_DEPTH, MemoryStorageBackend, database_options,
+};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::pruning_holds::PruningHoldTarget;
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions, ReclamationOptions,
+};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 256;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+const RETAINED_BLOCKS: BlockNumber = BlockNumber::from(12);
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn is_known(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) -> bool {
+    block_on(database.block(&root(block))).is_ok()
+}
+
+#[test]
+fn archiving_retention() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = block_on(ClientDatabase::open(ClientDatabaseOptions {
+        reclamation: ReclamationOptions {
+            retained_blocks: Some(RETAINED_BLOCKS),
+            ..
+        },
+        ..database_options(&genesis, storage_backend)
+    }))
+    .unwrap();
+
+    // Archiver uses the same confirmation depth as the database
+    assert_eq!(
+        database.block_confirmation_depth(),
+        BLOCK_CONFIRMATION_DEPTH
+    );
+
+    // Blocks `1..=30`
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 30);
+
+    // Blocks starting with `5` are still needed by the archiver
+    database.retain_blocks_for_archiving(BlockNumber::from(5));
+    for block in &blocks {
+        persist_block(&database, block);
+    }
+    assert!(!is_known(&database, &blocks[3]));
+    for block in &blocks[4..] {
+        assert!(is_known(&database, block));
+    }
+
+    let pruning_holds = database.pruning_holds();
+    assert_eq!(pruning_holds.len(), 1);
+    assert_eq!(pruning_holds[0].owner, "archiver");
+    assert_eq!(
+        pruning_holds[0].target,
+        PruningHoldTarget::BlockRange(BlockNumber::from(5)..=BlockNumber::MAX)
+    );
+
+    // Archiver made progress, blocks are pruned according to the number of retained blocks again
+    database.retain_blocks_for_archiving(BlockNumber::from(25));
+    assert_eq!(database.pruning_holds().len(), 1);
+    let block = TestBeaconChainBlockBuilder::default()
+        .chain(&blocks[29], 1)
+        .remove(0);
+    persist_block(&database, &block);
+    for block in &blocks[..18] {
+        assert!(!is_known(&database, block));
+    }
+    for block in &blocks[18..] {
+        assert!(is_known(&database, block));
+    }
+}
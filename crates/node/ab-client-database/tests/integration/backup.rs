@@ -0,0 +1,146 @@
+//! Database restored from a backup must contain the same blocks and auxiliary data, even with a
+//! different page group size, and corrupted backups must be rejected
+
+use crate::memory_storage_backend::{MemoryStorageBackend, open_database};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockAuxDataNamespace, BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite,
+};
+use ab_client_database::backup::BackupError;
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::iter;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+const RESTORED_NUM_PAGES: u32 = 96;
+const RESTORED_PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(8).expect("Not zero; qed");
+const NAMESPACE: BlockAuxDataNamespace = BlockAuxDataNamespace::new(*b"testtest");
+
+fn format_database(storage_backend: &MemoryStorageBackend, page_group_size: NonZeroU32) {
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+}
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn restore(backup: &[u8]) -> Result<MemoryStorageBackend, BackupError> {
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::restore(
+        MemoryStorageBackend::new(RESTORED_NUM_PAGES),
+        ClientDatabaseFormatOptions {
+            page_group_size: RESTORED_PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+        &mut &*backup,
+    ))
+}
+
+#[test]
+fn backup() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    format_database(&storage_backend, PAGE_GROUP_SIZE);
+    let database = open_database(&genesis, storage_backend);
+
+    // Blocks `1..=20`, the first ones are confirmed and the last ones are only in memory
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 20);
+    for block in &blocks {
+        persist_block(&database, block);
+    }
+    // Fork block `19` that doesn't become the best block
+    let fork_block = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(1)
+        .chain(&blocks[17], 1)
+        .remove(0);
+    persist_block(&database, &fork_block);
+    block_on(database.persist_block_aux_data(
+        &root(&blocks[5]),
+        NAMESPACE,
+        SharedAlignedBuffer::from_bytes(&[1, 2, 3]),
+    ))
+    .unwrap();
+
+    let mut backup = Vec::new();
+    block_on(database.backup(&mut backup)).unwrap();
+
+    let restored_database = open_database(&genesis, restore(&backup).unwrap());
+
+    assert_eq!(restored_database.best_root(), database.best_root());
+    for (block_number, block) in iter::once(&genesis).chain(&blocks).enumerate() {
+        assert_eq!(
+            restored_database.canonical_root(BlockNumber::from(block_number as u64)),
+            Some(root(block))
+        );
+        assert_eq!(
+            root(&block_on(restored_database.block(&root(block))).unwrap()),
+            root(block)
+        );
+    }
+    assert_eq!(
+        root(&block_on(restored_database.block(&root(&fork_block))).unwrap()),
+        root(&fork_block)
+    );
+    assert_eq!(
+        restored_database
+            .block_aux_data(&root(&blocks[5]), NAMESPACE)
+            .unwrap()
+            .as_slice(),
+        &[1, 2, 3]
+    );
+    assert!(block_on(restored_database.verify()).unwrap().is_ok());
+
+    // Corrupted backups are rejected
+    {
+        let mut corrupted_backup = backup.clone();
+        corrupted_backup[0] ^= 0xff;
+        assert!(matches!(
+            restore(&corrupted_backup),
+            Err(BackupError::InvalidMagic)
+        ));
+
+        let mut corrupted_backup = backup.clone();
+        *corrupted_backup.last_mut().unwrap() ^= 0xff;
+        assert!(matches!(
+            restore(&corrupted_backup),
+            Err(BackupError::ChecksumMismatch { .. })
+        ));
+
+        let truncated_backup = &backup[..backup.len() / 2];
+        assert!(matches!(
+            restore(truncated_backup),
+            Err(BackupError::Io { .. } | BackupError::InvalidStorageItem { .. })
+        ));
+    }
+}
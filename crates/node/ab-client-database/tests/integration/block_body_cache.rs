@@ -0,0 +1,85 @@
+//! Bodies of recently read persisted blocks are served from the cache without reading the storage
+
+use crate::memory_storage_backend::{
+    MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, database_options,
+};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::{
+    BlockBodyCacheOptions, ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions,
+};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 80;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+/// Number of blocks on top of genesis
+const NUM_BLOCKS: usize = 6;
+/// Number of blocks that are soft-confirmed and written to the storage
+const NUM_PERSISTED_BLOCKS: usize = NUM_BLOCKS - u64::from(SOFT_CONFIRMATION_DEPTH) as usize;
+const MAX_CACHED_BLOCKS: usize = 2;
+
+#[test]
+fn block_body_cache() {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = block_on(ClientDatabase::open(ClientDatabaseOptions {
+        block_body_cache: BlockBodyCacheOptions {
+            max_blocks: MAX_CACHED_BLOCKS,
+            ..
+        },
+        ..database_options(&genesis, storage_backend.clone())
+    }))
+    .unwrap();
+
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, NUM_BLOCKS);
+    for block in &blocks {
+        block_on(database.persist_block(
+            block.clone(),
+            BlockDetails {
+                mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+    }
+
+    let persisted_blocks = &blocks[..NUM_PERSISTED_BLOCKS];
+    for block in persisted_blocks {
+        block_on(database.block(&block.header.header().root())).unwrap();
+    }
+
+    // Storage contents are gone, only cached blocks can be read now
+    storage_backend.replace_bytes(&vec![0; storage_backend.to_bytes().len()]);
+
+    let (evicted_blocks, cached_blocks) =
+        persisted_blocks.split_at(NUM_PERSISTED_BLOCKS - MAX_CACHED_BLOCKS);
+    for block in cached_blocks {
+        let read_block = block_on(database.block(&block.header.header().root())).unwrap();
+        assert_eq!(
+            *read_block.header.header().root(),
+            *block.header.header().root()
+        );
+        assert_eq!(
+            read_block.body.buffer().as_slice(),
+            block.body.buffer().as_slice()
+        );
+    }
+    for block in evicted_blocks {
+        block_on(database.block(&block.header.header().root())).unwrap_err();
+    }
+}
@@ -0,0 +1,101 @@
+//! Blocks compressed according to block compression selected during formatting must use less
+//! space and must be read back unchanged, both before and after restart
+
+use crate::memory_storage_backend::{MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::stats::StorageItemKind;
+use ab_client_database::{
+    BlockCompression, BlockCompressionOptions, ClientDatabase, ClientDatabaseFormatOptions,
+};
+use ab_core_primitives::block::owned::{GenericOwnedBlock, OwnedBeaconChainBlock};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+const NUM_BLOCKS: usize = 20;
+
+fn assert_block_eq(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    expected: &OwnedBeaconChainBlock,
+) {
+    let block = block_on(database.block(&expected.header.header().root())).unwrap();
+    assert_eq!(
+        block.header().buffer().as_slice(),
+        expected.header().buffer().as_slice()
+    );
+    assert_eq!(
+        block.body().buffer().as_slice(),
+        expected.body().buffer().as_slice()
+    );
+}
+
+/// Returns the total size of stored blocks in bytes
+fn persist_and_check_blocks(block_compression: BlockCompression) -> u64 {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            block_compression: BlockCompressionOptions {
+                temporary: block_compression,
+                ..
+            },
+        },
+    ))
+    .unwrap();
+
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    let mut blocks = vec![genesis.clone()];
+    for _ in 0..NUM_BLOCKS {
+        let block = TestBeaconChainBlockBuilder::default().child(blocks.last().unwrap());
+        block_on(database.persist_block(
+            block.clone(),
+            BlockDetails {
+                mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+        blocks.push(block);
+    }
+
+    for block in &blocks {
+        assert_block_eq(&database, block);
+    }
+    drop(database);
+
+    let database = open_database(&genesis, storage_backend);
+
+    // More recent blocks are only kept in memory and are lost after restart
+    let persisted_blocks = blocks.len() - u64::from(SOFT_CONFIRMATION_DEPTH) as usize;
+    for block in &blocks[..persisted_blocks] {
+        assert_block_eq(&database, block);
+    }
+
+    block_on(database.stats())
+        .page_groups
+        .iter()
+        .map(|page_group| page_group.storage_items[StorageItemKind::Block].bytes)
+        .sum()
+}
+
+#[test]
+fn compressed_blocks_round_trip() {
+    let uncompressed_bytes = persist_and_check_blocks(BlockCompression::None);
+
+    for block_compression in [BlockCompression::Lz4, BlockCompression::Zstd] {
+        let compressed_bytes = persist_and_check_blocks(block_compression);
+        assert!(
+            compressed_bytes < uncompressed_bytes,
+            "{block_compression:?}: {compressed_bytes} >= {uncompressed_bytes}"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! MMR proofs must be available for all blocks of the canonical chain, including blocks that were
+//! pruned and whose page groups were reclaimed, both before and after restart
+
+use crate::memory_storage_backend::{
+    MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, database_options,
+};
+use ab_client_api::{
+    BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite, ReadMmrProofError,
+};
+use ab_client_database::stats::StorageItemKind;
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions, ReclamationOptions,
+};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 256;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+const RETAINED_BLOCKS: BlockNumber = BlockNumber::from(12);
+const NUM_BLOCKS: usize = 100;
+
+fn format_storage_backend() -> MemoryStorageBackend {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    storage_backend
+}
+
+fn open_database(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    block_on(ClientDatabase::open(ClientDatabaseOptions {
+        reclamation: ReclamationOptions {
+            retained_blocks: Some(RETAINED_BLOCKS),
+            ..
+        },
+        ..database_options(genesis, storage_backend)
+    }))
+    .unwrap()
+}
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+/// Check proofs of all blocks of the canonical chain, starting with genesis and ending with the
+/// best block
+fn assert_mmr_proofs(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    blocks: &[OwnedBeaconChainBlock],
+) {
+    let mut mmr = BlockMerkleMountainRange::new();
+    for block in blocks {
+        assert!(mmr.add_leaf(&root(block)));
+    }
+    let mmr_root = mmr.root().unwrap();
+
+    for (block_number, block) in blocks.iter().enumerate() {
+        let proof = database
+            .mmr_proof(BlockNumber::from(block_number as u64))
+            .unwrap();
+
+        assert_eq!(proof.block_root, root(block));
+        assert_eq!(proof.num_leaves, blocks.len() as u64);
+        assert_eq!(proof.mmr_root, mmr_root);
+        assert!(proof.verify());
+    }
+}
+
+#[test]
+fn block_mmr() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    let mut blocks = vec![genesis.clone()];
+    blocks.extend(TestBeaconChainBlockBuilder::default().chain(&genesis, NUM_BLOCKS));
+    for block in &blocks[1..] {
+        persist_block(&database, block);
+    }
+
+    // Proofs are generated for pruned blocks too
+    block_on(database.block(&root(&blocks[1]))).unwrap_err();
+    assert_mmr_proofs(&database, &blocks);
+
+    // Proofs are not available above the best block
+    assert!(matches!(
+        database.mmr_proof(BlockNumber::from(NUM_BLOCKS as u64 + 1)),
+        Err(ReadMmrProofError::UnknownBlockNumber { .. })
+    ));
+
+    // Roots of pruned blocks are persisted before their page groups are reclaimed
+    block_on(database.reclaim()).unwrap();
+    let stats = block_on(database.stats());
+    assert!(stats.storage_items[StorageItemKind::BlockMmr].count > 0);
+
+    // More recent blocks are only kept in memory and are lost after restart
+    drop(database);
+    let database = open_database(&genesis, storage_backend);
+    blocks.truncate(blocks.len() - u64::from(SOFT_CONFIRMATION_DEPTH) as usize);
+    assert_mmr_proofs(&database, &blocks);
+
+    // Proofs follow the canonical chain as new blocks are added
+    let more_blocks = TestBeaconChainBlockBuilder::default().chain(blocks.last().unwrap(), 20);
+    for block in &more_blocks {
+        persist_block(&database, block);
+    }
+    blocks.extend(more_blocks);
+    assert_mmr_proofs(&database, &blocks);
+}
@@ -0,0 +1,161 @@
+//! Execution outcomes of blocks must be readable after restart and pruned together with blocks
+
+use crate::memory_storage_backend::{MemoryStorageBackend, database_options};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockDetails, BlockMerkleMountainRange, BlockOutcome, ChainInfo, ChainInfoWrite,
+    PersistBlockOutcomeError, TransactionOutcome, TransactionStatus,
+};
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions, ReclamationOptions,
+};
+use ab_core_primitives::block::execution::ExecutionReceipt;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use ab_core_primitives::transaction::{Gas, TransactionHash};
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 256;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+const RETAINED_BLOCKS: BlockNumber = BlockNumber::from(12);
+
+fn format_storage_backend() -> MemoryStorageBackend {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    storage_backend
+}
+
+fn open_database(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    block_on(ClientDatabase::open(ClientDatabaseOptions {
+        reclamation: ReclamationOptions {
+            retained_blocks: Some(RETAINED_BLOCKS),
+            ..
+        },
+        ..database_options(genesis, storage_backend)
+    }))
+    .unwrap()
+}
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+/// Outcome with `num_transactions` transactions, the last of which failed
+fn outcome(seed: u8, num_transactions: u8) -> BlockOutcome {
+    BlockOutcome {
+        transactions: (0..num_transactions)
+            .map(|index| TransactionOutcome {
+                receipt: ExecutionReceipt {
+                    transaction_hash: TransactionHash::from(Blake3Hash::new([seed; _])),
+                    pre_state_root: Blake3Hash::new([index; _]),
+                    post_state_root: Blake3Hash::new([index + 1; _]),
+                    gas_used: Gas::from(u64::from(index) + 1),
+                },
+                status: if index + 1 == num_transactions {
+                    TransactionStatus::Failed
+                } else {
+                    TransactionStatus::Success
+                },
+                events: SharedAlignedBuffer::from_bytes(&vec![seed; usize::from(index) * 7]),
+            })
+            .collect(),
+    }
+}
+
+fn assert_outcome(actual: Option<BlockOutcome>, expected: &BlockOutcome) {
+    let actual = actual.unwrap();
+
+    assert_eq!(actual.transactions.len(), expected.transactions.len());
+    assert_eq!(actual.gas_used(), expected.gas_used());
+    for (actual, expected) in actual.transactions.iter().zip(expected.transactions.iter()) {
+        assert_eq!(actual.receipt, expected.receipt);
+        assert_eq!(actual.status, expected.status);
+        assert_eq!(actual.events.as_slice(), expected.events.as_slice());
+    }
+}
+
+#[test]
+fn block_outcomes() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    // Blocks `1..=10`
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 10);
+    for block in &blocks {
+        persist_block(&database, block);
+    }
+
+    assert!(database.block_outcome(&root(&blocks[1])).is_none());
+
+    block_on(database.persist_block_outcome(&root(&blocks[1]), outcome(1, 3))).unwrap();
+    block_on(database.persist_block_outcome(&root(&blocks[2]), outcome(2, 0))).unwrap();
+    block_on(database.persist_block_outcome(&root(&blocks[9]), outcome(9, 2))).unwrap();
+    // Replaces the previous outcome
+    block_on(database.persist_block_outcome(&root(&blocks[1]), outcome(10, 5))).unwrap();
+
+    assert_outcome(database.block_outcome(&root(&blocks[1])), &outcome(10, 5));
+    assert_outcome(database.block_outcome(&root(&blocks[2])), &outcome(2, 0));
+    assert_outcome(database.block_outcome(&root(&blocks[9])), &outcome(9, 2));
+    assert_eq!(outcome(10, 5).gas_used(), Gas::from(15));
+
+    // Outcomes can't be persisted for unknown blocks
+    let unknown_block = TestBeaconChainBlockBuilder::default().child(&blocks[9]);
+    assert!(matches!(
+        block_on(database.persist_block_outcome(&root(&unknown_block), outcome(0, 1))),
+        Err(PersistBlockOutcomeError::UnknownBlockRoot)
+    ));
+
+    // Outcomes of persisted blocks are restored after restart, the block within soft confirmation
+    // depth is lost together with its outcome
+    drop(database);
+    let database = open_database(&genesis, storage_backend.clone());
+    assert_outcome(database.block_outcome(&root(&blocks[1])), &outcome(10, 5));
+    assert_outcome(database.block_outcome(&root(&blocks[2])), &outcome(2, 0));
+    assert!(database.block_outcome(&root(&blocks[9])).is_none());
+
+    // Blocks `8..=30`, the first ones are pruned together with their outcomes
+    let more_blocks = TestBeaconChainBlockBuilder::default().chain(&blocks[6], 23);
+    for block in &more_blocks {
+        persist_block(&database, block);
+    }
+    assert!(database.block_outcome(&root(&blocks[1])).is_none());
+    assert!(database.block_outcome(&root(&blocks[2])).is_none());
+
+    drop(database);
+    let database = open_database(&genesis, storage_backend);
+    assert!(database.block_outcome(&root(&blocks[1])).is_none());
+    assert!(database.block_outcome(&root(&blocks[2])).is_none());
+}
@@ -0,0 +1,151 @@
+//! Block positions must be stable across reorgs and restarts, and the order of block forks must
+//! only depend on the sequence of persisted blocks
+
+use crate::memory_storage_backend::{MemoryStorageBackend, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfoWrite};
+use ab_client_database::{BlockPosition, ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 80;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+fn format_storage_backend() -> MemoryStorageBackend {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    storage_backend
+}
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn position(number: u64, fork_ordinal: u32) -> BlockPosition {
+    BlockPosition {
+        number: BlockNumber::from(number),
+        fork_ordinal,
+    }
+}
+
+/// Blocks at height 2 in the order they are persisted
+struct Forks {
+    a2: OwnedBeaconChainBlock,
+    f2a: OwnedBeaconChainBlock,
+    f2b: OwnedBeaconChainBlock,
+}
+
+/// Import two blocks, two forks at height 2, then reorg to the last fork and extend it, such that
+/// all blocks at height 2 are persisted
+fn import_blocks(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    genesis: &OwnedBeaconChainBlock,
+) -> Forks {
+    let [a1, a2] = TestBeaconChainBlockBuilder::default()
+        .chain(genesis, 2)
+        .try_into()
+        .unwrap();
+    let f2a = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(1)
+        .child(&a1);
+    let f2b = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(2)
+        .child(&a1);
+
+    for block in [&a1, &a2, &f2a, &f2b] {
+        persist_block(database, block);
+    }
+
+    assert_eq!(
+        database.block_forks(BlockNumber::from(2)),
+        vec![
+            (position(2, 0), root(&a2)),
+            (position(2, 1), root(&f2a)),
+            (position(2, 2), root(&f2b)),
+        ]
+    );
+
+    for block in TestBeaconChainBlockBuilder::default()
+        .with_fork_id(2)
+        .chain(&f2b, 4)
+    {
+        persist_block(database, &block);
+    }
+
+    Forks { a2, f2a, f2b }
+}
+
+#[test]
+fn stable_positions_and_deterministic_order() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = format_storage_backend();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    assert_eq!(
+        database.block_position(&root(&genesis)),
+        Some(position(0, 0))
+    );
+
+    let Forks { a2, f2a, f2b } = import_blocks(&database, &genesis);
+
+    // Positions are not affected by reorg, while the new canonical block is first and the rest are
+    // sorted by fork ordinal
+    let expected_forks = vec![
+        (position(2, 2), root(&f2b)),
+        (position(2, 0), root(&a2)),
+        (position(2, 1), root(&f2a)),
+    ];
+    assert_eq!(database.block_forks(BlockNumber::from(2)), expected_forks);
+    assert_eq!(database.block_position(&root(&f2a)), Some(position(2, 1)));
+    assert_eq!(database.block_root_at(position(2, 0)), Some(root(&a2)));
+    assert_eq!(database.block_root_at(position(2, 3)), None);
+    assert!(database.block_forks(BlockNumber::from(100)).is_empty());
+
+    // The same sequence of blocks results in the same order
+    {
+        let other_database = open_database(&genesis, format_storage_backend());
+        import_blocks(&other_database, &genesis);
+
+        for block_number in 0..=6 {
+            let block_number = BlockNumber::from(block_number);
+            assert_eq!(
+                other_database.block_forks(block_number),
+                database.block_forks(block_number)
+            );
+        }
+    }
+
+    // Positions and order are retained after restart
+    drop(database);
+    let database = open_database(&genesis, storage_backend);
+    assert_eq!(database.block_forks(BlockNumber::from(2)), expected_forks);
+    assert_eq!(database.block_position(&root(&f2b)), Some(position(2, 2)));
+}
@@ -0,0 +1,106 @@
+//! Filters of block roots in temporary page groups, ensures that they are persisted and used for
+//! lookups of stored blocks without affecting results
+
+use crate::memory_storage_backend::{
+    MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, database_options,
+};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfoWrite};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions};
+use ab_core_primitives::block::BlockRoot;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+/// One permanent and seven temporary page groups
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+/// Enough blocks to fill more than one temporary page group
+const NUM_BLOCKS: usize = 40;
+
+fn open_database(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+    block_roots_filters: bool,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    block_on(ClientDatabase::open(ClientDatabaseOptions {
+        block_roots_filters,
+        ..database_options(genesis, storage_backend)
+    }))
+    .unwrap()
+}
+
+fn assert_stored_blocks(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    persisted_blocks: &[OwnedBeaconChainBlock],
+) {
+    for block in persisted_blocks {
+        let stored_block = block_on(database.find_stored_block(&block.header.header().root()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored_block.header.buffer().as_slice(),
+            block.header.buffer().as_slice()
+        );
+    }
+
+    assert!(
+        block_on(database.find_stored_block(&BlockRoot::default()))
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn block_roots_filters() {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone(), true);
+
+    let mut blocks = Vec::<OwnedBeaconChainBlock>::with_capacity(NUM_BLOCKS);
+    let mut mmr = BlockMerkleMountainRange::new();
+    assert!(mmr.add_leaf(&genesis.header.header().root()));
+
+    for _ in 0..NUM_BLOCKS {
+        let block = TestBeaconChainBlockBuilder::default().child(blocks.last().unwrap_or(&genesis));
+        assert!(mmr.add_leaf(&block.header.header().root()));
+
+        block_on(database.persist_block(
+            block.clone(),
+            BlockDetails {
+                mmr_with_block: Arc::new(mmr),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+        blocks.push(block);
+    }
+
+    // More recent blocks are only kept in memory and are lost after restart
+    let persisted_blocks = &blocks[..NUM_BLOCKS - u64::from(SOFT_CONFIRMATION_DEPTH) as usize];
+
+    assert_stored_blocks(&database, persisted_blocks);
+    drop(database);
+
+    // Persisted filters are restored after restart
+    let database = open_database(&genesis, storage_backend.clone(), true);
+    assert_stored_blocks(&database, persisted_blocks);
+    drop(database);
+
+    // Filters are ignored when disabled, which must not affect lookups
+    let database = open_database(&genesis, storage_backend, false);
+    assert_stored_blocks(&database, persisted_blocks);
+}
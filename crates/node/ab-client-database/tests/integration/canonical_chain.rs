@@ -0,0 +1,105 @@
+//! Lookups of canonical blocks by block number must follow reorgs, cover confirmed blocks and
+//! survive restarts
+
+use crate::memory_storage_backend::{MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::iter;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+/// Check that canonical lookups match `canonical_chain`, which starts with genesis
+fn assert_canonical_chain(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    canonical_chain: &[&OwnedBeaconChainBlock],
+) {
+    for (block_number, block) in canonical_chain.iter().enumerate() {
+        let block_number = BlockNumber::from(block_number as u64);
+
+        assert_eq!(database.canonical_root(block_number), Some(root(block)));
+        assert_eq!(
+            database
+                .canonical_header(block_number)
+                .map(|header| *header.header().root()),
+            Some(root(block))
+        );
+    }
+
+    let next_block_number = BlockNumber::from(canonical_chain.len() as u64);
+    assert_eq!(database.canonical_root(next_block_number), None);
+    assert!(database.canonical_header(next_block_number).is_none());
+}
+
+#[test]
+fn canonical_chain() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    // Blocks `1..=20`, the first ones are confirmed
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 20);
+    for block in &blocks {
+        persist_block(&database, block);
+    }
+    assert_canonical_chain(
+        &database,
+        &iter::once(&genesis).chain(&blocks).collect::<Vec<_>>(),
+    );
+
+    // Longer fork of blocks `18..=25` replaces the last blocks of the canonical chain
+    let fork = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(1)
+        .chain(&blocks[16], 8);
+    for block in &fork {
+        persist_block(&database, block);
+    }
+    let canonical_chain = iter::once(&genesis)
+        .chain(&blocks[..17])
+        .chain(&fork)
+        .collect::<Vec<_>>();
+    assert_canonical_chain(&database, &canonical_chain);
+
+    // Blocks within soft confirmation depth are only kept in memory and are lost after restart
+    drop(database);
+    let database = open_database(&genesis, storage_backend);
+    assert_canonical_chain(
+        &database,
+        &canonical_chain[..canonical_chain.len() - u64::from(SOFT_CONFIRMATION_DEPTH) as usize],
+    );
+}
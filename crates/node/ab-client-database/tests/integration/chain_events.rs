@@ -0,0 +1,151 @@
+//! Chain events must reflect best block updates, reorgs, forks and their pruning
+
+use crate::memory_storage_backend::{MemoryStorageBackend, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfoWrite};
+use ab_client_database::chain_events::{ChainEvent, ChainEventsTopic};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_client_notifications::{BufferingPolicy, Subscription};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use futures::{FutureExt, StreamExt};
+use rclite::Arc;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn number(block: &OwnedBeaconChainBlock) -> BlockNumber {
+    block.header.header().prefix.number
+}
+
+/// Take all events that are currently buffered
+fn take_events(subscription: &mut Subscription<ChainEventsTopic>) -> Vec<ChainEvent> {
+    let mut events = Vec::new();
+    while let Some(event) = subscription.next().now_or_never() {
+        events.push(event.expect("Database is still alive; qed"));
+    }
+    events
+}
+
+#[test]
+fn chain_events() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = open_database(&genesis, storage_backend);
+
+    let mut subscription = database.subscribe_chain_events(BufferingPolicy::DropOldest {
+        capacity: NonZeroUsize::new(64).expect("Not zero; qed"),
+    });
+
+    // Blocks `1..=5` extend the best block one by one
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 5);
+    let mut old_best_root = root(&genesis);
+    for block in &blocks {
+        persist_block(&database, block);
+
+        assert_eq!(
+            take_events(&mut subscription),
+            [ChainEvent::BestBlockUpdated {
+                old_best_root,
+                new_best_number: number(block),
+                new_best_root: root(block),
+                reorg: false,
+            }]
+        );
+        old_best_root = root(block);
+    }
+
+    // Fork of blocks `4..=15` that becomes the best chain once it is longer
+    let fork = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(1)
+        .chain(&blocks[2], 12);
+
+    // Blocks `4..=5` of the fork do not change the best block
+    for (block, parent_root) in fork[..2].iter().zip([root(&blocks[2]), root(&fork[0])]) {
+        persist_block(&database, block);
+
+        assert_eq!(
+            take_events(&mut subscription),
+            [ChainEvent::ForkCreated {
+                number: number(block),
+                root: root(block),
+                parent_root,
+            }]
+        );
+    }
+
+    // Block `6` of the fork causes a reorg
+    persist_block(&database, &fork[2]);
+    assert_eq!(
+        take_events(&mut subscription),
+        [ChainEvent::BestBlockUpdated {
+            old_best_root: root(&blocks[4]),
+            new_best_number: number(&fork[2]),
+            new_best_root: root(&fork[2]),
+            reorg: true,
+        }]
+    );
+
+    // Blocks `7..=14` extend the new best chain
+    for (parent, block) in fork[2..11].iter().zip(&fork[3..11]) {
+        persist_block(&database, block);
+
+        assert_eq!(
+            take_events(&mut subscription),
+            [ChainEvent::BestBlockUpdated {
+                old_best_root: root(parent),
+                new_best_number: number(block),
+                new_best_root: root(block),
+                reorg: false,
+            }]
+        );
+    }
+
+    // Block `15` confirms the block `4` of the fork and prunes the original blocks `4..=5`
+    persist_block(&database, &fork[11]);
+    assert_eq!(
+        take_events(&mut subscription),
+        [
+            ChainEvent::BestBlockUpdated {
+                old_best_root: root(&fork[10]),
+                new_best_number: number(&fork[11]),
+                new_best_root: root(&fork[11]),
+                reorg: false,
+            },
+            ChainEvent::ForksPruned {
+                roots: vec![root(&blocks[3]), root(&blocks[4])],
+            }
+        ]
+    );
+}
@@ -0,0 +1,124 @@
+//! Compaction of temporary page groups, ensures that page groups holding mostly outdated storage
+//! items are reclaimed and that the database remains readable afterward
+
+use crate::memory_storage_backend::{
+    MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, database_options,
+};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockAuxDataNamespace, BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite,
+};
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions, CompactionOptions,
+};
+use ab_core_primitives::block::owned::{GenericOwnedBlock, OwnedBeaconChainBlock};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+/// One permanent and four temporary page groups
+const NUM_PAGES: u32 = 80;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+const AUX_DATA_NAMESPACE: BlockAuxDataNamespace = BlockAuxDataNamespace::new(*b"compact0");
+/// Number of blocks on top of genesis
+const NUM_BLOCKS: usize = 6;
+const NUM_ROUNDS: usize = 10;
+/// Every write occupies a page and makes the previously written auxiliary data outdated
+const AUX_DATA_WRITES_PER_ROUND: usize = 10;
+
+fn open_database(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    block_on(ClientDatabase::open(ClientDatabaseOptions {
+        compaction: CompactionOptions {
+            // Compact every inactive page group regardless of the number of live storage items
+            max_live_percentage: 100,
+            max_pages_per_second: NonZeroU32::MAX,
+            ..
+        },
+        ..database_options(genesis, storage_backend)
+    }))
+    .unwrap()
+}
+
+#[test]
+fn compaction_reclaims_page_groups() {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    let mut blocks = Vec::<OwnedBeaconChainBlock>::with_capacity(NUM_BLOCKS);
+    let mut mmr = BlockMerkleMountainRange::new();
+    assert!(mmr.add_leaf(&genesis.header.header().root()));
+
+    for _ in 0..NUM_BLOCKS {
+        let block = TestBeaconChainBlockBuilder::default().child(blocks.last().unwrap_or(&genesis));
+        assert!(mmr.add_leaf(&block.header.header().root()));
+
+        block_on(database.persist_block(
+            block.clone(),
+            BlockDetails {
+                mmr_with_block: Arc::new(mmr),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+        blocks.push(block);
+    }
+
+    // More recent blocks are only kept in memory and are lost after restart
+    let persisted_blocks = &blocks[..NUM_BLOCKS - u64::from(SOFT_CONFIRMATION_DEPTH) as usize];
+    let persisted_best_root = *persisted_blocks.last().unwrap().header.header().root();
+
+    let mut last_aux_data = SharedAlignedBuffer::default();
+    for round in 0..NUM_ROUNDS {
+        for index in 0..AUX_DATA_WRITES_PER_ROUND {
+            last_aux_data = SharedAlignedBuffer::from_bytes(format!("{round}-{index}").as_bytes());
+
+            // Without compaction, the database runs out of free page groups after a few rounds
+            block_on(database.persist_block_aux_data(
+                &persisted_best_root,
+                AUX_DATA_NAMESPACE,
+                last_aux_data.clone(),
+            ))
+            .unwrap();
+        }
+
+        block_on(database.compact()).unwrap();
+    }
+    drop(database);
+
+    let database = open_database(&genesis, storage_backend);
+
+    assert_eq!(*database.best_header().header().root(), persisted_best_root);
+
+    for block in persisted_blocks {
+        let stored_block = block_on(database.block(&block.header.header().root())).unwrap();
+        assert_eq!(
+            stored_block.body().buffer().as_slice(),
+            block.body().buffer().as_slice()
+        );
+    }
+
+    assert_eq!(
+        database
+            .block_aux_data(&persisted_best_root, AUX_DATA_NAMESPACE)
+            .unwrap()
+            .as_slice(),
+        last_aux_data.as_slice()
+    );
+}
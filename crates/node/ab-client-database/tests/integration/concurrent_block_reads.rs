@@ -0,0 +1,83 @@
+//! Concurrent reads of the same persisted blocks are coalesced and all readers get the same block
+
+use crate::memory_storage_backend::{MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use futures::future::join_all;
+use rclite::Arc;
+use std::iter;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 80;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+/// Number of blocks on top of genesis
+const NUM_BLOCKS: usize = 6;
+/// Number of blocks that are soft-confirmed and written to the storage
+const NUM_PERSISTED_BLOCKS: usize = NUM_BLOCKS - u64::from(SOFT_CONFIRMATION_DEPTH) as usize;
+const NUM_CONCURRENT_READS: usize = 8;
+
+#[test]
+fn concurrent_block_reads() {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend);
+
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, NUM_BLOCKS);
+    for block in &blocks {
+        block_on(database.persist_block(
+            block.clone(),
+            BlockDetails {
+                mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+    }
+
+    // Every persisted block is requested several times concurrently
+    let persisted_blocks = &blocks[..NUM_PERSISTED_BLOCKS];
+    let read_blocks = block_on(join_all(persisted_blocks.iter().flat_map(|block| {
+        let block_root = *block.header.header().root();
+        let database = &database;
+
+        iter::repeat_with(move || async move { database.block(&block_root).await })
+            .take(NUM_CONCURRENT_READS)
+    })));
+
+    for (read_blocks, block) in read_blocks
+        .chunks_exact(NUM_CONCURRENT_READS)
+        .zip(persisted_blocks)
+    {
+        for read_block in read_blocks {
+            let read_block = read_block.as_ref().unwrap();
+            assert_eq!(
+                *read_block.header.header().root(),
+                *block.header.header().root()
+            );
+            assert_eq!(
+                read_block.body.buffer().as_slice(),
+                block.body.buffer().as_slice()
+            );
+        }
+    }
+
+    // Reads are no longer in progress, so reading again works as usual
+    for block in persisted_blocks {
+        block_on(database.block(&block.header.header().root())).unwrap();
+    }
+}
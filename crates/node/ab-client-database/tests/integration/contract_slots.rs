@@ -0,0 +1,291 @@
+//! Contract slots must be readable at the block that modified them and its descendants, but not
+//! at ancestors or other forks, including after restart and after blocks are pruned
+
+use crate::memory_storage_backend::{MemoryStorageBackend, database_options};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite, ContractSlotState,
+    ReadContractSlotError,
+};
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions, ReclamationOptions,
+};
+use ab_core_primitives::address::Address;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 256;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+const RETAINED_BLOCKS: BlockNumber = BlockNumber::from(12);
+const OWNER: Address = Address::from(1_u128);
+const CONTRACT: Address = Address::from(2_u128);
+
+fn format_storage_backend() -> MemoryStorageBackend {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    storage_backend
+}
+
+fn open_database(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+    retained_blocks: Option<BlockNumber>,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    block_on(ClientDatabase::open(ClientDatabaseOptions {
+        reclamation: ReclamationOptions {
+            retained_blocks,
+            ..
+        },
+        ..database_options(genesis, storage_backend)
+    }))
+    .unwrap()
+}
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn persist_slot(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+    contents: &[u8],
+) {
+    block_on(database.persist_contract_slots(
+        &root(block),
+        StdArc::new([ContractSlotState {
+            owner: OWNER,
+            contract: CONTRACT,
+            contents: SharedAlignedBuffer::from_bytes(contents),
+        }]),
+    ))
+    .unwrap();
+}
+
+fn read_slot(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) -> Result<Option<Vec<u8>>, ReadContractSlotError> {
+    database
+        .contract_slot(&root(block), &OWNER, &CONTRACT)
+        .map(|maybe_contents| maybe_contents.map(|contents| contents.as_slice().to_vec()))
+}
+
+#[test]
+fn contract_slots() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone(), None);
+
+    // Blocks `1..=20`
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 20);
+    for block in &blocks {
+        persist_block(&database, block);
+    }
+    persist_slot(&database, &blocks[1], b"a");
+    persist_slot(&database, &blocks[4], b"b");
+
+    // Unknown slot
+    assert!(
+        database
+            .contract_slot(&root(&blocks[19]), &CONTRACT, &OWNER)
+            .unwrap()
+            .is_none()
+    );
+
+    // Versions are only visible at the block that wrote them and its descendants
+    assert_eq!(read_slot(&database, &genesis).unwrap(), None);
+    assert_eq!(read_slot(&database, &blocks[0]).unwrap(), None);
+    for block in &blocks[1..4] {
+        assert_eq!(
+            read_slot(&database, block).unwrap().as_deref(),
+            Some(&b"a"[..])
+        );
+    }
+    for block in &blocks[4..] {
+        assert_eq!(
+            read_slot(&database, block).unwrap().as_deref(),
+            Some(&b"b"[..])
+        );
+    }
+
+    // Fork of blocks `15..=16` doesn't affect the canonical chain
+    let fork = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(1)
+        .chain(&blocks[13], 2);
+    for block in &fork {
+        persist_block(&database, block);
+    }
+    persist_slot(&database, &fork[0], b"c");
+    assert_eq!(
+        read_slot(&database, &fork[0]).unwrap().as_deref(),
+        Some(&b"c"[..])
+    );
+    assert_eq!(
+        read_slot(&database, &fork[1]).unwrap().as_deref(),
+        Some(&b"c"[..])
+    );
+    assert_eq!(
+        read_slot(&database, &blocks[14]).unwrap().as_deref(),
+        Some(&b"b"[..])
+    );
+    assert_eq!(
+        read_slot(&database, &blocks[19]).unwrap().as_deref(),
+        Some(&b"b"[..])
+    );
+
+    // Blocks `21..=30` confirm the canonical chain and prune the fork
+    let more_blocks = TestBeaconChainBlockBuilder::default().chain(&blocks[19], 10);
+    for block in &more_blocks {
+        persist_block(&database, block);
+    }
+    persist_slot(&database, &more_blocks[0], b"d");
+    assert!(matches!(
+        read_slot(&database, &fork[0]),
+        Err(ReadContractSlotError::UnknownBlockRoot)
+    ));
+    assert_eq!(
+        read_slot(&database, &blocks[19]).unwrap().as_deref(),
+        Some(&b"b"[..])
+    );
+    assert_eq!(
+        read_slot(&database, &more_blocks[9]).unwrap().as_deref(),
+        Some(&b"d"[..])
+    );
+
+    // Versions of the fork are not restored after restart, blocks within soft confirmation depth
+    // and their versions are lost. The fork is below the confirmation depth even without those
+    // blocks, so it is pruned again.
+    drop(database);
+    let database = open_database(&genesis, storage_backend, None);
+    assert!(matches!(
+        read_slot(&database, &fork[0]),
+        Err(ReadContractSlotError::UnknownBlockRoot)
+    ));
+    assert_eq!(read_slot(&database, &blocks[0]).unwrap(), None);
+    assert_eq!(
+        read_slot(&database, &blocks[2]).unwrap().as_deref(),
+        Some(&b"a"[..])
+    );
+    assert_eq!(
+        read_slot(&database, &blocks[19]).unwrap().as_deref(),
+        Some(&b"b"[..])
+    );
+    assert_eq!(
+        read_slot(&database, &more_blocks[6]).unwrap().as_deref(),
+        Some(&b"d"[..])
+    );
+
+    // Versions can't be persisted for unknown blocks
+    assert!(block_on(database.persist_contract_slots(&root(&fork[0]), StdArc::new([]))).is_err());
+}
+
+#[test]
+fn contract_slots_of_pruned_blocks() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone(), Some(RETAINED_BLOCKS));
+
+    // Blocks `1..=30`, the first ones are pruned
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 30);
+    for (index, block) in blocks.iter().enumerate() {
+        persist_block(&database, block);
+        if index == 1 {
+            persist_slot(&database, block, b"a");
+        }
+    }
+    block_on(database.block(&root(&blocks[1]))).unwrap_err();
+
+    // Versions of pruned blocks are inherited by all remaining blocks
+    assert_eq!(
+        read_slot(&database, &blocks[29]).unwrap().as_deref(),
+        Some(&b"a"[..])
+    );
+
+    drop(database);
+    let database = open_database(&genesis, storage_backend, Some(RETAINED_BLOCKS));
+    let best_root = *database.best_header().header().root();
+    assert_eq!(
+        database
+            .contract_slot(&best_root, &OWNER, &CONTRACT)
+            .unwrap()
+            .map(|contents| contents.as_slice().to_vec())
+            .as_deref(),
+        Some(&b"a"[..])
+    );
+}
+
+#[test]
+fn state_reader() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend, Some(RETAINED_BLOCKS));
+
+    // Blocks `1..=5`
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 5);
+    for block in &blocks {
+        persist_block(&database, block);
+    }
+    persist_slot(&database, &blocks[1], b"a");
+    persist_slot(&database, &blocks[3], b"b");
+
+    let state_reader = database.state_at(&root(&blocks[2])).unwrap();
+    assert_eq!(state_reader.block_number(), BlockNumber::from(3));
+    assert_eq!(state_reader.block_root(), root(&blocks[2]));
+
+    // Blocks `6..=30`, the state of the block is still readable even though it would have been
+    // pruned otherwise
+    let more_blocks = TestBeaconChainBlockBuilder::default().chain(&blocks[4], 25);
+    for block in &more_blocks {
+        persist_block(&database, block);
+    }
+    persist_slot(&database, &more_blocks[0], b"c");
+    assert_eq!(
+        state_reader
+            .contract_slot(&OWNER, &CONTRACT)
+            .unwrap()
+            .map(|contents| contents.as_slice().to_vec())
+            .as_deref(),
+        Some(&b"a"[..])
+    );
+    assert_eq!(
+        read_slot(&database, &more_blocks[24]).unwrap().as_deref(),
+        Some(&b"c"[..])
+    );
+
+    // Once the reader is dropped, the block is pruned
+    drop(state_reader);
+    persist_block(
+        &database,
+        &TestBeaconChainBlockBuilder::default().child(&more_blocks[24]),
+    );
+    database.state_at(&root(&blocks[2])).unwrap_err();
+}
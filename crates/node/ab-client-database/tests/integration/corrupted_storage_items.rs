@@ -0,0 +1,93 @@
+//! Storage items that don't match the location they are read from must be reported as corrupted
+//! instead of being decoded
+
+use crate::memory_storage_backend::{MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, open_database};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockAuxDataNamespace, BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite,
+    ReadBlockError,
+};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 80;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+/// Number of blocks on top of genesis
+const NUM_BLOCKS: usize = 6;
+/// Number of blocks that are soft-confirmed and written to the storage
+const NUM_PERSISTED_BLOCKS: usize = NUM_BLOCKS - u64::from(SOFT_CONFIRMATION_DEPTH) as usize;
+
+fn import_blocks(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    genesis: &OwnedBeaconChainBlock,
+) -> Vec<OwnedBeaconChainBlock> {
+    let blocks = TestBeaconChainBlockBuilder::default().chain(genesis, NUM_BLOCKS);
+
+    for block in &blocks {
+        block_on(database.persist_block(
+            block.clone(),
+            BlockDetails {
+                mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+    }
+
+    blocks
+}
+
+#[test]
+fn stale_write_locations() {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    // The same database, but storage items will end up at different locations
+    let other_storage_backend = MemoryStorageBackend::from_bytes(&storage_backend.to_bytes());
+
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+
+    let database = open_database(&genesis, storage_backend.clone());
+    // Shift locations of all following storage items
+    block_on(database.persist_block_aux_data(
+        &genesis.header.header().root(),
+        BlockAuxDataNamespace::new(*b"testtest"),
+        SharedAlignedBuffer::from_bytes(&[1, 2, 3]),
+    ))
+    .unwrap();
+    let blocks = import_blocks(&database, &genesis);
+
+    {
+        let other_database = open_database(&genesis, other_storage_backend.clone());
+        import_blocks(&other_database, &genesis);
+    }
+
+    let persisted_blocks = &blocks[..NUM_PERSISTED_BLOCKS];
+    for block in persisted_blocks {
+        block_on(database.block(&block.header.header().root())).unwrap();
+    }
+
+    // Write locations known to the database are now stale
+    storage_backend.replace_bytes(&other_storage_backend.to_bytes());
+
+    for block in persisted_blocks {
+        let error = block_on(database.block(&block.header.header().root())).unwrap_err();
+        assert!(
+            matches!(error, ReadBlockError::CorruptedStorageItem { .. }),
+            "{error:?}"
+        );
+    }
+}
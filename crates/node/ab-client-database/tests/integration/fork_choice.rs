@@ -0,0 +1,165 @@
+//! Custom fork choice rule must be able to select a block at the same height as the best block as
+//! the new best block
+
+use crate::memory_storage_backend::{MemoryStorageBackend, database_options};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::chain_events::{ChainEvent, ChainEventsTopic};
+use ab_client_database::fork_choice::ForkChoice;
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions};
+use ab_client_notifications::{BufferingPolicy, Subscription};
+use ab_core_primitives::block::header::SharedBlockHeader;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::pot::SlotNumber;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use futures::{FutureExt, StreamExt};
+use rclite::Arc;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+/// Longest chain, but the block with the lowest slot wins among blocks with the same block number
+#[derive(Debug)]
+struct LowestSlotForkChoice;
+
+impl ForkChoice for LowestSlotForkChoice {
+    fn is_new_best(&self, best: &SharedBlockHeader<'_>, candidate: &SharedBlockHeader<'_>) -> bool {
+        candidate.prefix.number > best.prefix.number
+            || (candidate.prefix.number == best.prefix.number
+                && candidate.consensus_info.slot < best.consensus_info.slot)
+    }
+}
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn number(block: &OwnedBeaconChainBlock) -> BlockNumber {
+    block.header.header().prefix.number
+}
+
+/// Take all events that are currently buffered
+fn take_events(subscription: &mut Subscription<ChainEventsTopic>) -> Vec<ChainEvent> {
+    let mut events = Vec::new();
+    while let Some(event) = subscription.next().now_or_never() {
+        events.push(event.expect("Database is still alive; qed"));
+    }
+    events
+}
+
+#[test]
+fn custom_fork_choice() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = block_on(ClientDatabase::open(ClientDatabaseOptions {
+        fork_choice: Some(StdArc::new(LowestSlotForkChoice)),
+        ..database_options(&genesis, storage_backend)
+    }))
+    .unwrap();
+
+    // Blocks `1..=5`, two slots apart
+    let blocks = TestBeaconChainBlockBuilder::default()
+        .with_slot_step(SlotNumber::from(2))
+        .chain(&genesis, 5);
+    for block in &blocks {
+        persist_block(&database, block);
+    }
+    let generation = database.generation();
+
+    let mut subscription = database.subscribe_chain_events(BufferingPolicy::DropOldest {
+        capacity: NonZeroUsize::new(64).expect("Not zero; qed"),
+    });
+
+    // Block `5` with a higher slot doesn't change the best block
+    let late_fork = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(1)
+        .with_slot_step(SlotNumber::from(3))
+        .child(&blocks[3]);
+    persist_block(&database, &late_fork);
+    assert_eq!(
+        take_events(&mut subscription),
+        [ChainEvent::ForkCreated {
+            number: number(&late_fork),
+            root: root(&late_fork),
+            parent_root: root(&blocks[3]),
+        }]
+    );
+    assert_eq!(database.best_root(), root(&blocks[4]));
+    assert_eq!(database.generation(), generation);
+
+    // Block `5` with a lower slot becomes the new best block
+    let early_fork = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(2)
+        .with_slot_step(SlotNumber::ONE)
+        .child(&blocks[3]);
+    persist_block(&database, &early_fork);
+    assert_eq!(
+        take_events(&mut subscription),
+        [ChainEvent::BestBlockUpdated {
+            old_best_root: root(&blocks[4]),
+            new_best_number: number(&early_fork),
+            new_best_root: root(&early_fork),
+            reorg: true,
+        }]
+    );
+    assert_eq!(database.best_root(), root(&early_fork));
+    assert_eq!(
+        database.canonical_root(number(&early_fork)),
+        Some(root(&early_fork))
+    );
+    assert_eq!(
+        database.canonical_root(number(&blocks[3])),
+        Some(root(&blocks[3]))
+    );
+    assert!(database.generation() > generation);
+
+    // The previous best block is still known
+    block_on(database.block(&root(&blocks[4]))).unwrap();
+
+    // Block `6` extends the new best block
+    let child = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(2)
+        .child(&early_fork);
+    persist_block(&database, &child);
+    assert_eq!(
+        take_events(&mut subscription),
+        [ChainEvent::BestBlockUpdated {
+            old_best_root: root(&early_fork),
+            new_best_number: number(&child),
+            new_best_root: root(&child),
+            reorg: false,
+        }]
+    );
+    assert_eq!(
+        database.canonical_root(number(&early_fork)),
+        Some(root(&early_fork))
+    );
+    assert!(block_on(database.verify()).unwrap().is_ok());
+}
@@ -0,0 +1,281 @@
+//! Regression corpus for the on-disk database format.
+//!
+//! Fixtures in `tests/fixtures` are database images written by older versions of the code, each
+//! named `v<database version>-<fixture name>.bin`, there must be a fixture for every spec and every
+//! database version up to [`DATABASE_VERSION`]. They are opened with the current code to ensure
+//! the database remains readable after upgrades, and images of versions that are no longer
+//! supported are rejected cleanly. Fixtures are never regenerated once checked in, when the format
+//! changes, a new fixture is added for the new database version instead:
+//! ```text
+//! cargo test -p ab-client-database --test integration -- --ignored generate_fixtures
+//! ```
+
+use crate::memory_storage_backend::{
+    BLOCK_CONFIRMATION_DEPTH, MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH,
+};
+use ab_aligned_buffer::SharedAlignedBuffer;
+use ab_client_api::{
+    BlockAuxDataNamespace, BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite,
+};
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseError, ClientDatabaseFormatOptions, ClientDatabaseOptions,
+    DATABASE_VERSION, GenesisBlockBuilderResult,
+};
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::block::owned::{GenericOwnedBlock, OwnedBeaconChainBlock};
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::segments::{
+    ArchivedBlockProgress, LastArchivedBlock, LocalSegmentIndex, SegmentHeader, SegmentRoot,
+};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Arc as StdArc;
+
+/// The oldest database version that can be opened by the current code, images of older versions
+/// must be rejected
+const MIN_SUPPORTED_DATABASE_VERSION: u8 = 1;
+const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+const AUX_DATA_NAMESPACE: BlockAuxDataNamespace = BlockAuxDataNamespace::new(*b"fixture0");
+
+/// Description of the database contents, used both for generating a fixture and for checking the
+/// contents of the fixture after opening
+#[derive(Debug, Copy, Clone)]
+struct FixtureSpec {
+    name: &'static str,
+    num_pages: u32,
+    page_group_size: NonZeroU32,
+    /// Number of blocks on top of genesis
+    num_blocks: usize,
+}
+
+const FIXTURE_SPECS: &[FixtureSpec] = &[
+    // Single page group for blocks
+    FixtureSpec {
+        name: "small",
+        num_pages: 48,
+        page_group_size: NonZeroU32::new(16).expect("Not zero; qed"),
+        num_blocks: 8,
+    },
+    // Blocks span multiple page groups
+    FixtureSpec {
+        name: "multiple-page-groups",
+        num_pages: 128,
+        page_group_size: NonZeroU32::new(16).expect("Not zero; qed"),
+        num_blocks: 40,
+    },
+];
+
+impl FixtureSpec {
+    fn file_name(&self, database_version: u8) -> String {
+        format!("v{database_version}-{}.bin", self.name)
+    }
+
+    fn genesis(&self) -> OwnedBeaconChainBlock {
+        TestBeaconChainBlockBuilder::default().genesis()
+    }
+
+    fn blocks(&self) -> Vec<OwnedBeaconChainBlock> {
+        let mut blocks = Vec::<OwnedBeaconChainBlock>::with_capacity(self.num_blocks);
+        let genesis = self.genesis();
+
+        for index in 0..self.num_blocks {
+            // Varying body size
+            let block = TestBeaconChainBlockBuilder::default()
+                .with_num_pot_checkpoints(index % 8)
+                .child(blocks.last().unwrap_or(&genesis));
+            blocks.push(block);
+        }
+
+        blocks
+    }
+
+    fn segment_headers(&self) -> Vec<SegmentHeader> {
+        vec![SegmentHeader {
+            index: LocalSegmentIndex::ZERO.into(),
+            root: SegmentRoot::default(),
+            prev_segment_header_hash: Blake3Hash::default(),
+            last_archived_block: LastArchivedBlock {
+                number: BlockNumber::ZERO.into(),
+                archived_progress: ArchivedBlockProgress::new_complete(),
+            },
+        }]
+    }
+
+    /// The best block after restart, more recent blocks are only kept in memory and are lost
+    fn persisted_best_block(&self) -> OwnedBeaconChainBlock {
+        let num_persisted_blocks = self
+            .num_blocks
+            .saturating_sub(u64::from(SOFT_CONFIRMATION_DEPTH) as usize);
+
+        match num_persisted_blocks.checked_sub(1) {
+            Some(index) => self.blocks().swap_remove(index),
+            None => self.genesis(),
+        }
+    }
+
+    fn aux_data(&self) -> SharedAlignedBuffer {
+        SharedAlignedBuffer::from_bytes(self.name.as_bytes())
+    }
+}
+
+fn try_open_database(
+    spec: FixtureSpec,
+    storage_backend: MemoryStorageBackend,
+) -> Result<ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>, ClientDatabaseError> {
+    block_on(ClientDatabase::open(ClientDatabaseOptions {
+        write_buffer_size: 0,
+        block_confirmation_depth: BLOCK_CONFIRMATION_DEPTH,
+        soft_confirmation_depth: SOFT_CONFIRMATION_DEPTH,
+        genesis_block_builder: || GenesisBlockBuilderResult {
+            block: spec.genesis(),
+            system_contract_states: StdArc::new([]),
+        },
+        storage_backend,
+        ..
+    }))
+}
+
+fn open_database(
+    spec: FixtureSpec,
+    storage_backend: MemoryStorageBackend,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    try_open_database(spec, storage_backend).unwrap()
+}
+
+/// Create a database image according to the spec using the current code
+fn create_image(spec: FixtureSpec) -> Vec<u8> {
+    let storage_backend = MemoryStorageBackend::new(spec.num_pages);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: spec.page_group_size,
+            force: false,
+            known_segment_headers: spec.segment_headers(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    let database = open_database(spec, storage_backend.clone());
+
+    let mut mmr = BlockMerkleMountainRange::new();
+    assert!(mmr.add_leaf(&spec.genesis().header.header().root()));
+
+    for block in spec.blocks() {
+        assert!(mmr.add_leaf(&block.header.header().root()));
+
+        block_on(database.persist_block(
+            block,
+            BlockDetails {
+                mmr_with_block: Arc::new(mmr),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+    }
+
+    block_on(database.persist_block_aux_data(
+        &spec.persisted_best_block().header.header().root(),
+        AUX_DATA_NAMESPACE,
+        spec.aux_data(),
+    ))
+    .unwrap();
+
+    storage_backend.to_bytes()
+}
+
+/// Check that the database opened from an image has the contents described by the spec
+fn check_image(spec: FixtureSpec, image: &[u8]) {
+    let database = open_database(spec, MemoryStorageBackend::from_bytes(image));
+
+    let expected_best_block = spec.persisted_best_block();
+    let expected_best_header = expected_best_block.header.header();
+    let best_header = database.best_header();
+    let best_header = best_header.header();
+
+    assert_eq!(
+        best_header.prefix.number,
+        expected_best_header.prefix.number
+    );
+    assert_eq!(*best_header.root(), *expected_best_header.root());
+
+    let best_block = block_on(database.block(&best_header.root())).unwrap();
+    assert_eq!(
+        best_block.body().buffer().as_slice(),
+        expected_best_block.body().buffer().as_slice()
+    );
+
+    assert_eq!(
+        database
+            .block_aux_data(&best_header.root(), AUX_DATA_NAMESPACE)
+            .unwrap()
+            .as_slice(),
+        spec.aux_data().as_slice()
+    );
+
+    assert_eq!(
+        database.get_segment_header(LocalSegmentIndex::ZERO),
+        spec.segment_headers().first().copied()
+    );
+}
+
+fn fixture_path(file_name: &str) -> PathBuf {
+    Path::new(FIXTURES_DIR).join(file_name)
+}
+
+#[test]
+fn current_format_roundtrip() {
+    for &spec in FIXTURE_SPECS {
+        check_image(spec, &create_image(spec));
+    }
+}
+
+#[test]
+fn open_fixtures() {
+    for database_version in 0..=DATABASE_VERSION {
+        for &spec in FIXTURE_SPECS {
+            let path = fixture_path(&spec.file_name(database_version));
+            let image = fs::read(&path).unwrap_or_else(|error| {
+                panic!("Failed to read fixture {}: {error}", path.display());
+            });
+
+            if database_version >= MIN_SUPPORTED_DATABASE_VERSION {
+                check_image(spec, &image);
+                continue;
+            }
+
+            let result = try_open_database(spec, MemoryStorageBackend::from_bytes(&image));
+            assert!(
+                matches!(
+                    result,
+                    Err(ClientDatabaseError::UnsupportedDatabaseVersion {
+                        database_version: version
+                    }) if version == database_version
+                ),
+                "Fixture {} of unsupported database version must be rejected",
+                path.display()
+            );
+        }
+    }
+}
+
+#[test]
+#[ignore = "Generates fixtures for the current database version"]
+fn generate_fixtures() {
+    fs::create_dir_all(FIXTURES_DIR).unwrap();
+
+    for &spec in FIXTURE_SPECS {
+        let path = fixture_path(&spec.file_name(DATABASE_VERSION));
+
+        if path.exists() {
+            // Existing fixtures are never overwritten
+            continue;
+        }
+
+        fs::write(&path, create_image(spec)).unwrap();
+    }
+}
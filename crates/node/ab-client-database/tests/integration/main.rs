@@ -0,0 +1,58 @@
+#![feature(const_convert, const_trait_impl, default_field_values)]
+#![expect(incomplete_features, reason = "generic_const_exprs")]
+// TODO: This feature is not actually used in this crate, but is added as a workaround for
+//  https://github.com/rust-lang/rust/issues/141492
+#![feature(generic_const_exprs)]
+
+#[cfg(not(miri))]
+mod archiving_retention;
+#[cfg(not(miri))]
+mod backup;
+#[cfg(not(miri))]
+mod block_body_cache;
+#[cfg(not(miri))]
+mod block_compression;
+#[cfg(not(miri))]
+mod block_mmr;
+#[cfg(not(miri))]
+mod block_outcomes;
+#[cfg(not(miri))]
+mod block_positions;
+#[cfg(not(miri))]
+mod block_roots_filters;
+#[cfg(not(miri))]
+mod canonical_chain;
+#[cfg(not(miri))]
+mod chain_events;
+#[cfg(not(miri))]
+mod compaction;
+#[cfg(not(miri))]
+mod concurrent_block_reads;
+#[cfg(not(miri))]
+mod contract_slots;
+#[cfg(not(miri))]
+mod corrupted_storage_items;
+#[cfg(not(miri))]
+mod fork_choice;
+#[cfg(not(miri))]
+mod format_compatibility;
+#[cfg(not(miri))]
+mod memory_storage_backend;
+#[cfg(not(miri))]
+mod metrics;
+#[cfg(not(miri))]
+mod persist_blocks;
+#[cfg(not(miri))]
+mod pruning_holds;
+#[cfg(not(miri))]
+mod read_your_writes;
+#[cfg(not(miri))]
+mod reclamation;
+#[cfg(not(miri))]
+mod segment_headers;
+#[cfg(not(miri))]
+mod snapshot;
+#[cfg(not(miri))]
+mod stats;
+#[cfg(not(miri))]
+mod verification;
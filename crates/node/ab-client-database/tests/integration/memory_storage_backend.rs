@@ -0,0 +1,206 @@
+use ab_client_database::storage_backend::{AlignedPage, ClientDatabaseStorageBackend};
+use ab_client_database::{ClientDatabase, ClientDatabaseOptions, GenesisBlockBuilderResult};
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use std::io;
+use std::sync::{Arc as StdArc, Mutex};
+
+pub(crate) const BLOCK_CONFIRMATION_DEPTH: BlockNumber = BlockNumber::from(10);
+pub(crate) const SOFT_CONFIRMATION_DEPTH: BlockNumber = BlockNumber::from(3);
+
+/// Write that was accepted, but not applied yet
+type PausedWrite = (
+    StdArc<Vec<AlignedPage>>,
+    u32,
+    oneshot::Sender<io::Result<StdArc<Vec<AlignedPage>>>>,
+);
+
+/// Storage backend that keeps database image in memory, clones share the same image
+#[derive(Debug, Clone)]
+pub(crate) struct MemoryStorageBackend {
+    pages: StdArc<Mutex<Vec<AlignedPage>>>,
+    /// `None` unless writes are paused
+    paused_writes: StdArc<Mutex<Option<Vec<PausedWrite>>>>,
+}
+
+impl ClientDatabaseStorageBackend for MemoryStorageBackend {
+    fn num_pages(&self) -> u32 {
+        self.pages.lock().expect("Not poisoned; qed").len() as u32
+    }
+
+    fn read(
+        &self,
+        mut buffer: Vec<AlignedPage>,
+        length: u32,
+        offset: u32,
+    ) -> oneshot::Receiver<io::Result<Vec<AlignedPage>>> {
+        let (sender, receiver) = oneshot::channel();
+
+        let pages = self.pages.lock().expect("Not poisoned; qed");
+        let result = match pages
+            .get(offset as usize..)
+            .and_then(|pages| pages.get(..length as usize))
+        {
+            Some(pages) => {
+                buffer.extend_from_slice(pages);
+                Ok(buffer)
+            }
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        };
+        // Receiver is never dropped before the result is sent
+        let _: Result<(), _> = sender.send(result);
+
+        receiver
+    }
+
+    fn write(
+        &self,
+        buffer: StdArc<Vec<AlignedPage>>,
+        offset: u32,
+    ) -> oneshot::Receiver<io::Result<StdArc<Vec<AlignedPage>>>> {
+        let (sender, receiver) = oneshot::channel();
+
+        if let Some(paused_writes) = &mut *self.paused_writes.lock().expect("Not poisoned; qed") {
+            paused_writes.push((buffer, offset, sender));
+            return receiver;
+        }
+
+        // Receiver is never dropped before the result is sent
+        let _: Result<(), _> = sender.send(self.apply_write(buffer, offset));
+
+        receiver
+    }
+
+    fn sync(&self) -> oneshot::Receiver<io::Result<()>> {
+        let (sender, receiver) = oneshot::channel();
+
+        // Memory is always in sync
+        // Receiver is never dropped before the result is sent
+        let _: Result<(), _> = sender.send(Ok(()));
+
+        receiver
+    }
+}
+
+impl MemoryStorageBackend {
+    pub(crate) fn new(num_pages: u32) -> Self {
+        Self {
+            pages: StdArc::new(Mutex::new(vec![AlignedPage::default(); num_pages as usize])),
+            paused_writes: StdArc::default(),
+        }
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(
+            bytes.len() % AlignedPage::SIZE,
+            0,
+            "Database image must consist of whole pages"
+        );
+
+        let mut pages = vec![AlignedPage::default(); bytes.len() / AlignedPage::SIZE];
+        AlignedPage::slice_mut_to_repr(&mut pages)
+            .as_flattened_mut()
+            .copy_from_slice(bytes);
+
+        Self {
+            pages: StdArc::new(Mutex::new(pages)),
+            paused_writes: StdArc::default(),
+        }
+    }
+
+    /// Database image, only contains writes that were applied (not paused)
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let pages = self.pages.lock().expect("Not poisoned; qed");
+
+        AlignedPage::slice_to_repr(&pages).as_flattened().to_vec()
+    }
+
+    /// Replace database image with the provided bytes, simulating corruption underneath the
+    /// database
+    pub(crate) fn replace_bytes(&self, bytes: &[u8]) {
+        let mut pages = self.pages.lock().expect("Not poisoned; qed");
+        AlignedPage::slice_mut_to_repr(&mut pages)
+            .as_flattened_mut()
+            .copy_from_slice(bytes);
+    }
+
+    /// Accept writes without applying them until [`Self::resume_writes()`] is called, like a slow
+    /// storage device would
+    pub(crate) fn pause_writes(&self) {
+        self.paused_writes
+            .lock()
+            .expect("Not poisoned; qed")
+            .get_or_insert_default();
+    }
+
+    /// Apply writes that were accepted while writes were paused and stop pausing writes
+    pub(crate) fn resume_writes(&self) {
+        let paused_writes = self
+            .paused_writes
+            .lock()
+            .expect("Not poisoned; qed")
+            .take()
+            .unwrap_or_default();
+
+        for (buffer, offset, sender) in paused_writes {
+            // Receiver might have been dropped already, which is fine
+            let _: Result<(), _> = sender.send(self.apply_write(buffer, offset));
+        }
+    }
+
+    fn apply_write(
+        &self,
+        buffer: StdArc<Vec<AlignedPage>>,
+        offset: u32,
+    ) -> io::Result<StdArc<Vec<AlignedPage>>> {
+        let mut pages = self.pages.lock().expect("Not poisoned; qed");
+        match pages
+            .get_mut(offset as usize..)
+            .and_then(|pages| pages.get_mut(..buffer.len()))
+        {
+            Some(pages) => {
+                pages.copy_from_slice(&buffer);
+                Ok(buffer)
+            }
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        }
+    }
+}
+
+/// Options for opening the database on top of the storage backend without write buffering and with
+/// empty genesis state, tests override individual options with struct update syntax
+pub(crate) fn database_options(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+) -> ClientDatabaseOptions<
+    impl FnOnce() -> GenesisBlockBuilderResult<OwnedBeaconChainBlock>,
+    MemoryStorageBackend,
+> {
+    let genesis = genesis.clone();
+
+    ClientDatabaseOptions {
+        write_buffer_size: 0,
+        block_confirmation_depth: BLOCK_CONFIRMATION_DEPTH,
+        soft_confirmation_depth: SOFT_CONFIRMATION_DEPTH,
+        genesis_block_builder: move || GenesisBlockBuilderResult {
+            block: genesis,
+            system_contract_states: StdArc::new([]),
+        },
+        storage_backend,
+        ..
+    }
+}
+
+/// Open the database with [`database_options()`]
+pub(crate) fn open_database(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    block_on(ClientDatabase::open(database_options(
+        genesis,
+        storage_backend,
+    )))
+    .unwrap()
+}
@@ -0,0 +1,80 @@
+//! Database metrics must reflect writes and the in-memory state once registered
+
+use crate::memory_storage_backend::{MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfoWrite};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use prometheus_client::encoding::text::encode;
+use prometheus_client::registry::Registry;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+/// Number of blocks on top of genesis
+const NUM_BLOCKS: usize = 10;
+
+/// Value of the first sample of a metric whose name starts with `name`
+fn metric_value(registry: &Registry, name: &str) -> f64 {
+    let mut encoded = String::new();
+    encode(&mut encoded, registry).unwrap();
+
+    encoded
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .find(|line| line.starts_with(name))
+        .and_then(|line| line.rsplit(' ').next())
+        .unwrap_or_else(|| panic!("Metric {name} not found in:\n{encoded}"))
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn metrics() {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend);
+
+    let mut registry = Registry::default();
+    database.register_metrics(&mut registry);
+
+    // Only genesis block
+    assert_eq!(metric_value(&registry, "database_fork_tips"), 1.0);
+
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, NUM_BLOCKS);
+    for block in &blocks {
+        block_on(database.persist_block(
+            block.clone(),
+            BlockDetails {
+                mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+    }
+
+    assert_eq!(
+        metric_value(&registry, "database_in_memory_blocks"),
+        u64::from(SOFT_CONFIRMATION_DEPTH) as f64
+    );
+    assert_eq!(metric_value(&registry, "database_fork_tips"), 1.0);
+    assert!(metric_value(&registry, "database_pages_written") > 0.0);
+    assert_eq!(
+        metric_value(&registry, "database_write_buffer_occupancy"),
+        0.0
+    );
+}
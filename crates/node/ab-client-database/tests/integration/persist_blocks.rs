@@ -0,0 +1,127 @@
+//! A batch of blocks persisted at once must end up in the same state as blocks persisted one by
+//! one, including batches that are longer than the distance between soft confirmation and
+//! confirmation depths, and invalid batches must be rejected without any changes
+
+use crate::memory_storage_backend::{MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, open_database};
+use ab_client_api::{
+    BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite, PersistBlockError,
+};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::iter;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+fn persist_blocks(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    blocks: &[OwnedBeaconChainBlock],
+) -> Result<(), PersistBlockError> {
+    block_on(
+        database.persist_blocks(
+            blocks
+                .iter()
+                .map(|block| {
+                    (
+                        block.clone(),
+                        BlockDetails {
+                            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+                            system_contract_states: StdArc::new([]),
+                        },
+                    )
+                })
+                .collect(),
+        ),
+    )
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn assert_canonical_chain(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    canonical_chain: &[&OwnedBeaconChainBlock],
+) {
+    for (block_number, block) in canonical_chain.iter().enumerate() {
+        assert_eq!(
+            database.canonical_root(BlockNumber::from(block_number as u64)),
+            Some(root(block))
+        );
+    }
+    assert_eq!(database.best_root(), root(canonical_chain.last().unwrap()));
+}
+
+#[test]
+fn persist_blocks_batch() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 30);
+
+    // Empty batch is a no-op
+    persist_blocks(&database, &[]).unwrap();
+    assert_eq!(database.best_root(), root(&genesis));
+
+    // Gap in the batch
+    assert!(matches!(
+        persist_blocks(&database, &[blocks[0].clone(), blocks[2].clone()]),
+        Err(PersistBlockError::MissingParent)
+    ));
+    // Nothing was inserted
+    assert_eq!(database.best_root(), root(&genesis));
+    block_on(database.block(&root(&blocks[0]))).unwrap_err();
+
+    // Blocks `1..=25` in a single batch, much longer than the distance between soft confirmation
+    // and confirmation depths
+    persist_blocks(&database, &blocks[..25]).unwrap();
+    assert_canonical_chain(
+        &database,
+        &iter::once(&genesis)
+            .chain(&blocks[..25])
+            .collect::<Vec<_>>(),
+    );
+
+    // Batch doesn't extend the best block
+    assert!(matches!(
+        persist_blocks(&database, &blocks[26..]),
+        Err(PersistBlockError::MissingParent)
+    ));
+
+    // Batch that starts below the best block is not supported
+    assert!(matches!(
+        persist_blocks(&database, &blocks[20..]),
+        Err(PersistBlockError::UnsupportedForkChoice)
+    ));
+
+    // Blocks `26..=30`
+    persist_blocks(&database, &blocks[25..]).unwrap();
+    let canonical_chain = iter::once(&genesis).chain(&blocks).collect::<Vec<_>>();
+    assert_canonical_chain(&database, &canonical_chain);
+    assert!(block_on(database.verify()).unwrap().is_ok());
+
+    // Soft-confirmed blocks were persisted
+    drop(database);
+    let database = open_database(&genesis, storage_backend);
+    assert_canonical_chain(
+        &database,
+        &canonical_chain[..canonical_chain.len() - u64::from(SOFT_CONFIRMATION_DEPTH) as usize],
+    );
+}
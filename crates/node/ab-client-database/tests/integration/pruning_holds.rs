@@ -0,0 +1,118 @@
+//! Blocks covered by pruning holds must not be pruned until holds are released
+
+use crate::memory_storage_backend::{MemoryStorageBackend, database_options};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::pruning_holds::PruningHoldTarget;
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn is_known(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) -> bool {
+    block_on(database.block(&root(block))).is_ok()
+}
+
+#[test]
+fn pruning_holds() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = block_on(ClientDatabase::open(ClientDatabaseOptions {
+        // Any fork is pruned as soon as it is created unless held
+        max_fork_tips: NonZeroUsize::MIN,
+        ..database_options(&genesis, storage_backend)
+    }))
+    .unwrap();
+
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 6);
+    for block in &blocks[..5] {
+        persist_block(&database, block);
+    }
+
+    // Forks of block `5`
+    let [fork_1, fork_2, fork_3] = [1, 2, 3].map(|fork_id| {
+        TestBeaconChainBlockBuilder::default()
+            .with_fork_id(fork_id)
+            .chain(&blocks[3], 1)
+            .remove(0)
+    });
+
+    // Not held
+    persist_block(&database, &fork_1);
+    assert!(!is_known(&database, &fork_1));
+
+    // Held by root and by block range
+    let roots_hold = database.hold_block_roots("test-roots", [root(&fork_2)]);
+    let range_hold =
+        database.hold_block_range("test-range", BlockNumber::from(5)..=BlockNumber::from(5));
+    persist_block(&database, &fork_2);
+    drop(range_hold);
+    assert!(is_known(&database, &fork_2));
+
+    let pruning_holds = database.pruning_holds();
+    assert_eq!(pruning_holds.len(), 1);
+    assert_eq!(pruning_holds[0].id, roots_hold.id());
+    assert_eq!(pruning_holds[0].owner, "test-roots");
+    assert_eq!(
+        pruning_holds[0].target,
+        PruningHoldTarget::BlockRoots(vec![root(&fork_2)])
+    );
+
+    let range_hold =
+        database.hold_block_range("test-range", BlockNumber::from(5)..=BlockNumber::from(5));
+    persist_block(&database, &fork_3);
+    assert!(is_known(&database, &fork_3));
+
+    // Forks are still held after the next block
+    persist_block(&database, &blocks[5]);
+    assert!(is_known(&database, &fork_2));
+    assert!(is_known(&database, &fork_3));
+
+    // Forks are pruned once holds are released
+    drop(roots_hold);
+    drop(range_hold);
+    assert!(database.pruning_holds().is_empty());
+    let block = TestBeaconChainBlockBuilder::default()
+        .chain(&blocks[5], 1)
+        .remove(0);
+    persist_block(&database, &block);
+    assert!(!is_known(&database, &fork_2));
+    assert!(!is_known(&database, &fork_3));
+}
@@ -0,0 +1,200 @@
+//! Storage items must be readable as soon as their writes are accepted into the write buffer (or
+//! coalesced with other writes), and a crash before buffered writes reach the storage must leave
+//! the database in a consistent state
+
+use crate::memory_storage_backend::{
+    MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, database_options,
+};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions, DurabilityPolicy,
+};
+use ab_core_primitives::block::owned::{GenericOwnedBlock, OwnedBeaconChainBlock};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+use std::time::Duration;
+
+/// One permanent and four temporary page groups
+const NUM_PAGES: u32 = 80;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+/// Large enough for all persisted blocks, such that writes never wait for each other
+const WRITE_BUFFER_SIZE: usize = 5;
+/// Larger than a page group, such that coalesced writes are only limited by soft-confirmation and
+/// flushing
+const MAX_COALESCED_WRITE_PAGES: u32 = 32;
+/// Number of blocks on top of genesis
+const NUM_BLOCKS: usize = 6;
+/// Number of blocks that are soft-confirmed and written to the storage
+const NUM_PERSISTED_BLOCKS: usize = NUM_BLOCKS - u64::from(SOFT_CONFIRMATION_DEPTH) as usize;
+
+fn format_storage_backend() -> MemoryStorageBackend {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    storage_backend
+}
+
+fn open_database(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+    max_coalesced_write_pages: u32,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    block_on(ClientDatabase::open(ClientDatabaseOptions {
+        write_buffer_size: WRITE_BUFFER_SIZE,
+        max_coalesced_write_pages,
+        // Periodic flush is never polled, such that buffered writes are only flushed explicitly
+        durability_policy: DurabilityPolicy::Periodic {
+            interval: Duration::from_hours(1),
+        },
+        ..database_options(genesis, storage_backend)
+    }))
+    .unwrap()
+}
+
+fn import_blocks(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    genesis: &OwnedBeaconChainBlock,
+) -> Vec<OwnedBeaconChainBlock> {
+    let mut blocks = Vec::<OwnedBeaconChainBlock>::with_capacity(NUM_BLOCKS);
+    let mut mmr = BlockMerkleMountainRange::new();
+    assert!(mmr.add_leaf(&genesis.header.header().root()));
+
+    for _ in 0..NUM_BLOCKS {
+        let block = TestBeaconChainBlockBuilder::default().child(blocks.last().unwrap_or(genesis));
+        assert!(mmr.add_leaf(&block.header.header().root()));
+
+        block_on(database.persist_block(
+            block.clone(),
+            BlockDetails {
+                mmr_with_block: Arc::new(mmr),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+fn assert_blocks_readable(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    blocks: &[OwnedBeaconChainBlock],
+) {
+    for block in blocks {
+        let stored_block = block_on(database.block(&block.header.header().root())).unwrap();
+        assert_eq!(
+            stored_block.body().buffer().as_slice(),
+            block.body().buffer().as_slice()
+        );
+    }
+}
+
+#[test]
+fn pending_writes_are_readable() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone(), 0);
+
+    storage_backend.pause_writes();
+
+    let blocks = import_blocks(&database, &genesis);
+
+    // Soft-confirmed blocks were removed from memory, but their writes didn't reach the storage
+    // yet, so they must be served from the write buffer
+    assert_blocks_readable(&database, &blocks);
+
+    storage_backend.resume_writes();
+    block_on(database.flush()).unwrap();
+
+    // The same blocks are now read from the storage
+    assert_blocks_readable(&database, &blocks);
+    drop(database);
+
+    let database = open_database(&genesis, storage_backend, 0);
+    let persisted_blocks = &blocks[..NUM_PERSISTED_BLOCKS];
+    assert_eq!(
+        *database.best_header().header().root(),
+        *persisted_blocks.last().unwrap().header.header().root()
+    );
+    assert_blocks_readable(&database, persisted_blocks);
+}
+
+#[test]
+fn crash_before_buffered_writes_are_durable() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone(), 0);
+
+    storage_backend.pause_writes();
+
+    let blocks = import_blocks(&database, &genesis);
+    assert_blocks_readable(&database, &blocks);
+
+    // Crash while writes were accepted into the write buffer, but didn't reach the storage
+    let image = storage_backend.to_bytes();
+    drop(database);
+
+    // Buffered blocks are lost, but the database is consistent and usable
+    let storage_backend = MemoryStorageBackend::from_bytes(&image);
+    let database = open_database(&genesis, storage_backend.clone(), 0);
+    assert_eq!(
+        *database.best_header().header().root(),
+        *genesis.header.header().root()
+    );
+    assert_blocks_readable(&database, std::slice::from_ref(&genesis));
+
+    let blocks = import_blocks(&database, &genesis);
+    assert_blocks_readable(&database, &blocks);
+    block_on(database.flush()).unwrap();
+    drop(database);
+
+    let database = open_database(&genesis, storage_backend, 0);
+    let persisted_blocks = &blocks[..NUM_PERSISTED_BLOCKS];
+    assert_eq!(
+        *database.best_header().header().root(),
+        *persisted_blocks.last().unwrap().header.header().root()
+    );
+    assert_blocks_readable(&database, persisted_blocks);
+}
+
+#[test]
+fn coalesced_writes_are_readable() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone(), MAX_COALESCED_WRITE_PAGES);
+
+    storage_backend.pause_writes();
+
+    let blocks = import_blocks(&database, &genesis);
+
+    // Soft-confirmed blocks were submitted to the storage backend as coalesced writes that didn't
+    // reach the storage yet
+    assert_blocks_readable(&database, &blocks);
+
+    storage_backend.resume_writes();
+    block_on(database.flush()).unwrap();
+
+    assert_blocks_readable(&database, &blocks);
+    drop(database);
+
+    let database = open_database(&genesis, storage_backend, MAX_COALESCED_WRITE_PAGES);
+    let persisted_blocks = &blocks[..NUM_PERSISTED_BLOCKS];
+    assert_eq!(
+        *database.best_header().header().root(),
+        *persisted_blocks.last().unwrap().header.header().root()
+    );
+    assert_blocks_readable(&database, persisted_blocks);
+}
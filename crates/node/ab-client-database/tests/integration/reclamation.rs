@@ -0,0 +1,178 @@
+//! Reclamation of page groups occupied by blocks older than retained blocks, ensures that
+//! outdated blocks are pruned, their page groups are reused and that pruning holds are respected
+
+use crate::memory_storage_backend::{
+    BLOCK_CONFIRMATION_DEPTH, MemoryStorageBackend, SOFT_CONFIRMATION_DEPTH, database_options,
+};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::{
+    ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseOptions, CompactionOptions,
+    ReclamationOptions,
+};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+/// One permanent and four temporary page groups, plus one more page group for roots of pruned
+/// blocks that are persisted as leaves of the block MMR
+const NUM_PAGES: u32 = 96;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+const RETAINED_BLOCKS: BlockNumber = BlockNumber::from(12);
+/// Much more than fits into the database without reclamation
+const NUM_BLOCKS: usize = 200;
+const BLOCKS_PER_RECLAMATION: usize = 5;
+
+fn format_storage_backend() -> MemoryStorageBackend {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    storage_backend
+}
+
+fn open_database(
+    genesis: &OwnedBeaconChainBlock,
+    storage_backend: MemoryStorageBackend,
+) -> ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend> {
+    block_on(ClientDatabase::open(ClientDatabaseOptions {
+        compaction: CompactionOptions {
+            max_pages_per_second: NonZeroU32::MAX,
+            ..
+        },
+        reclamation: ReclamationOptions {
+            retained_blocks: Some(RETAINED_BLOCKS),
+            ..
+        },
+        ..database_options(genesis, storage_backend)
+    }))
+    .unwrap()
+}
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+fn is_known(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) -> bool {
+    block_on(database.block(&root(block))).is_ok()
+}
+
+#[test]
+fn invalid_retained_blocks() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+
+    let result = block_on(ClientDatabase::open(ClientDatabaseOptions {
+        reclamation: ReclamationOptions {
+            retained_blocks: Some(BLOCK_CONFIRMATION_DEPTH),
+            ..
+        },
+        ..database_options(&genesis, format_storage_backend())
+    }));
+
+    result.unwrap_err();
+}
+
+#[test]
+fn reclamation_reuses_page_groups() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    let mut blocks = vec![genesis.clone()];
+    for _ in 0..NUM_BLOCKS {
+        let block = TestBeaconChainBlockBuilder::default().child(blocks.last().unwrap());
+        // Without reclamation, the database runs out of free page groups after a few dozen blocks
+        persist_block(&database, &block);
+        blocks.push(block);
+
+        if blocks.len().is_multiple_of(BLOCKS_PER_RECLAMATION) {
+            block_on(database.reclaim()).unwrap();
+        }
+    }
+
+    let best_index = blocks.len() - 1;
+    let first_retained_index = best_index - u64::from(RETAINED_BLOCKS) as usize;
+    for (index, block) in blocks.iter().enumerate() {
+        assert_eq!(is_known(&database, block), index >= first_retained_index);
+    }
+    drop(database);
+
+    let database = open_database(&genesis, storage_backend);
+
+    // More recent blocks are only kept in memory and are lost after restart
+    let persisted_best_index = best_index - u64::from(SOFT_CONFIRMATION_DEPTH) as usize;
+    assert_eq!(
+        *database.best_header().header().root(),
+        root(&blocks[persisted_best_index])
+    );
+
+    // Blocks pruned before restart might still be stored if their page groups were not reclaimed
+    // yet, but are pruned again when the database is opened
+    for (index, block) in blocks[..=persisted_best_index].iter().enumerate() {
+        if index >= first_retained_index {
+            assert!(is_known(&database, block));
+        } else if index < persisted_best_index - u64::from(RETAINED_BLOCKS) as usize {
+            assert!(!is_known(&database, block));
+        }
+    }
+}
+
+#[test]
+fn reclamation_respects_pruning_holds() {
+    let storage_backend = format_storage_backend();
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend);
+
+    let hold = database.hold_block_range("test", BlockNumber::from(2)..=BlockNumber::from(3));
+
+    let mut blocks = vec![genesis.clone()];
+    for _ in 0..30 {
+        let block = TestBeaconChainBlockBuilder::default().child(blocks.last().unwrap());
+        persist_block(&database, &block);
+        blocks.push(block);
+    }
+    block_on(database.reclaim()).unwrap();
+
+    // Blocks below the hold are pruned, held blocks and everything above them are retained
+    for (index, block) in blocks.iter().enumerate() {
+        assert_eq!(is_known(&database, block), index >= 2);
+    }
+
+    drop(hold);
+    let block = TestBeaconChainBlockBuilder::default().child(blocks.last().unwrap());
+    persist_block(&database, &block);
+    blocks.push(block);
+
+    let first_retained_index = blocks.len() - 1 - u64::from(RETAINED_BLOCKS) as usize;
+    for (index, block) in blocks.iter().enumerate() {
+        assert_eq!(is_known(&database, block), index >= first_retained_index);
+    }
+}
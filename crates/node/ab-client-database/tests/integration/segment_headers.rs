@@ -0,0 +1,113 @@
+//! Stored segment headers are write-once: identical headers can be re-inserted, but different
+//! contents must never overwrite them since that would rewrite history
+
+use crate::memory_storage_backend::{MemoryStorageBackend, open_database};
+use ab_client_api::{ChainInfo, ChainInfoWrite, PersistSegmentHeadersError};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::BlockNumber;
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::segments::{
+    ArchivedBlockProgress, LastArchivedBlock, LocalSegmentIndex, SegmentHeader, SegmentRoot,
+};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use std::num::NonZeroU32;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+fn segment_header(local_segment_index: u64, root_byte: u8) -> SegmentHeader {
+    SegmentHeader {
+        index: LocalSegmentIndex::from(local_segment_index).into(),
+        root: SegmentRoot::from([root_byte; _]),
+        prev_segment_header_hash: Blake3Hash::default(),
+        last_archived_block: LastArchivedBlock {
+            number: BlockNumber::ZERO.into(),
+            archived_progress: ArchivedBlockProgress::new_complete(),
+        },
+    }
+}
+
+/// Check that stored segment headers match `segment_headers`, which starts with segment index `0`
+fn assert_segment_headers(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    segment_headers: &[SegmentHeader],
+) {
+    for segment_header in segment_headers {
+        assert_eq!(
+            database.get_segment_header(segment_header.index.as_inner()),
+            Some(*segment_header)
+        );
+    }
+
+    assert_eq!(
+        database.last_segment_header(),
+        segment_headers.last().copied()
+    );
+    assert_eq!(
+        database.get_segment_header(LocalSegmentIndex::from(segment_headers.len() as u64)),
+        None
+    );
+}
+
+#[test]
+fn segment_headers_are_write_once() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    let segment_headers = vec![segment_header(0, 0), segment_header(1, 1)];
+    block_on(database.persist_segment_headers(segment_headers.clone())).unwrap();
+    assert_segment_headers(&database, &segment_headers);
+
+    // Re-inserting identical segment headers is fine and doesn't change anything
+    block_on(database.persist_segment_headers(segment_headers.clone())).unwrap();
+    assert_segment_headers(&database, &segment_headers);
+
+    // Identical segment header can be re-inserted together with a new one
+    let segment_headers = vec![
+        segment_header(0, 0),
+        segment_header(1, 1),
+        segment_header(2, 2),
+    ];
+    block_on(database.persist_segment_headers(segment_headers[1..].to_vec())).unwrap();
+    assert_segment_headers(&database, &segment_headers);
+
+    // Different segment header is rejected, the whole batch is not stored
+    let error = block_on(
+        database.persist_segment_headers(vec![segment_header(1, 100), segment_header(3, 3)]),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        PersistSegmentHeadersError::SegmentHeaderMismatch { local_segment_index }
+            if local_segment_index == LocalSegmentIndex::from(1)
+    ));
+    assert_segment_headers(&database, &segment_headers);
+
+    // Stored segment headers are not changed after restart either
+    drop(database);
+    let database = open_database(&genesis, storage_backend);
+    assert_segment_headers(&database, &segment_headers);
+
+    // Different segment header is still rejected after restart
+    let error =
+        block_on(database.persist_segment_headers(vec![segment_header(0, 100)])).unwrap_err();
+    assert!(matches!(
+        error,
+        PersistSegmentHeadersError::SegmentHeaderMismatch { local_segment_index }
+            if local_segment_index == LocalSegmentIndex::ZERO
+    ));
+    assert_segment_headers(&database, &segment_headers);
+}
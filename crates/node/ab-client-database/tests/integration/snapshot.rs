@@ -0,0 +1,148 @@
+//! Snapshot must keep returning the canonical chain as of its creation after reorgs and pruning
+
+use crate::memory_storage_backend::{MemoryStorageBackend, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfo, ChainInfoWrite};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions, ClientDatabaseSnapshot};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::iter;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+fn root(block: &OwnedBeaconChainBlock) -> BlockRoot {
+    *block.header.header().root()
+}
+
+/// Check that snapshot lookups match `canonical_chain`, which starts with genesis
+fn assert_snapshot_chain(
+    snapshot: &ClientDatabaseSnapshot<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    canonical_chain: &[&OwnedBeaconChainBlock],
+) {
+    for (block_number, block) in canonical_chain.iter().enumerate() {
+        let block_number = BlockNumber::from(block_number as u64);
+
+        assert_eq!(
+            snapshot
+                .header(block_number)
+                .map(|header| *header.header().root()),
+            Some(root(block))
+        );
+        assert_eq!(
+            root(&block_on(snapshot.block(block_number)).unwrap()),
+            root(block)
+        );
+    }
+
+    let next_block_number = BlockNumber::from(canonical_chain.len() as u64);
+    assert!(snapshot.header(next_block_number).is_none());
+}
+
+#[test]
+fn snapshot_survives_reorg() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = open_database(&genesis, storage_backend);
+
+    // Blocks `1..=8`
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 8);
+    for block in &blocks {
+        persist_block(&database, block);
+    }
+    let canonical_chain = iter::once(&genesis).chain(&blocks).collect::<Vec<_>>();
+
+    let snapshot = database.snapshot();
+    assert!(snapshot.is_current());
+    assert_eq!(snapshot.best_number(), BlockNumber::from(8));
+    assert_eq!(snapshot.best_root(), root(&blocks[7]));
+    assert_snapshot_chain(&snapshot, &canonical_chain);
+
+    // Longer fork of blocks `6..=10` replaces the last blocks of the canonical chain
+    let fork = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(1)
+        .chain(&blocks[4], 5);
+    for block in &fork {
+        persist_block(&database, block);
+    }
+    assert_eq!(database.best_root(), root(&fork[4]));
+    assert_eq!(
+        database.canonical_root(BlockNumber::from(6)),
+        Some(root(&fork[0]))
+    );
+
+    // Snapshot still returns the canonical chain as of its creation
+    assert!(!snapshot.is_current());
+    assert_eq!(snapshot.best_number(), BlockNumber::from(8));
+    assert_eq!(snapshot.best_root(), root(&blocks[7]));
+    assert_snapshot_chain(&snapshot, &canonical_chain);
+
+    // New snapshot follows the new canonical chain
+    let new_snapshot = database.snapshot();
+    assert!(new_snapshot.is_current());
+    assert_snapshot_chain(
+        &new_snapshot,
+        &iter::once(&genesis)
+            .chain(&blocks[..5])
+            .chain(&fork)
+            .collect::<Vec<_>>(),
+    );
+
+    // Extending the fork until block `6` is confirmed prunes the previous canonical blocks `6..=8`
+    let fork_extension = TestBeaconChainBlockBuilder::default()
+        .with_fork_id(1)
+        .chain(&fork[4], 7);
+    for block in &fork_extension {
+        persist_block(&database, block);
+    }
+    assert!(database.header(&root(&blocks[7])).is_none());
+
+    // Headers are still returned by the snapshot, but bodies of pruned blocks are no longer
+    // available
+    for (block_number, block) in canonical_chain.iter().enumerate() {
+        let block_number = BlockNumber::from(block_number as u64);
+
+        assert_eq!(
+            snapshot
+                .header(block_number)
+                .map(|header| *header.header().root()),
+            Some(root(block))
+        );
+        if block_number <= BlockNumber::from(5) {
+            assert_eq!(
+                root(&block_on(snapshot.block(block_number)).unwrap()),
+                root(block)
+            );
+        } else {
+            block_on(snapshot.block(block_number)).unwrap_err();
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Database statistics must account for all used pages and storage items and must be the same
+//! after restart
+
+use crate::memory_storage_backend::{MemoryStorageBackend, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfoWrite};
+use ab_client_database::stats::{ClientDatabaseStats, PageGroupKind, StorageItemKind};
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 128;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+
+fn persist_block(
+    database: &ClientDatabase<OwnedBeaconChainBlock, MemoryStorageBackend>,
+    block: &OwnedBeaconChainBlock,
+) {
+    block_on(database.persist_block(
+        block.clone(),
+        BlockDetails {
+            mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+            system_contract_states: StdArc::new([]),
+        },
+    ))
+    .unwrap();
+}
+
+/// Check that statistics are internally consistent
+fn assert_consistent(stats: &ClientDatabaseStats) {
+    assert_eq!(stats.total_pages, NUM_PAGES);
+    assert_eq!(stats.page_group_size, PAGE_GROUP_SIZE.get());
+    assert_eq!(stats.used_pages + stats.free_pages, stats.total_pages);
+    assert_eq!(
+        stats.page_groups.len() as u32 + stats.free_page_groups,
+        NUM_PAGES / PAGE_GROUP_SIZE.get()
+    );
+
+    for page_group in &stats.page_groups {
+        assert_eq!(
+            page_group
+                .storage_items
+                .values()
+                .map(|storage_item_stats| storage_item_stats.pages)
+                .sum::<u64>(),
+            u64::from(page_group.used_pages)
+        );
+        assert_eq!(
+            page_group.storage_items[StorageItemKind::PageGroupHeader].count,
+            1
+        );
+    }
+
+    assert_eq!(
+        stats
+            .storage_items
+            .values()
+            .map(|storage_item_stats| storage_item_stats.pages)
+            .sum::<u64>(),
+        u64::from(stats.used_pages)
+    );
+    assert_eq!(
+        stats.storage_items[StorageItemKind::PageGroupHeader].count,
+        stats.page_groups.len() as u64
+    );
+    for kind in [PageGroupKind::Permanent, PageGroupKind::Temporary] {
+        assert!(
+            stats
+                .page_groups
+                .iter()
+                .filter(|page_group| page_group.kind == kind && page_group.active)
+                .count()
+                <= 1
+        );
+    }
+}
+
+#[test]
+fn stats() {
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    let blocks = TestBeaconChainBlockBuilder::default().chain(&genesis, 10);
+    for block in &blocks[..9] {
+        persist_block(&database, block);
+    }
+    let stats_before = block_on(database.stats());
+    assert_consistent(&stats_before);
+    assert_eq!(
+        stats_before.storage_items[StorageItemKind::KnownSegmentHeaders].count,
+        0
+    );
+    assert!(stats_before.storage_items[StorageItemKind::Block].count > 0);
+
+    // Every new best block results in one more block being persisted
+    persist_block(&database, &blocks[9]);
+    let stats = block_on(database.stats());
+    assert_consistent(&stats);
+    let blocks_before = stats_before.storage_items[StorageItemKind::Block];
+    let blocks_after = stats.storage_items[StorageItemKind::Block];
+    assert_eq!(blocks_after.count, blocks_before.count + 1);
+    assert!(blocks_after.pages > blocks_before.pages);
+    assert!(blocks_after.bytes > blocks_before.bytes);
+    assert!(stats.used_pages > stats_before.used_pages);
+
+    // The same statistics are reconstructed from storage after restart
+    block_on(database.flush()).unwrap();
+    drop(database);
+    let database = open_database(&genesis, storage_backend);
+    let stats_after_restart = block_on(database.stats());
+    assert_consistent(&stats_after_restart);
+    assert_eq!(stats_after_restart.used_pages, stats.used_pages);
+    assert_eq!(stats_after_restart.storage_items, stats.storage_items);
+}
@@ -0,0 +1,100 @@
+//! Integrity verification must pass on a healthy database and report corrupted storage items and
+//! orphaned pages otherwise
+
+use crate::memory_storage_backend::{MemoryStorageBackend, open_database};
+use ab_client_api::{BlockDetails, BlockMerkleMountainRange, ChainInfoWrite};
+use ab_client_database::storage_backend::AlignedPage;
+use ab_client_database::verification::VerificationIssue;
+use ab_client_database::{ClientDatabase, ClientDatabaseFormatOptions};
+use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
+use ab_core_primitives::test_utils::TestBeaconChainBlockBuilder;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use std::sync::Arc as StdArc;
+
+const NUM_PAGES: u32 = 80;
+const PAGE_GROUP_SIZE: NonZeroU32 = NonZeroU32::new(16).expect("Not zero; qed");
+/// Number of blocks on top of genesis
+const NUM_BLOCKS: usize = 6;
+/// The first temporary page group follows the first (permanent) page group
+const FIRST_TEMPORARY_PAGE_GROUP_OFFSET: u32 = PAGE_GROUP_SIZE.get();
+
+#[test]
+fn verification() {
+    let storage_backend = MemoryStorageBackend::new(NUM_PAGES);
+    block_on(ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+        &storage_backend,
+        ClientDatabaseFormatOptions {
+            page_group_size: PAGE_GROUP_SIZE,
+            force: false,
+            known_segment_headers: Vec::new(),
+            ..
+        },
+    ))
+    .unwrap();
+
+    let genesis = TestBeaconChainBlockBuilder::default().genesis();
+    let database = open_database(&genesis, storage_backend.clone());
+
+    for block in TestBeaconChainBlockBuilder::default().chain(&genesis, NUM_BLOCKS) {
+        block_on(database.persist_block(
+            block,
+            BlockDetails {
+                mmr_with_block: Arc::new(BlockMerkleMountainRange::new()),
+                system_contract_states: StdArc::new([]),
+            },
+        ))
+        .unwrap();
+    }
+
+    // Healthy database
+    {
+        let report = block_on(database.verify()).unwrap();
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(
+            report.checked_page_groups,
+            NUM_PAGES / PAGE_GROUP_SIZE.get()
+        );
+        assert!(report.checked_storage_items > 0);
+        assert_eq!(report.checked_blocks, NUM_BLOCKS as u64 + 1);
+    }
+
+    // Corrupt the prefix of the first storage item after the page group header of the first
+    // temporary page group and write some data into the last page group, which is free
+    {
+        let mut bytes = storage_backend.to_bytes();
+        let corrupted_page_offset = FIRST_TEMPORARY_PAGE_GROUP_OFFSET + 1;
+        bytes[corrupted_page_offset as usize * AlignedPage::SIZE + 20] ^= 0xff;
+        let last_page_offset = NUM_PAGES - 1;
+        bytes[last_page_offset as usize * AlignedPage::SIZE] = 1;
+        storage_backend.replace_bytes(&bytes);
+
+        let report = block_on(database.verify()).unwrap();
+        assert!(!report.is_ok());
+        assert!(
+            report.issues.iter().any(|issue| matches!(
+                issue,
+                VerificationIssue::CorruptedStorageItem { page_offset, .. }
+                    if *page_offset == corrupted_page_offset
+            )),
+            "{report:?}"
+        );
+        // Storage items after the corrupted one are not reachable anymore
+        assert!(
+            report.issues.iter().any(|issue| matches!(
+                issue,
+                VerificationIssue::OrphanedPages { page_offset, .. }
+                    if *page_offset == corrupted_page_offset + 1
+            )),
+            "{report:?}"
+        );
+        assert!(
+            report.issues.contains(&VerificationIssue::OrphanedPages {
+                page_offset: last_page_offset,
+                num_pages: 1,
+            }),
+            "{report:?}"
+        );
+    }
+}
@@ -1,43 +1,125 @@
-//! Client informer, which logs node state periodically
+//! Client informer, which logs node state periodically.
+//!
+//! Node may run clients of multiple shards in the same process (beacon chain and any number of
+//! intermediate and leaf shards), all of them are registered in [`ShardClients`] and reported
+//! together.
 
 use ab_client_api::ChainInfo;
 use ab_core_primitives::block::header::GenericBlockHeader;
 use ab_core_primitives::block::header::owned::GenericOwnedBlockHeader;
 use ab_core_primitives::block::owned::GenericOwnedBlock;
-use ab_core_primitives::shard::RealShardKind;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::shard::{RealShardKind, ShardIndex};
+use std::fmt;
+use std::marker::PhantomData;
 use std::time::Duration;
 use tracing::info;
 
-pub async fn run_informer<Block, CI>(chain_info: &CI, log_interval: Duration)
+/// Status of a shard client
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ShardStatus {
+    /// Shard kind
+    pub shard_kind: RealShardKind,
+    /// Shard index
+    pub shard_index: ShardIndex,
+    /// Best block number
+    pub best_number: BlockNumber,
+    /// Best block root
+    pub best_root: BlockRoot,
+}
+
+impl fmt::Display for ShardStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.shard_kind {
+            RealShardKind::BeaconChain => write!(f, "BeaconChain"),
+            RealShardKind::IntermediateShard => {
+                write!(f, "Intermediate[{}]", u32::from(self.shard_index))
+            }
+            RealShardKind::LeafShard => write!(f, "Leaf[{}]", u32::from(self.shard_index)),
+        }
+    }
+}
+
+/// Client of a shard running in this process
+pub trait ShardClient: fmt::Debug + Send + Sync {
+    /// Current status of the shard client
+    fn status(&self) -> ShardStatus;
+}
+
+/// [`ShardClient`] implementation for any [`ChainInfo`]
+#[derive(Debug)]
+pub struct ChainInfoShardClient<Block, CI> {
+    chain_info: CI,
+    _block: PhantomData<fn() -> Block>,
+}
+
+impl<Block, CI> ShardClient for ChainInfoShardClient<Block, CI>
 where
     Block: GenericOwnedBlock,
-    CI: ChainInfo<Block>,
+    CI: ChainInfo<Block> + fmt::Debug,
 {
-    let shard = match Block::SHARD_KIND {
-        RealShardKind::BeaconChain => "BeaconChain".to_string(),
-        RealShardKind::IntermediateShard => {
-            format!(
-                "Intermediate[{}]",
-                u32::from(chain_info.best_header().header().prefix.shard_index)
-            )
+    fn status(&self) -> ShardStatus {
+        let best_header = self.chain_info.best_header();
+        let best_header = best_header.header();
+
+        ShardStatus {
+            shard_kind: Block::SHARD_KIND,
+            shard_index: best_header.prefix.shard_index,
+            best_number: best_header.prefix.number,
+            best_root: *best_header.root(),
         }
-        RealShardKind::LeafShard => {
-            format!(
-                "Leaf[{}]",
-                u32::from(chain_info.best_header().header().prefix.shard_index)
-            )
+    }
+}
+
+impl<Block, CI> ChainInfoShardClient<Block, CI>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfo<Block>,
+{
+    /// Create a new instance
+    #[inline(always)]
+    pub fn new(chain_info: CI) -> Self {
+        Self {
+            chain_info,
+            _block: PhantomData,
         }
-    };
+    }
+}
+
+/// Clients of all shards running in this process
+#[derive(Debug, Default)]
+pub struct ShardClients {
+    clients: Vec<Box<dyn ShardClient>>,
+}
+
+impl ShardClients {
+    /// Register a shard client
+    pub fn add<C>(&mut self, client: C)
+    where
+        C: ShardClient + 'static,
+    {
+        self.clients.push(Box::new(client));
+    }
+
+    /// Combined status of all shard clients in the order they were registered
+    pub fn statuses(&self) -> Vec<ShardStatus> {
+        self.clients.iter().map(|client| client.status()).collect()
+    }
+}
+
+/// Log status of all shard clients every `log_interval`
+pub async fn run_informer(shard_clients: &ShardClients, log_interval: Duration) {
     loop {
         // TODO: Sync and networking status once implemented
 
-        let best_header = chain_info.best_header();
-        info!(
-            %shard,
-            best_number = %best_header.header().prefix.number,
-            best_root = %*best_header.header().root(),
-            "💤"
-        );
+        for status in shard_clients.statuses() {
+            info!(
+                shard = %status,
+                best_number = %status.best_number,
+                best_root = %status.best_root,
+                "💤"
+            );
+        }
 
         tokio::time::sleep(log_interval).await;
     }
@@ -0,0 +1,433 @@
+//! Typed notification bus for node events.
+//!
+//! Various components of the node (block import, archiver, slot worker, RPC server, etc.) need to
+//! notify each other about events like newly imported blocks or archived segments. Instead of a
+//! dedicated channel between every producer and consumer, each kind of event is a [`Topic`] on a
+//! shared [`NotificationBus`]. Producers [`publish`](NotificationBus::publish()) messages to a
+//! topic and any number of consumers can [`subscribe`](NotificationBus::subscribe()) to it, which
+//! makes adding new consumers (like indexers) a local change that doesn't affect producers.
+//!
+//! Every subscriber has its own buffer and decides what happens when it is full with
+//! [`BufferingPolicy`]: consumers that must not miss notifications apply backpressure to the
+//! publisher, while others drop either the newest or the oldest notifications instead.
+//!
+//! The number of published and dropped notifications, as well as the number of subscribers of
+//! each topic are optionally recorded in the metrics registry.
+
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+use crate::metrics::NotificationBusMetrics;
+use event_listener::{Event, EventListener};
+use futures::Stream;
+use futures::stream::FusedStream;
+use parking_lot::Mutex;
+use prometheus_client::registry::Registry;
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+use tracing::debug;
+
+/// Topic of notifications.
+///
+/// Topics are usually zero-sized types declared next to the message type by the crate that
+/// publishes corresponding notifications.
+pub trait Topic: 'static {
+    /// Name of the topic, used in logs and metrics
+    const NAME: &'static str;
+    /// Message published to the topic
+    type Message: Clone + Send + 'static;
+}
+
+/// What happens with new notifications when the buffer of a subscriber is full
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BufferingPolicy {
+    /// Publisher waits for the subscriber to make space in the buffer, no notifications are lost
+    Backpressure {
+        /// Max number of buffered notifications
+        capacity: NonZeroUsize,
+    },
+    /// New notifications are dropped while the buffer is full
+    DropNewest {
+        /// Max number of buffered notifications
+        capacity: NonZeroUsize,
+    },
+    /// The oldest buffered notification is dropped to make space for a new one
+    DropOldest {
+        /// Max number of buffered notifications
+        capacity: NonZeroUsize,
+    },
+}
+
+impl BufferingPolicy {
+    #[inline(always)]
+    fn capacity(self) -> usize {
+        match self {
+            Self::Backpressure { capacity }
+            | Self::DropNewest { capacity }
+            | Self::DropOldest { capacity } => capacity.get(),
+        }
+    }
+}
+
+/// Result of pushing a message into the queue of a subscriber
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum PushResult {
+    /// Message was added to the queue
+    Pushed,
+    /// Message was added to the queue, but the oldest message was dropped to make space for it
+    PushedDroppingOldest,
+    /// Message was dropped because the queue is full
+    Dropped,
+    /// Queue is closed
+    Closed,
+}
+
+#[derive(Debug)]
+struct QueueState<M> {
+    messages: VecDeque<M>,
+    /// Either the subscription or the bus was dropped
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct SubscriberQueue<M> {
+    policy: BufferingPolicy,
+    state: Mutex<QueueState<M>>,
+    /// Notified when a message is added or the queue is closed
+    message_added: Event,
+    /// Notified when a message is taken or the queue is closed
+    message_taken: Event,
+}
+
+impl<M> SubscriberQueue<M> {
+    fn new(policy: BufferingPolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(QueueState {
+                messages: VecDeque::with_capacity(policy.capacity()),
+                closed: false,
+            }),
+            message_added: Event::new(),
+            message_taken: Event::new(),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.state.lock().closed
+    }
+
+    fn close(&self) {
+        self.state.lock().closed = true;
+        self.message_added.notify(usize::MAX);
+        self.message_taken.notify(usize::MAX);
+    }
+
+    async fn push(&self, message: M) -> PushResult {
+        loop {
+            let listener = {
+                let mut state = self.state.lock();
+
+                if state.closed {
+                    return PushResult::Closed;
+                }
+
+                if state.messages.len() < self.policy.capacity() {
+                    state.messages.push_back(message);
+                    drop(state);
+                    self.message_added.notify(1);
+
+                    return PushResult::Pushed;
+                }
+
+                match self.policy {
+                    BufferingPolicy::Backpressure { .. } => {
+                        // Listener is created while the lock is held, so notification about taken
+                        // message can't be missed
+                        self.message_taken.listen()
+                    }
+                    BufferingPolicy::DropNewest { .. } => {
+                        return PushResult::Dropped;
+                    }
+                    BufferingPolicy::DropOldest { .. } => {
+                        state.messages.pop_front();
+                        state.messages.push_back(message);
+                        drop(state);
+                        self.message_added.notify(1);
+
+                        return PushResult::PushedDroppingOldest;
+                    }
+                }
+            };
+
+            listener.await;
+        }
+    }
+}
+
+trait TopicSubscribersErased: Send {
+    fn close(&self);
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+struct TopicSubscribers<M> {
+    queues: Vec<Arc<SubscriberQueue<M>>>,
+}
+
+impl<M> TopicSubscribersErased for TopicSubscribers<M>
+where
+    M: Send + 'static,
+{
+    fn close(&self) {
+        for queue in &self.queues {
+            queue.close();
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+struct Inner {
+    topics: Mutex<HashMap<TypeId, Box<dyn TopicSubscribersErased>>>,
+    metrics: Option<NotificationBusMetrics>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Subscriptions end once there is nobody left to publish notifications
+        for topic_subscribers in self.topics.get_mut().values() {
+            topic_subscribers.close();
+        }
+    }
+}
+
+impl Inner {
+    /// Run `f` with subscribers of the topic after removing subscribers that are gone
+    fn with_subscribers<T, F, R>(&self, f: F) -> R
+    where
+        T: Topic,
+        F: FnOnce(&mut Vec<Arc<SubscriberQueue<T::Message>>>) -> R,
+    {
+        let mut topics = self.topics.lock();
+        let topic_subscribers = topics
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(TopicSubscribers::<T::Message> { queues: Vec::new() }))
+            .as_any_mut()
+            .downcast_mut::<TopicSubscribers<T::Message>>()
+            .expect("Entries are always inserted with the message type of the topic; qed");
+
+        topic_subscribers.queues.retain(|queue| !queue.is_closed());
+        let result = f(&mut topic_subscribers.queues);
+
+        if let Some(metrics) = &self.metrics {
+            metrics.update_subscribers(T::NAME, topic_subscribers.queues.len());
+        }
+
+        result
+    }
+}
+
+/// Subscription to notifications of a topic, created with [`NotificationBus::subscribe()`].
+///
+/// The stream ends once all instances of the [`NotificationBus`] are dropped and all buffered
+/// notifications were received.
+pub struct Subscription<T>
+where
+    T: Topic,
+{
+    queue: Arc<SubscriberQueue<T::Message>>,
+    listener: Option<EventListener>,
+    _topic: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for Subscription<T>
+where
+    T: Topic,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("topic", &T::NAME)
+            .field("policy", &self.queue.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Subscription<T>
+where
+    T: Topic,
+{
+    #[inline]
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+impl<T> Stream for Subscription<T>
+where
+    T: Topic,
+{
+    type Item = T::Message;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            {
+                let mut state = this.queue.state.lock();
+
+                if let Some(message) = state.messages.pop_front() {
+                    drop(state);
+                    this.queue.message_taken.notify(1);
+                    this.listener = None;
+
+                    return Poll::Ready(Some(message));
+                }
+
+                if state.closed {
+                    return Poll::Ready(None);
+                }
+
+                if this.listener.is_none() {
+                    // Listener is created while the lock is held, so notification about added
+                    // message can't be missed
+                    this.listener.replace(this.queue.message_added.listen());
+                }
+            }
+
+            let listener = this
+                .listener
+                .as_mut()
+                .expect("Listener was created above if it didn't exist; qed");
+            ready!(Pin::new(listener).poll(cx));
+            this.listener = None;
+        }
+    }
+}
+
+impl<T> FusedStream for Subscription<T>
+where
+    T: Topic,
+{
+    fn is_terminated(&self) -> bool {
+        let state = self.queue.state.lock();
+        state.closed && state.messages.is_empty()
+    }
+}
+
+/// Typed notification bus.
+///
+/// Cheap to clone, all clones share the same topics and subscribers.
+#[derive(Clone)]
+pub struct NotificationBus {
+    inner: Arc<Inner>,
+}
+
+impl fmt::Debug for NotificationBus {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NotificationBus").finish_non_exhaustive()
+    }
+}
+
+impl NotificationBus {
+    /// Create a new instance.
+    ///
+    /// Metrics are recorded if `registry` is provided.
+    pub fn new(registry: Option<&mut Registry>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                topics: Mutex::default(),
+                metrics: registry.map(NotificationBusMetrics::new),
+            }),
+        }
+    }
+
+    /// Subscribe to notifications of a topic.
+    ///
+    /// Only notifications published after subscription are received.
+    pub fn subscribe<T>(&self, policy: BufferingPolicy) -> Subscription<T>
+    where
+        T: Topic,
+    {
+        let queue = Arc::new(SubscriberQueue::new(policy));
+
+        self.inner.with_subscribers::<T, _, _>(|queues| {
+            queues.push(Arc::clone(&queue));
+        });
+
+        Subscription {
+            queue,
+            listener: None,
+            _topic: PhantomData,
+        }
+    }
+
+    /// Publish a message to all current subscribers of a topic.
+    ///
+    /// Waits for subscribers with [`BufferingPolicy::Backpressure`] to make space in their buffers.
+    /// Returns the number of subscribers the message was delivered to.
+    pub async fn publish<T>(&self, message: T::Message) -> usize
+    where
+        T: Topic,
+    {
+        let queues = self
+            .inner
+            .with_subscribers::<T, _, _>(|queues| queues.clone());
+
+        if let Some(metrics) = &self.inner.metrics {
+            metrics.notification_published(T::NAME);
+        }
+
+        let mut delivered = 0;
+        for queue in queues {
+            match queue.push(message.clone()).await {
+                PushResult::Pushed => {
+                    delivered += 1;
+                }
+                PushResult::PushedDroppingOldest => {
+                    delivered += 1;
+                    debug!(
+                        topic = T::NAME,
+                        "Subscriber is too slow, dropped the oldest notification"
+                    );
+                    if let Some(metrics) = &self.inner.metrics {
+                        metrics.notification_dropped(T::NAME);
+                    }
+                }
+                PushResult::Dropped => {
+                    debug!(
+                        topic = T::NAME,
+                        "Subscriber is too slow, dropped the new notification"
+                    );
+                    if let Some(metrics) = &self.inner.metrics {
+                        metrics.notification_dropped(T::NAME);
+                    }
+                }
+                PushResult::Closed => {
+                    // Subscription was dropped, will be removed next time
+                }
+            }
+        }
+
+        delivered
+    }
+
+    /// Number of current subscribers of a topic
+    pub fn num_subscribers<T>(&self) -> usize
+    where
+        T: Topic,
+    {
+        self.inner
+            .with_subscribers::<T, _, _>(|queues| queues.len())
+    }
+}
@@ -0,0 +1,70 @@
+//! Metrics for notification bus
+
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::registry::{Registry, Unit};
+use std::sync::atomic::{AtomicI64, AtomicU64};
+
+/// Metrics for notification bus
+#[derive(Debug)]
+pub(super) struct NotificationBusMetrics {
+    notifications_published: Family<Vec<(&'static str, String)>, Counter<u64, AtomicU64>>,
+    notifications_dropped: Family<Vec<(&'static str, String)>, Counter<u64, AtomicU64>>,
+    subscribers: Family<Vec<(&'static str, String)>, Gauge<i64, AtomicI64>>,
+}
+
+impl NotificationBusMetrics {
+    /// Create a new instance
+    pub(super) fn new(registry: &mut Registry) -> Self {
+        let registry = registry.sub_registry_with_prefix("notification_bus");
+
+        let notifications_published = Family::default();
+        registry.register_with_unit(
+            "notifications_published_counter",
+            "Number of notifications published to a topic",
+            Unit::Other("Notifications".to_string()),
+            notifications_published.clone(),
+        );
+
+        let notifications_dropped = Family::default();
+        registry.register_with_unit(
+            "notifications_dropped_counter",
+            "Number of notifications dropped because subscribers were too slow",
+            Unit::Other("Notifications".to_string()),
+            notifications_dropped.clone(),
+        );
+
+        let subscribers = Family::default();
+        registry.register_with_unit(
+            "subscribers",
+            "Number of subscribers of a topic",
+            Unit::Other("Subscribers".to_string()),
+            subscribers.clone(),
+        );
+
+        Self {
+            notifications_published,
+            notifications_dropped,
+            subscribers,
+        }
+    }
+
+    pub(super) fn notification_published(&self, topic: &'static str) {
+        self.notifications_published
+            .get_or_create(&vec![("topic", topic.to_string())])
+            .inc();
+    }
+
+    pub(super) fn notification_dropped(&self, topic: &'static str) {
+        self.notifications_dropped
+            .get_or_create(&vec![("topic", topic.to_string())])
+            .inc();
+    }
+
+    pub(super) fn update_subscribers(&self, topic: &'static str, subscribers: usize) {
+        self.subscribers
+            .get_or_create(&vec![("topic", topic.to_string())])
+            .set(i64::try_from(subscribers).unwrap_or(i64::MAX));
+    }
+}
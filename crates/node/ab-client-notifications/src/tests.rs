@@ -0,0 +1,101 @@
+use crate::{BufferingPolicy, NotificationBus, Topic};
+use futures::executor::block_on;
+use futures::{FutureExt, StreamExt};
+use prometheus_client::registry::Registry;
+use std::num::NonZeroUsize;
+
+struct NumbersTopic;
+
+impl Topic for NumbersTopic {
+    const NAME: &'static str = "numbers";
+    type Message = u32;
+}
+
+struct OtherNumbersTopic;
+
+impl Topic for OtherNumbersTopic {
+    const NAME: &'static str = "other-numbers";
+    type Message = u32;
+}
+
+const CAPACITY: NonZeroUsize = NonZeroUsize::new(2).unwrap();
+
+#[test]
+fn publish_subscribe() {
+    let bus = NotificationBus::new(None);
+
+    // Nobody to deliver to
+    assert_eq!(block_on(bus.publish::<NumbersTopic>(0)), 0);
+
+    let mut subscription_1 =
+        bus.subscribe::<NumbersTopic>(BufferingPolicy::Backpressure { capacity: CAPACITY });
+    let mut subscription_2 =
+        bus.subscribe::<NumbersTopic>(BufferingPolicy::DropNewest { capacity: CAPACITY });
+    let mut other_subscription =
+        bus.subscribe::<OtherNumbersTopic>(BufferingPolicy::DropNewest { capacity: CAPACITY });
+    assert_eq!(bus.num_subscribers::<NumbersTopic>(), 2);
+    assert_eq!(bus.num_subscribers::<OtherNumbersTopic>(), 1);
+
+    assert_eq!(block_on(bus.publish::<NumbersTopic>(1)), 2);
+    assert_eq!(block_on(subscription_1.next()), Some(1));
+    assert_eq!(block_on(subscription_2.next()), Some(1));
+    // Topics are independent even though message types are the same
+    assert!(other_subscription.next().now_or_never().is_none());
+
+    drop(subscription_2);
+    assert_eq!(bus.num_subscribers::<NumbersTopic>(), 1);
+    assert_eq!(block_on(bus.publish::<NumbersTopic>(2)), 1);
+
+    // Buffered notifications are still received after the bus is dropped, then the stream ends
+    drop(bus);
+    assert_eq!(block_on(subscription_1.next()), Some(2));
+    assert_eq!(block_on(subscription_1.next()), None);
+    assert_eq!(block_on(other_subscription.next()), None);
+}
+
+#[test]
+fn buffering_policies() {
+    let bus = NotificationBus::new(Some(&mut Registry::default()));
+
+    let mut drop_newest =
+        bus.subscribe::<NumbersTopic>(BufferingPolicy::DropNewest { capacity: CAPACITY });
+    let mut drop_oldest =
+        bus.subscribe::<NumbersTopic>(BufferingPolicy::DropOldest { capacity: CAPACITY });
+
+    for number in 0..4 {
+        block_on(bus.publish::<NumbersTopic>(number));
+    }
+
+    assert_eq!(block_on(drop_newest.next()), Some(0));
+    assert_eq!(block_on(drop_newest.next()), Some(1));
+    assert!(drop_newest.next().now_or_never().is_none());
+
+    assert_eq!(block_on(drop_oldest.next()), Some(2));
+    assert_eq!(block_on(drop_oldest.next()), Some(3));
+    assert!(drop_oldest.next().now_or_never().is_none());
+}
+
+#[test]
+fn backpressure() {
+    let bus = NotificationBus::new(None);
+
+    let mut subscription =
+        bus.subscribe::<NumbersTopic>(BufferingPolicy::Backpressure { capacity: CAPACITY });
+
+    for number in 0..2 {
+        assert_eq!(bus.publish::<NumbersTopic>(number).now_or_never(), Some(1));
+    }
+
+    // Buffer is full, publisher has to wait
+    let mut publish_fut = Box::pin(bus.publish::<NumbersTopic>(2));
+    assert!((&mut publish_fut).now_or_never().is_none());
+
+    assert_eq!(block_on(subscription.next()), Some(0));
+    assert_eq!((&mut publish_fut).now_or_never(), Some(1));
+
+    // Publisher doesn't wait for subscriptions that are gone
+    let mut publish_fut = Box::pin(bus.publish::<NumbersTopic>(3));
+    assert!((&mut publish_fut).now_or_never().is_none());
+    drop(subscription);
+    assert_eq!((&mut publish_fut).now_or_never(), Some(0));
+}
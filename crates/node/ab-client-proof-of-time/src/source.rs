@@ -1,5 +1,6 @@
 pub mod block_import;
 pub mod gossip;
+pub mod mock_timekeeper;
 pub mod state;
 pub mod timekeeper;
 
@@ -1,9 +1,35 @@
 //! PoT gossip functionality.
 
+#[cfg(test)]
+mod tests;
+
 use crate::PotNextSlotInput;
+use crate::source::state::PotState;
+use crate::verifier::PotVerifier;
+use ab_core_primitives::block::BlockRoot;
 use ab_core_primitives::pot::{PotCheckpoints, PotSeed, SlotNumber};
+use ab_networking::libp2p::PeerId;
+use ab_networking::libp2p::gossipsub::{MessageAcceptance, Sha256Topic};
+use ab_networking::{GossipMessage, Node};
+use futures::channel::mpsc;
+use futures::{StreamExt, select};
 use parity_scale_codec::{Decode, Encode};
+use rclite::Arc;
+use schnellru::{ByLength, LruMap};
+use std::collections::BTreeMap;
 use std::num::NonZeroU32;
+use tracing::{debug, error, trace, warn};
+
+/// How many slots ahead of the current PoT tip gossip proofs are accepted
+const MAX_SLOTS_IN_THE_FUTURE: u64 = 10;
+/// Max number of proofs a single peer can send for the same slot
+const MAX_PROOFS_PER_PEER_PER_SLOT: u8 = 2;
+/// Max number of verified proofs for the same future slot to retain
+const MAX_PENDING_PROOFS_PER_SLOT: usize = 3;
+/// Number of peers to track rate limits for
+const PEER_RATE_LIMITS_CACHE_SIZE: u32 = 1_000;
+/// Capacity of channels between gossip worker and PoT source worker
+const GOSSIP_CHANNEL_CAPACITY: usize = 10;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Encode, Decode)]
 pub struct GossipProof {
@@ -22,3 +48,315 @@ pub enum ToGossipMessage {
     Proof(GossipProof),
     NextSlotInput(PotNextSlotInput),
 }
+
+/// Gossip topic for proofs of time of the chain with specified genesis root
+pub fn pot_gossip_topic(genesis_root: &BlockRoot) -> Sha256Topic {
+    Sha256Topic::new(format!("/{genesis_root}/pot/1"))
+}
+
+/// Misbehavior of a peer observed in PoT gossip
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PotGossipMisbehavior {
+    /// Message can't be decoded
+    Undecodable,
+    /// Proof is too far in the future
+    TooFarInFuture,
+    /// Peer sent too many proofs for the same slot
+    RateLimitExceeded,
+    /// Proof checkpoints are invalid
+    InvalidProof,
+}
+
+impl PotGossipMisbehavior {
+    /// Whether misbehavior is severe enough for the peer to be banned.
+    ///
+    /// Non-fatal misbehavior can be caused by honest peers that are out of sync or on a different
+    /// fork.
+    pub fn is_fatal(self) -> bool {
+        match self {
+            Self::Undecodable | Self::InvalidProof => true,
+            Self::TooFarInFuture | Self::RateLimitExceeded => false,
+        }
+    }
+}
+
+/// Peer scoring hooks for PoT gossip.
+///
+/// Peers are banned on fatal misbehavior regardless of the implementation, hooks allow to
+/// implement more nuanced scoring on top of that.
+pub trait PotGossipPeerScoring: Send {
+    /// Peer sent a valid proof
+    fn on_valid_proof(&mut self, _peer_id: PeerId) {}
+
+    /// Peer misbehaved
+    fn on_misbehavior(&mut self, peer_id: PeerId, misbehavior: PotGossipMisbehavior);
+}
+
+impl PotGossipPeerScoring for () {
+    fn on_misbehavior(&mut self, _peer_id: PeerId, _misbehavior: PotGossipMisbehavior) {}
+}
+
+/// Validator of proofs received from gossip
+#[derive(Debug)]
+struct PotGossipValidator {
+    pot_verifier: PotVerifier,
+    pot_state: Arc<PotState>,
+    /// Number of proofs received from each peer for every slot that is not stale yet.
+    ///
+    /// Counters are tracked for each slot separately, such that alternating between slots doesn't
+    /// reset them. The number of slots per peer is bounded by [`MAX_SLOTS_IN_THE_FUTURE`].
+    peer_rate_limits: LruMap<PeerId, BTreeMap<SlotNumber, u8>>,
+}
+
+impl PotGossipValidator {
+    fn new(pot_verifier: PotVerifier, pot_state: Arc<PotState>) -> Self {
+        Self {
+            pot_verifier,
+            pot_state,
+            peer_rate_limits: LruMap::new(ByLength::new(PEER_RATE_LIMITS_CACHE_SIZE)),
+        }
+    }
+
+    /// Returns `Ok(None)` for proofs that are correct, but not useful (stale, from a different
+    /// fork, etc.)
+    async fn validate(
+        &mut self,
+        propagation_source: PeerId,
+        data: &[u8],
+    ) -> Result<Option<GossipProof>, PotGossipMisbehavior> {
+        let proof =
+            GossipProof::decode(&mut &*data).map_err(|_error| PotGossipMisbehavior::Undecodable)?;
+
+        let next_slot_input = self.pot_state.next_slot_input();
+        if proof.slot < next_slot_input.slot {
+            trace!(
+                slot = %proof.slot,
+                next_slot = %next_slot_input.slot,
+                "Ignoring stale PoT gossip proof"
+            );
+            return Ok(None);
+        }
+        if proof.slot > next_slot_input.slot + SlotNumber::from(MAX_SLOTS_IN_THE_FUTURE) {
+            return Err(PotGossipMisbehavior::TooFarInFuture);
+        }
+        if proof.slot == next_slot_input.slot
+            && (proof.seed != next_slot_input.seed
+                || proof.slot_iterations != next_slot_input.slot_iterations)
+        {
+            trace!(
+                slot = %proof.slot,
+                "Ignoring PoT gossip proof that doesn't match next slot input"
+            );
+            return Ok(None);
+        }
+
+        let peer_rate_limits = self
+            .peer_rate_limits
+            .get_or_insert(propagation_source, BTreeMap::new)
+            .expect("LRU map is not empty; qed");
+        // Counters for slots before the next slot are no longer needed
+        *peer_rate_limits = peer_rate_limits.split_off(&next_slot_input.slot);
+        let proofs = peer_rate_limits.entry(proof.slot).or_default();
+        if *proofs == MAX_PROOFS_PER_PEER_PER_SLOT {
+            return Err(PotGossipMisbehavior::RateLimitExceeded);
+        }
+        *proofs += 1;
+
+        // Verification is CPU-intensive, hence done in a blocking task
+        let pot_verifier = self.pot_verifier.clone();
+        let valid = tokio::task::spawn_blocking(move || {
+            pot_verifier.verify_checkpoints(proof.seed, proof.slot_iterations, &proof.checkpoints)
+        })
+        .await;
+
+        match valid {
+            Ok(true) => Ok(Some(proof)),
+            Ok(false) => Err(PotGossipMisbehavior::InvalidProof),
+            Err(error) => {
+                error!(%error, slot = %proof.slot, "PoT gossip proof verification task failed");
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Gossip worker that exchanges proofs of time with other peers.
+///
+/// Received proofs are validated against the PoT chain tip, rate limited and verified before being
+/// propagated further and sent to the PoT source worker.
+#[derive(Debug)]
+#[must_use = "Gossip worker doesn't do anything unless run() method is called"]
+pub struct PotGossipWorker<PS> {
+    node: Node,
+    topic: Sha256Topic,
+    pot_state: Arc<PotState>,
+    validator: PotGossipValidator,
+    peer_scoring: PS,
+    to_gossip_receiver: mpsc::Receiver<ToGossipMessage>,
+    from_gossip_sender: mpsc::Sender<GossipProof>,
+    /// Verified proofs for future slots that will be sent to PoT source worker once PoT chain
+    /// reaches corresponding slot
+    pending_proofs: BTreeMap<SlotNumber, Vec<GossipProof>>,
+}
+
+impl<PS> PotGossipWorker<PS>
+where
+    PS: PotGossipPeerScoring,
+{
+    /// Create a new gossip worker.
+    ///
+    /// Returns the worker itself, sender for messages to gossip and receiver for proofs received
+    /// from gossip (to be used with `PotSourceWorker`).
+    pub fn new(
+        node: Node,
+        genesis_root: &BlockRoot,
+        pot_verifier: PotVerifier,
+        pot_state: Arc<PotState>,
+        peer_scoring: PS,
+    ) -> (
+        Self,
+        mpsc::Sender<ToGossipMessage>,
+        mpsc::Receiver<GossipProof>,
+    ) {
+        let (to_gossip_sender, to_gossip_receiver) = mpsc::channel(GOSSIP_CHANNEL_CAPACITY);
+        let (from_gossip_sender, from_gossip_receiver) = mpsc::channel(GOSSIP_CHANNEL_CAPACITY);
+
+        let worker = Self {
+            node,
+            topic: pot_gossip_topic(genesis_root),
+            validator: PotGossipValidator::new(pot_verifier, Arc::clone(&pot_state)),
+            pot_state,
+            peer_scoring,
+            to_gossip_receiver,
+            from_gossip_sender,
+            pending_proofs: BTreeMap::new(),
+        };
+
+        (worker, to_gossip_sender, from_gossip_receiver)
+    }
+
+    /// Run gossip worker
+    pub async fn run(mut self) {
+        let mut subscription = match self.node.subscribe(self.topic.clone()).await {
+            Ok(subscription) => subscription.fuse(),
+            Err(error) => {
+                error!(%error, "Failed to subscribe to PoT gossip topic");
+                return;
+            }
+        };
+
+        loop {
+            select! {
+                maybe_gossip_message = subscription.next() => {
+                    if let Some(gossip_message) = maybe_gossip_message {
+                        self.handle_gossip_message(gossip_message).await;
+                    } else {
+                        debug!("PoT gossip subscription ended, exiting");
+                        return;
+                    }
+                }
+                maybe_to_gossip_message = self.to_gossip_receiver.next() => {
+                    if let Some(to_gossip_message) = maybe_to_gossip_message {
+                        self.handle_to_gossip_message(to_gossip_message).await;
+                    } else {
+                        debug!("Outgoing gossip messages stream ended, exiting");
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn handle_to_gossip_message(&mut self, message: ToGossipMessage) {
+        match message {
+            ToGossipMessage::Proof(proof) => {
+                if let Err(error) = self.node.publish(self.topic.clone(), proof.encode()).await {
+                    debug!(%error, slot = %proof.slot, "Failed to publish PoT gossip proof");
+                }
+            }
+            ToGossipMessage::NextSlotInput(next_slot_input) => {
+                // Proofs for slots before the next slot are no longer useful
+                self.pending_proofs = self.pending_proofs.split_off(&next_slot_input.slot);
+
+                let Some(proofs) = self.pending_proofs.remove(&next_slot_input.slot) else {
+                    return;
+                };
+
+                if let Some(proof) = proofs.into_iter().find(|proof| {
+                    proof.seed == next_slot_input.seed
+                        && proof.slot_iterations == next_slot_input.slot_iterations
+                }) {
+                    self.send_to_source(proof);
+                }
+            }
+        }
+    }
+
+    async fn handle_gossip_message(&mut self, message: GossipMessage) {
+        let GossipMessage {
+            message_id,
+            propagation_source,
+            data,
+        } = message;
+
+        let acceptance = match self.validator.validate(propagation_source, &data).await {
+            Ok(Some(proof)) => {
+                self.peer_scoring.on_valid_proof(propagation_source);
+                self.handle_valid_proof(proof);
+
+                MessageAcceptance::Accept
+            }
+            Ok(None) => MessageAcceptance::Ignore,
+            Err(misbehavior) => {
+                debug!(
+                    %propagation_source,
+                    ?misbehavior,
+                    "Peer misbehaved in PoT gossip"
+                );
+                self.peer_scoring
+                    .on_misbehavior(propagation_source, misbehavior);
+
+                if misbehavior.is_fatal() {
+                    if let Err(error) = self.node.ban_peer(propagation_source).await {
+                        warn!(%error, %propagation_source, "Failed to ban peer");
+                    }
+
+                    MessageAcceptance::Reject
+                } else {
+                    MessageAcceptance::Ignore
+                }
+            }
+        };
+
+        if let Err(error) = self
+            .node
+            .report_message_validation_result(message_id, propagation_source, acceptance)
+            .await
+        {
+            warn!(%error, "Failed to report PoT gossip message validation result");
+        }
+    }
+
+    fn handle_valid_proof(&mut self, proof: GossipProof) {
+        let next_slot_input = self.pot_state.next_slot_input();
+
+        if proof.slot == next_slot_input.slot {
+            self.send_to_source(proof);
+        } else if proof.slot > next_slot_input.slot {
+            let proofs = self.pending_proofs.entry(proof.slot).or_default();
+            if proofs.len() < MAX_PENDING_PROOFS_PER_SLOT && !proofs.contains(&proof) {
+                proofs.push(proof);
+            }
+        }
+    }
+
+    fn send_to_source(&mut self, proof: GossipProof) {
+        if let Err(error) = self.from_gossip_sender.try_send(proof) {
+            debug!(
+                %error,
+                slot = %proof.slot,
+                "PoT source is not able to keep-up with gossip proofs"
+            );
+        }
+    }
+}
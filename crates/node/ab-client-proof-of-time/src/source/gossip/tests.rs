@@ -0,0 +1,205 @@
+use crate::PotNextSlotInput;
+use crate::source::gossip::{
+    GossipProof, MAX_PROOFS_PER_PEER_PER_SLOT, MAX_SLOTS_IN_THE_FUTURE, PotGossipMisbehavior,
+    PotGossipValidator,
+};
+use crate::source::mock_timekeeper::mock_checkpoints;
+use crate::source::state::PotState;
+use crate::verifier::PotVerifier;
+use ab_core_primitives::pot::{PotCheckpoints, PotSeed, SlotNumber};
+use ab_networking::libp2p::PeerId;
+use parity_scale_codec::Encode;
+use rclite::Arc;
+use std::num::NonZeroU32;
+
+// Way too many iterations to actually prove in a test
+const SLOT_ITERATIONS: NonZeroU32 = NonZeroU32::new(u32::MAX).expect("Not zero; qed");
+
+fn seed(byte: u8) -> PotSeed {
+    PotSeed::from([byte; _])
+}
+
+/// Validator with the next slot `1` (with seed `1`) and verifier that knows checkpoints for
+/// provided seeds without proving
+fn validator(known_seeds: &[PotSeed]) -> PotGossipValidator {
+    let pot_verifier = PotVerifier::new(seed(1), 100);
+    for &known_seed in known_seeds {
+        pot_verifier.inject_verified_checkpoints(
+            known_seed,
+            SLOT_ITERATIONS,
+            mock_checkpoints(known_seed, SLOT_ITERATIONS),
+        );
+    }
+    let pot_state = Arc::new(PotState::new(
+        PotNextSlotInput {
+            slot: SlotNumber::ONE,
+            slot_iterations: SLOT_ITERATIONS,
+            seed: seed(1),
+        },
+        None,
+        pot_verifier.clone(),
+    ));
+
+    PotGossipValidator::new(pot_verifier, pot_state)
+}
+
+fn proof(slot: SlotNumber, seed: PotSeed) -> GossipProof {
+    GossipProof {
+        slot,
+        seed,
+        slot_iterations: SLOT_ITERATIONS,
+        checkpoints: mock_checkpoints(seed, SLOT_ITERATIONS),
+    }
+}
+
+#[tokio::test]
+async fn valid_proof() {
+    let mut validator = validator(&[seed(1)]);
+    let proof = proof(SlotNumber::ONE, seed(1));
+
+    assert_eq!(
+        validator.validate(PeerId::random(), &proof.encode()).await,
+        Ok(Some(proof))
+    );
+}
+
+#[tokio::test]
+async fn undecodable_proof() {
+    let mut validator = validator(&[seed(1)]);
+    let encoded_proof = proof(SlotNumber::ONE, seed(1)).encode();
+
+    let misbehavior = validator
+        .validate(PeerId::random(), &encoded_proof[..encoded_proof.len() - 1])
+        .await
+        .unwrap_err();
+    assert_eq!(misbehavior, PotGossipMisbehavior::Undecodable);
+    // Peer is banned
+    assert!(misbehavior.is_fatal());
+}
+
+#[tokio::test]
+async fn proof_too_far_in_future() {
+    let future_seed = seed(2);
+    let mut validator = validator(&[future_seed]);
+    let max_future_slot = SlotNumber::ONE + SlotNumber::from(MAX_SLOTS_IN_THE_FUTURE);
+
+    // The furthest acceptable slot
+    let proof = proof(max_future_slot, future_seed);
+    assert_eq!(
+        validator.validate(PeerId::random(), &proof.encode()).await,
+        Ok(Some(proof))
+    );
+
+    let misbehavior = validator
+        .validate(
+            PeerId::random(),
+            &self::proof(max_future_slot + SlotNumber::ONE, future_seed).encode(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(misbehavior, PotGossipMisbehavior::TooFarInFuture);
+    // Honest peer might be on a different fork, it is not banned
+    assert!(!misbehavior.is_fatal());
+}
+
+#[tokio::test]
+async fn stale_and_mismatching_proofs() {
+    let mut validator = validator(&[seed(1)]);
+
+    // Slot before the next slot
+    assert_eq!(
+        validator
+            .validate(PeerId::random(), &proof(SlotNumber::ZERO, seed(1)).encode())
+            .await,
+        Ok(None)
+    );
+
+    // Seed doesn't match the next slot input
+    assert_eq!(
+        validator
+            .validate(PeerId::random(), &proof(SlotNumber::ONE, seed(2)).encode())
+            .await,
+        Ok(None)
+    );
+}
+
+#[tokio::test]
+async fn rate_limit() {
+    let future_seed = seed(2);
+    let mut validator = validator(&[seed(1), future_seed]);
+    let peer_id = PeerId::random();
+    let proof = proof(SlotNumber::ONE, seed(1));
+
+    for _ in 0..MAX_PROOFS_PER_PEER_PER_SLOT {
+        assert_eq!(
+            validator.validate(peer_id, &proof.encode()).await,
+            Ok(Some(proof))
+        );
+    }
+
+    let misbehavior = validator
+        .validate(peer_id, &proof.encode())
+        .await
+        .unwrap_err();
+    assert_eq!(misbehavior, PotGossipMisbehavior::RateLimitExceeded);
+    // Honest peer might forward the same proof received from different peers, it is not banned
+    assert!(!misbehavior.is_fatal());
+
+    // Other peers are not affected
+    assert_eq!(
+        validator.validate(PeerId::random(), &proof.encode()).await,
+        Ok(Some(proof))
+    );
+
+    // Different slot has its own limit
+    let future_proof = self::proof(SlotNumber::from(2), future_seed);
+    assert_eq!(
+        validator.validate(peer_id, &future_proof.encode()).await,
+        Ok(Some(future_proof))
+    );
+}
+
+#[tokio::test]
+async fn rate_limit_alternating_slots() {
+    let future_seed = seed(2);
+    let mut validator = validator(&[seed(1), future_seed]);
+    let peer_id = PeerId::random();
+    let proof = proof(SlotNumber::ONE, seed(1));
+    let future_proof = self::proof(SlotNumber::from(2), future_seed);
+
+    // Alternating between slots doesn't reset the limit of either of them
+    for _ in 0..MAX_PROOFS_PER_PEER_PER_SLOT {
+        assert_eq!(
+            validator.validate(peer_id, &proof.encode()).await,
+            Ok(Some(proof))
+        );
+        assert_eq!(
+            validator.validate(peer_id, &future_proof.encode()).await,
+            Ok(Some(future_proof))
+        );
+    }
+
+    for proof in [proof, future_proof, proof, future_proof] {
+        assert_eq!(
+            validator.validate(peer_id, &proof.encode()).await,
+            Err(PotGossipMisbehavior::RateLimitExceeded)
+        );
+    }
+}
+
+#[tokio::test]
+async fn invalid_proof() {
+    let mut validator = validator(&[seed(1)]);
+    let proof = GossipProof {
+        checkpoints: PotCheckpoints::default(),
+        ..proof(SlotNumber::ONE, seed(1))
+    };
+
+    let misbehavior = validator
+        .validate(PeerId::random(), &proof.encode())
+        .await
+        .unwrap_err();
+    assert_eq!(misbehavior, PotGossipMisbehavior::InvalidProof);
+    // Peer is banned
+    assert!(misbehavior.is_fatal());
+}
@@ -0,0 +1,129 @@
+//! Mock timekeeper for deterministic tests.
+//!
+//! [`MockTimekeeper`] produces [`TimekeeperProof`]s the same way as [`Timekeeper`] does, except
+//! slots are advanced manually with [`MockTimekeeper::advance_by_slot()`] and checkpoints are
+//! derived with a cheap hash function instead of AES iterations (see [`mock_checkpoints()`]).
+//!
+//! Mock checkpoints are injected into [`PotVerifier`] as verified, such that verification of
+//! blocks and gossip proofs succeeds without proving. This means all simulated nodes must share
+//! the same verifier (it is cheap to clone) with cache large enough to contain all slots produced
+//! during the test, otherwise verifier will attempt to prove and verification will fail.
+//!
+//! [`Timekeeper`]: crate::source::timekeeper::Timekeeper
+
+#[cfg(test)]
+mod tests;
+
+use crate::source::state::PotState;
+use crate::source::timekeeper::TimekeeperProof;
+use crate::verifier::PotVerifier;
+use ab_blake3::single_block_hash;
+use ab_core_primitives::pot::{PotCheckpoints, PotOutput, PotSeed, SlotNumber};
+use futures::SinkExt;
+use futures::channel::mpsc;
+use rclite::Arc;
+use std::num::NonZeroU32;
+use tracing::trace;
+
+/// Derive mock checkpoints for provided seed and number of iterations.
+///
+/// Each checkpoint is a hash of the previous checkpoint (seed for the first one) and number of
+/// iterations, which is deterministic and cheap, but obviously doesn't prove passage of time.
+pub fn mock_checkpoints(seed: PotSeed, slot_iterations: NonZeroU32) -> PotCheckpoints {
+    let mut checkpoints = PotCheckpoints::default();
+    let mut bytes_to_hash = [0; PotOutput::SIZE + size_of::<u32>()];
+    bytes_to_hash[..PotOutput::SIZE].copy_from_slice(seed.as_ref());
+    bytes_to_hash[PotOutput::SIZE..].copy_from_slice(&slot_iterations.get().to_le_bytes());
+
+    for checkpoint in checkpoints.iter_mut() {
+        let hash = single_block_hash(&bytes_to_hash)
+            .expect("Less than a single block worth of bytes; qed");
+        checkpoint.copy_from_slice(&hash[..PotOutput::SIZE]);
+        bytes_to_hash[..PotOutput::SIZE].copy_from_slice(checkpoint.as_ref());
+    }
+
+    checkpoints
+}
+
+/// Mock timekeeper source driven by a manual clock
+#[derive(Debug)]
+pub struct MockTimekeeper {
+    state: Arc<PotState>,
+    pot_verifier: PotVerifier,
+    proof_sender: mpsc::Sender<TimekeeperProof>,
+}
+
+impl MockTimekeeper {
+    /// Create a new mock timekeeper source
+    pub fn new(
+        state: Arc<PotState>,
+        pot_verifier: PotVerifier,
+    ) -> (Self, mpsc::Receiver<TimekeeperProof>) {
+        let (proof_sender, proof_receiver) = mpsc::channel(1);
+
+        (
+            Self {
+                state,
+                pot_verifier,
+                proof_sender,
+            },
+            proof_receiver,
+        )
+    }
+
+    /// Produce a proof for the next slot.
+    ///
+    /// Waits for the proof to be accepted by the receiver, returns the slot number of the produced
+    /// proof or an error if receiver returned from constructor was dropped.
+    pub async fn advance_by_slot(&mut self) -> Result<SlotNumber, mpsc::SendError> {
+        let next_slot_input = self.state.next_slot_input();
+
+        trace!(
+            "Mock proving for slot {} with {} iterations",
+            next_slot_input.slot, next_slot_input.slot_iterations
+        );
+        let checkpoints = mock_checkpoints(next_slot_input.seed, next_slot_input.slot_iterations);
+
+        self.pot_verifier.inject_verified_checkpoints(
+            next_slot_input.seed,
+            next_slot_input.slot_iterations,
+            checkpoints,
+        );
+
+        // State might have been changed concurrently (for example, by block import), in which case
+        // it was already extended past this slot using injected checkpoints
+        let _: Result<_, _> = self.state.try_extend(
+            next_slot_input,
+            next_slot_input.slot,
+            checkpoints.output(),
+            None,
+        );
+
+        self.proof_sender
+            .send(TimekeeperProof {
+                slot: next_slot_input.slot,
+                seed: next_slot_input.seed,
+                slot_iterations: next_slot_input.slot_iterations,
+                checkpoints,
+            })
+            .await?;
+
+        Ok(next_slot_input.slot)
+    }
+
+    /// Produce proofs for the next `slots` slots, see [`Self::advance_by_slot()`] for details.
+    ///
+    /// Returns the slot number of the last produced proof, `None` if `slots` is zero.
+    pub async fn advance_by_slots(
+        &mut self,
+        slots: u64,
+    ) -> Result<Option<SlotNumber>, mpsc::SendError> {
+        let mut last_slot = None;
+
+        for _ in 0..slots {
+            last_slot.replace(self.advance_by_slot().await?);
+        }
+
+        Ok(last_slot)
+    }
+}
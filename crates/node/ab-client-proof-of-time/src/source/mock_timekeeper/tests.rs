@@ -0,0 +1,57 @@
+use crate::PotNextSlotInput;
+use crate::source::mock_timekeeper::{MockTimekeeper, mock_checkpoints};
+use crate::source::state::PotState;
+use crate::verifier::PotVerifier;
+use ab_core_primitives::pot::{PotSeed, SlotNumber};
+use futures::StreamExt;
+use futures::executor::block_on;
+use rclite::Arc;
+use std::num::NonZeroU32;
+
+#[test]
+fn advance_slots() {
+    let genesis_seed = PotSeed::from([1; _]);
+    // Way too many iterations to actually prove in a test
+    let slot_iterations = NonZeroU32::new(u32::MAX).unwrap();
+    let pot_verifier = PotVerifier::new(genesis_seed, 100);
+    let genesis_input = PotNextSlotInput {
+        slot: SlotNumber::ONE,
+        slot_iterations,
+        seed: genesis_seed,
+    };
+    let state = Arc::new(PotState::new(genesis_input, None, pot_verifier.clone()));
+
+    let (mut timekeeper, mut proof_receiver) =
+        MockTimekeeper::new(Arc::clone(&state), pot_verifier.clone());
+
+    assert_eq!(block_on(timekeeper.advance_by_slot()), Ok(SlotNumber::ONE));
+    let proof_1 = block_on(proof_receiver.next()).unwrap();
+    assert_eq!(proof_1.slot, SlotNumber::ONE);
+    assert_eq!(proof_1.seed, genesis_seed);
+    assert_eq!(
+        proof_1.checkpoints,
+        mock_checkpoints(genesis_seed, slot_iterations)
+    );
+    assert_eq!(state.next_slot_input().slot, SlotNumber::from(2));
+
+    assert_eq!(
+        block_on(timekeeper.advance_by_slots(1)),
+        Ok(Some(SlotNumber::from(2)))
+    );
+    let proof_2 = block_on(proof_receiver.next()).unwrap();
+    assert_eq!(proof_2.slot, SlotNumber::from(2));
+    assert_eq!(proof_2.seed, proof_1.checkpoints.output().seed());
+
+    // Produced proofs are valid without proving
+    assert!(pot_verifier.try_is_output_valid(
+        genesis_input,
+        SlotNumber::from(2),
+        proof_2.checkpoints.output(),
+        None
+    ));
+
+    assert_eq!(block_on(timekeeper.advance_by_slots(0)), Ok(None));
+
+    drop(proof_receiver);
+    assert!(block_on(timekeeper.advance_by_slot()).is_err());
+}
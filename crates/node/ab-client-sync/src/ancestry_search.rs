@@ -0,0 +1,389 @@
+//! Search for the common ancestor with a remote peer.
+//!
+//! When a peer is on a different fork, blocks need to be requested starting with the first block
+//! after the common ancestor. Instead of walking backwards one block at a time, block roots at
+//! exponentially spaced block numbers are requested first (`best`, `best - 1`, `best - 2`,
+//! `best - 4`, ..., genesis), which bounds the range that contains the common ancestor, and then a
+//! binary search within that range finds the exact block.
+//!
+//! This relies on the fact that block roots commit to the whole ancestry of the block, so once
+//! roots at some block number match, roots at all lower block numbers match as well.
+
+#[cfg(test)]
+mod tests;
+
+use ab_client_api::ChainInfo;
+use ab_core_primitives::block::owned::GenericOwnedBlock;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_networking::libp2p::PeerId;
+use ab_networking::protocols::request_response::handlers::generic_request_handler::{
+    GenericRequest, GenericRequestHandler,
+};
+use ab_networking::protocols::request_response::request_response_factory::RequestHandler;
+use ab_networking::{Node, SendRequestError};
+use parity_scale_codec::{Decode, Encode};
+use std::ops::RangeInclusive;
+use tracing::debug;
+
+/// Max number of block numbers in a single [`BlockRootsRequest`]
+pub const MAX_BLOCK_NUMBERS_PER_REQUEST: usize = 128;
+
+/// Block roots by block numbers protocol request
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode)]
+pub struct BlockRootsRequest {
+    /// Block numbers to get roots of blocks on the best chain for
+    pub block_numbers: Vec<BlockNumber>,
+}
+
+impl GenericRequest for BlockRootsRequest {
+    const PROTOCOL_NAME: &'static str = "/subspace/block-roots-by-numbers/0.1.0";
+    const LOG_TARGET: &'static str = "block-roots-by-numbers-request-response-handler";
+    type Response = BlockRootsResponse;
+}
+
+/// Block roots by block numbers protocol response
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode)]
+pub struct BlockRootsResponse {
+    /// Roots of blocks on the best chain in the same order as block numbers in the request,
+    /// `None` for unknown blocks
+    pub block_roots: Vec<Option<BlockRoot>>,
+}
+
+/// Create a new `block-roots-by-numbers` request handler that answers requests with roots of
+/// blocks on the best chain
+pub fn block_roots_request_handler<Block, CI>(chain_info: CI) -> Box<dyn RequestHandler>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfo<Block>,
+{
+    GenericRequestHandler::<BlockRootsRequest>::create(move |peer_id, request| {
+        let response = if request.block_numbers.len() > MAX_BLOCK_NUMBERS_PER_REQUEST {
+            debug!(
+                %peer_id,
+                block_numbers = %request.block_numbers.len(),
+                "Too many block numbers in block roots request"
+            );
+            None
+        } else {
+            let best_root = chain_info.best_root();
+
+            Some(BlockRootsResponse {
+                block_roots: request
+                    .block_numbers
+                    .iter()
+                    .map(|&block_number| {
+                        chain_info
+                            .ancestor_header(block_number, &best_root)
+                            .map(|header| *header.header().root())
+                    })
+                    .collect(),
+            })
+        };
+
+        async move { response }
+    })
+}
+
+/// Common ancestor of local and remote chains
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CommonAncestor {
+    /// Block number
+    pub number: BlockNumber,
+    /// Block root
+    pub root: BlockRoot,
+}
+
+impl CommonAncestor {
+    /// Range of blocks that need to be requested from the remote peer with specified best block
+    /// number in order to import its best chain, `None` if there is nothing to request
+    pub fn blocks_to_request(
+        &self,
+        remote_best_number: BlockNumber,
+    ) -> Option<RangeInclusive<BlockNumber>> {
+        let first_block_number = self.number.checked_add(BlockNumber::ONE)?;
+
+        (first_block_number <= remote_best_number)
+            .then_some(first_block_number..=remote_best_number)
+    }
+}
+
+/// Ancestry search error
+#[derive(Debug, thiserror::Error)]
+pub enum AncestrySearchError {
+    /// Number of block roots in the response doesn't match the request
+    #[error("Number of block roots in the response {actual} doesn't match the request {expected}")]
+    InvalidResponseLength {
+        /// Expected number of block roots
+        expected: usize,
+        /// Actual number of block roots
+        actual: usize,
+    },
+    /// Remote peer doesn't know a block it claimed to have
+    #[error("Remote peer doesn't know block {block_number} it claimed to have")]
+    MissingBlockRoot {
+        /// Block number
+        block_number: BlockNumber,
+    },
+    /// Remote peer returned block roots that contradict each other
+    #[error("Remote peer returned block roots that contradict each other at {block_number}")]
+    InconsistentResponse {
+        /// Block number of mismatching block below a matching block
+        block_number: BlockNumber,
+    },
+    /// No common ancestor, the remote peer is on a different chain
+    #[error("No common ancestor, the remote peer is on a different chain")]
+    NoCommonAncestor,
+    /// Search is already finished
+    #[error("Search is already finished")]
+    AlreadyFinished,
+    /// Failed to send request
+    #[error("Failed to send request: {error}")]
+    SendRequest {
+        /// Low-level error
+        #[from]
+        error: SendRequestError,
+    },
+}
+
+#[derive(Debug, Copy, Clone)]
+enum SearchState {
+    /// Block roots at exponentially spaced block numbers were not requested yet
+    Exponential { top: BlockNumber },
+    /// Common ancestor is at or above `low` and below `high`
+    Binary {
+        low: BlockNumber,
+        low_root: BlockRoot,
+        high: BlockNumber,
+    },
+    /// Common ancestor was found
+    Finished(CommonAncestor),
+}
+
+/// Progress of the ancestry search after processing of the response
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AncestrySearchProgress {
+    /// More block roots need to be requested
+    Request(BlockRootsRequest),
+    /// Common ancestor was found
+    Finished(CommonAncestor),
+}
+
+/// State machine of the search for the common ancestor with a remote peer.
+///
+/// Call [`Self::next_request()`] to get the request that needs to be sent to the remote peer and
+/// [`Self::on_response()`] with the response to make progress, until the common ancestor is found.
+#[derive(Debug)]
+pub struct AncestrySearch {
+    remote_best_number: BlockNumber,
+    state: SearchState,
+}
+
+impl AncestrySearch {
+    /// Create a new instance for the local and remote best block numbers
+    pub fn new(local_best_number: BlockNumber, remote_best_number: BlockNumber) -> Self {
+        Self {
+            remote_best_number,
+            state: SearchState::Exponential {
+                top: local_best_number.min(remote_best_number),
+            },
+        }
+    }
+
+    /// Request that needs to be sent to the remote peer next, `None` if search is finished
+    pub fn next_request(&self) -> Option<BlockRootsRequest> {
+        let block_numbers = match self.state {
+            SearchState::Exponential { top } => exponential_block_numbers(top),
+            SearchState::Binary { low, high, .. } => {
+                vec![BlockNumber::from(u64::midpoint(
+                    u64::from(low),
+                    u64::from(high),
+                ))]
+            }
+            SearchState::Finished(_) => {
+                return None;
+            }
+        };
+
+        Some(BlockRootsRequest { block_numbers })
+    }
+
+    /// Process the response to the request returned by [`Self::next_request()`].
+    ///
+    /// `local_block_root` returns the root of the block on the local best chain, `None` if the
+    /// block is not known.
+    pub fn on_response<LBR>(
+        &mut self,
+        request: &BlockRootsRequest,
+        response: &BlockRootsResponse,
+        local_block_root: LBR,
+    ) -> Result<AncestrySearchProgress, AncestrySearchError>
+    where
+        LBR: Fn(BlockNumber) -> Option<BlockRoot>,
+    {
+        if request.block_numbers.len() != response.block_roots.len() {
+            return Err(AncestrySearchError::InvalidResponseLength {
+                expected: request.block_numbers.len(),
+                actual: response.block_roots.len(),
+            });
+        }
+
+        // Block numbers are in descending order in all requests
+        let mut highest_mismatch = None;
+        let mut highest_match = None;
+        for (&block_number, &remote_block_root) in
+            request.block_numbers.iter().zip(&response.block_roots)
+        {
+            let Some(remote_block_root) = remote_block_root else {
+                return Err(AncestrySearchError::MissingBlockRoot { block_number });
+            };
+            if block_number > self.remote_best_number {
+                return Err(AncestrySearchError::MissingBlockRoot { block_number });
+            }
+
+            if local_block_root(block_number) == Some(remote_block_root) {
+                if highest_match.is_none() {
+                    highest_match.replace((block_number, remote_block_root));
+                }
+            } else if highest_match.is_some() {
+                return Err(AncestrySearchError::InconsistentResponse { block_number });
+            } else {
+                highest_mismatch.replace(block_number);
+            }
+        }
+
+        self.state = match self.state {
+            SearchState::Exponential { .. } => {
+                let Some((low, low_root)) = highest_match else {
+                    return Err(AncestrySearchError::NoCommonAncestor);
+                };
+
+                match highest_mismatch {
+                    Some(high) => SearchState::Binary {
+                        low,
+                        low_root,
+                        high,
+                    },
+                    None => SearchState::Finished(CommonAncestor {
+                        number: low,
+                        root: low_root,
+                    }),
+                }
+            }
+            SearchState::Binary {
+                mut low,
+                mut low_root,
+                mut high,
+            } => {
+                if let Some((block_number, block_root)) = highest_match {
+                    low = block_number;
+                    low_root = block_root;
+                } else if let Some(block_number) = highest_mismatch {
+                    high = block_number;
+                }
+
+                SearchState::Binary {
+                    low,
+                    low_root,
+                    high,
+                }
+            }
+            SearchState::Finished(_) => {
+                return Err(AncestrySearchError::AlreadyFinished);
+            }
+        };
+
+        if let SearchState::Binary {
+            low,
+            low_root,
+            high,
+        } = self.state
+            && high - low <= BlockNumber::ONE
+        {
+            self.state = SearchState::Finished(CommonAncestor {
+                number: low,
+                root: low_root,
+            });
+        }
+
+        Ok(match self.state {
+            SearchState::Finished(common_ancestor) => {
+                AncestrySearchProgress::Finished(common_ancestor)
+            }
+            SearchState::Exponential { .. } | SearchState::Binary { .. } => {
+                AncestrySearchProgress::Request(
+                    self.next_request()
+                        .expect("Search is not finished, hence there is a request; qed"),
+                )
+            }
+        })
+    }
+}
+
+/// Block numbers `top`, `top - 1`, `top - 2`, `top - 4`, ..., genesis in descending order
+fn exponential_block_numbers(top: BlockNumber) -> Vec<BlockNumber> {
+    let mut block_numbers = vec![top];
+    let mut maybe_step = Some(1_u64);
+
+    while let Some(step) = maybe_step
+        && let Some(block_number) = u64::from(top).checked_sub(step)
+        && block_number > 0
+    {
+        block_numbers.push(BlockNumber::from(block_number));
+        maybe_step = step.checked_mul(2);
+    }
+
+    if top > BlockNumber::ZERO {
+        block_numbers.push(BlockNumber::ZERO);
+    }
+
+    block_numbers
+}
+
+/// Find the common ancestor with a remote peer with specified best block number, using block
+/// roots on the local best chain
+pub async fn find_common_ancestor<Block, CI>(
+    node: &Node,
+    peer_id: PeerId,
+    chain_info: &CI,
+    remote_best_number: BlockNumber,
+) -> Result<CommonAncestor, AncestrySearchError>
+where
+    Block: GenericOwnedBlock,
+    CI: ChainInfo<Block>,
+{
+    let best_header = chain_info.best_header();
+    let best_header = best_header.header();
+    let best_root = *best_header.root();
+    let local_block_root = |block_number| {
+        chain_info
+            .ancestor_header(block_number, &best_root)
+            .map(|header| *header.header().root())
+    };
+
+    let mut search = AncestrySearch::new(best_header.prefix.number, remote_best_number);
+    let mut request = search
+        .next_request()
+        .expect("Search was just created and is not finished; qed");
+
+    loop {
+        let response = node
+            .send_generic_request(peer_id, Vec::new(), request.clone())
+            .await?;
+
+        match search.on_response(&request, &response, &local_block_root)? {
+            AncestrySearchProgress::Request(next_request) => {
+                request = next_request;
+            }
+            AncestrySearchProgress::Finished(common_ancestor) => {
+                debug!(
+                    %peer_id,
+                    number = %common_ancestor.number,
+                    root = %common_ancestor.root,
+                    "Found common ancestor"
+                );
+
+                return Ok(common_ancestor);
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::ancestry_search::{
+    AncestrySearch, AncestrySearchError, AncestrySearchProgress, BlockRootsResponse, CommonAncestor,
+};
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
+use ab_core_primitives::hashes::Blake3Hash;
+
+/// Chain that shares blocks up to `fork_point` with chains of other forks
+#[derive(Debug, Copy, Clone)]
+struct TestChain {
+    fork_id: u8,
+    fork_point: BlockNumber,
+    best_number: BlockNumber,
+}
+
+impl TestChain {
+    fn block_root(&self, block_number: BlockNumber) -> Option<BlockRoot> {
+        if block_number > self.best_number {
+            return None;
+        }
+
+        let mut hash = [0; Blake3Hash::SIZE];
+        hash[..size_of::<u64>()].copy_from_slice(&u64::from(block_number).to_le_bytes());
+        if block_number > self.fork_point {
+            hash[size_of::<u64>()] = self.fork_id;
+        }
+
+        Some(BlockRoot::new(Blake3Hash::new(hash)))
+    }
+}
+
+/// Run search to completion, returns common ancestor and the number of requests made
+fn search(
+    local: TestChain,
+    remote: TestChain,
+) -> Result<(CommonAncestor, usize), AncestrySearchError> {
+    let mut search = AncestrySearch::new(local.best_number, remote.best_number);
+    let mut request = search.next_request().unwrap();
+    let mut num_requests = 1;
+
+    loop {
+        let response = BlockRootsResponse {
+            block_roots: request
+                .block_numbers
+                .iter()
+                .map(|&block_number| remote.block_root(block_number))
+                .collect(),
+        };
+
+        match search.on_response(&request, &response, |block_number| {
+            local.block_root(block_number)
+        })? {
+            AncestrySearchProgress::Request(next_request) => {
+                request = next_request;
+                num_requests += 1;
+            }
+            AncestrySearchProgress::Finished(common_ancestor) => {
+                assert!(search.next_request().is_none());
+                return Ok((common_ancestor, num_requests));
+            }
+        }
+    }
+}
+
+#[test]
+fn common_ancestor() {
+    for (local_best, remote_best, fork_point) in [
+        (0, 0, 0),
+        (10, 10, 10),
+        (10, 20, 10),
+        (20, 10, 10),
+        (10, 10, 9),
+        (10, 10, 0),
+        (1_000, 1_200, 537),
+        (1_200, 1_000, 999),
+        (1_000_000, 1_000_050, 123_456),
+    ] {
+        let local = TestChain {
+            fork_id: 1,
+            fork_point: BlockNumber::from(fork_point),
+            best_number: BlockNumber::from(local_best),
+        };
+        let remote = TestChain {
+            fork_id: 2,
+            fork_point: BlockNumber::from(fork_point),
+            best_number: BlockNumber::from(remote_best),
+        };
+
+        let (common_ancestor, num_requests) = search(local, remote).unwrap();
+        assert_eq!(
+            common_ancestor,
+            CommonAncestor {
+                number: BlockNumber::from(fork_point),
+                root: local.block_root(BlockNumber::from(fork_point)).unwrap(),
+            },
+            "local_best={local_best}, remote_best={remote_best}, fork_point={fork_point}"
+        );
+        // Exponential request followed by binary search
+        assert!(
+            num_requests <= 1 + (local_best.max(1).ilog2() as usize + 1),
+            "num_requests={num_requests}, local_best={local_best}"
+        );
+    }
+}
+
+#[test]
+fn blocks_to_request() {
+    let common_ancestor = CommonAncestor {
+        number: BlockNumber::from(10),
+        root: BlockRoot::default(),
+    };
+
+    assert_eq!(
+        common_ancestor.blocks_to_request(BlockNumber::from(15)),
+        Some(BlockNumber::from(11)..=BlockNumber::from(15))
+    );
+    assert_eq!(
+        common_ancestor.blocks_to_request(BlockNumber::from(10)),
+        None
+    );
+}
+
+#[test]
+fn invalid_responses() {
+    let local = TestChain {
+        fork_id: 1,
+        fork_point: BlockNumber::from(5),
+        best_number: BlockNumber::from(100),
+    };
+    let local_block_root = |block_number| local.block_root(block_number);
+
+    // Different genesis
+    let mut search = AncestrySearch::new(local.best_number, local.best_number);
+    let request = search.next_request().unwrap();
+    let response = BlockRootsResponse {
+        block_roots: vec![Some(BlockRoot::default()); request.block_numbers.len()],
+    };
+    assert!(matches!(
+        search.on_response(&request, &response, local_block_root),
+        Err(AncestrySearchError::NoCommonAncestor)
+    ));
+
+    // Wrong number of block roots
+    let mut search = AncestrySearch::new(local.best_number, local.best_number);
+    let request = search.next_request().unwrap();
+    let response = BlockRootsResponse {
+        block_roots: vec![None],
+    };
+    assert!(matches!(
+        search.on_response(&request, &response, local_block_root),
+        Err(AncestrySearchError::InvalidResponseLength { .. })
+    ));
+
+    // Missing block that remote peer claimed to have
+    let mut search = AncestrySearch::new(local.best_number, local.best_number);
+    let request = search.next_request().unwrap();
+    let mut response = BlockRootsResponse {
+        block_roots: request
+            .block_numbers
+            .iter()
+            .map(|&block_number| local.block_root(block_number))
+            .collect(),
+    };
+    response.block_roots[0] = None;
+    assert!(matches!(
+        search.on_response(&request, &response, local_block_root),
+        Err(AncestrySearchError::MissingBlockRoot { .. })
+    ));
+
+    // Mismatch below a match
+    let mut search = AncestrySearch::new(local.best_number, local.best_number);
+    let request = search.next_request().unwrap();
+    let mut response = BlockRootsResponse {
+        block_roots: request
+            .block_numbers
+            .iter()
+            .map(|&block_number| local.block_root(block_number))
+            .collect(),
+    };
+    *response.block_roots.last_mut().unwrap() = Some(BlockRoot::default());
+    assert!(matches!(
+        search.on_response(&request, &response, local_block_root),
+        Err(AncestrySearchError::InconsistentResponse { .. })
+    ));
+}
@@ -0,0 +1,3 @@
+//! Client-side chain synchronization with other peers on the network
+
+pub mod ancestry_search;
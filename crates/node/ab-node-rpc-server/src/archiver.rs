@@ -0,0 +1,49 @@
+//! Archiver management.
+//!
+//! Methods are only exposed under `unsafe` namespace and only when explicitly enabled, since they
+//! are meant for operators that know what they are doing.
+
+use crate::Error;
+use ab_client_archiving::task::ArchiverControlMessage;
+use ab_core_primitives::block::BlockNumber;
+use futures::SinkExt;
+use futures::channel::{mpsc, oneshot};
+use jsonrpsee::core::async_trait;
+use jsonrpsee::proc_macros::rpc;
+use tracing::info;
+
+/// Provides unsafe rpc methods for managing the archiver
+#[rpc(server, namespace = "unsafe")]
+pub trait ArchiverUnsafeRpcApi {
+    /// Re-initialize archiver from segment headers and blocks in the database the same way as it is
+    /// done on startup, for example after manual changes to the database or special sync.
+    ///
+    /// Re-initialization happens between blocks, returns the best archived block number once done.
+    #[method(name = "reinitializeArchiver")]
+    async fn reinitialize_archiver(&self) -> Result<BlockNumber, Error>;
+}
+
+/// Implements [`ArchiverUnsafeRpcApiServer`] trait
+#[derive(Debug, Clone)]
+pub(crate) struct ArchiverRpc {
+    pub(crate) archiver_control_sender: mpsc::Sender<ArchiverControlMessage>,
+}
+
+#[async_trait]
+impl ArchiverUnsafeRpcApiServer for ArchiverRpc {
+    async fn reinitialize_archiver(&self) -> Result<BlockNumber, Error> {
+        let (result_sender, result_receiver) = oneshot::channel();
+
+        info!("Archiver re-initialization requested over RPC");
+
+        self.archiver_control_sender
+            .clone()
+            .send(ArchiverControlMessage::Reinitialize { result_sender })
+            .await
+            .map_err(|_error| Error::ArchiverReinitializationFailed)?;
+
+        result_receiver
+            .await
+            .map_err(|_canceled| Error::ArchiverReinitializationFailed)
+    }
+}
@@ -0,0 +1,68 @@
+//! Discovery of contract metadata.
+//!
+//! Contract metadata is stored in the metadata registry system contract during deployment, which
+//! allows wallets and other tooling to construct method calls without out-of-band ABI information.
+
+use crate::Error;
+use ab_client_api::BeaconChainInfo;
+use ab_client_consensus_common::state_cache::StateCache;
+use ab_core_primitives::address::Address;
+use ab_executor_slots::SlotKey;
+use ab_farmer_rpc_primitives::{BlockInfo, ContractMetadataInfo};
+use jsonrpsee::proc_macros::rpc;
+use std::sync::Arc;
+
+/// Provides rpc methods for discovering contract metadata
+#[rpc(server)]
+pub trait ContractMetadataRpcApi {
+    /// Metadata of the contract with specified address (formatted with [`Address::format()`]) as of
+    /// the best block.
+    ///
+    /// Returns `None` if the contract doesn't exist or doesn't have metadata stored.
+    #[method(name = "getContractMetadata")]
+    fn get_contract_metadata(&self, address: String)
+    -> Result<Option<ContractMetadataInfo>, Error>;
+}
+
+/// Implements [`ContractMetadataRpcApiServer`] trait
+#[derive(Debug, Clone)]
+pub(crate) struct ContractMetadataRpc<BCI> {
+    pub(crate) beacon_chain_info: BCI,
+    pub(crate) state_cache: Arc<StateCache>,
+}
+
+impl<BCI> ContractMetadataRpcApiServer for ContractMetadataRpc<BCI>
+where
+    BCI: BeaconChainInfo,
+{
+    fn get_contract_metadata(
+        &self,
+        address: String,
+    ) -> Result<Option<ContractMetadataInfo>, Error> {
+        let (_hrp, address) = Address::parse(&address).ok_or(Error::InvalidAddress)?;
+
+        let (best_header, best_block_details) = self.beacon_chain_info.best_header_with_details();
+        let best_header = best_header.header();
+        let best_root = *best_header.root();
+
+        let slot_key = SlotKey {
+            owner: address,
+            contract: Address::SYSTEM_METADATA,
+        };
+        let maybe_metadata = self.state_cache.get_or_read(&best_root, slot_key, || {
+            best_block_details
+                .system_contract_states
+                .iter()
+                .find(|state| state.owner == address && state.contract == Address::SYSTEM_METADATA)
+                .map(|state| state.contents.clone())
+        });
+
+        Ok(maybe_metadata.map(|metadata| ContractMetadataInfo {
+            block: BlockInfo {
+                root: best_root,
+                number: best_header.prefix.number,
+            },
+            metadata: metadata.as_slice().to_vec(),
+        }))
+    }
+}
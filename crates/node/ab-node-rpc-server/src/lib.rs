@@ -1,22 +1,57 @@
 //! RPC API for the farmer
 
+mod archiver;
+mod contract_metadata;
+mod sector_expiration;
+mod shard_membership;
+mod status_server;
+mod super_segment_header_feed;
+#[cfg(test)]
+mod tests;
+mod transaction_pool;
+
+use crate::archiver::ArchiverRpc;
+pub use crate::archiver::ArchiverUnsafeRpcApiServer;
+use crate::contract_metadata::ContractMetadataRpc;
+pub use crate::contract_metadata::ContractMetadataRpcApiServer;
+use crate::sector_expiration::{
+    SectorExpirationSubscription, current_history_size, sector_expirations,
+};
+use crate::shard_membership::{
+    ShardCommitmentsRoots, ShardMembershipAssignments, ShardMembershipEra,
+};
+pub use crate::status_server::{
+    NodeStatusDocument, ResourceUsageInfo, StatusServer, StatusServerConfig,
+};
+use crate::status_server::{archiver_progress_info, segment_stats_info};
+use crate::super_segment_header_feed::SuperSegmentHeaderFeedSubscription;
+use crate::transaction_pool::TransactionPoolRpc;
+pub use crate::transaction_pool::{TransactionPoolRpcApiServer, TransactionPoolUnsafeRpcApiServer};
 use ab_archiving::archiver::NewArchivedSegment;
-use ab_client_api::{BeaconChainInfo, ChainSyncStatus};
+use ab_archiving::objects::ObjectMappingPolicy;
+use ab_client_api::{BeaconChainInfo, BlockId, ChainSyncStatus, resolve_block_id};
 use ab_client_archiving::recreate::{
     RecreateSegmentError, RecreateSegmentSuperSegmentDetails, recreate_genesis_segment,
     recreate_segment,
 };
+use ab_client_archiving::task::{ArchiverControlMessage, ArchiverProgress, SegmentStats};
 use ab_client_block_authoring::slot_worker::{
-    BlockSealNotification, NewSlotInfo, NewSlotNotification,
+    BlockSealNotification, NewSlotInfo, NewSlotNotification, NewSlotTopic, SealRequestTopic,
 };
+use ab_client_block_import::beacon_chain::NewSuperSegmentTopic;
 use ab_client_consensus_common::ConsensusConstants;
+use ab_client_consensus_common::consensus_constants::ConsensusConstantsSchedule;
+use ab_client_consensus_common::state_cache::StateCache;
+use ab_client_notifications::{BufferingPolicy, NotificationBus, Subscription};
+use ab_core_primitives::block::BlockNumber;
 use ab_core_primitives::block::header::OwnedBlockHeaderSeal;
 use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pieces::{Piece, PieceIndex};
 use ab_core_primitives::pot::SlotNumber;
+use ab_core_primitives::sectors::SectorExpiration;
 use ab_core_primitives::segments::{
-    HistorySize, LocalSegmentIndex, SegmentIndex, SuperSegment, SuperSegmentHeader,
+    HistorySize, LocalSegmentIndex, SegmentIndex, SegmentRoot, SuperSegment, SuperSegmentHeader,
     SuperSegmentIndex, SuperSegmentRoot,
 };
 use ab_core_primitives::shard::ShardIndex;
@@ -24,33 +59,84 @@ use ab_core_primitives::solutions::Solution;
 use ab_erasure_coding::ErasureCoding;
 use ab_farmer_components::FarmerProtocolInfo;
 use ab_farmer_rpc_primitives::{
-    BlockSealInfo, BlockSealResponse, FarmerAppInfo, FarmerShardMembershipInfo,
-    MAX_SUPER_SEGMENT_HEADERS_PER_REQUEST, SHARD_MEMBERSHIP_EXPIRATION, SlotInfo, SolutionResponse,
+    BlockInfo, BlockSealInfo, BlockSealResponse, FarmerAppInfo, FarmerShardAssignment,
+    FarmerShardMembershipInfo, MAX_PAGE_ITEMS, MAX_PAGE_SIZE, MAX_SECTOR_EXPIRATIONS_PER_REQUEST,
+    MAX_SHARD_ASSIGNMENTS_PER_REQUEST, MAX_SUPER_SEGMENT_HEADERS_PER_REQUEST, NodeShardInfo,
+    NodeStatusInfo, PageBuilder, RpcErrorCode, SHARD_MEMBERSHIP_EXPIRATION, SectorExpirationInfo,
+    SectorExpirationRequest, SegmentHeadersRange, SegmentInclusionProof, SegmentStatsInfo,
+    ShardTopology, SlotInfo, SlotInfoFilter, SolutionCheck, SolutionResponse,
+    SolutionVerificationInfo, SuperSegmentHeaderFeedItem,
 };
 use ab_networking::libp2p::Multiaddr;
+use ab_node_rpc_subscriptions::{
+    Delivery, LagPolicy, Subscriber, SubscriptionFanout, SubscriptionFanoutMetrics,
+};
+use ab_solution_verification::{
+    SolutionChallenges, SolutionPotVerifier, SolutionVerifyError, SolutionVerifyFullParams,
+    SolutionVerifyPieceParams, SolutionVerifyStatelessParams, verify_full, verify_stateless,
+};
+use ab_transaction_pool::TransactionPool;
 use async_lock::Mutex as AsyncMutex;
-use futures::channel::{mpsc, oneshot};
+use futures::channel::mpsc;
 use futures::{FutureExt, SinkExt, StreamExt, select};
 use jsonrpsee::core::{SubscriptionResult, async_trait};
 use jsonrpsee::proc_macros::rpc;
-use jsonrpsee::server::{Server, ServerConfig};
+use jsonrpsee::server::{BatchRequestConfig, Server, ServerConfig};
 use jsonrpsee::tokio::task::{JoinError, spawn_blocking};
 use jsonrpsee::tokio::time::MissedTickBehavior;
 use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
-use jsonrpsee::{
-    ConnectionId, Extensions, PendingSubscriptionSink, SubscriptionSink, TrySendError,
-};
+use jsonrpsee::{ConnectionId, Extensions, PendingSubscriptionSink};
 use parking_lot::Mutex;
 use schnellru::{ByLength, LruMap};
 use std::collections::{HashMap, VecDeque};
 use std::io;
+use std::marker::PhantomData;
 use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tracing::{error, info, warn};
-
+use tokio::sync::watch;
+use tracing::{debug, error, info, warn};
+
+/// Max number of requests in a single batch
+const MAX_BATCH_REQUESTS: u32 = 64;
+/// Max size of a request in bytes, applies to batch requests as a whole
+const MAX_REQUEST_BODY_SIZE: u32 = 10 * 1024 * 1024;
+/// Max size of a response in bytes, applies to batch responses as a whole.
+///
+/// Pieces are the largest responses and farmers retrieve them in batches, so this is derived from a
+/// full batch of [`MAX_BATCH_REQUESTS`] pieces (~128 MiB). Pieces are hex-encoded in JSON, which
+/// doubles their size, and each response has a small JSON-RPC envelope around it. jsonrpsee's
+/// default of 10 MiB would only fit 4 pieces per batch.
+const MAX_RESPONSE_BODY_SIZE: u32 =
+    MAX_BATCH_REQUESTS * (Piece::SIZE as u32 * 2 + PIECE_RESPONSE_ENVELOPE_SIZE);
+/// Allowance for the JSON-RPC envelope around a single piece in a batch response
+const PIECE_RESPONSE_ENVELOPE_SIZE: u32 = 1024;
 const CACHED_SUPER_SEGMENTS_CAPACITY: usize = 5;
+/// Default max number of active subscriptions per connection
+const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: u32 = 64;
+/// Default max number of pending notifications (and responses) per connection
+const DEFAULT_MAX_PENDING_NOTIFICATIONS_PER_CONNECTION: u32 = 1024;
 const CACHED_ARCHIVED_SEGMENT_TIMEOUT: Duration = Duration::from_mins(1);
+/// Number of recent slots whose information is retained for solution verification dry-runs
+const CACHED_SLOT_INFOS_CAPACITY: u32 = 256;
+/// Buffered new slot and block sealing notifications, the oldest are dropped if the RPC server is
+/// too slow
+const SLOT_NOTIFICATIONS_BUFFER: NonZeroUsize = NonZeroUsize::new(2).expect("Not zero; qed");
+/// Buffered new super segment notifications, block import waits if farmers are too slow
+const SUPER_SEGMENT_NOTIFICATIONS_BUFFER: NonZeroUsize =
+    NonZeroUsize::new(1).expect("Not zero; qed");
+/// Slot info and block sealing notifications are only useful while they are fresh, undelivered
+/// notifications are coalesced into the latest one if farmers are too slow
+const SLOT_SUBSCRIPTIONS_LAG_POLICY: LagPolicy = LagPolicy::KeepLatest;
+/// New super segment headers are rare and important, RPC server applies backpressure and waits for
+/// a while if farmers are too slow before dropping their subscriptions
+const NEW_SUPER_SEGMENT_HEADER_LAG_POLICY: LagPolicy = LagPolicy::BlockWithTimeout {
+    timeout: Duration::from_secs(5),
+};
+/// Super segment header feed and sector expirations deliver what was not delivered due to a slow
+/// receiver with the next notification
+const CATCH_UP_SUBSCRIPTIONS_LAG_POLICY: LagPolicy = LagPolicy::DropNewest;
 
 /// Top-level error type for the RPC handler.
 #[derive(Debug, thiserror::Error)]
@@ -61,6 +147,19 @@ pub enum Error {
         /// Slot number
         slot: SlotNumber,
     },
+    /// Solution was submitted too late
+    #[error(
+        "Solution for slot {slot} was submitted {slots_late} slots too late, current slot is \
+        {current_slot}"
+    )]
+    SolutionTooLate {
+        /// Slot number of the solution
+        slot: SlotNumber,
+        /// Current slot number
+        current_slot: SlotNumber,
+        /// Number of slots after the deadline when solution was submitted
+        slots_late: u64,
+    },
     /// Super segment headers length exceeded the limit
     #[error(
         "Super segment headers length exceeded the limit: \
@@ -76,18 +175,87 @@ pub enum Error {
     /// Blocking task join error
     #[error("Blocking task join error: {0}")]
     BlockingTaskJoinError(#[from] JoinError),
+    /// Shard assignments length exceeded the limit
+    #[error(
+        "Shard assignments length exceeded the limit: {actual}/{MAX_SHARD_ASSIGNMENTS_PER_REQUEST}"
+    )]
+    ShardAssignmentsLengthExceeded {
+        /// Requested number of public key hashes
+        actual: usize,
+    },
+    /// Sector expirations length exceeded the limit
+    #[error(
+        "Sector expirations length exceeded the limit: \
+        {actual}/{MAX_SECTOR_EXPIRATIONS_PER_REQUEST}"
+    )]
+    SectorExpirationsLengthExceeded {
+        /// Requested number of sectors
+        actual: usize,
+    },
+    /// Too many subscriptions on a single connection
+    #[error("Too many subscriptions on a single connection, limit is {limit}")]
+    TooManySubscriptions {
+        /// Max number of active subscriptions per connection
+        limit: u32,
+    },
+    /// Slot is unknown or too old
+    #[error("Slot {slot} is unknown or too old")]
+    UnknownSlot {
+        /// Slot number
+        slot: SlotNumber,
+    },
+    /// Archiver re-initialization failed
+    #[error("Archiver re-initialization failed, check node logs for details")]
+    ArchiverReinitializationFailed,
+    /// Requested number of items in a page exceeded the limit
+    #[error("Requested number of items in a page exceeded the limit: {actual}/{MAX_PAGE_ITEMS}")]
+    PageItemsLimitExceeded {
+        /// Requested number of items
+        actual: usize,
+    },
+    /// Invalid contract address
+    #[error("Invalid contract address")]
+    InvalidAddress,
+    /// Invalid slot info filter
+    #[error("Invalid slot info filter: offset {offset} must be smaller than stride {stride}")]
+    InvalidSlotInfoFilter {
+        /// Stride of the filter
+        stride: u64,
+        /// Offset of the filter
+        offset: u64,
+    },
+}
+
+impl Error {
+    /// Stable error code that is sent to the client alongside the error message
+    pub fn code(&self) -> RpcErrorCode {
+        match self {
+            Self::SolutionWasIgnored { .. } => RpcErrorCode::SolutionWasIgnored,
+            Self::SuperSegmentHeadersLengthExceeded { .. } => {
+                RpcErrorCode::SuperSegmentHeadersLengthExceeded
+            }
+            Self::FailedToRecreateSegment(_) => RpcErrorCode::FailedToRecreateSegment,
+            Self::BlockingTaskJoinError(_) => RpcErrorCode::BlockingTaskJoinError,
+            Self::ShardAssignmentsLengthExceeded { .. } => {
+                RpcErrorCode::ShardAssignmentsLengthExceeded
+            }
+            Self::SectorExpirationsLengthExceeded { .. } => {
+                RpcErrorCode::SectorExpirationsLengthExceeded
+            }
+            Self::SolutionTooLate { .. } => RpcErrorCode::SolutionTooLate,
+            Self::TooManySubscriptions { .. } => RpcErrorCode::TooManySubscriptions,
+            Self::UnknownSlot { .. } => RpcErrorCode::UnknownSlot,
+            Self::ArchiverReinitializationFailed => RpcErrorCode::ArchiverReinitializationFailed,
+            Self::PageItemsLimitExceeded { .. } => RpcErrorCode::PageItemsLimitExceeded,
+            Self::InvalidAddress => RpcErrorCode::InvalidAddress,
+            Self::InvalidSlotInfoFilter { .. } => RpcErrorCode::InvalidSlotInfoFilter,
+        }
+    }
 }
 
 impl From<Error> for ErrorObjectOwned {
     fn from(error: Error) -> Self {
-        let code = match &error {
-            Error::SolutionWasIgnored { .. } => 0,
-            Error::SuperSegmentHeadersLengthExceeded { .. } => 1,
-            Error::FailedToRecreateSegment(_) => 2,
-            Error::BlockingTaskJoinError(_) => 3,
-        };
-
-        ErrorObject::owned(code, error.to_string(), None::<()>)
+        ErrorObject::owned(error.code().code(), error.to_string(), None::<()>)
     }
 }
 
@@ -98,16 +266,44 @@ pub trait FarmerRpcApi {
     #[method(name = "getFarmerAppInfo")]
     fn get_farmer_app_info(&self) -> Result<FarmerAppInfo, Error>;
 
+    /// Current status of the node, including archiver catch-up progress
+    #[method(name = "nodeStatus")]
+    fn node_status(&self) -> Result<NodeStatusInfo, Error>;
+
+    /// Resolve block identifier (`"best"`, `"confirmed"`, `"last-archived"`, block number or block
+    /// root) into a concrete block, `None` if the block is not known
+    #[method(name = "resolveBlock")]
+    fn resolve_block(&self, block: BlockId) -> Result<Option<BlockInfo>, Error>;
+
+    /// Statistics of the last archived segment, `None` if no segment was archived since the node
+    /// started or segment statistics are not available
+    #[method(name = "lastSegmentStats")]
+    fn last_segment_stats(&self) -> Result<Option<SegmentStatsInfo>, Error>;
+
     #[method(name = "submitSolutionResponse")]
     fn submit_solution_response(&self, solution_response: SolutionResponse) -> Result<(), Error>;
 
-    /// Slot info subscription
+    /// Run the same verification the node performs on a solution included in a block against the
+    /// challenge of one of the recent slots, without submitting it.
+    ///
+    /// Returns a detailed breakdown of individual checks to help with debugging of farms whose
+    /// solutions never result in blocks.
+    #[method(name = "verifySolution")]
+    fn verify_solution(
+        &self,
+        solution: Solution,
+        slot: SlotNumber,
+    ) -> Result<SolutionVerificationInfo, Error>;
+
+    /// Slot info subscription.
+    ///
+    /// When `filter` is provided, only slots that pass the filter are delivered.
     #[subscription(
         name = "subscribeSlotInfo" => "slot_info",
         unsubscribe = "unsubscribeSlotInfo",
         item = SlotInfo,
     )]
-    async fn subscribe_slot_info(&self) -> SubscriptionResult;
+    async fn subscribe_slot_info(&self, filter: Option<SlotInfoFilter>) -> SubscriptionResult;
 
     /// Sign block subscription
     #[subscription(
@@ -128,6 +324,22 @@ pub trait FarmerRpcApi {
     )]
     async fn subscribe_new_super_segment_header(&self) -> SubscriptionResult;
 
+    /// Gapless super segment header feed subscription.
+    ///
+    /// Each item includes the index of the previously delivered super segment header. When
+    /// `last_seen_super_segment_index` is provided (on resubscription after reconnect), all
+    /// super segment headers after it are replayed first, otherwise the feed starts with the
+    /// latest super segment header known to the node.
+    #[subscription(
+        name = "subscribeSuperSegmentHeaderFeed" => "super_segment_header_feed",
+        unsubscribe = "unsubscribeSuperSegmentHeaderFeed",
+        item = SuperSegmentHeaderFeedItem,
+    )]
+    async fn subscribe_super_segment_header_feed(
+        &self,
+        last_seen_super_segment_index: Option<SuperSegmentIndex>,
+    ) -> SubscriptionResult;
+
     #[method(name = "superSegmentHeaders")]
     async fn super_segment_headers(
         &self,
@@ -146,6 +358,18 @@ pub trait FarmerRpcApi {
         segment_index: SegmentIndex,
     ) -> Result<Option<SuperSegmentRoot>, Error>;
 
+    /// Contiguous range of up to `limit` beacon chain segment headers starting with
+    /// `first_segment_index` along with proofs binding them to the chain.
+    ///
+    /// Fewer segment headers are returned if the response would otherwise be too large, the rest
+    /// of the range can be requested using the continuation token in the response.
+    #[method(name = "getSegmentHeadersRange")]
+    async fn segment_headers_range(
+        &self,
+        first_segment_index: LocalSegmentIndex,
+        limit: u32,
+    ) -> Result<SegmentHeadersRange, Error>;
+
     #[method(name = "piece")]
     async fn piece(&self, piece_index: PieceIndex) -> Result<Option<Piece>, Error>;
 
@@ -154,12 +378,46 @@ pub trait FarmerRpcApi {
         &self,
         info: Vec<FarmerShardMembershipInfo>,
     ) -> Result<(), Error>;
+
+    /// Shard assignments of farmers with specified public key hashes in the current shard rotation
+    /// era.
+    ///
+    /// Farmers must have submitted their shard membership info with `updateShardMembershipInfo`
+    /// before, otherwise no assignments will be returned for them.
+    #[method(name = "shardAssignments")]
+    fn shard_assignments(
+        &self,
+        public_key_hashes: Vec<Blake3Hash>,
+    ) -> Result<Vec<FarmerShardAssignment>, Error>;
+
+    /// Expiration of specified sectors as of the current history size
+    #[method(name = "sectorExpirations")]
+    fn sector_expirations(
+        &self,
+        sectors: Vec<SectorExpirationRequest>,
+    ) -> Result<Vec<SectorExpirationInfo>, Error>;
+
+    /// Sector expiration subscription.
+    ///
+    /// Expiration of all specified sectors is sent right after subscription, after that only
+    /// sectors whose expiration has changed or that are due for replotting are sent as history
+    /// grows.
+    #[subscription(
+        name = "subscribeSectorExpirations" => "sector_expirations",
+        unsubscribe = "unsubscribeSectorExpirations",
+        item = Vec<SectorExpirationInfo>,
+    )]
+    async fn subscribe_sector_expirations(
+        &self,
+        sectors: Vec<SectorExpirationRequest>,
+    ) -> SubscriptionResult;
 }
 
+/// Senders for seals of blocks that are being produced, multiple blocks (one per shard) might be
+/// waiting for a seal at the same time
 #[derive(Debug, Default)]
 struct BlockSignatureSenders {
-    current_pre_seal_hash: Blake3Hash,
-    senders: Vec<oneshot::Sender<OwnedBlockHeaderSeal>>,
+    senders: HashMap<Blake3Hash, Vec<mpsc::Sender<OwnedBlockHeaderSeal>>>,
 }
 
 #[derive(Debug)]
@@ -187,6 +445,43 @@ impl CachedSuperSegments {
         })
     }
 
+    /// Proof of inclusion of a shard segment into one of the cached super segments
+    fn inclusion_proof(
+        &self,
+        shard_index: ShardIndex,
+        local_segment_index: LocalSegmentIndex,
+        segment_root: &SegmentRoot,
+    ) -> Option<SegmentInclusionProof> {
+        self.super_segments.iter().find_map(|super_segment| {
+            let shard_segment_root =
+                super_segment
+                    .segment_roots
+                    .iter()
+                    .find(|root_with_position| {
+                        root_with_position.shard_index == shard_index
+                            && root_with_position.local_segment_index == local_segment_index
+                    })?;
+
+            if &shard_segment_root.segment_root != segment_root {
+                error!(
+                    %shard_index,
+                    %local_segment_index,
+                    super_segment_header = ?super_segment.header,
+                    "Segment root in super segment doesn't match segment header, this should \
+                    never happen"
+                );
+                return None;
+            }
+
+            Some(SegmentInclusionProof {
+                super_segment_index: super_segment.header.index.as_inner(),
+                segment_position: shard_segment_root.segment_position,
+                segment_proof: super_segment
+                    .proof_for_segment(shard_segment_root.segment_position)?,
+            })
+        })
+    }
+
     fn add(&mut self, super_segment: SuperSegment) {
         if self.super_segments.len() == CACHED_SUPER_SEGMENTS_CAPACITY {
             self.super_segments.pop_front();
@@ -215,6 +510,28 @@ struct ShardMembershipConnections {
     connections: HashMap<ConnectionId, ShardMembershipConnectionsState>,
 }
 
+/// Per-connection limits for subscriptions of farmer RPC
+#[derive(Debug, Copy, Clone)]
+pub struct SubscriptionLimits {
+    /// Max number of active subscriptions per connection, new subscriptions beyond this limit are
+    /// rejected with [`Error::TooManySubscriptions`]
+    pub max_subscriptions_per_connection: u32,
+    /// Max number of pending notifications (and responses) per connection, notifications beyond
+    /// this limit are not delivered to slow clients
+    pub max_pending_notifications_per_connection: u32,
+}
+
+impl Default for SubscriptionLimits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            max_pending_notifications_per_connection:
+                DEFAULT_MAX_PENDING_NOTIFICATIONS_PER_CONNECTION,
+        }
+    }
+}
+
 /// Farmer RPC configuration
 #[derive(Debug)]
 pub struct FarmerRpcConfig<BCI, CSS> {
@@ -226,54 +543,94 @@ pub struct FarmerRpcConfig<BCI, CSS> {
     pub consensus_constants: ConsensusConstants,
     /// Max pieces in a sector
     pub max_pieces_in_sector: u16,
-    /// New slot notifications
-    pub new_slot_notification_receiver: mpsc::Receiver<NewSlotNotification>,
-    /// Block sealing notifications
-    pub block_sealing_notification_receiver: mpsc::Receiver<BlockSealNotification>,
-    /// Super segment notifications
-    pub new_super_segment_notification_receiver: mpsc::Receiver<SuperSegment>,
+    /// Notification bus to subscribe to new slot, block sealing and super segment notifications
+    pub notification_bus: NotificationBus,
     /// Shard membership updates
     pub shard_membership_updates_sender: mpsc::Sender<Vec<FarmerShardMembershipInfo>>,
     /// DSN bootstrap nodes
     pub dsn_bootstrap_nodes: Vec<Multiaddr>,
     /// Beacon chain info
     pub beacon_chain_info: BCI,
+    /// Cache of contract slots of the last imported block, shared with block import
+    pub state_cache: Arc<StateCache>,
     /// Chain sync status
     pub chain_sync_status: CSS,
     /// Erasure coding instance
     pub erasure_coding: ErasureCoding,
+    /// Transaction pool, transaction pool methods are not exposed if `None`
+    pub transaction_pool: Option<Arc<Mutex<TransactionPool>>>,
+    /// Whether to expose unsafe methods that modify the state of the node
+    pub unsafe_methods: bool,
+    /// Sender for archiver control messages, archiver management methods are not exposed if `None`
+    pub archiver_control_sender: Option<mpsc::Sender<ArchiverControlMessage>>,
+    /// Archiver progress, not included in node status if `None`
+    pub archiver_progress: Option<watch::Receiver<ArchiverProgress>>,
+    /// Statistics of the last archived segment, not available if `None`
+    pub last_segment_stats: Option<watch::Receiver<Option<SegmentStats>>>,
+    /// Per-connection subscription limits
+    pub subscription_limits: SubscriptionLimits,
+    /// Metrics of subscriptions, not recorded if `None`
+    pub subscription_metrics: Option<SubscriptionFanoutMetrics>,
 }
 
 /// Worker that drives RPC server tasks
 #[derive(Debug)]
-pub struct FarmerRpcWorker<BCI, CSS>
+pub struct FarmerRpcWorker<PosTable, BCI, CSS>
 where
+    PosTable: SolutionPotVerifier + Send + Sync + 'static,
     BCI: BeaconChainInfo,
     CSS: ChainSyncStatus,
 {
     server: Option<Server>,
-    rpc: Option<FarmerRpc<BCI, CSS>>,
-    new_slot_notification_receiver: mpsc::Receiver<NewSlotNotification>,
-    block_sealing_notification_receiver: mpsc::Receiver<BlockSealNotification>,
-    new_super_segment_notification_receiver: mpsc::Receiver<SuperSegment>,
+    rpc: Option<FarmerRpc<PosTable, BCI, CSS>>,
+    transaction_pool_rpc: Option<TransactionPoolRpc>,
+    archiver_rpc: Option<ArchiverRpc>,
+    unsafe_methods: bool,
+    new_slot_notifications: Subscription<NewSlotTopic>,
+    block_sealing_notifications: Subscription<SealRequestTopic>,
+    new_super_segment_notifications: Subscription<NewSuperSegmentTopic>,
     solution_response_senders: Arc<Mutex<LruMap<SlotNumber, mpsc::Sender<Solution>>>>,
     block_sealing_senders: Arc<Mutex<BlockSignatureSenders>>,
-    slot_info_subscriptions: Arc<Mutex<Vec<SubscriptionSink>>>,
-    block_sealing_subscriptions: Arc<Mutex<Vec<SubscriptionSink>>>,
-    new_super_segment_header_subscriptions: Arc<Mutex<Vec<SubscriptionSink>>>,
+    current_slot: Arc<Mutex<Option<SlotNumber>>>,
+    cached_slot_infos: Arc<Mutex<LruMap<SlotNumber, NewSlotInfo>>>,
+    slot_info_subscriptions: Arc<SubscriptionFanout<Option<SlotInfoFilter>>>,
+    block_sealing_subscriptions: Arc<SubscriptionFanout<()>>,
+    new_super_segment_header_subscriptions: Arc<SubscriptionFanout<()>>,
+    super_segment_header_feed_subscriptions:
+        Arc<SubscriptionFanout<SuperSegmentHeaderFeedSubscription>>,
     cached_archived_segment: Arc<AsyncMutex<Option<CachedArchivedSegment>>>,
     cached_super_segments: Arc<Mutex<CachedSuperSegments>>,
+    shard_membership_assignments: Arc<Mutex<ShardMembershipAssignments>>,
+    sector_expiration_subscriptions: Arc<SubscriptionFanout<SectorExpirationSubscription>>,
+    beacon_chain_info: BCI,
+    state_cache: Arc<StateCache>,
+    min_sector_lifetime: HistorySize,
 }
 
-impl<BCI, CSS> FarmerRpcWorker<BCI, CSS>
+impl<PosTable, BCI, CSS> FarmerRpcWorker<PosTable, BCI, CSS>
 where
+    PosTable: SolutionPotVerifier + Send + Sync + 'static,
     BCI: BeaconChainInfo,
     CSS: ChainSyncStatus,
 {
     /// Creates a new farmer RPC worker
     pub async fn new(config: FarmerRpcConfig<BCI, CSS>) -> io::Result<Self> {
         let server = Server::builder()
-            .set_config(ServerConfig::builder().ws_only().build())
+            .set_config(
+                ServerConfig::builder()
+                    .ws_only()
+                    .set_batch_request_config(BatchRequestConfig::Limit(MAX_BATCH_REQUESTS))
+                    .max_request_body_size(MAX_REQUEST_BODY_SIZE)
+                    .max_response_body_size(MAX_RESPONSE_BODY_SIZE)
+                    // The limit is enforced by the RPC implementation to return structured errors
+                    .max_subscriptions_per_connection(u32::MAX)
+                    .set_message_buffer_capacity(
+                        config
+                            .subscription_limits
+                            .max_pending_notifications_per_connection,
+                    )
+                    .build(),
+            )
             .build(config.listen_on)
             .await?;
 
@@ -286,21 +643,48 @@ where
         let solution_response_senders_capacity = u32::try_from(block_authoring_delay)
             .expect("Always a tiny constant in the protocol; qed");
 
-        let slot_info_subscriptions = Arc::default();
-        let block_sealing_subscriptions = Arc::default();
+        let subscription_metrics = config.subscription_metrics;
+        let slot_info_subscriptions = Arc::new(SubscriptionFanout::new(
+            "slot_info",
+            subscription_metrics.clone(),
+        ));
+        let block_sealing_subscriptions = Arc::new(SubscriptionFanout::new(
+            "block_seal",
+            subscription_metrics.clone(),
+        ));
 
         let solution_response_senders = Arc::new(Mutex::new(LruMap::new(ByLength::new(
             solution_response_senders_capacity,
         ))));
         let block_sealing_senders = Arc::default();
-        let new_super_segment_header_subscriptions = Arc::default();
+        let current_slot = Arc::default();
+        let cached_slot_infos = Arc::new(Mutex::new(LruMap::new(ByLength::new(
+            CACHED_SLOT_INFOS_CAPACITY,
+        ))));
+        let new_super_segment_header_subscriptions = Arc::new(SubscriptionFanout::new(
+            "new_super_segment_header",
+            subscription_metrics.clone(),
+        ));
+        let super_segment_header_feed_subscriptions = Arc::new(SubscriptionFanout::new(
+            "super_segment_header_feed",
+            subscription_metrics.clone(),
+        ));
         let cached_archived_segment = Arc::default();
         let cached_super_segments = Arc::default();
+        let shard_membership_assignments = Arc::default();
+        let sector_expiration_subscriptions = Arc::new(SubscriptionFanout::new(
+            "sector_expirations",
+            subscription_metrics,
+        ));
+        let beacon_chain_info = config.beacon_chain_info.clone();
+        let min_sector_lifetime = config.consensus_constants.min_sector_lifetime;
 
         let rpc = FarmerRpc {
             genesis_block: config.genesis_block,
             solution_response_senders: Arc::clone(&solution_response_senders),
             block_sealing_senders: Arc::clone(&block_sealing_senders),
+            current_slot: Arc::clone(&current_slot),
+            cached_slot_infos: Arc::clone(&cached_slot_infos),
             dsn_bootstrap_nodes: config.dsn_bootstrap_nodes,
             beacon_chain_info: config.beacon_chain_info,
             chain_sync_status: config.chain_sync_status,
@@ -311,26 +695,67 @@ where
             new_super_segment_header_subscriptions: Arc::clone(
                 &new_super_segment_header_subscriptions,
             ),
+            super_segment_header_feed_subscriptions: Arc::clone(
+                &super_segment_header_feed_subscriptions,
+            ),
             cached_archived_segment: Arc::clone(&cached_archived_segment),
             cached_super_segments: Arc::clone(&cached_super_segments),
             shard_membership_connections: Arc::default(),
             shard_membership_updates_sender: config.shard_membership_updates_sender,
+            shard_commitments_roots: Arc::default(),
+            shard_membership_assignments: Arc::clone(&shard_membership_assignments),
+            sector_expiration_subscriptions: Arc::clone(&sector_expiration_subscriptions),
             erasure_coding: config.erasure_coding,
+            archiver_progress: config.archiver_progress,
+            last_segment_stats: config.last_segment_stats,
+            max_subscriptions_per_connection: config
+                .subscription_limits
+                .max_subscriptions_per_connection,
+            _pos_table: PhantomData,
         };
 
         Ok(Self {
             server: Some(server),
             rpc: Some(rpc),
-            new_slot_notification_receiver: config.new_slot_notification_receiver,
-            block_sealing_notification_receiver: config.block_sealing_notification_receiver,
-            new_super_segment_notification_receiver: config.new_super_segment_notification_receiver,
+            transaction_pool_rpc: config
+                .transaction_pool
+                .map(|transaction_pool| TransactionPoolRpc { transaction_pool }),
+            archiver_rpc: config
+                .archiver_control_sender
+                .map(|archiver_control_sender| ArchiverRpc {
+                    archiver_control_sender,
+                }),
+            unsafe_methods: config.unsafe_methods,
+            new_slot_notifications: config.notification_bus.subscribe(
+                BufferingPolicy::DropOldest {
+                    capacity: SLOT_NOTIFICATIONS_BUFFER,
+                },
+            ),
+            block_sealing_notifications: config.notification_bus.subscribe(
+                BufferingPolicy::DropOldest {
+                    capacity: SLOT_NOTIFICATIONS_BUFFER,
+                },
+            ),
+            new_super_segment_notifications: config.notification_bus.subscribe(
+                BufferingPolicy::Backpressure {
+                    capacity: SUPER_SEGMENT_NOTIFICATIONS_BUFFER,
+                },
+            ),
             solution_response_senders,
             block_sealing_senders,
+            current_slot,
+            cached_slot_infos,
             slot_info_subscriptions,
             block_sealing_subscriptions,
             new_super_segment_header_subscriptions,
+            super_segment_header_feed_subscriptions,
             cached_archived_segment,
             cached_super_segments,
+            shard_membership_assignments,
+            sector_expiration_subscriptions,
+            beacon_chain_info,
+            state_cache: config.state_cache,
+            min_sector_lifetime,
         })
     }
 
@@ -338,7 +763,35 @@ where
     pub async fn run(mut self) {
         let server = self.server.take().expect("Called only once from here; qed");
         let rpc = self.rpc.take().expect("Called only once from here; qed");
-        let mut server_fut = server.start(rpc.into_rpc()).stopped().boxed().fuse();
+        let mut rpc_module = rpc.into_rpc();
+        rpc_module
+            .merge(ContractMetadataRpcApiServer::into_rpc(
+                ContractMetadataRpc {
+                    beacon_chain_info: self.beacon_chain_info.clone(),
+                    state_cache: Arc::clone(&self.state_cache),
+                },
+            ))
+            .expect("Method names are unique; qed");
+        if let Some(transaction_pool_rpc) = self.transaction_pool_rpc.take() {
+            if self.unsafe_methods {
+                rpc_module
+                    .merge(TransactionPoolUnsafeRpcApiServer::into_rpc(
+                        transaction_pool_rpc.clone(),
+                    ))
+                    .expect("Method names are unique; qed");
+            }
+            rpc_module
+                .merge(TransactionPoolRpcApiServer::into_rpc(transaction_pool_rpc))
+                .expect("Method names are unique; qed");
+        }
+        if let Some(archiver_rpc) = self.archiver_rpc.take()
+            && self.unsafe_methods
+        {
+            rpc_module
+                .merge(ArchiverUnsafeRpcApiServer::into_rpc(archiver_rpc))
+                .expect("Method names are unique; qed");
+        }
+        let mut server_fut = server.start(rpc_module).stopped().boxed().fuse();
 
         // Also send periodic updates in addition to the subscription response
         let mut archived_segment_cache_cleanup_interval =
@@ -348,26 +801,26 @@ where
         loop {
             select! {
                 () = server_fut => {}
-                maybe_new_slot_notification = self.new_slot_notification_receiver.next() => {
+                maybe_new_slot_notification = self.new_slot_notifications.next() => {
                     let Some(new_slot_notification) = maybe_new_slot_notification else {
                         break;
                     };
 
-                    self.handle_new_slot_notification(new_slot_notification);
+                    self.handle_new_slot_notification(new_slot_notification).await;
                 }
-                maybe_block_sealing_notification = self.block_sealing_notification_receiver.next() => {
+                maybe_block_sealing_notification = self.block_sealing_notifications.next() => {
                     let Some(block_sealing_notification) = maybe_block_sealing_notification else {
                         break;
                     };
 
-                    self.handle_block_sealing_notification(block_sealing_notification);
+                    self.handle_block_sealing_notification(block_sealing_notification).await;
                 }
-                maybe_new_super_segment = self.new_super_segment_notification_receiver.next() => {
+                maybe_new_super_segment = self.new_super_segment_notifications.next() => {
                     let Some(new_super_segment) = maybe_new_super_segment else {
                         break;
                     };
 
-                    self.handle_new_super_segment(new_super_segment);
+                    self.handle_new_super_segment(new_super_segment).await;
                 }
                 _ = archived_segment_cache_cleanup_interval.tick().fuse() => {
                     if let Some(mut maybe_cached_archived_segment) = self.cached_archived_segment.try_lock()
@@ -381,7 +834,7 @@ where
         }
     }
 
-    fn handle_new_slot_notification(&mut self, new_slot_notification: NewSlotNotification) {
+    async fn handle_new_slot_notification(&mut self, new_slot_notification: NewSlotNotification) {
         let NewSlotNotification {
             new_slot_info,
             solution_sender,
@@ -395,6 +848,9 @@ where
             num_shards,
         } = new_slot_info;
 
+        self.current_slot.lock().replace(slot);
+        self.cached_slot_infos.lock().insert(slot, new_slot_info);
+
         // Store solution sender so that we can retrieve it when solution comes from
         // the farmer
         let mut solution_response_senders = self.solution_response_senders.lock();
@@ -402,59 +858,70 @@ where
             solution_response_senders.insert(slot, solution_sender);
         }
 
+        // Assignments only change when the shard rotation era changes, which is a no-op otherwise
+        self.shard_membership_assignments
+            .lock()
+            .update_era(ShardMembershipEra {
+                shard_membership_entropy,
+                num_shards,
+            });
+
         let global_challenge = proof_of_time.derive_global_challenge(slot);
 
         // This will be sent to the farmer
         let slot_info = SlotInfo {
             slot,
             global_challenge,
-            solution_range: solution_range.to_leaf_shard(num_shards),
+            solution_range: solution_range.to_farmer_solution_range(num_shards),
             shard_membership_entropy,
             num_shards,
         };
         let slot_info = serde_json::value::to_raw_value(&slot_info)
             .expect("Serialization of slot info never fails; qed");
 
-        self.slot_info_subscriptions.lock().retain_mut(|sink| {
-            match sink.try_send(slot_info.clone()) {
-                Ok(()) => true,
-                Err(error) => match error {
-                    TrySendError::Closed(_) => {
-                        // Remove closed receivers
-                        false
-                    }
-                    TrySendError::Full(_) => {
-                        warn!(
-                            subscription_id = ?sink.subscription_id(),
-                            "Slot info receiver is too slow, dropping notification"
-                        );
-                        true
-                    }
-                },
-            }
-        });
+        self.slot_info_subscriptions
+            .notify(|filter| {
+                if let Some(filter) = filter
+                    && !filter.matches(slot)
+                {
+                    return None;
+                }
+
+                Some(slot_info.clone())
+            })
+            .await;
     }
 
-    fn handle_block_sealing_notification(
+    async fn handle_block_sealing_notification(
         &mut self,
         block_sealing_notification: BlockSealNotification,
     ) {
         let BlockSealNotification {
+            shard_index,
             pre_seal_hash,
             public_key_hash,
             seal_sender,
         } = block_sealing_notification;
 
+        debug!(%shard_index, %pre_seal_hash, "Block seal requested");
+
         // Store signature sender so that we can retrieve it when a solution comes from the farmer
         {
             let mut block_sealing_senders = self.block_sealing_senders.lock();
 
-            if block_sealing_senders.current_pre_seal_hash != pre_seal_hash {
-                block_sealing_senders.current_pre_seal_hash = pre_seal_hash;
-                block_sealing_senders.senders.clear();
-            }
+            // Remove senders of blocks that are no longer waiting for a seal
+            block_sealing_senders
+                .senders
+                .retain(|_pre_seal_hash, senders| {
+                    senders.retain(|sender| !sender.is_closed());
+                    !senders.is_empty()
+                });
 
-            block_sealing_senders.senders.push(seal_sender);
+            block_sealing_senders
+                .senders
+                .entry(pre_seal_hash)
+                .or_default()
+                .push(seal_sender);
         }
 
         // This will be sent to the farmer
@@ -465,27 +932,13 @@ where
         let block_seal_info = serde_json::value::to_raw_value(&block_seal_info)
             .expect("Serialization of block seal info never fails; qed");
 
-        self.block_sealing_subscriptions.lock().retain_mut(|sink| {
-            match sink.try_send(block_seal_info.clone()) {
-                Ok(()) => true,
-                Err(error) => match error {
-                    TrySendError::Closed(_) => {
-                        // Remove closed receivers
-                        false
-                    }
-                    TrySendError::Full(_) => {
-                        warn!(
-                            subscription_id = ?sink.subscription_id(),
-                            "Block seal info receiver is too slow, dropping notification"
-                        );
-                        true
-                    }
-                },
-            }
-        });
+        self.block_sealing_subscriptions
+            .notify(|()| Some(block_seal_info.clone()))
+            .await;
     }
 
-    fn handle_new_super_segment(&mut self, super_segment: SuperSegment) {
+    async fn handle_new_super_segment(&mut self, super_segment: SuperSegment) {
+        let new_super_segment_header = super_segment.header;
         // This will be sent to the farmer
         let super_segment_header = serde_json::value::to_raw_value(&super_segment.header)
             .expect("Serialization of super segment info never fails; qed");
@@ -493,77 +946,273 @@ where
         self.cached_super_segments.lock().add(super_segment);
 
         self.new_super_segment_header_subscriptions
-            .lock()
-            .retain_mut(|sink| {
-                let subscription_id = sink.subscription_id();
-
-                match sink.try_send(super_segment_header.clone()) {
-                    Ok(()) => true,
-                    Err(error) => match error {
-                        TrySendError::Closed(_) => false,
-                        TrySendError::Full(_) => {
-                            warn!(
-                                ?subscription_id,
-                                "Super segment receiver is too slow, dropping notification"
-                            );
-                            true
-                        }
-                    },
+            .notify(|()| Some(super_segment_header.clone()))
+            .await;
+
+        self.super_segment_header_feed_subscriptions
+            .notify_with(|subscriber| {
+                SuperSegmentHeaderFeedSubscription::notify(
+                    subscriber,
+                    &self.beacon_chain_info,
+                    &new_super_segment_header,
+                )
+            })
+            .await;
+
+        self.notify_sector_expirations().await;
+    }
+
+    /// Notify sector expiration subscribers about sectors whose expiration has changed or that are
+    /// due for replotting with the new history size
+    async fn notify_sector_expirations(&self) {
+        let current_history_size = current_history_size(&self.beacon_chain_info);
+
+        self.sector_expiration_subscriptions
+            .notify_with(|subscriber| {
+                let subscription = &mut subscriber.state;
+                let expirations = sector_expirations(
+                    &self.beacon_chain_info,
+                    self.min_sector_lifetime,
+                    current_history_size,
+                    &subscription.sectors,
+                );
+                let changed = subscription.changed(expirations, current_history_size);
+                let notification = subscription.take_notification(changed);
+
+                if notification.is_empty() {
+                    return !subscriber.is_closed();
                 }
-            });
+
+                let raw_notification = serde_json::value::to_raw_value(&notification)
+                    .expect("Serialization of sector expirations never fails; qed");
+
+                match subscriber.send(raw_notification) {
+                    Delivery::Delivered => true,
+                    Delivery::Dropped(_raw_notification) => {
+                        debug!(
+                            subscription_id = ?subscriber.sink().subscription_id(),
+                            "Sector expirations receiver is too slow, coalescing with the next \
+                            notification"
+                        );
+                        subscriber.state.defer_notification(notification);
+                        true
+                    }
+                    Delivery::Closed => false,
+                }
+            })
+            .await;
     }
 }
 
+/// Check that solution for `slot` was submitted no later than `block_authoring_delay` slots after
+/// it, solutions are always accepted when the current slot is not known yet
+fn check_solution_deadline(
+    slot: SlotNumber,
+    maybe_current_slot: Option<SlotNumber>,
+    block_authoring_delay: SlotNumber,
+) -> Result<(), Error> {
+    if let Some(current_slot) = maybe_current_slot
+        && let Some(slots_late) = current_slot
+            .checked_sub(slot)
+            .and_then(|slot_age| slot_age.checked_sub(block_authoring_delay))
+        && slots_late > SlotNumber::ZERO
+    {
+        return Err(Error::SolutionTooLate {
+            slot,
+            current_slot,
+            slots_late: u64::from(slots_late),
+        });
+    }
+
+    Ok(())
+}
+
 /// Implements the [`FarmerRpcApiServer`] trait for a farmer to connect to
 #[derive(Debug)]
-struct FarmerRpc<BCI, CSS>
+struct FarmerRpc<PosTable, BCI, CSS>
 where
+    PosTable: SolutionPotVerifier + Send + Sync + 'static,
     BCI: BeaconChainInfo,
     CSS: ChainSyncStatus,
 {
     genesis_block: OwnedBeaconChainBlock,
     solution_response_senders: Arc<Mutex<LruMap<SlotNumber, mpsc::Sender<Solution>>>>,
     block_sealing_senders: Arc<Mutex<BlockSignatureSenders>>,
+    current_slot: Arc<Mutex<Option<SlotNumber>>>,
+    cached_slot_infos: Arc<Mutex<LruMap<SlotNumber, NewSlotInfo>>>,
     dsn_bootstrap_nodes: Vec<Multiaddr>,
     beacon_chain_info: BCI,
     chain_sync_status: CSS,
     consensus_constants: ConsensusConstants,
     max_pieces_in_sector: u16,
-    slot_info_subscriptions: Arc<Mutex<Vec<SubscriptionSink>>>,
-    block_sealing_subscriptions: Arc<Mutex<Vec<SubscriptionSink>>>,
-    new_super_segment_header_subscriptions: Arc<Mutex<Vec<SubscriptionSink>>>,
+    slot_info_subscriptions: Arc<SubscriptionFanout<Option<SlotInfoFilter>>>,
+    block_sealing_subscriptions: Arc<SubscriptionFanout<()>>,
+    new_super_segment_header_subscriptions: Arc<SubscriptionFanout<()>>,
+    super_segment_header_feed_subscriptions:
+        Arc<SubscriptionFanout<SuperSegmentHeaderFeedSubscription>>,
     cached_archived_segment: Arc<AsyncMutex<Option<CachedArchivedSegment>>>,
     cached_super_segments: Arc<Mutex<CachedSuperSegments>>,
     shard_membership_connections: Arc<Mutex<ShardMembershipConnections>>,
     shard_membership_updates_sender: mpsc::Sender<Vec<FarmerShardMembershipInfo>>,
+    shard_commitments_roots: Arc<Mutex<ShardCommitmentsRoots>>,
+    shard_membership_assignments: Arc<Mutex<ShardMembershipAssignments>>,
+    sector_expiration_subscriptions: Arc<SubscriptionFanout<SectorExpirationSubscription>>,
     erasure_coding: ErasureCoding,
+    archiver_progress: Option<watch::Receiver<ArchiverProgress>>,
+    last_segment_stats: Option<watch::Receiver<Option<SegmentStats>>>,
+    max_subscriptions_per_connection: u32,
+    _pos_table: PhantomData<PosTable>,
 }
 
-#[async_trait]
-impl<BCI, CSS> FarmerRpcApiServer for FarmerRpc<BCI, CSS>
+impl<PosTable, BCI, CSS> FarmerRpc<PosTable, BCI, CSS>
 where
+    PosTable: SolutionPotVerifier + Send + Sync + 'static,
     BCI: BeaconChainInfo,
     CSS: ChainSyncStatus,
 {
-    fn get_farmer_app_info(&self) -> Result<FarmerAppInfo, Error> {
-        let max_segment_index = self
-            .beacon_chain_info
-            .last_super_segment_header()
-            .map_or(SegmentIndex::ZERO, |super_segment_header| {
-                super_segment_header.max_segment_index.as_inner()
+    /// Check that another subscription can be created on a connection
+    fn check_subscription_limit(&self, connection_id: ConnectionId) -> Result<(), Error> {
+        let active_subscriptions = self
+            .slot_info_subscriptions
+            .active_on_connection(connection_id)
+            + self
+                .block_sealing_subscriptions
+                .active_on_connection(connection_id)
+            + self
+                .new_super_segment_header_subscriptions
+                .active_on_connection(connection_id)
+            + self
+                .super_segment_header_feed_subscriptions
+                .active_on_connection(connection_id)
+            + self
+                .sector_expiration_subscriptions
+                .active_on_connection(connection_id);
+
+        if active_subscriptions >= self.max_subscriptions_per_connection as usize {
+            return Err(Error::TooManySubscriptions {
+                limit: self.max_subscriptions_per_connection,
             });
+        }
+
+        Ok(())
+    }
+
+    /// History size that will be used for verification of the solution in the next block, the
+    /// same way as during block verification
+    fn next_block_history_size(&self) -> HistorySize {
+        let best_number = self.beacon_chain_info.best_header().header().prefix.number;
+
+        self.beacon_chain_info
+            .previous_super_segment_header(best_number + BlockNumber::ONE)
+            .map_or_else(
+                || current_history_size(&self.beacon_chain_info),
+                |super_segment_header| {
+                    HistorySize::from(super_segment_header.max_segment_index.as_inner())
+                },
+            )
+    }
 
-        let consensus_constants = &self.consensus_constants;
+    /// Consensus constants that will be used for the next block, taking into account changes
+    /// scheduled in the state of the best block
+    fn next_block_consensus_constants(&self) -> ConsensusConstants {
+        let (best_header, best_block_details) = self.beacon_chain_info.best_header_with_details();
+        let best_number = best_header.header().prefix.number;
+
+        match ConsensusConstantsSchedule::from_system_contract_states(
+            &best_block_details.system_contract_states,
+        ) {
+            Ok(schedule) => schedule
+                .consensus_constants_at(&self.consensus_constants, best_number + BlockNumber::ONE),
+            Err(error) => {
+                warn!(
+                    %error,
+                    %best_number,
+                    "Invalid consensus constants schedule, using default consensus constants"
+                );
+                self.consensus_constants
+            }
+        }
+    }
+
+    /// Parameters for piece verification of the solution the same way as during block
+    /// verification, returns the reason if they can't be determined
+    fn solution_verify_piece_params(
+        &self,
+        solution: &Solution,
+        current_history_size: HistorySize,
+    ) -> Result<SolutionVerifyPieceParams, String> {
+        let consensus_constants = &self.next_block_consensus_constants();
+
+        let solution_super_segment_header = self
+            .beacon_chain_info
+            .get_super_segment_header(solution.piece_super_segment_index)
+            .ok_or_else(|| {
+                format!(
+                    "Super segment {} of the solution piece is not known",
+                    solution.piece_super_segment_index
+                )
+            })?;
+
+        let sector_expiration_check_super_segment_root = solution
+            .history_size
+            .sector_expiration_check(consensus_constants.min_sector_lifetime)
+            .ok_or_else(|| SolutionVerifyError::InvalidHistorySize.to_string())
+            .map(|expiration_check_history_size| {
+                self.beacon_chain_info
+                    .get_super_segment_header_for_segment_index(
+                        expiration_check_history_size.segment_index(),
+                    )
+                    .map(|super_segment_header| super_segment_header.root)
+            })?;
+
+        Ok(SolutionVerifyPieceParams {
+            max_pieces_in_sector: self.max_pieces_in_sector,
+            super_segment_root: solution_super_segment_header.root,
+            num_segments: solution_super_segment_header.num_segments,
+            recent_segments: consensus_constants.recent_segments,
+            recent_history_fraction: consensus_constants.recent_history_fraction,
+            min_sector_lifetime: consensus_constants.min_sector_lifetime,
+            current_history_size,
+            sector_expiration_check_super_segment_root,
+        })
+    }
+}
+
+#[async_trait]
+impl<PosTable, BCI, CSS> FarmerRpcApiServer for FarmerRpc<PosTable, BCI, CSS>
+where
+    PosTable: SolutionPotVerifier + Send + Sync + 'static,
+    BCI: BeaconChainInfo,
+    CSS: ChainSyncStatus,
+{
+    fn get_farmer_app_info(&self) -> Result<FarmerAppInfo, Error> {
+        let consensus_constants = &self.next_block_consensus_constants();
         let protocol_info = FarmerProtocolInfo {
-            history_size: HistorySize::from(max_segment_index),
+            history_size: current_history_size(&self.beacon_chain_info),
             max_pieces_in_sector: self.max_pieces_in_sector,
             recent_segments: consensus_constants.recent_segments,
             recent_history_fraction: consensus_constants.recent_history_fraction,
             min_sector_lifetime: consensus_constants.min_sector_lifetime,
         };
 
+        let genesis_root = *self.genesis_block.header.header().root();
+        // TODO: Include intermediate and leaf shards once the node is able to serve them
+        let shard_topology = ShardTopology {
+            num_shards: self
+                .beacon_chain_info
+                .best_header()
+                .header()
+                .consensus_parameters()
+                .fixed_parameters
+                .num_shards,
+            shards: vec![NodeShardInfo {
+                shard_index: ShardIndex::BEACON_CHAIN,
+                genesis_root: Some(genesis_root),
+            }],
+        };
+
         let farmer_app_info = FarmerAppInfo {
-            genesis_root: *self.genesis_block.header.header().root(),
+            genesis_root,
             dsn_bootstrap_nodes: self.dsn_bootstrap_nodes.clone(),
             syncing: self.chain_sync_status.is_syncing(),
             farming_timeout: consensus_constants
@@ -571,15 +1220,72 @@ where
                 .as_duration()
                 .mul_f64(u64::from(consensus_constants.block_authoring_delay) as f64),
             protocol_info,
+            shard_topology,
         };
 
         Ok(farmer_app_info)
     }
 
+    fn node_status(&self) -> Result<NodeStatusInfo, Error> {
+        let archiver = self
+            .archiver_progress
+            .as_ref()
+            .map(|archiver_progress| archiver_progress_info(*archiver_progress.borrow()));
+
+        Ok(NodeStatusInfo {
+            best_block_number: self.beacon_chain_info.best_header().header().prefix.number,
+            syncing: self.chain_sync_status.is_syncing(),
+            archiver,
+        })
+    }
+
+    fn last_segment_stats(&self) -> Result<Option<SegmentStatsInfo>, Error> {
+        let Some(last_segment_stats) = &self.last_segment_stats else {
+            return Ok(None);
+        };
+
+        Ok(last_segment_stats.borrow().map(segment_stats_info))
+    }
+
+    fn resolve_block(&self, block: BlockId) -> Result<Option<BlockInfo>, Error> {
+        let maybe_header = resolve_block_id(
+            &self.beacon_chain_info,
+            block,
+            self.consensus_constants.block_confirmation_depth,
+        );
+
+        Ok(maybe_header.map(|header| {
+            let header = header.header();
+
+            BlockInfo {
+                root: *header.root(),
+                number: header.prefix.number,
+            }
+        }))
+    }
+
     fn submit_solution_response(&self, solution_response: SolutionResponse) -> Result<(), Error> {
         let slot = solution_response.slot_number;
         let public_key_hash = solution_response.solution.public_key_hash;
         let sector_index = solution_response.solution.sector_index;
+
+        let maybe_current_slot = *self.current_slot.lock();
+        if let Err(error) = check_solution_deadline(
+            slot,
+            maybe_current_slot,
+            self.consensus_constants.block_authoring_delay,
+        ) {
+            warn!(
+                %slot,
+                %sector_index,
+                %public_key_hash,
+                %error,
+                "Solution was submitted too late"
+            );
+
+            return Err(error);
+        }
+
         let mut solution_response_senders = self.solution_response_senders.lock();
 
         let success = solution_response_senders
@@ -601,12 +1307,134 @@ where
         Ok(())
     }
 
+    fn verify_solution(
+        &self,
+        solution: Solution,
+        slot: SlotNumber,
+    ) -> Result<SolutionVerificationInfo, Error> {
+        let NewSlotInfo {
+            slot,
+            proof_of_time,
+            solution_range,
+            shard_membership_entropy,
+            num_shards,
+        } = self
+            .cached_slot_infos
+            .lock()
+            .peek(&slot)
+            .copied()
+            .ok_or(Error::UnknownSlot { slot })?;
+
+        let failed = |error: SolutionVerifyError| SolutionCheck::Failed {
+            reason: error.to_string(),
+        };
+
+        let global_challenge = proof_of_time.derive_global_challenge(slot);
+        let challenges = SolutionChallenges::derive(&solution, &global_challenge);
+
+        let solution_distance = challenges.solution_distance(&solution);
+        let range_check = match challenges.check_solution_range(&solution, solution_range) {
+            Ok(_solution_distance) => SolutionCheck::Passed,
+            Err(error) => failed(error),
+        };
+
+        let proof_of_space_check = match challenges.check_proof_of_space::<PosTable>(&solution) {
+            Ok(()) => SolutionCheck::Passed,
+            Err(error) => failed(error),
+        };
+
+        let current_history_size = self.next_block_history_size();
+        let sector_expiration = sector_expirations(
+            &self.beacon_chain_info,
+            self.consensus_constants.min_sector_lifetime,
+            current_history_size,
+            &[SectorExpirationRequest {
+                sector_id: challenges.sector_id,
+                history_size: solution.history_size,
+            }],
+        )
+        .first()
+        .map(|sector_expiration_info| sector_expiration_info.expiration);
+        let sector_expiration_check = match sector_expiration {
+            Some(SectorExpiration::Expired {
+                expiration_history_size,
+            }) => failed(SolutionVerifyError::SectorExpired {
+                expiration_history_size,
+                current_history_size,
+            }),
+            Some(SectorExpiration::Undetermined { .. } | SectorExpiration::ExpiresAt { .. }) => {
+                SolutionCheck::Passed
+            }
+            None => failed(SolutionVerifyError::InvalidHistorySize),
+        };
+
+        let stateless_params = SolutionVerifyStatelessParams {
+            shard_index: ShardIndex::BEACON_CHAIN,
+            global_challenge,
+            solution_range,
+            shard_membership_entropy,
+            num_shards,
+        };
+        let full_verification =
+            match self.solution_verify_piece_params(&solution, current_history_size) {
+                Ok(piece_params) => match verify_full::<PosTable>(
+                    &solution,
+                    &SolutionVerifyFullParams {
+                        stateless: stateless_params,
+                        piece: piece_params,
+                    },
+                ) {
+                    Ok(()) => SolutionCheck::Passed,
+                    Err(error) => failed(error),
+                },
+                Err(reason) => match verify_stateless::<PosTable>(&solution, &stateless_params) {
+                    Ok(()) => SolutionCheck::Skipped { reason },
+                    Err(error) => failed(error),
+                },
+            };
+
+        Ok(SolutionVerificationInfo {
+            slot,
+            solution_range,
+            solution_distance,
+            range_check,
+            proof_of_space_check,
+            sector_expiration,
+            sector_expiration_check,
+            full_verification,
+        })
+    }
+
     async fn subscribe_slot_info(
         &self,
         subscription_sink: PendingSubscriptionSink,
+        filter: Option<SlotInfoFilter>,
     ) -> SubscriptionResult {
-        let subscription = subscription_sink.accept().await?;
-        self.slot_info_subscriptions.lock().push(subscription);
+        if let Some(filter) = filter
+            && !filter.is_valid()
+        {
+            subscription_sink
+                .reject(Error::InvalidSlotInfoFilter {
+                    stride: filter.stride.get(),
+                    offset: filter.offset,
+                })
+                .await;
+
+            return Ok(());
+        }
+
+        if let Err(error) = self.check_subscription_limit(subscription_sink.connection_id()) {
+            subscription_sink.reject(error).await;
+
+            return Ok(());
+        }
+
+        let sink = subscription_sink.accept().await?;
+        self.slot_info_subscriptions.add(Subscriber::new(
+            sink,
+            SLOT_SUBSCRIPTIONS_LAG_POLICY,
+            filter,
+        ));
 
         Ok(())
     }
@@ -615,8 +1443,18 @@ where
         &self,
         subscription_sink: PendingSubscriptionSink,
     ) -> SubscriptionResult {
-        let subscription = subscription_sink.accept().await?;
-        self.block_sealing_subscriptions.lock().push(subscription);
+        if let Err(error) = self.check_subscription_limit(subscription_sink.connection_id()) {
+            subscription_sink.reject(error).await;
+
+            return Ok(());
+        }
+
+        let sink = subscription_sink.accept().await?;
+        self.block_sealing_subscriptions.add(Subscriber::new(
+            sink,
+            SLOT_SUBSCRIPTIONS_LAG_POLICY,
+            (),
+        ));
 
         Ok(())
     }
@@ -626,10 +1464,12 @@ where
 
         let mut block_sealing_senders = block_sealing_senders.lock();
 
-        if block_sealing_senders.current_pre_seal_hash == block_seal.pre_seal_hash
-            && let Some(sender) = block_sealing_senders.senders.pop()
+        if let Some(senders) = block_sealing_senders
+            .senders
+            .get_mut(&block_seal.pre_seal_hash)
+            && let Some(sender) = senders.pop()
         {
-            let _: Result<(), _> = sender.send(block_seal.seal);
+            let _: Result<(), _> = sender.try_send(block_seal.seal);
         }
 
         Ok(())
@@ -639,10 +1479,79 @@ where
         &self,
         subscription_sink: PendingSubscriptionSink,
     ) -> SubscriptionResult {
-        let subscription = subscription_sink.accept().await?;
+        if let Err(error) = self.check_subscription_limit(subscription_sink.connection_id()) {
+            subscription_sink.reject(error).await;
+
+            return Ok(());
+        }
+
+        let sink = subscription_sink.accept().await?;
         self.new_super_segment_header_subscriptions
-            .lock()
-            .push(subscription);
+            .add(Subscriber::new(
+                sink,
+                NEW_SUPER_SEGMENT_HEADER_LAG_POLICY,
+                (),
+            ));
+
+        Ok(())
+    }
+
+    async fn subscribe_super_segment_header_feed(
+        &self,
+        subscription_sink: PendingSubscriptionSink,
+        last_seen_super_segment_index: Option<SuperSegmentIndex>,
+    ) -> SubscriptionResult {
+        if let Err(error) = self.check_subscription_limit(subscription_sink.connection_id()) {
+            subscription_sink.reject(error).await;
+
+            return Ok(());
+        }
+
+        if let Some(last_seen_super_segment_index) = last_seen_super_segment_index
+            && let Some(latest) = self.beacon_chain_info.last_super_segment_header()
+        {
+            let num_missing = u64::from(latest.index.as_inner())
+                .saturating_sub(u64::from(last_seen_super_segment_index));
+            let num_missing = usize::try_from(num_missing).unwrap_or(usize::MAX);
+
+            // Too far behind, the farmer is expected to catch up using `superSegmentHeaders` first
+            if num_missing > MAX_SUPER_SEGMENT_HEADERS_PER_REQUEST {
+                subscription_sink
+                    .reject(Error::SuperSegmentHeadersLengthExceeded {
+                        actual: num_missing,
+                    })
+                    .await;
+
+                return Ok(());
+            }
+        }
+
+        let sink = subscription_sink.accept().await?;
+        let mut subscriber = Subscriber::new(
+            sink,
+            CATCH_UP_SUBSCRIPTIONS_LAG_POLICY,
+            SuperSegmentHeaderFeedSubscription::new(last_seen_super_segment_index),
+        );
+
+        subscriber
+            .state
+            .replay(subscriber.sink(), &self.beacon_chain_info)
+            .await?;
+
+        // Deliver headers that were added during replay under the lock, such that the worker can
+        // continue from where the replay has finished without any gaps
+        self.super_segment_header_feed_subscriptions
+            .add_if(subscriber, |subscriber| {
+                let Some(latest) = self.beacon_chain_info.last_super_segment_header() else {
+                    return true;
+                };
+
+                SuperSegmentHeaderFeedSubscription::notify(
+                    subscriber,
+                    &self.beacon_chain_info,
+                    &latest,
+                )
+            });
 
         Ok(())
     }
@@ -717,6 +1626,88 @@ where
             .map(|super_segment_header| super_segment_header.root))
     }
 
+    // Note: inclusion proofs are only available for super segments cached in memory
+    async fn segment_headers_range(
+        &self,
+        first_segment_index: LocalSegmentIndex,
+        limit: u32,
+    ) -> Result<SegmentHeadersRange, Error> {
+        if limit as usize > MAX_PAGE_ITEMS {
+            error!(
+                "Request limit ({}) exceed the server limit: {} ",
+                limit, MAX_PAGE_ITEMS
+            );
+
+            return Err(Error::PageItemsLimitExceeded {
+                actual: limit as usize,
+            });
+        }
+
+        let segment_headers = (first_segment_index..)
+            .take(limit as usize)
+            .map_while(|segment_index| self.beacon_chain_info.get_segment_header(segment_index))
+            .collect::<Vec<_>>();
+
+        let mut page = PageBuilder::new(
+            u64::from(first_segment_index),
+            limit as usize,
+            MAX_PAGE_SIZE,
+        );
+        {
+            let cached_super_segments = self.cached_super_segments.lock();
+
+            for segment_header in segment_headers {
+                let inclusion_proof = cached_super_segments.inclusion_proof(
+                    ShardIndex::BEACON_CHAIN,
+                    segment_header.index.as_inner(),
+                    &segment_header.root,
+                );
+
+                if page.push((segment_header, inclusion_proof)).is_err() {
+                    break;
+                }
+            }
+        }
+        let next_segment_index = first_segment_index + LocalSegmentIndex::from(page.len() as u64);
+        let has_more = page.is_full()
+            || self
+                .beacon_chain_info
+                .get_segment_header(next_segment_index)
+                .is_some();
+        let page = page.finish(has_more);
+        let (segment_headers, inclusion_proofs) =
+            page.items.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
+
+        let first_super_segment_index = inclusion_proofs
+            .iter()
+            .flatten()
+            .map(|inclusion_proof| inclusion_proof.super_segment_index)
+            .min();
+        let last_super_segment_index = self
+            .beacon_chain_info
+            .last_super_segment_header()
+            .map(|super_segment_header| super_segment_header.index.as_inner());
+
+        let super_segment_headers = match (first_super_segment_index, last_super_segment_index) {
+            (Some(first_super_segment_index), Some(last_super_segment_index)) => {
+                (first_super_segment_index..=last_super_segment_index)
+                    .map_while(|super_segment_index| {
+                        self.beacon_chain_info
+                            .get_super_segment_header(super_segment_index)
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(SegmentHeadersRange {
+            segment_headers,
+            inclusion_proofs,
+            super_segment_headers,
+            continuation_token: page.continuation_token,
+        })
+    }
+
     // Note: this RPC uses the cached archived segment, which is only updated by archived segments
     // subscriptions
     async fn piece(&self, piece_index: PieceIndex) -> Result<Option<Piece>, Error> {
@@ -826,6 +1817,7 @@ where
             &self.beacon_chain_info,
             self.erasure_coding.clone(),
             &recreate_segment_super_segment_details,
+            &ObjectMappingPolicy::DISABLED,
             |_| Vec::new(),
         )
         .await?;
@@ -882,6 +1874,21 @@ where
                 .collect::<Vec<_>>()
         };
 
+        let commitments = spawn_blocking({
+            let shard_commitments_roots = Arc::clone(&self.shard_commitments_roots);
+            let shard_membership = shard_membership.clone();
+
+            move || {
+                shard_commitments_roots
+                    .lock()
+                    .commitments(&shard_membership)
+            }
+        })
+        .await?;
+        self.shard_membership_assignments
+            .lock()
+            .update_commitments(commitments);
+
         if let Err(error) = self
             .shard_membership_updates_sender
             .clone()
@@ -893,4 +1900,95 @@ where
 
         Ok(())
     }
+
+    fn shard_assignments(
+        &self,
+        public_key_hashes: Vec<Blake3Hash>,
+    ) -> Result<Vec<FarmerShardAssignment>, Error> {
+        if public_key_hashes.len() > MAX_SHARD_ASSIGNMENTS_PER_REQUEST {
+            error!(
+                "`public_key_hashes` length exceed the limit: {} ",
+                public_key_hashes.len()
+            );
+
+            return Err(Error::ShardAssignmentsLengthExceeded {
+                actual: public_key_hashes.len(),
+            });
+        }
+
+        let shard_membership_assignments = self.shard_membership_assignments.lock();
+
+        Ok(public_key_hashes
+            .iter()
+            .flat_map(|public_key_hash| shard_membership_assignments.assignments(public_key_hash))
+            .copied()
+            .collect())
+    }
+
+    fn sector_expirations(
+        &self,
+        sectors: Vec<SectorExpirationRequest>,
+    ) -> Result<Vec<SectorExpirationInfo>, Error> {
+        if sectors.len() > MAX_SECTOR_EXPIRATIONS_PER_REQUEST {
+            error!("`sectors` length exceed the limit: {} ", sectors.len());
+
+            return Err(Error::SectorExpirationsLengthExceeded {
+                actual: sectors.len(),
+            });
+        }
+
+        Ok(sector_expirations(
+            &self.beacon_chain_info,
+            self.consensus_constants.min_sector_lifetime,
+            current_history_size(&self.beacon_chain_info),
+            &sectors,
+        ))
+    }
+
+    async fn subscribe_sector_expirations(
+        &self,
+        subscription_sink: PendingSubscriptionSink,
+        sectors: Vec<SectorExpirationRequest>,
+    ) -> SubscriptionResult {
+        if sectors.len() > MAX_SECTOR_EXPIRATIONS_PER_REQUEST {
+            subscription_sink
+                .reject(Error::SectorExpirationsLengthExceeded {
+                    actual: sectors.len(),
+                })
+                .await;
+
+            return Ok(());
+        }
+
+        if let Err(error) = self.check_subscription_limit(subscription_sink.connection_id()) {
+            subscription_sink.reject(error).await;
+
+            return Ok(());
+        }
+
+        let sink = subscription_sink.accept().await?;
+        let mut subscriber = Subscriber::new(
+            sink,
+            CATCH_UP_SUBSCRIPTIONS_LAG_POLICY,
+            SectorExpirationSubscription::new(sectors),
+        );
+        let subscription = &mut subscriber.state;
+
+        // Send initial expirations right away, later only changes will be sent
+        let current_history_size = current_history_size(&self.beacon_chain_info);
+        let expirations = sector_expirations(
+            &self.beacon_chain_info,
+            self.consensus_constants.min_sector_lifetime,
+            current_history_size,
+            &subscription.sectors,
+        );
+        let expirations = subscription.changed(expirations, current_history_size);
+        let expirations = serde_json::value::to_raw_value(&expirations)
+            .expect("Serialization of sector expirations never fails; qed");
+        subscriber.sink().send(expirations).await?;
+
+        self.sector_expiration_subscriptions.add(subscriber);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,152 @@
+//! Sector expiration tracking for farmers.
+//!
+//! Farmers can both query expiration of their sectors on demand and subscribe to notifications,
+//! in which case they are notified proactively as history grows and expiration of sectors is
+//! determined or replotting becomes due.
+
+#[cfg(test)]
+mod tests;
+
+use ab_client_api::BeaconChainInfo;
+use ab_core_primitives::sectors::{SectorExpiration, SectorId};
+use ab_core_primitives::segments::{HistorySize, SegmentIndex};
+use ab_farmer_rpc_primitives::{SectorExpirationInfo, SectorExpirationRequest};
+use std::collections::HashMap;
+use std::mem;
+use tracing::warn;
+
+/// Current history size according to the last super segment header
+pub(crate) fn current_history_size<BCI>(beacon_chain_info: &BCI) -> HistorySize
+where
+    BCI: BeaconChainInfo,
+{
+    let max_segment_index = beacon_chain_info
+        .last_super_segment_header()
+        .map_or(SegmentIndex::ZERO, |super_segment_header| {
+            super_segment_header.max_segment_index.as_inner()
+        });
+
+    HistorySize::from(max_segment_index)
+}
+
+/// Derive expiration of requested sectors as of `current_history_size`.
+///
+/// Sectors with invalid history size (that cause overflow during derivation) are skipped.
+pub(crate) fn sector_expirations<BCI>(
+    beacon_chain_info: &BCI,
+    min_sector_lifetime: HistorySize,
+    current_history_size: HistorySize,
+    sectors: &[SectorExpirationRequest],
+) -> Vec<SectorExpirationInfo>
+where
+    BCI: BeaconChainInfo,
+{
+    sectors
+        .iter()
+        .filter_map(|sector| {
+            let expiration_check_history_size = sector
+                .history_size
+                .sector_expiration_check(min_sector_lifetime)?;
+            let sector_expiration_check_super_segment_root = beacon_chain_info
+                .get_super_segment_header_for_segment_index(
+                    expiration_check_history_size.segment_index(),
+                )
+                .map(|super_segment_header| super_segment_header.root);
+
+            let Some(expiration) = sector.sector_id.derive_expiration(
+                sector.history_size,
+                min_sector_lifetime,
+                current_history_size,
+                sector_expiration_check_super_segment_root.as_ref(),
+            ) else {
+                warn!(
+                    sector_id = ?sector.sector_id,
+                    history_size = %sector.history_size,
+                    "Failed to derive sector expiration, invalid history size"
+                );
+                return None;
+            };
+
+            Some(SectorExpirationInfo {
+                sector_id: sector.sector_id,
+                expiration,
+            })
+        })
+        .collect()
+}
+
+/// State of a subscription to expiration of a set of sectors
+#[derive(Debug)]
+pub(crate) struct SectorExpirationSubscription {
+    pub(crate) sectors: Vec<SectorExpirationRequest>,
+    /// Last notified expiration of each sector and whether replotting was due at that point
+    last_notified: HashMap<SectorId, (SectorExpiration, bool)>,
+    /// Changes that were not delivered due to slow receiver, will be coalesced with the next
+    /// notification
+    pending: Vec<SectorExpirationInfo>,
+}
+
+impl SectorExpirationSubscription {
+    pub(crate) fn new(sectors: Vec<SectorExpirationRequest>) -> Self {
+        Self {
+            sectors,
+            last_notified: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Filter expirations down to those that changed since the last notification (including
+    /// replotting becoming due) and remember them as notified
+    pub(crate) fn changed(
+        &mut self,
+        expirations: Vec<SectorExpirationInfo>,
+        current_history_size: HistorySize,
+    ) -> Vec<SectorExpirationInfo> {
+        expirations
+            .into_iter()
+            .filter(|info| {
+                let state = (
+                    info.expiration,
+                    info.expiration.should_replot(current_history_size),
+                );
+
+                self.last_notified.insert(info.sector_id, state) != Some(state)
+            })
+            .collect()
+    }
+
+    /// Coalesce changes with those that were not delivered previously (if any) into a single
+    /// notification, newer changes of the same sector take precedence
+    pub(crate) fn take_notification(
+        &mut self,
+        changed: Vec<SectorExpirationInfo>,
+    ) -> Vec<SectorExpirationInfo> {
+        if self.pending.is_empty() {
+            return changed;
+        }
+
+        let mut notification = mem::take(&mut self.pending);
+        let mut sector_offsets = notification
+            .iter()
+            .enumerate()
+            .map(|(offset, info)| (info.sector_id, offset))
+            .collect::<HashMap<_, _>>();
+
+        for info in changed {
+            if let Some(&offset) = sector_offsets.get(&info.sector_id) {
+                notification[offset] = info;
+            } else {
+                sector_offsets.insert(info.sector_id, notification.len());
+                notification.push(info);
+            }
+        }
+
+        notification
+    }
+
+    /// Store notification that was not delivered due to slow receiver to be coalesced with the
+    /// next one
+    pub(crate) fn defer_notification(&mut self, notification: Vec<SectorExpirationInfo>) {
+        self.pending = notification;
+    }
+}
@@ -0,0 +1,57 @@
+use crate::sector_expiration::SectorExpirationSubscription;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::sectors::{SectorExpiration, SectorId, SectorIndex};
+use ab_core_primitives::segments::HistorySize;
+use ab_core_primitives::solutions::ShardCommitmentHash;
+use ab_farmer_rpc_primitives::SectorExpirationInfo;
+use std::num::NonZeroU64;
+
+fn history_size(value: u64) -> HistorySize {
+    HistorySize::new(NonZeroU64::new(value).unwrap())
+}
+
+fn sector_id(sector_index: u16) -> SectorId {
+    SectorId::new(
+        &Blake3Hash::default(),
+        &ShardCommitmentHash::default(),
+        SectorIndex::from(sector_index),
+        history_size(1),
+    )
+}
+
+fn info(sector_index: u16, expiration_history_size: u64) -> SectorExpirationInfo {
+    SectorExpirationInfo {
+        sector_id: sector_id(sector_index),
+        expiration: SectorExpiration::ExpiresAt {
+            expiration_history_size: history_size(expiration_history_size),
+        },
+    }
+}
+
+#[test]
+fn take_notification() {
+    let mut subscription = SectorExpirationSubscription::new(Vec::new());
+
+    // Nothing pending, changes are returned as is
+    assert_eq!(
+        subscription.take_notification(vec![info(0, 10), info(1, 10)]),
+        vec![info(0, 10), info(1, 10)]
+    );
+
+    // Pending changes are merged with new ones, newer changes of the same sector win
+    subscription.defer_notification(vec![info(0, 10), info(1, 10)]);
+    assert_eq!(
+        subscription.take_notification(vec![info(1, 20), info(2, 20)]),
+        vec![info(0, 10), info(1, 20), info(2, 20)]
+    );
+
+    // Pending changes are only delivered once
+    assert_eq!(subscription.take_notification(Vec::new()), Vec::new());
+
+    // Pending changes are delivered even if there are no new changes
+    subscription.defer_notification(vec![info(0, 30)]);
+    assert_eq!(
+        subscription.take_notification(Vec::new()),
+        vec![info(0, 30)]
+    );
+}
@@ -0,0 +1,167 @@
+//! Shard membership assignment engine.
+//!
+//! Converts shard membership entropy of the current shard rotation era and farmer commitments into
+//! concrete shard assignments that farmers can query over RPC.
+
+#[cfg(test)]
+mod tests;
+
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::segments::HistorySize;
+use ab_core_primitives::shard::NumShards;
+use ab_core_primitives::solutions::{ShardCommitmentHash, ShardMembershipEntropy};
+use ab_farmer_components::shard_commitment::derive_shard_commitments_root;
+use ab_farmer_rpc_primitives::{FarmerShardAssignment, FarmerShardMembershipInfo};
+use schnellru::{ByLength, LruMap};
+use std::collections::HashMap;
+
+/// Shard commitments roots are expensive to derive, so they are cached for reuse across shard
+/// rotation eras and shard membership info updates
+const SHARD_COMMITMENTS_ROOTS_CACHE_SIZE: u32 = 1024;
+
+/// Farmer commitment used for shard membership assignment
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct ShardMembershipCommitment {
+    /// Public key hash of the plot identity
+    pub(crate) public_key_hash: Blake3Hash,
+    /// Shard commitments root for `history_size`
+    pub(crate) shard_commitments_root: ShardCommitmentHash,
+    /// History size
+    pub(crate) history_size: HistorySize,
+}
+
+/// Shard membership parameters of a shard rotation era
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct ShardMembershipEra {
+    /// Shard membership entropy
+    pub(crate) shard_membership_entropy: ShardMembershipEntropy,
+    /// The number of shards in the network
+    pub(crate) num_shards: NumShards,
+}
+
+impl ShardMembershipEra {
+    /// Derive shard assignment for a single commitment
+    pub(crate) fn assign(&self, commitment: &ShardMembershipCommitment) -> FarmerShardAssignment {
+        let shard_index = self.num_shards.derive_shard_index(
+            &commitment.public_key_hash,
+            &commitment.shard_commitments_root,
+            &self.shard_membership_entropy,
+            commitment.history_size,
+        );
+
+        FarmerShardAssignment {
+            public_key_hash: commitment.public_key_hash,
+            history_size: commitment.history_size,
+            shard_membership_entropy: self.shard_membership_entropy,
+            shard_index,
+        }
+    }
+}
+
+/// Cache of shard commitments roots derived from farmer shard membership info
+#[derive(Debug)]
+pub(crate) struct ShardCommitmentsRoots {
+    lru: LruMap<(Blake3Hash, HistorySize), ShardCommitmentHash, ByLength>,
+}
+
+impl Default for ShardCommitmentsRoots {
+    fn default() -> Self {
+        Self {
+            lru: LruMap::new(ByLength::new(SHARD_COMMITMENTS_ROOTS_CACHE_SIZE)),
+        }
+    }
+}
+
+impl ShardCommitmentsRoots {
+    /// Convert farmer shard membership info into commitments, deriving shard commitments roots
+    /// where necessary.
+    ///
+    /// NOTE: Derivation of roots is CPU-intensive, this should be called from a blocking task.
+    pub(crate) fn commitments(
+        &mut self,
+        info: &[FarmerShardMembershipInfo],
+    ) -> Vec<ShardMembershipCommitment> {
+        info.iter()
+            .flat_map(|info| {
+                info.history_sizes
+                    .iter()
+                    .map(move |&history_size| (info, history_size))
+            })
+            .map(|(info, history_size)| {
+                let shard_commitments_seed = info.shard_commitments_seed;
+                // NOTE: See https://github.com/koute/schnellru/issues/7 for an explanation of the
+                // `Option` return type
+                let shard_commitments_root = *self
+                    .lru
+                    .get_or_insert((shard_commitments_seed, history_size), || {
+                        derive_shard_commitments_root(&shard_commitments_seed, history_size)
+                    })
+                    .expect("Not limited by memory; qed");
+
+                ShardMembershipCommitment {
+                    public_key_hash: info.public_key_hash,
+                    shard_commitments_root,
+                    history_size,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Shard membership assignment engine.
+///
+/// Assignments are recomputed whenever either the shard rotation era or the set of farmer
+/// commitments changes, reads are cheap lookups.
+#[derive(Debug, Default)]
+pub(crate) struct ShardMembershipAssignments {
+    era: Option<ShardMembershipEra>,
+    commitments: Vec<ShardMembershipCommitment>,
+    assignments: HashMap<Blake3Hash, Vec<FarmerShardAssignment>>,
+}
+
+impl ShardMembershipAssignments {
+    /// Update shard rotation era.
+    ///
+    /// Returns `true` if the era has changed and assignments were recomputed.
+    pub(crate) fn update_era(&mut self, era: ShardMembershipEra) -> bool {
+        if self.era == Some(era) {
+            return false;
+        }
+
+        self.era.replace(era);
+        self.recompute();
+
+        true
+    }
+
+    /// Replace farmer commitments with a new set
+    pub(crate) fn update_commitments(&mut self, commitments: Vec<ShardMembershipCommitment>) {
+        self.commitments = commitments;
+        self.recompute();
+    }
+
+    /// Shard assignments of a farmer with specified public key hash.
+    ///
+    /// Empty slice is returned for unknown farmers or when the era is not known yet.
+    pub(crate) fn assignments(&self, public_key_hash: &Blake3Hash) -> &[FarmerShardAssignment] {
+        self.assignments
+            .get(public_key_hash)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn recompute(&mut self) {
+        self.assignments.clear();
+
+        let Some(era) = self.era else {
+            return;
+        };
+
+        for commitment in &self.commitments {
+            self.assignments
+                .entry(commitment.public_key_hash)
+                .or_default()
+                .push(era.assign(commitment));
+        }
+    }
+}
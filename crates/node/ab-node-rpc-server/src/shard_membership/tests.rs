@@ -0,0 +1,147 @@
+use crate::shard_membership::{
+    ShardMembershipAssignments, ShardMembershipCommitment, ShardMembershipEra,
+};
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::segments::HistorySize;
+use ab_core_primitives::shard::{NumShards, ShardIndex};
+use ab_core_primitives::solutions::{ShardCommitmentHash, ShardMembershipEntropy};
+use std::collections::HashMap;
+use std::num::{NonZeroU16, NonZeroU64};
+
+fn num_shards() -> NumShards {
+    NumShards::new(NonZeroU16::new(4).unwrap(), NonZeroU16::new(4).unwrap()).unwrap()
+}
+
+fn commitment(index: u64, history_size: u64) -> ShardMembershipCommitment {
+    let mut public_key_hash = [0; Blake3Hash::SIZE];
+    public_key_hash[..size_of::<u64>()].copy_from_slice(&index.to_le_bytes());
+    let mut shard_commitments_root = [0xff; ShardCommitmentHash::SIZE];
+    shard_commitments_root[..size_of::<u64>()].copy_from_slice(&index.to_le_bytes());
+
+    ShardMembershipCommitment {
+        public_key_hash: Blake3Hash::new(public_key_hash),
+        shard_commitments_root: ShardCommitmentHash::new(shard_commitments_root),
+        history_size: HistorySize::new(NonZeroU64::new(history_size).unwrap()),
+    }
+}
+
+fn era(entropy: u8) -> ShardMembershipEra {
+    ShardMembershipEra {
+        shard_membership_entropy: ShardMembershipEntropy::new([entropy; _]),
+        num_shards: num_shards(),
+    }
+}
+
+#[test]
+fn assignment_stability() {
+    let commitments = (0..100)
+        .map(|index| commitment(index, 10))
+        .collect::<Vec<_>>();
+
+    let mut assignments = ShardMembershipAssignments::default();
+    assignments.update_commitments(commitments.clone());
+
+    // Nothing is assigned until era is known
+    assert!(
+        assignments
+            .assignments(&commitments[0].public_key_hash)
+            .is_empty()
+    );
+
+    assert!(assignments.update_era(era(1)));
+    // Same era doesn't cause recomputation
+    assert!(!assignments.update_era(era(1)));
+
+    let before = commitments
+        .iter()
+        .map(|commitment| assignments.assignments(&commitment.public_key_hash)[0])
+        .collect::<Vec<_>>();
+
+    // All farmers are assigned to leaf shards
+    let leaf_shards = num_shards().iter_leaf_shards().collect::<Vec<_>>();
+    for assignment in &before {
+        assert!(leaf_shards.contains(&assignment.shard_index));
+    }
+
+    // Adding more farmers doesn't affect existing assignments
+    assignments.update_commitments(
+        commitments
+            .iter()
+            .copied()
+            .chain((100..200).map(|index| commitment(index, 10)))
+            .collect(),
+    );
+    for (commitment, assignment) in commitments.iter().zip(&before) {
+        assert_eq!(
+            assignments.assignments(&commitment.public_key_hash),
+            &[*assignment]
+        );
+    }
+
+    // Same era results in the same assignments
+    let mut other_assignments = ShardMembershipAssignments::default();
+    other_assignments.update_era(era(1));
+    other_assignments.update_commitments(commitments.clone());
+    for (commitment, assignment) in commitments.iter().zip(&before) {
+        assert_eq!(
+            other_assignments.assignments(&commitment.public_key_hash),
+            &[*assignment]
+        );
+    }
+
+    // The next era reshuffles farmers
+    assert!(assignments.update_era(era(2)));
+    let num_moved = commitments
+        .iter()
+        .zip(&before)
+        .filter(|(commitment, assignment)| {
+            assignments.assignments(&commitment.public_key_hash)[0].shard_index
+                != assignment.shard_index
+        })
+        .count();
+    assert!(num_moved > commitments.len() / 2);
+}
+
+#[test]
+fn assignment_per_history_size() {
+    let mut assignments = ShardMembershipAssignments::default();
+    assignments.update_era(era(1));
+    assignments.update_commitments(vec![commitment(0, 10), commitment(0, 11)]);
+
+    let farmer_assignments = assignments.assignments(&commitment(0, 10).public_key_hash);
+    assert_eq!(farmer_assignments.len(), 2);
+    assert_eq!(
+        farmer_assignments[0].history_size,
+        HistorySize::new(NonZeroU64::new(10).unwrap())
+    );
+    assert_eq!(
+        farmer_assignments[1].history_size,
+        HistorySize::new(NonZeroU64::new(11).unwrap())
+    );
+}
+
+#[test]
+fn assignment_distribution_uniformity() {
+    const NUM_FARMERS: u64 = 16_000;
+
+    let num_shards = num_shards();
+    let era = era(1);
+
+    let mut per_shard = HashMap::<ShardIndex, u64>::new();
+    for index in 0..NUM_FARMERS {
+        let assignment = era.assign(&commitment(index, 10));
+        *per_shard.entry(assignment.shard_index).or_default() += 1;
+    }
+
+    // Every leaf shard has farmers assigned to it
+    assert_eq!(per_shard.len(), num_shards.leaf_shards().get() as usize);
+
+    let expected = NUM_FARMERS / u64::from(num_shards.leaf_shards().get());
+    for (shard_index, count) in per_shard {
+        // Allow 20% deviation from a perfectly uniform distribution
+        assert!(
+            count.abs_diff(expected) < expected / 5,
+            "Shard {shard_index} has {count} farmers assigned, expected ~{expected}"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Read-only node status served as a JSON document over plain HTTP.
+//!
+//! This is separate from the farmer RPC and intended for lightweight dashboards and load balancer
+//! health checks that don't want to speak WebSocket JSON-RPC. Only a tiny subset of HTTP/1.1 is
+//! supported: `GET` requests of `/status` (full status document) and `/health` (`200 OK` when the
+//! node is synced, `503 Service Unavailable` otherwise), every connection is closed after a single
+//! response.
+
+use ab_client_api::{BeaconChainInfo, BlockId, BlockTag, ChainSyncStatus, resolve_block_id};
+use ab_client_archiving::task::{ArchiverProgress, SegmentStats};
+use ab_core_primitives::block::BlockNumber;
+use ab_farmer_rpc_primitives::{ArchiverProgressInfo, BlockInfo, SegmentStatsInfo};
+use serde::Serialize;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+/// Max size of the request head, requests with larger heads are rejected
+const MAX_REQUEST_HEAD_SIZE: usize = 8 * 1024;
+/// Time given to the client to send the request and receive the response
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Resource usage of the node process
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUsageInfo {
+    /// CPU time spent in user mode
+    pub user_cpu_time: Duration,
+    /// CPU time spent in kernel mode
+    pub system_cpu_time: Duration,
+    /// Peak resident memory size in bytes
+    pub max_resident_memory: u64,
+}
+
+impl ResourceUsageInfo {
+    /// Resource usage of the current process, `None` if not supported on this platform
+    #[cfg(unix)]
+    fn current() -> Option<Self> {
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+        // SAFETY: Valid pointer to `rusage` struct that is initialized on success
+        if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+            return None;
+        }
+        // SAFETY: Initialized by successful `getrusage()` call above
+        let usage = unsafe { usage.assume_init() };
+
+        let timeval_to_duration = |timeval: libc::timeval| {
+            Duration::from_secs(u64::try_from(timeval.tv_sec).unwrap_or_default())
+                + Duration::from_micros(u64::try_from(timeval.tv_usec).unwrap_or_default())
+        };
+        let max_resident_memory = u64::try_from(usage.ru_maxrss).unwrap_or_default();
+        // Linux and most other platforms report it in kilobytes, while macOS uses bytes
+        let max_resident_memory = if cfg!(target_os = "macos") {
+            max_resident_memory
+        } else {
+            max_resident_memory.saturating_mul(1024)
+        };
+
+        Some(Self {
+            user_cpu_time: timeval_to_duration(usage.ru_utime),
+            system_cpu_time: timeval_to_duration(usage.ru_stime),
+            max_resident_memory,
+        })
+    }
+
+    /// Resource usage of the current process, `None` if not supported on this platform
+    #[cfg(not(unix))]
+    fn current() -> Option<Self> {
+        None
+    }
+}
+
+/// Node status document served by [`StatusServer`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatusDocument {
+    /// Whether node is syncing right now
+    pub syncing: bool,
+    /// Best block
+    pub best_block: BlockInfo,
+    /// The latest confirmed block, `None` if the chain is not deep enough yet
+    pub confirmed_block: Option<BlockInfo>,
+    /// Archiver progress, `None` if not available
+    pub archiver: Option<ArchiverProgressInfo>,
+    /// Statistics of the last archived segment, `None` if no segment was archived since the node
+    /// started or segment statistics are not available
+    pub last_segment_stats: Option<SegmentStatsInfo>,
+    /// Number of connected peers, `None` if not available
+    pub connected_peers: Option<usize>,
+    /// Resource usage of the node process, `None` if not available
+    pub resource_usage: Option<ResourceUsageInfo>,
+}
+
+/// Status server configuration
+#[derive(Debug)]
+pub struct StatusServerConfig<BCI, CSS> {
+    /// IP and port (TCP) on which to listen for HTTP requests
+    pub listen_on: SocketAddr,
+    /// Beacon chain info
+    pub beacon_chain_info: BCI,
+    /// Chain sync status
+    pub chain_sync_status: CSS,
+    /// Depth after which blocks are considered confirmed
+    pub block_confirmation_depth: BlockNumber,
+    /// Archiver progress
+    pub archiver_progress: Option<watch::Receiver<ArchiverProgress>>,
+    /// Statistics of the last archived segment
+    pub last_segment_stats: Option<watch::Receiver<Option<SegmentStats>>>,
+    /// Number of connected peers
+    pub connected_peers: Option<watch::Receiver<usize>>,
+}
+
+/// HTTP server that serves node status as a JSON document
+#[derive(Debug)]
+pub struct StatusServer<BCI, CSS> {
+    listener: TcpListener,
+    beacon_chain_info: BCI,
+    chain_sync_status: CSS,
+    block_confirmation_depth: BlockNumber,
+    archiver_progress: Option<watch::Receiver<ArchiverProgress>>,
+    last_segment_stats: Option<watch::Receiver<Option<SegmentStats>>>,
+    connected_peers: Option<watch::Receiver<usize>>,
+}
+
+impl<BCI, CSS> StatusServer<BCI, CSS>
+where
+    BCI: BeaconChainInfo,
+    CSS: ChainSyncStatus,
+{
+    /// Create a new status server listening on the configured address
+    pub async fn new(config: StatusServerConfig<BCI, CSS>) -> io::Result<Self> {
+        let listener = TcpListener::bind(config.listen_on).await?;
+
+        let address = listener.local_addr()?;
+        info!(%address, "Started node status server");
+
+        Ok(Self {
+            listener,
+            beacon_chain_info: config.beacon_chain_info,
+            chain_sync_status: config.chain_sync_status,
+            block_confirmation_depth: config.block_confirmation_depth,
+            archiver_progress: config.archiver_progress,
+            last_segment_stats: config.last_segment_stats,
+            connected_peers: config.connected_peers,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Current node status
+    pub fn status(&self) -> NodeStatusDocument {
+        let best_header = self.beacon_chain_info.best_header();
+        let best_header = best_header.header();
+        let confirmed_block = resolve_block_id(
+            &self.beacon_chain_info,
+            BlockId::Tag(BlockTag::Confirmed),
+            self.block_confirmation_depth,
+        )
+        .map(|header| {
+            let header = header.header();
+
+            BlockInfo {
+                root: *header.root(),
+                number: header.prefix.number,
+            }
+        });
+
+        NodeStatusDocument {
+            syncing: self.chain_sync_status.is_syncing(),
+            best_block: BlockInfo {
+                root: *best_header.root(),
+                number: best_header.prefix.number,
+            },
+            confirmed_block,
+            archiver: self
+                .archiver_progress
+                .as_ref()
+                .map(|archiver_progress| archiver_progress_info(*archiver_progress.borrow())),
+            last_segment_stats: self
+                .last_segment_stats
+                .as_ref()
+                .and_then(|last_segment_stats| *last_segment_stats.borrow())
+                .map(segment_stats_info),
+            connected_peers: self
+                .connected_peers
+                .as_ref()
+                .map(|connected_peers| *connected_peers.borrow()),
+            resource_usage: ResourceUsageInfo::current(),
+        }
+    }
+
+    /// Serve requests until the listener fails
+    pub async fn run(self) {
+        loop {
+            let (stream, peer_address) = match self.listener.accept().await {
+                Ok(result) => result,
+                Err(error) => {
+                    warn!(%error, "Failed to accept status server connection");
+                    continue;
+                }
+            };
+
+            // Requests are tiny and responses are generated synchronously, so connections are
+            // handled one at a time with a timeout to not let slow clients block others
+            match tokio::time::timeout(CONNECTION_TIMEOUT, self.handle_connection(stream)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => {
+                    debug!(%error, %peer_address, "Failed to handle status server connection");
+                }
+                Err(_elapsed) => {
+                    debug!(%peer_address, "Status server connection timed out");
+                }
+            }
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        let mut request_head = Vec::with_capacity(1024);
+        let mut buffer = [0; 1024];
+        while !request_head.windows(4).any(|window| window == b"\r\n\r\n") {
+            if request_head.len() >= MAX_REQUEST_HEAD_SIZE {
+                return write_response(&mut stream, "431 Request Header Fields Too Large", None)
+                    .await;
+            }
+
+            let read = stream.read(&mut buffer).await?;
+            if read == 0 {
+                return Ok(());
+            }
+            request_head.extend_from_slice(&buffer[..read]);
+        }
+
+        let request_line = request_head
+            .split(|&byte| byte == b'\r')
+            .next()
+            .unwrap_or_default();
+        let mut request_line = request_line.split(|&byte| byte == b' ');
+        let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+            return write_response(&mut stream, "400 Bad Request", None).await;
+        };
+
+        if method != b"GET" {
+            return write_response(&mut stream, "405 Method Not Allowed", None).await;
+        }
+
+        match path {
+            b"/status" => {
+                let status = serde_json::to_string(&self.status())
+                    .expect("Serialization of node status never fails; qed");
+                write_response(&mut stream, "200 OK", Some(&status)).await
+            }
+            b"/health" => {
+                let status = if self.chain_sync_status.is_syncing() {
+                    "503 Service Unavailable"
+                } else {
+                    "200 OK"
+                };
+                write_response(&mut stream, status, None).await
+            }
+            _ => write_response(&mut stream, "404 Not Found", None).await,
+        }
+    }
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    maybe_json_body: Option<&str>,
+) -> io::Result<()> {
+    let response = match maybe_json_body {
+        Some(body) => format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+            Cache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        ),
+        None => format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+pub(crate) fn archiver_progress_info(archiver_progress: ArchiverProgress) -> ArchiverProgressInfo {
+    let ArchiverProgress {
+        caught_up,
+        current_block,
+        target_block,
+        segments_produced,
+        eta,
+    } = archiver_progress;
+
+    ArchiverProgressInfo {
+        caught_up,
+        current_block,
+        target_block,
+        segments_produced,
+        eta,
+    }
+}
+
+pub(crate) fn segment_stats_info(segment_stats: SegmentStats) -> SegmentStatsInfo {
+    let SegmentStats {
+        segment_index,
+        blocks,
+        bytes,
+        encoding_time,
+        erasure_coding_time,
+        acknowledgement_wait_time,
+    } = segment_stats;
+
+    SegmentStatsInfo {
+        segment_index,
+        blocks,
+        bytes,
+        encoding_time,
+        erasure_coding_time,
+        acknowledgement_wait_time,
+    }
+}
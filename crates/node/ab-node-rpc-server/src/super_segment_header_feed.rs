@@ -0,0 +1,143 @@
+//! Gapless feed of super segment headers for farmers.
+//!
+//! Every delivered header refers to the index of the header delivered before it, such that
+//! farmers can detect gaps. Headers missed while disconnected are replayed on resubscription
+//! starting after the last index seen by the farmer, while headers that were not delivered due to
+//! a slow receiver are delivered along with the next notification.
+
+use ab_client_api::BeaconChainInfo;
+use ab_core_primitives::segments::{SuperSegmentHeader, SuperSegmentIndex};
+use ab_farmer_rpc_primitives::SuperSegmentHeaderFeedItem;
+use ab_node_rpc_subscriptions::{Delivery, Subscriber};
+use jsonrpsee::SubscriptionSink;
+use jsonrpsee::core::SubscriptionResult;
+use serde_json::value::RawValue;
+use tracing::{debug, warn};
+
+/// State of a subscription to the super segment header feed
+#[derive(Debug)]
+pub(crate) struct SuperSegmentHeaderFeedSubscription {
+    /// Index of the last delivered super segment header or the last seen index supplied on
+    /// subscription
+    last_delivered: Option<SuperSegmentIndex>,
+}
+
+impl SuperSegmentHeaderFeedSubscription {
+    pub(crate) fn new(last_seen: Option<SuperSegmentIndex>) -> Self {
+        Self {
+            last_delivered: last_seen,
+        }
+    }
+
+    /// Deliver all super segment headers up to the latest one known to the node, waiting for the
+    /// receiver if necessary
+    pub(crate) async fn replay<BCI>(
+        &mut self,
+        sink: &SubscriptionSink,
+        beacon_chain_info: &BCI,
+    ) -> SubscriptionResult
+    where
+        BCI: BeaconChainInfo,
+    {
+        let Some(latest) = beacon_chain_info.last_super_segment_header() else {
+            return Ok(());
+        };
+
+        while let Some(super_segment_header) = self.next_header(sink, beacon_chain_info, &latest) {
+            sink.send(self.item(super_segment_header)).await?;
+            self.last_delivered
+                .replace(super_segment_header.index.as_inner());
+        }
+
+        Ok(())
+    }
+
+    /// Deliver all super segment headers up to `latest` without waiting for the receiver.
+    ///
+    /// Headers that were not delivered due to a slow receiver are delivered with the next
+    /// notification. Returns `false` if the subscription is closed.
+    pub(crate) fn notify<BCI>(
+        subscriber: &mut Subscriber<Self>,
+        beacon_chain_info: &BCI,
+        latest: &SuperSegmentHeader,
+    ) -> bool
+    where
+        BCI: BeaconChainInfo,
+    {
+        while let Some(super_segment_header) =
+            subscriber
+                .state
+                .next_header(subscriber.sink(), beacon_chain_info, latest)
+        {
+            match subscriber.send(subscriber.state.item(super_segment_header)) {
+                Delivery::Delivered => {
+                    subscriber
+                        .state
+                        .last_delivered
+                        .replace(super_segment_header.index.as_inner());
+                }
+                Delivery::Dropped(_item) => {
+                    debug!(
+                        subscription_id = ?subscriber.sink().subscription_id(),
+                        "Super segment header feed receiver is too slow, remaining headers \
+                        will be delivered with the next notification"
+                    );
+                    break;
+                }
+                Delivery::Closed => {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Next super segment header to deliver, `None` if all headers up to `latest` were delivered
+    /// already.
+    ///
+    /// Without anything delivered so far, the feed starts with `latest`.
+    fn next_header<BCI>(
+        &self,
+        sink: &SubscriptionSink,
+        beacon_chain_info: &BCI,
+        latest: &SuperSegmentHeader,
+    ) -> Option<SuperSegmentHeader>
+    where
+        BCI: BeaconChainInfo,
+    {
+        let latest_index = latest.index.as_inner();
+        let next_index = match self.last_delivered {
+            Some(last_delivered) if last_delivered >= latest_index => {
+                return None;
+            }
+            Some(last_delivered) => last_delivered + SuperSegmentIndex::ONE,
+            None => latest_index,
+        };
+
+        if next_index == latest_index {
+            return Some(*latest);
+        }
+
+        let maybe_super_segment_header = beacon_chain_info.get_super_segment_header(next_index);
+        if maybe_super_segment_header.is_none() {
+            warn!(
+                subscription_id = ?sink.subscription_id(),
+                super_segment_index = %next_index,
+                "Super segment header is missing, can't deliver it to the feed"
+            );
+        }
+
+        maybe_super_segment_header
+    }
+
+    fn item(&self, super_segment_header: SuperSegmentHeader) -> Box<RawValue> {
+        let item = SuperSegmentHeaderFeedItem {
+            previous_super_segment_index: self.last_delivered,
+            super_segment_header,
+        };
+
+        serde_json::value::to_raw_value(&item)
+            .expect("Serialization of super segment header feed item never fails; qed")
+    }
+}
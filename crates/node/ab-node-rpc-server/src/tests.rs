@@ -0,0 +1,48 @@
+use crate::{Error, check_solution_deadline};
+use ab_core_primitives::pot::SlotNumber;
+
+fn block_authoring_delay() -> SlotNumber {
+    SlotNumber::from(4)
+}
+
+#[test]
+fn solution_at_deadline_is_accepted() {
+    let slot = SlotNumber::from(10);
+
+    // Current slot is not known yet
+    check_solution_deadline(slot, None, block_authoring_delay()).unwrap();
+    // Solution for a future slot
+    check_solution_deadline(slot, Some(SlotNumber::from(9)), block_authoring_delay()).unwrap();
+    // Solution for the current slot
+    check_solution_deadline(slot, Some(slot), block_authoring_delay()).unwrap();
+    // Exactly at the deadline
+    check_solution_deadline(
+        slot,
+        Some(slot + block_authoring_delay()),
+        block_authoring_delay(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn solution_after_deadline_is_rejected() {
+    let slot = SlotNumber::from(10);
+
+    for slots_late in [1, 5] {
+        let current_slot = slot + block_authoring_delay() + SlotNumber::from(slots_late);
+        let error =
+            check_solution_deadline(slot, Some(current_slot), block_authoring_delay()).unwrap_err();
+
+        let Error::SolutionTooLate {
+            slot: error_slot,
+            current_slot: error_current_slot,
+            slots_late: error_slots_late,
+        } = error
+        else {
+            panic!("Unexpected error: {error}");
+        };
+        assert_eq!(error_slot, slot);
+        assert_eq!(error_current_slot, current_slot);
+        assert_eq!(error_slots_late, slots_late);
+    }
+}
@@ -0,0 +1,122 @@
+//! Transaction pool inspection and management.
+//!
+//! Safe methods allow operators and tooling to observe the contents of the transaction pool,
+//! methods that modify the pool are only exposed under `unsafe` namespace and only when explicitly
+//! enabled.
+
+use crate::Error;
+use ab_core_primitives::transaction::TransactionHash;
+use ab_farmer_rpc_primitives::{
+    ContinuationToken, MAX_PAGE_ITEMS, MAX_PAGE_SIZE, Page, PageBuilder, PendingTransactionInfo,
+    TransactionPoolStatusInfo,
+};
+use ab_transaction_pool::{TransactionPool, TransactionState};
+use jsonrpsee::proc_macros::rpc;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Provides rpc methods for inspecting the transaction pool
+#[rpc(server)]
+pub trait TransactionPoolRpcApi {
+    /// Page of up to `limit` transactions currently in the transaction pool.
+    ///
+    /// Continuation token from the previous page is used to request the next page, the pool might
+    /// change in between pages though, so some transactions might be skipped or returned twice.
+    #[method(name = "pendingTransactions")]
+    fn pending_transactions(
+        &self,
+        limit: u32,
+        continuation_token: Option<ContinuationToken>,
+    ) -> Result<Page<PendingTransactionInfo>, Error>;
+
+    /// Current status of the transaction pool
+    #[method(name = "poolStatus")]
+    fn pool_status(&self) -> Result<TransactionPoolStatusInfo, Error>;
+}
+
+/// Provides unsafe rpc methods for managing the transaction pool
+#[rpc(server, namespace = "unsafe")]
+pub trait TransactionPoolUnsafeRpcApi {
+    /// Remove transaction from the transaction pool.
+    ///
+    /// Returns `false` if transaction was not in the pool.
+    #[method(name = "removeTransaction")]
+    fn remove_transaction(&self, hash: TransactionHash) -> Result<bool, Error>;
+}
+
+/// Implements [`TransactionPoolRpcApiServer`] and [`TransactionPoolUnsafeRpcApiServer`] traits
+#[derive(Debug, Clone)]
+pub(crate) struct TransactionPoolRpc {
+    pub(crate) transaction_pool: Arc<Mutex<TransactionPool>>,
+}
+
+impl TransactionPoolRpcApiServer for TransactionPoolRpc {
+    fn pending_transactions(
+        &self,
+        limit: u32,
+        continuation_token: Option<ContinuationToken>,
+    ) -> Result<Page<PendingTransactionInfo>, Error> {
+        if limit as usize > MAX_PAGE_ITEMS {
+            error!(
+                "Request limit ({}) exceed the server limit: {} ",
+                limit, MAX_PAGE_ITEMS
+            );
+
+            return Err(Error::PageItemsLimitExceeded {
+                actual: limit as usize,
+            });
+        }
+
+        let offset = continuation_token.map_or(0, ContinuationToken::position);
+        let mut page = PageBuilder::new(offset, limit as usize, MAX_PAGE_SIZE);
+
+        let transaction_pool = self.transaction_pool.lock();
+
+        let mut pending_transactions = transaction_pool
+            .iter()
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .map(|(tx_hash, pool_tx)| PendingTransactionInfo {
+                hash: *tx_hash,
+                block_root: pool_tx.tx.transaction().header.block_root,
+                size: pool_tx.tx.buffer().len(),
+                authorized_at: match &pool_tx.state {
+                    TransactionState::New => None,
+                    TransactionState::Authorized { at } => {
+                        at.front().map(|details| details.block_number)
+                    }
+                },
+            });
+
+        // Page is full once it fails to fit the next transaction
+        let has_more =
+            pending_transactions.any(|pending_transaction| page.push(pending_transaction).is_err());
+
+        Ok(page.finish(has_more))
+    }
+
+    fn pool_status(&self) -> Result<TransactionPoolStatusInfo, Error> {
+        let status = self.transaction_pool.lock().status();
+
+        Ok(TransactionPoolStatusInfo {
+            count: status.count,
+            size: status.size,
+            new: status.new,
+            authorized: status.authorized,
+            count_limit: status.limits.count.get(),
+            size_limit: status.limits.size.get(),
+        })
+    }
+}
+
+impl TransactionPoolUnsafeRpcApiServer for TransactionPoolRpc {
+    fn remove_transaction(&self, hash: TransactionHash) -> Result<bool, Error> {
+        let removed = self.transaction_pool.lock().remove_single(&hash);
+
+        if removed {
+            info!(%hash, "Transaction removed from the pool over RPC");
+        }
+
+        Ok(removed)
+    }
+}
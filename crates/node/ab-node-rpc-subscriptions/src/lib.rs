@@ -0,0 +1,384 @@
+//! Fanout of notifications to subscribers of RPC subscriptions.
+//!
+//! RPC servers notify many subscribers about the same events (new slots, super segments, etc.),
+//! while some of the subscribers might be too slow to receive all notifications.
+//! [`SubscriptionFanout`] keeps track of subscribers of a single subscription, removes those that
+//! are closed and decides what happens with notifications of slow subscribers according to the
+//! [`LagPolicy`] of each subscriber.
+//!
+//! The number of sent and dropped notifications, subscribers disconnected due to being too slow,
+//! as well as the number of subscribers of each subscription are optionally recorded in the
+//! metrics registry, see [`SubscriptionFanoutMetrics`].
+
+mod metrics;
+
+pub use crate::metrics::SubscriptionFanoutMetrics;
+use futures::future::join_all;
+use jsonrpsee::{
+    ConnectionId, SendTimeoutError, SubscriptionMessage, SubscriptionSink, TrySendError,
+};
+use parking_lot::Mutex;
+use serde_json::value::RawValue;
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// What happens with notifications when a subscriber is too slow to receive them
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LagPolicy {
+    /// New notifications are not delivered while the subscriber is lagging, they are returned to
+    /// the caller as [`Delivery::Dropped`] instead
+    DropNewest,
+    /// Notifications are buffered while the subscriber is lagging, the oldest buffered
+    /// notification is dropped to make space for a new one
+    DropOldest {
+        /// Max number of buffered notifications
+        capacity: NonZeroUsize,
+    },
+    /// Notifications are coalesced while the subscriber is lagging: only the latest notification
+    /// is buffered, replacing the one that was buffered before (if any).
+    ///
+    /// Meant for notifications where a newer notification supersedes older ones, such that the
+    /// subscriber receives the freshest notification as soon as it catches up instead of a backlog
+    /// of outdated ones.
+    KeepLatest,
+    /// Subscriber is dropped as soon as it is lagging, no more notifications are sent to it
+    Disconnect,
+    /// Wait for the subscriber to receive the notification, the subscriber is dropped if it
+    /// doesn't happen within `timeout`.
+    ///
+    /// Waiting happens after all other subscribers were notified, see
+    /// [`SubscriptionFanout::notify_with()`].
+    BlockWithTimeout {
+        /// Max time to wait for a single notification to be received
+        timeout: Duration,
+    },
+}
+
+/// Result of [`Subscriber::send()`]
+#[derive(Debug)]
+pub enum Delivery {
+    /// Notification was delivered or buffered for delivery
+    Delivered,
+    /// Notification was not delivered because the subscriber is too slow, only happens with
+    /// [`LagPolicy::DropNewest`]
+    Dropped(SubscriptionMessage),
+    /// Subscription is closed or the subscriber was dropped due to its [`LagPolicy`]
+    Closed,
+}
+
+/// Subscriber of a subscription with subscription-specific state
+#[derive(Debug)]
+#[expect(
+    clippy::partial_pub_fields,
+    reason = "Subscription-specific state is public for notification callbacks, the rest is internal"
+)]
+pub struct Subscriber<S> {
+    sink: SubscriptionSink,
+    lag_policy: LagPolicy,
+    /// Notifications waiting to be delivered with [`LagPolicy::DropOldest`],
+    /// [`LagPolicy::KeepLatest`] and [`LagPolicy::BlockWithTimeout`]
+    backlog: VecDeque<SubscriptionMessage>,
+    /// Name of the subscription, set when added to [`SubscriptionFanout`]
+    name: &'static str,
+    /// Metrics, set when added to [`SubscriptionFanout`]
+    metrics: Option<SubscriptionFanoutMetrics>,
+    /// Subscription-specific state
+    pub state: S,
+}
+
+impl<S> Subscriber<S> {
+    /// Create a new instance
+    pub fn new(sink: SubscriptionSink, lag_policy: LagPolicy, state: S) -> Self {
+        Self {
+            sink,
+            lag_policy,
+            backlog: VecDeque::new(),
+            name: "",
+            metrics: None,
+            state,
+        }
+    }
+
+    /// Subscription sink.
+    ///
+    /// Can be used to send notifications that must be delivered before the subscriber is added to
+    /// [`SubscriptionFanout`], like the initial state.
+    #[inline(always)]
+    pub fn sink(&self) -> &SubscriptionSink {
+        &self.sink
+    }
+
+    /// Whether the subscription is closed
+    #[inline(always)]
+    pub fn is_closed(&self) -> bool {
+        self.sink.is_closed()
+    }
+
+    /// Send a notification without waiting, [`LagPolicy`] is applied if the subscriber is too slow
+    pub fn send(&mut self, notification: Box<RawValue>) -> Delivery {
+        let mut notification = SubscriptionMessage::from(notification);
+
+        // Buffered notification is superseded by the new one
+        if matches!(self.lag_policy, LagPolicy::KeepLatest) && self.backlog.pop_front().is_some() {
+            debug!(
+                subscription = self.name,
+                subscription_id = ?self.sink.subscription_id(),
+                "Subscriber is too slow, replacing buffered notification with the latest one"
+            );
+            self.notification_dropped();
+        }
+
+        // Notifications that were buffered before are delivered first to preserve the order
+        while let Some(buffered_notification) = self.backlog.pop_front() {
+            match self.sink.try_send(buffered_notification) {
+                Ok(()) => {
+                    self.notification_sent();
+                }
+                Err(TrySendError::Closed(_)) => {
+                    return Delivery::Closed;
+                }
+                Err(TrySendError::Full(buffered_notification)) => {
+                    self.backlog.push_front(buffered_notification);
+                    break;
+                }
+            }
+        }
+
+        if self.backlog.is_empty() {
+            match self.sink.try_send(notification) {
+                Ok(()) => {
+                    self.notification_sent();
+                    return Delivery::Delivered;
+                }
+                Err(TrySendError::Closed(_)) => {
+                    return Delivery::Closed;
+                }
+                Err(TrySendError::Full(returned_notification)) => {
+                    notification = returned_notification;
+                }
+            }
+        }
+
+        match self.lag_policy {
+            LagPolicy::DropNewest => {
+                self.notification_dropped();
+                Delivery::Dropped(notification)
+            }
+            LagPolicy::DropOldest { capacity } => {
+                if self.backlog.len() >= capacity.get() {
+                    self.backlog.pop_front();
+                    debug!(
+                        subscription = self.name,
+                        subscription_id = ?self.sink.subscription_id(),
+                        "Subscriber is too slow, dropping the oldest notification"
+                    );
+                    self.notification_dropped();
+                }
+                self.backlog.push_back(notification);
+                Delivery::Delivered
+            }
+            LagPolicy::KeepLatest => {
+                self.backlog.push_back(notification);
+                Delivery::Delivered
+            }
+            LagPolicy::Disconnect => {
+                self.disconnect();
+                Delivery::Closed
+            }
+            LagPolicy::BlockWithTimeout { .. } => {
+                self.backlog.push_back(notification);
+                Delivery::Delivered
+            }
+        }
+    }
+
+    /// Whether there are buffered notifications that [`SubscriptionFanout`] needs to wait for
+    fn is_blocked(&self) -> bool {
+        matches!(self.lag_policy, LagPolicy::BlockWithTimeout { .. }) && !self.backlog.is_empty()
+    }
+
+    /// Wait for buffered notifications to be delivered with [`LagPolicy::BlockWithTimeout`].
+    ///
+    /// Returns `false` if the subscription is closed or the subscriber was dropped.
+    async fn deliver_backlog(&mut self) -> bool {
+        let LagPolicy::BlockWithTimeout { timeout } = self.lag_policy else {
+            return true;
+        };
+
+        while let Some(notification) = self.backlog.pop_front() {
+            match self.sink.send_timeout(notification, timeout).await {
+                Ok(()) => {
+                    self.notification_sent();
+                }
+                Err(SendTimeoutError::Closed(_)) => {
+                    return false;
+                }
+                Err(SendTimeoutError::Timeout(_)) => {
+                    self.disconnect();
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    fn disconnect(&self) {
+        warn!(
+            subscription = self.name,
+            subscription_id = ?self.sink.subscription_id(),
+            "Subscriber is too slow, dropping subscription"
+        );
+        if let Some(metrics) = &self.metrics {
+            metrics.subscriber_disconnected(self.name);
+        }
+    }
+
+    fn notification_sent(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.notification_sent(self.name);
+        }
+    }
+
+    fn notification_dropped(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.notification_dropped(self.name);
+        }
+    }
+}
+
+/// Subscribers of a single subscription
+#[derive(Debug)]
+pub struct SubscriptionFanout<S> {
+    name: &'static str,
+    subscribers: Mutex<Vec<Subscriber<S>>>,
+    metrics: Option<SubscriptionFanoutMetrics>,
+}
+
+impl<S> SubscriptionFanout<S> {
+    /// Create a new instance, `name` is used in logs and metrics.
+    ///
+    /// Metrics are recorded if `metrics` is provided.
+    pub fn new(name: &'static str, metrics: Option<SubscriptionFanoutMetrics>) -> Self {
+        Self {
+            name,
+            subscribers: Mutex::default(),
+            metrics,
+        }
+    }
+
+    /// Add a subscriber
+    pub fn add(&self, subscriber: Subscriber<S>) {
+        self.add_if(subscriber, |_subscriber| true);
+    }
+
+    /// Add a subscriber if `f` returns `true`.
+    ///
+    /// `f` is called under the same lock that is used for notifications, such that the subscriber
+    /// can catch up with what happened before it was added without any gaps.
+    pub fn add_if<F>(&self, mut subscriber: Subscriber<S>, f: F)
+    where
+        F: FnOnce(&mut Subscriber<S>) -> bool,
+    {
+        subscriber.name = self.name;
+        subscriber.metrics.clone_from(&self.metrics);
+
+        let mut subscribers = self.subscribers.lock();
+        if f(&mut subscriber) {
+            subscribers.push(subscriber);
+            self.update_subscribers(subscribers.len());
+        }
+    }
+
+    /// Number of subscribers on a connection whose subscriptions are not closed yet
+    pub fn active_on_connection(&self, connection_id: ConnectionId) -> usize {
+        self.subscribers
+            .lock()
+            .iter()
+            .filter(|subscriber| {
+                subscriber.sink.connection_id() == connection_id && !subscriber.is_closed()
+            })
+            .count()
+    }
+
+    /// Notify all subscribers with a notification created by `notification` for each of them,
+    /// `None` means there is nothing to send to a particular subscriber.
+    ///
+    /// Notifications dropped with [`LagPolicy::DropNewest`] are logged and not retried, see
+    /// [`Self::notify_with()`] for more control.
+    pub async fn notify<F>(&self, mut notification: F)
+    where
+        F: FnMut(&mut S) -> Option<Box<RawValue>>,
+    {
+        self.notify_with(|subscriber| {
+            let Some(notification) = notification(&mut subscriber.state) else {
+                return !subscriber.is_closed();
+            };
+
+            match subscriber.send(notification) {
+                Delivery::Delivered => true,
+                Delivery::Dropped(_notification) => {
+                    warn!(
+                        subscription = subscriber.name,
+                        subscription_id = ?subscriber.sink.subscription_id(),
+                        "Subscriber is too slow, dropping notification"
+                    );
+                    true
+                }
+                Delivery::Closed => false,
+            }
+        })
+        .await;
+    }
+
+    /// Call `f` for every subscriber, which is expected to send notifications with
+    /// [`Subscriber::send()`] and return whether the subscriber should be retained.
+    ///
+    /// Once all subscribers were notified, waits for subscribers with
+    /// [`LagPolicy::BlockWithTimeout`] that are lagging to receive their notifications. They are
+    /// not notified about anything else in the meantime.
+    pub async fn notify_with<F>(&self, mut f: F)
+    where
+        F: FnMut(&mut Subscriber<S>) -> bool,
+    {
+        let blocked_subscribers = {
+            let mut subscribers = self.subscribers.lock();
+            subscribers.retain_mut(&mut f);
+
+            let blocked_subscribers = subscribers
+                .extract_if(.., |subscriber| subscriber.is_blocked())
+                .collect::<Vec<_>>();
+
+            if blocked_subscribers.is_empty() {
+                self.update_subscribers(subscribers.len());
+            }
+
+            blocked_subscribers
+        };
+
+        if blocked_subscribers.is_empty() {
+            return;
+        }
+
+        let blocked_subscribers = join_all(
+            blocked_subscribers
+                .into_iter()
+                .map(|mut subscriber| async move {
+                    subscriber.deliver_backlog().await.then_some(subscriber)
+                }),
+        )
+        .await;
+
+        let mut subscribers = self.subscribers.lock();
+        subscribers.extend(blocked_subscribers.into_iter().flatten());
+        self.update_subscribers(subscribers.len());
+    }
+
+    fn update_subscribers(&self, subscribers: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.update_subscribers(self.name, subscribers);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Metrics for subscription fanout
+
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::registry::{Registry, Unit};
+use std::sync::atomic::{AtomicI64, AtomicU64};
+
+/// Metrics for [`SubscriptionFanout`](crate::SubscriptionFanout).
+///
+/// Created once and shared by all fanouts, which are distinguished by the `subscription` label.
+#[derive(Debug, Clone)]
+pub struct SubscriptionFanoutMetrics {
+    notifications_sent: Family<Vec<(&'static str, String)>, Counter<u64, AtomicU64>>,
+    notifications_dropped: Family<Vec<(&'static str, String)>, Counter<u64, AtomicU64>>,
+    subscribers_disconnected: Family<Vec<(&'static str, String)>, Counter<u64, AtomicU64>>,
+    subscribers: Family<Vec<(&'static str, String)>, Gauge<i64, AtomicI64>>,
+}
+
+impl SubscriptionFanoutMetrics {
+    /// Create a new instance
+    pub fn new(registry: &mut Registry) -> Self {
+        let registry = registry.sub_registry_with_prefix("rpc_subscriptions");
+
+        let notifications_sent = Family::default();
+        registry.register_with_unit(
+            "notifications_sent_counter",
+            "Number of notifications sent to subscribers",
+            Unit::Other("Notifications".to_string()),
+            notifications_sent.clone(),
+        );
+
+        let notifications_dropped = Family::default();
+        registry.register_with_unit(
+            "notifications_dropped_counter",
+            "Number of notifications dropped because subscribers were too slow",
+            Unit::Other("Notifications".to_string()),
+            notifications_dropped.clone(),
+        );
+
+        let subscribers_disconnected = Family::default();
+        registry.register_with_unit(
+            "subscribers_disconnected_counter",
+            "Number of subscribers disconnected because they were too slow",
+            Unit::Other("Subscribers".to_string()),
+            subscribers_disconnected.clone(),
+        );
+
+        let subscribers = Family::default();
+        registry.register_with_unit(
+            "subscribers",
+            "Number of active subscribers",
+            Unit::Other("Subscribers".to_string()),
+            subscribers.clone(),
+        );
+
+        Self {
+            notifications_sent,
+            notifications_dropped,
+            subscribers_disconnected,
+            subscribers,
+        }
+    }
+
+    pub(super) fn notification_sent(&self, subscription: &'static str) {
+        self.notifications_sent
+            .get_or_create(&vec![("subscription", subscription.to_string())])
+            .inc();
+    }
+
+    pub(super) fn notification_dropped(&self, subscription: &'static str) {
+        self.notifications_dropped
+            .get_or_create(&vec![("subscription", subscription.to_string())])
+            .inc();
+    }
+
+    pub(super) fn subscriber_disconnected(&self, subscription: &'static str) {
+        self.subscribers_disconnected
+            .get_or_create(&vec![("subscription", subscription.to_string())])
+            .inc();
+    }
+
+    pub(super) fn update_subscribers(&self, subscription: &'static str, subscribers: usize) {
+        self.subscribers
+            .get_or_create(&vec![("subscription", subscription.to_string())])
+            .set(i64::try_from(subscribers).unwrap_or(i64::MAX));
+    }
+}
@@ -1,9 +1,44 @@
 pub(crate) mod format_database;
+pub(crate) mod rotate_key;
 pub(crate) mod run;
 
 use crate::Error;
+use clap::Parser;
+use std::path::PathBuf;
+use std::{fs, io};
+use zeroize::Zeroizing;
 
 pub(crate) trait CliCommand {
     /// Run the command
     fn run(self) -> Result<(), Error>;
 }
+
+/// Keystore options
+#[derive(Debug, Parser)]
+pub(crate) struct KeystoreOptions {
+    /// Path to the keystore directory with node keys
+    #[arg(long)]
+    keystore_path: Option<PathBuf>,
+    /// Path to the file with passphrase used to encrypt keys in the keystore.
+    ///
+    /// Existing keys must have been encrypted with the same passphrase, newly generated keys will
+    /// be encrypted with it.
+    #[arg(long)]
+    keystore_passphrase_file: Option<PathBuf>,
+}
+
+impl KeystoreOptions {
+    /// Read passphrase from the passphrase file (if specified)
+    fn passphrase(&self) -> io::Result<Option<Zeroizing<String>>> {
+        let Some(keystore_passphrase_file) = &self.keystore_passphrase_file else {
+            return Ok(None);
+        };
+
+        let mut passphrase = Zeroizing::new(fs::read_to_string(keystore_passphrase_file)?);
+        // Trailing new line is typically not a part of the passphrase
+        let len = passphrase.trim_end_matches(['\r', '\n']).len();
+        passphrase.truncate(len);
+
+        Ok(Some(passphrase))
+    }
+}
@@ -1,11 +1,14 @@
 use crate::cli::CliCommand;
 use crate::storage_backend::FileStorageBackend;
 use crate::{Error, PAGE_GROUP_SIZE};
-use ab_client_database::{ClientDatabase, ClientDatabaseFormatError, ClientDatabaseFormatOptions};
+use ab_client_database::{
+    BlockCompression, BlockCompressionOptions, ClientDatabase, ClientDatabaseFormatError,
+    ClientDatabaseFormatOptions,
+};
 use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
 use ab_direct_io_file::DirectIoFile;
 use bytesize::ByteSize;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use rclite::Arc;
 use std::fs::OpenOptions;
 use std::io;
@@ -41,6 +44,17 @@ pub(crate) enum FormatDbError {
     },
 }
 
+/// Compression of blocks stored in the database
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum DbBlockCompression {
+    /// Blocks are stored as is
+    None,
+    /// LZ4, very fast with a moderate compression ratio
+    Lz4,
+    /// Zstd, slower than LZ4, but with a better compression ratio
+    Zstd,
+}
+
 /// Format a database file/disk
 #[derive(Debug, Parser)]
 pub(crate) struct FormatDb {
@@ -54,6 +68,9 @@ pub(crate) struct FormatDb {
     /// Force formatting of the existing database
     #[arg(long)]
     force: bool,
+    /// Compression of stored blocks, can't be changed after formatting
+    #[arg(long, value_enum, default_value = "none")]
+    block_compression: DbBlockCompression,
 }
 
 impl CliCommand for FormatDb {
@@ -65,7 +82,12 @@ impl CliCommand for FormatDb {
 impl FormatDb {
     #[tokio::main]
     async fn run(self) -> Result<(), FormatDbError> {
-        let Self { path, size, force } = self;
+        let Self {
+            path,
+            size,
+            force,
+            block_compression,
+        } = self;
 
         let file = DirectIoFile::open(
             {
@@ -103,6 +125,16 @@ impl FormatDb {
             ClientDatabaseFormatOptions {
                 page_group_size: PAGE_GROUP_SIZE,
                 force,
+                // TODO: Known segment headers from the chain spec once chain can be selected here
+                known_segment_headers: Vec::new(),
+                block_compression: BlockCompressionOptions {
+                    temporary: match block_compression {
+                        DbBlockCompression::None => BlockCompression::None,
+                        DbBlockCompression::Lz4 => BlockCompression::Lz4,
+                        DbBlockCompression::Zstd => BlockCompression::Zstd,
+                    },
+                    ..
+                },
             },
         )
         .await?;
@@ -0,0 +1,77 @@
+use crate::Error;
+use crate::cli::{CliCommand, KeystoreOptions};
+use crate::keystore::{KeyKind, Keystore, KeystoreError, KeystoreSigner};
+use clap::Parser;
+use std::io;
+
+/// Error for [`RotateKey`]
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RotateKeyError {
+    /// Keystore path required
+    #[error("Keystore path required, specify it with `--keystore-path`")]
+    KeystorePathRequired,
+    /// Failed to read keystore passphrase
+    #[error("Failed to read keystore passphrase: {error}")]
+    KeystorePassphrase {
+        /// Low-level error
+        error: io::Error,
+    },
+    /// Keystore error
+    #[error("Keystore error: {error}")]
+    Keystore {
+        /// Low-level error
+        #[from]
+        error: KeystoreError,
+    },
+}
+
+/// Rotate a key in the keystore.
+///
+/// The previous key is preserved in a file with `.previous` extension next to the new key.
+#[derive(Debug, Parser)]
+pub(crate) struct RotateKey {
+    /// Kind of key to rotate
+    kind: KeyKind,
+    /// Keystore options
+    #[clap(flatten)]
+    keystore_options: KeystoreOptions,
+}
+
+impl CliCommand for RotateKey {
+    fn run(self) -> Result<(), Error> {
+        Ok(self.run()?)
+    }
+}
+
+impl RotateKey {
+    fn run(self) -> Result<(), RotateKeyError> {
+        let Self {
+            kind,
+            keystore_options,
+        } = self;
+
+        let passphrase = keystore_options
+            .passphrase()
+            .map_err(|error| RotateKeyError::KeystorePassphrase { error })?;
+        let keystore_path = keystore_options
+            .keystore_path
+            .ok_or(RotateKeyError::KeystorePathRequired)?;
+
+        let keystore = Keystore::open(keystore_path, passphrase)?;
+        keystore.rotate(kind)?;
+
+        match kind {
+            KeyKind::Networking => {
+                println!(
+                    "New peer ID: {}",
+                    keystore.networking_keypair()?.public().to_peer_id()
+                );
+            }
+            KeyKind::Operator => {
+                println!("New public key: {}", keystore.public_key(kind)?);
+            }
+        }
+
+        Ok(())
+    }
+}
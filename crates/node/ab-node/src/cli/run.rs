@@ -1,46 +1,63 @@
 mod chain_spec;
+mod db_size_policy;
 
-use crate::cli::CliCommand;
 use crate::cli::run::chain_spec::ChainSpec;
+use crate::cli::run::db_size_policy::DbSizePolicy;
+use crate::cli::{CliCommand, KeystoreOptions};
+use crate::keystore::{Keystore, KeystoreError};
 use crate::storage_backend::FileStorageBackend;
 use crate::{Error, PAGE_GROUP_SIZE};
 use ab_cli_utils::shutdown_signal;
 use ab_client_api::{ChainInfo, ChainSyncStatus};
-use ab_client_archiving::task::{SegmentArchiverTaskError, create_segment_archiver_task};
+use ab_client_archiving::task::{
+    CatchUpMemoryBudget, SegmentArchiverTaskError, create_segment_archiver_task,
+};
 use ab_client_block_authoring::beacon_chain::BeaconChainBlockProducer;
 use ab_client_block_authoring::slot_worker::{SlotWorker, SlotWorkerOptions};
 use ab_client_block_builder::beacon_chain::BeaconChainBlockBuilder;
-use ab_client_block_import::beacon_chain::BeaconChainBlockImport;
-use ab_client_block_verification::beacon_chain::BeaconChainBlockVerification;
+use ab_client_block_import::beacon_chain::{BeaconChainBlockImport, NewBeaconChainBlockTopic};
+use ab_client_block_verification::beacon_chain::{
+    BeaconChainBlockVerification, BeaconChainBlockVerificationOptions,
+};
+use ab_client_consensus_common::slot_subscriptions::SystemContractSlotSubscriptions;
+use ab_client_consensus_common::state_cache::StateCache;
 use ab_client_database::{
     ClientDatabase, ClientDatabaseError, ClientDatabaseFormatError, ClientDatabaseFormatOptions,
-    ClientDatabaseOptions, GenesisBlockBuilderResult,
+    ClientDatabaseOptions, DurabilityPolicy, GenesisBlockBuilderResult,
 };
-use ab_client_informer::run_informer;
+use ab_client_informer::{ChainInfoShardClient, ShardClients, run_informer};
+use ab_client_notifications::{BufferingPolicy, NotificationBus};
 use ab_client_proof_of_time::source::block_import::BestBlockPotInfo;
+use ab_client_proof_of_time::source::gossip::PotGossipWorker;
 use ab_client_proof_of_time::source::timekeeper::Timekeeper;
 use ab_client_proof_of_time::source::{PotSourceWorker, init_pot_state};
 use ab_client_proof_of_time::verifier::PotVerifier;
 use ab_core_primitives::block::BlockNumber;
 use ab_core_primitives::block::owned::{GenericOwnedBlock, OwnedBeaconChainBlock};
 use ab_core_primitives::pot::{PotParametersChange, PotSeed};
+use ab_core_primitives::shard::ShardIndex;
 use ab_direct_io_file::DirectIoFile;
 use ab_erasure_coding::ErasureCoding;
 use ab_networking::libp2p::Multiaddr;
-use ab_node_rpc_server::{FarmerRpcConfig, FarmerRpcWorker};
+use ab_networking::{Config, CreationError, Node, construct, default_gossipsub_config};
+use ab_node_rpc_server::{
+    FarmerRpcConfig, FarmerRpcWorker, StatusServer, StatusServerConfig, SubscriptionLimits,
+};
+use ab_node_rpc_subscriptions::SubscriptionFanoutMetrics;
 use ab_proof_of_space::chia::ChiaTable;
 use bytesize::ByteSize;
 use clap::{Parser, ValueEnum};
 use core_affinity::CoreId;
 use futures::channel::mpsc;
 use futures::prelude::*;
-use futures::select;
 use futures::task::noop_waker_ref;
 use gdt_cpus::{ThreadPriority, set_thread_priority};
+use prometheus_client::registry::Registry;
 use rclite::Arc;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::OpenOptions;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::pin::pin;
 use std::sync::Arc as StdArc;
@@ -54,6 +71,8 @@ use tracing::{Span, debug, error, info, warn};
 /// This is over 15 minutes of slots assuming there are no forks, should be both sufficient and not
 /// too large to handle
 const POT_VERIFIER_CACHE_SIZE: u32 = 30_000;
+/// Number of hot contract slots to keep in the state cache
+const STATE_CACHE_SIZE: u32 = 10_000;
 const INFORMER_INTERVAL: Duration = Duration::from_secs(5);
 
 type PosTable = ChiaTable;
@@ -102,6 +121,24 @@ pub(crate) enum RunError {
         /// Low-level error
         error: io::Error,
     },
+    /// Database size policy required
+    #[error(
+        "Database size policy required for automatic formatting of an empty database, specify it \
+        with `--db-size`"
+    )]
+    DatabaseSizePolicyRequired,
+    /// Unsupported database size policy
+    #[error("Database size policy `{policy}` is not supported by the storage backend")]
+    UnsupportedDatabaseSizePolicy {
+        /// Database size policy
+        policy: DbSizePolicy,
+    },
+    /// Failed to determine database size
+    #[error("Failed to determine database size: {error}")]
+    DatabaseSize {
+        /// Low-level error
+        error: io::Error,
+    },
     /// Failed to allocate the database
     #[error("Failed to allocate the database: {error}")]
     AllocateDatabase {
@@ -141,6 +178,37 @@ pub(crate) enum RunError {
         /// Low-level error
         error: io::Error,
     },
+    /// Failed to start node status server
+    #[error("Failed to start node status server: {error}")]
+    StatusServer {
+        /// Low-level error
+        error: io::Error,
+    },
+    /// Failed to create a temporary keystore
+    #[error("Failed to create a temporary keystore: {error}")]
+    TemporaryKeystore {
+        /// Low-level error
+        error: io::Error,
+    },
+    /// Failed to read keystore passphrase
+    #[error("Failed to read keystore passphrase: {error}")]
+    KeystorePassphrase {
+        /// Low-level error
+        error: io::Error,
+    },
+    /// Keystore error
+    #[error("Keystore error: {error}")]
+    Keystore {
+        /// Low-level error
+        #[from]
+        error: KeystoreError,
+    },
+    /// Failed to create networking stack
+    #[error("Failed to create networking stack: {error}")]
+    Networking {
+        /// Low-level error
+        error: CreationError,
+    },
 }
 
 // TODO: Support loading serialized chain spec from a file?
@@ -150,6 +218,17 @@ enum ChainKind {
     Dev,
 }
 
+/// Database durability policy
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum DbDurability {
+    /// Flush after every written storage item
+    EveryItem,
+    /// Flush periodically, see `--db-flush-interval-ms`
+    Periodic,
+    /// Flush when blocks are confirmed
+    OnConfirmation,
+}
+
 fn parse_timekeeper_cpu_cores(
     s: &str,
 ) -> Result<HashSet<usize>, Box<dyn std::error::Error + Send + Sync>> {
@@ -266,6 +345,34 @@ pub(crate) struct Run {
     /// Required unless --dev mode is used.
     #[arg(long)]
     db_path: Option<PathBuf>,
+    /// Database durability policy, determines when written data is flushed to disk.
+    ///
+    /// More frequent flushes reduce the amount of data that might be lost on crash or power loss
+    /// at the cost of higher write latency.
+    #[arg(long, value_enum, default_value = "on-confirmation")]
+    db_durability: DbDurability,
+    /// Interval between database flushes in milliseconds, used with `--db-durability periodic`
+    #[arg(long, default_value_t = 1_000)]
+    db_flush_interval_ms: u64,
+    /// Format the database automatically if it is not formatted yet.
+    ///
+    /// Database file is created if it doesn't exist yet, empty database file is allocated
+    /// according to `--db-size` first.
+    #[arg(long)]
+    db_auto_format: bool,
+    /// Size policy for the database allocated with `--db-auto-format`.
+    ///
+    /// Either a fixed size (like `100GiB`), percentage of free space on the volume where the
+    /// database is located (like `50%`) or `grow` to grow the database on demand (if supported by
+    /// the storage backend). Not needed for disks (block devices) or already allocated files.
+    #[arg(long)]
+    db_size: Option<DbSizePolicy>,
+    /// Memory budget of the archiver for archiving already produced blocks (like `1GiB`).
+    ///
+    /// Larger budget allows reading more blocks ahead of archiving, values below the minimum
+    /// needed for archiving a segment are rounded up. The minimum is used by default.
+    #[arg(long)]
+    archiver_catch_up_memory_budget: Option<ByteSize>,
     // TODO: Use enum with chain specs instead of a string
     /// Chain kind to use
     #[arg(long)]
@@ -298,6 +405,14 @@ pub(crate) struct Run {
         9944,
     ))]
     farmer_rpc_listen_on: SocketAddr,
+    /// Expose unsafe farmer RPC methods that modify the state of the node (like removing
+    /// transactions from the transaction pool)
+    #[arg(long)]
+    farmer_rpc_unsafe_methods: bool,
+    /// IP and port (TCP) on which to serve read-only node status as JSON over HTTP (`/status` and
+    /// `/health` paths), for dashboards and load balancer health checks
+    #[arg(long)]
+    status_listen_on: Option<SocketAddr>,
     /// IP and port (TCP) to start Prometheus exporter on
     #[clap(long)]
     prometheus_listen_on: Option<SocketAddr>,
@@ -314,6 +429,12 @@ pub(crate) struct Run {
     /// External entropy, used initially when the PoT chain starts to derive the first seed
     #[arg(long)]
     pot_external_entropy: Option<String>,
+    /// Keystore options.
+    ///
+    /// Temporary keystore is used unless keystore path is specified, meaning node identity will
+    /// change on every restart.
+    #[clap(flatten)]
+    keystore_options: KeystoreOptions,
     /// Network options
     #[clap(flatten)]
     network_options: NetworkOptions,
@@ -334,14 +455,22 @@ impl Run {
     async fn run(self) -> Result<(), RunError> {
         let Self {
             db_path,
+            db_durability,
+            db_flush_interval_ms,
+            db_auto_format,
+            db_size,
+            archiver_catch_up_memory_budget,
             mut chain,
             dev,
             mut tmp,
             farmer_rpc_listen_on,
+            farmer_rpc_unsafe_methods,
+            status_listen_on,
             prometheus_listen_on,
             mut force_synced,
             mut force_authoring,
             pot_external_entropy,
+            keystore_options,
             network_options,
             mut timekeeper_options,
         } = self;
@@ -392,20 +521,72 @@ impl Run {
             }
         };
 
+        let keystore_passphrase = keystore_options
+            .passphrase()
+            .map_err(|error| RunError::KeystorePassphrase { error })?;
+        let mut maybe_tmp_keystore_dir = None;
+        let keystore_path = match keystore_options.keystore_path {
+            Some(keystore_path) => keystore_path,
+            None => {
+                if !tmp {
+                    warn!(
+                        "Keystore path is not specified, using a temporary keystore, node identity \
+                        will change on restart"
+                    );
+                }
+
+                let tmp = tempfile::Builder::new()
+                    .prefix("ab-node-keystore-")
+                    .tempdir()
+                    .map_err(|error| RunError::TemporaryKeystore { error })?;
+
+                maybe_tmp_keystore_dir.insert(tmp).path().to_path_buf()
+            }
+        };
+        let keystore = Keystore::open(keystore_path, keystore_passphrase)?;
+        let networking_keypair = keystore.networking_keypair()?;
+
         let file = DirectIoFile::open(
             {
                 let mut open_options = OpenOptions::new();
-                open_options.read(true).write(true);
+                open_options
+                    .read(true)
+                    .write(true)
+                    .create(db_auto_format)
+                    .truncate(false);
                 open_options
             },
             &db_path,
         )
         .map_err(|error| RunError::OpenDatabaseFile { error })?;
 
-        if maybe_tmp_file.is_some() {
+        let maybe_db_size = if maybe_tmp_file.is_some() {
             // TODO: Proper database size calculation here
-            let size = ByteSize::gib(1).as_u64();
+            Some(ByteSize::gib(1).as_u64())
+        } else if db_auto_format
+            && file
+                .is_empty()
+                .map_err(|error| RunError::OpenDatabaseFile { error })?
+        {
+            let policy = db_size.ok_or(RunError::DatabaseSizePolicyRequired)?;
+            let size = policy
+                .database_size(&db_path)
+                .map_err(|error| RunError::DatabaseSize { error })?
+                .ok_or(RunError::UnsupportedDatabaseSizePolicy { policy })?;
+
+            info!(
+                path = %db_path.display(),
+                %policy,
+                size = %ByteSize::b(size),
+                "Allocating new database"
+            );
+
+            Some(size)
+        } else {
+            None
+        };
 
+        if let Some(size) = maybe_db_size {
             // Allocating the whole file (`set_len` below can create a sparse file, which will cause
             // writes to fail later)
             file.allocate(size)
@@ -416,7 +597,8 @@ impl Run {
                 .map_err(|error| RunError::AllocateDatabase { error })?;
         }
 
-        let storage_backend = FileStorageBackend::new(Arc::new(file))
+        let file = Arc::new(file);
+        let storage_backend = FileStorageBackend::new(Arc::clone(&file))
             .map_err(|error| RunError::InstantiateStorageBackend { error })?;
 
         if maybe_tmp_file.is_some() {
@@ -425,6 +607,8 @@ impl Run {
                 ClientDatabaseFormatOptions {
                     page_group_size: PAGE_GROUP_SIZE,
                     force: true,
+                    known_segment_headers: chain_spec.known_segment_headers().to_vec(),
+                    ..
                 },
             )
             .await?;
@@ -433,7 +617,7 @@ impl Run {
         let genesis_block = chain_spec.genesis_block();
         let consensus_constants = *chain_spec.consensus_constants();
 
-        let client_database =
+        let open_client_database = |storage_backend: FileStorageBackend| {
             ClientDatabase::<OwnedBeaconChainBlock, _>::open(ClientDatabaseOptions {
                 block_confirmation_depth: consensus_constants.block_confirmation_depth,
                 genesis_block_builder: || GenesisBlockBuilderResult {
@@ -441,23 +625,107 @@ impl Run {
                     // TODO: Fill correct initial state
                     system_contract_states: StdArc::new([]),
                 },
+                durability_policy: match db_durability {
+                    DbDurability::EveryItem => DurabilityPolicy::EveryItem,
+                    DbDurability::Periodic => DurabilityPolicy::Periodic {
+                        interval: Duration::from_millis(db_flush_interval_ms),
+                    },
+                    DbDurability::OnConfirmation => DurabilityPolicy::OnConfirmation,
+                },
                 storage_backend,
                 ..
             })
-            .await?;
+        };
+
+        let client_database = match open_client_database(storage_backend).await {
+            Ok(client_database) => client_database,
+            Err(ClientDatabaseError::Unformatted) if db_auto_format => {
+                info!(
+                    path = %db_path.display(),
+                    "Database is not formatted yet, formatting automatically"
+                );
+
+                let storage_backend = FileStorageBackend::new(file)
+                    .map_err(|error| RunError::InstantiateStorageBackend { error })?;
+
+                ClientDatabase::<OwnedBeaconChainBlock, _>::format(
+                    &storage_backend,
+                    ClientDatabaseFormatOptions {
+                        page_group_size: PAGE_GROUP_SIZE,
+                        force: false,
+                        known_segment_headers: chain_spec.known_segment_headers().to_vec(),
+                        ..
+                    },
+                )
+                .await?;
+
+                open_client_database(storage_backend).await?
+            }
+            Err(error) => {
+                return Err(error.into());
+            }
+        };
+
+        tokio::spawn({
+            let client_database = client_database.clone();
+
+            async move {
+                if let Err(error) = client_database.run_periodic_flush().await {
+                    error!(%error, "Periodic database flush failed");
+                }
+            }
+        });
+
+        tokio::spawn({
+            let client_database = client_database.clone();
+
+            async move {
+                if let Err(error) = client_database.run_compaction().await {
+                    error!(%error, "Database compaction failed");
+                }
+            }
+        });
+
+        tokio::spawn({
+            let client_database = client_database.clone();
+
+            async move {
+                if let Err(error) = client_database.run_reclamation().await {
+                    error!(%error, "Database reclamation failed");
+                }
+            }
+        });
 
         info!("✌️ Abundance {}", env!("CARGO_PKG_VERSION"));
         // TODO: Un-comment when there is a chain spec notion
         info!("📋 Chain specification: {}", chain_spec.name(),);
         info!("💾 Database path: {}", db_path.display());
+        info!("🔑 Keystore path: {}", keystore.directory().display());
+        info!(
+            "🏷  Local identity: {}",
+            networking_keypair.public().to_peer_id()
+        );
 
         let pot_external_entropy = derive_pot_external_entropy(
             &chain_spec,
             pot_external_entropy.as_deref().map(str::as_bytes),
         );
 
+        let genesis_root = genesis_block.header.header().root();
+
+        let (node, mut node_runner) = construct(Config {
+            bootstrap_addresses: network_options.bootstrap_nodes,
+            // PoT gossip worker is the only topic subscriber and reports validation results
+            gossipsub: Some(default_gossipsub_config(true)),
+            ..Config::new(genesis_root.to_string(), networking_keypair, None)
+        })
+        .map_err(|error| RunError::Networking { error })?;
+
+        // TODO: Better thread management, probably move to its own dedicated thread
+        tokio::spawn(async move { node_runner.run().await });
+
         let pot_verifier = PotVerifier::new(
-            PotSeed::from_genesis(&genesis_block.header.header().root(), pot_external_entropy),
+            PotSeed::from_genesis(&genesis_root, pot_external_entropy),
             POT_VERIFIER_CACHE_SIZE,
         );
 
@@ -509,21 +777,21 @@ impl Run {
                 .expect("Thread creation must not panic");
         }
 
+        let (pot_gossip_worker, to_gossip_sender, from_gossip_receiver) = PotGossipWorker::new(
+            node.clone(),
+            &genesis_root,
+            pot_verifier.clone(),
+            Arc::clone(&pot_state),
+            (),
+        );
+
+        // TODO: Better thread management, probably move to its own dedicated thread
+        tokio::spawn(pot_gossip_worker.run());
+
         // TODO: These are currently not implementable, but should be eventually
-        // let (pot_gossip_worker, to_gossip_sender, from_gossip_receiver) =
-        //     PotGossipWorker::<Block>::new(
-        //         pot_verifier.clone(),
-        //         Arc::clone(&pot_state),
-        //         StdArc::clone(&network_service),
-        //         pot_gossip_notification_service,
-        //         sync_service.clone(),
-        //         sync_oracle.clone(),
-        //     );
         // let (best_block_pot_source, best_block_pot_info_receiver) =
         //     BestBlockPotSource::new(client.clone()).map_err(|error| Error::Other(error.into()))?;
         // TODO: Code below is just a placeholder
-        let (to_gossip_sender, to_gossip_receiver) = mpsc::channel(10);
-        let (from_gossip_sender, from_gossip_receiver) = mpsc::channel(10);
         let (mut best_block_pot_info_sender, best_block_pot_info_receiver) = mpsc::channel(1);
 
         let chain_sync_status = ChainSyncStatusPlaceholder {};
@@ -541,30 +809,44 @@ impl Run {
         tokio::spawn(pot_source_worker.run());
 
         let block_builder =
-            BeaconChainBlockBuilder::new(consensus_constants, client_database.clone());
+            BeaconChainBlockBuilder::new(consensus_constants, client_database.clone(), ());
 
         let block_verification = BeaconChainBlockVerification::<PosTable, _, _>::new(
             consensus_constants,
+            BeaconChainBlockVerificationOptions::default(),
             pot_verifier.clone(),
             client_database.clone(),
             chain_sync_status.clone(),
         );
 
+        let state_cache = StdArc::new(StateCache::new(STATE_CACHE_SIZE));
+        let mut registry = Registry::with_prefix("ab_node");
+        if prometheus_listen_on.is_some() {
+            client_database.register_metrics(&mut registry);
+            state_cache.register_metrics(&mut registry);
+        }
+        let notification_bus =
+            NotificationBus::new(prometheus_listen_on.is_some().then_some(&mut registry));
+
         let (block_importing_notification_sender, block_importing_notification_receiver) =
             mpsc::channel(1);
-        let (super_segments_sender, super_segments_receiver) = mpsc::channel(0);
-        let (block_imported_notification_sender, mut block_imported_notification_receiver) =
-            mpsc::channel(1);
+        let mut new_block_notifications =
+            notification_bus.subscribe::<NewBeaconChainBlockTopic>(BufferingPolicy::Backpressure {
+                capacity: NonZeroUsize::MIN,
+            });
+        // TODO: Pass to transaction pool and RPC once they need to react to parameter changes
+        let slot_subscriptions = StdArc::new(SystemContractSlotSubscriptions::default());
         let block_import = BeaconChainBlockImport::<PosTable, _, _>::new(
             client_database.clone(),
             block_verification,
             block_importing_notification_sender,
-            super_segments_sender,
-            block_imported_notification_sender,
+            notification_bus.clone(),
+            StdArc::clone(&state_cache),
+            StdArc::clone(&slot_subscriptions),
         );
 
         tokio::spawn(async move {
-            while let Some(block) = block_imported_notification_receiver.next().await {
+            while let Some(block) = new_block_notifications.next().await {
                 let header = block.header().header();
                 let slot = header.consensus_info.slot + consensus_constants.block_authoring_delay;
                 let pot_parameters_change = header
@@ -590,59 +872,88 @@ impl Run {
             }
         });
 
-        let (new_slot_notification_sender, new_slot_notification_receiver) = mpsc::channel(1);
-        let (block_sealing_notification_sender, block_sealing_notification_receiver) =
-            mpsc::channel(0);
-        let (archived_segment_notification_sender, archived_segment_notification_receiver) =
-            mpsc::channel(0);
         let (shard_membership_updates_sender, shard_membership_updates_receiver) = mpsc::channel(0);
+        let (archiver_control_sender, archiver_control_receiver) = mpsc::channel(0);
 
         let erasure_coding = ErasureCoding::new();
 
-        let farmer_rpc_worker_fut = FarmerRpcWorker::new(FarmerRpcConfig {
+        // TODO: Initialize in a blocking task
+        let (archiver_task, archiver_progress, last_segment_stats) =
+            tokio::task::block_in_place(|| {
+                Handle::current().block_on(create_segment_archiver_task(
+                    client_database.clone(),
+                    block_importing_notification_receiver,
+                    archiver_control_receiver,
+                    notification_bus.clone(),
+                    consensus_constants,
+                    erasure_coding.clone(),
+                    archiver_catch_up_memory_budget
+                        .map(|budget| CatchUpMemoryBudget::new(budget.as_u64()))
+                        .unwrap_or_default(),
+                    prometheus_listen_on.is_some().then_some(&mut registry),
+                ))
+            })?;
+
+        // TODO: Better thread management, probably move to its own dedicated thread
+        tokio::spawn(archiver_task);
+
+        if let Some(status_listen_on) = status_listen_on {
+            let status_server = StatusServer::new(StatusServerConfig {
+                listen_on: status_listen_on,
+                beacon_chain_info: client_database.clone(),
+                chain_sync_status: chain_sync_status.clone(),
+                block_confirmation_depth: consensus_constants.block_confirmation_depth,
+                archiver_progress: Some(archiver_progress.clone()),
+                last_segment_stats: Some(last_segment_stats.clone()),
+                // TODO: Pass connected peers once networking stack is integrated
+                connected_peers: None,
+            })
+            .await
+            .map_err(|error| RunError::StatusServer { error })?;
+
+            // TODO: Better thread management, probably move to its own dedicated thread
+            tokio::spawn(status_server.run());
+        }
+
+        let farmer_rpc_worker_fut = FarmerRpcWorker::<PosTable, _, _>::new(FarmerRpcConfig {
             listen_on: farmer_rpc_listen_on,
             genesis_block,
             consensus_constants,
             // TODO: Query it from an actual chain
             max_pieces_in_sector: 1000,
-            new_slot_notification_receiver,
-            block_sealing_notification_receiver,
-            new_super_segment_notification_receiver: super_segments_receiver,
+            notification_bus: notification_bus.clone(),
             shard_membership_updates_sender,
             // TODO: Correct values once networking stack is integrated
             dsn_bootstrap_nodes: Vec::new(),
             beacon_chain_info: client_database.clone(),
+            state_cache,
             chain_sync_status: chain_sync_status.clone(),
-            erasure_coding: erasure_coding.clone(),
+            erasure_coding,
+            // TODO: Pass transaction pool once it is integrated into the node
+            transaction_pool: None,
+            unsafe_methods: farmer_rpc_unsafe_methods,
+            archiver_control_sender: Some(archiver_control_sender),
+            archiver_progress: Some(archiver_progress),
+            last_segment_stats: Some(last_segment_stats),
+            subscription_limits: SubscriptionLimits::default(),
+            subscription_metrics: prometheus_listen_on
+                .is_some()
+                .then(|| SubscriptionFanoutMetrics::new(&mut registry)),
         });
         let farmer_rpc_worker = farmer_rpc_worker_fut
             .await
             .map_err(|error| RunError::FarmerRpcServer { error })?;
 
-        // TODO: Initialize in a blocking task
-        let archiver_task = tokio::task::block_in_place(|| {
-            Handle::current().block_on(create_segment_archiver_task(
-                client_database.clone(),
-                block_importing_notification_receiver,
-                archived_segment_notification_sender,
-                consensus_constants,
-                erasure_coding,
-            ))
-        })?;
-
-        // TODO: Better thread management, probably move to its own dedicated thread
-        tokio::spawn(archiver_task);
-
         let block_producer =
             BeaconChainBlockProducer::new(block_builder, block_import, client_database.clone());
 
         let slot_worker = SlotWorker::<PosTable, _, _, _>::new(SlotWorkerOptions {
-            block_producer,
+            // TODO: Produce blocks for intermediate and leaf shards too
+            block_producers: BTreeMap::from([(ShardIndex::BEACON_CHAIN, block_producer)]),
             beacon_chain_info: client_database.clone(),
             chain_sync_status,
             force_authoring,
-            new_slot_notification_sender,
-            block_sealing_notification_sender,
+            notification_bus,
             consensus_constants,
             pot_verifier,
         });
@@ -652,40 +963,39 @@ impl Run {
 
         // TODO: Code below is just a placeholder
         tokio::spawn(async move {
-            let _from_gossip_sender = from_gossip_sender;
-            let mut to_gossip_receiver = to_gossip_receiver.fuse();
-            let mut archived_segment_notification_receiver =
-                archived_segment_notification_receiver.fuse();
-            let mut shard_membership_updates_receiver = shard_membership_updates_receiver.fuse();
-
-            loop {
-                select! {
-                    _ = to_gossip_receiver.next() => {
-                        // TODO
-                    }
-                    _ = archived_segment_notification_receiver.next() => {
-                        // TODO
-                    }
-                    _ = shard_membership_updates_receiver.next() => {
-                        // TODO
-                    }
-                }
+            let mut shard_membership_updates_receiver = shard_membership_updates_receiver;
+
+            while let Some(_shard_membership_update) =
+                shard_membership_updates_receiver.next().await
+            {
+                // TODO
             }
         });
 
         // TODO: Better thread management, probably move to its own dedicated thread
         tokio::spawn(farmer_rpc_worker.run());
 
+        let mut shard_clients = ShardClients::default();
+        shard_clients.add(ChainInfoShardClient::<OwnedBeaconChainBlock, _>::new(
+            client_database.clone(),
+        ));
+        // TODO: Register intermediate and leaf shard clients once they are implemented
+
         // TODO: Better thread management, probably move to its own dedicated thread
-        tokio::spawn(async move { run_informer(&client_database, INFORMER_INTERVAL).await });
+        tokio::spawn(async move { run_informer(&shard_clients, INFORMER_INTERVAL).await });
 
         // TODO: This is just a placeholder to keep the node running
         shutdown_signal_fut.await;
 
+        if let Err(error) = client_database.flush().await {
+            error!(%error, "Failed to flush database on shutdown");
+        }
+
         // TODO: These should be used
         let _: bool = force_synced;
         let _: Option<_> = prometheus_listen_on;
-        let _: NetworkOptions = network_options;
+        let _: Registry = registry;
+        let _: Node = node;
 
         Ok(())
     }
@@ -1,16 +1,17 @@
 use ab_client_consensus_common::{ConsensusConstants, PotConsensusConstants};
 use ab_core_primitives::block::header::{
     BlockHeaderConsensusInfo, BlockHeaderConsensusParameters, BlockHeaderEd25519Seal,
-    BlockHeaderFixedConsensusParameters, BlockHeaderPrefix, BlockHeaderSeal,
+    BlockHeaderExtensions, BlockHeaderFixedConsensusParameters, BlockHeaderPrefix, BlockHeaderSeal,
 };
 use ab_core_primitives::block::owned::OwnedBeaconChainBlock;
 use ab_core_primitives::block::{BlockNumber, BlockRoot, BlockTimestamp};
 use ab_core_primitives::ed25519::{Ed25519PublicKey, Ed25519Signature};
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pot::{PotOutput, SlotDuration, SlotNumber};
-use ab_core_primitives::segments::HistorySize;
+use ab_core_primitives::segments::{HistorySize, SegmentHeader};
 use ab_core_primitives::shard::{NumShards, ShardIndex};
 use ab_core_primitives::solutions::{Solution, SolutionRange};
+use ab_core_primitives::transaction::Gas;
 use std::num::{NonZeroU16, NonZeroU32, NonZeroU64};
 
 const CONSENSUS_CONSTANTS: ConsensusConstants = ConsensusConstants {
@@ -40,6 +41,9 @@ const CONSENSUS_CONSTANTS: ConsensusConstants = ConsensusConstants {
     // TODO: Reduced values just for testing to hit potential bugs sooner
     // shard_rotation_delay: BlockNumber::from(180),
     shard_rotation_delay: BlockNumber::from(18),
+    // Half of the slot duration
+    max_block_transactions_gas: Gas::from(500_000_000),
+    max_block_transactions_size: 4 * 1024 * 1024,
 };
 
 const {
@@ -79,6 +83,13 @@ impl ChainSpec {
         None
     }
 
+    /// Segment headers of the history that existed before genesis, for networks bootstrapped from
+    /// an existing history snapshot
+    pub(super) fn known_segment_headers(&self) -> &[SegmentHeader] {
+        // TODO: Proper value for networks restarted from a snapshot
+        &[]
+    }
+
     pub(super) fn genesis_block(&self) -> OwnedBeaconChainBlock {
         // TODO: Constants need to be mixed into the genesis block somehow, such that they impact
         //  genesis hash
@@ -95,6 +106,8 @@ impl ChainSpec {
                 },
                 // TODO: Genesis state root must be the result of genesis block execution
                 Blake3Hash::default(),
+                // Genesis block has no transactions
+                Blake3Hash::default(),
                 &BlockHeaderConsensusInfo {
                     slot: SlotNumber::ZERO,
                     proof_of_time: PotOutput::default(),
@@ -120,6 +133,7 @@ impl ChainSpec {
                     next_solution_range: None,
                     pot_parameters_change: None,
                 },
+                &BlockHeaderExtensions::EMPTY,
             )
             .expect("Values of the genesis block are valid; qed")
             .with_seal(BlockHeaderSeal::Ed25519(&BlockHeaderEd25519Seal {
@@ -0,0 +1,84 @@
+use ab_client_database::storage_backend::AlignedPage;
+use bytesize::ByteSize;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fmt, io};
+
+/// Size policy for the database that is formatted automatically on first run
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(super) enum DbSizePolicy {
+    /// Fixed size
+    Fixed(ByteSize),
+    /// Percentage of free space on the volume where the database is located
+    FreeSpacePercentage(u8),
+    /// Grow the database on demand
+    GrowOnDemand,
+}
+
+impl FromStr for DbSizePolicy {
+    type Err = String;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "grow" {
+            return Ok(Self::GrowOnDemand);
+        }
+
+        if let Some(percentage) = s.strip_suffix('%') {
+            let percentage = percentage
+                .trim()
+                .parse::<u8>()
+                .map_err(|error| format!("Invalid percentage {percentage}: {error}"))?;
+
+            return if (1..=100).contains(&percentage) {
+                Ok(Self::FreeSpacePercentage(percentage))
+            } else {
+                Err(format!(
+                    "Percentage must be within 1..=100, got {percentage}"
+                ))
+            };
+        }
+
+        let size = ByteSize::from_str(s).map_err(|error| format!("Invalid size {s}: {error}"))?;
+        if size.as_u64() < AlignedPage::SIZE as u64 {
+            return Err(format!("Size {size} is too small"));
+        }
+
+        Ok(Self::Fixed(size))
+    }
+}
+
+impl fmt::Display for DbSizePolicy {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(size) => write!(f, "{size}"),
+            Self::FreeSpacePercentage(percentage) => write!(f, "{percentage}%"),
+            Self::GrowOnDemand => f.write_str("grow"),
+        }
+    }
+}
+
+impl DbSizePolicy {
+    /// Size of the database located at `path` in bytes, rounded down to a multiple of the page
+    /// size.
+    ///
+    /// Returns `None` for [`Self::GrowOnDemand`] since the size is not known upfront.
+    pub(super) fn database_size(self, path: &Path) -> io::Result<Option<u64>> {
+        let size = match self {
+            Self::Fixed(size) => size.as_u64(),
+            Self::FreeSpacePercentage(percentage) => {
+                let available_space = fs4::available_space(path)?;
+
+                (u128::from(available_space) * u128::from(percentage) / 100) as u64
+            }
+            Self::GrowOnDemand => {
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(
+            size / AlignedPage::SIZE as u64 * AlignedPage::SIZE as u64,
+        ))
+    }
+}
@@ -0,0 +1,420 @@
+//! Node keystore.
+//!
+//! Stores key material of the node (networking identity, node operator signing keys) in a
+//! directory on disk, one file per key. Keys can optionally be encrypted with a passphrase.
+//!
+//! Passphrase is stretched with Argon2id into an encryption key, which is used to encrypt keys
+//! with ChaCha20-Poly1305. Key files are only readable by the owner on Unix.
+//!
+//! All key usage goes through [`KeystoreSigner`], such that a remote signer can be plugged in
+//! instead of the local keystore in the future.
+
+#[cfg(test)]
+mod tests;
+
+use ab_core_primitives::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use ab_networking::libp2p::identity::{Keypair, ed25519};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{AeadInOut, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce, Tag};
+use clap::ValueEnum;
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use parity_scale_codec::{Decode, Encode};
+use rand::TryRng;
+use rand::rngs::{SysError, SysRng};
+use std::fs::OpenOptions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+use tracing::{debug, info};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Extension of the file with previous key after rotation
+const PREVIOUS_KEY_EXTENSION: &str = "previous";
+/// Extension of the temporary file used while writing a key
+const TMP_KEY_EXTENSION: &str = "tmp";
+/// Argon2 parameters used for newly encrypted keys (OWASP recommendation for Argon2id)
+const DEFAULT_KDF_PARAMS: KdfParams = KdfParams {
+    memory_cost_kib: 19 * 1024,
+    iterations: 2,
+    parallelism: 1,
+};
+/// Max Argon2 parameters accepted from key files, such that a crafted key file can't force huge
+/// allocations or unbounded computation during key derivation
+const MAX_KDF_PARAMS: KdfParams = KdfParams {
+    memory_cost_kib: 1024 * 1024,
+    iterations: 16,
+    parallelism: 16,
+};
+/// Permissions of key files (read and write by the owner only)
+#[cfg(unix)]
+const KEY_FILE_MODE: u32 = 0o600;
+/// Permissions of the keystore directory (access by the owner only)
+#[cfg(unix)]
+const KEYSTORE_DIRECTORY_MODE: u32 = 0o700;
+
+/// Kind of key stored in the keystore
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub(crate) enum KeyKind {
+    /// Networking identity (libp2p peer ID)
+    Networking,
+    /// Node operator signing key
+    Operator,
+}
+
+impl fmt::Display for KeyKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Networking => "networking",
+            Self::Operator => "operator",
+        })
+    }
+}
+
+impl KeyKind {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Networking => "networking.key",
+            Self::Operator => "operator.key",
+        }
+    }
+}
+
+/// Argon2id parameters used to derive encryption key from the passphrase.
+///
+/// Stored alongside the encrypted key, such that parameters can be changed for new keys without
+/// breaking existing ones.
+#[derive(Debug, Copy, Clone, Encode, Decode, Zeroize)]
+struct KdfParams {
+    memory_cost_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+#[derive(Debug, Encode, Decode, Zeroize)]
+enum KeyFileContents {
+    /// Unencrypted secret key
+    Plain { secret_key: [u8; 32] },
+    /// Secret key encrypted with ChaCha20-Poly1305 using a key derived from the passphrase
+    Encrypted {
+        kdf_params: KdfParams,
+        salt: [u8; 16],
+        nonce: [u8; 12],
+        encrypted_secret_key: [u8; 32],
+        tag: [u8; 16],
+    },
+}
+
+/// Errors happening when working with keystore
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum KeystoreError {
+    /// I/O error occurred
+    #[error("Keystore I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// Decoding error
+    #[error("Decoding error: {0}")]
+    Decoding(#[from] parity_scale_codec::Error),
+    /// Failed to generate randomness
+    #[error("Failed to generate randomness: {0}")]
+    RandomnessGeneration(#[from] SysError),
+    /// Key is encrypted, but passphrase was not provided
+    #[error("Key {kind} is encrypted, but passphrase was not provided")]
+    PassphraseRequired {
+        /// Key kind
+        kind: KeyKind,
+    },
+    /// Invalid key derivation parameters
+    #[error("Invalid key derivation parameters: {0}")]
+    KeyDerivation(#[from] argon2::Error),
+    /// Key derivation parameters exceed supported maximums
+    #[error(
+        "Key derivation parameters of key {kind} exceed supported maximums: memory cost \
+        {memory_cost_kib} KiB, {iterations} iterations, parallelism {parallelism}"
+    )]
+    KdfParamsTooLarge {
+        /// Key kind
+        kind: KeyKind,
+        /// Memory cost in KiB
+        memory_cost_kib: u32,
+        /// Number of iterations
+        iterations: u32,
+        /// Degree of parallelism
+        parallelism: u32,
+    },
+    /// Invalid passphrase
+    #[error("Invalid passphrase for key {kind}")]
+    InvalidPassphrase {
+        /// Key kind
+        kind: KeyKind,
+    },
+    /// Key not found
+    #[error("Key {kind} not found")]
+    KeyNotFound {
+        /// Key kind
+        kind: KeyKind,
+    },
+}
+
+/// Signer of messages with keys of the node
+pub(crate) trait KeystoreSigner {
+    /// Public key of the specified kind
+    fn public_key(&self, kind: KeyKind) -> Result<Ed25519PublicKey, KeystoreError>;
+
+    /// Sign a message with a key of the specified kind
+    #[expect(dead_code, reason = "Not used yet")]
+    fn sign(&self, kind: KeyKind, message: &[u8]) -> Result<Ed25519Signature, KeystoreError>;
+}
+
+/// File-based keystore
+pub(crate) struct Keystore {
+    directory: PathBuf,
+    passphrase: Option<Zeroizing<String>>,
+}
+
+impl fmt::Debug for Keystore {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keystore")
+            .field("directory", &self.directory)
+            .field("encrypted", &self.passphrase.is_some())
+            .finish()
+    }
+}
+
+impl KeystoreSigner for Keystore {
+    fn public_key(&self, kind: KeyKind) -> Result<Ed25519PublicKey, KeystoreError> {
+        let signing_key = self.key(kind)?.ok_or(KeystoreError::KeyNotFound { kind })?;
+
+        Ok(Ed25519PublicKey::from(VerifyingKey::from(&signing_key)))
+    }
+
+    fn sign(&self, kind: KeyKind, message: &[u8]) -> Result<Ed25519Signature, KeystoreError> {
+        let signing_key = self.key(kind)?.ok_or(KeystoreError::KeyNotFound { kind })?;
+
+        Ok(Ed25519Signature::from(signing_key.sign(message)))
+    }
+}
+
+impl Keystore {
+    /// Open keystore in the specified directory, creating the directory if necessary.
+    ///
+    /// If passphrase is provided, it is used to decrypt existing keys and encrypt newly generated
+    /// keys.
+    pub(crate) fn open(
+        directory: PathBuf,
+        passphrase: Option<Zeroizing<String>>,
+    ) -> Result<Self, KeystoreError> {
+        let mut dir_builder = fs::DirBuilder::new();
+        dir_builder.recursive(true);
+        #[cfg(unix)]
+        dir_builder.mode(KEYSTORE_DIRECTORY_MODE);
+        dir_builder.create(&directory)?;
+
+        Ok(Self {
+            directory,
+            passphrase,
+        })
+    }
+
+    /// Directory where keys are stored
+    pub(crate) fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Read key of the specified kind, returns `Ok(None)` if it doesn't exist
+    pub(crate) fn key(&self, kind: KeyKind) -> Result<Option<SigningKey>, KeystoreError> {
+        let key_file = self.directory.join(kind.file_name());
+        if !key_file.exists() {
+            return Ok(None);
+        }
+
+        let bytes = Zeroizing::new(fs::read(key_file)?);
+        let contents = Zeroizing::new(KeyFileContents::decode(&mut bytes.as_ref())?);
+
+        let secret_key = match &*contents {
+            KeyFileContents::Plain { secret_key } => Zeroizing::new(*secret_key),
+            KeyFileContents::Encrypted {
+                kdf_params,
+                salt,
+                nonce,
+                encrypted_secret_key,
+                tag,
+            } => {
+                let passphrase = self
+                    .passphrase
+                    .as_ref()
+                    .ok_or(KeystoreError::PassphraseRequired { kind })?;
+                if kdf_params.memory_cost_kib > MAX_KDF_PARAMS.memory_cost_kib
+                    || kdf_params.iterations > MAX_KDF_PARAMS.iterations
+                    || kdf_params.parallelism > MAX_KDF_PARAMS.parallelism
+                {
+                    return Err(KeystoreError::KdfParamsTooLarge {
+                        kind,
+                        memory_cost_kib: kdf_params.memory_cost_kib,
+                        iterations: kdf_params.iterations,
+                        parallelism: kdf_params.parallelism,
+                    });
+                }
+                let encryption_key = derive_encryption_key(passphrase, salt, kdf_params)?;
+
+                let mut secret_key = Zeroizing::new(*encrypted_secret_key);
+                ChaCha20Poly1305::new(&(*encryption_key).into())
+                    .decrypt_inout_detached(
+                        &Nonce::from(*nonce),
+                        kind.file_name().as_bytes(),
+                        secret_key.as_mut_slice().into(),
+                        &Tag::from(*tag),
+                    )
+                    // Authentication failure means the passphrase is wrong (or the file was
+                    // tampered with)
+                    .map_err(|_error| KeystoreError::InvalidPassphrase { kind })?;
+
+                secret_key
+            }
+        };
+
+        Ok(Some(SigningKey::from(*secret_key)))
+    }
+
+    /// Read key of the specified kind or generate a new one if it doesn't exist
+    pub(crate) fn key_or_generate(&self, kind: KeyKind) -> Result<SigningKey, KeystoreError> {
+        if let Some(signing_key) = self.key(kind)? {
+            return Ok(signing_key);
+        }
+
+        debug!(%kind, "Key not found, generating a new one");
+        self.generate(kind)
+    }
+
+    /// Rotate key of the specified kind.
+    ///
+    /// The current key (if any) is preserved in a file with `.previous` extension, replacing the
+    /// key preserved during the previous rotation.
+    pub(crate) fn rotate(&self, kind: KeyKind) -> Result<SigningKey, KeystoreError> {
+        let key_file = self.directory.join(kind.file_name());
+        if key_file.exists() {
+            // Make sure the current key is readable before replacing it
+            self.key(kind)?;
+        }
+
+        // New key is fully written before the current key is moved, such that there is always a
+        // key in place if anything fails
+        let (signing_key, tmp_key_file) = self.write_tmp_key(kind)?;
+        if key_file.exists() {
+            fs::rename(&key_file, key_file.with_extension(PREVIOUS_KEY_EXTENSION))?;
+        }
+        fs::rename(tmp_key_file, key_file)?;
+        info!(%kind, "Key rotated");
+
+        Ok(signing_key)
+    }
+
+    /// Networking identity keypair, generated if it doesn't exist yet
+    pub(crate) fn networking_keypair(&self) -> Result<Keypair, KeystoreError> {
+        let signing_key = self.key_or_generate(KeyKind::Networking)?;
+
+        let keypair = ed25519::Keypair::from(
+            ed25519::SecretKey::try_from_bytes(&mut signing_key.to_bytes())
+                .expect("Secret key is exactly 32 bytes in size; qed"),
+        );
+
+        Ok(Keypair::from(keypair))
+    }
+
+    /// Generate a new key and store it, overriding the key that might already exist
+    fn generate(&self, kind: KeyKind) -> Result<SigningKey, KeystoreError> {
+        // Write to a temporary file first, such that partially written key never replaces an
+        // existing one
+        let (signing_key, tmp_key_file) = self.write_tmp_key(kind)?;
+        fs::rename(tmp_key_file, self.directory.join(kind.file_name()))?;
+
+        Ok(signing_key)
+    }
+
+    /// Generate a new key and write it to a temporary file, returns the key and the path to the
+    /// file, which is to be renamed into the actual key file
+    fn write_tmp_key(&self, kind: KeyKind) -> Result<(SigningKey, PathBuf), KeystoreError> {
+        let mut secret_key = Zeroizing::new([0u8; 32]);
+        SysRng.try_fill_bytes(secret_key.as_mut_slice())?;
+
+        let contents = Zeroizing::new(if let Some(passphrase) = &self.passphrase {
+            let kdf_params = DEFAULT_KDF_PARAMS;
+            let mut salt = [0u8; 16];
+            SysRng.try_fill_bytes(&mut salt)?;
+            let mut nonce = [0u8; 12];
+            SysRng.try_fill_bytes(&mut nonce)?;
+            let encryption_key = derive_encryption_key(passphrase, &salt, &kdf_params)?;
+
+            let mut encrypted_secret_key = *secret_key;
+            let tag = ChaCha20Poly1305::new(&(*encryption_key).into())
+                .encrypt_inout_detached(
+                    &Nonce::from(nonce),
+                    kind.file_name().as_bytes(),
+                    encrypted_secret_key.as_mut_slice().into(),
+                )
+                .expect("Secret key is much smaller than the max message size; qed");
+
+            KeyFileContents::Encrypted {
+                kdf_params,
+                salt,
+                nonce,
+                encrypted_secret_key,
+                tag: tag.into(),
+            }
+        } else {
+            KeyFileContents::Plain {
+                secret_key: *secret_key,
+            }
+        });
+
+        let tmp_key_file = self
+            .directory
+            .join(kind.file_name())
+            .with_extension(TMP_KEY_EXTENSION);
+        // Leftover from an interrupted write might have arbitrary permissions
+        match fs::remove_file(&tmp_key_file) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(error.into());
+            }
+        }
+        {
+            let mut open_options = OpenOptions::new();
+            open_options.write(true).create_new(true);
+            #[cfg(unix)]
+            open_options.mode(KEY_FILE_MODE);
+
+            let mut file = open_options.open(&tmp_key_file)?;
+            file.write_all(&Zeroizing::new(contents.encode()))?;
+            file.sync_all()?;
+        }
+
+        Ok((SigningKey::from(*secret_key), tmp_key_file))
+    }
+}
+
+fn derive_encryption_key(
+    passphrase: &str,
+    salt: &[u8; 16],
+    kdf_params: &KdfParams,
+) -> Result<Zeroizing<[u8; 32]>, KeystoreError> {
+    let KdfParams {
+        memory_cost_kib,
+        iterations,
+        parallelism,
+    } = *kdf_params;
+    let params = Params::new(memory_cost_kib, iterations, parallelism, Some(32))?;
+
+    let mut encryption_key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params).hash_password_into(
+        passphrase.as_bytes(),
+        salt,
+        encryption_key.as_mut_slice(),
+    )?;
+
+    Ok(encryption_key)
+}
@@ -0,0 +1,257 @@
+use crate::keystore::{
+    KdfParams, KeyFileContents, KeyKind, Keystore, KeystoreError, KeystoreSigner,
+    PREVIOUS_KEY_EXTENSION, TMP_KEY_EXTENSION,
+};
+use parity_scale_codec::Encode;
+use std::fs;
+use tempfile::TempDir;
+use zeroize::Zeroizing;
+
+fn passphrase(passphrase: &str) -> Option<Zeroizing<String>> {
+    Some(Zeroizing::new(passphrase.to_string()))
+}
+
+#[test]
+fn round_trip_plain() {
+    let directory = TempDir::new().unwrap();
+    let keystore = Keystore::open(directory.path().to_path_buf(), None).unwrap();
+
+    assert!(keystore.key(KeyKind::Operator).unwrap().is_none());
+    assert!(matches!(
+        keystore.public_key(KeyKind::Operator),
+        Err(KeystoreError::KeyNotFound {
+            kind: KeyKind::Operator
+        })
+    ));
+
+    let signing_key = keystore.key_or_generate(KeyKind::Operator).unwrap();
+    assert_eq!(
+        keystore.key_or_generate(KeyKind::Operator).unwrap(),
+        signing_key
+    );
+
+    // Reopening the keystore returns the same key
+    let keystore = Keystore::open(directory.path().to_path_buf(), None).unwrap();
+    assert_eq!(
+        keystore.key(KeyKind::Operator).unwrap().unwrap(),
+        signing_key
+    );
+    // Different kinds of keys are independent
+    assert!(keystore.key(KeyKind::Networking).unwrap().is_none());
+}
+
+#[test]
+fn round_trip_encrypted() {
+    let directory = TempDir::new().unwrap();
+    let keystore =
+        Keystore::open(directory.path().to_path_buf(), passphrase("correct horse")).unwrap();
+
+    let signing_key = keystore.key_or_generate(KeyKind::Operator).unwrap();
+    let networking_keypair = keystore.networking_keypair().unwrap();
+
+    // Secret key is not stored in plain text
+    let key_file = fs::read(directory.path().join(KeyKind::Operator.file_name())).unwrap();
+    assert!(
+        !key_file
+            .windows(signing_key.as_bytes().len())
+            .any(|window| window == signing_key.as_bytes())
+    );
+
+    let keystore =
+        Keystore::open(directory.path().to_path_buf(), passphrase("correct horse")).unwrap();
+    assert_eq!(
+        keystore.key(KeyKind::Operator).unwrap().unwrap(),
+        signing_key
+    );
+    assert_eq!(
+        keystore.networking_keypair().unwrap().public(),
+        networking_keypair.public()
+    );
+}
+
+#[test]
+fn wrong_passphrase() {
+    let directory = TempDir::new().unwrap();
+    let keystore =
+        Keystore::open(directory.path().to_path_buf(), passphrase("correct horse")).unwrap();
+    keystore.key_or_generate(KeyKind::Operator).unwrap();
+
+    let keystore =
+        Keystore::open(directory.path().to_path_buf(), passphrase("battery staple")).unwrap();
+    assert!(matches!(
+        keystore.key(KeyKind::Operator),
+        Err(KeystoreError::InvalidPassphrase {
+            kind: KeyKind::Operator
+        })
+    ));
+    // Existing key must not be replaced with a new one when passphrase is wrong
+    assert!(matches!(
+        keystore.key_or_generate(KeyKind::Operator),
+        Err(KeystoreError::InvalidPassphrase {
+            kind: KeyKind::Operator
+        })
+    ));
+
+    let keystore = Keystore::open(directory.path().to_path_buf(), None).unwrap();
+    assert!(matches!(
+        keystore.key(KeyKind::Operator),
+        Err(KeystoreError::PassphraseRequired {
+            kind: KeyKind::Operator
+        })
+    ));
+}
+
+#[test]
+fn tampered_key_file() {
+    let directory = TempDir::new().unwrap();
+    let keystore =
+        Keystore::open(directory.path().to_path_buf(), passphrase("correct horse")).unwrap();
+    keystore.key_or_generate(KeyKind::Operator).unwrap();
+
+    let key_file_path = directory.path().join(KeyKind::Operator.file_name());
+    let mut key_file = fs::read(&key_file_path).unwrap();
+    // Flip a bit in the encrypted secret key
+    let encrypted_secret_key_offset = key_file.len() - 16 - 32;
+    key_file[encrypted_secret_key_offset] ^= 1;
+    fs::write(&key_file_path, key_file).unwrap();
+
+    assert!(matches!(
+        keystore.key(KeyKind::Operator),
+        Err(KeystoreError::InvalidPassphrase {
+            kind: KeyKind::Operator
+        })
+    ));
+}
+
+#[test]
+fn rotation() {
+    let directory = TempDir::new().unwrap();
+    let keystore =
+        Keystore::open(directory.path().to_path_buf(), passphrase("correct horse")).unwrap();
+
+    // Rotation without an existing key simply generates one
+    let first_key = keystore.rotate(KeyKind::Operator).unwrap();
+    assert_eq!(keystore.key(KeyKind::Operator).unwrap().unwrap(), first_key);
+
+    let second_key = keystore.rotate(KeyKind::Operator).unwrap();
+    assert_ne!(second_key, first_key);
+    assert_eq!(
+        keystore.key(KeyKind::Operator).unwrap().unwrap(),
+        second_key
+    );
+
+    // Previous key is preserved and can still be decrypted
+    let key_file_path = directory.path().join(KeyKind::Operator.file_name());
+    let previous_key_file_path = key_file_path.with_extension(PREVIOUS_KEY_EXTENSION);
+    let previous_key_directory = TempDir::new().unwrap();
+    fs::copy(
+        &previous_key_file_path,
+        previous_key_directory
+            .path()
+            .join(KeyKind::Operator.file_name()),
+    )
+    .unwrap();
+    let previous_keystore = Keystore::open(
+        previous_key_directory.path().to_path_buf(),
+        passphrase("correct horse"),
+    )
+    .unwrap();
+    assert_eq!(
+        previous_keystore.key(KeyKind::Operator).unwrap().unwrap(),
+        first_key
+    );
+
+    // Rotation with a wrong passphrase must not touch existing keys
+    let wrong_keystore =
+        Keystore::open(directory.path().to_path_buf(), passphrase("battery staple")).unwrap();
+    assert!(matches!(
+        wrong_keystore.rotate(KeyKind::Operator),
+        Err(KeystoreError::InvalidPassphrase {
+            kind: KeyKind::Operator
+        })
+    ));
+    assert_eq!(
+        keystore.key(KeyKind::Operator).unwrap().unwrap(),
+        second_key
+    );
+}
+
+#[test]
+fn excessive_kdf_params() {
+    let directory = TempDir::new().unwrap();
+    let keystore =
+        Keystore::open(directory.path().to_path_buf(), passphrase("correct horse")).unwrap();
+
+    let contents = KeyFileContents::Encrypted {
+        kdf_params: KdfParams {
+            memory_cost_kib: u32::MAX,
+            iterations: 2,
+            parallelism: 1,
+        },
+        salt: [1; _],
+        nonce: [2; _],
+        encrypted_secret_key: [3; _],
+        tag: [4; _],
+    };
+    fs::write(
+        directory.path().join(KeyKind::Operator.file_name()),
+        contents.encode(),
+    )
+    .unwrap();
+
+    // Parameters are rejected before attempting to derive the key
+    assert!(matches!(
+        keystore.key(KeyKind::Operator),
+        Err(KeystoreError::KdfParamsTooLarge {
+            kind: KeyKind::Operator,
+            memory_cost_kib: u32::MAX,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn failed_rotation_keeps_current_key() {
+    let directory = TempDir::new().unwrap();
+    let keystore = Keystore::open(directory.path().to_path_buf(), None).unwrap();
+    let signing_key = keystore.key_or_generate(KeyKind::Operator).unwrap();
+
+    // Non-empty directory in place of the temporary file makes writing the new key fail
+    let key_file_path = directory.path().join(KeyKind::Operator.file_name());
+    let tmp_key_file_path = key_file_path.with_extension(TMP_KEY_EXTENSION);
+    fs::create_dir(&tmp_key_file_path).unwrap();
+    fs::write(tmp_key_file_path.join("file"), []).unwrap();
+
+    keystore.rotate(KeyKind::Operator).unwrap_err();
+    assert!(
+        !key_file_path
+            .with_extension(PREVIOUS_KEY_EXTENSION)
+            .exists()
+    );
+    assert_eq!(
+        keystore.key_or_generate(KeyKind::Operator).unwrap(),
+        signing_key
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn key_file_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let directory = TempDir::new().unwrap();
+    let keystore_directory = directory.path().join("keystore");
+    let keystore = Keystore::open(keystore_directory.clone(), None).unwrap();
+    keystore.key_or_generate(KeyKind::Networking).unwrap();
+    keystore.rotate(KeyKind::Networking).unwrap();
+
+    let mode = |path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+    assert_eq!(mode(keystore_directory.clone()), 0o700);
+    let key_file_path = keystore_directory.join(KeyKind::Networking.file_name());
+    assert_eq!(mode(key_file_path.clone()), 0o600);
+    assert_eq!(
+        mode(key_file_path.with_extension(PREVIOUS_KEY_EXTENSION)),
+        0o600
+    );
+}
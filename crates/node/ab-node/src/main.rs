@@ -12,10 +12,12 @@
 #![feature(generic_const_exprs)]
 
 mod cli;
+mod keystore;
 mod storage_backend;
 
 use crate::cli::CliCommand;
 use crate::cli::format_database::{FormatDb, FormatDbError};
+use crate::cli::rotate_key::{RotateKey, RotateKeyError};
 use crate::cli::run::{Run, RunError};
 use ab_cli_utils::{init_logger, raise_fd_limit, set_exit_on_panic};
 use ab_client_database::storage_backend::AlignedPage;
@@ -42,6 +44,8 @@ enum Cli {
     FormatDb(FormatDb),
     /// Run the blockchain node
     Run(Run),
+    /// Rotate a key in the keystore
+    RotateKey(RotateKey),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -52,6 +56,9 @@ enum Error {
     /// Run error
     #[error("Run error: {0}")]
     Run(#[from] RunError),
+    /// Rotate key error
+    #[error("Rotate key error: {0}")]
+    RotateKey(#[from] RotateKeyError),
 }
 
 fn main() -> Result<(), Error> {
@@ -62,5 +69,6 @@ fn main() -> Result<(), Error> {
     match Cli::parse() {
         Cli::FormatDb(cmd) => cmd.run(),
         Cli::Run(cmd) => cmd.run(),
+        Cli::RotateKey(cmd) => cmd.run(),
     }
 }
@@ -3,6 +3,7 @@ use ab_direct_io_file::DirectIoFile;
 use futures::channel::oneshot;
 use rclite::Arc;
 use std::io;
+use std::sync::Arc as StdArc;
 use tracing::{Span, debug};
 
 // TODO: This is a simple wrapper, but it will need to deal with multiple dynamic chains eventually
@@ -67,9 +68,9 @@ impl ClientDatabaseStorageBackend for FileStorageBackend {
     #[inline(always)]
     fn write(
         &self,
-        buffer: Vec<AlignedPage>,
+        buffer: StdArc<Vec<AlignedPage>>,
         offset: u32,
-    ) -> oneshot::Receiver<io::Result<Vec<AlignedPage>>> {
+    ) -> oneshot::Receiver<io::Result<StdArc<Vec<AlignedPage>>>> {
         let offset = u64::from(offset) * AlignedPage::SIZE as u64;
         let (sender, receiver) = oneshot::channel();
 
@@ -96,6 +97,26 @@ impl ClientDatabaseStorageBackend for FileStorageBackend {
 
         receiver
     }
+
+    #[inline(always)]
+    fn sync(&self) -> oneshot::Receiver<io::Result<()>> {
+        let (sender, receiver) = oneshot::channel();
+
+        tokio::task::spawn_blocking({
+            let file = Arc::clone(&self.file);
+            let span = Span::current();
+
+            move || {
+                let _guard = span.enter();
+
+                if sender.send(file.file().sync_data()).is_err() {
+                    debug!("Failed to send a sync result back, receiver dropped");
+                }
+            }
+        });
+
+        receiver
+    }
 }
 
 impl FileStorageBackend {
@@ -6,6 +6,9 @@
 //! [`SharedAlignedBuffer`] can't be modified but supports cheap reference-counting clones (like
 //! `Arc`, but much more efficient).
 //!
+//! [`AlignedBufferChain`] represents a logical buffer that consists of multiple
+//! [`SharedAlignedBuffer`] segments, allowing zero-copy concatenation and vectored writes.
+//!
 //! Does not require a standard library (`no_std`) but does require allocator and atomics.
 
 #![feature(const_block_items, box_vec_non_null)]
@@ -19,6 +22,8 @@ extern crate alloc;
 
 use alloc::alloc::realloc;
 use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
 use core::alloc::Layout;
 use core::mem::MaybeUninit;
 use core::ops::{Deref, DerefMut};
@@ -484,3 +489,156 @@ impl SharedAlignedBuffer {
         self.inner.len()
     }
 }
+
+/// Logical buffer that consists of multiple [`SharedAlignedBuffer`] segments.
+///
+/// Concatenation is zero-copy, data is only copied when the chain is written somewhere with
+/// [`Self::write_to()`] or converted into a contiguous buffer with [`Self::to_contiguous()`].
+///
+/// Each segment is aligned to 16 bytes individually, but there is no alignment guarantee for
+/// segment boundaries in the logical buffer.
+#[derive(Debug, Default, Clone)]
+pub struct AlignedBufferChain {
+    segments: Vec<SharedAlignedBuffer>,
+    len: u32,
+}
+
+impl From<SharedAlignedBuffer> for AlignedBufferChain {
+    #[inline(always)]
+    fn from(buffer: SharedAlignedBuffer) -> Self {
+        Self {
+            len: buffer.len(),
+            segments: if buffer.is_empty() {
+                Vec::new()
+            } else {
+                vec![buffer]
+            },
+        }
+    }
+}
+
+impl AlignedBufferChain {
+    /// Create a new empty instance
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            segments: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Create a new empty instance with capacity for at least `num_segments` segments
+    #[inline(always)]
+    pub fn with_capacity(num_segments: usize) -> Self {
+        Self {
+            segments: Vec::with_capacity(num_segments),
+            len: 0,
+        }
+    }
+
+    /// Append a segment to the end of the chain without copying its contents.
+    ///
+    /// Empty segments are ignored.
+    ///
+    /// Returns `false` if `self.len() + segment.len()` doesn't fit into `u32`.
+    #[inline]
+    #[must_use]
+    pub fn push(&mut self, segment: SharedAlignedBuffer) -> bool {
+        let Some(new_len) = self.len.checked_add(segment.len()) else {
+            return false;
+        };
+
+        if !segment.is_empty() {
+            self.segments.push(segment);
+            self.len = new_len;
+        }
+
+        true
+    }
+
+    /// Append segments of another chain to the end of this chain without copying their contents.
+    ///
+    /// Returns `false` if `self.len() + other.len()` doesn't fit into `u32`.
+    #[inline]
+    #[must_use]
+    pub fn append(&mut self, mut other: Self) -> bool {
+        let Some(new_len) = self.len.checked_add(other.len) else {
+            return false;
+        };
+
+        self.segments.append(&mut other.segments);
+        self.len = new_len;
+
+        true
+    }
+
+    /// Segments of the chain, none of them are empty
+    #[inline(always)]
+    pub fn segments(&self) -> &[SharedAlignedBuffer] {
+        &self.segments
+    }
+
+    /// Iterator over slices of all segments in order, suitable for vectored IO
+    #[inline]
+    pub fn slices(&self) -> impl ExactSizeIterator<Item = &[u8]> {
+        self.segments.iter().map(SharedAlignedBuffer::as_slice)
+    }
+
+    /// Write contents of all segments into the output buffer.
+    ///
+    /// Returns initialized bytes of the output buffer (`self.len()` bytes from the beginning) or
+    /// `None` if the output buffer is too small.
+    #[inline]
+    pub fn write_to<'a>(&self, output: &'a mut [MaybeUninit<u8>]) -> Option<&'a mut [u8]> {
+        let output = output.get_mut(..self.len as usize)?;
+
+        let mut offset = 0;
+        for segment in self.slices() {
+            // SAFETY: Total length of all segments is `self.len`, which was checked above,
+            // natural alignment of bytes is 1 for input and output, non-overlapping allocations
+            // guaranteed by the type system
+            unsafe {
+                output
+                    .as_mut_ptr()
+                    .add(offset)
+                    .cast::<u8>()
+                    .copy_from_nonoverlapping(segment.as_ptr(), segment.len());
+            }
+            offset += segment.len();
+        }
+
+        // SAFETY: All `self.len` bytes were initialized above
+        Some(unsafe { output.assume_init_mut() })
+    }
+
+    /// Convert into a contiguous buffer.
+    ///
+    /// This is zero-copy if the chain consists of at most one segment, otherwise contents of all
+    /// segments are copied into a new allocation.
+    #[inline]
+    pub fn to_contiguous(&self) -> SharedAlignedBuffer {
+        match self.segments.as_slice() {
+            [] => SharedAlignedBuffer::default(),
+            [segment] => segment.clone(),
+            _ => {
+                let mut buffer = OwnedAlignedBuffer::with_capacity(self.len);
+                for segment in self.slices() {
+                    let true = buffer.append(segment) else {
+                        unreachable!("Total length fits into `u32`; qed");
+                    };
+                }
+                buffer.into_shared()
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline(always)]
+    pub const fn len(&self) -> u32 {
+        self.len
+    }
+}
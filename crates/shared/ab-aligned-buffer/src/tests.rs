@@ -1,5 +1,6 @@
-use crate::{OwnedAlignedBuffer, SharedAlignedBuffer};
+use crate::{AlignedBufferChain, OwnedAlignedBuffer, SharedAlignedBuffer};
 use alloc::vec;
+use core::mem::MaybeUninit;
 use core::ops::{Deref, DerefMut};
 
 const EXPECTED_ALIGNMENT: usize = align_of::<u128>();
@@ -228,3 +229,35 @@ fn realloc() {
     // Ensure it didn't reallocate
     assert_eq!(ptr_before_append, owned.as_ptr());
 }
+
+#[test]
+fn chain() {
+    let mut chain = AlignedBufferChain::new();
+    assert!(chain.is_empty());
+    assert_eq!(chain.to_contiguous().as_slice(), b"");
+
+    let abc = SharedAlignedBuffer::from_bytes(b"abc");
+    assert!(chain.push(abc.clone()));
+    assert!(chain.push(SharedAlignedBuffer::default()));
+    assert_eq!(chain.segments().len(), 1);
+    // Single segment is not copied
+    assert_eq!(chain.to_contiguous().as_ptr(), abc.as_ptr());
+
+    let mut other = AlignedBufferChain::from(SharedAlignedBuffer::from_bytes(b"de"));
+    assert!(other.push(SharedAlignedBuffer::from_bytes(b"fgh")));
+    assert!(chain.append(other));
+    assert_eq!(chain.len(), 8);
+    assert_eq!(chain.segments().len(), 3);
+    assert_eq!(
+        chain.slices().collect::<vec::Vec<_>>(),
+        [&b"abc"[..], b"de", b"fgh"]
+    );
+
+    let contiguous = chain.to_contiguous();
+    assert_eq!(contiguous.as_slice(), b"abcdefgh");
+    assert!(contiguous.as_ptr().is_aligned_to(EXPECTED_ALIGNMENT));
+
+    let mut output = [MaybeUninit::uninit(); 10];
+    assert!(chain.write_to(&mut output[..7]).is_none());
+    assert_eq!(chain.write_to(&mut output).unwrap(), b"abcdefgh");
+}
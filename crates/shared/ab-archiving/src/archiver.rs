@@ -1,4 +1,4 @@
-use crate::objects::{BlockObject, GlobalObject};
+use crate::objects::{BlockObject, GlobalObject, ObjectMappingPolicy};
 use ab_core_primitives::block::BlockNumber;
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pieces::{
@@ -279,6 +279,8 @@ pub struct Archiver {
     prev_segment_header_hash: Blake3Hash,
     /// Last archived block
     last_archived_block: Option<LastArchivedBlock>,
+    /// Policy enforced on object mappings of added blocks
+    object_mapping_policy: ObjectMappingPolicy,
 }
 
 impl Archiver {
@@ -291,20 +293,36 @@ impl Archiver {
             segment_index: LocalSegmentIndex::ZERO,
             prev_segment_header_hash: Blake3Hash::default(),
             last_archived_block: None,
+            object_mapping_policy: ObjectMappingPolicy::default(),
         }
     }
 
+    /// Set policy enforced on object mappings of added blocks.
+    ///
+    /// The archiver can only enforce the number of objects per block and that objects start
+    /// within the block, the rest of the policy is expected to be applied by the extraction hook
+    /// (see [`ObjectMappingPolicy::apply()`]).
+    pub fn with_object_mapping_policy(
+        mut self,
+        object_mapping_policy: ObjectMappingPolicy,
+    ) -> Self {
+        self.object_mapping_policy = object_mapping_policy;
+        self
+    }
+
     /// Create a new instance of the archiver with the initial state in case of restart.
     ///
     /// `block` corresponds to `last_archived_block` and will be processed according to its state.
     pub fn with_initial_state(
         shard_index: ShardIndex,
         erasure_coding: ErasureCoding,
+        object_mapping_policy: ObjectMappingPolicy,
         segment_header: SegmentHeader,
         encoded_block: &[u8],
         mut block_objects: Vec<BlockObject>,
     ) -> Result<Self, ArchiverInstantiationError> {
-        let mut archiver = Self::new(shard_index, erasure_coding);
+        let mut archiver = Self::new(shard_index, erasure_coding)
+            .with_object_mapping_policy(object_mapping_policy);
 
         archiver.segment_index = segment_header.index.as_inner() + LocalSegmentIndex::ONE;
         archiver.prev_segment_header_hash = segment_header.hash();
@@ -337,6 +355,9 @@ impl Archiver {
                     ));
                 }
                 Ordering::Greater => {
+                    archiver
+                        .object_mapping_policy
+                        .enforce(encoded_block_bytes, &mut block_objects);
                     // Take part of the encoded block that wasn't archived yet and push to the
                     // buffer as a block continuation
                     block_objects.retain_mut(|block_object: &mut BlockObject| {
@@ -379,12 +400,15 @@ impl Archiver {
     pub fn add_block(
         &mut self,
         bytes: Vec<u8>,
-        block_objects: Vec<BlockObject>,
+        mut block_objects: Vec<BlockObject>,
     ) -> Option<ArchiveBlockOutcome> {
         if !(1..u32::MAX as usize).contains(&bytes.len()) {
             return None;
         }
 
+        self.object_mapping_policy
+            .enforce(bytes.len() as u32, &mut block_objects);
+
         // Append new block to the buffer
         self.buffer.push_back(SegmentItem::Block {
             bytes: BlockBytes(bytes),
@@ -4,8 +4,10 @@
 //! * for objects within a block
 //! * for global objects in the global history of the blockchain (inside a piece)
 
+use ab_core_primitives::address::Address;
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pieces::PiecePosition;
+use alloc::vec::Vec;
 use parity_scale_codec::{Decode, Encode};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -21,6 +23,89 @@ pub struct BlockObject {
     pub offset: u32,
 }
 
+/// Object found in the block by the extraction hook, before [`ObjectMappingPolicy`] is applied
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BlockObjectCandidate {
+    /// Object stored inside the block
+    pub object: BlockObject,
+    /// Size of the object in bytes
+    pub size: u32,
+    /// Contract that has stored the object
+    pub contract: Address,
+}
+
+/// Policy that limits which objects in a block get mapped.
+///
+/// Object mappings are created for data stored by arbitrary contracts, the policy ensures that
+/// this can't be used to bloat archiving time or the mapping index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectMappingPolicy {
+    /// Max number of objects mapped per block, objects beyond this limit are ignored
+    pub max_objects_per_block: u32,
+    /// Max size of a mapped object in bytes, larger objects are ignored
+    pub max_object_size: u32,
+    /// Contracts whose objects get mapped, `None` means objects of all contracts are mapped
+    pub allowed_contracts: Option<Vec<Address>>,
+}
+
+impl Default for ObjectMappingPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_objects_per_block: Self::DEFAULT_MAX_OBJECTS_PER_BLOCK,
+            max_object_size: Self::DEFAULT_MAX_OBJECT_SIZE,
+            allowed_contracts: None,
+        }
+    }
+}
+
+impl ObjectMappingPolicy {
+    /// Default max number of objects mapped per block
+    pub const DEFAULT_MAX_OBJECTS_PER_BLOCK: u32 = 1024;
+    /// Default max size of a mapped object in bytes
+    pub const DEFAULT_MAX_OBJECT_SIZE: u32 = 1024 * 1024;
+
+    /// Policy that doesn't map any objects
+    pub const DISABLED: Self = Self {
+        max_objects_per_block: 0,
+        max_object_size: 0,
+        allowed_contracts: Some(Vec::new()),
+    };
+
+    /// Check whether an individual object candidate is allowed to be mapped (doesn't check
+    /// per-block limits)
+    #[inline]
+    pub fn is_allowed(&self, candidate: &BlockObjectCandidate) -> bool {
+        candidate.size <= self.max_object_size
+            && self
+                .allowed_contracts
+                .as_ref()
+                .is_none_or(|allowed_contracts| allowed_contracts.contains(&candidate.contract))
+    }
+
+    /// Apply the policy to object candidates of a single block, returning objects that should be
+    /// mapped
+    pub fn apply<I>(&self, candidates: I) -> Vec<BlockObject>
+    where
+        I: IntoIterator<Item = BlockObjectCandidate>,
+    {
+        candidates
+            .into_iter()
+            .filter(|candidate| self.is_allowed(candidate))
+            .map(|candidate| candidate.object)
+            .take(self.max_objects_per_block as usize)
+            .collect()
+    }
+
+    /// Enforce limits that can be checked without knowing object details on objects of an encoded
+    /// block of `block_size` bytes: objects must start within the block and there must not be more
+    /// than [`Self::max_objects_per_block`] of them
+    pub fn enforce(&self, block_size: u32, block_objects: &mut Vec<BlockObject>) {
+        block_objects.retain(|block_object| block_object.offset < block_size);
+        block_objects.truncate(self.max_objects_per_block as usize);
+    }
+}
+
 /// Object stored in the history of the blockchain.
 ///
 /// This data structure is produced during archiving when the piece index is not yet known, hence it
@@ -1,5 +1,6 @@
 use ab_archiving::archiver::{Archiver, ArchiverInstantiationError, SegmentItem};
-use ab_archiving::objects::{BlockObject, GlobalObject};
+use ab_archiving::objects::{BlockObject, BlockObjectCandidate, GlobalObject, ObjectMappingPolicy};
+use ab_core_primitives::address::Address;
 use ab_core_primitives::block::BlockNumber;
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pieces::{Piece, PiecePosition, Record};
@@ -259,6 +260,7 @@ fn archiver() {
         let mut archiver_with_initial_state = Archiver::with_initial_state(
             TEST_SHARD_INDEX,
             erasure_coding.clone(),
+            ObjectMappingPolicy::default(),
             first_archived_segment.segment_header,
             &block_1,
             block_1_block_objects.clone(),
@@ -423,6 +425,7 @@ fn archiver() {
         let mut archiver_with_initial_state = Archiver::with_initial_state(
             TEST_SHARD_INDEX,
             erasure_coding,
+            ObjectMappingPolicy::default(),
             last_segment_header,
             &block_2,
             Vec::new(),
@@ -511,6 +514,7 @@ fn invalid_usage() {
         let result = Archiver::with_initial_state(
             TEST_SHARD_INDEX,
             erasure_coding.clone(),
+            ObjectMappingPolicy::default(),
             SegmentHeader {
                 index: LocalSegmentIndex::ZERO.into(),
                 root: SegmentRoot::default(),
@@ -540,6 +544,7 @@ fn invalid_usage() {
         let result = Archiver::with_initial_state(
             TEST_SHARD_INDEX,
             erasure_coding.clone(),
+            ObjectMappingPolicy::default(),
             SegmentHeader {
                 index: LocalSegmentIndex::ZERO.into(),
                 root: SegmentRoot::default(),
@@ -699,3 +704,73 @@ fn object_on_the_edge_of_segment() {
         mapped_bytes
     );
 }
+
+#[test]
+fn object_mapping_policy() {
+    let allowed_contract = Address::SYSTEM_CODE;
+    let other_contract = Address::SYSTEM_STATE;
+    let object = |offset: u32| BlockObject {
+        hash: Blake3Hash::new([offset as u8; _]),
+        offset,
+    };
+    let candidates = [
+        BlockObjectCandidate {
+            object: object(0),
+            size: 10,
+            contract: allowed_contract,
+        },
+        // Too large
+        BlockObjectCandidate {
+            object: object(1),
+            size: 11,
+            contract: allowed_contract,
+        },
+        // Not allowed contract
+        BlockObjectCandidate {
+            object: object(2),
+            size: 1,
+            contract: other_contract,
+        },
+        BlockObjectCandidate {
+            object: object(3),
+            size: 1,
+            contract: allowed_contract,
+        },
+        // Over the limit of objects per block
+        BlockObjectCandidate {
+            object: object(4),
+            size: 1,
+            contract: allowed_contract,
+        },
+    ];
+    let policy = ObjectMappingPolicy {
+        max_objects_per_block: 2,
+        max_object_size: 10,
+        allowed_contracts: Some(vec![allowed_contract]),
+    };
+    assert_eq!(policy.apply(candidates), vec![object(0), object(3)]);
+    assert!(ObjectMappingPolicy::DISABLED.apply(candidates).is_empty());
+    assert_eq!(
+        ObjectMappingPolicy::default().apply(candidates),
+        candidates.map(|candidate| candidate.object).to_vec()
+    );
+
+    // Archiver enforces the number of objects per block and ignores objects outside the block
+    let mut archiver =
+        Archiver::new(TEST_SHARD_INDEX, ErasureCoding::new()).with_object_mapping_policy(policy);
+    let outcome = archiver
+        .add_block(
+            vec![0u8; 10],
+            vec![object(0), object(10), object(1), object(2)],
+        )
+        .unwrap();
+    assert!(outcome.archived_segments.is_empty());
+    assert_eq!(
+        outcome
+            .global_objects
+            .iter()
+            .map(|global_object| global_object.hash)
+            .collect::<Vec<_>>(),
+        vec![object(0).hash, object(1).hash]
+    );
+}
@@ -163,6 +163,8 @@ impl Address {
     pub const SYSTEM_STATE: Self = Self::from(3);
     /// System contract for native token
     pub const SYSTEM_NATIVE_TOKEN: Self = Self::from(4);
+    /// System contract for managing metadata of other contracts
+    pub const SYSTEM_METADATA: Self = Self::from(5);
     /// System simple wallet base contract that can be used by end user wallets
     pub const SYSTEM_SIMPLE_WALLET_BASE: Self = Self::from(10);
 
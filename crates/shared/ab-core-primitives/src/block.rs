@@ -1,6 +1,9 @@
 //! Block-related primitives
 
 pub mod body;
+pub mod execution;
+#[cfg(feature = "alloc")]
+pub mod fraud_proof;
 pub mod header;
 #[cfg(feature = "alloc")]
 pub mod owned;
@@ -9,7 +9,7 @@ use crate::block::body::owned::{
     OwnedLeafShardBody,
 };
 use crate::block::header::{IntermediateShardHeader, LeafShardHeader};
-use crate::block::{BlockNumber, align_to_and_ensure_zero_padding};
+use crate::block::{BlockNumber, BlockRoot, align_to_and_ensure_zero_padding};
 use crate::hashes::Blake3Hash;
 use crate::pot::PotCheckpoints;
 use crate::segments::{LocalSegmentIndex, SegmentRoot};
@@ -960,6 +960,23 @@ pub struct LeafShardBlockInfo<'a> {
     pub segments: Option<LeafShardOwnSegments<'a>>,
 }
 
+impl LeafShardBlockInfo<'_> {
+    /// Commitment to segments of the leaf shard block, a leaf in
+    /// [`LeafShardBlocksInfo::segments_root()`].
+    ///
+    /// Returns the default value if there are no segments in the leaf shard block.
+    #[inline]
+    pub fn segments_commitment(&self) -> Blake3Hash {
+        self.segments
+            .map(|own_segments| {
+                own_segments
+                    .own_segments
+                    .root_with_shard_index(self.header.prefix.shard_index)
+            })
+            .unwrap_or_default()
+    }
+}
+
 /// Information about a collection of leaf shard blocks
 #[derive(Debug, Copy, Clone)]
 pub struct LeafShardBlocksInfo<'a> {
@@ -1250,16 +1267,8 @@ impl<'a> LeafShardBlocksInfo<'a> {
     #[inline]
     pub fn segments_root(&self) -> Blake3Hash {
         let root = UnbalancedMerkleTree::compute_root_only::<{ u16::MAX as u64 }, _, _>(
-            self.iter().map(|shard_block_info| {
-                shard_block_info
-                    .segments
-                    .map(|own_segments| {
-                        own_segments
-                            .own_segments
-                            .root_with_shard_index(shard_block_info.header.prefix.shard_index)
-                    })
-                    .unwrap_or_default()
-            }),
+            self.iter()
+                .map(|shard_block_info| *shard_block_info.segments_commitment()),
         )
         .unwrap_or_default();
 
@@ -1272,18 +1281,97 @@ impl<'a> LeafShardBlocksInfo<'a> {
     #[inline]
     pub fn headers_root(&self) -> Blake3Hash {
         let root = UnbalancedMerkleTree::compute_root_only::<{ u16::MAX as u64 }, _, _>(
-            self.iter().map(|shard_block_info| {
-                // Hash the root again so we can prove it, otherwise root of headers is
-                // indistinguishable from individual block roots and can be used to confuse
-                // verifier
-                single_block_hash(shard_block_info.header.root().as_ref())
-                    .expect("Less than a single block worth of bytes; qed")
-            }),
+            self.iter()
+                .map(|shard_block_info| Self::header_leaf(&shard_block_info.header.root())),
         )
         .unwrap_or_default();
 
         Blake3Hash::new(root)
     }
+
+    /// Generate a proof of inclusion of the segments commitment (see
+    /// [`LeafShardBlockInfo::segments_commitment()`]) of the leaf shard block at `index` into
+    /// [`Self::segments_root()`].
+    ///
+    /// `None` is returned if `index` is out of range.
+    #[cfg(feature = "alloc")]
+    pub fn segments_proof(&self, index: usize) -> Option<alloc::vec::Vec<[u8; OUT_LEN]>> {
+        let mut proof = [core::mem::MaybeUninit::uninit(); _];
+        let (_root, proof) =
+            UnbalancedMerkleTree::compute_root_and_proof_in::<{ u16::MAX as u64 }, _, _>(
+                self.iter()
+                    .map(|shard_block_info| *shard_block_info.segments_commitment()),
+                index,
+                &mut proof,
+            )?;
+
+        Some(proof.to_vec())
+    }
+
+    /// Generate a proof of inclusion of the header of the leaf shard block at `index` into
+    /// [`Self::headers_root()`].
+    ///
+    /// `None` is returned if `index` is out of range.
+    #[cfg(feature = "alloc")]
+    pub fn header_proof(&self, index: usize) -> Option<alloc::vec::Vec<[u8; OUT_LEN]>> {
+        let mut proof = [core::mem::MaybeUninit::uninit(); _];
+        let (_root, proof) =
+            UnbalancedMerkleTree::compute_root_and_proof_in::<{ u16::MAX as u64 }, _, _>(
+                self.iter()
+                    .map(|shard_block_info| Self::header_leaf(&shard_block_info.header.root())),
+                index,
+                &mut proof,
+            )?;
+
+        Some(proof.to_vec())
+    }
+
+    /// Verify that the segments commitment (see [`LeafShardBlockInfo::segments_commitment()`]) of
+    /// a leaf shard block is included in the root of segments (see [`Self::segments_root()`]) at
+    /// `index` using provided proof
+    #[inline]
+    pub fn verify_segments_inclusion(
+        segments_root: &Blake3Hash,
+        proof: &[[u8; OUT_LEN]],
+        index: u16,
+        num_blocks: u16,
+        segments_commitment: &Blake3Hash,
+    ) -> bool {
+        UnbalancedMerkleTree::verify(
+            segments_root,
+            proof,
+            u64::from(index),
+            **segments_commitment,
+            u64::from(num_blocks),
+        )
+    }
+
+    /// Verify that the header of a leaf shard block is included in the root of headers (see
+    /// [`Self::headers_root()`]) at `index` using provided proof
+    #[inline]
+    pub fn verify_header_inclusion(
+        headers_root: &Blake3Hash,
+        proof: &[[u8; OUT_LEN]],
+        index: u16,
+        num_blocks: u16,
+        header_root: &BlockRoot,
+    ) -> bool {
+        UnbalancedMerkleTree::verify(
+            headers_root,
+            proof,
+            u64::from(index),
+            Self::header_leaf(header_root),
+            u64::from(num_blocks),
+        )
+    }
+
+    #[inline]
+    fn header_leaf(header_root: &BlockRoot) -> [u8; OUT_LEN] {
+        // Hash the root again so we can prove it, otherwise root of headers is indistinguishable
+        // from individual block roots and can be used to confuse verifier
+        single_block_hash(header_root.as_ref())
+            .expect("Less than a single block worth of bytes; qed")
+    }
 }
 
 /// Block body that corresponds to an intermediate shard
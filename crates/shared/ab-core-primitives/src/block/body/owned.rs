@@ -4,6 +4,7 @@ use crate::block::body::{
     BeaconChainBody, BlockBody, GenericBlockBody, IntermediateShardBlockInfo,
     IntermediateShardBody, LeafShardBlockInfo, LeafShardBody,
 };
+use crate::block::header::BlockHeaderExtensions;
 use crate::block::header::owned::{
     OwnedIntermediateShardHeader, OwnedIntermediateShardHeaderError, OwnedLeafShardHeader,
 };
@@ -313,7 +314,10 @@ impl OwnedBeaconChainBody {
                 + u16::SIZE
                 // This is only an estimate to get in the ballpark where reallocation should not be
                 // necessary in many cases
-                + u32::from(num_blocks) * OwnedIntermediateShardHeader::max_allocation_for(&[]) * 2,
+                + u32::from(num_blocks) * OwnedIntermediateShardHeader::max_allocation_for(
+                    &[],
+                    &BlockHeaderExtensions::EMPTY,
+                ) * 2,
         );
 
         let true = buffer.append(&num_pot_checkpoints.to_le_bytes()) else {
@@ -383,6 +387,7 @@ impl OwnedBeaconChainBody {
                     intermediate_shard_block.header.consensus_info,
                     intermediate_shard_block.header.beacon_chain_info(),
                     intermediate_shard_block.header.child_shard_blocks(),
+                    intermediate_shard_block.header.extensions(),
                     &mut buffer,
                 )?;
 
@@ -572,7 +577,7 @@ impl OwnedIntermediateShardBody {
                 + u32::from(num_own_segment_roots) * SegmentRoot::SIZE as u32
                 // This is only an estimate to get in the ballpark where reallocation should not be
                 // necessary
-                + u32::from(num_blocks) * OwnedLeafShardHeader::MAX_ALLOCATION * 2,
+                + u32::from(num_blocks) * OwnedLeafShardHeader::max_allocation_for(&BlockHeaderExtensions::EMPTY) * 2,
         );
 
         let true = buffer.append(&[num_own_segment_roots]) else {
@@ -621,6 +626,7 @@ impl OwnedIntermediateShardBody {
                     leaf_shard_block.header.result,
                     leaf_shard_block.header.consensus_info,
                     leaf_shard_block.header.beacon_chain_info(),
+                    leaf_shard_block.header.extensions(),
                     &mut buffer,
                 );
                 let true = align_to_8_with_padding(&mut buffer) else {
@@ -0,0 +1,95 @@
+//! Block execution-related primitives
+
+#[cfg(test)]
+mod tests;
+
+use crate::hashes::Blake3Hash;
+use crate::transaction::{Gas, TransactionHash};
+use ab_blake3::{single_block_hash, single_chunk_hash};
+use ab_io_type::trivial_type::TrivialType;
+use ab_merkle_tree::unbalanced::UnbalancedMerkleTree;
+use blake3::CHUNK_LEN;
+
+/// Execution receipt of a single transaction.
+///
+/// Commits to the state transition caused by the transaction, such that the execution of a block
+/// can be challenged one transaction at a time.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TrivialType)]
+#[repr(C)]
+pub struct ExecutionReceipt {
+    /// Hash of the executed transaction
+    pub transaction_hash: TransactionHash,
+    /// Root of the state tree before the transaction was executed
+    pub pre_state_root: Blake3Hash,
+    /// Root of the state tree after the transaction was executed
+    pub post_state_root: Blake3Hash,
+    /// Gas used by the transaction
+    pub gas_used: Gas,
+}
+
+impl ExecutionReceipt {
+    /// Hash of the execution receipt, used as a leaf in [`compute_execution_receipts_root()`]
+    pub fn hash(&self) -> Blake3Hash {
+        const {
+            assert!(size_of::<Self>() <= CHUNK_LEN);
+        }
+        // TODO: Keyed hash
+        Blake3Hash::new(
+            single_chunk_hash(self.as_bytes())
+                .expect("Less than a single chunk worth of bytes; qed"),
+        )
+    }
+
+    /// Leaf of the execution receipt in the tree of execution receipts
+    #[inline]
+    pub fn leaf(&self) -> [u8; Blake3Hash::SIZE] {
+        // Hash the hash again so we can prove it, otherwise root of execution receipts is
+        // indistinguishable from individual execution receipt hashes and can be used to confuse
+        // verifier
+        single_block_hash(self.hash().as_ref())
+            .expect("Less than a single block worth of bytes; qed")
+    }
+
+    /// Verify that the execution receipt is included in the execution receipts root at
+    /// `transaction_index` using provided proof
+    #[inline]
+    pub fn verify_inclusion(
+        &self,
+        execution_receipts_root: &Blake3Hash,
+        proof: &[[u8; Blake3Hash::SIZE]],
+        transaction_index: u32,
+        num_transactions: u32,
+    ) -> bool {
+        UnbalancedMerkleTree::verify(
+            execution_receipts_root,
+            proof,
+            u64::from(transaction_index),
+            self.leaf(),
+            u64::from(num_transactions),
+        )
+    }
+}
+
+/// Calculates a Merkle Tree root for a provided list of execution receipts.
+///
+/// Execution receipts are expected to be in the same order as transactions in the block body.
+/// Returns the default value for an empty collection of execution receipts.
+#[inline]
+pub fn compute_execution_receipts_root<'a, Iter>(execution_receipts: Iter) -> Blake3Hash
+where
+    Iter: IntoIterator<Item = &'a ExecutionReceipt>,
+{
+    // TODO: This is a workaround for https://github.com/rust-lang/rust/issues/139866 that allows
+    //  the code to compile. Constant 4_294_967_295 is hardcoded here for compilation to succeed.
+    #[expect(clippy::assertions_on_constants, reason = "Intentional documentation")]
+    #[expect(clippy::eq_op, reason = "Intentional documentation")]
+    const {
+        assert!(u32::MAX == 4_294_967_295);
+    }
+    let root = UnbalancedMerkleTree::compute_root_only::<4_294_967_295, _, _>(
+        execution_receipts.into_iter().map(ExecutionReceipt::leaf),
+    )
+    .unwrap_or_default();
+
+    Blake3Hash::new(root)
+}
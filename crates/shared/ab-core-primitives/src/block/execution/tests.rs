@@ -0,0 +1,63 @@
+use crate::block::execution::{ExecutionReceipt, compute_execution_receipts_root};
+use crate::hashes::Blake3Hash;
+use crate::transaction::{Gas, TransactionHash};
+use ab_merkle_tree::unbalanced::UnbalancedMerkleTree;
+use core::mem::MaybeUninit;
+
+fn execution_receipts() -> Vec<ExecutionReceipt> {
+    (0..5_u8)
+        .map(|index| ExecutionReceipt {
+            transaction_hash: TransactionHash::from(Blake3Hash::new([index; _])),
+            pre_state_root: Blake3Hash::new([index.wrapping_add(1); _]),
+            post_state_root: Blake3Hash::new([index.wrapping_add(2); _]),
+            gas_used: Gas::from(u64::from(index) * 1_000),
+        })
+        .collect()
+}
+
+#[test]
+fn execution_receipts_root() {
+    assert_eq!(compute_execution_receipts_root(&[]), Blake3Hash::default());
+
+    let execution_receipts = execution_receipts();
+    let root = compute_execution_receipts_root(&execution_receipts);
+    assert_ne!(root, Blake3Hash::default());
+
+    // Order of execution receipts matters
+    let mut reversed = execution_receipts.clone();
+    reversed.reverse();
+    assert_ne!(compute_execution_receipts_root(&reversed), root);
+}
+
+#[test]
+fn execution_receipt_inclusion() {
+    let execution_receipts = execution_receipts();
+    let root = compute_execution_receipts_root(&execution_receipts);
+    let num_transactions = execution_receipts.len() as u32;
+
+    for (transaction_index, execution_receipt) in execution_receipts.iter().enumerate() {
+        let mut proof = [MaybeUninit::uninit(); _];
+        let (computed_root, proof) = UnbalancedMerkleTree::compute_root_and_proof_in::<8, _, _>(
+            execution_receipts.iter().map(ExecutionReceipt::leaf),
+            transaction_index,
+            &mut proof,
+        )
+        .unwrap();
+        assert_eq!(computed_root, *root);
+
+        let transaction_index = transaction_index as u32;
+        assert!(execution_receipt.verify_inclusion(
+            &root,
+            proof,
+            transaction_index,
+            num_transactions
+        ));
+        // Wrong index
+        assert!(!execution_receipt.verify_inclusion(
+            &root,
+            proof,
+            (transaction_index + 1) % num_transactions,
+            num_transactions
+        ));
+    }
+}
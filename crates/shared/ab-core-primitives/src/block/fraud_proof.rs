@@ -0,0 +1,106 @@
+//! Fraud proof primitives.
+//!
+//! Fraud proofs allow a parent shard to optimistically accept blocks of child shards without
+//! executing them, while still being able to reject invalid execution after the fact. A fraud
+//! proof points to a specific transaction in a block and contains just enough of the state before
+//! that transaction to re-execute it and compare the result with the committed execution receipt.
+
+use crate::address::Address;
+use crate::block::BlockRoot;
+use crate::block::execution::ExecutionReceipt;
+use crate::hashes::Blake3Hash;
+use ab_merkle_tree::sparse::SparseMerkleTree;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Number of bits in a path of each level of the state tree
+const STATE_TREE_BITS: u8 = size_of::<Address>() as u8 * u8::BITS as u8;
+
+type Smt128 = SparseMerkleTree<STATE_TREE_BITS>;
+
+/// Proof of a single contract slot in the state tree.
+///
+/// The state tree is a two-level sparse Merkle Tree: the first level is indexed by the owner
+/// address, the second level (owner root) is indexed by the address of the managing contract.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StateSlotProof {
+    /// Owner of the slot
+    pub owner: Address,
+    /// Contract managing the slot
+    pub contract: Address,
+    /// Contents of the slot, `None` if the slot is empty
+    pub contents: Option<Vec<u8>>,
+    /// Root of the subtree of slots that belong to the owner
+    pub owner_root: Blake3Hash,
+    /// Proof of the slot in the subtree of the owner
+    pub contract_proof: Box<[[u8; Blake3Hash::SIZE]; STATE_TREE_BITS as usize]>,
+    /// Proof of the owner subtree in the state tree
+    pub owner_proof: Box<[[u8; Blake3Hash::SIZE]; STATE_TREE_BITS as usize]>,
+}
+
+impl StateSlotProof {
+    /// Verify the proof against the state root
+    pub fn verify(&self, state_root: &Blake3Hash) -> bool {
+        let leaf = self
+            .contents
+            .as_ref()
+            .map_or([0; Blake3Hash::SIZE], |contents| {
+                // TODO: Should probably use keyed hash instead
+                *blake3::hash(contents).as_bytes()
+            });
+
+        Smt128::verify(
+            &self.owner_root,
+            &self.contract_proof,
+            u128::from(self.contract),
+            leaf,
+        ) && Smt128::verify(
+            state_root,
+            &self.owner_proof,
+            u128::from(self.owner),
+            *self.owner_root,
+        )
+    }
+}
+
+/// Fraud proof of an invalid execution receipt.
+///
+/// Claims that re-executing the transaction at `transaction_index` of the block on top of the
+/// state described by `pre_state_proofs` doesn't result in the committed execution receipt.
+// TODO: Verification requires execution environment and will be implemented on top of this
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidExecutionReceiptFraudProof {
+    /// Root of the block that contains the transaction
+    pub block_root: BlockRoot,
+    /// Index of the transaction in the block body
+    pub transaction_index: u32,
+    /// Execution receipt committed in the block for the transaction
+    pub execution_receipt: ExecutionReceipt,
+    /// Proof of the execution receipt in the execution receipts root of the block
+    pub execution_receipt_proof: Vec<[u8; Blake3Hash::SIZE]>,
+    /// Proofs of all slots accessed by the transaction against
+    /// [`ExecutionReceipt::pre_state_root`]
+    pub pre_state_proofs: Vec<StateSlotProof>,
+}
+
+impl InvalidExecutionReceiptFraudProof {
+    /// Check that the fraud proof is well-formed with respect to the block it references.
+    ///
+    /// This verifies that the execution receipt is committed in the block and that all pre-state
+    /// proofs are valid, but it doesn't re-execute the transaction.
+    pub fn verify_structure(
+        &self,
+        execution_receipts_root: &Blake3Hash,
+        num_transactions: u32,
+    ) -> bool {
+        self.execution_receipt.verify_inclusion(
+            execution_receipts_root,
+            &self.execution_receipt_proof,
+            self.transaction_index,
+            num_transactions,
+        ) && self
+            .pre_state_proofs
+            .iter()
+            .all(|proof| proof.verify(&self.execution_receipt.pre_state_root))
+    }
+}
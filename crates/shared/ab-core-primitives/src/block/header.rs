@@ -2,6 +2,8 @@
 
 #[cfg(feature = "alloc")]
 pub mod owned;
+#[cfg(test)]
+mod tests;
 
 #[cfg(feature = "alloc")]
 use crate::block::header::owned::{
@@ -15,13 +17,13 @@ use crate::pot::{PotOutput, PotParametersChange, SlotNumber};
 use crate::segments::SuperSegmentRoot;
 use crate::shard::{NumShards, NumShardsUnchecked, RealShardKind, ShardIndex, ShardKind};
 use crate::solutions::{Solution, SolutionRange};
-use ab_blake3::{BLOCK_LEN, single_block_hash, single_chunk_hash};
+use ab_blake3::{BLOCK_LEN, OUT_LEN, single_block_hash, single_chunk_hash};
 use ab_io_type::trivial_type::TrivialType;
 use ab_merkle_tree::unbalanced::UnbalancedMerkleTree;
 use blake3::CHUNK_LEN;
 use core::num::NonZeroU32;
 use core::ops::Deref;
-use core::{fmt, slice};
+use core::{fmt, iter, slice};
 use derive_more::{Deref, From};
 #[cfg(feature = "scale-codec")]
 use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
@@ -528,19 +530,54 @@ impl<'a> BlockHeaderChildShardBlocks<'a> {
     /// `None` is returned if there are no child shard blocks.
     pub fn root(&self) -> Option<Blake3Hash> {
         let root = UnbalancedMerkleTree::compute_root_only::<'_, { u32::MAX as u64 }, _, _>(
-            // TODO: Keyed hash
-            self.child_shard_blocks
-                .iter()
-                .map(|child_shard_block_root| {
-                    // Hash the root again so we can prove it, otherwise headers root is
-                    // indistinguishable from individual block roots and can be used to confuse
-                    // verifier
-                    single_block_hash(child_shard_block_root.as_ref())
-                        .expect("Less than a single block worth of bytes; qed")
-                }),
+            self.child_shard_blocks.iter().map(Self::leaf),
         )?;
         Some(Blake3Hash::new(root))
     }
+
+    /// Generate a proof of inclusion of the child shard block at `index` into [`Self::root()`].
+    ///
+    /// `None` is returned if `index` is out of range.
+    #[cfg(feature = "alloc")]
+    pub fn proof(&self, index: usize) -> Option<alloc::vec::Vec<[u8; OUT_LEN]>> {
+        let mut proof = [core::mem::MaybeUninit::uninit(); _];
+        let (_root, proof) =
+            UnbalancedMerkleTree::compute_root_and_proof_in::<{ u32::MAX as u64 }, _, _>(
+                self.child_shard_blocks.iter().map(Self::leaf),
+                index,
+                &mut proof,
+            )?;
+
+        Some(proof.to_vec())
+    }
+
+    /// Verify that the child shard block is included in the root of child shard blocks (see
+    /// [`Self::root()`]) at `index` using provided proof
+    #[inline]
+    pub fn verify_inclusion(
+        root: &Blake3Hash,
+        proof: &[[u8; OUT_LEN]],
+        index: u32,
+        num_child_shard_blocks: u32,
+        child_shard_block_root: &BlockRoot,
+    ) -> bool {
+        UnbalancedMerkleTree::verify(
+            root,
+            proof,
+            u64::from(index),
+            Self::leaf(child_shard_block_root),
+            u64::from(num_child_shard_blocks),
+        )
+    }
+
+    #[inline]
+    fn leaf(child_shard_block_root: &BlockRoot) -> [u8; OUT_LEN] {
+        // TODO: Keyed hash
+        // Hash the root again so we can prove it, otherwise headers root is indistinguishable from
+        // individual block roots and can be used to confuse verifier
+        single_block_hash(child_shard_block_root.as_ref())
+            .expect("Less than a single block worth of bytes; qed")
+    }
 }
 
 /// Block header result.
@@ -558,22 +595,224 @@ pub struct BlockHeaderResult {
     /// Root of the state tree
     // TODO: New type?
     pub state_root: Blake3Hash,
+    /// Root of execution receipts of transactions in the block body, see
+    /// [`compute_execution_receipts_root()`]
+    ///
+    /// [`compute_execution_receipts_root()`]: crate::block::execution::compute_execution_receipts_root
+    pub execution_receipts_root: Blake3Hash,
 }
 
 impl BlockHeaderResult {
     /// Hash of the block header result, part of the eventual block root
     pub fn hash(&self) -> Blake3Hash {
         const {
-            assert!(size_of::<Self>() <= BLOCK_LEN);
+            assert!(size_of::<Self>() <= CHUNK_LEN);
         }
         // TODO: Keyed hash
         Blake3Hash::new(
-            single_block_hash(self.as_bytes())
-                .expect("Less than a single block worth of bytes; qed"),
+            single_chunk_hash(self.as_bytes())
+                .expect("Less than a single chunk worth of bytes; qed"),
         )
     }
 }
 
+/// Type of the block header extension entry
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(transparent)]
+pub struct BlockHeaderExtensionType(u8);
+
+impl BlockHeaderExtensionType {
+    /// Create a new instance
+    #[inline(always)]
+    pub const fn new(n: u8) -> Self {
+        Self(n)
+    }
+
+    /// Get internal representation
+    #[inline(always)]
+    pub const fn as_u8(self) -> u8 {
+        self.0
+    }
+}
+
+/// Version of block header extensions.
+///
+/// Version determines consensus rules for extensions, like which extension types are allowed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum BlockHeaderExtensionsVersion {
+    /// Initial version, no extension types are allowed
+    #[cfg_attr(feature = "scale-codec", codec(index = 0))]
+    V0 = 0,
+}
+
+impl BlockHeaderExtensionsVersion {
+    /// Create an instance from bytes if valid
+    #[inline(always)]
+    pub const fn try_from_byte(byte: u8) -> Option<Self> {
+        if byte == Self::V0 as u8 {
+            Some(Self::V0)
+        } else {
+            None
+        }
+    }
+
+    /// Extension types allowed in this version
+    #[inline(always)]
+    pub const fn allowed_extension_types(self) -> &'static [BlockHeaderExtensionType] {
+        match self {
+            Self::V0 => &[],
+        }
+    }
+}
+
+/// Block header extension entry
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BlockHeaderExtension<'a> {
+    /// Extension type
+    pub extension_type: BlockHeaderExtensionType,
+    /// Extension data
+    pub data: &'a [u8],
+}
+
+/// Extension area of the block header.
+///
+/// Contains typed length-prefixed optional entries sorted by extension type. Only the structure is
+/// checked during decoding, so headers with extension types (or versions) unknown to this
+/// implementation can still be decoded and their root computed. Consensus rules of the version are
+/// checked separately with [`Self::is_allowed()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BlockHeaderExtensions<'a> {
+    /// All bytes of the extension area, including version and number of entries
+    bytes: &'a [u8],
+}
+
+impl<'a> BlockHeaderExtensions<'a> {
+    /// Max size of the extension area
+    pub const MAX_SIZE: u32 = 4096;
+    /// Empty extension area of [`BlockHeaderExtensionsVersion::V0`]
+    pub const EMPTY: Self = Self {
+        bytes: &[BlockHeaderExtensionsVersion::V0 as u8, 0],
+    };
+
+    /// Create an instance from provided bytes.
+    ///
+    /// `bytes` do not need to be aligned.
+    ///
+    /// Returns an instance and remaining bytes on success.
+    #[inline]
+    pub fn try_from_bytes(bytes: &'a [u8]) -> Option<(Self, &'a [u8])> {
+        // The layout here is as follows:
+        // * version: u8
+        // * number of entries: u8
+        // * for each entry (sorted by extension type in strictly increasing order):
+        //   * extension type: u8
+        //   * data length: u16 as unaligned little-endian bytes
+        //   * data: [u8; data length]
+
+        let (&[_version, num_entries], mut remainder) = bytes.split_first_chunk::<2>()?;
+
+        let mut last_extension_type = None;
+        for _ in 0..num_entries {
+            let (&[extension_type, length @ ..], entry_remainder) =
+                remainder.split_first_chunk::<3>()?;
+
+            if last_extension_type
+                .is_some_and(|last_extension_type| last_extension_type >= extension_type)
+            {
+                return None;
+            }
+            last_extension_type.replace(extension_type);
+
+            remainder = entry_remainder.get(usize::from(u16::from_le_bytes(length))..)?;
+        }
+
+        let (bytes, remainder) = bytes.split_at(bytes.len() - remainder.len());
+
+        if bytes.len() > Self::MAX_SIZE as usize {
+            return None;
+        }
+
+        Some((Self { bytes }, remainder))
+    }
+
+    /// All bytes of the extension area
+    #[inline(always)]
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Version of extensions, `None` if unknown
+    #[inline(always)]
+    pub fn version(&self) -> Option<BlockHeaderExtensionsVersion> {
+        BlockHeaderExtensionsVersion::try_from_byte(self.bytes[0])
+    }
+
+    /// Whether there are no extension entries
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.bytes[1] == 0
+    }
+
+    /// Iterate over extension entries
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = BlockHeaderExtension<'a>> + 'a {
+        let mut remainder = &self.bytes[2..];
+
+        iter::from_fn(move || {
+            let (&[extension_type, length @ ..], entry_remainder) =
+                remainder.split_first_chunk::<3>()?;
+            let data;
+            (data, remainder) = entry_remainder.split_at(usize::from(u16::from_le_bytes(length)));
+
+            Some(BlockHeaderExtension {
+                extension_type: BlockHeaderExtensionType::new(extension_type),
+                data,
+            })
+        })
+    }
+
+    /// Get data of the extension entry with the specified type
+    #[inline]
+    pub fn get(&self, extension_type: BlockHeaderExtensionType) -> Option<&'a [u8]> {
+        self.iter()
+            .find(|extension| extension.extension_type == extension_type)
+            .map(|extension| extension.data)
+    }
+
+    /// Check consensus rules of the extensions: the version must be known and all entries must be
+    /// of types allowed by the version
+    #[inline]
+    pub fn is_allowed(&self) -> bool {
+        let Some(version) = self.version() else {
+            return false;
+        };
+        let allowed_extension_types = version.allowed_extension_types();
+
+        self.iter()
+            .all(|extension| allowed_extension_types.contains(&extension.extension_type))
+    }
+
+    /// Hash of the block header extensions, part of the eventual block root.
+    ///
+    /// `None` is returned for [`Self::EMPTY`], in which case extensions do not contribute to the
+    /// block root.
+    #[inline]
+    pub fn hash(&self) -> Option<Blake3Hash> {
+        if self.bytes == Self::EMPTY.bytes {
+            return None;
+        }
+
+        // TODO: Keyed hash
+        Some(Blake3Hash::from(blake3::hash(self.bytes)))
+    }
+}
+
 /// Block header seal type
 #[derive(Debug, Copy, Clone, Eq, PartialEq, TrivialType)]
 #[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
@@ -727,6 +966,8 @@ pub struct BeaconChainHeader<'a> {
     child_shard_blocks: BlockHeaderChildShardBlocks<'a>,
     /// Consensus parameters (on the beacon chain)
     consensus_parameters: BlockHeaderConsensusParameters<'a>,
+    /// Header extensions
+    extensions: BlockHeaderExtensions<'a>,
     /// All bytes of the header except the seal
     pre_seal_bytes: &'a [u8],
     #[cfg(all(feature = "alloc", any(target_os = "none", target_os = "unknown")))]
@@ -782,6 +1023,7 @@ impl<'a> BeaconChainHeader<'a> {
         // * consensus info: BlockHeaderConsensusInfo
         // * child shard blocks: BlockHeaderChildShardBlocks
         // * beacon chain parameters: BlockHeaderBeaconChainParameters
+        // * extensions: BlockHeaderExtensions
         // * block header seal: BlockHeaderSeal
 
         let (prefix, consensus_info, result, remainder) =
@@ -797,6 +1039,8 @@ impl<'a> BeaconChainHeader<'a> {
         let (consensus_parameters, remainder) =
             BlockHeaderConsensusParameters::try_from_bytes(remainder)?;
 
+        let (extensions, remainder) = BlockHeaderExtensions::try_from_bytes(remainder)?;
+
         let pre_seal_bytes = &bytes[..bytes.len() - remainder.len()];
 
         let (seal, remainder) = BlockHeaderSeal::try_from_bytes(remainder)?;
@@ -812,6 +1056,7 @@ impl<'a> BeaconChainHeader<'a> {
             shared,
             child_shard_blocks,
             consensus_parameters,
+            extensions,
             pre_seal_bytes,
             #[cfg(any(feature = "alloc", not(any(target_os = "none", target_os = "unknown"))))]
             cached_block_root: rclite::Arc::default(),
@@ -846,6 +1091,7 @@ impl<'a> BeaconChainHeader<'a> {
         // * consensus info: BlockHeaderConsensusInfo
         // * child shard blocks: BlockHeaderChildShardBlocks
         // * beacon chain parameters: BlockHeaderBeaconChainParameters
+        // * extensions: BlockHeaderExtensions
         // * block header seal: BlockHeaderSeal
 
         let (prefix, consensus_info, result, remainder) =
@@ -861,6 +1107,8 @@ impl<'a> BeaconChainHeader<'a> {
         let (consensus_parameters, remainder) =
             BlockHeaderConsensusParameters::try_from_bytes(remainder)?;
 
+        let (extensions, remainder) = BlockHeaderExtensions::try_from_bytes(remainder)?;
+
         let pre_seal_bytes = &bytes[..bytes.len() - remainder.len()];
 
         let (seal, remainder) = BlockHeaderSeal::try_from_bytes(remainder)?;
@@ -877,6 +1125,7 @@ impl<'a> BeaconChainHeader<'a> {
                 shared,
                 child_shard_blocks,
                 consensus_parameters,
+                extensions,
                 pre_seal_bytes,
                 #[cfg(any(
                     feature = "alloc",
@@ -898,6 +1147,7 @@ impl<'a> BeaconChainHeader<'a> {
             self.shared.consensus_info,
             &self.child_shard_blocks,
             &self.consensus_parameters,
+            &self.extensions,
         )
         .expect("`self` is always a valid invariant; qed");
 
@@ -922,6 +1172,12 @@ impl<'a> BeaconChainHeader<'a> {
         &self.consensus_parameters
     }
 
+    /// Header extensions
+    #[inline(always)]
+    pub fn extensions(&self) -> &BlockHeaderExtensions<'a> {
+        &self.extensions
+    }
+
     /// Hash of the block before seal is applied to it
     #[inline]
     pub fn pre_seal_hash(&self) -> Blake3Hash {
@@ -950,6 +1206,7 @@ impl<'a> BeaconChainHeader<'a> {
             shared,
             child_shard_blocks,
             consensus_parameters,
+            extensions,
             pre_seal_bytes: _,
             #[cfg(any(feature = "alloc", not(any(target_os = "none", target_os = "unknown"))))]
             cached_block_root,
@@ -963,11 +1220,11 @@ impl<'a> BeaconChainHeader<'a> {
                 seal,
             } = shared;
 
-            const MAX_N: usize = 6;
+            const MAX_N: usize = 7;
             // TODO: separate constant should not be necessary, but
             //  https://github.com/rust-lang/rust/issues/148596
-            const MAX_N_U64: u64 = 6;
-            let leaves: [_; MAX_N] = [
+            const MAX_N_U64: u64 = 7;
+            let leaves: [_; MAX_N - 1] = [
                 prefix.hash(),
                 result.hash(),
                 consensus_info.hash(),
@@ -975,8 +1232,11 @@ impl<'a> BeaconChainHeader<'a> {
                 child_shard_blocks.root().unwrap_or_default(),
                 consensus_parameters.hash(),
             ];
-            let block_root = UnbalancedMerkleTree::compute_root_only::<MAX_N_U64, _, _>(leaves)
-                .expect("The list is not empty; qed");
+            // Extensions are only a part of the block root when not empty
+            let block_root = UnbalancedMerkleTree::compute_root_only::<MAX_N_U64, _, _>(
+                leaves.into_iter().chain(extensions.hash()),
+            )
+            .expect("The list is not empty; qed");
 
             BlockRoot::new(Blake3Hash::new(block_root))
         };
@@ -1018,6 +1278,8 @@ pub struct IntermediateShardHeader<'a> {
     beacon_chain_info: &'a BlockHeaderBeaconChainInfo,
     /// Information about child shard blocks
     child_shard_blocks: BlockHeaderChildShardBlocks<'a>,
+    /// Header extensions
+    extensions: BlockHeaderExtensions<'a>,
     /// All bytes of the header except the seal
     pre_seal_bytes: &'a [u8],
     #[cfg(all(feature = "alloc", any(target_os = "none", target_os = "unknown")))]
@@ -1073,6 +1335,7 @@ impl<'a> IntermediateShardHeader<'a> {
         // * consensus info: BlockHeaderConsensusInfo
         // * beacon chain: BlockHeaderBeaconChainInfo
         // * child shard blocks: BlockHeaderBeaconChainInfo
+        // * extensions: BlockHeaderExtensions
         // * block header seal: BlockHeaderSeal
 
         let (prefix, consensus_info, result, mut remainder) =
@@ -1090,6 +1353,8 @@ impl<'a> IntermediateShardHeader<'a> {
         let (child_shard_blocks, remainder) =
             BlockHeaderChildShardBlocks::try_from_bytes(remainder)?;
 
+        let (extensions, remainder) = BlockHeaderExtensions::try_from_bytes(remainder)?;
+
         let pre_seal_bytes = &bytes[..bytes.len() - remainder.len()];
 
         let (seal, remainder) = BlockHeaderSeal::try_from_bytes(remainder)?;
@@ -1105,6 +1370,7 @@ impl<'a> IntermediateShardHeader<'a> {
             shared,
             beacon_chain_info,
             child_shard_blocks,
+            extensions,
             pre_seal_bytes,
             #[cfg(any(feature = "alloc", not(any(target_os = "none", target_os = "unknown"))))]
             cached_block_root: rclite::Arc::default(),
@@ -1139,6 +1405,7 @@ impl<'a> IntermediateShardHeader<'a> {
         // * consensus info: BlockHeaderConsensusInfo
         // * beacon chain: BlockHeaderBeaconChainInfo
         // * child shard blocks: BlockHeaderBeaconChainInfo
+        // * extensions: BlockHeaderExtensions
         // * block header seal: BlockHeaderSeal
 
         let (prefix, consensus_info, result, mut remainder) =
@@ -1156,6 +1423,8 @@ impl<'a> IntermediateShardHeader<'a> {
         let (child_shard_blocks, remainder) =
             BlockHeaderChildShardBlocks::try_from_bytes(remainder)?;
 
+        let (extensions, remainder) = BlockHeaderExtensions::try_from_bytes(remainder)?;
+
         let pre_seal_bytes = &bytes[..bytes.len() - remainder.len()];
 
         let (seal, remainder) = BlockHeaderSeal::try_from_bytes(remainder)?;
@@ -1172,6 +1441,7 @@ impl<'a> IntermediateShardHeader<'a> {
                 shared,
                 beacon_chain_info,
                 child_shard_blocks,
+                extensions,
                 pre_seal_bytes,
                 #[cfg(any(
                     feature = "alloc",
@@ -1193,6 +1463,7 @@ impl<'a> IntermediateShardHeader<'a> {
             self.shared.consensus_info,
             self.beacon_chain_info,
             &self.child_shard_blocks,
+            &self.extensions,
         )
         .expect("`self` is always a valid invariant; qed");
 
@@ -1217,6 +1488,12 @@ impl<'a> IntermediateShardHeader<'a> {
         &self.child_shard_blocks
     }
 
+    /// Header extensions
+    #[inline(always)]
+    pub fn extensions(&self) -> &BlockHeaderExtensions<'a> {
+        &self.extensions
+    }
+
     /// Hash of the block before seal is applied to it
     #[inline]
     pub fn pre_seal_hash(&self) -> Blake3Hash {
@@ -1245,6 +1522,7 @@ impl<'a> IntermediateShardHeader<'a> {
             shared,
             beacon_chain_info,
             child_shard_blocks,
+            extensions,
             pre_seal_bytes: _,
             #[cfg(any(feature = "alloc", not(any(target_os = "none", target_os = "unknown"))))]
             cached_block_root,
@@ -1258,11 +1536,11 @@ impl<'a> IntermediateShardHeader<'a> {
                 seal,
             } = shared;
 
-            const MAX_N: usize = 6;
+            const MAX_N: usize = 7;
             // TODO: separate constant should not be necessary, but
             //  https://github.com/rust-lang/rust/issues/148596
-            const MAX_N_U64: u64 = 6;
-            let leaves: [_; MAX_N] = [
+            const MAX_N_U64: u64 = 7;
+            let leaves: [_; MAX_N - 1] = [
                 prefix.hash(),
                 result.hash(),
                 consensus_info.hash(),
@@ -1270,8 +1548,11 @@ impl<'a> IntermediateShardHeader<'a> {
                 beacon_chain_info.hash(),
                 child_shard_blocks.root().unwrap_or_default(),
             ];
-            let block_root = UnbalancedMerkleTree::compute_root_only::<MAX_N_U64, _, _>(leaves)
-                .expect("The list is not empty; qed");
+            // Extensions are only a part of the block root when not empty
+            let block_root = UnbalancedMerkleTree::compute_root_only::<MAX_N_U64, _, _>(
+                leaves.into_iter().chain(extensions.hash()),
+            )
+            .expect("The list is not empty; qed");
 
             BlockRoot::new(Blake3Hash::new(block_root))
         };
@@ -1311,6 +1592,8 @@ pub struct LeafShardHeader<'a> {
     shared: SharedBlockHeader<'a>,
     /// Beacon chain info
     beacon_chain_info: &'a BlockHeaderBeaconChainInfo,
+    /// Header extensions
+    extensions: BlockHeaderExtensions<'a>,
     /// All bytes of the header except the seal
     pre_seal_bytes: &'a [u8],
     #[cfg(all(feature = "alloc", any(target_os = "none", target_os = "unknown")))]
@@ -1365,6 +1648,7 @@ impl<'a> LeafShardHeader<'a> {
         // * block header prefix: BlockHeaderPrefix
         // * consensus info: BlockHeaderConsensusInfo
         // * beacon chain: BlockHeaderBeaconChainInfo
+        // * extensions: BlockHeaderExtensions
         // * block header seal: BlockHeaderSeal
 
         let (prefix, consensus_info, result, mut remainder) =
@@ -1379,6 +1663,8 @@ impl<'a> LeafShardHeader<'a> {
         let beacon_chain_info =
             unsafe { BlockHeaderBeaconChainInfo::from_bytes(beacon_chain_info) }?;
 
+        let (extensions, remainder) = BlockHeaderExtensions::try_from_bytes(remainder)?;
+
         let pre_seal_bytes = &bytes[..bytes.len() - remainder.len()];
 
         let (seal, remainder) = BlockHeaderSeal::try_from_bytes(remainder)?;
@@ -1393,6 +1679,7 @@ impl<'a> LeafShardHeader<'a> {
         let header = Self {
             shared,
             beacon_chain_info,
+            extensions,
             pre_seal_bytes,
             #[cfg(any(feature = "alloc", not(any(target_os = "none", target_os = "unknown"))))]
             cached_block_root: rclite::Arc::default(),
@@ -1426,6 +1713,7 @@ impl<'a> LeafShardHeader<'a> {
         // * block header prefix: BlockHeaderPrefix
         // * consensus info: BlockHeaderConsensusInfo
         // * beacon chain: BlockHeaderBeaconChainInfo
+        // * extensions: BlockHeaderExtensions
         // * block header seal: BlockHeaderSeal
 
         let (prefix, consensus_info, result, mut remainder) =
@@ -1440,6 +1728,8 @@ impl<'a> LeafShardHeader<'a> {
         let beacon_chain_info =
             unsafe { BlockHeaderBeaconChainInfo::from_bytes(beacon_chain_info) }?;
 
+        let (extensions, remainder) = BlockHeaderExtensions::try_from_bytes(remainder)?;
+
         let pre_seal_bytes = &bytes[..bytes.len() - remainder.len()];
 
         let (seal, remainder) = BlockHeaderSeal::try_from_bytes(remainder)?;
@@ -1455,6 +1745,7 @@ impl<'a> LeafShardHeader<'a> {
             Self {
                 shared,
                 beacon_chain_info,
+                extensions,
                 pre_seal_bytes,
                 #[cfg(any(
                     feature = "alloc",
@@ -1475,6 +1766,7 @@ impl<'a> LeafShardHeader<'a> {
             self.shared.result,
             self.shared.consensus_info,
             self.beacon_chain_info,
+            &self.extensions,
         );
 
         unsealed.with_seal(self.shared.seal)
@@ -1492,6 +1784,12 @@ impl<'a> LeafShardHeader<'a> {
         self.beacon_chain_info
     }
 
+    /// Header extensions
+    #[inline(always)]
+    pub fn extensions(&self) -> &BlockHeaderExtensions<'a> {
+        &self.extensions
+    }
+
     /// Hash of the block before seal is applied to it
     #[inline]
     pub fn pre_seal_hash(&self) -> Blake3Hash {
@@ -1519,6 +1817,7 @@ impl<'a> LeafShardHeader<'a> {
         let Self {
             shared,
             beacon_chain_info,
+            extensions,
             pre_seal_bytes: _,
             #[cfg(any(feature = "alloc", not(any(target_os = "none", target_os = "unknown"))))]
             cached_block_root,
@@ -1532,19 +1831,22 @@ impl<'a> LeafShardHeader<'a> {
                 seal,
             } = shared;
 
-            const MAX_N: usize = 5;
+            const MAX_N: usize = 6;
             // TODO: separate constant should not be necessary, but
             //  https://github.com/rust-lang/rust/issues/148596
-            const MAX_N_U64: u64 = 5;
-            let leaves: [_; MAX_N] = [
+            const MAX_N_U64: u64 = 6;
+            let leaves: [_; MAX_N - 1] = [
                 prefix.hash(),
                 result.hash(),
                 consensus_info.hash(),
                 seal.hash(),
                 beacon_chain_info.hash(),
             ];
-            let block_root = UnbalancedMerkleTree::compute_root_only::<MAX_N_U64, _, _>(leaves)
-                .expect("The list is not empty; qed");
+            // Extensions are only a part of the block root when not empty
+            let block_root = UnbalancedMerkleTree::compute_root_only::<MAX_N_U64, _, _>(
+                leaves.into_iter().chain(extensions.hash()),
+            )
+            .expect("The list is not empty; qed");
 
             BlockRoot::new(Blake3Hash::new(block_root))
         };
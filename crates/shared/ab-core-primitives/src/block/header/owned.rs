@@ -3,14 +3,16 @@
 use crate::block::BlockRoot;
 use crate::block::header::{
     BeaconChainHeader, BlockHeader, BlockHeaderBeaconChainInfo, BlockHeaderConsensusInfo,
-    BlockHeaderConsensusParameters, BlockHeaderFixedConsensusParameters, BlockHeaderPrefix,
-    BlockHeaderResult, BlockHeaderSeal, BlockHeaderSealType, GenericBlockHeader,
+    BlockHeaderConsensusParameters, BlockHeaderExtension, BlockHeaderExtensionType,
+    BlockHeaderExtensions, BlockHeaderExtensionsVersion, BlockHeaderFixedConsensusParameters,
+    BlockHeaderPrefix, BlockHeaderResult, BlockHeaderSeal, BlockHeaderSealType, GenericBlockHeader,
     IntermediateShardHeader, LeafShardHeader,
 };
 use crate::hashes::Blake3Hash;
 use crate::shard::{NumShardsUnchecked, RealShardKind};
 use ab_aligned_buffer::{OwnedAlignedBuffer, SharedAlignedBuffer};
 use ab_io_type::trivial_type::TrivialType;
+use alloc::vec::Vec;
 use core::fmt;
 use derive_more::From;
 use rclite::Arc;
@@ -54,6 +56,131 @@ fn append_seal(buffer: &mut OwnedAlignedBuffer, seal: BlockHeaderSeal<'_>) {
     }
 }
 
+fn append_extensions(buffer: &mut OwnedAlignedBuffer, extensions: &BlockHeaderExtensions<'_>) {
+    let true = buffer.append(extensions.as_bytes()) else {
+        unreachable!("Extensions size is limited and guaranteed to fit; qed");
+    };
+}
+
+/// Errors for [`OwnedBlockHeaderExtensions`]
+#[derive(Debug, thiserror::Error)]
+pub enum OwnedBlockHeaderExtensionsError {
+    /// Too many extension entries
+    #[error("Too many extension entries: {actual}")]
+    TooManyEntries {
+        /// Actual number of extension entries
+        actual: usize,
+    },
+    /// Extension entries are not sorted by extension type in strictly increasing order
+    #[error(
+        "Extension entries are not sorted by extension type in strictly increasing order: \
+        {extension_type:?} after {previous_extension_type:?}"
+    )]
+    NotSorted {
+        /// Extension type of the previous entry
+        previous_extension_type: BlockHeaderExtensionType,
+        /// Extension type of the entry that is out of order
+        extension_type: BlockHeaderExtensionType,
+    },
+    /// Extension entry data is too large
+    #[error("Extension entry {extension_type:?} data is too large: {actual} bytes")]
+    EntryTooLarge {
+        /// Extension type
+        extension_type: BlockHeaderExtensionType,
+        /// Actual size of the data
+        actual: usize,
+    },
+    /// Extensions are too large
+    #[error("Extensions are too large: {actual} bytes")]
+    TooLarge {
+        /// Actual size of extensions
+        actual: usize,
+    },
+}
+
+/// An owned version of [`BlockHeaderExtensions`].
+///
+/// Only the structure of extensions is checked during creation, consensus rules of the version
+/// are checked with [`BlockHeaderExtensions::is_allowed()`].
+#[derive(Debug, Clone)]
+pub struct OwnedBlockHeaderExtensions {
+    bytes: Vec<u8>,
+}
+
+impl Default for OwnedBlockHeaderExtensions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            bytes: BlockHeaderExtensions::EMPTY.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl OwnedBlockHeaderExtensions {
+    /// Create a new instance from extension entries, which must be sorted by extension type in
+    /// strictly increasing order
+    pub fn new(
+        version: BlockHeaderExtensionsVersion,
+        entries: &[BlockHeaderExtension<'_>],
+    ) -> Result<Self, OwnedBlockHeaderExtensionsError> {
+        let num_entries = u8::try_from(entries.len()).map_err(|_error| {
+            OwnedBlockHeaderExtensionsError::TooManyEntries {
+                actual: entries.len(),
+            }
+        })?;
+
+        let mut bytes = Vec::with_capacity(
+            2 + entries
+                .iter()
+                .map(|entry| 3 + entry.data.len())
+                .sum::<usize>(),
+        );
+        bytes.extend_from_slice(&[version as u8, num_entries]);
+
+        let mut previous_extension_type = None;
+        for &BlockHeaderExtension {
+            extension_type,
+            data,
+        } in entries
+        {
+            if let Some(previous_extension_type) = previous_extension_type
+                && previous_extension_type >= extension_type
+            {
+                return Err(OwnedBlockHeaderExtensionsError::NotSorted {
+                    previous_extension_type,
+                    extension_type,
+                });
+            }
+            previous_extension_type.replace(extension_type);
+
+            let length = u16::try_from(data.len()).map_err(|_error| {
+                OwnedBlockHeaderExtensionsError::EntryTooLarge {
+                    extension_type,
+                    actual: data.len(),
+                }
+            })?;
+
+            bytes.push(extension_type.as_u8());
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes.extend_from_slice(data);
+        }
+
+        if bytes.len() > BlockHeaderExtensions::MAX_SIZE as usize {
+            return Err(OwnedBlockHeaderExtensionsError::TooLarge {
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(Self { bytes })
+    }
+
+    /// Get a reference out of the owned version
+    #[inline(always)]
+    pub fn as_ref(&self) -> BlockHeaderExtensions<'_> {
+        BlockHeaderExtensions { bytes: &self.bytes }
+    }
+}
+
 /// Errors for [`OwnedBeaconChainHeader`]
 #[derive(Debug, thiserror::Error)]
 pub enum OwnedBeaconChainHeaderError {
@@ -103,7 +230,10 @@ impl GenericOwnedBlockHeader for OwnedBeaconChainHeader {
 impl OwnedBeaconChainHeader {
     /// Max allocation needed by this header
     #[inline(always)]
-    pub const fn max_allocation_for(child_shard_blocks: &[BlockRoot]) -> u32 {
+    pub const fn max_allocation_for(
+        child_shard_blocks: &[BlockRoot],
+        extensions: &BlockHeaderExtensions<'_>,
+    ) -> u32 {
         BlockHeaderPrefix::SIZE
             + BlockHeaderResult::SIZE
             + BlockHeaderConsensusInfo::SIZE
@@ -115,6 +245,7 @@ impl OwnedBeaconChainHeader {
                 + size_of_val(child_shard_blocks) as u32
             )
             + BlockHeaderConsensusParameters::MAX_SIZE
+            + extensions.as_bytes().len() as u32
             + BlockHeaderSeal::MAX_SIZE
     }
 
@@ -125,9 +256,12 @@ impl OwnedBeaconChainHeader {
         consensus_info: &BlockHeaderConsensusInfo,
         child_shard_blocks: &[BlockRoot],
         consensus_parameters: &BlockHeaderConsensusParameters<'_>,
+        extensions: &BlockHeaderExtensions<'_>,
     ) -> Result<OwnedBeaconChainHeaderUnsealed, OwnedBeaconChainHeaderError> {
-        let mut buffer =
-            OwnedAlignedBuffer::with_capacity(Self::max_allocation_for(child_shard_blocks));
+        let mut buffer = OwnedAlignedBuffer::with_capacity(Self::max_allocation_for(
+            child_shard_blocks,
+            extensions,
+        ));
 
         Self::from_parts_into(
             prefix,
@@ -135,6 +269,7 @@ impl OwnedBeaconChainHeader {
             consensus_info,
             child_shard_blocks,
             consensus_parameters,
+            extensions,
             &mut buffer,
         )?;
 
@@ -148,6 +283,7 @@ impl OwnedBeaconChainHeader {
         consensus_info: &BlockHeaderConsensusInfo,
         child_shard_blocks: &[BlockRoot],
         consensus_parameters: &BlockHeaderConsensusParameters<'_>,
+        extensions: &BlockHeaderExtensions<'_>,
         buffer: &mut OwnedAlignedBuffer,
     ) -> Result<(), OwnedBeaconChainHeaderError> {
         let BlockHeaderConsensusParameters {
@@ -247,6 +383,7 @@ impl OwnedBeaconChainHeader {
                 };
             }
         }
+        append_extensions(buffer, extensions);
 
         Ok(())
     }
@@ -367,7 +504,10 @@ impl GenericOwnedBlockHeader for OwnedIntermediateShardHeader {
 impl OwnedIntermediateShardHeader {
     /// Max allocation needed by this header
     #[inline(always)]
-    pub const fn max_allocation_for(child_shard_blocks: &[BlockRoot]) -> u32 {
+    pub const fn max_allocation_for(
+        child_shard_blocks: &[BlockRoot],
+        extensions: &BlockHeaderExtensions<'_>,
+    ) -> u32 {
         BlockHeaderPrefix::SIZE
             + BlockHeaderResult::SIZE
             + BlockHeaderConsensusInfo::SIZE
@@ -379,6 +519,7 @@ impl OwnedIntermediateShardHeader {
                 + <[u8; 2]>::SIZE
                 + size_of_val(child_shard_blocks) as u32
             )
+            + extensions.as_bytes().len() as u32
             + BlockHeaderSeal::MAX_SIZE
     }
 
@@ -389,9 +530,12 @@ impl OwnedIntermediateShardHeader {
         consensus_info: &BlockHeaderConsensusInfo,
         beacon_chain_info: &BlockHeaderBeaconChainInfo,
         child_shard_blocks: &[BlockRoot],
+        extensions: &BlockHeaderExtensions<'_>,
     ) -> Result<OwnedIntermediateShardHeaderUnsealed, OwnedIntermediateShardHeaderError> {
-        let mut buffer =
-            OwnedAlignedBuffer::with_capacity(Self::max_allocation_for(child_shard_blocks));
+        let mut buffer = OwnedAlignedBuffer::with_capacity(Self::max_allocation_for(
+            child_shard_blocks,
+            extensions,
+        ));
 
         Self::from_parts_into(
             prefix,
@@ -399,6 +543,7 @@ impl OwnedIntermediateShardHeader {
             consensus_info,
             beacon_chain_info,
             child_shard_blocks,
+            extensions,
             &mut buffer,
         )?;
 
@@ -412,6 +557,7 @@ impl OwnedIntermediateShardHeader {
         consensus_info: &BlockHeaderConsensusInfo,
         beacon_chain_info: &BlockHeaderBeaconChainInfo,
         child_shard_blocks: &[BlockRoot],
+        extensions: &BlockHeaderExtensions<'_>,
         buffer: &mut OwnedAlignedBuffer,
     ) -> Result<(), OwnedIntermediateShardHeaderError> {
         let num_blocks = child_shard_blocks.len();
@@ -443,6 +589,7 @@ impl OwnedIntermediateShardHeader {
                 unreachable!("Checked size above; qed");
             };
         }
+        append_extensions(buffer, extensions);
 
         Ok(())
     }
@@ -553,11 +700,15 @@ impl GenericOwnedBlockHeader for OwnedLeafShardHeader {
 
 impl OwnedLeafShardHeader {
     /// Max allocation needed by this header
-    pub const MAX_ALLOCATION: u32 = BlockHeaderPrefix::SIZE
-        + BlockHeaderResult::SIZE
-        + BlockHeaderConsensusInfo::SIZE
-        + BlockHeaderBeaconChainInfo::SIZE
-        + BlockHeaderSeal::MAX_SIZE;
+    #[inline(always)]
+    pub const fn max_allocation_for(extensions: &BlockHeaderExtensions<'_>) -> u32 {
+        BlockHeaderPrefix::SIZE
+            + BlockHeaderResult::SIZE
+            + BlockHeaderConsensusInfo::SIZE
+            + BlockHeaderBeaconChainInfo::SIZE
+            + extensions.as_bytes().len() as u32
+            + BlockHeaderSeal::MAX_SIZE
+    }
 
     /// Create a new [`OwnedLeafShardHeader`] from its parts
     pub fn from_parts(
@@ -565,14 +716,16 @@ impl OwnedLeafShardHeader {
         result: &BlockHeaderResult,
         consensus_info: &BlockHeaderConsensusInfo,
         beacon_chain_info: &BlockHeaderBeaconChainInfo,
+        extensions: &BlockHeaderExtensions<'_>,
     ) -> OwnedLeafShardHeaderUnsealed {
-        let mut buffer = OwnedAlignedBuffer::with_capacity(Self::MAX_ALLOCATION);
+        let mut buffer = OwnedAlignedBuffer::with_capacity(Self::max_allocation_for(extensions));
 
         Self::from_parts_into(
             prefix,
             result,
             consensus_info,
             beacon_chain_info,
+            extensions,
             &mut buffer,
         );
 
@@ -585,6 +738,7 @@ impl OwnedLeafShardHeader {
         result: &BlockHeaderResult,
         consensus_info: &BlockHeaderConsensusInfo,
         beacon_chain_info: &BlockHeaderBeaconChainInfo,
+        extensions: &BlockHeaderExtensions<'_>,
         buffer: &mut OwnedAlignedBuffer,
     ) {
         let true = buffer.append(prefix.as_bytes()) else {
@@ -599,6 +753,7 @@ impl OwnedLeafShardHeader {
         let true = buffer.append(beacon_chain_info.as_bytes()) else {
             unreachable!("Fixed size data structures that are guaranteed to fit; qed");
         };
+        append_extensions(buffer, extensions);
     }
 
     /// Create an owned header from a buffer
@@ -0,0 +1,143 @@
+#[cfg(feature = "alloc")]
+use crate::block::BlockRoot;
+#[cfg(feature = "alloc")]
+use crate::block::header::BlockHeaderChildShardBlocks;
+use crate::block::header::{
+    BlockHeaderExtension, BlockHeaderExtensionType, BlockHeaderExtensions,
+    BlockHeaderExtensionsVersion,
+};
+#[cfg(feature = "alloc")]
+use crate::hashes::Blake3Hash;
+
+#[test]
+fn empty_extensions() {
+    let (extensions, remainder) = BlockHeaderExtensions::try_from_bytes(&[0, 0, 1, 2, 3]).unwrap();
+
+    assert_eq!(extensions, BlockHeaderExtensions::EMPTY);
+    assert_eq!(remainder, &[1, 2, 3]);
+    assert_eq!(extensions.version(), Some(BlockHeaderExtensionsVersion::V0));
+    assert!(extensions.is_empty());
+    assert_eq!(extensions.iter().count(), 0);
+    assert!(extensions.is_allowed());
+    // Empty extensions do not contribute to the block root
+    assert_eq!(extensions.hash(), None);
+
+    // Not enough bytes
+    assert!(BlockHeaderExtensions::try_from_bytes(&[]).is_none());
+    assert!(BlockHeaderExtensions::try_from_bytes(&[0]).is_none());
+}
+
+#[test]
+fn extension_entries() {
+    let bytes = [
+        // Version and number of entries
+        0, 2, //
+        // First entry
+        1, 2, 0, 10, 11, //
+        // Second entry
+        5, 0, 0, //
+        // Remainder
+        42,
+    ];
+    let (extensions, remainder) = BlockHeaderExtensions::try_from_bytes(&bytes).unwrap();
+
+    assert_eq!(extensions.as_bytes(), &bytes[..bytes.len() - 1]);
+    assert_eq!(remainder, &[42]);
+    assert!(!extensions.is_empty());
+    assert_eq!(
+        extensions.iter().collect::<Vec<_>>(),
+        [
+            BlockHeaderExtension {
+                extension_type: BlockHeaderExtensionType::new(1),
+                data: &[10, 11],
+            },
+            BlockHeaderExtension {
+                extension_type: BlockHeaderExtensionType::new(5),
+                data: &[],
+            },
+        ]
+    );
+    assert_eq!(
+        extensions.get(BlockHeaderExtensionType::new(1)),
+        Some([10, 11].as_slice())
+    );
+    assert_eq!(
+        extensions.get(BlockHeaderExtensionType::new(5)),
+        Some([].as_slice())
+    );
+    assert_eq!(extensions.get(BlockHeaderExtensionType::new(2)), None);
+    assert!(extensions.hash().is_some());
+    // No extension types are allowed in the initial version
+    assert!(!extensions.is_allowed());
+
+    // Unknown version is decoded, but not allowed
+    let (extensions, _remainder) = BlockHeaderExtensions::try_from_bytes(&[1, 0]).unwrap();
+    assert_eq!(extensions.version(), None);
+    assert!(!extensions.is_allowed());
+    assert!(extensions.hash().is_some());
+}
+
+#[test]
+fn invalid_extension_entries() {
+    // Not sorted
+    assert!(BlockHeaderExtensions::try_from_bytes(&[0, 2, 5, 0, 0, 1, 0, 0]).is_none());
+    // Duplicate extension type
+    assert!(BlockHeaderExtensions::try_from_bytes(&[0, 2, 1, 0, 0, 1, 0, 0]).is_none());
+    // Truncated data
+    assert!(BlockHeaderExtensions::try_from_bytes(&[0, 1, 1, 3, 0, 10, 11]).is_none());
+    // Truncated length
+    assert!(BlockHeaderExtensions::try_from_bytes(&[0, 1, 1, 3]).is_none());
+    // Missing entry
+    assert!(BlockHeaderExtensions::try_from_bytes(&[0, 2, 1, 0, 0]).is_none());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn child_shard_blocks_proofs() {
+    let child_shard_blocks = (0..5u8)
+        .map(|i| BlockRoot::new(Blake3Hash::new([i; _])))
+        .collect::<Vec<_>>();
+    let child_shard_blocks = BlockHeaderChildShardBlocks {
+        child_shard_blocks: &child_shard_blocks,
+    };
+    let root = child_shard_blocks.root().unwrap();
+    let num_child_shard_blocks = child_shard_blocks.child_shard_blocks.len() as u32;
+
+    for (index, child_shard_block_root) in child_shard_blocks.child_shard_blocks.iter().enumerate()
+    {
+        let proof = child_shard_blocks.proof(index).unwrap();
+        assert!(BlockHeaderChildShardBlocks::verify_inclusion(
+            &root,
+            &proof,
+            index as u32,
+            num_child_shard_blocks,
+            child_shard_block_root,
+        ));
+
+        // Wrong index
+        assert!(!BlockHeaderChildShardBlocks::verify_inclusion(
+            &root,
+            &proof,
+            (index as u32 + 1) % num_child_shard_blocks,
+            num_child_shard_blocks,
+            child_shard_block_root,
+        ));
+        // Wrong child shard block root
+        assert!(!BlockHeaderChildShardBlocks::verify_inclusion(
+            &root,
+            &proof,
+            index as u32,
+            num_child_shard_blocks,
+            &BlockRoot::new(Blake3Hash::new([u8::MAX; _])),
+        ));
+    }
+
+    assert!(child_shard_blocks.proof(5).is_none());
+    assert!(
+        BlockHeaderChildShardBlocks {
+            child_shard_blocks: &[]
+        }
+        .proof(0)
+        .is_none()
+    );
+}
@@ -14,7 +14,8 @@ use crate::block::header::owned::{
 };
 use crate::block::header::{
     BlockHeader, BlockHeaderBeaconChainInfo, BlockHeaderConsensusInfo,
-    BlockHeaderConsensusParameters, BlockHeaderPrefix, BlockHeaderResult, BlockHeaderSeal,
+    BlockHeaderConsensusParameters, BlockHeaderExtensions, BlockHeaderPrefix, BlockHeaderResult,
+    BlockHeaderSeal,
 };
 use crate::block::{BeaconChainBlock, Block, GenericBlock, IntermediateShardBlock, LeafShardBlock};
 use crate::hashes::Blake3Hash;
@@ -166,8 +167,10 @@ impl OwnedBeaconChainBlockBuilder {
         self,
         prefix: &BlockHeaderPrefix,
         state_root: Blake3Hash,
+        execution_receipts_root: Blake3Hash,
         consensus_info: &BlockHeaderConsensusInfo,
         consensus_parameters: &BlockHeaderConsensusParameters<'_>,
+        extensions: &BlockHeaderExtensions<'_>,
     ) -> Result<OwnedBeaconChainBlockUnsealed, OwnedBeaconChainHeaderError> {
         let body = self.body;
         let header = OwnedBeaconChainHeader::from_parts(
@@ -175,6 +178,7 @@ impl OwnedBeaconChainBlockBuilder {
             &BlockHeaderResult {
                 body_root: body.body().root(),
                 state_root,
+                execution_receipts_root,
             },
             consensus_info,
             &body
@@ -184,6 +188,7 @@ impl OwnedBeaconChainBlockBuilder {
                 .map(|block| *block.header.root())
                 .collect::<Vec<_>>(),
             consensus_parameters,
+            extensions,
         )?;
 
         Ok(OwnedBeaconChainBlockUnsealed { body, header })
@@ -325,8 +330,10 @@ impl OwnedIntermediateShardBlockBuilder {
         self,
         prefix: &BlockHeaderPrefix,
         state_root: Blake3Hash,
+        execution_receipts_root: Blake3Hash,
         consensus_info: &BlockHeaderConsensusInfo,
         beacon_chain_info: &BlockHeaderBeaconChainInfo,
+        extensions: &BlockHeaderExtensions<'_>,
     ) -> Result<OwnedIntermediateShardBlockUnsealed, OwnedIntermediateShardHeaderError> {
         let body = self.body;
         let header = OwnedIntermediateShardHeader::from_parts(
@@ -334,6 +341,7 @@ impl OwnedIntermediateShardBlockBuilder {
             &BlockHeaderResult {
                 body_root: body.body().root(),
                 state_root,
+                execution_receipts_root,
             },
             consensus_info,
             beacon_chain_info,
@@ -343,6 +351,7 @@ impl OwnedIntermediateShardBlockBuilder {
                 .iter()
                 .map(|block| *block.header.root())
                 .collect::<Vec<_>>(),
+            extensions,
         )?;
 
         Ok(OwnedIntermediateShardBlockUnsealed { body, header })
@@ -480,8 +489,10 @@ impl OwnedLeafShardBlockBuilder {
         self,
         prefix: &BlockHeaderPrefix,
         state_root: Blake3Hash,
+        execution_receipts_root: Blake3Hash,
         consensus_info: &BlockHeaderConsensusInfo,
         beacon_chain_info: &BlockHeaderBeaconChainInfo,
+        extensions: &BlockHeaderExtensions<'_>,
     ) -> OwnedLeafShardBlockUnsealed {
         let body = self.body_builder.finish();
         let header = OwnedLeafShardHeader::from_parts(
@@ -489,9 +500,11 @@ impl OwnedLeafShardBlockBuilder {
             &BlockHeaderResult {
                 body_root: body.body().root(),
                 state_root,
+                execution_receipts_root,
             },
             consensus_info,
             beacon_chain_info,
+            extensions,
         );
         OwnedLeafShardBlockUnsealed { body, header }
     }
@@ -34,6 +34,8 @@ pub mod sectors;
 pub mod segments;
 pub mod shard;
 pub mod solutions;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
 pub mod transaction;
 
 #[cfg(feature = "alloc")]
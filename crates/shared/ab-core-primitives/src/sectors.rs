@@ -7,7 +7,7 @@ use crate::hashes::Blake3Hash;
 use crate::nano_u256::NanoU256;
 use crate::pieces::{PieceIndex, PieceOffset, Record};
 use crate::pos::PosSeed;
-use crate::segments::{HistorySize, SuperSegmentRoot};
+use crate::segments::{HistorySize, SegmentIndex, SuperSegmentRoot};
 use crate::solutions::ShardCommitmentHash;
 use ab_blake3::{single_block_hash, single_block_keyed_hash};
 use ab_io_type::trivial_type::TrivialType;
@@ -279,6 +279,99 @@ impl SectorId {
         );
         Some(HistorySize::new(expiration_history_size))
     }
+
+    /// Derive expiration of the sector created at `history_size` as of `current_history_size`.
+    ///
+    /// `sector_expiration_check_super_segment_root` is the super segment root that contains a
+    /// segment at `min_sector_lifetime` from sector creation (see
+    /// [`HistorySize::sector_expiration_check()`]), it is expected to be `None` until history
+    /// reaches that point.
+    ///
+    /// Returns `None` on overflow.
+    pub fn derive_expiration(
+        &self,
+        history_size: HistorySize,
+        min_sector_lifetime: HistorySize,
+        current_history_size: HistorySize,
+        sector_expiration_check_super_segment_root: Option<&SuperSegmentRoot>,
+    ) -> Option<SectorExpiration> {
+        let Some(sector_expiration_check_super_segment_root) =
+            sector_expiration_check_super_segment_root
+        else {
+            return Some(SectorExpiration::Undetermined {
+                expiration_check_history_size: history_size
+                    .sector_expiration_check(min_sector_lifetime)?,
+            });
+        };
+
+        let expiration_history_size = self.derive_expiration_history_size(
+            history_size,
+            sector_expiration_check_super_segment_root,
+            min_sector_lifetime,
+        )?;
+
+        Some(if expiration_history_size <= current_history_size {
+            SectorExpiration::Expired {
+                expiration_history_size,
+            }
+        } else {
+            SectorExpiration::ExpiresAt {
+                expiration_history_size,
+            }
+        })
+    }
+}
+
+/// Expiration of a plotted sector.
+///
+/// Sector created at history size `H` is guaranteed to live for at least `min_sector_lifetime`
+/// archived segments. Once history reaches `H + min_sector_lifetime` (expiration check history
+/// size), the super segment root containing the corresponding segment is used as a source of
+/// randomness to derive the history size at which sector expires, which is somewhere between the
+/// expiration check history size and `4 * H + min_sector_lifetime`. This spreads replotting over
+/// time instead of all sectors plotted at the same time expiring at once.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+pub enum SectorExpiration {
+    /// Expiration is not determined yet because history didn't reach expiration check history size
+    Undetermined {
+        /// History size at which expiration will be determined
+        expiration_check_history_size: HistorySize,
+    },
+    /// Sector expires in the future
+    ExpiresAt {
+        /// History size at which sector expires
+        expiration_history_size: HistorySize,
+    },
+    /// Sector has already expired and can no longer be used for farming
+    Expired {
+        /// History size at which sector expired
+        expiration_history_size: HistorySize,
+    },
+}
+
+impl SectorExpiration {
+    /// Whether sector should be replotted as of `current_history_size`.
+    ///
+    /// Replotting is scheduled one segment before sector actually expires to avoid storing expired
+    /// sectors.
+    pub fn should_replot(&self, current_history_size: HistorySize) -> bool {
+        match self {
+            Self::Undetermined { .. } => false,
+            Self::ExpiresAt {
+                expiration_history_size,
+            } => {
+                expiration_history_size.segment_index()
+                    <= current_history_size.segment_index() + SegmentIndex::ONE
+            }
+            Self::Expired { .. } => true,
+        }
+    }
 }
 
 /// S-bucket used in consensus
@@ -1,8 +1,92 @@
+use crate::hashes::Blake3Hash;
 use crate::pieces::Record;
-use crate::sectors::SBucket;
+use crate::sectors::{SBucket, SectorExpiration, SectorId, SectorIndex};
+use crate::segments::{HistorySize, SuperSegmentRoot};
+use crate::solutions::ShardCommitmentHash;
+use core::num::NonZeroU64;
 
 // Statically validate that we can store all possible s-buckets in SBucket data structure
 #[test]
 fn s_buckets_fit_into_data_structure() {
     assert!((SBucket::ZERO..=SBucket(u16::MAX)).count() <= Record::NUM_S_BUCKETS);
 }
+
+fn history_size(value: u64) -> HistorySize {
+    HistorySize::new(NonZeroU64::new(value).unwrap())
+}
+
+#[test]
+fn sector_expiration() {
+    let public_key_hash = Blake3Hash::new([1; _]);
+    let shard_commitments_root = ShardCommitmentHash::new([2; _]);
+    let sector_history_size = history_size(10);
+    let min_sector_lifetime = history_size(4);
+    let sector_expiration_check_super_segment_root = SuperSegmentRoot::from([3; _]);
+    let sector_id = SectorId::new(
+        &public_key_hash,
+        &shard_commitments_root,
+        SectorIndex::ZERO,
+        sector_history_size,
+    );
+
+    // Expiration is checked `min_sector_lifetime` segments after the sector was plotted
+    let undetermined = SectorExpiration::Undetermined {
+        expiration_check_history_size: history_size(14),
+    };
+    assert_eq!(
+        sector_id.derive_expiration(
+            sector_history_size,
+            min_sector_lifetime,
+            history_size(12),
+            None
+        ),
+        Some(undetermined)
+    );
+    assert!(!undetermined.should_replot(history_size(12)));
+    assert!(!undetermined.should_replot(history_size(14)));
+
+    // Expiration history size is derived from the super segment root
+    assert_eq!(
+        sector_id.derive_expiration_history_size(
+            sector_history_size,
+            &sector_expiration_check_super_segment_root,
+            min_sector_lifetime,
+        ),
+        Some(history_size(41))
+    );
+
+    let expires_at = SectorExpiration::ExpiresAt {
+        expiration_history_size: history_size(41),
+    };
+    for current_history_size in [14, 40] {
+        assert_eq!(
+            sector_id.derive_expiration(
+                sector_history_size,
+                min_sector_lifetime,
+                history_size(current_history_size),
+                Some(&sector_expiration_check_super_segment_root),
+            ),
+            Some(expires_at)
+        );
+    }
+    // Replotting is scheduled one segment before expiration
+    assert!(!expires_at.should_replot(history_size(14)));
+    assert!(!expires_at.should_replot(history_size(39)));
+    assert!(expires_at.should_replot(history_size(40)));
+
+    let expired = SectorExpiration::Expired {
+        expiration_history_size: history_size(41),
+    };
+    for current_history_size in [41, 42] {
+        assert_eq!(
+            sector_id.derive_expiration(
+                sector_history_size,
+                min_sector_lifetime,
+                history_size(current_history_size),
+                Some(&sector_expiration_check_super_segment_root),
+            ),
+            Some(expired)
+        );
+    }
+    assert!(expired.should_replot(history_size(41)));
+}
@@ -275,6 +275,20 @@ pub struct SuperSegmentHeader {
     pub num_segments: u32,
 }
 
+impl SuperSegmentHeader {
+    /// Hash of the whole super segment header
+    #[inline(always)]
+    pub fn hash(&self) -> Blake3Hash {
+        const {
+            assert!(size_of::<Self>() <= CHUNK_LEN);
+        }
+        Blake3Hash::new(
+            single_chunk_hash(self.as_bytes())
+                .expect("Less than a single chunk worth of bytes; qed"),
+        )
+    }
+}
+
 /// Super segment
 #[cfg(feature = "alloc")]
 #[derive(Debug, Clone)]
@@ -319,10 +333,7 @@ impl SuperSegment {
             header: SuperSegmentHeader {
                 index: (previous_header.index.as_inner() + SuperSegmentIndex::ONE).into(),
                 root: SuperSegmentRoot::from(maybe_super_segment_root),
-                prev_super_segment_header_hash: Blake3Hash::from(
-                    single_chunk_hash(previous_header.as_bytes())
-                        .expect("Less than a single chunk worth of bytes; qed"),
-                ),
+                prev_super_segment_header_hash: previous_header.hash(),
                 max_segment_index: max_segment_index.into(),
                 target_beacon_chain_block_number: target_beacon_chain_block_number.into(),
                 num_segments,
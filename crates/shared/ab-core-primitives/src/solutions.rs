@@ -1,22 +1,22 @@
 //! Solutions-related data structures and functions.
 
+#[cfg(test)]
+mod tests;
+
 use crate::block::BlockNumber;
 use crate::ed25519::Ed25519PublicKey;
 use crate::hashes::Blake3Hash;
 use crate::pieces::{PieceOffset, Record, RecordChunk, RecordProof, RecordRoot, SegmentProof};
-use crate::pos::{PosProof, PosSeed};
+use crate::pos::PosProof;
 use crate::pot::{PotOutput, SlotNumber};
-use crate::sectors::{SBucket, SectorId, SectorIndex, SectorSlotChallenge};
+use crate::sectors::{SectorIndex, SectorSlotChallenge};
 use crate::segments::{
     HistorySize, LocalSegmentIndex, SegmentIndex, SegmentPosition, SegmentRoot, SuperSegmentIndex,
-    SuperSegmentRoot,
 };
-use crate::shard::{NumShards, RealShardKind, ShardIndex, ShardKind};
+use crate::shard::{NumShards, RealShardKind, ShardIndex};
 use ab_blake3::single_block_keyed_hash;
 use ab_io_type::trivial_type::TrivialType;
-use ab_merkle_tree::balanced::BalancedMerkleTree;
 use blake3::{Hash, OUT_LEN};
-use core::simd::Simd;
 use core::{fmt, mem};
 use derive_more::{
     Add, AddAssign, AsMut, AsRef, Deref, DerefMut, Display, From, Into, Sub, SubAssign,
@@ -197,6 +197,25 @@ impl SolutionRange {
         )
     }
 
+    /// Expands the global solution range to a solution range that corresponds to a shard of
+    /// specified kind
+    #[inline]
+    pub const fn to_shard_kind(self, shard_kind: RealShardKind, num_shards: NumShards) -> Self {
+        match shard_kind {
+            RealShardKind::BeaconChain => self,
+            RealShardKind::IntermediateShard => self.to_intermediate_shard(num_shards),
+            RealShardKind::LeafShard => self.to_leaf_shard(num_shards),
+        }
+    }
+
+    /// Expands the global solution range to a solution range that farmers audit with.
+    ///
+    /// See [`FarmerSolutionRange`] for details.
+    #[inline]
+    pub const fn to_farmer_solution_range(self, num_shards: NumShards) -> FarmerSolutionRange {
+        FarmerSolutionRange(self.to_leaf_shard(num_shards))
+    }
+
     /// Bidirectional distance between two solution ranges
     #[inline]
     pub const fn bidirectional_distance(self, other: Self) -> SolutionDistance {
@@ -254,6 +273,50 @@ const {
     assert!(SolutionRange::from_pieces(5, (1, 6)).to_pieces((1, 6)) == 5);
 }
 
+/// Solution range that farmers audit with.
+///
+/// A farmer audits once for all shard kinds, hence it uses the widest solution range, which is the
+/// solution range of leaf shards. Created from the global (consensus) solution range with
+/// [`SolutionRange::to_farmer_solution_range()`]. Audit only finds solution candidates, whether a
+/// solution is within the solution range of a particular shard is checked during verification.
+#[derive(
+    Debug, Display, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, TrivialType,
+)]
+#[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C)]
+pub struct FarmerSolutionRange(SolutionRange);
+
+impl const From<FarmerSolutionRange> for SolutionRange {
+    #[inline(always)]
+    fn from(value: FarmerSolutionRange) -> Self {
+        value.0
+    }
+}
+
+impl FarmerSolutionRange {
+    /// Minimum value
+    pub const MIN: Self = Self(SolutionRange::MIN);
+    /// Maximum value
+    pub const MAX: Self = Self(SolutionRange::MAX);
+
+    /// Global (consensus) solution range this farmer solution range was created from.
+    ///
+    /// Returns `None` if there is no such solution range for the specified number of shards,
+    /// which includes farmer solution ranges that were saturated during expansion.
+    #[inline]
+    pub const fn to_consensus_solution_range(self, num_shards: NumShards) -> Option<SolutionRange> {
+        let leaf_shards = u64::from(num_shards.leaf_shards().get());
+        let farmer_solution_range = u64::from(self.0);
+
+        if farmer_solution_range % leaf_shards == 0 {
+            Some(SolutionRange(farmer_solution_range / leaf_shards))
+        } else {
+            None
+        }
+    }
+}
+
 /// Proof for chunk contained within a record.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Deref, DerefMut, From, Into, TrivialType)]
 #[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
@@ -361,142 +424,6 @@ impl ChunkProof {
     const NUM_HASHES: usize = Record::NUM_S_BUCKETS.ilog2() as usize;
 }
 
-/// Solution verification errors
-#[derive(Debug, Eq, PartialEq, thiserror::Error)]
-pub enum SolutionVerifyError {
-    /// Invalid piece offset
-    #[error("Piece verification failed")]
-    InvalidPieceOffset {
-        /// Index of the piece that failed verification
-        piece_offset: u16,
-        /// How many pieces one sector is supposed to contain (max)
-        max_pieces_in_sector: u16,
-    },
-    /// History size is in the future
-    #[error("History size {solution} is in the future, current is {current}")]
-    FutureHistorySize {
-        /// Current history size
-        current: HistorySize,
-        /// History size solution was created for
-        solution: HistorySize,
-    },
-    /// Sector expired
-    #[error("Sector expired")]
-    SectorExpired {
-        /// Expiration history size
-        expiration_history_size: HistorySize,
-        /// Current history size
-        current_history_size: HistorySize,
-    },
-    /// Record does not belong to the segment
-    #[error("Record does not belong to the segment")]
-    RecordNotInSegment,
-    /// Segment doesn't belong to the super segment
-    #[error("Segment doesn't belong to the super segment")]
-    SegmentNotInSuperSegment,
-    /// Solution is outside the solution range
-    #[error("Solution distance {solution_distance} is outside of solution range {solution_range}")]
-    OutsideSolutionRange {
-        /// Solution range
-        solution_range: SolutionRange,
-        /// Solution distance
-        solution_distance: SolutionDistance,
-    },
-    /// Invalid proof of space
-    #[error("Invalid proof of space")]
-    InvalidProofOfSpace,
-    /// Invalid shard commitment
-    #[error("Invalid shard commitment")]
-    InvalidShardCommitment,
-    /// Invalid input shard
-    #[error("Invalid input shard {shard_index} ({shard_kind:?})")]
-    InvalidInputShard {
-        /// Input shard index
-        shard_index: ShardIndex,
-        /// Input shard kind
-        shard_kind: Option<ShardKind>,
-    },
-    /// Invalid solution shard
-    #[error(
-        "Invalid solution shard {solution_shard_index} (parent {solution_parent_shard_index:?}), \
-        expected shard {expected_shard_index} ({expected_shard_kind:?})"
-    )]
-    InvalidSolutionShard {
-        /// Solution shard index
-        solution_shard_index: ShardIndex,
-        /// Solution shard index
-        solution_parent_shard_index: Option<ShardIndex>,
-        /// Expected shard index
-        expected_shard_index: ShardIndex,
-        /// Expected shard kind
-        expected_shard_kind: RealShardKind,
-    },
-    /// Invalid chunk proof
-    #[error("Invalid chunk proof")]
-    InvalidChunkProof,
-    /// Invalid history size
-    #[error("Invalid history size")]
-    InvalidHistorySize,
-}
-
-/// Parameters for stateless solution verification.
-///
-/// These only include the information already contained in the block itself, meaning verification
-/// of different blocks can be done concurrently.
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
-pub struct SolutionVerifyStatelessParams {
-    /// Shard for which the solution is built
-    pub shard_index: ShardIndex,
-    /// Proof of time for which solution is built
-    pub proof_of_time: PotOutput,
-    /// Solution range
-    pub solution_range: SolutionRange,
-    /// Shard membership entropy
-    pub shard_membership_entropy: ShardMembershipEntropy,
-    /// The number of shards in the network
-    pub num_shards: NumShards,
-}
-
-/// Parameters for checking piece validity used in a solution
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
-pub struct SolutionVerifyPieceParams {
-    /// How many pieces one sector is supposed to contain (max)
-    pub max_pieces_in_sector: u16,
-    /// Super segment root of the segment to which piece belongs
-    pub super_segment_root: SuperSegmentRoot,
-    /// Number of segments in the super segment
-    pub num_segments: u32,
-    /// Number of latest archived segments that are considered "recent history"
-    pub recent_segments: HistorySize,
-    /// Fraction of pieces from the "recent history" (`recent_segments`) in each sector
-    pub recent_history_fraction: (HistorySize, HistorySize),
-    /// Minimum lifetime of a plotted sector, measured in archived segments
-    pub min_sector_lifetime: HistorySize,
-    /// Current size of the history
-    pub current_history_size: HistorySize,
-    /// Super segment root that contains a segment at `min_sector_lifetime` from sector creation
-    /// (if exists)
-    pub sector_expiration_check_super_segment_root: Option<SuperSegmentRoot>,
-}
-
-/// Parameters for full solution verification
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "scale-codec", derive(Encode, Decode, MaxEncodedLen))]
-pub struct SolutionVerifyFullParams {
-    /// Parameters for stateless solution verification
-    pub stateless: SolutionVerifyStatelessParams,
-    /// Parameters for checking piece validity used in a solution
-    pub piece: SolutionVerifyPieceParams,
-}
-
-/// Proof-of-time verifier to be used in [`Solution::verify_full()`]
-pub trait SolutionPotVerifier {
-    /// Check whether proof created earlier is valid
-    fn is_proof_valid(seed: &PosSeed, s_bucket: SBucket, proof: &PosProof) -> bool;
-}
-
 /// Entropy used for shard membership assignment
 #[derive(
     Default,
@@ -871,268 +798,4 @@ impl Solution {
             padding: [0; _],
         }
     }
-
-    /// Check solution validity
-    pub fn verify_full<PotVerifier>(
-        &self,
-        slot: SlotNumber,
-        params: &SolutionVerifyFullParams,
-    ) -> Result<(), SolutionVerifyError>
-    where
-        PotVerifier: SolutionPotVerifier,
-    {
-        let sector_id = SectorId::new(
-            &self.public_key_hash,
-            &self.shard_commitment.root,
-            self.sector_index,
-            self.history_size,
-        );
-
-        self.verify_stateless_inner::<PotVerifier>(&sector_id, slot, &params.stateless)?;
-
-        self.verify_piece_inner(&sector_id, &params.piece)
-    }
-
-    /// Stateless solution verification.
-    ///
-    /// Checks most things, except checking that the piece belongs to the global history.
-    ///
-    /// For piece verification use [`Self::verify_piece()`] or call [`Self::verify_full()`] for more
-    /// efficient verification of both at once.
-    pub fn verify_stateless<PotVerifier>(
-        &self,
-        slot: SlotNumber,
-        params: &SolutionVerifyStatelessParams,
-    ) -> Result<(), SolutionVerifyError>
-    where
-        PotVerifier: SolutionPotVerifier,
-    {
-        let sector_id = SectorId::new(
-            &self.public_key_hash,
-            &self.shard_commitment.root,
-            self.sector_index,
-            self.history_size,
-        );
-
-        self.verify_stateless_inner::<PotVerifier>(&sector_id, slot, params)
-    }
-
-    fn verify_stateless_inner<PotVerifier>(
-        &self,
-        sector_id: &SectorId,
-        slot: SlotNumber,
-        params: &SolutionVerifyStatelessParams,
-    ) -> Result<(), SolutionVerifyError>
-    where
-        PotVerifier: SolutionPotVerifier,
-    {
-        let SolutionVerifyStatelessParams {
-            shard_index,
-            proof_of_time,
-            solution_range,
-            shard_membership_entropy,
-            num_shards,
-        } = params;
-
-        let shard_kind = shard_index
-            .shard_kind()
-            .and_then(ShardKind::to_real)
-            .ok_or(SolutionVerifyError::InvalidInputShard {
-                shard_index: *shard_index,
-                shard_kind: shard_index.shard_kind(),
-            })?;
-
-        let (solution_shard_index, shard_commitment_index) = num_shards
-            .derive_shard_index_and_shard_commitment_index(
-                &self.public_key_hash,
-                &self.shard_commitment.root,
-                shard_membership_entropy,
-                self.history_size,
-            );
-
-        // Adjust solution range according to shard kind
-        let solution_range = match shard_kind {
-            RealShardKind::BeaconChain => *solution_range,
-            RealShardKind::IntermediateShard => {
-                if solution_shard_index.parent_shard() != Some(*shard_index) {
-                    return Err(SolutionVerifyError::InvalidSolutionShard {
-                        solution_shard_index,
-                        solution_parent_shard_index: solution_shard_index.parent_shard(),
-                        expected_shard_index: *shard_index,
-                        expected_shard_kind: RealShardKind::IntermediateShard,
-                    });
-                }
-
-                solution_range.to_intermediate_shard(*num_shards)
-            }
-            RealShardKind::LeafShard => {
-                if solution_shard_index != *shard_index {
-                    return Err(SolutionVerifyError::InvalidSolutionShard {
-                        solution_shard_index,
-                        solution_parent_shard_index: solution_shard_index.parent_shard(),
-                        expected_shard_index: *shard_index,
-                        expected_shard_kind: RealShardKind::LeafShard,
-                    });
-                }
-
-                solution_range.to_leaf_shard(*num_shards)
-            }
-        };
-
-        // TODO: This is a workaround for https://github.com/rust-lang/rust/issues/139866 that
-        //  allows the code to compile. Constant 1_048_576 is hardcoded here and below for
-        // compilation  to succeed.
-        const {
-            assert!(SolutionShardCommitment::NUM_LEAVES == 1_048_576);
-        }
-        if !BalancedMerkleTree::<1_048_576>::verify(
-            &self.shard_commitment.root,
-            &ShardCommitmentHash::repr_from_array(self.shard_commitment.proof),
-            shard_commitment_index as usize,
-            *self.shard_commitment.leaf,
-        ) {
-            return Err(SolutionVerifyError::InvalidShardCommitment);
-        }
-
-        let global_challenge = proof_of_time.derive_global_challenge(slot);
-        let sector_slot_challenge = sector_id.derive_sector_slot_challenge(&global_challenge);
-        let s_bucket_audit_index = sector_slot_challenge.s_bucket_audit_index();
-
-        // Check that proof of space is valid
-        if !PotVerifier::is_proof_valid(
-            &sector_id.derive_evaluation_seed(self.piece_offset),
-            s_bucket_audit_index,
-            &self.proof_of_space,
-        ) {
-            return Err(SolutionVerifyError::InvalidProofOfSpace);
-        }
-
-        let masked_chunk =
-            (Simd::from(*self.chunk) ^ Simd::from(*self.proof_of_space.hash())).to_array();
-
-        let solution_distance =
-            SolutionDistance::calculate(&global_challenge, &masked_chunk, &sector_slot_challenge);
-
-        if !solution_distance.is_within(solution_range) {
-            return Err(SolutionVerifyError::OutsideSolutionRange {
-                solution_range,
-                solution_distance,
-            });
-        }
-
-        // TODO: This is a workaround for https://github.com/rust-lang/rust/issues/139866 that
-        //  allows the code to compile. Constant 65536 is hardcoded here and below for compilation
-        //  to succeed.
-        const {
-            assert!(Record::NUM_S_BUCKETS == 65536);
-        }
-        // Check that chunk belongs to the record
-        if !BalancedMerkleTree::<65536>::verify(
-            &self.record_root,
-            &self.chunk_proof,
-            usize::from(s_bucket_audit_index),
-            *self.chunk,
-        ) {
-            return Err(SolutionVerifyError::InvalidChunkProof);
-        }
-
-        Ok(())
-    }
-
-    /// Verify the piece details of the solution
-    pub fn verify_piece(
-        &self,
-        piece_check_params: &SolutionVerifyPieceParams,
-    ) -> Result<(), SolutionVerifyError> {
-        let sector_id = SectorId::new(
-            &self.public_key_hash,
-            &self.shard_commitment.root,
-            self.sector_index,
-            self.history_size,
-        );
-
-        self.verify_piece_inner(&sector_id, piece_check_params)
-    }
-
-    fn verify_piece_inner(
-        &self,
-        sector_id: &SectorId,
-        piece_check_params: &SolutionVerifyPieceParams,
-    ) -> Result<(), SolutionVerifyError> {
-        let SolutionVerifyPieceParams {
-            max_pieces_in_sector,
-            super_segment_root,
-            num_segments,
-            recent_segments,
-            recent_history_fraction,
-            min_sector_lifetime,
-            current_history_size,
-            sector_expiration_check_super_segment_root,
-        } = piece_check_params;
-
-        if &self.history_size > current_history_size {
-            return Err(SolutionVerifyError::FutureHistorySize {
-                current: *current_history_size,
-                solution: self.history_size,
-            });
-        }
-
-        if u16::from(self.piece_offset) >= *max_pieces_in_sector {
-            return Err(SolutionVerifyError::InvalidPieceOffset {
-                piece_offset: u16::from(self.piece_offset),
-                max_pieces_in_sector: *max_pieces_in_sector,
-            });
-        }
-
-        if let Some(sector_expiration_check_super_segment_root) =
-            sector_expiration_check_super_segment_root
-        {
-            let Some(expiration_history_size) = sector_id.derive_expiration_history_size(
-                self.history_size,
-                sector_expiration_check_super_segment_root,
-                *min_sector_lifetime,
-            ) else {
-                return Err(SolutionVerifyError::InvalidHistorySize);
-            };
-
-            if expiration_history_size <= *current_history_size {
-                return Err(SolutionVerifyError::SectorExpired {
-                    expiration_history_size,
-                    current_history_size: *current_history_size,
-                });
-            }
-        }
-
-        let position = sector_id
-            .derive_piece_index(
-                self.piece_offset,
-                self.history_size,
-                *max_pieces_in_sector,
-                *recent_segments,
-                *recent_history_fraction,
-            )
-            .position();
-
-        // Check that record belongs to the segment
-        if !self
-            .record_root
-            .is_valid(&self.segment_root, &self.record_proof, position)
-        {
-            return Err(SolutionVerifyError::RecordNotInSegment);
-        }
-
-        // Check that segment belongs to the super segment (global history)
-        if !self.segment_root.is_valid(
-            self.piece_shard_index,
-            self.piece_local_segment_index,
-            self.segment_position,
-            &self.segment_proof,
-            *num_segments,
-            super_segment_root,
-        ) {
-            return Err(SolutionVerifyError::SegmentNotInSuperSegment);
-        }
-
-        Ok(())
-    }
 }
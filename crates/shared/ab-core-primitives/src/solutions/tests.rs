@@ -0,0 +1,99 @@
+use crate::shard::{NumShards, RealShardKind};
+use crate::solutions::{FarmerSolutionRange, SolutionRange};
+use core::num::NonZeroU16;
+
+fn num_shards(intermediate_shards: u16, leaf_shards_per_intermediate_shard: u16) -> NumShards {
+    NumShards::new(
+        NonZeroU16::new(intermediate_shards).unwrap(),
+        NonZeroU16::new(leaf_shards_per_intermediate_shard).unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn farmer_solution_range_single_shard() {
+    let num_shards = num_shards(1, 1);
+
+    for solution_range in [
+        SolutionRange::MIN,
+        SolutionRange::from(1),
+        SolutionRange::from(u64::MAX / 2),
+        SolutionRange::MAX,
+    ] {
+        let farmer_solution_range = solution_range.to_farmer_solution_range(num_shards);
+        assert_eq!(SolutionRange::from(farmer_solution_range), solution_range);
+        assert_eq!(
+            farmer_solution_range.to_consensus_solution_range(num_shards),
+            Some(solution_range)
+        );
+    }
+}
+
+#[test]
+fn farmer_solution_range_round_trip() {
+    let num_shards = num_shards(4, 8);
+    let leaf_shards = u64::from(num_shards.leaf_shards().get());
+
+    // Largest solution range that doesn't saturate
+    let max_solution_range = SolutionRange::from(u64::MAX / leaf_shards);
+
+    for solution_range in [
+        SolutionRange::MIN,
+        SolutionRange::from(1),
+        SolutionRange::from(12_345_678),
+        max_solution_range,
+    ] {
+        let farmer_solution_range = solution_range.to_farmer_solution_range(num_shards);
+        assert_eq!(
+            u64::from(SolutionRange::from(farmer_solution_range)),
+            u64::from(solution_range) * leaf_shards
+        );
+        assert_eq!(
+            farmer_solution_range.to_consensus_solution_range(num_shards),
+            Some(solution_range)
+        );
+    }
+
+    // Saturates and can't be converted back
+    let farmer_solution_range =
+        SolutionRange::from(u64::from(max_solution_range) + 1).to_farmer_solution_range(num_shards);
+    assert_eq!(farmer_solution_range, FarmerSolutionRange::MAX);
+    assert_eq!(
+        farmer_solution_range.to_consensus_solution_range(num_shards),
+        None
+    );
+    assert_eq!(
+        SolutionRange::MAX.to_farmer_solution_range(num_shards),
+        FarmerSolutionRange::MAX
+    );
+}
+
+#[test]
+fn solution_range_per_shard_kind() {
+    let num_shards = num_shards(4, 8);
+    let solution_range = SolutionRange::from(1_000);
+
+    assert_eq!(
+        solution_range.to_shard_kind(RealShardKind::BeaconChain, num_shards),
+        solution_range
+    );
+    assert_eq!(
+        solution_range.to_shard_kind(RealShardKind::IntermediateShard, num_shards),
+        SolutionRange::from(4_000)
+    );
+    assert_eq!(
+        solution_range.to_shard_kind(RealShardKind::LeafShard, num_shards),
+        SolutionRange::from(32_000)
+    );
+
+    // Farmers audit with the widest solution range of all shard kinds
+    let farmer_solution_range =
+        SolutionRange::from(solution_range.to_farmer_solution_range(num_shards));
+    for shard_kind in [
+        RealShardKind::BeaconChain,
+        RealShardKind::IntermediateShard,
+        RealShardKind::LeafShard,
+    ] {
+        assert!(solution_range.to_shard_kind(shard_kind, num_shards) <= farmer_solution_range);
+    }
+}
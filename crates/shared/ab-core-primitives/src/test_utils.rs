@@ -0,0 +1,174 @@
+//! Utilities for tests that need blocks and chains of blocks.
+//!
+//! Blocks produced here are internally consistent (body root, parent root, block number, etc. are
+//! correct), but contain placeholder consensus information and seal, so they will not pass full
+//! consensus verification.
+
+use crate::block::header::{
+    BlockHeaderConsensusInfo, BlockHeaderConsensusParameters, BlockHeaderEd25519Seal,
+    BlockHeaderExtensions, BlockHeaderFixedConsensusParameters, BlockHeaderPrefix, BlockHeaderSeal,
+};
+use crate::block::owned::OwnedBeaconChainBlock;
+use crate::block::{BlockNumber, BlockRoot, BlockTimestamp};
+use crate::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use crate::hashes::Blake3Hash;
+use crate::pot::{PotCheckpoints, PotOutput, SlotNumber};
+use crate::shard::{NumShards, ShardIndex};
+use crate::solutions::{Solution, SolutionRange};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::num::{NonZeroU16, NonZeroU32};
+
+/// Builder of beacon chain blocks and chains of blocks for tests.
+///
+/// Each child block has the number, slot and timestamp one step after its parent. Blocks built
+/// with different fork IDs on top of the same parent have different roots, which allows creating
+/// forks.
+#[derive(Debug, Clone)]
+pub struct TestBeaconChainBlockBuilder {
+    num_pot_checkpoints: usize,
+    fork_id: u64,
+    slot_step: SlotNumber,
+    timestamp_step: BlockTimestamp,
+    fixed_parameters: BlockHeaderFixedConsensusParameters,
+}
+
+impl Default for TestBeaconChainBlockBuilder {
+    fn default() -> Self {
+        Self {
+            num_pot_checkpoints: 0,
+            fork_id: 0,
+            slot_step: SlotNumber::ONE,
+            timestamp_step: BlockTimestamp::from_millis(1_000),
+            fixed_parameters: BlockHeaderFixedConsensusParameters {
+                solution_range: SolutionRange::MAX,
+                slot_iterations: NonZeroU32::MIN,
+                num_shards: NumShards::new(NonZeroU16::MIN, NonZeroU16::MIN)
+                    .expect("Values are statically known to be valid; qed"),
+            },
+        }
+    }
+}
+
+impl TestBeaconChainBlockBuilder {
+    /// Number of PoT checkpoints in the body of each block, controls the body size
+    pub fn with_num_pot_checkpoints(mut self, num_pot_checkpoints: usize) -> Self {
+        self.num_pot_checkpoints = num_pot_checkpoints;
+        self
+    }
+
+    /// Fork ID mixed into the state root of each block, blocks with the same parent and different
+    /// fork IDs are different
+    pub fn with_fork_id(mut self, fork_id: u64) -> Self {
+        self.fork_id = fork_id;
+        self
+    }
+
+    /// Difference in slots between parent and child blocks
+    pub fn with_slot_step(mut self, slot_step: SlotNumber) -> Self {
+        self.slot_step = slot_step;
+        self
+    }
+
+    /// Difference in timestamps between parent and child blocks
+    pub fn with_timestamp_step(mut self, timestamp_step: BlockTimestamp) -> Self {
+        self.timestamp_step = timestamp_step;
+        self
+    }
+
+    /// Fixed consensus parameters included in the header of each block
+    pub fn with_fixed_parameters(
+        mut self,
+        fixed_parameters: BlockHeaderFixedConsensusParameters,
+    ) -> Self {
+        self.fixed_parameters = fixed_parameters;
+        self
+    }
+
+    /// Build genesis block
+    pub fn genesis(&self) -> OwnedBeaconChainBlock {
+        self.build(
+            BlockNumber::ZERO,
+            BlockRoot::default(),
+            SlotNumber::ZERO,
+            BlockTimestamp::default(),
+        )
+    }
+
+    /// Build a child block of `parent`
+    pub fn child(&self, parent: &OwnedBeaconChainBlock) -> OwnedBeaconChainBlock {
+        let parent_header = parent.header.header();
+
+        self.build(
+            parent_header.prefix.number + BlockNumber::ONE,
+            *parent_header.root(),
+            parent_header.consensus_info.slot + self.slot_step,
+            parent_header
+                .prefix
+                .timestamp
+                .saturating_add(self.timestamp_step),
+        )
+    }
+
+    /// Build a chain of `length` blocks on top of `parent`, `parent` itself is not included
+    pub fn chain(
+        &self,
+        parent: &OwnedBeaconChainBlock,
+        length: usize,
+    ) -> Vec<OwnedBeaconChainBlock> {
+        let mut blocks = Vec::<OwnedBeaconChainBlock>::with_capacity(length);
+
+        for _ in 0..length {
+            let block = self.child(blocks.last().unwrap_or(parent));
+            blocks.push(block);
+        }
+
+        blocks
+    }
+
+    fn build(
+        &self,
+        number: BlockNumber,
+        parent_root: BlockRoot,
+        slot: SlotNumber,
+        timestamp: BlockTimestamp,
+    ) -> OwnedBeaconChainBlock {
+        let pot_checkpoints = vec![PotCheckpoints::default(); self.num_pot_checkpoints];
+
+        let mut state_root = [0; Blake3Hash::SIZE];
+        state_root[..size_of::<u64>()].copy_from_slice(&self.fork_id.to_le_bytes());
+
+        OwnedBeaconChainBlock::init([].into_iter(), [].into_iter(), &pot_checkpoints)
+            .expect("Test block body is valid; qed")
+            .with_header(
+                &BlockHeaderPrefix {
+                    number,
+                    shard_index: ShardIndex::BEACON_CHAIN,
+                    padding_0: [0; _],
+                    timestamp,
+                    parent_root,
+                    mmr_root: Blake3Hash::default(),
+                },
+                Blake3Hash::new(state_root),
+                Blake3Hash::default(),
+                &BlockHeaderConsensusInfo {
+                    slot,
+                    proof_of_time: PotOutput::default(),
+                    future_proof_of_time: PotOutput::default(),
+                    solution: Solution::genesis_solution(),
+                },
+                &BlockHeaderConsensusParameters {
+                    fixed_parameters: self.fixed_parameters,
+                    super_segment_root: None,
+                    next_solution_range: None,
+                    pot_parameters_change: None,
+                },
+                &BlockHeaderExtensions::EMPTY,
+            )
+            .expect("Test block header is valid; qed")
+            .with_seal(BlockHeaderSeal::Ed25519(&BlockHeaderEd25519Seal {
+                public_key: Ed25519PublicKey::default(),
+                signature: Ed25519Signature::default(),
+            }))
+    }
+}
@@ -8,16 +8,49 @@ use crate::block::BlockRoot;
 use crate::hashes::Blake3Hash;
 #[cfg(feature = "alloc")]
 use crate::transaction::owned::{OwnedTransaction, OwnedTransactionError};
+#[cfg(feature = "serde")]
+use ::serde::{Deserialize, Serialize};
 use ab_io_type::trivial_type::TrivialType;
 use blake3::Hasher;
 use core::slice;
 use derive_more::{Deref, DerefMut, Display, From, Into};
 
 /// A measure of compute resources, 1 Gas == 1 ns of compute on reference hardware
-#[derive(Debug, Default, Copy, Clone, TrivialType)]
+#[derive(
+    Debug, Display, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, TrivialType,
+)]
 #[repr(C)]
 pub struct Gas(u64);
 
+impl const From<u64> for Gas {
+    #[inline(always)]
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl const From<Gas> for u64 {
+    #[inline(always)]
+    fn from(value: Gas) -> Self {
+        value.0
+    }
+}
+
+impl Gas {
+    /// No gas
+    pub const ZERO: Self = Self(0);
+
+    /// Checked addition, returns `None` on overflow
+    #[inline(always)]
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        if let Some(n) = self.0.checked_add(rhs.0) {
+            Some(Self(n))
+        } else {
+            None
+        }
+    }
+}
+
 /// Transaction hash
 #[derive(
     Debug,
@@ -36,6 +69,8 @@ pub struct Gas(u64);
     DerefMut,
     TrivialType,
 )]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[repr(C)]
 pub struct TransactionHash(Blake3Hash);
 
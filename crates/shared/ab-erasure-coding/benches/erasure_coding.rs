@@ -0,0 +1,64 @@
+use ab_erasure_coding::{ErasureCoding, ErasureCodingBackend, RecoveryShardState};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+const NUM_SHARDS: usize = 128;
+const SHARD_SIZE: usize = 1024;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    for (name, backend) in [
+        ("scalar", ErasureCodingBackend::Scalar),
+        ("simd", ErasureCodingBackend::Simd),
+    ] {
+        let Some(ec) = ErasureCoding::with_backend(backend) else {
+            println!("Backend {name} is not supported, skipping");
+            continue;
+        };
+
+        erasure_coding(c, name, &ec);
+    }
+}
+
+fn erasure_coding(c: &mut Criterion, name: &str, ec: &ErasureCoding) {
+    let source = (0..NUM_SHARDS)
+        .map(|index| [(index % u8::MAX as usize + 1) as u8; SHARD_SIZE])
+        .collect::<Vec<_>>();
+    let mut parity = vec![[0u8; SHARD_SIZE]; NUM_SHARDS];
+
+    c.bench_function(&format!("{NUM_SHARDS}x{SHARD_SIZE}/{name}/extend"), |b| {
+        b.iter(|| {
+            ec.extend(black_box(source.iter()), black_box(parity.iter_mut()))
+                .unwrap();
+        });
+    });
+
+    let mut recovered_source = source.clone();
+    let mut recovered_parity = parity.clone();
+
+    c.bench_function(
+        &format!("{NUM_SHARDS}x{SHARD_SIZE}/{name}/recover-half"),
+        |b| {
+            b.iter(|| {
+                // Every other source shard is missing, recovered from parity shards
+                let source = recovered_source
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(index, shard)| {
+                        if index % 2 == 0 {
+                            RecoveryShardState::MissingRecover(shard.as_mut_slice())
+                        } else {
+                            RecoveryShardState::Present(shard.as_slice())
+                        }
+                    });
+                let parity = recovered_parity
+                    .iter_mut()
+                    .map(|shard| RecoveryShardState::Present(shard.as_slice()));
+
+                ec.recover(black_box(source), black_box(parity)).unwrap();
+            });
+        },
+    );
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
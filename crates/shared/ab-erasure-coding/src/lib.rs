@@ -1,14 +1,22 @@
+//! Erasure coding implementation.
+//!
+//! Multiple backends are supported (see [`ErasureCodingBackend`]), the fastest available backend
+//! is selected automatically by default. Runtime capability detection requires `std` feature,
+//! otherwise only SIMD instructions enabled at compile time are used.
+
 #![feature(trusted_len)]
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use alloc::vec;
 use alloc::vec::Vec;
 use core::iter::TrustedLen;
 use core::mem::MaybeUninit;
 use reed_solomon_simd::Error;
-use reed_solomon_simd::engine::DefaultEngine;
+use reed_solomon_simd::engine::{DefaultEngine, Engine, NoSimd};
 use reed_solomon_simd::rate::{HighRateDecoder, HighRateEncoder, RateDecoder, RateEncoder};
 
 /// Error that occurs when calling [`ErasureCoding::recover()`]
@@ -45,11 +53,59 @@ pub enum RecoveryShardState<PresentShard, MissingShard> {
     MissingIgnore,
 }
 
+/// Erasure coding backend
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum ErasureCodingBackend {
+    /// Select the fastest backend supported by the current CPU
+    #[default]
+    Auto,
+    /// Portable scalar implementation that works everywhere
+    Scalar,
+    /// SIMD implementation (AVX2 or SSSE3 on x86/x86-64, NEON on aarch64)
+    Simd,
+    // TODO: GPU backend
+}
+
+impl ErasureCodingBackend {
+    /// Check whether the backend is supported by the current CPU
+    pub fn is_supported(self) -> bool {
+        match self {
+            Self::Auto | Self::Scalar => true,
+            Self::Simd => simd_supported(),
+        }
+    }
+}
+
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+fn simd_supported() -> bool {
+    // All CPUs with AVX2 support SSSE3 as well
+    std::is_x86_feature_detected!("ssse3")
+}
+
+#[cfg(all(feature = "std", target_arch = "aarch64"))]
+fn simd_supported() -> bool {
+    std::arch::is_aarch64_feature_detected!("neon")
+}
+
+#[cfg(not(all(
+    feature = "std",
+    any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")
+)))]
+fn simd_supported() -> bool {
+    cfg!(any(
+        target_feature = "avx2",
+        target_feature = "ssse3",
+        target_feature = "neon"
+    ))
+}
+
 /// Erasure coding abstraction.
 ///
 /// Supports creation of parity records and recovery of missing data.
 #[derive(Debug, Clone)]
-pub struct ErasureCoding;
+pub struct ErasureCoding {
+    backend: ErasureCodingBackend,
+}
 
 impl Default for ErasureCoding {
     fn default() -> Self {
@@ -58,9 +114,39 @@ impl Default for ErasureCoding {
 }
 
 impl ErasureCoding {
-    /// Create new erasure coding instance
+    /// Create new erasure coding instance with the fastest supported backend
     pub fn new() -> Self {
-        Self {}
+        Self::with_backend(ErasureCodingBackend::Auto)
+            .expect("Automatic backend selection is always supported; qed")
+    }
+
+    /// Create new erasure coding instance with a specified backend.
+    ///
+    /// Returns `None` if the backend is not supported by the current CPU.
+    pub fn with_backend(backend: ErasureCodingBackend) -> Option<Self> {
+        let backend = match backend {
+            ErasureCodingBackend::Auto => {
+                if simd_supported() {
+                    ErasureCodingBackend::Simd
+                } else {
+                    ErasureCodingBackend::Scalar
+                }
+            }
+            ErasureCodingBackend::Scalar | ErasureCodingBackend::Simd => {
+                if !backend.is_supported() {
+                    return None;
+                }
+
+                backend
+            }
+        };
+
+        Some(Self { backend })
+    }
+
+    /// Backend used by this instance, never [`ErasureCodingBackend::Auto`]
+    pub fn backend(&self) -> ErasureCodingBackend {
+        self.backend
     }
 
     /// Extend sources using erasure coding
@@ -74,6 +160,44 @@ impl ErasureCoding {
         ParityIter: TrustedLen<Item = ParityBytes>,
         SourceBytes: AsRef<[u8]> + 'a,
         ParityBytes: AsMut<[u8]> + 'a,
+    {
+        match self.backend {
+            ErasureCodingBackend::Auto | ErasureCodingBackend::Simd => {
+                Self::extend_with_engine(DefaultEngine::new(), source, parity)
+            }
+            ErasureCodingBackend::Scalar => Self::extend_with_engine(NoSimd::new(), source, parity),
+        }
+    }
+
+    /// Recover missing shards
+    pub fn recover<'a, SourceIter, ParityIter>(
+        &self,
+        source: SourceIter,
+        parity: ParityIter,
+    ) -> Result<(), ErasureCodingError>
+    where
+        SourceIter: TrustedLen<Item = RecoveryShardState<&'a [u8], &'a mut [u8]>>,
+        ParityIter: TrustedLen<Item = RecoveryShardState<&'a [u8], &'a mut [u8]>>,
+    {
+        match self.backend {
+            ErasureCodingBackend::Auto | ErasureCodingBackend::Simd => {
+                Self::recover_with_engine(DefaultEngine::new, source, parity)
+            }
+            ErasureCodingBackend::Scalar => Self::recover_with_engine(NoSimd::new, source, parity),
+        }
+    }
+
+    fn extend_with_engine<'a, E, SourceIter, ParityIter, SourceBytes, ParityBytes>(
+        engine: E,
+        source: SourceIter,
+        parity: ParityIter,
+    ) -> Result<(), ErasureCodingError>
+    where
+        E: Engine,
+        SourceIter: TrustedLen<Item = SourceBytes>,
+        ParityIter: TrustedLen<Item = ParityBytes>,
+        SourceBytes: AsRef<[u8]> + 'a,
+        ParityBytes: AsMut<[u8]> + 'a,
     {
         let mut source = source.peekable();
         let shard_byte_len = source
@@ -85,7 +209,7 @@ impl ErasureCoding {
             source.size_hint().0,
             parity.size_hint().0,
             shard_byte_len,
-            DefaultEngine::new(),
+            engine,
             None,
         )?;
 
@@ -109,13 +233,13 @@ impl ErasureCoding {
         Ok(())
     }
 
-    /// Recover missing shards
-    pub fn recover<'a, SourceIter, ParityIter>(
-        &self,
+    fn recover_with_engine<'a, E, SourceIter, ParityIter>(
+        create_engine: fn() -> E,
         source: SourceIter,
         parity: ParityIter,
     ) -> Result<(), ErasureCodingError>
     where
+        E: Engine,
         SourceIter: TrustedLen<Item = RecoveryShardState<&'a [u8], &'a mut [u8]>>,
         ParityIter: TrustedLen<Item = RecoveryShardState<&'a [u8], &'a mut [u8]>>,
     {
@@ -168,7 +292,7 @@ impl ErasureCoding {
                 num_source,
                 num_parity,
                 shard_byte_len,
-                DefaultEngine::new(),
+                create_engine(),
                 None,
             )?;
 
@@ -225,7 +349,7 @@ impl ErasureCoding {
                 num_source,
                 num_parity,
                 shard_byte_len,
-                DefaultEngine::new(),
+                create_engine(),
                 None,
             )?;
 
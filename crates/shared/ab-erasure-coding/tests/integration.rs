@@ -1,6 +1,8 @@
 #![feature(trusted_len)]
 
-use ab_erasure_coding::{ErasureCoding, ErasureCodingError, RecoveryShardState};
+use ab_erasure_coding::{
+    ErasureCoding, ErasureCodingBackend, ErasureCodingError, RecoveryShardState,
+};
 use chacha20::ChaCha8Rng;
 use chacha20::rand_core::{Rng, SeedableRng};
 use reed_solomon_simd::Error;
@@ -107,3 +109,57 @@ fn basic_data() {
         ))
     );
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn backends_are_identical() {
+    let scalar_ec = ErasureCoding::with_backend(ErasureCodingBackend::Scalar)
+        .expect("Scalar backend is always supported; qed");
+    assert_eq!(scalar_ec.backend(), ErasureCodingBackend::Scalar);
+    // SIMD is only detected at runtime with `std` feature, otherwise it might not be available
+    let maybe_simd_ec = ErasureCoding::with_backend(ErasureCodingBackend::Simd);
+    if let Some(simd_ec) = &maybe_simd_ec {
+        assert_eq!(simd_ec.backend(), ErasureCodingBackend::Simd);
+    }
+
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let num_shards = 2usize.pow(8);
+
+    let source_shards = iter::repeat_with(|| {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        bytes
+    })
+    .take(num_shards / 2)
+    .collect::<Vec<_>>();
+    let mut parity_shards = vec![[0u8; 32]; source_shards.len()];
+
+    scalar_ec
+        .extend(source_shards.iter(), parity_shards.iter_mut())
+        .unwrap();
+
+    assert_ne!(source_shards, parity_shards);
+
+    for ec in iter::once(&scalar_ec).chain(&maybe_simd_ec) {
+        let mut backend_parity_shards = vec![[0u8; 32]; source_shards.len()];
+        ec.extend(source_shards.iter(), backend_parity_shards.iter_mut())
+            .unwrap();
+
+        assert_eq!(backend_parity_shards, parity_shards);
+
+        let mut recovered_source_shards = source_shards.clone();
+        let mut recovered_parity_shards = parity_shards.clone();
+
+        ec.recover(
+            corrupt_shards(recovered_source_shards.iter_mut(), 0..num_shards / 4),
+            corrupt_shards(
+                recovered_parity_shards.iter_mut(),
+                num_shards / 4..num_shards * 2 / 4,
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(recovered_source_shards, source_shards);
+        assert_eq!(recovered_parity_shards, parity_shards);
+    }
+}
@@ -1,7 +1,7 @@
 #![feature(type_changing_struct_update)]
 
 use ab_cli_utils::init_logger;
-use ab_networking::Config;
+use ab_networking::{Config, default_gossipsub_config};
 use futures::StreamExt;
 use futures::channel::oneshot;
 use libp2p::gossipsub::Sha256Topic;
@@ -19,6 +19,7 @@ async fn main() {
     let config_1 = Config {
         listen_on: vec!["/ip4/0.0.0.0/tcp/0".parse().unwrap()],
         allow_non_global_addresses_in_dht: true,
+        gossipsub: Some(default_gossipsub_config(false)),
         ..Config::default()
     };
     let (node_1, mut node_runner_1) = ab_networking::construct(config_1).unwrap();
@@ -53,6 +54,7 @@ async fn main() {
         listen_on: vec!["/ip4/0.0.0.0/tcp/0".parse().unwrap()],
         allow_non_global_addresses_in_dht: true,
         bootstrap_addresses,
+        gossipsub: Some(default_gossipsub_config(false)),
         ..Config::default()
     };
 
@@ -76,7 +78,7 @@ async fn main() {
     tokio::time::sleep(Duration::from_secs(1)).await;
 
     let message = subscription.next().await.unwrap();
-    println!("Got message: {}", String::from_utf8_lossy(&message));
+    println!("Got message: {}", String::from_utf8_lossy(&message.data));
 
     tokio::time::sleep(Duration::from_secs(5)).await;
 }
@@ -6,6 +6,7 @@ use crate::constructor::DummyRecordStore;
 use crate::protocols::autonat_wrapper::{
     Behaviour as AutonatWrapper, Config as AutonatWrapperConfig,
 };
+use crate::protocols::request_response::compression::CompressionConfig;
 use crate::protocols::request_response::request_response_factory::{
     Event as RequestResponseEvent, RequestHandler, RequestResponseFactoryBehaviour,
 };
@@ -45,6 +46,8 @@ pub(crate) struct BehaviorConfig {
     /// The upper bound for the number of concurrent inbound + outbound streams for
     /// request/response protocols.
     pub(crate) request_response_max_concurrent_streams: usize,
+    /// Compression of outgoing payloads of request/response protocols that support it.
+    pub(crate) request_response_compression: CompressionConfig,
     /// Connection limits for the swarm.
     pub(crate) connection_limits: ConnectionLimits,
     /// The configuration for the [`ReservedPeersBehaviour`].
@@ -94,6 +97,7 @@ impl Behavior {
             request_response: RequestResponseFactoryBehaviour::new(
                 config.request_response_protocols,
                 config.request_response_max_concurrent_streams,
+                config.request_response_compression,
             )
             //TODO: Convert to an error.
             .expect("RequestResponse protocols registration failed."),
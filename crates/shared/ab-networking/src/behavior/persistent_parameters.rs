@@ -11,21 +11,25 @@ use memmap2::{MmapMut, MmapOptions};
 use parity_scale_codec::{Compact, CompactLen, Decode, Encode};
 use parking_lot::Mutex;
 use schnellru::{ByLength, LruMap};
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::fs::OpenOptions;
 use std::io::{Read, Seek, SeekFrom};
+use std::net::IpAddr;
 use std::path::Path;
 use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
-use std::{io, mem};
+use std::{fmt, io, mem};
 use thiserror::Error;
 use tokio::time::{Sleep, sleep};
 use tracing::{debug, error, trace, warn};
 
 /// Defines optional time for address dial failure
 type FailureTime = Option<SystemTime>;
+/// Score of a known peer, increased on successful connections and decreased on dial failures
+type PeerScore = i16;
 
 /// Size of the LRU cache for peers.
 const KNOWN_PEERS_CACHE_SIZE: u32 = 100;
@@ -39,6 +43,11 @@ const REMOVE_KNOWN_PEERS_GRACE_PERIOD: Duration = Duration::from_days(1);
 const REMOVE_KNOWN_PEERS_GRACE_PERIOD_FOR_KADEMLIA: Duration = Duration::from_hours(1);
 /// Defines an expiration period for the peer marked for the removal for Kademlia DHT.
 const STALE_KNOWN_PEERS_TIMEOUT: Duration = Duration::from_days(1);
+/// Max absolute value of the peer score, such that peers can recover from (or lose) a good score
+/// reasonably quickly.
+const MAX_PEER_SCORE: PeerScore = 100;
+/// Default number of known peers to dial during bootstrapping.
+const BOOTSTRAP_KNOWN_PEERS: usize = 20;
 
 /// Defines the event triggered when the peer address is removed from the permanent storage.
 #[derive(Debug, Clone)]
@@ -56,28 +65,45 @@ struct EncodableKnownPeerAddress {
     failure_time: Option<u64>,
 }
 
+#[derive(Debug, Encode, Decode)]
+struct EncodableKnownPeer {
+    peer_id: Vec<u8>,
+    score: PeerScore,
+    /// List of multiaddresses with corresponding failure time
+    addresses: Vec<EncodableKnownPeerAddress>,
+}
+
 #[derive(Debug, Encode, Decode)]
 struct EncodableKnownPeers {
     cache_size: u32,
     timestamp: u64,
-    // Each entry is a tuple of peer ID + list of multiaddresses with corresponding failure time
-    known_peers: Vec<(Vec<u8>, Vec<EncodableKnownPeerAddress>)>,
+    known_peers: Vec<EncodableKnownPeer>,
 }
 
 impl EncodableKnownPeers {
-    fn into_cache(mut self) -> LruMap<PeerId, LruMap<Multiaddr, FailureTime>> {
+    fn into_cache(
+        mut self,
+    ) -> (
+        LruMap<PeerId, LruMap<Multiaddr, FailureTime>>,
+        LruMap<PeerId, PeerScore>,
+    ) {
         let mut peers_cache = LruMap::new(ByLength::new(self.cache_size));
+        let mut scores_cache = LruMap::new(ByLength::new(self.cache_size));
 
         // Sort peers with the oldest expiration date first
-        self.known_peers
-            .sort_by_cached_key(|(_peer_id, addresses)| {
-                addresses.iter().fold(0u64, |acc, address| {
-                    acc.max(address.failure_time.unwrap_or(u64::MAX))
-                })
-            });
+        self.known_peers.sort_by_cached_key(|known_peer| {
+            known_peer.addresses.iter().fold(0u64, |acc, address| {
+                acc.max(address.failure_time.unwrap_or(u64::MAX))
+            })
+        });
 
         // Iterate over known peers with most recent failure time (or no failire time) first
-        'peers: for (peer_id, addresses) in self.known_peers.into_iter().rev() {
+        'peers: for known_peer in self.known_peers.into_iter().rev() {
+            let EncodableKnownPeer {
+                peer_id,
+                score,
+                addresses,
+            } = known_peer;
             let mut peer_cache =
                 LruMap::<Multiaddr, FailureTime>::new(ByLength::new(ADDRESSES_CACHE_SIZE));
 
@@ -109,12 +135,17 @@ impl EncodableKnownPeers {
             }
 
             peers_cache.insert(peer_id, peer_cache);
+            scores_cache.insert(peer_id, score.clamp(-MAX_PEER_SCORE, MAX_PEER_SCORE));
         }
 
-        peers_cache
+        (peers_cache, scores_cache)
     }
 
-    fn from_cache(cache: &LruMap<PeerId, LruMap<Multiaddr, FailureTime>>, cache_size: u32) -> Self {
+    fn from_cache(
+        cache: &LruMap<PeerId, LruMap<Multiaddr, FailureTime>>,
+        scores: &LruMap<PeerId, PeerScore>,
+        cache_size: u32,
+    ) -> Self {
         let single_peer_encoded_address_size =
             KnownPeersManager::single_peer_encoded_address_size();
         Self {
@@ -125,38 +156,36 @@ impl EncodableKnownPeers {
                 .as_secs(),
             known_peers: cache
                 .iter()
-                .map(|(peer_id, addresses)| {
-                    (
-                        peer_id.to_bytes(),
-                        addresses
-                            .iter()
-                            .filter_map(|(multiaddr, failure_time)| {
-                                let multiaddr_bytes = multiaddr.to_vec();
-
-                                if multiaddr_bytes.encoded_size() > single_peer_encoded_address_size
-                                {
-                                    // Skip unexpectedly large multiaddresses
-                                    debug!(
-                                        encoded_multiaddress_size = %multiaddr_bytes.encoded_size(),
-                                        limit = %single_peer_encoded_address_size,
-                                        ?multiaddr,
-                                        "Unexpectedly large multiaddress"
-                                    );
-                                    return None;
-                                }
-
-                                Some(EncodableKnownPeerAddress {
-                                    multiaddr: multiaddr_bytes,
-                                    failure_time: failure_time.map(|failure_time| {
-                                        failure_time
-                                            .duration_since(SystemTime::UNIX_EPOCH)
-                                            .expect("Never before Unix epoch; qed")
-                                            .as_secs()
-                                    }),
-                                })
+                .map(|(peer_id, addresses)| EncodableKnownPeer {
+                    peer_id: peer_id.to_bytes(),
+                    score: scores.peek(peer_id).copied().unwrap_or_default(),
+                    addresses: addresses
+                        .iter()
+                        .filter_map(|(multiaddr, failure_time)| {
+                            let multiaddr_bytes = multiaddr.to_vec();
+
+                            if multiaddr_bytes.encoded_size() > single_peer_encoded_address_size {
+                                // Skip unexpectedly large multiaddresses
+                                debug!(
+                                    encoded_multiaddress_size = %multiaddr_bytes.encoded_size(),
+                                    limit = %single_peer_encoded_address_size,
+                                    ?multiaddr,
+                                    "Unexpectedly large multiaddress"
+                                );
+                                return None;
+                            }
+
+                            Some(EncodableKnownPeerAddress {
+                                multiaddr: multiaddr_bytes,
+                                failure_time: failure_time.map(|failure_time| {
+                                    failure_time
+                                        .duration_since(SystemTime::UNIX_EPOCH)
+                                        .expect("Never before Unix epoch; qed")
+                                        .as_secs()
+                                }),
                             })
-                            .collect(),
-                    )
+                        })
+                        .collect(),
                 })
                 .collect(),
         }
@@ -202,6 +231,13 @@ pub trait KnownPeersRegistry: Send + Sync {
     /// Returns all known peers and their addresses without P2P suffix at the end
     async fn all_known_peers(&mut self) -> Vec<(PeerId, Vec<Multiaddr>)>;
 
+    /// Returns a subset of known peers that should be dialed during bootstrapping and their
+    /// addresses without P2P suffix at the end.
+    ///
+    /// Peers with better score are preferred, but peers from different network groups are
+    /// interleaved such that a few networks can't dominate the set.
+    async fn bootstrap_known_peers(&mut self) -> Vec<(PeerId, Vec<Multiaddr>)>;
+
     /// Drive async work in the persistence provider
     async fn run(&mut self);
 
@@ -238,6 +274,10 @@ impl KnownPeersRegistry for StubNetworkingParametersManager {
         Vec::new()
     }
 
+    async fn bootstrap_known_peers(&mut self) -> Vec<(PeerId, Vec<Multiaddr>)> {
+        Vec::new()
+    }
+
     async fn run(&mut self) {
         // Never resolves
         futures::future::pending::<()>().await;
@@ -251,6 +291,126 @@ impl KnownPeersRegistry for StubNetworkingParametersManager {
     }
 }
 
+/// Hint about autonomous system number (ASN) of an IP address.
+///
+/// Used by [`KnownPeersManager`] to diversify peers dialed during bootstrapping, peers are grouped
+/// by IP prefix when ASN is not known.
+#[derive(Clone)]
+pub struct AsnHint(Arc<dyn Fn(IpAddr) -> Option<u32> + Send + Sync>);
+
+impl fmt::Debug for AsnHint {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AsnHint").finish_non_exhaustive()
+    }
+}
+
+impl AsnHint {
+    /// Create a new instance from a function that returns ASN of an IP address, if known
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(IpAddr) -> Option<u32> + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+}
+
+/// Network group of a peer, peers in the same group are likely operated by the same entity or
+/// located in the same network
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum NetworkGroup {
+    /// Autonomous system number
+    Asn(u32),
+    /// First two octets of IPv4 address (/16)
+    Ipv4([u8; 2]),
+    /// First two segments of IPv6 address (/32)
+    Ipv6([u16; 2]),
+    /// DNS name
+    Dns(String),
+    /// Address without IP or DNS component
+    Unknown,
+}
+
+impl NetworkGroup {
+    fn new(address: &Multiaddr, asn_hint: Option<&AsnHint>) -> Self {
+        for protocol in address {
+            let ip = match protocol {
+                Protocol::Ip4(ip) => IpAddr::V4(ip),
+                Protocol::Ip6(ip) => IpAddr::V6(ip),
+                Protocol::Dns(name)
+                | Protocol::Dns4(name)
+                | Protocol::Dns6(name)
+                | Protocol::Dnsaddr(name) => {
+                    return Self::Dns(name.into_owned());
+                }
+                _ => {
+                    continue;
+                }
+            };
+
+            if let Some(asn) = asn_hint.and_then(|asn_hint| (asn_hint.0)(ip)) {
+                return Self::Asn(asn);
+            }
+
+            return match ip {
+                IpAddr::V4(ip) => {
+                    let [a, b, ..] = ip.octets();
+                    Self::Ipv4([a, b])
+                }
+                IpAddr::V6(ip) => {
+                    let [a, b, ..] = ip.segments();
+                    Self::Ipv6([a, b])
+                }
+            };
+        }
+
+        Self::Unknown
+    }
+}
+
+/// Select up to `limit` peers for bootstrapping.
+///
+/// Peers with negative score are skipped. The rest are taken in rounds, one peer per network group
+/// in each round, such that peers from diverse network groups are preferred. Within a round peers
+/// with higher score go first.
+pub(super) fn select_bootstrap_peers<I>(
+    peers: I,
+    limit: usize,
+    asn_hint: Option<&AsnHint>,
+) -> Vec<(PeerId, Vec<Multiaddr>)>
+where
+    I: IntoIterator<Item = (PeerId, PeerScore, Vec<Multiaddr>)>,
+{
+    let mut peers = peers
+        .into_iter()
+        .filter(|(_peer_id, score, addresses)| *score >= 0 && !addresses.is_empty())
+        .collect::<Vec<_>>();
+    // Stable sort, such that more recently used peers go first among peers with the same score
+    peers.sort_by_key(|(_peer_id, score, _addresses)| Reverse(*score));
+
+    // Rank of the peer within its network group
+    let mut peers_per_group = HashMap::<NetworkGroup, usize>::new();
+    let mut peers = peers
+        .into_iter()
+        .map(|(peer_id, _score, addresses)| {
+            let group = NetworkGroup::new(&addresses[0], asn_hint);
+            let group_peers = peers_per_group.entry(group).or_default();
+            let rank = *group_peers;
+            *group_peers += 1;
+
+            (rank, peer_id, addresses)
+        })
+        .collect::<Vec<_>>();
+    // Stable sort again, such that higher score peers go first within each round
+    peers.sort_by_key(|(rank, _peer_id, _addresses)| *rank);
+
+    peers
+        .into_iter()
+        .take(limit)
+        .map(|(_rank, peer_id, addresses)| (peer_id, addresses))
+        .collect()
+}
+
 /// Configuration for [`KnownPeersManager`].
 #[derive(Debug, Clone)]
 pub struct KnownPeersManagerConfig {
@@ -268,6 +428,10 @@ pub struct KnownPeersManagerConfig {
     pub failed_address_kademlia_removal_interval: Duration,
     /// Amount of time after which stored known peers contents is assumed to be stale.
     pub stale_known_peers_timeout: Duration,
+    /// Max number of known peers returned by [`KnownPeersRegistry::bootstrap_known_peers()`].
+    pub bootstrap_known_peers: usize,
+    /// Optional ASN hint for diversifying bootstrap peers, IP prefixes are used otherwise.
+    pub asn_hint: Option<AsnHint>,
 }
 
 impl Default for KnownPeersManagerConfig {
@@ -280,6 +444,8 @@ impl Default for KnownPeersManagerConfig {
             failed_address_cache_removal_interval: REMOVE_KNOWN_PEERS_GRACE_PERIOD,
             failed_address_kademlia_removal_interval: REMOVE_KNOWN_PEERS_GRACE_PERIOD_FOR_KADEMLIA,
             stale_known_peers_timeout: STALE_KNOWN_PEERS_TIMEOUT,
+            bootstrap_known_peers: BOOTSTRAP_KNOWN_PEERS,
+            asn_hint: None,
         }
     }
 }
@@ -302,6 +468,8 @@ pub struct KnownPeersManager {
     cache_need_saving: bool,
     /// LRU cache for the known peers and their addresses
     known_peers: LruMap<PeerId, LruMap<Multiaddr, FailureTime>>,
+    /// LRU cache for scores of known peers
+    peer_scores: LruMap<PeerId, PeerScore>,
     /// Period between networking parameters saves.
     networking_parameters_save_delay: Pin<Box<Fuse<Sleep>>>,
     /// Slots backed by file that store known peers
@@ -321,6 +489,7 @@ impl Drop for KnownPeersManager {
                 .lock()
                 .write_to_inactive_slot(&EncodableKnownPeers::from_cache(
                     &self.known_peers,
+                    &self.peer_scores,
                     self.config.cache_size,
                 ));
         }
@@ -454,7 +623,7 @@ impl KnownPeersManager {
             (None, None)
         };
 
-        let known_peers = maybe_newest_known_addresses
+        let (known_peers, peer_scores) = maybe_newest_known_addresses
             .filter(|newest_known_addresses| {
                 let time_since_unix_epoch = SystemTime::now()
                     .duration_since(SystemTime::UNIX_EPOCH)
@@ -465,13 +634,19 @@ impl KnownPeersManager {
                 known_peers_age <= config.stale_known_peers_timeout
             })
             .map_or_else(
-                || LruMap::new(ByLength::new(config.cache_size)),
+                || {
+                    (
+                        LruMap::new(ByLength::new(config.cache_size)),
+                        LruMap::new(ByLength::new(config.cache_size)),
+                    )
+                },
                 EncodableKnownPeers::into_cache,
             );
 
         Ok(Self {
             cache_need_saving: false,
             known_peers,
+            peer_scores,
             networking_parameters_save_delay: Self::default_delay(),
             known_peers_slots,
             address_removed: Bag::default(),
@@ -508,9 +683,10 @@ impl KnownPeersManager {
     /// Size of single peer known addresses, this is an estimate and in some pathological cases peer
     /// will have to be rejected if encoding exceeds this length.
     fn single_peer_encoded_size() -> usize {
-        // Peer ID encoding + compact encoding of the length of list of addresses + (length of a
-        // single peer address entry + optional failure time) * number of entries
+        // Peer ID encoding + score + compact encoding of the length of list of addresses + (length
+        // of a single peer address entry + optional failure time) * number of entries
         PeerId::random().to_bytes().encoded_size()
+            + size_of::<PeerScore>()
             + Compact::compact_len(&(ADDRESSES_CACHE_SIZE))
             + (Self::single_peer_encoded_address_size() + Some(0u64).encoded_size())
                 * ADDRESSES_CACHE_SIZE as usize
@@ -533,6 +709,20 @@ impl KnownPeersManager {
         self.config.path.is_some()
     }
 
+    fn update_peer_score(&mut self, peer_id: PeerId, delta: PeerScore) {
+        let score = self.peer_scores.get_or_insert(peer_id, PeerScore::default);
+        if let Some(score) = score {
+            *score = score
+                .saturating_add(delta)
+                .clamp(-MAX_PEER_SCORE, MAX_PEER_SCORE);
+        }
+    }
+
+    #[cfg(all(test, not(miri)))]
+    pub(crate) fn peer_score(&self, peer_id: &PeerId) -> Option<PeerScore> {
+        self.peer_scores.peek(peer_id).copied()
+    }
+
     #[cfg(all(test, not(miri)))]
     pub(crate) fn contains_address(&self, peer_id: &PeerId, address: &Multiaddr) -> bool {
         self.known_peers
@@ -586,6 +776,10 @@ impl KnownPeersRegistry for KnownPeersManager {
                 }
             });
 
+        if self.known_peers.peek(&peer_id).is_some() {
+            self.update_peer_score(peer_id, 1);
+        }
+
         self.cache_need_saving = true;
     }
 
@@ -604,6 +798,12 @@ impl KnownPeersRegistry for KnownPeersManager {
             self.address_removed.call_simple(&event);
         }
 
+        if self.known_peers.peek(&peer_id).is_some() {
+            self.update_peer_score(peer_id, -1);
+        } else {
+            self.peer_scores.remove(&peer_id);
+        }
+
         self.cache_need_saving = true;
     }
 
@@ -611,6 +811,7 @@ impl KnownPeersRegistry for KnownPeersManager {
         trace!(%peer_id, "Remove all peer addresses from the networking parameters registry");
 
         self.known_peers.remove(&peer_id);
+        self.peer_scores.remove(&peer_id);
 
         self.cache_need_saving = true;
     }
@@ -634,6 +835,27 @@ impl KnownPeersRegistry for KnownPeersManager {
             .collect()
     }
 
+    async fn bootstrap_known_peers(&mut self) -> Vec<(PeerId, Vec<Multiaddr>)> {
+        if !self.config.enable_known_peers_source {
+            return Vec::new();
+        }
+
+        select_bootstrap_peers(
+            self.known_peers.iter().map(|(peer_id, addresses)| {
+                (
+                    *peer_id,
+                    self.peer_scores.peek(peer_id).copied().unwrap_or_default(),
+                    addresses
+                        .iter()
+                        .map(|(addr, _failure_time)| addr.clone())
+                        .collect(),
+                )
+            }),
+            self.config.bootstrap_known_peers,
+            self.config.asn_hint.as_ref(),
+        )
+    }
+
     async fn run(&mut self) {
         if !self.persistent_enabled() {
             pending::<()>().await;
@@ -645,8 +867,11 @@ impl KnownPeersRegistry for KnownPeersManager {
             if let Some(known_peers_slots) = &self.known_peers_slots
                 && self.cache_need_saving
             {
-                let known_peers =
-                    EncodableKnownPeers::from_cache(&self.known_peers, self.config.cache_size);
+                let known_peers = EncodableKnownPeers::from_cache(
+                    &self.known_peers,
+                    &self.peer_scores,
+                    self.config.cache_size,
+                );
                 let known_peers_slots = Arc::clone(known_peers_slots);
                 let write_known_peers_fut =
                     AsyncJoinOnDrop::new(tokio::task::spawn_blocking(move || {
@@ -1,9 +1,9 @@
-use super::persistent_parameters::remove_known_peer_addresses_internal;
+use super::persistent_parameters::{remove_known_peer_addresses_internal, select_bootstrap_peers};
 use crate::behavior::persistent_parameters::{append_p2p_suffix, remove_p2p_suffix};
 use crate::protocols::request_response::handlers::generic_request_handler::{
     GenericRequest, GenericRequestHandler,
 };
-use crate::{Config, KnownPeersManager, KnownPeersManagerConfig, KnownPeersRegistry};
+use crate::{AsnHint, Config, KnownPeersManager, KnownPeersManagerConfig, KnownPeersRegistry};
 use futures::channel::oneshot;
 use futures::future::pending;
 use libp2p::multiaddr::Protocol;
@@ -323,3 +323,86 @@ async fn test_known_peers_removal_address_after_specified_interval() {
     // We removed address after the configured interval.
     assert!(!known_peers.contains_address(&peer_id, &address));
 }
+
+#[tokio::test]
+async fn test_known_peers_scoring() {
+    let mut known_peers = KnownPeersManager::new(KnownPeersManagerConfig::default()).unwrap();
+    let good_peer_id = PeerId::random();
+    let good_address: Multiaddr = "/ip4/1.1.1.1/tcp/30333".parse().unwrap();
+    let bad_peer_id = PeerId::random();
+    let bad_address: Multiaddr = "/ip4/2.2.2.2/tcp/30333".parse().unwrap();
+
+    for _ in 0..3 {
+        known_peers
+            .add_known_peer(good_peer_id, vec![good_address.clone()])
+            .await;
+    }
+    known_peers
+        .add_known_peer(bad_peer_id, vec![bad_address.clone()])
+        .await;
+    for _ in 0..2 {
+        known_peers
+            .remove_known_peer_addresses(bad_peer_id, vec![bad_address.clone()])
+            .await;
+    }
+
+    assert_eq!(known_peers.peer_score(&good_peer_id), Some(3));
+    assert_eq!(known_peers.peer_score(&bad_peer_id), Some(-1));
+
+    // Peers with negative score are not dialed during bootstrapping
+    assert_eq!(
+        known_peers.bootstrap_known_peers().await,
+        vec![(good_peer_id, vec![good_address])]
+    );
+
+    known_peers.remove_all_known_peer_addresses(good_peer_id);
+    assert_eq!(known_peers.peer_score(&good_peer_id), None);
+}
+
+#[test]
+fn test_bootstrap_peers_diversity() {
+    let peers = [
+        ("/ip4/10.1.0.1/tcp/30333", 10),
+        ("/ip4/10.1.0.2/tcp/30333", 9),
+        ("/ip4/10.1.0.3/tcp/30333", 8),
+        ("/ip4/10.2.0.1/tcp/30333", 1),
+        ("/ip6/2001:db8::1/tcp/30333", 0),
+        ("/dns/bootstrap.example.com/tcp/30333", 5),
+        ("/ip4/10.3.0.1/tcp/30333", -1),
+    ]
+    .map(|(address, score)| (PeerId::random(), score, vec![address.parse().unwrap()]));
+    let peer_ids = peers
+        .each_ref()
+        .map(|(peer_id, _score, _addresses)| *peer_id);
+
+    let selected_peer_ids = |limit, asn_hint: Option<&AsnHint>| {
+        select_bootstrap_peers(peers.clone(), limit, asn_hint)
+            .into_iter()
+            .map(|(peer_id, _addresses)| peer_id)
+            .collect::<Vec<_>>()
+    };
+
+    // The best peer of each network group goes first, peer with negative score is skipped
+    assert_eq!(
+        selected_peer_ids(usize::MAX, None),
+        vec![
+            peer_ids[0],
+            peer_ids[5],
+            peer_ids[3],
+            peer_ids[4],
+            peer_ids[1],
+            peer_ids[2],
+        ]
+    );
+    assert_eq!(
+        selected_peer_ids(3, None),
+        vec![peer_ids[0], peer_ids[5], peer_ids[3]]
+    );
+
+    // All IPv4 peers are in the same autonomous system
+    let asn_hint = AsnHint::new(|ip| ip.is_ipv4().then_some(1));
+    assert_eq!(
+        selected_peer_ids(3, Some(&asn_hint)),
+        vec![peer_ids[0], peer_ids[5], peer_ids[4]]
+    );
+}
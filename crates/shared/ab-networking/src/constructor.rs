@@ -8,6 +8,7 @@ use crate::constructor::transport::build_transport;
 use crate::node::Node;
 use crate::node_runner::{NodeRunner, NodeRunnerConfig};
 use crate::protocols::autonat_wrapper::Config as AutonatWrapperConfig;
+use crate::protocols::request_response::compression::CompressionConfig;
 use crate::protocols::request_response::request_response_factory::RequestHandler;
 use crate::protocols::reserved_peers::Config as ReservedPeersConfig;
 use crate::shared::Shared;
@@ -194,6 +195,9 @@ pub struct Config {
     pub known_peers_registry: Box<dyn KnownPeersRegistry>,
     /// The configuration for the `RequestResponsesBehaviour` protocol.
     pub request_response_protocols: Vec<Box<dyn RequestHandler>>,
+    /// Trade-off between CPU usage and bandwidth for request-response protocols that support
+    /// compression.
+    pub request_response_compression: CompressionConfig,
     /// Defines set of peers with a permanent connection (and reconnection if necessary).
     pub reserved_peers: Vec<Multiaddr>,
     /// Established incoming swarm connection limit.
@@ -244,6 +248,36 @@ impl Default for Config {
     }
 }
 
+/// Default configuration of the gossip protocol.
+///
+/// Gossip protocol is disabled by default, this configuration can be used to enable it with
+/// [`Config::gossipsub`].
+///
+/// With `validate_messages` set to `true` messages received through topic subscriptions are only
+/// propagated further after validation result is reported with
+/// [`Node::report_message_validation_result()`]. It must only be enabled when every topic
+/// subscriber reports validation results, otherwise messages of the topic will never be propagated.
+pub fn default_gossipsub_config(validate_messages: bool) -> GossipsubConfig {
+    let mut builder = GossipsubConfigBuilder::default();
+    builder
+        .protocol_id_prefix(GOSSIPSUB_PROTOCOL_PREFIX)
+        // TODO: Do we want message signing?
+        .validation_mode(ValidationMode::None)
+        // To content-address message, we can take the hash of message and use it as an ID.
+        .message_id_fn(|message: &GossipsubMessage| {
+            MessageId::from(blake3::hash(&message.data).as_bytes())
+        })
+        .max_transmit_size(2 * 1024 * 1024); // 2MB
+
+    if validate_messages {
+        builder.validate_messages();
+    }
+
+    builder
+        .build()
+        .expect("Default config for gossipsub is always correct; qed")
+}
+
 impl Config {
     /// Creates a new [`Config`].
     /// Applies a subspace-specific version prefix to the `protocol_version`.
@@ -278,19 +312,7 @@ impl Config {
         let mut yamux_config = YamuxConfig::default();
         yamux_config.set_max_num_streams(YAMUX_MAX_STREAMS);
 
-        let gossipsub = ENABLE_GOSSIP_PROTOCOL.then(|| {
-            GossipsubConfigBuilder::default()
-                .protocol_id_prefix(GOSSIPSUB_PROTOCOL_PREFIX)
-                // TODO: Do we want message signing?
-                .validation_mode(ValidationMode::None)
-                // To content-address message, we can take the hash of message and use it as an ID.
-                .message_id_fn(|message: &GossipsubMessage| {
-                    MessageId::from(blake3::hash(&message.data).as_bytes())
-                })
-                .max_transmit_size(2 * 1024 * 1024) // 2MB
-                .build()
-                .expect("Default config for gossipsub is always correct; qed")
-        });
+        let gossipsub = ENABLE_GOSSIP_PROTOCOL.then(|| default_gossipsub_config(false));
 
         let protocol_version = format!("/subspace/2/{protocol_version}");
         let identify = IdentifyConfig::new(protocol_version.clone(), keypair.public());
@@ -313,6 +335,7 @@ impl Config {
             initial_random_query_interval: Duration::from_secs(1),
             known_peers_registry: StubNetworkingParametersManager.boxed(),
             request_response_protocols: Vec::new(),
+            request_response_compression: CompressionConfig::default(),
             yamux_config,
             reserved_peers: Vec::new(),
             max_established_incoming_connections: SWARM_MAX_ESTABLISHED_INCOMING_CONNECTIONS,
@@ -371,6 +394,7 @@ pub fn construct(config: Config) -> Result<(Node, NodeRunner), CreationError> {
         initial_random_query_interval,
         known_peers_registry,
         request_response_protocols,
+        request_response_compression,
         reserved_peers,
         max_established_incoming_connections,
         max_established_outgoing_connections,
@@ -427,6 +451,7 @@ pub fn construct(config: Config) -> Result<(Node, NodeRunner), CreationError> {
                 + max_established_outgoing_connections as usize;
             max_num_connections * MAX_CONCURRENT_STREAMS_PER_CONNECTION
         },
+        request_response_compression,
         connection_limits,
         reserved_peers: ReservedPeersConfig {
             reserved_peers: reserved_peers.clone(),
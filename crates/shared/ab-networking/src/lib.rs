@@ -14,16 +14,18 @@ mod shared;
 pub mod utils;
 
 pub use crate::behavior::persistent_parameters::{
-    KnownPeersManager, KnownPeersManagerConfig, KnownPeersManagerPersistenceError,
+    AsnHint, KnownPeersManager, KnownPeersManagerConfig, KnownPeersManagerPersistenceError,
     KnownPeersRegistry, PeerAddressRemovedEvent,
 };
 pub use crate::node::{
     GetClosestPeersError, Node, SendRequestError, SubscribeError, TopicSubscription, WeakNode,
 };
 pub use crate::node_runner::NodeRunner;
-pub use constructor::{Config, CreationError, KademliaMode, construct, peer_id};
+pub use constructor::{
+    Config, CreationError, KademliaMode, construct, default_gossipsub_config, peer_id,
+};
 pub use libp2p;
-pub use shared::PeerDiscovered;
+pub use shared::{GossipMessage, PeerDiscovered};
 pub use utils::PeerAddress;
 pub use utils::key_with_distance::KeyWithDistance;
 pub use utils::multihash::Multihash;
@@ -3,14 +3,13 @@ mod tests;
 
 use crate::protocols::request_response::handlers::generic_request_handler::GenericRequest;
 use crate::protocols::request_response::request_response_factory;
-use crate::shared::{Command, CreatedSubscription, PeerDiscovered, Shared};
+use crate::shared::{Command, CreatedSubscription, GossipMessage, PeerDiscovered, Shared};
 use crate::utils::HandlerFn;
 use crate::utils::multihash::Multihash;
-use bytes::Bytes;
 use event_listener_primitives::HandlerId;
 use futures::channel::{mpsc, oneshot};
 use futures::{SinkExt, Stream, StreamExt};
-use libp2p::gossipsub::{Sha256Topic, SubscriptionError};
+use libp2p::gossipsub::{MessageAcceptance, MessageId, Sha256Topic, SubscriptionError};
 use libp2p::kad::{PeerRecord, RecordKey};
 use libp2p::{Multiaddr, PeerId};
 use parity_scale_codec::Decode;
@@ -29,12 +28,12 @@ pub struct TopicSubscription {
     subscription_id: usize,
     command_sender: Option<mpsc::Sender<Command>>,
     #[pin]
-    receiver: mpsc::UnboundedReceiver<Bytes>,
+    receiver: mpsc::UnboundedReceiver<GossipMessage>,
     _permit: OwnedSemaphorePermit,
 }
 
 impl Stream for TopicSubscription {
-    type Item = Bytes;
+    type Item = GossipMessage;
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         self.project().receiver.poll_next(cx)
     }
@@ -327,6 +326,12 @@ impl Node {
     }
 
     /// Subscribe to some topic on the DSN.
+    ///
+    /// When message validation is enabled (see [`default_gossipsub_config()`]), received messages
+    /// are not propagated further until validation result is reported with
+    /// [`Self::report_message_validation_result()`].
+    ///
+    /// [`default_gossipsub_config()`]: crate::default_gossipsub_config
     pub async fn subscribe(&self, topic: Sha256Topic) -> Result<TopicSubscription, SubscribeError> {
         let permit = self.shared.rate_limiter.acquire_permit().await;
         let (result_sender, result_receiver) = oneshot::channel();
@@ -520,6 +525,27 @@ impl Node {
         Ok(result_receiver)
     }
 
+    /// Report validation result of the message received through topic subscription.
+    ///
+    /// Accepted messages are propagated to other peers, rejected messages penalize the peer from
+    /// which message was received.
+    pub async fn report_message_validation_result(
+        &self,
+        message_id: MessageId,
+        propagation_source: PeerId,
+        acceptance: MessageAcceptance,
+    ) -> Result<(), mpsc::SendError> {
+        self.shared
+            .command_sender
+            .clone()
+            .send(Command::ReportMessageValidationResult {
+                message_id,
+                propagation_source,
+                acceptance,
+            })
+            .await
+    }
+
     /// Ban peer with specified peer ID.
     pub async fn ban_peer(&self, peer_id: PeerId) -> Result<(), mpsc::SendError> {
         self.shared
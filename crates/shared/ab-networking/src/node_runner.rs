@@ -7,7 +7,7 @@ use crate::constructor::temporary_bans::TemporaryBans;
 use crate::protocols::request_response::request_response_factory::{
     Event as RequestResponseEvent, IfDisconnected,
 };
-use crate::shared::{Command, CreatedSubscription, PeerDiscovered, Shared};
+use crate::shared::{Command, CreatedSubscription, GossipMessage, PeerDiscovered, Shared};
 use crate::utils::{SubspaceMetrics, is_global_address_or_dns, strip_peer_id};
 use async_lock::Mutex as AsyncMutex;
 use bytes::Bytes;
@@ -98,7 +98,8 @@ pub struct NodeRunner {
     next_subscription_id: usize,
     /// Topic subscription senders for logical subscriptions (multiple logical subscriptions can be
     /// present for the same physical subscription).
-    topic_subscription_senders: HashMap<TopicHash, IntMap<usize, mpsc::UnboundedSender<Bytes>>>,
+    topic_subscription_senders:
+        HashMap<TopicHash, IntMap<usize, mpsc::UnboundedSender<GossipMessage>>>,
     random_query_timeout: Pin<Box<Fuse<Sleep>>>,
     /// Defines an interval between periodical tasks.
     periodical_tasks_interval: Pin<Box<Fuse<Sleep>>>,
@@ -292,7 +293,7 @@ impl NodeRunner {
 
         if !known_peers.is_empty() {
             for (peer_id, addresses) in known_peers {
-                for address in addresses.clone() {
+                for address in addresses {
                     let address = match address.with_p2p(peer_id) {
                         Ok(address) => address,
                         Err(address) => {
@@ -305,7 +306,15 @@ impl NodeRunner {
                         .kademlia
                         .add_address(&peer_id, address);
                 }
+            }
 
+            // Only dial a diverse subset of known peers, the rest will be discovered through them
+            let bootstrap_known_peers = self.known_peers_registry.bootstrap_known_peers().await;
+            debug!(
+                num_peers = %bootstrap_known_peers.len(),
+                "Dialing known peers during bootstrapping"
+            );
+            for (peer_id, addresses) in bootstrap_known_peers {
                 if let Err(error) = self
                     .swarm
                     .dial(DialOpts::peer_id(peer_id).addresses(addresses).build())
@@ -1154,14 +1163,22 @@ impl NodeRunner {
     }
 
     fn handle_gossipsub_event(&mut self, event: GossipsubEvent) {
-        if let GossipsubEvent::Message { message, .. } = event
+        if let GossipsubEvent::Message {
+            propagation_source,
+            message_id,
+            message,
+        } = event
             && let Some(senders) = self.topic_subscription_senders.get(&message.topic)
         {
-            let bytes = Bytes::from(message.data);
+            let gossip_message = GossipMessage {
+                message_id,
+                propagation_source,
+                data: Bytes::from(message.data),
+            };
 
             for sender in senders.values() {
                 // Doesn't matter if receiver is still listening for messages or not.
-                let _: Result<(), _> = sender.unbounded_send(bytes.clone());
+                let _: Result<(), _> = sender.unbounded_send(gossip_message.clone());
             }
         }
     }
@@ -1400,6 +1417,19 @@ impl NodeRunner {
                         result_sender.send(gossipsub.publish(topic, message).map(|_message_id| ()));
                 }
             }
+            Command::ReportMessageValidationResult {
+                message_id,
+                propagation_source,
+                acceptance,
+            } => {
+                if let Some(gossipsub) = self.swarm.behaviour_mut().gossipsub.as_mut() {
+                    gossipsub.report_message_validation_result(
+                        &message_id,
+                        &propagation_source,
+                        acceptance,
+                    );
+                }
+            }
             Command::GetClosestPeers {
                 key,
                 result_sender,
@@ -1,4 +1,5 @@
 //! Request-response protocol
 
+pub mod compression;
 pub mod handlers;
 pub mod request_response_factory;
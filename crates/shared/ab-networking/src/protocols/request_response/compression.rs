@@ -0,0 +1,169 @@
+//! Optional compression of request-response protocol payloads.
+//!
+//! Protocols with [`ProtocolCompression`] are advertised under two names: the original one and a
+//! compressed variant with `/zstd` suffix (or `/zstd-<dictionary id>` when a dictionary is used).
+//! The compressed variant is preferred during protocol negotiation, peers that don't support it
+//! (or use a different dictionary) fall back to the original uncompressed protocol.
+//!
+//! Every payload of the compressed protocol variant starts with a single byte that indicates
+//! whether the rest of the payload is compressed, such that small and incompressible payloads can
+//! be sent as is.
+
+#[cfg(test)]
+mod tests;
+
+use libp2p::StreamProtocol;
+use std::io;
+use std::io::Read;
+
+/// Payload is not compressed
+const UNCOMPRESSED: u8 = 0;
+/// Payload is compressed with zstd
+const ZSTD: u8 = 1;
+
+/// Compression support of a request-response protocol.
+///
+/// Unlike [`CompressionConfig`], this is a property of the protocol itself and must be the same
+/// for both peers in order for compression to be used.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ProtocolCompression {
+    /// Zstd dictionary, improves compression ratio of small payloads with a lot of common
+    /// structure.
+    ///
+    /// Dictionary is identified by its hash in the protocol name, so only peers with the same
+    /// dictionary use compression with each other.
+    pub dictionary: Option<&'static [u8]>,
+}
+
+/// Trade-off between CPU usage and bandwidth for compression of outgoing payloads.
+///
+/// This is a local configuration that doesn't need to match other peers, incoming payloads are
+/// decompressed regardless.
+#[derive(Debug, Copy, Clone)]
+pub struct CompressionConfig {
+    /// Zstd compression level, higher levels result in better compression ratio at the cost of
+    /// higher CPU usage.
+    ///
+    /// The default is 3.
+    pub level: i32,
+    /// Payloads smaller than this size in bytes are sent uncompressed, `usize::MAX` disables
+    /// compression of outgoing payloads.
+    ///
+    /// The default is 1 KiB.
+    pub min_size: usize,
+}
+
+impl Default for CompressionConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            level: 3,
+            min_size: 1024,
+        }
+    }
+}
+
+/// Compression of payloads of a compressed protocol variant
+#[derive(Debug, Clone)]
+pub(crate) struct PayloadCompression {
+    protocol: StreamProtocol,
+    dictionary: &'static [u8],
+    config: CompressionConfig,
+}
+
+impl PayloadCompression {
+    pub(crate) fn new(
+        protocol_name: &'static str,
+        protocol_compression: ProtocolCompression,
+        config: CompressionConfig,
+    ) -> Self {
+        let protocol = match protocol_compression.dictionary {
+            Some(dictionary) => {
+                let dictionary_hash = blake3::hash(dictionary);
+                format!(
+                    "{protocol_name}/zstd-{}",
+                    hex::encode(&dictionary_hash.as_bytes()[..8])
+                )
+            }
+            None => format!("{protocol_name}/zstd"),
+        };
+
+        Self {
+            protocol: StreamProtocol::try_from_owned(protocol)
+                .expect("Original protocol name is valid, suffix keeps it valid; qed"),
+            dictionary: protocol_compression.dictionary.unwrap_or_default(),
+            config,
+        }
+    }
+
+    /// Name of the compressed protocol variant
+    pub(crate) fn protocol(&self) -> &StreamProtocol {
+        &self.protocol
+    }
+
+    /// Whether negotiated `protocol` is the compressed protocol variant
+    pub(crate) fn is_compressed(&self, protocol: &StreamProtocol) -> bool {
+        protocol == &self.protocol
+    }
+
+    /// Compress payload if it is large enough and compression actually reduces its size
+    pub(crate) fn compress(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        if payload.len() >= self.config.min_size {
+            let compressed =
+                zstd::bulk::Compressor::with_dictionary(self.config.level, self.dictionary)?
+                    .compress(payload)?;
+
+            if compressed.len() < payload.len() {
+                let mut output = Vec::with_capacity(1 + compressed.len());
+                output.push(ZSTD);
+                output.extend_from_slice(&compressed);
+                return Ok(output);
+            }
+        }
+
+        let mut output = Vec::with_capacity(1 + payload.len());
+        output.push(UNCOMPRESSED);
+        output.extend_from_slice(payload);
+        Ok(output)
+    }
+
+    /// Decompress payload, returns an error if decompressed payload exceeds `max_size`
+    pub(crate) fn decompress(&self, mut payload: Vec<u8>, max_size: u64) -> io::Result<Vec<u8>> {
+        let Some(&compression) = payload.first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Compressed protocol payload is empty",
+            ));
+        };
+
+        let output = match compression {
+            UNCOMPRESSED => {
+                payload.remove(0);
+                payload
+            }
+            ZSTD => {
+                let decoder =
+                    zstd::stream::read::Decoder::with_dictionary(&payload[1..], self.dictionary)?;
+                let mut output = Vec::new();
+                // One more byte to detect payloads that exceed the limit
+                decoder.take(max_size + 1).read_to_end(&mut output)?;
+                output
+            }
+            compression => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown payload compression {compression}"),
+                ));
+            }
+        };
+
+        if output.len() as u64 > max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Decompressed payload size exceeds limit: {max_size}"),
+            ));
+        }
+
+        Ok(output)
+    }
+}
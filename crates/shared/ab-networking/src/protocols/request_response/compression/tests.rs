@@ -0,0 +1,87 @@
+use crate::protocols::request_response::compression::{
+    CompressionConfig, PayloadCompression, ProtocolCompression,
+};
+
+const PROTOCOL_NAME: &str = "/test/compression/1";
+const DICTIONARY: &[u8] = b"abundance block body piece segment header";
+
+#[test]
+fn protocol_names() {
+    let without_dictionary = PayloadCompression::new(
+        PROTOCOL_NAME,
+        ProtocolCompression::default(),
+        CompressionConfig::default(),
+    );
+    assert_eq!(
+        without_dictionary.protocol().as_ref(),
+        "/test/compression/1/zstd"
+    );
+
+    let with_dictionary = PayloadCompression::new(
+        PROTOCOL_NAME,
+        ProtocolCompression {
+            dictionary: Some(DICTIONARY),
+        },
+        CompressionConfig::default(),
+    );
+    assert!(
+        with_dictionary
+            .protocol()
+            .as_ref()
+            .starts_with("/test/compression/1/zstd-")
+    );
+    assert!(!with_dictionary.is_compressed(without_dictionary.protocol()));
+    assert!(with_dictionary.is_compressed(with_dictionary.protocol()));
+}
+
+#[test]
+fn round_trip() {
+    for dictionary in [None, Some(DICTIONARY)] {
+        let compression = PayloadCompression::new(
+            PROTOCOL_NAME,
+            ProtocolCompression { dictionary },
+            CompressionConfig::default(),
+        );
+
+        // Small payload is sent as is
+        let payload = b"small".to_vec();
+        let compressed = compression.compress(&payload).unwrap();
+        assert_eq!(compressed.len(), payload.len() + 1);
+        assert_eq!(compression.decompress(compressed, 1024).unwrap(), payload);
+
+        // Large compressible payload is compressed
+        let payload = DICTIONARY.repeat(1000);
+        let compressed = compression.compress(&payload).unwrap();
+        assert!(compressed.len() < payload.len());
+        assert_eq!(
+            compression
+                .decompress(compressed.clone(), payload.len() as u64)
+                .unwrap(),
+            payload
+        );
+
+        // Decompressed size is limited
+        assert!(
+            compression
+                .decompress(compressed, payload.len() as u64 - 1)
+                .is_err()
+        );
+    }
+}
+
+#[test]
+fn invalid_payloads() {
+    let compression = PayloadCompression::new(
+        PROTOCOL_NAME,
+        ProtocolCompression::default(),
+        CompressionConfig::default(),
+    );
+
+    assert!(compression.decompress(Vec::new(), 1024).is_err());
+    assert!(
+        compression
+            .decompress(vec![u8::MAX, 1, 2, 3], 1024)
+            .is_err()
+    );
+    assert!(compression.decompress(vec![1, 2, 3], 1024).is_err());
+}
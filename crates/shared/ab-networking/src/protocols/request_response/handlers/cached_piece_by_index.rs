@@ -5,6 +5,7 @@
 #[cfg(test)]
 mod tests;
 
+use crate::protocols::request_response::compression::ProtocolCompression;
 use crate::protocols::request_response::handlers::generic_request_handler::{
     GenericRequest, GenericRequestHandler,
 };
@@ -33,6 +34,7 @@ pub struct CachedPieceByIndexRequest {
 impl GenericRequest for CachedPieceByIndexRequest {
     const PROTOCOL_NAME: &'static str = "/subspace/cached-piece-by-index/0.1.0";
     const LOG_TARGET: &'static str = "cached-piece-by-index-request-response-handler";
+    const COMPRESSION: Option<ProtocolCompression> = Some(ProtocolCompression { dictionary: None });
     type Response = CachedPieceByIndexResponse;
 }
 
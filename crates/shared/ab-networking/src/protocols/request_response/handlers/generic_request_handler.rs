@@ -1,21 +1,28 @@
 //! Generic request-response handler, typically is used with a type implementing [`GenericRequest`]
 //! to significantly reduce boilerplate when implementing [`RequestHandler`].
 
+use crate::protocols::request_response::compression::ProtocolCompression;
+use crate::protocols::request_response::handlers::request_limits::{PeerFairQueue, RequestLimits};
 use crate::protocols::request_response::request_response_factory::{
     IncomingRequest, OutgoingResponse, ProtocolConfig, RequestHandler,
 };
 use async_trait::async_trait;
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use futures::prelude::*;
 use libp2p::PeerId;
 use parity_scale_codec::{Decode, Encode};
 use std::fmt;
+use std::num::NonZeroUsize;
 use std::pin::Pin;
 use std::sync::Arc;
 use tracing::{debug, trace};
 
 /// Could be changed after the production feedback.
 const REQUESTS_BUFFER_SIZE: usize = 50;
+/// Default max number of queued requests per peer when no explicit limits are provided, allows any
+/// peer to use the whole buffer
+const DEFAULT_MAX_QUEUED_REQUESTS_PER_PEER: NonZeroUsize =
+    NonZeroUsize::new(REQUESTS_BUFFER_SIZE).expect("Not zero; qed");
 
 /// Generic request with associated response
 pub trait GenericRequest: Encode + Decode + Send + Sync + 'static {
@@ -23,6 +30,10 @@ pub trait GenericRequest: Encode + Decode + Send + Sync + 'static {
     const PROTOCOL_NAME: &'static str;
     /// Specifies log-parameters for tracing.
     const LOG_TARGET: &'static str;
+    /// Compression support, worth enabling for protocols with large compressible payloads.
+    ///
+    /// Not supported by default.
+    const COMPRESSION: Option<ProtocolCompression> = None;
     /// Response type that corresponds to this request
     type Response: Encode + Decode + Send + Sync + 'static;
 }
@@ -46,6 +57,7 @@ where
     request_receiver: mpsc::Receiver<IncomingRequest>,
     request_handler: RequestHandlerFn<Request>,
     protocol_config: ProtocolConfig,
+    limits: RequestLimits,
 }
 
 impl<Request> fmt::Debug for GenericRequestHandler<Request>
@@ -66,6 +78,26 @@ where
 {
     /// Creates new [`GenericRequestHandler`] by given handler.
     pub fn create<RH, Fut>(request_handler: RH) -> Box<dyn RequestHandler>
+    where
+        RH: (Fn(PeerId, Request) -> Fut) + Send + Sync + 'static,
+        Fut: Future<Output = Option<Request::Response>> + Send + 'static,
+    {
+        Self::create_with_limits(
+            request_handler,
+            RequestLimits {
+                max_queued_requests_per_peer: DEFAULT_MAX_QUEUED_REQUESTS_PER_PEER,
+                rate_limiter: None,
+            },
+        )
+    }
+
+    /// Creates new [`GenericRequestHandler`] by given handler with limits for incoming requests.
+    ///
+    /// Requests from different peers are handled in round-robin order.
+    pub fn create_with_limits<RH, Fut>(
+        request_handler: RH,
+        limits: RequestLimits,
+    ) -> Box<dyn RequestHandler>
     where
         RH: (Fn(PeerId, Request) -> Fut) + Send + Sync + 'static,
         Fut: Future<Output = Option<Request::Response>> + Send + 'static,
@@ -74,6 +106,7 @@ where
 
         let mut protocol_config = ProtocolConfig::new(Request::PROTOCOL_NAME);
         protocol_config.inbound_queue = Some(request_sender);
+        protocol_config.compression = Request::COMPRESSION;
 
         Box::new(Self {
             request_receiver,
@@ -81,6 +114,7 @@ where
                 Box::pin(request_handler(peer_id, request))
             }),
             protocol_config,
+            limits,
         })
     }
 
@@ -97,6 +131,76 @@ where
 
         Ok(response.ok_or(RequestHandlerError::NoResponse)?.encode())
     }
+
+    async fn handle_incoming_request(&self, request: IncomingRequest) {
+        let IncomingRequest {
+            peer,
+            payload,
+            pending_response,
+        } = request;
+
+        match self.handle_request(peer, payload).await {
+            Ok(response_data) => {
+                let response = OutgoingResponse {
+                    result: Ok(response_data),
+                    sent_feedback: None,
+                };
+
+                if pending_response.send(response).is_ok() {
+                    trace!(target = Request::LOG_TARGET, %peer, "Handled request");
+                } else {
+                    debug!(
+                        target = Request::LOG_TARGET,
+                        protocol = Request::PROTOCOL_NAME,
+                        %peer,
+                        "Failed to handle request: {}",
+                        RequestHandlerError::SendResponse
+                    );
+                }
+            }
+            Err(e) => {
+                debug!(
+                    target = Request::LOG_TARGET,
+                    protocol = Request::PROTOCOL_NAME,
+                    %e,
+                    "Failed to handle request.",
+                );
+
+                Self::send_error_response(peer, pending_response);
+            }
+        }
+    }
+
+    fn send_error_response(peer: PeerId, pending_response: oneshot::Sender<OutgoingResponse>) {
+        let response = OutgoingResponse {
+            result: Err(()),
+            sent_feedback: None,
+        };
+
+        if pending_response.send(response).is_err() {
+            debug!(
+                target = Request::LOG_TARGET,
+                protocol = Request::PROTOCOL_NAME,
+                %peer,
+                "Failed to handle request: {}", RequestHandlerError::SendResponse
+            );
+        }
+    }
+
+    /// Add request to the queue, rejects request if peer already has too many requests queued
+    fn enqueue(queue: &mut PeerFairQueue<IncomingRequest>, request: IncomingRequest) {
+        if let Err(request) = queue.push(request.peer, request) {
+            debug!(
+                target = Request::LOG_TARGET,
+                protocol = Request::PROTOCOL_NAME,
+                peer = %request.peer,
+                "Rejecting request: {}",
+                RequestHandlerError::TooManyRequests
+            );
+
+            Self::send_error_response(request.peer, request.pending_response);
+        }
+    }
 }
 
 #[async_trait]
@@ -106,55 +210,32 @@ where
 {
     /// Run [`RequestHandler`].
     async fn run(&mut self) {
-        while let Some(request) = self.request_receiver.next().await {
-            let IncomingRequest {
-                peer,
-                payload,
-                pending_response,
-            } = request;
-
-            match self.handle_request(peer, payload).await {
-                Ok(response_data) => {
-                    let response = OutgoingResponse {
-                        result: Ok(response_data),
-                        sent_feedback: None,
-                    };
-
-                    if pending_response.send(response).is_ok() {
-                        trace!(target = Request::LOG_TARGET, %peer, "Handled request");
-                    } else {
-                        debug!(
-                            target = Request::LOG_TARGET,
-                            protocol = Request::PROTOCOL_NAME,
-                            %peer,
-                            "Failed to handle request: {}",
-                            RequestHandlerError::SendResponse
-                        );
-                    }
-                }
-                Err(e) => {
-                    debug!(
-                        target = Request::LOG_TARGET,
-                        protocol = Request::PROTOCOL_NAME,
-                        %e,
-                        "Failed to handle request.",
-                    );
+        let mut queue = PeerFairQueue::new(self.limits.max_queued_requests_per_peer);
 
-                    let response = OutgoingResponse {
-                        result: Err(()),
-                        sent_feedback: None,
-                    };
-
-                    if pending_response.send(response).is_err() {
-                        debug!(
-                            target = Request::LOG_TARGET,
-                            protocol = Request::PROTOCOL_NAME,
-                            %peer,
-                            "Failed to handle request: {}", RequestHandlerError::SendResponse
-                        );
-                    }
-                }
+        loop {
+            // Move all requests that are already received into the queue, such that peers are
+            // served fairly
+            while let Ok(maybe_request) = self.request_receiver.try_next() {
+                let Some(request) = maybe_request else {
+                    // Channel is closed
+                    return;
+                };
+                Self::enqueue(&mut queue, request);
             }
+
+            let Some((_peer, request)) = queue.pop() else {
+                let Some(request) = self.request_receiver.next().await else {
+                    return;
+                };
+                Self::enqueue(&mut queue, request);
+                continue;
+            };
+
+            if let Some(rate_limiter) = &self.limits.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            self.handle_incoming_request(request).await;
         }
     }
 
@@ -171,11 +252,13 @@ where
 
         let mut protocol_config = ProtocolConfig::new(Request::PROTOCOL_NAME);
         protocol_config.inbound_queue = Some(request_sender);
+        protocol_config.compression = Request::COMPRESSION;
 
         Box::new(Self {
             request_receiver,
             request_handler: Arc::clone(&self.request_handler),
             protocol_config,
+            limits: self.limits.clone(),
         })
     }
 }
@@ -190,4 +273,7 @@ enum RequestHandlerError {
 
     #[error("No response.")]
     NoResponse,
+
+    #[error("Too many requests from peer.")]
+    TooManyRequests,
 }
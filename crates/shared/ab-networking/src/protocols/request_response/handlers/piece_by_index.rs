@@ -2,6 +2,7 @@
 //!
 //! Request handler can be created with [`PieceByIndexRequestHandler`].
 
+use crate::protocols::request_response::compression::ProtocolCompression;
 use crate::protocols::request_response::handlers::generic_request_handler::{
     GenericRequest, GenericRequestHandler,
 };
@@ -23,6 +24,7 @@ pub struct PieceByIndexRequest {
 impl GenericRequest for PieceByIndexRequest {
     const PROTOCOL_NAME: &'static str = "/subspace/piece-by-index/0.1.0";
     const LOG_TARGET: &'static str = "piece-by-index-request-response-handler";
+    const COMPRESSION: Option<ProtocolCompression> = Some(ProtocolCompression { dictionary: None });
     type Response = PieceByIndexResponse;
 }
 
@@ -0,0 +1,136 @@
+//! Limits for incoming requests: per-peer fair queuing and global rate limiting.
+//!
+//! Rate limiter is shared between request handlers that opt into it (typically those serving
+//! pieces), handlers of consensus-critical protocols should not use it, such that they are never
+//! starved by a large number of piece requests.
+
+#[cfg(test)]
+mod tests;
+
+use libp2p::PeerId;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Limits for incoming requests handled by
+/// [`GenericRequestHandler`](super::generic_request_handler::GenericRequestHandler)
+#[derive(Debug, Clone)]
+pub struct RequestLimits {
+    /// Max number of requests from a single peer waiting to be handled, requests beyond this limit
+    /// are rejected
+    pub max_queued_requests_per_peer: NonZeroUsize,
+    /// Global rate limiter, can be shared between multiple request handlers
+    pub rate_limiter: Option<RequestRateLimiter>,
+}
+
+#[derive(Debug)]
+struct RateLimiterState {
+    /// Time at which the next request would be handled if requests arrived exactly at the
+    /// configured rate
+    theoretical_arrival_time: Instant,
+}
+
+/// Global rate limiter for incoming requests.
+///
+/// Allows up to `requests_per_second` requests on average with bursts of up to `burst` requests.
+/// Cloned instances share the same limit.
+#[derive(Debug, Clone)]
+pub struct RequestRateLimiter {
+    interval: Duration,
+    burst_tolerance: Duration,
+    state: Arc<Mutex<RateLimiterState>>,
+}
+
+impl RequestRateLimiter {
+    /// Create a new instance
+    pub fn new(requests_per_second: NonZeroU32, burst: NonZeroU32) -> Self {
+        let interval = Duration::from_secs(1) / requests_per_second.get();
+
+        Self {
+            interval,
+            burst_tolerance: interval * (burst.get() - 1),
+            state: Arc::new(Mutex::new(RateLimiterState {
+                theoretical_arrival_time: Instant::now(),
+            })),
+        }
+    }
+
+    /// Wait until the next request is allowed to be handled
+    pub async fn acquire(&self) {
+        let delay = self.reserve(Instant::now());
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Reserve a slot for a request at `now`, returns how long to wait before handling it
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut state = self.state.lock();
+
+        let theoretical_arrival_time = state.theoretical_arrival_time.max(now);
+        state.theoretical_arrival_time = theoretical_arrival_time + self.interval;
+
+        theoretical_arrival_time
+            .saturating_duration_since(now)
+            .saturating_sub(self.burst_tolerance)
+    }
+}
+
+/// Queue of requests that serves peers in round-robin order, such that a single peer can't
+/// monopolize request handler
+#[derive(Debug)]
+pub(crate) struct PeerFairQueue<T> {
+    max_queued_per_peer: NonZeroUsize,
+    queues: HashMap<PeerId, VecDeque<T>>,
+    /// Peers with non-empty queues in the order they will be served
+    order: VecDeque<PeerId>,
+}
+
+impl<T> PeerFairQueue<T> {
+    pub(crate) fn new(max_queued_per_peer: NonZeroUsize) -> Self {
+        Self {
+            max_queued_per_peer,
+            queues: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Add an item to the queue of the peer, returns the item back if peer's queue is full
+    pub(crate) fn push(&mut self, peer: PeerId, item: T) -> Result<(), T> {
+        let queue = self.queues.entry(peer).or_default();
+
+        if queue.len() >= self.max_queued_per_peer.get() {
+            return Err(item);
+        }
+
+        if queue.is_empty() {
+            self.order.push_back(peer);
+        }
+        queue.push_back(item);
+
+        Ok(())
+    }
+
+    /// Take the next item from the peer whose turn it is
+    pub(crate) fn pop(&mut self) -> Option<(PeerId, T)> {
+        let peer = self.order.pop_front()?;
+        let queue = self
+            .queues
+            .get_mut(&peer)
+            .expect("Peers in order always have non-empty queues; qed");
+        let item = queue
+            .pop_front()
+            .expect("Peers in order always have non-empty queues; qed");
+
+        if queue.is_empty() {
+            self.queues.remove(&peer);
+        } else {
+            self.order.push_back(peer);
+        }
+
+        Some((peer, item))
+    }
+}
@@ -0,0 +1,47 @@
+use crate::protocols::request_response::handlers::request_limits::{
+    PeerFairQueue, RequestRateLimiter,
+};
+use libp2p::PeerId;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::time::{Duration, Instant};
+
+#[test]
+fn peer_fair_queue() {
+    let peer_a = PeerId::random();
+    let peer_b = PeerId::random();
+    let mut queue = PeerFairQueue::new(NonZeroUsize::new(2).unwrap());
+
+    assert!(queue.push(peer_a, 1).is_ok());
+    assert!(queue.push(peer_a, 2).is_ok());
+    // Over the per-peer limit
+    assert_eq!(queue.push(peer_a, 3), Err(3));
+    assert!(queue.push(peer_b, 4).is_ok());
+
+    // Peers are served in round-robin order
+    assert_eq!(queue.pop(), Some((peer_a, 1)));
+    assert_eq!(queue.pop(), Some((peer_b, 4)));
+    assert_eq!(queue.pop(), Some((peer_a, 2)));
+    assert_eq!(queue.pop(), None);
+
+    // Space is freed after items were taken from the queue
+    assert!(queue.push(peer_a, 5).is_ok());
+    assert_eq!(queue.pop(), Some((peer_a, 5)));
+}
+
+#[test]
+fn rate_limiter() {
+    let rate_limiter =
+        RequestRateLimiter::new(NonZeroU32::new(10).unwrap(), NonZeroU32::new(2).unwrap());
+    let now = Instant::now();
+
+    // Burst is allowed immediately
+    assert_eq!(rate_limiter.reserve(now), Duration::ZERO);
+    assert_eq!(rate_limiter.reserve(now), Duration::ZERO);
+    // Then requests are spread according to the rate
+    assert_eq!(rate_limiter.reserve(now), Duration::from_millis(100));
+    assert_eq!(rate_limiter.reserve(now), Duration::from_millis(200));
+
+    // Limit recovers over time
+    let later = now + Duration::from_secs(1);
+    assert_eq!(rate_limiter.reserve(later), Duration::ZERO);
+}
@@ -4,6 +4,7 @@
 //! `RequestResponsesBehaviour` with generic [`GenericRequestHandler`].
 
 use super::generic_request_handler::{GenericRequest, GenericRequestHandler};
+use crate::protocols::request_response::compression::ProtocolCompression;
 use ab_core_primitives::segments::{SuperSegmentHeader, SuperSegmentIndex};
 use parity_scale_codec::{Decode, Encode};
 use std::sync::Arc;
@@ -30,6 +31,7 @@ pub enum SuperSegmentHeaderRequest {
 impl GenericRequest for SuperSegmentHeaderRequest {
     const PROTOCOL_NAME: &'static str = "/subspace/super-segment-headers-by-indexes/0.1.0";
     const LOG_TARGET: &'static str = "super-segment-headers-by-indexes-request-response-handler";
+    const COMPRESSION: Option<ProtocolCompression> = Some(ProtocolCompression { dictionary: None });
     type Response = SuperSegmentHeaderResponse;
 }
 
@@ -32,11 +32,17 @@
 //! - If provided, a [requests processing](ProtocolConfig::inbound_queue) channel is used to handle
 //!   incoming requests.
 //!
+//! - If [compression](ProtocolConfig::compression) is supported, a compressed protocol variant is
+//!   preferred during negotiation, see [`compression`](super::compression) module for details.
+//!
 //! Original file commit: <https://github.com/paritytech/substrate/commit/c2fc4b3ca0d7a15cc3f9cb1e5f441d99ec8d6e0b>
 
 #[cfg(all(test, not(miri)))]
 mod tests;
 
+use crate::protocols::request_response::compression::{
+    CompressionConfig, PayloadCompression, ProtocolCompression,
+};
 use async_trait::async_trait;
 use futures::channel::{mpsc, oneshot};
 use futures::prelude::*;
@@ -133,6 +139,12 @@ pub struct ProtocolConfig {
     /// advertise support for this protocol, but any incoming request will lead to an error being
     /// sent back.
     pub inbound_queue: Option<mpsc::Sender<IncomingRequest>>,
+
+    /// Compression support of the protocol.
+    ///
+    /// If `Some`, a compressed protocol variant is advertised in addition to the original one and
+    /// preferred during negotiation.
+    pub compression: Option<ProtocolCompression>,
 }
 
 impl ProtocolConfig {
@@ -144,6 +156,7 @@ impl ProtocolConfig {
             max_response_size: 16 * 1024 * 1024,
             request_timeout: Duration::from_secs(20),
             inbound_queue: None,
+            compression: None,
         }
     }
 }
@@ -331,7 +344,13 @@ struct RequestProcessingOutcome {
 impl RequestResponseFactoryBehaviour {
     /// Creates a new behaviour. Must be passed a list of supported protocols. Returns an error if
     /// the same protocol is passed twice.
-    pub fn new<List>(list: List, max_concurrent_streams: usize) -> Result<Self, RegisterError>
+    ///
+    /// `compression_config` applies to outgoing payloads of protocols that support compression.
+    pub fn new<List>(
+        list: List,
+        max_concurrent_streams: usize,
+        compression_config: CompressionConfig,
+    ) -> Result<Self, RegisterError>
     where
         List: IntoIterator<Item = Box<dyn RequestHandler>>,
     {
@@ -346,12 +365,23 @@ impl RequestResponseFactoryBehaviour {
                 ProtocolSupport::Outbound
             };
 
+            let compression = config.compression.map(|protocol_compression| {
+                PayloadCompression::new(config.name, protocol_compression, compression_config)
+            });
+            // Compressed protocol variant goes first, such that it is preferred during negotiation
+            let stream_protocols = compression
+                .as_ref()
+                .map(|compression| compression.protocol().clone())
+                .into_iter()
+                .chain(iter::once(StreamProtocol::new(config.name)));
+
             let rq_rp = RequestResponse::with_codec(
                 GenericCodec {
                     max_request_size: config.max_request_size,
                     max_response_size: config.max_response_size,
+                    compression,
                 },
-                iter::once(StreamProtocol::new(config.name)).zip(iter::repeat(protocol_support)),
+                stream_protocols.zip(iter::repeat(protocol_support)),
                 RequestResponseConfig::default()
                     .with_request_timeout(config.request_timeout)
                     .with_max_concurrent_streams(max_concurrent_streams),
@@ -953,6 +983,26 @@ pub enum ResponseFailure {
 pub struct GenericCodec {
     max_request_size: u64,
     max_response_size: u64,
+    compression: Option<PayloadCompression>,
+}
+
+impl GenericCodec {
+    /// Compression of payloads if compressed protocol variant was negotiated
+    fn negotiated_compression(&self, protocol: &StreamProtocol) -> Option<&PayloadCompression> {
+        self.compression
+            .as_ref()
+            .filter(|compression| compression.is_compressed(protocol))
+    }
+
+    /// Max size of the payload on the wire, compressed payloads have an extra byte that indicates
+    /// whether the rest is compressed
+    fn max_wire_size(&self, protocol: &StreamProtocol, max_size: u64) -> u64 {
+        if self.negotiated_compression(protocol).is_some() {
+            max_size + 1
+        } else {
+            max_size
+        }
+    }
 }
 
 impl RequestResponseCodec for GenericCodec {
@@ -962,7 +1012,7 @@ impl RequestResponseCodec for GenericCodec {
 
     async fn read_request<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         mut io: &mut T,
     ) -> io::Result<Self::Request>
     where
@@ -972,25 +1022,27 @@ impl RequestResponseCodec for GenericCodec {
         let length = unsigned_varint::aio::read_usize(&mut io)
             .await
             .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
-        if length > usize::try_from(self.max_request_size).unwrap_or(usize::MAX) {
+        let max_wire_size = self.max_wire_size(protocol, self.max_request_size);
+        if length > usize::try_from(max_wire_size).unwrap_or(usize::MAX) {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
-                format!(
-                    "Request size exceeds limit: {} > {}",
-                    length, self.max_request_size
-                ),
+                format!("Request size exceeds limit: {length} > {max_wire_size}"),
             ));
         }
 
         // Read the payload.
         let mut buffer = vec![0; length];
         io.read_exact(&mut buffer).await?;
-        Ok(buffer)
+
+        match self.negotiated_compression(protocol) {
+            Some(compression) => compression.decompress(buffer, self.max_request_size),
+            None => Ok(buffer),
+        }
     }
 
     async fn read_response<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         mut io: &mut T,
     ) -> io::Result<Self::Response>
     where
@@ -1012,31 +1064,39 @@ impl RequestResponseCodec for GenericCodec {
             Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidInput, err)),
         };
 
-        if length > usize::try_from(self.max_response_size).unwrap_or(usize::MAX) {
+        let max_wire_size = self.max_wire_size(protocol, self.max_response_size);
+        if length > usize::try_from(max_wire_size).unwrap_or(usize::MAX) {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
-                format!(
-                    "Response size exceeds limit: {} > {}",
-                    length, self.max_response_size
-                ),
+                format!("Response size exceeds limit: {length} > {max_wire_size}"),
             ));
         }
 
         // Read the payload.
         let mut buffer = vec![0; length];
         io.read_exact(&mut buffer).await?;
-        Ok(Ok(buffer))
+
+        match self.negotiated_compression(protocol) {
+            Some(compression) => compression
+                .decompress(buffer, self.max_response_size)
+                .map(Ok),
+            None => Ok(Ok(buffer)),
+        }
     }
 
     async fn write_request<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
-        req: Self::Request,
+        mut req: Self::Request,
     ) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send,
     {
+        if let Some(compression) = self.negotiated_compression(protocol) {
+            req = compression.compress(&req)?;
+        }
+
         // Write the length.
         {
             let mut buffer = unsigned_varint::encode::usize_buffer();
@@ -1053,7 +1113,7 @@ impl RequestResponseCodec for GenericCodec {
 
     async fn write_response<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
         res: Self::Response,
     ) -> io::Result<()>
@@ -1061,7 +1121,11 @@ impl RequestResponseCodec for GenericCodec {
         T: AsyncWrite + Unpin + Send,
     {
         // If `res` is an `Err`, we jump to closing the substream without writing anything on it.
-        if let Ok(res) = res {
+        if let Ok(mut res) = res {
+            if let Some(compression) = self.negotiated_compression(protocol) {
+                res = compression.compress(&res)?;
+            }
+
             // Write the length.
             {
                 let mut buffer = unsigned_varint::encode::usize_buffer();
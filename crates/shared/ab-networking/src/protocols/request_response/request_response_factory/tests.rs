@@ -1,3 +1,4 @@
+use crate::protocols::request_response::compression::{CompressionConfig, ProtocolCompression};
 use crate::protocols::request_response::request_response_factory::{
     Event, IfDisconnected, IncomingRequest, OutboundFailure, OutgoingResponse, ProtocolConfig,
     RequestFailure, RequestHandler, RequestResponseFactoryBehaviour,
@@ -41,7 +42,8 @@ async fn build_swarm(
         .into_iter()
         .map(|config| Box::new(MockRunner(config)) as Box<dyn RequestHandler>)
         .collect::<Vec<_>>();
-    let behaviour = RequestResponseFactoryBehaviour::new(configs, 100).unwrap();
+    let behaviour =
+        RequestResponseFactoryBehaviour::new(configs, 100, CompressionConfig::default()).unwrap();
 
     let mut swarm = SwarmBuilder::with_new_identity()
         .with_tokio()
@@ -89,6 +91,7 @@ async fn basic_request_response_works() {
             max_response_size: 1024 * 1024,
             request_timeout: Duration::from_secs(30),
             inbound_queue: Some(tx),
+            compression: None,
         };
 
         build_swarm(iter::once(protocol_config)).await
@@ -165,6 +168,7 @@ async fn max_response_size_exceeded() {
             max_response_size: 8, // <-- important for the test
             request_timeout: Duration::from_secs(30),
             inbound_queue: Some(tx),
+            compression: None,
         };
 
         build_swarm(iter::once(protocol_config)).await
@@ -251,6 +255,7 @@ async fn request_id_collision() {
                 max_response_size: 1024 * 1024,
                 request_timeout: Duration::from_secs(30),
                 inbound_queue: None,
+                compression: None,
             },
             ProtocolConfig {
                 name: protocol_name_2,
@@ -258,6 +263,7 @@ async fn request_id_collision() {
                 max_response_size: 1024 * 1024,
                 request_timeout: Duration::from_secs(30),
                 inbound_queue: None,
+                compression: None,
             },
         ];
 
@@ -275,6 +281,7 @@ async fn request_id_collision() {
                 max_response_size: 1024 * 1024,
                 request_timeout: Duration::from_secs(30),
                 inbound_queue: Some(tx_1),
+                compression: None,
             },
             ProtocolConfig {
                 name: protocol_name_2,
@@ -282,6 +289,7 @@ async fn request_id_collision() {
                 max_response_size: 1024 * 1024,
                 request_timeout: Duration::from_secs(30),
                 inbound_queue: Some(tx_2),
+                compression: None,
             },
         ];
 
@@ -371,3 +379,84 @@ async fn request_id_collision() {
     assert_eq!(receiver_1.await.unwrap().unwrap(), b"this is a response 1");
     assert_eq!(receiver_2.await.unwrap().unwrap(), b"this is a response 2");
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn compressed_request_response_works() {
+    let protocol_name = "/test/req-resp/1";
+    let request = b"this is a request".repeat(100);
+    let response = b"this is a response".repeat(10_000);
+
+    // Compression is used when both peers support it, otherwise the uncompressed protocol is used
+    for (compression_0, compression_1) in [(true, true), (true, false), (false, true)] {
+        let mut swarms = Vec::with_capacity(2);
+        for compression in [compression_0, compression_1] {
+            let (tx, mut rx) = mpsc::channel::<IncomingRequest>(64);
+
+            tokio::spawn({
+                let request = request.clone();
+                let response = response.clone();
+
+                async move {
+                    while let Some(rq) = rx.next().await {
+                        assert_eq!(rq.payload, request);
+                        let _: Result<_, _> = rq.pending_response.send(OutgoingResponse {
+                            result: Ok(response.clone()),
+                            sent_feedback: None,
+                        });
+                    }
+                }
+            });
+
+            let protocol_config = ProtocolConfig {
+                name: protocol_name,
+                max_request_size: 1024 * 1024,
+                max_response_size: 1024 * 1024,
+                request_timeout: Duration::from_secs(30),
+                inbound_queue: Some(tx),
+                compression: compression.then(ProtocolCompression::default),
+            };
+
+            swarms.push(build_swarm(iter::once(protocol_config)).await);
+        }
+
+        let mut swarm_0 = swarms.remove(0);
+        let mut swarm_1 = swarms.remove(0);
+
+        swarm_0.connect(&mut swarm_1).await;
+
+        let peer_id_0 = *swarm_0.local_peer_id();
+
+        // Running `swarm_0` in the background.
+        let swarm_0_task = tokio::spawn(async move {
+            loop {
+                if let SwarmEvent::Behaviour(Event::InboundRequest { result, .. }) =
+                    swarm_0.select_next_some().await
+                {
+                    result.unwrap();
+                }
+            }
+        });
+
+        let (sender, receiver) = oneshot::channel();
+        swarm_1.behaviour_mut().send_request(
+            &peer_id_0,
+            protocol_name,
+            request.clone(),
+            sender,
+            IfDisconnected::ImmediateError,
+            Vec::new(),
+        );
+        // Wait for request to finish
+        loop {
+            if let SwarmEvent::Behaviour(Event::RequestFinished { result, .. }) =
+                swarm_1.select_next_some().await
+            {
+                result.unwrap();
+                break;
+            }
+        }
+        assert_eq!(receiver.await.unwrap().unwrap(), response);
+
+        swarm_0_task.abort();
+    }
+}
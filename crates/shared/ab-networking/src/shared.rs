@@ -7,7 +7,9 @@ use crate::utils::multihash::Multihash;
 use crate::utils::rate_limiter::RateLimiter;
 use bytes::Bytes;
 use futures::channel::{mpsc, oneshot};
-use libp2p::gossipsub::{PublishError, Sha256Topic, SubscriptionError};
+use libp2p::gossipsub::{
+    MessageAcceptance, MessageId, PublishError, Sha256Topic, SubscriptionError,
+};
 use libp2p::kad::{PeerRecord, RecordKey};
 use libp2p::{Multiaddr, PeerId};
 use parking_lot::Mutex;
@@ -43,12 +45,23 @@ impl PeerDiscovered {
     }
 }
 
+/// Message received through topic subscription.
+#[derive(Debug, Clone)]
+pub struct GossipMessage {
+    /// Message ID, used for reporting validation result
+    pub message_id: MessageId,
+    /// Peer from which message was received (not necessarily the original author)
+    pub propagation_source: PeerId,
+    /// Message contents
+    pub data: Bytes,
+}
+
 #[derive(Debug)]
 pub(crate) struct CreatedSubscription {
     /// Subscription ID to be used for unsubscribing.
     pub(crate) subscription_id: usize,
     /// Receiver side of the channel with new messages.
-    pub(crate) receiver: mpsc::UnboundedReceiver<Bytes>,
+    pub(crate) receiver: mpsc::UnboundedReceiver<GossipMessage>,
 }
 
 #[derive(Debug)]
@@ -77,6 +90,11 @@ pub(crate) enum Command {
         message: Vec<u8>,
         result_sender: oneshot::Sender<Result<(), PublishError>>,
     },
+    ReportMessageValidationResult {
+        message_id: MessageId,
+        propagation_source: PeerId,
+        acceptance: MessageAcceptance,
+    },
     GetClosestPeers {
         key: Multihash,
         result_sender: mpsc::UnboundedSender<PeerId>,
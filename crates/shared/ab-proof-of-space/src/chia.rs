@@ -42,7 +42,7 @@ impl TableGenerator<ChiaTable> for ChiaTableGenerator {
 #[derive(Debug)]
 pub struct ChiaTable;
 
-impl ab_core_primitives::solutions::SolutionPotVerifier for ChiaTable {
+impl ab_solution_verification::SolutionPotVerifier for ChiaTable {
     fn is_proof_valid(seed: &PosSeed, s_bucket: SBucket, proof: &PosProof) -> bool {
         Tables::<K>::verify_only_raw(seed, u32::from(s_bucket), proof)
     }
@@ -54,7 +54,7 @@ impl Table for ChiaTable {
     type Generator = ChiaTableGenerator;
 
     fn is_proof_valid(seed: &PosSeed, s_bucket: SBucket, proof: &PosProof) -> bool {
-        <Self as ab_core_primitives::solutions::SolutionPotVerifier>::is_proof_valid(
+        <Self as ab_solution_verification::SolutionPotVerifier>::is_proof_valid(
             seed, s_bucket, proof,
         )
     }
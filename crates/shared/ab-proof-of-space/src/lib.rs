@@ -28,7 +28,7 @@ extern crate alloc;
 use ab_core_primitives::pieces::Record;
 use ab_core_primitives::pos::{PosProof, PosSeed};
 use ab_core_primitives::sectors::SBucket;
-use ab_core_primitives::solutions::SolutionPotVerifier;
+use ab_solution_verification::SolutionPotVerifier;
 #[cfg(feature = "alloc")]
 use alloc::boxed::Box;
 #[cfg(feature = "alloc")]
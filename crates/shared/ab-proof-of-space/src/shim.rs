@@ -56,7 +56,7 @@ impl TableGenerator<ShimTable> for ShimTableGenerator {
 #[derive(Debug)]
 pub struct ShimTable;
 
-impl ab_core_primitives::solutions::SolutionPotVerifier for ShimTable {
+impl ab_solution_verification::SolutionPotVerifier for ShimTable {
     fn is_proof_valid(seed: &PosSeed, s_bucket: SBucket, proof: &PosProof) -> bool {
         let Some(correct_proof) = find_proof(seed, u32::from(s_bucket)) else {
             return false;
@@ -72,7 +72,7 @@ impl Table for ShimTable {
     type Generator = ShimTableGenerator;
 
     fn is_proof_valid(seed: &PosSeed, s_bucket: SBucket, proof: &PosProof) -> bool {
-        <Self as ab_core_primitives::solutions::SolutionPotVerifier>::is_proof_valid(
+        <Self as ab_solution_verification::SolutionPotVerifier>::is_proof_valid(
             seed, s_bucket, proof,
         )
     }
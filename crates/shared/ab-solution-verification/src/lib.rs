@@ -0,0 +1,492 @@
+//! Solution verification.
+//!
+//! This crate contains the only implementation of solution verification (challenge derivation,
+//! solution range check, proof of space and piece verification) that is used during block
+//! verification, by RPC server for solution pre-validation and by farmer for checking its own
+//! solutions, such that all of them agree on what a valid solution is.
+
+#![no_std]
+#![warn(rust_2018_idioms, missing_debug_implementations, missing_docs)]
+#![feature(portable_simd)]
+#![expect(incomplete_features, reason = "generic_const_exprs")]
+// TODO: This feature is not actually used in this crate, but is added as a workaround for
+//  https://github.com/rust-lang/rust/issues/141492
+#![feature(generic_const_exprs)]
+
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::pieces::Record;
+use ab_core_primitives::pos::{PosProof, PosSeed};
+use ab_core_primitives::sectors::{SBucket, SectorId, SectorSlotChallenge};
+use ab_core_primitives::segments::{HistorySize, SuperSegmentRoot};
+use ab_core_primitives::shard::{NumShards, RealShardKind, ShardIndex, ShardKind};
+use ab_core_primitives::solutions::{
+    ShardCommitmentHash, ShardMembershipEntropy, Solution, SolutionDistance, SolutionRange,
+    SolutionShardCommitment,
+};
+use ab_merkle_tree::balanced::BalancedMerkleTree;
+use core::simd::Simd;
+
+/// Solution verification errors
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum SolutionVerifyError {
+    /// Invalid piece offset
+    #[error("Piece verification failed")]
+    InvalidPieceOffset {
+        /// Index of the piece that failed verification
+        piece_offset: u16,
+        /// How many pieces one sector is supposed to contain (max)
+        max_pieces_in_sector: u16,
+    },
+    /// History size is in the future
+    #[error("History size {solution} is in the future, current is {current}")]
+    FutureHistorySize {
+        /// Current history size
+        current: HistorySize,
+        /// History size solution was created for
+        solution: HistorySize,
+    },
+    /// Sector expired
+    #[error("Sector expired")]
+    SectorExpired {
+        /// Expiration history size
+        expiration_history_size: HistorySize,
+        /// Current history size
+        current_history_size: HistorySize,
+    },
+    /// Record does not belong to the segment
+    #[error("Record does not belong to the segment")]
+    RecordNotInSegment,
+    /// Segment doesn't belong to the super segment
+    #[error("Segment doesn't belong to the super segment")]
+    SegmentNotInSuperSegment,
+    /// Solution is outside the solution range
+    #[error("Solution distance {solution_distance} is outside of solution range {solution_range}")]
+    OutsideSolutionRange {
+        /// Solution range
+        solution_range: SolutionRange,
+        /// Solution distance
+        solution_distance: SolutionDistance,
+    },
+    /// Invalid proof of space
+    #[error("Invalid proof of space")]
+    InvalidProofOfSpace,
+    /// Invalid shard commitment
+    #[error("Invalid shard commitment")]
+    InvalidShardCommitment,
+    /// Invalid input shard
+    #[error("Invalid input shard {shard_index} ({shard_kind:?})")]
+    InvalidInputShard {
+        /// Input shard index
+        shard_index: ShardIndex,
+        /// Input shard kind
+        shard_kind: Option<ShardKind>,
+    },
+    /// Invalid solution shard
+    #[error(
+        "Invalid solution shard {solution_shard_index} (parent {solution_parent_shard_index:?}), \
+        expected shard {expected_shard_index} ({expected_shard_kind:?})"
+    )]
+    InvalidSolutionShard {
+        /// Solution shard index
+        solution_shard_index: ShardIndex,
+        /// Solution shard index
+        solution_parent_shard_index: Option<ShardIndex>,
+        /// Expected shard index
+        expected_shard_index: ShardIndex,
+        /// Expected shard kind
+        expected_shard_kind: RealShardKind,
+    },
+    /// Invalid chunk proof
+    #[error("Invalid chunk proof")]
+    InvalidChunkProof,
+    /// Invalid history size
+    #[error("Invalid history size")]
+    InvalidHistorySize,
+}
+
+/// Parameters for stateless solution verification.
+///
+/// These only include the information already contained in the block itself, meaning verification
+/// of different blocks can be done concurrently.
+#[derive(Debug, Clone)]
+pub struct SolutionVerifyStatelessParams {
+    /// Shard for which the solution is built
+    pub shard_index: ShardIndex,
+    /// Global challenge for which solution is built, see
+    /// [`PotOutput::derive_global_challenge()`](ab_core_primitives::pot::PotOutput::derive_global_challenge)
+    pub global_challenge: Blake3Hash,
+    /// Solution range
+    pub solution_range: SolutionRange,
+    /// Shard membership entropy
+    pub shard_membership_entropy: ShardMembershipEntropy,
+    /// The number of shards in the network
+    pub num_shards: NumShards,
+}
+
+/// Parameters for checking piece validity used in a solution
+#[derive(Debug, Clone)]
+pub struct SolutionVerifyPieceParams {
+    /// How many pieces one sector is supposed to contain (max)
+    pub max_pieces_in_sector: u16,
+    /// Super segment root of the segment to which piece belongs
+    pub super_segment_root: SuperSegmentRoot,
+    /// Number of segments in the super segment
+    pub num_segments: u32,
+    /// Number of latest archived segments that are considered "recent history"
+    pub recent_segments: HistorySize,
+    /// Fraction of pieces from the "recent history" (`recent_segments`) in each sector
+    pub recent_history_fraction: (HistorySize, HistorySize),
+    /// Minimum lifetime of a plotted sector, measured in archived segments
+    pub min_sector_lifetime: HistorySize,
+    /// Current size of the history
+    pub current_history_size: HistorySize,
+    /// Super segment root that contains a segment at `min_sector_lifetime` from sector creation
+    /// (if exists)
+    pub sector_expiration_check_super_segment_root: Option<SuperSegmentRoot>,
+}
+
+/// Parameters for full solution verification
+#[derive(Debug, Clone)]
+pub struct SolutionVerifyFullParams {
+    /// Parameters for stateless solution verification
+    pub stateless: SolutionVerifyStatelessParams,
+    /// Parameters for checking piece validity used in a solution
+    pub piece: SolutionVerifyPieceParams,
+}
+
+/// Proof-of-space verifier to be used in [`verify_full()`] and [`verify_stateless()`]
+pub trait SolutionPotVerifier {
+    /// Check whether proof created earlier is valid
+    fn is_proof_valid(seed: &PosSeed, s_bucket: SBucket, proof: &PosProof) -> bool;
+}
+
+/// Challenges of a solution derived from the global challenge
+#[derive(Debug, Copy, Clone)]
+pub struct SolutionChallenges {
+    /// Sector ID of the solution
+    pub sector_id: SectorId,
+    /// Global challenge
+    pub global_challenge: Blake3Hash,
+    /// Sector slot challenge
+    pub sector_slot_challenge: SectorSlotChallenge,
+    /// Audited s-bucket
+    pub s_bucket_audit_index: SBucket,
+}
+
+impl SolutionChallenges {
+    /// Derive challenges for a solution and global challenge
+    pub fn derive(solution: &Solution, global_challenge: &Blake3Hash) -> Self {
+        let sector_id = SectorId::new(
+            &solution.public_key_hash,
+            &solution.shard_commitment.root,
+            solution.sector_index,
+            solution.history_size,
+        );
+        let sector_slot_challenge = sector_id.derive_sector_slot_challenge(global_challenge);
+        let s_bucket_audit_index = sector_slot_challenge.s_bucket_audit_index();
+
+        Self {
+            sector_id,
+            global_challenge: *global_challenge,
+            sector_slot_challenge,
+            s_bucket_audit_index,
+        }
+    }
+
+    /// Solution distance of the solution.
+    ///
+    /// This is a cheap sanity check that doesn't verify proof of space or any of the proofs, it is
+    /// meant for pre-checks before [`verify_stateless()`] or [`verify_full()`] and must not be
+    /// used instead of them.
+    pub fn solution_distance(&self, solution: &Solution) -> SolutionDistance {
+        let masked_chunk =
+            (Simd::from(*solution.chunk) ^ Simd::from(*solution.proof_of_space.hash())).to_array();
+
+        SolutionDistance::calculate(
+            &self.global_challenge,
+            &masked_chunk,
+            &self.sector_slot_challenge,
+        )
+    }
+
+    /// Check that the solution distance is within the solution range, returns solution distance
+    /// on success
+    pub fn check_solution_range(
+        &self,
+        solution: &Solution,
+        solution_range: SolutionRange,
+    ) -> Result<SolutionDistance, SolutionVerifyError> {
+        let solution_distance = self.solution_distance(solution);
+
+        if !solution_distance.is_within(solution_range) {
+            return Err(SolutionVerifyError::OutsideSolutionRange {
+                solution_range,
+                solution_distance,
+            });
+        }
+
+        Ok(solution_distance)
+    }
+
+    /// Check that proof of space of the solution is valid
+    pub fn check_proof_of_space<PotVerifier>(
+        &self,
+        solution: &Solution,
+    ) -> Result<(), SolutionVerifyError>
+    where
+        PotVerifier: SolutionPotVerifier,
+    {
+        if !PotVerifier::is_proof_valid(
+            &self.sector_id.derive_evaluation_seed(solution.piece_offset),
+            self.s_bucket_audit_index,
+            &solution.proof_of_space,
+        ) {
+            return Err(SolutionVerifyError::InvalidProofOfSpace);
+        }
+
+        Ok(())
+    }
+}
+
+/// Shard index the solution belongs to
+pub fn solution_shard_index(
+    solution: &Solution,
+    shard_membership_entropy: &ShardMembershipEntropy,
+    num_shards: NumShards,
+) -> ShardIndex {
+    let (solution_shard_index, _shard_commitment_index) = num_shards
+        .derive_shard_index_and_shard_commitment_index(
+            &solution.public_key_hash,
+            &solution.shard_commitment.root,
+            shard_membership_entropy,
+            solution.history_size,
+        );
+
+    solution_shard_index
+}
+
+/// Check solution validity
+pub fn verify_full<PotVerifier>(
+    solution: &Solution,
+    params: &SolutionVerifyFullParams,
+) -> Result<(), SolutionVerifyError>
+where
+    PotVerifier: SolutionPotVerifier,
+{
+    let challenges = SolutionChallenges::derive(solution, &params.stateless.global_challenge);
+
+    verify_stateless_inner::<PotVerifier>(solution, &challenges, &params.stateless)?;
+
+    verify_piece_inner(solution, &challenges.sector_id, &params.piece)
+}
+
+/// Stateless solution verification.
+///
+/// Checks most things, except checking that the piece belongs to the global history.
+///
+/// For piece verification use [`verify_piece()`] or call [`verify_full()`] for more efficient
+/// verification of both at once.
+pub fn verify_stateless<PotVerifier>(
+    solution: &Solution,
+    params: &SolutionVerifyStatelessParams,
+) -> Result<(), SolutionVerifyError>
+where
+    PotVerifier: SolutionPotVerifier,
+{
+    let challenges = SolutionChallenges::derive(solution, &params.global_challenge);
+
+    verify_stateless_inner::<PotVerifier>(solution, &challenges, params)
+}
+
+/// Verify the piece details of the solution
+pub fn verify_piece(
+    solution: &Solution,
+    piece_check_params: &SolutionVerifyPieceParams,
+) -> Result<(), SolutionVerifyError> {
+    let sector_id = SectorId::new(
+        &solution.public_key_hash,
+        &solution.shard_commitment.root,
+        solution.sector_index,
+        solution.history_size,
+    );
+
+    verify_piece_inner(solution, &sector_id, piece_check_params)
+}
+
+fn verify_stateless_inner<PotVerifier>(
+    solution: &Solution,
+    challenges: &SolutionChallenges,
+    params: &SolutionVerifyStatelessParams,
+) -> Result<(), SolutionVerifyError>
+where
+    PotVerifier: SolutionPotVerifier,
+{
+    let SolutionVerifyStatelessParams {
+        shard_index,
+        global_challenge: _,
+        solution_range,
+        shard_membership_entropy,
+        num_shards,
+    } = params;
+
+    let shard_kind = shard_index
+        .shard_kind()
+        .and_then(ShardKind::to_real)
+        .ok_or(SolutionVerifyError::InvalidInputShard {
+            shard_index: *shard_index,
+            shard_kind: shard_index.shard_kind(),
+        })?;
+
+    let (solution_shard_index, shard_commitment_index) = num_shards
+        .derive_shard_index_and_shard_commitment_index(
+            &solution.public_key_hash,
+            &solution.shard_commitment.root,
+            shard_membership_entropy,
+            solution.history_size,
+        );
+
+    // Check that solution belongs to the shard
+    match shard_kind {
+        RealShardKind::BeaconChain => {}
+        RealShardKind::IntermediateShard => {
+            if solution_shard_index.parent_shard() != Some(*shard_index) {
+                return Err(SolutionVerifyError::InvalidSolutionShard {
+                    solution_shard_index,
+                    solution_parent_shard_index: solution_shard_index.parent_shard(),
+                    expected_shard_index: *shard_index,
+                    expected_shard_kind: RealShardKind::IntermediateShard,
+                });
+            }
+        }
+        RealShardKind::LeafShard => {
+            if solution_shard_index != *shard_index {
+                return Err(SolutionVerifyError::InvalidSolutionShard {
+                    solution_shard_index,
+                    solution_parent_shard_index: solution_shard_index.parent_shard(),
+                    expected_shard_index: *shard_index,
+                    expected_shard_kind: RealShardKind::LeafShard,
+                });
+            }
+        }
+    }
+    let solution_range = solution_range.to_shard_kind(shard_kind, *num_shards);
+
+    // TODO: This is a workaround for https://github.com/rust-lang/rust/issues/139866 that allows
+    //  the code to compile. Constant 1_048_576 is hardcoded here and below for compilation to
+    //  succeed.
+    const {
+        assert!(SolutionShardCommitment::NUM_LEAVES == 1_048_576);
+    }
+    if !BalancedMerkleTree::<1_048_576>::verify(
+        &solution.shard_commitment.root,
+        &ShardCommitmentHash::repr_from_array(solution.shard_commitment.proof),
+        shard_commitment_index as usize,
+        *solution.shard_commitment.leaf,
+    ) {
+        return Err(SolutionVerifyError::InvalidShardCommitment);
+    }
+
+    // Check that proof of space is valid
+    challenges.check_proof_of_space::<PotVerifier>(solution)?;
+
+    challenges.check_solution_range(solution, solution_range)?;
+
+    // TODO: This is a workaround for https://github.com/rust-lang/rust/issues/139866 that allows
+    //  the code to compile. Constant 65536 is hardcoded here and below for compilation to succeed.
+    const {
+        assert!(Record::NUM_S_BUCKETS == 65536);
+    }
+    // Check that chunk belongs to the record
+    if !BalancedMerkleTree::<65536>::verify(
+        &solution.record_root,
+        &solution.chunk_proof,
+        usize::from(challenges.s_bucket_audit_index),
+        *solution.chunk,
+    ) {
+        return Err(SolutionVerifyError::InvalidChunkProof);
+    }
+
+    Ok(())
+}
+
+fn verify_piece_inner(
+    solution: &Solution,
+    sector_id: &SectorId,
+    piece_check_params: &SolutionVerifyPieceParams,
+) -> Result<(), SolutionVerifyError> {
+    let SolutionVerifyPieceParams {
+        max_pieces_in_sector,
+        super_segment_root,
+        num_segments,
+        recent_segments,
+        recent_history_fraction,
+        min_sector_lifetime,
+        current_history_size,
+        sector_expiration_check_super_segment_root,
+    } = piece_check_params;
+
+    if &solution.history_size > current_history_size {
+        return Err(SolutionVerifyError::FutureHistorySize {
+            current: *current_history_size,
+            solution: solution.history_size,
+        });
+    }
+
+    if u16::from(solution.piece_offset) >= *max_pieces_in_sector {
+        return Err(SolutionVerifyError::InvalidPieceOffset {
+            piece_offset: u16::from(solution.piece_offset),
+            max_pieces_in_sector: *max_pieces_in_sector,
+        });
+    }
+
+    if let Some(sector_expiration_check_super_segment_root) =
+        sector_expiration_check_super_segment_root
+    {
+        let Some(expiration_history_size) = sector_id.derive_expiration_history_size(
+            solution.history_size,
+            sector_expiration_check_super_segment_root,
+            *min_sector_lifetime,
+        ) else {
+            return Err(SolutionVerifyError::InvalidHistorySize);
+        };
+
+        if expiration_history_size <= *current_history_size {
+            return Err(SolutionVerifyError::SectorExpired {
+                expiration_history_size,
+                current_history_size: *current_history_size,
+            });
+        }
+    }
+
+    let position = sector_id
+        .derive_piece_index(
+            solution.piece_offset,
+            solution.history_size,
+            *max_pieces_in_sector,
+            *recent_segments,
+            *recent_history_fraction,
+        )
+        .position();
+
+    // Check that record belongs to the segment
+    if !solution
+        .record_root
+        .is_valid(&solution.segment_root, &solution.record_proof, position)
+    {
+        return Err(SolutionVerifyError::RecordNotInSegment);
+    }
+
+    // Check that segment belongs to the super segment (global history)
+    if !solution.segment_root.is_valid(
+        solution.piece_shard_index,
+        solution.piece_local_segment_index,
+        solution.segment_position,
+        &solution.segment_proof,
+        *num_segments,
+        super_segment_root,
+    ) {
+        return Err(SolutionVerifyError::SegmentNotInSuperSegment);
+    }
+
+    Ok(())
+}
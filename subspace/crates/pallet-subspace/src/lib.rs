@@ -11,30 +11,33 @@ mod mock;
 mod tests;
 
 pub mod extensions;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod weights;
 
 use crate::extensions::weights::WeightInfo as ExtensionWeightInfo;
 use ab_core_primitives::block::BlockNumber;
-use ab_core_primitives::pot::{PotParametersChange, SlotNumber};
+use ab_core_primitives::pot::{PotOutput, PotParametersChange, SlotNumber};
 use ab_core_primitives::segments::{
     ArchivedHistorySegment, HistorySize, SegmentHeader, SegmentIndex,
 };
 use ab_core_primitives::solutions::SolutionRange;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
+use core::marker::PhantomData;
 use core::num::NonZeroU64;
 use frame_support::dispatch::DispatchResult;
 use frame_support::pallet_prelude::{EnsureOrigin, RuntimeDebug};
-use frame_support::traits::Get;
+use frame_support::traits::{Get, Randomness};
 use frame_system::pallet_prelude::*;
 use log::{debug, warn};
 pub use pallet::*;
 use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
-use sp_consensus_subspace::PotParameters;
 use sp_consensus_subspace::digests::{CompatibleDigestItem, PreDigest};
+use sp_consensus_subspace::{MAX_SEGMENT_ROOTS_PER_PAGE, PotParameters, SegmentRootsPage};
 use sp_runtime::generic::DigestItem;
-use sp_runtime::traits::{CheckedSub, Zero};
+use sp_runtime::traits::{CheckedSub, Hash, One, Saturating, Zero};
 use sp_runtime::transaction_validity::{
     InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
     TransactionValidityError, ValidTransaction,
@@ -83,7 +86,7 @@ pub mod pallet {
     use crate::weights::WeightInfo;
     use crate::{ConsensusConstants, ExtensionWeightInfo, RawOrigin};
     use ab_core_primitives::hashes::Blake3Hash;
-    use ab_core_primitives::pot::{PotCheckpoints, SlotNumber};
+    use ab_core_primitives::pot::{PotCheckpoints, PotOutput, SlotNumber};
     use ab_core_primitives::segments::{SegmentHeader, SegmentIndex};
     use ab_core_primitives::solutions::SolutionRange;
     use alloc::collections::btree_map::BTreeMap;
@@ -120,6 +123,11 @@ pub mod pallet {
         #[pallet::constant]
         type ConsensusConstants: Get<ConsensusConstants<BlockNumberFor<Self>>>;
 
+        /// Number of the latest blocks for which randomness is retained, see
+        /// [`Pallet::random_at()`]. Must be at least one.
+        #[pallet::constant]
+        type BlockRandomnessHistoryDepth: Get<u32>;
+
         /// Weight information for extrinsics in this pallet.
         type WeightInfo: WeightInfo;
 
@@ -290,6 +298,12 @@ pub mod pallet {
     pub(super) type PotEntropy<T: Config> =
         StorageValue<_, BTreeMap<BlockNumberFor<T>, PotEntropyValue>, ValueQuery>;
 
+    /// Proof of time output of the latest blocks used as a source of randomness, only
+    /// [`Config::BlockRandomnessHistoryDepth`] latest blocks are retained.
+    #[pallet::storage]
+    pub(super) type BlockRandomness<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumberFor<T>, PotOutput>;
+
     /// Allow block authoring by anyone or just root.
     #[pallet::storage]
     pub(super) type AllowAuthoringByAnyone<T> = StorageValue<_, bool, ValueQuery>;
@@ -526,6 +540,8 @@ impl<T: Config> Pallet<T> {
             }
         }
 
+        Self::initialize_block_randomness(block_number, pre_digest.pot_info.proof_of_time);
+
         let consensus_constants = T::ConsensusConstants::get();
 
         Self::initialize_solution_range(
@@ -545,6 +561,16 @@ impl<T: Config> Pallet<T> {
         );
     }
 
+    fn initialize_block_randomness(block_number: BlockNumberFor<T>, proof_of_time: PotOutput) {
+        BlockRandomness::<T>::insert(block_number, proof_of_time);
+
+        // Remove randomness of the block that is no longer within history depth
+        let history_depth = BlockNumberFor::<T>::from(T::BlockRandomnessHistoryDepth::get());
+        if let Some(expired_block_number) = block_number.checked_sub(&history_depth) {
+            BlockRandomness::<T>::remove(expired_block_number);
+        }
+    }
+
     fn initialize_solution_range(
         current_slot: SlotNumber,
         block_number: BlockNumberFor<T>,
@@ -843,12 +869,57 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    /// Segment roots of up to `limit` consecutive segments starting with `first_segment_index`,
+    /// `limit` is capped at [`MAX_SEGMENT_ROOTS_PER_PAGE`]
+    pub fn segment_roots(first_segment_index: SegmentIndex, limit: u32) -> SegmentRootsPage {
+        let limit = limit.min(MAX_SEGMENT_ROOTS_PER_PAGE);
+
+        // Segment roots are stored without gaps, so the first missing one ends the page
+        let segment_roots = (first_segment_index..)
+            .take(limit as usize)
+            .map_while(|segment_index| SegmentRoot::<T>::get(segment_index))
+            .collect();
+
+        SegmentRootsPage {
+            segment_roots,
+            total_count: SegmentRoot::<T>::count(),
+        }
+    }
+
     /// Size of the archived history of the blockchain in bytes
     pub fn archived_history_size() -> u64 {
         let archived_segments = SegmentRoot::<T>::count();
 
         u64::from(archived_segments) * ArchivedHistorySegment::SIZE as u64
     }
+
+    /// Randomness for `subject` derived from proof of time of the block at `block_number`.
+    ///
+    /// Block number is a part of the derivation, so the same subject results in different values
+    /// at different blocks. Returns `None` if the block is in the future or older than
+    /// [`Config::BlockRandomnessHistoryDepth`] latest blocks.
+    pub fn random_at(block_number: BlockNumberFor<T>, subject: &[u8]) -> Option<T::Hash> {
+        let proof_of_time = BlockRandomness::<T>::get(block_number)?;
+
+        Some(T::Hashing::hash_of(&(subject, block_number, proof_of_time)))
+    }
+}
+
+/// Randomness derived from proof of time of the parent block, see [`Pallet::random_at()`].
+///
+/// Proof of time of the current block is not used since block author can choose to skip a slot.
+pub struct ParentBlockRandomness<T>(PhantomData<T>);
+
+impl<T: Config> Randomness<T::Hash, BlockNumberFor<T>> for ParentBlockRandomness<T> {
+    fn random(subject: &[u8]) -> (T::Hash, BlockNumberFor<T>) {
+        let parent_block_number =
+            frame_system::Pallet::<T>::block_number().saturating_sub(One::one());
+
+        // Genesis block has no proof of time
+        let randomness = Pallet::<T>::random_at(parent_block_number, subject).unwrap_or_default();
+
+        (randomness, parent_block_number)
+    }
 }
 
 /// Methods for the `ValidateUnsigned` implementation:
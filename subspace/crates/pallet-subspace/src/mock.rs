@@ -1,23 +1,21 @@
 //! Test utilities
 
+use crate::testing::DigestBuilder;
 use crate::{self as pallet_subspace, AllowAuthoringBy, Config, ConsensusConstants};
 use ab_core_primitives::block::BlockNumber;
 use ab_core_primitives::ed25519::Ed25519PublicKey;
 use ab_core_primitives::hashes::Blake3Hash;
-use ab_core_primitives::pieces::PieceOffset;
-use ab_core_primitives::pot::SlotNumber;
-use ab_core_primitives::sectors::SectorIndex;
+use ab_core_primitives::pot::{PotOutput, SlotNumber};
 use ab_core_primitives::segments::{
-    ArchivedBlockProgress, HistorySize, LastArchivedBlock, SegmentHeader, SegmentIndex, SegmentRoot,
+    ArchivedBlockProgress, LastArchivedBlock, SegmentHeader, SegmentIndex, SegmentRoot,
 };
-use ab_core_primitives::solutions::{Solution, SolutionRange, SolutionShardCommitment};
-use frame_support::traits::{ConstU128, OnInitialize};
+use ab_core_primitives::solutions::SolutionRange;
+use frame_support::traits::{ConstU32, ConstU128, OnInitialize};
 use frame_support::{derive_impl, parameter_types};
 use schnorrkel::Keypair;
-use sp_consensus_subspace::digests::{CompatibleDigestItem, PreDigest, PreDigestPotInfo};
 use sp_io::TestExternalities;
 use sp_runtime::BuildStorage;
-use sp_runtime::testing::{Digest, DigestItem, TestXt};
+use sp_runtime::testing::TestXt;
 use std::marker::PhantomData;
 use std::num::NonZeroU32;
 use subspace_runtime_primitives::ConsensusEventSegmentSize;
@@ -79,11 +77,21 @@ impl Config for Test {
     type RuntimeEvent = RuntimeEvent;
     type SubspaceOrigin = pallet_subspace::EnsureSubspaceOrigin;
     type ConsensusConstants = MockConsensusConstants;
+    type BlockRandomnessHistoryDepth = ConstU32<3>;
     type WeightInfo = ();
     type ExtensionWeightInfo = crate::extensions::weights::SubstrateWeight<Test>;
 }
 
 pub fn go_to_block(keypair: &Keypair, block: u64, slot: SlotNumber) {
+    go_to_block_with_proof_of_time(keypair, block, slot, PotOutput::default());
+}
+
+pub fn go_to_block_with_proof_of_time(
+    keypair: &Keypair,
+    block: u64,
+    slot: SlotNumber,
+    proof_of_time: PotOutput,
+) {
     use frame_support::traits::OnFinalize;
 
     Subspace::on_finalize(System::block_number());
@@ -95,28 +103,10 @@ pub fn go_to_block(keypair: &Keypair, block: u64, slot: SlotNumber) {
         System::parent_hash()
     };
 
-    let chunk = Default::default();
-
-    let pre_digest = make_pre_digest(
-        slot,
-        Solution {
-            public_key_hash: Ed25519PublicKey::from(keypair.public.to_bytes()).hash(),
-            shard_commitment: SolutionShardCommitment {
-                root: Default::default(),
-                proof: [Default::default(); _],
-                leaf: Default::default(),
-            },
-            record_root: Default::default(),
-            record_proof: Default::default(),
-            chunk,
-            chunk_proof: Default::default(),
-            proof_of_space: Default::default(),
-            history_size: HistorySize::from(SegmentIndex::ZERO),
-            sector_index: SectorIndex::ZERO,
-            piece_offset: PieceOffset::default(),
-            padding: [0; _],
-        },
-    );
+    let pre_digest = DigestBuilder::new(slot)
+        .with_public_key_hash(Ed25519PublicKey::from(keypair.public.to_bytes()).hash())
+        .with_proof_of_time(proof_of_time, PotOutput::default())
+        .build();
 
     System::reset_events();
     System::initialize(&block, &parent_hash, &pre_digest);
@@ -133,18 +123,6 @@ pub fn progress_to_block(keypair: &Keypair, n: u64) {
     }
 }
 
-pub fn make_pre_digest(slot: SlotNumber, solution: Solution) -> Digest {
-    let log = DigestItem::subspace_pre_digest(&PreDigest {
-        slot,
-        solution,
-        pot_info: PreDigestPotInfo {
-            proof_of_time: Default::default(),
-            future_proof_of_time: Default::default(),
-        },
-    });
-    Digest { logs: vec![log] }
-}
-
 pub fn new_test_ext() -> TestExternalities {
     let mut storage = frame_system::GenesisConfig::<Test>::default()
         .build_storage()
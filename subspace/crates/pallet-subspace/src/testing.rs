@@ -0,0 +1,114 @@
+//! Utilities for tests that need to initialize blocks with this pallet.
+//!
+//! Digests are constructed the same way as by the client, such that the mock of this pallet and
+//! downstream runtimes do not need to duplicate construction of digest items in their tests.
+
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::pot::{PotOutput, PotParametersChange, SlotNumber};
+use ab_core_primitives::segments::{SegmentIndex, SegmentRoot};
+use ab_core_primitives::solutions::Solution;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use sp_consensus_subspace::digests::{CompatibleDigestItem, PreDigest, PreDigestPotInfo};
+use sp_runtime::Digest;
+use sp_runtime::generic::DigestItem;
+
+/// Builder of block digests.
+///
+/// The pre-digest is always present since it is required by `on_initialize` of this pallet, PoT
+/// parameters change and segment roots are only included when explicitly added.
+#[derive(Debug, Clone)]
+pub struct DigestBuilder {
+    pre_digest: PreDigest,
+    pot_parameters_change: Option<PotParametersChange>,
+    segment_roots: Vec<(SegmentIndex, SegmentRoot)>,
+}
+
+impl DigestBuilder {
+    /// Create a new instance for a block at `slot`.
+    ///
+    /// Solution defaults to [`Solution::genesis_solution()`] and proofs of time default to zeroes.
+    pub fn new(slot: SlotNumber) -> Self {
+        Self {
+            pre_digest: PreDigest {
+                slot,
+                solution: Solution::genesis_solution(),
+                pot_info: PreDigestPotInfo {
+                    proof_of_time: PotOutput::default(),
+                    future_proof_of_time: PotOutput::default(),
+                },
+            },
+            pot_parameters_change: None,
+            segment_roots: Vec::new(),
+        }
+    }
+
+    /// Replace the hash of the public key of the farmer that authored the block
+    pub fn with_public_key_hash(mut self, public_key_hash: Blake3Hash) -> Self {
+        self.pre_digest.solution.public_key_hash = public_key_hash;
+        self
+    }
+
+    /// Replace the solution
+    pub fn with_solution(mut self, solution: Solution) -> Self {
+        self.pre_digest.solution = solution;
+        self
+    }
+
+    /// Replace proofs of time for the slot of the block and the future slot
+    pub fn with_proof_of_time(
+        mut self,
+        proof_of_time: PotOutput,
+        future_proof_of_time: PotOutput,
+    ) -> Self {
+        self.pre_digest.pot_info = PreDigestPotInfo {
+            proof_of_time,
+            future_proof_of_time,
+        };
+        self
+    }
+
+    /// Include PoT parameters change
+    pub fn with_pot_parameters_change(
+        mut self,
+        pot_parameters_change: PotParametersChange,
+    ) -> Self {
+        self.pot_parameters_change = Some(pot_parameters_change);
+        self
+    }
+
+    /// Include a segment root, can be called multiple times to include multiple segment roots
+    pub fn with_segment_root(
+        mut self,
+        segment_index: SegmentIndex,
+        segment_root: SegmentRoot,
+    ) -> Self {
+        self.segment_roots.push((segment_index, segment_root));
+        self
+    }
+
+    /// Pre-digest that will be included in the digest
+    pub fn pre_digest(&self) -> &PreDigest {
+        &self.pre_digest
+    }
+
+    /// Build a digest, the pre-digest always comes first
+    pub fn build(&self) -> Digest {
+        let mut logs = vec![DigestItem::subspace_pre_digest(&self.pre_digest)];
+        logs.extend(
+            self.pot_parameters_change
+                .map(DigestItem::pot_parameters_change),
+        );
+        logs.extend(
+            self.segment_roots
+                .iter()
+                .map(|&(segment_index, segment_root)| {
+                    DigestItem::segment_root(segment_index, segment_root)
+                }),
+        );
+
+        Digest { logs }
+    }
+}
@@ -2,18 +2,24 @@
 
 use crate::mock::{
     INITIAL_SOLUTION_RANGE, RuntimeEvent, RuntimeOrigin, SLOT_PROBABILITY, Subspace, System, Test,
-    create_segment_header, go_to_block, new_test_ext, progress_to_block,
+    create_segment_header, go_to_block, go_to_block_with_proof_of_time, new_test_ext,
+    progress_to_block,
 };
+use crate::testing::DigestBuilder;
 use crate::{
-    AllowAuthoringByAnyone, Call, Config, PotSlotIterations, PotSlotIterationsValue, pallet,
+    AllowAuthoringByAnyone, Call, Config, ParentBlockRandomness, PotSlotIterations,
+    PotSlotIterationsValue, pallet,
 };
-use ab_core_primitives::pot::SlotNumber;
-use ab_core_primitives::segments::SegmentIndex;
+use ab_core_primitives::hashes::Blake3Hash;
+use ab_core_primitives::pot::{PotOutput, PotParametersChange, SlotNumber};
+use ab_core_primitives::segments::{SegmentIndex, SegmentRoot};
 use ab_core_primitives::solutions::SolutionRange;
+use frame_support::traits::Randomness;
 use frame_support::{assert_err, assert_ok};
 use frame_system::{EventRecord, Phase};
 use schnorrkel::Keypair;
 use sp_consensus_subspace::SolutionRanges;
+use sp_consensus_subspace::digests::CompatibleDigestItem;
 use sp_runtime::DispatchError;
 use sp_runtime::traits::BlockNumberProvider;
 use sp_runtime::transaction_validity::{
@@ -192,6 +198,52 @@ fn store_segment_header_works() {
     });
 }
 
+#[test]
+fn segment_roots_pages() {
+    new_test_ext().execute_with(|| {
+        let keypair = Keypair::generate();
+
+        progress_to_block(&keypair, 1);
+
+        let segment_headers = (0..5u8)
+            .map(|index| {
+                let mut segment_header =
+                    create_segment_header(SegmentIndex::from(u64::from(index)));
+                segment_header.segment_root = SegmentRoot::from([index; _]);
+                segment_header
+            })
+            .collect::<Vec<_>>();
+        Subspace::store_segment_headers(RuntimeOrigin::none(), segment_headers).unwrap();
+
+        let page = Subspace::segment_roots(SegmentIndex::ONE, 2);
+        assert_eq!(
+            page.segment_roots,
+            vec![SegmentRoot::from([1; _]), SegmentRoot::from([2; _])]
+        );
+        assert_eq!(page.total_count, 5);
+
+        // The last page is shorter than requested
+        let page = Subspace::segment_roots(SegmentIndex::from(3), 10);
+        assert_eq!(
+            page.segment_roots,
+            vec![SegmentRoot::from([3; _]), SegmentRoot::from([4; _])]
+        );
+        assert_eq!(page.total_count, 5);
+
+        // Past the last segment
+        let page = Subspace::segment_roots(SegmentIndex::from(5), 10);
+        assert!(page.segment_roots.is_empty());
+        assert_eq!(page.total_count, 5);
+
+        // Empty page
+        assert!(
+            Subspace::segment_roots(SegmentIndex::ZERO, 0)
+                .segment_roots
+                .is_empty()
+        );
+    });
+}
+
 #[test]
 fn store_segment_header_validate_unsigned_prevents_duplicates() {
     new_test_ext().execute_with(|| {
@@ -396,3 +448,87 @@ fn set_pot_slot_iterations_works() {
         );
     });
 }
+
+#[test]
+fn block_randomness_history() {
+    new_test_ext().execute_with(|| {
+        let keypair = Keypair::generate();
+
+        let history_depth = u64::from(<Test as Config>::BlockRandomnessHistoryDepth::get());
+        assert_eq!(history_depth, 3);
+
+        // The same proof of time for all blocks
+        for block_number in 1..=history_depth + 2 {
+            go_to_block_with_proof_of_time(
+                &keypair,
+                block_number,
+                SlotNumber::new(block_number),
+                PotOutput::from([1; _]),
+            );
+        }
+
+        // Blocks outside of history depth and future blocks
+        assert_eq!(Subspace::random_at(1, b"subject"), None);
+        assert_eq!(Subspace::random_at(2, b"subject"), None);
+        assert_eq!(Subspace::random_at(6, b"subject"), None);
+
+        // Randomness is different for different subjects and blocks even with the same proof of
+        // time
+        let randomness = Subspace::random_at(5, b"subject").unwrap();
+        assert_ne!(Subspace::random_at(5, b"other").unwrap(), randomness);
+        assert_ne!(Subspace::random_at(4, b"subject").unwrap(), randomness);
+        assert_ne!(Subspace::random_at(3, b"subject").unwrap(), randomness);
+
+        // The next block pushes the oldest block out of history depth
+        go_to_block_with_proof_of_time(&keypair, 6, SlotNumber::new(6), PotOutput::from([2; _]));
+        assert_eq!(Subspace::random_at(3, b"subject"), None);
+        assert_eq!(Subspace::random_at(5, b"subject").unwrap(), randomness);
+        assert!(Subspace::random_at(6, b"subject").is_some());
+
+        // Randomness of the parent block
+        assert_eq!(
+            ParentBlockRandomness::<Test>::random(b"subject"),
+            (randomness, 5)
+        );
+    });
+}
+
+#[test]
+fn digest_builder() {
+    let pot_parameters_change = PotParametersChange {
+        slot: SlotNumber::new(10),
+        slot_iterations: NonZeroU32::new(100_000).unwrap(),
+        entropy: Blake3Hash::new([1; _]),
+    };
+    let digest = DigestBuilder::new(SlotNumber::new(5))
+        .with_public_key_hash(Blake3Hash::new([2; _]))
+        .with_proof_of_time(PotOutput::from([3; _]), PotOutput::from([4; _]))
+        .with_pot_parameters_change(pot_parameters_change)
+        .with_segment_root(SegmentIndex::ZERO, SegmentRoot::from([5; _]))
+        .with_segment_root(SegmentIndex::ONE, SegmentRoot::from([6; _]))
+        .build();
+
+    assert_eq!(digest.logs.len(), 4);
+
+    let pre_digest = digest.logs[0].as_subspace_pre_digest().unwrap();
+    assert_eq!(pre_digest.slot, SlotNumber::new(5));
+    assert_eq!(pre_digest.solution.public_key_hash, Blake3Hash::new([2; _]));
+    assert_eq!(pre_digest.pot_info.proof_of_time, PotOutput::from([3; _]));
+    assert_eq!(
+        pre_digest.pot_info.future_proof_of_time,
+        PotOutput::from([4; _])
+    );
+
+    assert_eq!(
+        digest.logs[1].as_pot_parameters_change(),
+        Some(pot_parameters_change)
+    );
+    assert_eq!(
+        digest.logs[2].as_segment_root(),
+        Some((SegmentIndex::ZERO, SegmentRoot::from([5; _])))
+    );
+    assert_eq!(
+        digest.logs[3].as_segment_root(),
+        Some((SegmentIndex::ONE, SegmentRoot::from([6; _])))
+    );
+}
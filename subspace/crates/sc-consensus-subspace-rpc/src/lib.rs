@@ -3,19 +3,21 @@
 #![feature(try_blocks)]
 
 use ab_archiving::archiver::NewArchivedSegment;
+use ab_archiving::objects::GlobalObject;
 use ab_client_api::ChainSyncStatus;
-use ab_core_primitives::block::BlockRoot;
+use ab_core_primitives::block::{BlockNumber, BlockRoot};
 use ab_core_primitives::hashes::Blake3Hash;
 use ab_core_primitives::pieces::{Piece, PieceIndex};
 use ab_core_primitives::pot::SlotNumber;
 use ab_core_primitives::segments::{HistorySize, SegmentHeader, SegmentIndex};
-use ab_core_primitives::shard::NumShards;
+use ab_core_primitives::shard::{NumShards, ShardIndex};
 use ab_core_primitives::solutions::Solution;
 use ab_erasure_coding::ErasureCoding;
 use ab_farmer_components::FarmerProtocolInfo;
 use ab_farmer_rpc_primitives::{
     BlockSealInfo, BlockSealResponse, FarmerAppInfo, FarmerShardMembershipInfo,
-    MAX_SEGMENT_HEADERS_PER_REQUEST, SlotInfo, SolutionResponse,
+    MAX_SEGMENT_HEADERS_PER_REQUEST, NodeShardInfo, SHARD_MEMBERSHIP_EXPIRATION, ShardTopology,
+    SlotInfo, SolutionResponse,
 };
 use ab_networking::libp2p::Multiaddr;
 use futures::channel::mpsc;
@@ -23,11 +25,12 @@ use futures::{FutureExt, StreamExt, future};
 use jsonrpsee::core::async_trait;
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
-use jsonrpsee::{Extensions, PendingSubscriptionSink};
+use jsonrpsee::{ConnectionId, Extensions, PendingSubscriptionSink};
 use parking_lot::Mutex;
 use sc_client_api::{AuxStore, BlockBackend};
 use sc_consensus_subspace::archiver::{
-    ArchivedSegmentNotification, SegmentHeadersStore, recreate_genesis_segment,
+    ArchivedSegmentNotification, ObjectMappingNotification, SegmentHeadersStore,
+    recreate_genesis_segment,
 };
 use sc_consensus_subspace::notification::SubspaceNotificationStream;
 use sc_consensus_subspace::slot_worker::{BlockSealingNotification, NewSlotNotification};
@@ -36,16 +39,17 @@ use sc_rpc::utils::{BoundedVecDeque, PendingSubscription};
 use sc_rpc_api::{UnsafeRpcError, check_if_safe};
 use sc_utils::mpsc::TracingUnboundedSender;
 use schnellru::{ByLength, LruMap};
+use serde::{Deserialize, Serialize};
 use sp_api::{ApiError, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_consensus_subspace::{ChainConstants, SubspaceApi};
 use sp_runtime::traits::Block as BlockT;
-use std::collections::HashMap;
 use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Weak};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, warn};
 
 const SUBSPACE_ERROR: i32 = 9000;
@@ -61,6 +65,23 @@ pub enum Error {
     /// Call to an unsafe RPC was denied.
     #[error(transparent)]
     UnsafeRpcCalled(#[from] UnsafeRpcError),
+    /// Block seal was submitted for a block that is not being sealed
+    #[error("Block seal for unexpected pre-seal hash {pre_seal_hash}")]
+    UnexpectedBlockSeal {
+        /// Pre-seal hash from the submitted block seal
+        pre_seal_hash: Blake3Hash,
+    },
+    /// Block seal was created by a different plot identity than requested
+    #[error(
+        "Block seal public key hash mismatch: expected {expected_public_key_hash}, got \
+        {public_key_hash}"
+    )]
+    BlockSealPublicKeyHashMismatch {
+        /// Public key hash of the plot identity that should have created the seal
+        expected_public_key_hash: Blake3Hash,
+        /// Public key hash of the submitted block seal
+        public_key_hash: Blake3Hash,
+    },
 }
 
 impl From<Error> for ErrorObjectOwned {
@@ -68,10 +89,26 @@ impl From<Error> for ErrorObjectOwned {
         match error {
             Error::StringError(e) => ErrorObject::owned(SUBSPACE_ERROR + 1, e, None::<()>),
             Error::UnsafeRpcCalled(e) => e.into(),
+            Error::UnexpectedBlockSeal { .. } => {
+                ErrorObject::owned(SUBSPACE_ERROR + 2, error.to_string(), None::<()>)
+            }
+            Error::BlockSealPublicKeyHashMismatch { .. } => {
+                ErrorObject::owned(SUBSPACE_ERROR + 3, error.to_string(), None::<()>)
+            }
         }
     }
 }
 
+/// Object mappings of a block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMappingResponse {
+    /// Block number that object mappings are from
+    pub block_number: BlockNumber,
+    /// Object mappings for a block (and any previous block continuation)
+    pub objects: Vec<GlobalObject>,
+}
+
 /// Provides rpc methods for interacting with the farmer
 #[rpc(server)]
 pub trait FarmerRpcApi {
@@ -91,7 +128,10 @@ pub trait FarmerRpcApi {
     )]
     fn subscribe_slot_info(&self);
 
-    /// Sign block subscription
+    /// Sign block subscription.
+    ///
+    /// Only requests for public key hashes announced by the same connection with
+    /// `updateShardMembershipInfo` are sent, unless no connection announced the public key hash.
     #[subscription(
         name = "subscribeBlockSealing" => "block_sealing",
         unsubscribe = "unsubscribeBlockSealing",
@@ -130,11 +170,27 @@ pub trait FarmerRpcApi {
     #[method(name = "lastSegmentHeaders")]
     async fn last_segment_headers(&self, limit: u32) -> Result<Vec<Option<SegmentHeader>>, Error>;
 
-    #[method(name = "updateShardMembershipInfo")]
+    #[method(name = "updateShardMembershipInfo", with_extensions)]
     fn update_shard_membership_info(
         &self,
         info: Vec<FarmerShardMembershipInfo>,
     ) -> Result<(), Error>;
+
+    /// Object mappings subscription.
+    ///
+    /// Only mappings of blocks archived after subscription starting with `from_block` (if
+    /// specified) are sent. When unsafe RPC methods are allowed, archiving waits for each
+    /// notification to be acknowledged with `acknowledgeObjectMappings` (or for a timeout).
+    #[subscription(
+        name = "subscribeObjectMappings" => "object_mappings",
+        unsubscribe = "unsubscribeObjectMappings",
+        item = ObjectMappingResponse,
+        with_extensions,
+    )]
+    fn subscribe_object_mappings(&self, from_block: Option<BlockNumber>);
+
+    #[method(name = "acknowledgeObjectMappings", with_extensions)]
+    async fn acknowledge_object_mappings(&self, block_number: BlockNumber) -> Result<(), Error>;
 }
 
 #[derive(Default)]
@@ -143,12 +199,51 @@ struct ArchivedSegmentHeaderAcknowledgementSenders {
     senders: HashMap<u64, TracingUnboundedSender<()>>,
 }
 
+#[derive(Default)]
+struct ObjectMappingAcknowledgementSenders {
+    block_number: BlockNumber,
+    senders: HashMap<u64, TracingUnboundedSender<()>>,
+}
+
 #[derive(Default)]
 struct BlockSignatureSenders {
     current_pre_seal_hash: Blake3Hash,
+    public_key_hash: Blake3Hash,
     senders: Vec<async_oneshot::Sender<BlockSealResponse>>,
 }
 
+struct FarmerConnectionState {
+    last_update: Instant,
+    public_key_hashes: HashSet<Blake3Hash>,
+}
+
+/// Public key hashes of plot identities announced by each connection
+#[derive(Default)]
+struct FarmerConnections {
+    connections: HashMap<ConnectionId, FarmerConnectionState>,
+}
+
+impl FarmerConnections {
+    /// Whether block sealing request for `public_key_hash` should be sent to `connection_id`.
+    ///
+    /// Requests are only sent to connections that announced the public key hash, but if none did
+    /// (farmer didn't call `updateShardMembershipInfo` yet), they are sent to all connections.
+    fn is_sealing_target(&self, connection_id: ConnectionId, public_key_hash: &Blake3Hash) -> bool {
+        let is_announced_by = |state: &FarmerConnectionState| {
+            state.last_update.elapsed() < SHARD_MEMBERSHIP_EXPIRATION
+                && state.public_key_hashes.contains(public_key_hash)
+        };
+
+        if let Some(state) = self.connections.get(&connection_id)
+            && is_announced_by(state)
+        {
+            return true;
+        }
+
+        !self.connections.values().any(is_announced_by)
+    }
+}
+
 /// In-memory cache of last archived segment, such that when request comes back right after
 /// archived segment notification, RPC server is able to answer quickly.
 ///
@@ -185,6 +280,8 @@ where
     /// Archived segment notification stream
     pub archived_segment_notification_stream:
         SubspaceNotificationStream<ArchivedSegmentNotification>,
+    /// Object mapping notification stream
+    pub object_mapping_notification_stream: SubspaceNotificationStream<ObjectMappingNotification>,
     /// DSN bootstrap nodes
     pub dsn_bootstrap_nodes: Vec<Multiaddr>,
     /// Segment headers store
@@ -206,13 +303,16 @@ where
     new_slot_notification_stream: SubspaceNotificationStream<NewSlotNotification>,
     block_sealing_notification_stream: SubspaceNotificationStream<BlockSealingNotification>,
     archived_segment_notification_stream: SubspaceNotificationStream<ArchivedSegmentNotification>,
+    object_mapping_notification_stream: SubspaceNotificationStream<ObjectMappingNotification>,
     solution_response_senders: Arc<Mutex<LruMap<SlotNumber, mpsc::Sender<Solution>>>>,
     block_seal_senders: Arc<Mutex<BlockSignatureSenders>>,
+    farmer_connections: Arc<Mutex<FarmerConnections>>,
     dsn_bootstrap_nodes: Vec<Multiaddr>,
     segment_headers_store: SegmentHeadersStore<AS>,
     cached_archived_segment: Arc<Mutex<Option<CachedArchivedSegment>>>,
     archived_segment_acknowledgement_senders:
         Arc<Mutex<ArchivedSegmentHeaderAcknowledgementSenders>>,
+    object_mapping_acknowledgement_senders: Arc<Mutex<ObjectMappingAcknowledgementSenders>>,
     next_subscription_id: AtomicU64,
     chain_sync_status: CSS,
     genesis_root: BlockRoot,
@@ -261,14 +361,17 @@ where
             new_slot_notification_stream: config.new_slot_notification_stream,
             block_sealing_notification_stream: config.block_sealing_notification_stream,
             archived_segment_notification_stream: config.archived_segment_notification_stream,
+            object_mapping_notification_stream: config.object_mapping_notification_stream,
             solution_response_senders: Arc::new(Mutex::new(LruMap::new(ByLength::new(
                 solution_response_senders_capacity,
             )))),
             block_seal_senders: Arc::default(),
+            farmer_connections: Arc::default(),
             dsn_bootstrap_nodes: config.dsn_bootstrap_nodes,
             segment_headers_store: config.segment_headers_store,
             cached_archived_segment: Arc::default(),
             archived_segment_acknowledgement_senders: Arc::default(),
+            object_mapping_acknowledgement_senders: Arc::default(),
             next_subscription_id: AtomicU64::default(),
             chain_sync_status: config.chain_sync_status,
             genesis_root: genesis_hash,
@@ -313,6 +416,14 @@ where
                     .as_duration()
                     .mul_f64(chain_constants.block_authoring_delay().as_u64() as f64),
                 protocol_info,
+                shard_topology: ShardTopology {
+                    num_shards: NumShards::new(0, 0)
+                        .expect("Values are statically known to be valid; qed"),
+                    shards: vec![NodeShardInfo {
+                        shard_index: ShardIndex::BEACON_CHAIN,
+                        genesis_root: Some(self.genesis_root),
+                    }],
+                },
             }
         };
 
@@ -434,15 +545,27 @@ where
 
         let executor = self.subscription_executor.clone();
         let block_seal_senders = self.block_seal_senders.clone();
+        let farmer_connections = Arc::clone(&self.farmer_connections);
+        let connection_id = pending.connection_id();
 
-        let stream = self.block_sealing_notification_stream.subscribe().map(
-            move |block_sealing_notification| {
+        let stream = self
+            .block_sealing_notification_stream
+            .subscribe()
+            .filter_map(move |block_sealing_notification| {
                 let BlockSealingNotification {
                     pre_seal_hash,
                     public_key_hash,
                     signature_sender,
                 } = block_sealing_notification;
 
+                // Only farmer with the matching plot identity is supposed to seal the block
+                if !farmer_connections
+                    .lock()
+                    .is_sealing_target(connection_id, &public_key_hash)
+                {
+                    return future::ready(None);
+                }
+
                 let (response_sender, response_receiver) = async_oneshot::oneshot();
 
                 // Store signature sender so that we can retrieve it when solution comes from
@@ -452,6 +575,7 @@ where
 
                     if block_seal_senders.current_pre_seal_hash != pre_seal_hash {
                         block_seal_senders.current_pre_seal_hash = pre_seal_hash;
+                        block_seal_senders.public_key_hash = public_key_hash;
                         block_seal_senders.senders.clear();
                     }
 
@@ -474,17 +598,16 @@ where
                         futures_timer::Delay::new(BLOCK_SEALING_TIMEOUT),
                         Box::pin(forward_signature_fut),
                     )
-                        .map(|_| ())
-                        .boxed(),
+                    .map(|_| ())
+                    .boxed(),
                 );
 
                 // This will be sent to the farmer
-                BlockSealInfo {
+                future::ready(Some(BlockSealInfo {
                     pre_seal_hash,
                     public_key_hash,
-                }
-            },
-        );
+                }))
+            });
 
         self.subscription_executor.spawn(
             "block-signing-subscription",
@@ -504,13 +627,23 @@ where
 
         let block_seal_senders = self.block_seal_senders.clone();
 
-        // TODO: This doesn't track what client sent a solution, allowing some clients to send
-        //  multiple (https://github.com/paritytech/jsonrpsee/issues/452)
         let mut block_seal_senders = block_seal_senders.lock();
 
-        if block_seal_senders.current_pre_seal_hash == block_seal.pre_seal_hash
-            && let Some(mut sender) = block_seal_senders.senders.pop()
-        {
+        if block_seal_senders.current_pre_seal_hash != block_seal.pre_seal_hash {
+            return Err(Error::UnexpectedBlockSeal {
+                pre_seal_hash: block_seal.pre_seal_hash,
+            });
+        }
+
+        let public_key_hash = block_seal.seal.as_ref().public_key_hash();
+        if block_seal_senders.public_key_hash != public_key_hash {
+            return Err(Error::BlockSealPublicKeyHashMismatch {
+                expected_public_key_hash: block_seal_senders.public_key_hash,
+                public_key_hash,
+            });
+        }
+
+        if let Some(mut sender) = block_seal_senders.senders.pop() {
             let _ = sender.send(block_seal);
         }
 
@@ -754,8 +887,135 @@ where
 
     fn update_shard_membership_info(
         &self,
-        _info: Vec<FarmerShardMembershipInfo>,
+        ext: &Extensions,
+        info: Vec<FarmerShardMembershipInfo>,
+    ) -> Result<(), Error> {
+        let connection_id = ext
+            .get::<ConnectionId>()
+            .expect("`ConnectionId` is always present; qed");
+
+        let mut farmer_connections = self.farmer_connections.lock();
+
+        // TODO: This is a workaround for https://github.com/paritytech/jsonrpsee/issues/1617
+        //  and should be replaced with cleanup on disconnection once that issue is resolved
+        farmer_connections
+            .connections
+            .retain(|_connection_id, state| {
+                state.last_update.elapsed() < SHARD_MEMBERSHIP_EXPIRATION
+            });
+
+        farmer_connections.connections.insert(
+            *connection_id,
+            FarmerConnectionState {
+                last_update: Instant::now(),
+                public_key_hashes: info.into_iter().map(|info| info.public_key_hash).collect(),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn subscribe_object_mappings(
+        &self,
+        pending: PendingSubscriptionSink,
+        ext: &Extensions,
+        from_block: Option<BlockNumber>,
+    ) {
+        let object_mapping_acknowledgement_senders =
+            Arc::clone(&self.object_mapping_acknowledgement_senders);
+
+        let subscription_id = self.next_subscription_id.fetch_add(1, Ordering::Relaxed);
+        let allow_acknowledgements = check_if_safe(ext).is_ok();
+
+        let stream = self
+            .object_mapping_notification_stream
+            .subscribe()
+            .filter_map(move |object_mapping_notification| {
+                let ObjectMappingNotification {
+                    object_mapping,
+                    block_number,
+                    acknowledgement_sender,
+                } = object_mapping_notification;
+
+                if let Some(from_block) = from_block
+                    && block_number < from_block
+                {
+                    return future::ready(None);
+                }
+
+                // Store acknowledgment sender so that we can retrieve it when acknowledgement
+                // comes from the subscriber, but only if unsafe APIs are allowed
+                if allow_acknowledgements {
+                    let mut object_mapping_acknowledgement_senders =
+                        object_mapping_acknowledgement_senders.lock();
+
+                    if object_mapping_acknowledgement_senders.block_number != block_number {
+                        object_mapping_acknowledgement_senders.block_number = block_number;
+                        object_mapping_acknowledgement_senders.senders.clear();
+                    }
+
+                    object_mapping_acknowledgement_senders
+                        .senders
+                        .insert(subscription_id, acknowledgement_sender);
+                }
+
+                future::ready(Some(ObjectMappingResponse {
+                    block_number,
+                    objects: object_mapping,
+                }))
+            });
+
+        let object_mapping_acknowledgement_senders =
+            Arc::clone(&self.object_mapping_acknowledgement_senders);
+        let fut = async move {
+            PendingSubscription::from(pending)
+                .pipe_from_stream(stream, BoundedVecDeque::default())
+                .await;
+
+            object_mapping_acknowledgement_senders
+                .lock()
+                .senders
+                .remove(&subscription_id);
+        };
+
+        self.subscription_executor
+            .spawn("object-mappings-subscription", Some("rpc"), fut.boxed());
+    }
+
+    async fn acknowledge_object_mappings(
+        &self,
+        ext: &Extensions,
+        block_number: BlockNumber,
     ) -> Result<(), Error> {
+        check_if_safe(ext)?;
+
+        let maybe_sender = {
+            let mut object_mapping_acknowledgement_senders =
+                self.object_mapping_acknowledgement_senders.lock();
+
+            (object_mapping_acknowledgement_senders.block_number == block_number)
+                .then(|| {
+                    let last_key = *object_mapping_acknowledgement_senders
+                        .senders
+                        .keys()
+                        .next()?;
+
+                    object_mapping_acknowledgement_senders
+                        .senders
+                        .remove(&last_key)
+                })
+                .flatten()
+        };
+
+        if let Some(sender) = maybe_sender
+            && let Err(error) = sender.unbounded_send(())
+            && !error.is_closed()
+        {
+            warn!("Failed to acknowledge object mappings: {error}");
+        }
+
+        debug!(%block_number, "Acknowledged object mappings.");
+
         Ok(())
     }
 }
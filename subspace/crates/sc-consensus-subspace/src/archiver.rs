@@ -49,7 +49,7 @@ use rayon::prelude::*;
 use sc_client_api::{
     AuxStore, Backend as BackendT, BlockBackend, BlockchainEvents, Finalizer, LockImportRun,
 };
-use sc_utils::mpsc::{TracingUnboundedSender, tracing_unbounded};
+use sc_utils::mpsc::{TracingUnboundedReceiver, TracingUnboundedSender, tracing_unbounded};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_consensus_subspace::SubspaceJustification;
@@ -70,6 +70,8 @@ use tracing::{debug, info, trace, warn};
 const BLOCKS_TO_ARCHIVE_CONCURRENCY: usize = 6;
 /// Do not wait for acknowledgements beyond this time limit
 const ACKNOWLEDGEMENT_TIMEOUT: Duration = Duration::from_mins(2);
+/// Do not wait for object mapping acknowledgements beyond this time limit
+const OBJECT_MAPPING_ACKNOWLEDGEMENT_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// How deep (in segments) should block be in order to be finalized.
 ///
@@ -338,7 +340,11 @@ pub struct ObjectMappingNotification {
     pub object_mapping: Vec<GlobalObject>,
     /// The block that these mappings are from.
     pub block_number: BlockNumber,
-    // TODO: add an acknowledgement_sender for backpressure if needed
+    /// Sender that signified the fact of receiving object mappings by subscriber.
+    ///
+    /// Archiving of the next block waits (with a timeout) until all senders are dropped, which
+    /// provides backpressure for slow subscribers.
+    pub acknowledgement_sender: TracingUnboundedSender<()>,
 }
 
 /// Whether to create object mappings.
@@ -802,7 +808,9 @@ where
                 let block_outcome = archiver
                     .add_block(encoded_block, block_object_mappings)
                     .expect("Block is never empty and doesn't exceed u32; qed");
-                send_object_mapping_notification(
+                // Archiver initialization is synchronous, so there is no waiting for
+                // acknowledgements here
+                let _maybe_acknowledgement_receiver = send_object_mapping_notification(
                     &subspace_link.object_mapping_notification_sender,
                     block_outcome.global_objects,
                     block_number_to_archive,
@@ -1187,11 +1195,14 @@ where
     let block_outcome = archiver
         .add_block(encoded_block, block_object_mappings)
         .expect("Block is never empty and doesn't exceed u32; qed");
-    send_object_mapping_notification(
+    if let Some(acknowledgement_receiver) = send_object_mapping_notification(
         &object_mapping_notification_sender,
         block_outcome.global_objects,
         block_number_to_archive,
-    );
+    ) {
+        wait_for_object_mapping_acknowledgements(block_number_to_archive, acknowledgement_receiver)
+            .await;
+    }
     for archived_segment in block_outcome.archived_segments {
         let segment_header = archived_segment.segment_header;
 
@@ -1204,21 +1215,50 @@ where
     Ok((block_hash_to_archive, block_number_to_archive))
 }
 
+/// Send object mapping notification, returns a receiver that can be used to wait for
+/// acknowledgements (`None` if there was nothing to send)
 fn send_object_mapping_notification(
     object_mapping_notification_sender: &SubspaceNotificationSender<ObjectMappingNotification>,
     object_mapping: Vec<GlobalObject>,
     block_number: BlockNumber,
-) {
+) -> Option<TracingUnboundedReceiver<()>> {
     if object_mapping.is_empty() {
-        return;
+        return None;
     }
 
+    let (acknowledgement_sender, acknowledgement_receiver) =
+        tracing_unbounded::<()>("subspace_object_mapping_acknowledgement", 1000);
     let object_mapping_notification = ObjectMappingNotification {
         object_mapping,
         block_number,
+        acknowledgement_sender,
     };
 
     object_mapping_notification_sender.notify(move || object_mapping_notification);
+
+    Some(acknowledgement_receiver)
+}
+
+async fn wait_for_object_mapping_acknowledgements(
+    block_number: BlockNumber,
+    mut acknowledgement_receiver: TracingUnboundedReceiver<()>,
+) {
+    let wait_fut = async {
+        while acknowledgement_receiver.next().await.is_some() {
+            trace!(%block_number, "Object mapping notification acknowledged");
+        }
+    };
+
+    if tokio::time::timeout(OBJECT_MAPPING_ACKNOWLEDGEMENT_TIMEOUT, wait_fut)
+        .await
+        .is_err()
+    {
+        warn!(
+            %block_number,
+            "Object mapping notification was not acknowledged and reached timeout, continue \
+            regardless"
+        );
+    }
 }
 
 async fn send_archived_segment_notification(
@@ -212,8 +212,22 @@ pub struct PotParameters {
     pub next_change: Option<PotParametersChange>,
 }
 
+/// Max number of segment roots returned in a single page by [`SubspaceApi::segment_roots()`]
+pub const MAX_SEGMENT_ROOTS_PER_PAGE: u32 = 1000;
+
+/// Page of segment roots
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct SegmentRootsPage {
+    /// Segment roots of consecutive segments starting with the requested segment index, may be
+    /// shorter than requested if some of the segments are not stored yet
+    pub segment_roots: Vec<SegmentRoot>,
+    /// Total number of segment roots stored in the runtime
+    pub total_count: u32,
+}
+
 sp_api::decl_runtime_apis! {
     /// API necessary for block authorship with Subspace.
+    #[api_version(3)]
     pub trait SubspaceApi {
         /// Proof of time parameters
         fn pot_parameters() -> PotParameters;
@@ -230,6 +244,11 @@ sp_api::decl_runtime_apis! {
         /// Get the segment root of records for specified segment index
         fn segment_root(segment_index: SegmentIndex) -> Option<SegmentRoot>;
 
+        /// Get segment roots of up to `limit` consecutive segments starting with
+        /// `first_segment_index`, `limit` is capped at [`MAX_SEGMENT_ROOTS_PER_PAGE`]
+        #[api_version(3)]
+        fn segment_roots(first_segment_index: SegmentIndex, limit: u32) -> SegmentRootsPage;
+
         /// Returns `Vec<SegmentHeader>` if a given extrinsic has them.
         fn extract_segment_headers(ext: &Block::Extrinsic) -> Option<Vec<SegmentHeader >>;
 
@@ -242,6 +261,11 @@ sp_api::decl_runtime_apis! {
         /// Whether solution range adjustment is enabled.
         fn should_adjust_solution_range() -> bool;
 
+        /// Randomness for `subject` derived from proof of time of the block at `block_number`,
+        /// only available for a limited number of the latest blocks
+        #[api_version(2)]
+        fn random_at(block_number: BlockNumber, subject: Vec<u8>) -> Option<Block::Hash>;
+
         /// Get Subspace blockchain constants
         fn chain_constants() -> ChainConstants;
     }
@@ -41,7 +41,7 @@ use frame_system::pallet_prelude::RuntimeCallFor;
 pub use pallet_subspace::AllowAuthoringBy;
 use pallet_subspace::ConsensusConstants;
 use sp_api::impl_runtime_apis;
-use sp_consensus_subspace::{ChainConstants, PotParameters, SolutionRanges};
+use sp_consensus_subspace::{ChainConstants, PotParameters, SegmentRootsPage, SolutionRanges};
 use sp_core::OpaqueMetadata;
 use sp_runtime::traits::{AccountIdLookup, BlakeTwo256, Block as BlockT};
 use sp_runtime::transaction_validity::{TransactionSource, TransactionValidity};
@@ -69,7 +69,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     impl_name: Cow::Borrowed("subspace"),
     authoring_version: 0,
     // The spec version can be different on Taurus and Mainnet
-    spec_version: 2,
+    spec_version: 4,
     impl_version: 0,
     apis: RUNTIME_API_VERSIONS,
     transaction_version: 0,
@@ -118,6 +118,10 @@ const RECENT_HISTORY_FRACTION: (HistorySize, HistorySize) = (
 const MIN_SECTOR_LIFETIME: HistorySize =
     HistorySize::new(NonZeroU64::new(4).expect("Not zero; qed"));
 
+/// Number of the latest blocks for which randomness is retained, such that transactions consuming
+/// it can be included a few blocks later.
+const BLOCK_RANDOMNESS_HISTORY_DEPTH: u32 = 256;
+
 parameter_types! {
     pub const Version: RuntimeVersion = VERSION;
     pub const BlockHashCount: u64 = 250;
@@ -204,6 +208,7 @@ impl pallet_subspace::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     type SubspaceOrigin = pallet_subspace::EnsureSubspaceOrigin;
     type ConsensusConstants = RuntimeConsensusConstants;
+    type BlockRandomnessHistoryDepth = ConstU32<BLOCK_RANDOMNESS_HISTORY_DEPTH>;
     type WeightInfo = pallet_subspace::weights::SubstrateWeight<Runtime>;
     type ExtensionWeightInfo = pallet_subspace::extensions::weights::SubstrateWeight<Runtime>;
 }
@@ -505,6 +510,7 @@ impl_runtime_apis! {
         }
     }
 
+    #[api_version(3)]
     impl sp_consensus_subspace::SubspaceApi<Block> for Runtime {
         fn pot_parameters() -> PotParameters {
             Subspace::pot_parameters()
@@ -526,6 +532,10 @@ impl_runtime_apis! {
             Subspace::segment_root(segment_index)
         }
 
+        fn segment_roots(first_segment_index: SegmentIndex, limit: u32) -> SegmentRootsPage {
+            Subspace::segment_roots(first_segment_index, limit)
+        }
+
         fn extract_segment_headers(ext: &<Block as BlockT>::Extrinsic) -> Option<Vec<SegmentHeader >> {
             extract_segment_headers(ext)
         }
@@ -546,6 +556,10 @@ impl_runtime_apis! {
             Subspace::should_adjust_solution_range()
         }
 
+        fn random_at(block_number: BlockNumber, subject: Vec<u8>) -> Option<Hash> {
+            Subspace::random_at(block_number.as_u64(), &subject)
+        }
+
         fn chain_constants() -> ChainConstants {
             ChainConstants::V0 {
                 confirmation_depth_k: BlockNumber::new(pallet_runtime_configs::ConfirmationDepthK::<Runtime>::get()),
@@ -798,6 +798,7 @@ where
     let block_sealing_notification_stream = subspace_link.block_sealing_notification_stream();
     let block_importing_notification_stream = subspace_link.block_importing_notification_stream();
     let archived_segment_notification_stream = subspace_link.archived_segment_notification_stream();
+    let object_mapping_notification_stream = subspace_link.object_mapping_notification_stream();
 
     let pot_state = rclite::Arc::new(
         init_pot_state(client.clone(), pot_verifier.clone())
@@ -950,6 +951,7 @@ where
             let new_slot_notification_stream = new_slot_notification_stream.clone();
             let block_sealing_notification_stream = block_sealing_notification_stream.clone();
             let archived_segment_notification_stream = archived_segment_notification_stream.clone();
+            let object_mapping_notification_stream = object_mapping_notification_stream.clone();
 
             Box::new(move |subscription_executor| {
                 let deps = rpc::FullDeps {
@@ -959,6 +961,7 @@ where
                     block_sealing_notification_stream: block_sealing_notification_stream.clone(),
                     archived_segment_notification_stream: archived_segment_notification_stream
                         .clone(),
+                    object_mapping_notification_stream: object_mapping_notification_stream.clone(),
                     dsn_bootstrap_nodes: dsn_bootstrap_nodes.clone(),
                     segment_headers_store: segment_headers_store.clone(),
                     chain_sync_status: chain_sync_status.clone(),
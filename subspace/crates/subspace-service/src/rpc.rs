@@ -11,7 +11,9 @@ use ab_erasure_coding::ErasureCoding;
 use ab_networking::libp2p::Multiaddr;
 use jsonrpsee::RpcModule;
 use sc_client_api::{AuxStore, BlockBackend};
-use sc_consensus_subspace::archiver::{ArchivedSegmentNotification, SegmentHeadersStore};
+use sc_consensus_subspace::archiver::{
+    ArchivedSegmentNotification, ObjectMappingNotification, SegmentHeadersStore,
+};
 use sc_consensus_subspace::notification::SubspaceNotificationStream;
 use sc_consensus_subspace::slot_worker::{BlockSealingNotification, NewSlotNotification};
 use sc_consensus_subspace_rpc::{FarmerRpc, FarmerRpcApiServer, FarmerRpcConfig};
@@ -36,6 +38,8 @@ pub struct FullDeps<C, CSS, AS> {
     /// A stream with notifications about archived segment creation.
     pub archived_segment_notification_stream:
         SubspaceNotificationStream<ArchivedSegmentNotification>,
+    /// A stream with notifications about object mappings of archived blocks.
+    pub object_mapping_notification_stream: SubspaceNotificationStream<ObjectMappingNotification>,
     /// Bootstrap nodes for DSN.
     pub dsn_bootstrap_nodes: Vec<Multiaddr>,
     /// Segment header provider.
@@ -67,6 +71,7 @@ where
         new_slot_notification_stream,
         block_sealing_notification_stream,
         archived_segment_notification_stream,
+        object_mapping_notification_stream,
         dsn_bootstrap_nodes,
         segment_headers_store,
         chain_sync_status,
@@ -81,6 +86,7 @@ where
             new_slot_notification_stream,
             block_sealing_notification_stream,
             archived_segment_notification_stream,
+            object_mapping_notification_stream,
             dsn_bootstrap_nodes,
             segment_headers_store,
             chain_sync_status,